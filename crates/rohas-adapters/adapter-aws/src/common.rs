@@ -1,7 +1,10 @@
+use adapter_codec::WireCodec;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 use thiserror::Error;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 
 pub type Result<T> = std::result::Result<T, AdapterError>;
 
@@ -13,12 +16,18 @@ pub enum AdapterError {
     #[error("AWS EventBridge error: {0}")]
     AwsEventBridge(String),
 
+    #[error("AWS S3 error: {0}")]
+    AwsS3(String),
+
     #[error("Queue not found: {0}")]
     QueueNotFound(String),
 
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
 
+    #[error("Codec error: {0}")]
+    Codec(#[from] adapter_codec::CodecError),
+
     #[error("Invalid message format: {0}")]
     InvalidMessage(String),
 
@@ -60,6 +69,44 @@ pub trait MessageHandler: Send + Sync {
     async fn handle(&self, message: Message) -> Result<()>;
 }
 
+/// Most messages a single SQS receive call returns.
+pub const MAX_RECEIVE_BATCH: usize = 10;
+
+/// Bounds how many received messages a subscription holds at once. Each
+/// message keeps its slot until its handler has finished and it is deleted.
+#[derive(Debug, Clone)]
+pub struct Prefetch {
+    slots: Arc<Semaphore>,
+}
+
+impl Prefetch {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            slots: Arc::new(Semaphore::new(limit.max(1))),
+        }
+    }
+
+    /// Waits for a free slot, then takes any others that are free, up to
+    /// [`MAX_RECEIVE_BATCH`]. Slots not used by the receive are returned
+    /// when dropped.
+    pub async fn claim(&self) -> Vec<OwnedSemaphorePermit> {
+        let first = self
+            .slots
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("prefetch semaphore is never closed");
+        let mut permits = vec![first];
+        while permits.len() < MAX_RECEIVE_BATCH {
+            match self.slots.clone().try_acquire_owned() {
+                Ok(permit) => permits.push(permit),
+                Err(_) => break,
+            }
+        }
+        permits
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct AwsConfig {
     pub region: String,
@@ -69,6 +116,7 @@ pub struct AwsConfig {
     pub visibility_timeout_seconds: Option<i32>, // For SQS
     pub message_retention_seconds: Option<i32>, // For SQS
     pub receive_wait_time_seconds: Option<i32>, // For SQS (long polling)
+    pub codec: WireCodec, // For SQS; EventBridge details are always JSON
 }
 
 impl Default for AwsConfig {
@@ -81,6 +129,7 @@ impl Default for AwsConfig {
             visibility_timeout_seconds: Some(30),
             message_retention_seconds: Some(345600), // 4 days
             receive_wait_time_seconds: Some(20), // Long polling
+            codec: WireCodec::Json,
         }
     }
 }
@@ -1,10 +1,12 @@
-use crate::common::{AdapterError, Message, MessageHandler, Result};
+use crate::common::{AdapterError, Message, MessageHandler, Prefetch, Result};
+use adapter_codec::DECODE_ERROR;
 use aws_sdk_eventbridge::Client as EventBridgeClient;
 use aws_sdk_sqs::Client as SqsClient;
 use async_trait::async_trait;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, warn};
 
 #[derive(Debug, Clone)]
@@ -34,6 +36,7 @@ pub struct EventBridgeAdapter {
     published_topics: Arc<RwLock<HashMap<String, ()>>>,
     queue_urls: Arc<RwLock<HashMap<String, String>>>, // topic -> queue_url
     rule_names: Arc<RwLock<HashMap<String, String>>>, // topic -> rule_name
+    shutdown: CancellationToken,
 }
 
 impl EventBridgeAdapter {
@@ -64,16 +67,34 @@ impl EventBridgeAdapter {
             published_topics: Arc::new(RwLock::new(HashMap::new())),
             queue_urls: Arc::new(RwLock::new(HashMap::new())),
             rule_names: Arc::new(RwLock::new(HashMap::new())),
+            shutdown: CancellationToken::new(),
         })
     }
 
+    /// Stops the polling loops of every subscription. The rules and queues
+    /// are kept, so a new adapter resumes where this one stopped.
+    pub fn close(&self) {
+        self.shutdown.cancel();
+    }
+
     pub async fn publish(
         &self,
         topic: impl Into<String>,
         payload: serde_json::Value,
+    ) -> Result<()> {
+        self.publish_with_metadata(topic, payload, HashMap::new()).await
+    }
+
+    /// Publishes a message whose metadata travels in the event detail.
+    pub async fn publish_with_metadata(
+        &self,
+        topic: impl Into<String>,
+        payload: serde_json::Value,
+        metadata: HashMap<String, String>,
     ) -> Result<()> {
         let topic = topic.into();
-        let message = Message::new(topic.clone(), payload.clone());
+        let mut message = Message::new(topic.clone(), payload);
+        message.metadata = metadata;
 
         {
             let mut topics = self.published_topics.write().await;
@@ -482,6 +503,21 @@ impl EventBridgeAdapter {
     }
 
     pub async fn subscribe_fn<F, Fut>(&self, topic: impl Into<String>, handler: F) -> Result<()>
+    where
+        F: Fn(Message) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<()>> + Send + 'static,
+    {
+        self.subscribe_fn_with_prefetch(topic, handler, 1).await
+    }
+
+    /// Subscribes with up to `prefetch` received events in flight at once.
+    /// Each is handled on its own task and deleted once its handler returns.
+    pub async fn subscribe_fn_with_prefetch<F, Fut>(
+        &self,
+        topic: impl Into<String>,
+        handler: F,
+        prefetch: usize,
+    ) -> Result<()>
     where
         F: Fn(Message) -> Fut + Send + Sync + 'static,
         Fut: std::future::Future<Output = Result<()>> + Send + 'static,
@@ -594,6 +630,8 @@ impl EventBridgeAdapter {
         let queue_url_clone = queue_url.clone();
         let queue_arn_clone = queue_arn.clone();
         let rule_name_clone = rule_name.clone();
+        let shutdown = self.shutdown.clone();
+        let prefetch = Prefetch::new(prefetch);
 
         struct ClosureHandler<F, Fut>
         where
@@ -620,6 +658,14 @@ impl EventBridgeAdapter {
             info!("EventBridge subscription polling loop started for topic '{}' (queue: {})", topic_clone, queue_url);
             let mut poll_count = 0u64;
             loop {
+                let mut slots = tokio::select! {
+                    _ = shutdown.cancelled() => {
+                        info!("EventBridge subscription for topic '{}' closed", topic_clone);
+                        break;
+                    }
+                    slots = prefetch.claim() => slots,
+                };
+
                 poll_count += 1;
                 if poll_count % 10 == 0 {
                     info!("EventBridge polling loop still active for topic '{}' (poll #{}), queue: {}", topic_clone, poll_count, queue_url);
@@ -629,13 +675,19 @@ impl EventBridgeAdapter {
                 } else {
                     debug!("Polling SQS queue for EventBridge topic '{}' (poll #{})...", topic_clone, poll_count);
                 }
-                let receive_result = sqs_client
+                let receive = sqs_client
                     .receive_message()
                     .queue_url(&queue_url)
-                    .max_number_of_messages(10)
+                    .max_number_of_messages(slots.len() as i32)
                     .wait_time_seconds(20)
-                    .send()
-                    .await;
+                    .send();
+                let receive_result = tokio::select! {
+                    _ = shutdown.cancelled() => {
+                        info!("EventBridge subscription for topic '{}' closed", topic_clone);
+                        break;
+                    }
+                    result = receive => result,
+                };
 
                 match receive_result {
                     Ok(response) => {
@@ -643,152 +695,18 @@ impl EventBridgeAdapter {
                         if !messages.is_empty() {
                             info!("Received {} message(s) from EventBridge queue for topic '{}'", messages.len(), topic_clone);
                             for sqs_message in messages {
-                                if let Some(body) = sqs_message.body() {
-                                    info!("Raw SQS message body for topic '{}': {}", topic_clone, body);
-                                    
-                                    debug!("Attempting to parse EventBridge message for topic '{}'", topic_clone);
-                                    let message_result = {
-                                        debug!("Trying to parse as array of events...");
-                                        if let Ok(events_array) = serde_json::from_str::<Vec<serde_json::Value>>(body) {
-                                            debug!("Successfully parsed as array with {} event(s)", events_array.len());
-                                            if let Some(event) = events_array.first() {
-                                                debug!("First event structure: {:?}", event);
-                                                if let Some(detail_str) = event.get("detail").and_then(|d| d.as_str()) {
-                                                    debug!("Found 'detail' field as string (length: {}): {}", detail_str.len(), detail_str);
-                                                    match serde_json::from_str::<Message>(detail_str) {
-                                                        Ok(msg) => {
-                                                            debug!("Successfully parsed Message from detail string");
-                                                            Some(msg)
-                                                        }
-                                                        Err(e) => {
-                                                            debug!("Failed to parse Message from detail string: {}", e);
-                                                            None
-                                                        }
-                                                    }
-                                                } else if let Some(detail_obj) = event.get("detail") {
-                                                    debug!("Found 'detail' field as object: {:?}", detail_obj);
-                                                    match serde_json::from_value::<Message>(detail_obj.clone()) {
-                                                        Ok(msg) => {
-                                                            debug!("Successfully parsed Message from detail object");
-                                                            Some(msg)
-                                                        }
-                                                        Err(e) => {
-                                                            debug!("Failed to parse Message from detail object: {}", e);
-                                                            None
-                                                        }
-                                                    }
-                                                } else {
-                                                    debug!("No 'detail' field found in event object");
-                                                    None
-                                                }
-                                            } else {
-                                                debug!("Array is empty");
-                                                None
-                                            }
-                                        } else {
-                                            debug!("Not an array, trying as single event object...");
-                                            None
-                                        }
-                                    }.or_else(|| {
-                                        debug!("Trying to parse as single event object...");
-                                        if let Ok(event_obj) = serde_json::from_str::<serde_json::Value>(body) {
-                                            debug!("Successfully parsed as event object");
-                                            if let Some(detail_str) = event_obj.get("detail").and_then(|d| d.as_str()) {
-                                                debug!("Found 'detail' field as string (length: {}): {}", detail_str.len(), detail_str);
-                                                match serde_json::from_str::<Message>(detail_str) {
-                                                    Ok(msg) => {
-                                                        debug!("Successfully parsed Message from detail string");
-                                                        Some(msg)
-                                                    }
-                                                    Err(e) => {
-                                                        debug!("Failed to parse Message from detail string: {}", e);
-                                                        None
-                                                    }
-                                                }
-                                            } else if let Some(detail_obj) = event_obj.get("detail") {
-                                                debug!("Found 'detail' field as object: {:?}", detail_obj);
-                                                match serde_json::from_value::<Message>(detail_obj.clone()) {
-                                                    Ok(msg) => {
-                                                        debug!("Successfully parsed Message from detail object");
-                                                        Some(msg)
-                                                    }
-                                                    Err(e) => {
-                                                        debug!("Failed to parse Message from detail object: {}", e);
-                                                        None
-                                                    }
-                                                }
-                                            } else {
-                                                debug!("No 'detail' field found in event object");
-                                                None
-                                            }
-                                        } else {
-                                            debug!("Not a valid JSON object, trying direct Message parse...");
-                                            None
-                                        }
-                                    }).or_else(|| {
-                                        debug!("Trying to parse body directly as Message...");
-                                        match serde_json::from_str::<Message>(body) {
-                                            Ok(msg) => {
-                                                debug!("Successfully parsed body directly as Message");
-                                                Some(msg)
-                                            }
-                                            Err(e) => {
-                                                debug!("Failed to parse body directly as Message: {}", e);
-                                                None
-                                            }
-                                        }
-                                    });
-                                    
-                                    let message_result = message_result.ok_or_else(|| {
-                                        let last_error = serde_json::from_str::<Message>(body)
-                                            .map_err(|e| e)
-                                            .unwrap_err();
-                                        debug!("All parsing attempts failed. Last error: {}", last_error);
-                                        last_error
-                                    });
-
-                                    match message_result {
-                                        Ok(message) => {
-                                            info!("Successfully parsed EventBridge message for topic '{}'", topic_clone);
-                                            info!("Message topic: {}, payload: {:?}", message.topic, message.payload);
-                                            info!("Calling handler for EventBridge message...");
-                                            if let Err(e) = handler.handle(message).await {
-                                                error!("Handler error for EventBridge topic '{}': {}", topic_clone, e);
-                                            } else {
-                                                info!("Handler completed successfully for EventBridge topic '{}'", topic_clone);
-                                            }
-
-                                            if let Some(receipt_handle) = sqs_message.receipt_handle() {
-                                                if let Err(e) = sqs_client
-                                                    .delete_message()
-                                                    .queue_url(&queue_url)
-                                                    .receipt_handle(receipt_handle)
-                                                    .send()
-                                                    .await
-                                                {
-                                                    warn!(
-                                                        "Failed to delete message from EventBridge queue '{}': {}",
-                                                        queue_url, e
-                                                    );
-                                                }
-                                            }
-                                        }
-                                        Err(e) => {
-                                            error!(
-                                                "Failed to deserialize EventBridge message for topic '{}': {}. Body: {}",
-                                                topic_clone, e, body
-                                            );
-                                            if let Some(receipt_handle) = sqs_message.receipt_handle() {
-                                                let _ = sqs_client
-                                                    .delete_message()
-                                                    .queue_url(&queue_url)
-                                                    .receipt_handle(receipt_handle)
-                                                    .send()
-                                                    .await;
-                                            }
-                                        }
-                                    }
-                                }
+                                let Some(slot) = slots.pop() else {
+                                    break;
+                                };
+                                let client = sqs_client.clone();
+                                let queue_url = queue_url.clone();
+                                let topic = topic_clone.clone();
+                                let handler = handler.clone();
+                                let sqs_message = sqs_message.clone();
+                                tokio::spawn(async move {
+                                    handle_message(&client, &queue_url, &topic, handler.as_ref(), &sqs_message).await;
+                                    drop(slot);
+                                });
                             }
                         } else {
                             debug!("No messages received from EventBridge queue for topic '{}' (this is normal, continuing to poll...)", topic_clone);
@@ -842,3 +760,168 @@ impl EventBridgeAdapter {
     }
 }
 
+/// Unwraps the Rohas message from an EventBridge delivery, handles it and
+/// deletes it from the queue. A delivery that fails to parse is handed over
+/// raw, flagged with [`DECODE_ERROR`], and deleted too.
+async fn handle_message<H>(
+    client: &SqsClient,
+    queue_url: &str,
+    topic: &str,
+    handler: &H,
+    sqs_message: &aws_sdk_sqs::types::Message,
+) where
+    H: MessageHandler + ?Sized,
+{
+    let Some(body) = sqs_message.body() else {
+        return;
+    };
+    info!("Raw SQS message body for topic '{}': {}", topic, body);
+
+    debug!("Attempting to parse EventBridge message for topic '{}'", topic);
+    let message_result = {
+        debug!("Trying to parse as array of events...");
+        if let Ok(events_array) = serde_json::from_str::<Vec<serde_json::Value>>(body) {
+            debug!("Successfully parsed as array with {} event(s)", events_array.len());
+            if let Some(event) = events_array.first() {
+                debug!("First event structure: {:?}", event);
+                if let Some(detail_str) = event.get("detail").and_then(|d| d.as_str()) {
+                    debug!("Found 'detail' field as string (length: {}): {}", detail_str.len(), detail_str);
+                    match serde_json::from_str::<Message>(detail_str) {
+                        Ok(msg) => {
+                            debug!("Successfully parsed Message from detail string");
+                            Some(msg)
+                        }
+                        Err(e) => {
+                            debug!("Failed to parse Message from detail string: {}", e);
+                            None
+                        }
+                    }
+                } else if let Some(detail_obj) = event.get("detail") {
+                    debug!("Found 'detail' field as object: {:?}", detail_obj);
+                    match serde_json::from_value::<Message>(detail_obj.clone()) {
+                        Ok(msg) => {
+                            debug!("Successfully parsed Message from detail object");
+                            Some(msg)
+                        }
+                        Err(e) => {
+                            debug!("Failed to parse Message from detail object: {}", e);
+                            None
+                        }
+                    }
+                } else {
+                    debug!("No 'detail' field found in event object");
+                    None
+                }
+            } else {
+                debug!("Array is empty");
+                None
+            }
+        } else {
+            debug!("Not an array, trying as single event object...");
+            None
+        }
+    }.or_else(|| {
+        debug!("Trying to parse as single event object...");
+        if let Ok(event_obj) = serde_json::from_str::<serde_json::Value>(body) {
+            debug!("Successfully parsed as event object");
+            if let Some(detail_str) = event_obj.get("detail").and_then(|d| d.as_str()) {
+                debug!("Found 'detail' field as string (length: {}): {}", detail_str.len(), detail_str);
+                match serde_json::from_str::<Message>(detail_str) {
+                    Ok(msg) => {
+                        debug!("Successfully parsed Message from detail string");
+                        Some(msg)
+                    }
+                    Err(e) => {
+                        debug!("Failed to parse Message from detail string: {}", e);
+                        None
+                    }
+                }
+            } else if let Some(detail_obj) = event_obj.get("detail") {
+                debug!("Found 'detail' field as object: {:?}", detail_obj);
+                match serde_json::from_value::<Message>(detail_obj.clone()) {
+                    Ok(msg) => {
+                        debug!("Successfully parsed Message from detail object");
+                        Some(msg)
+                    }
+                    Err(e) => {
+                        debug!("Failed to parse Message from detail object: {}", e);
+                        None
+                    }
+                }
+            } else {
+                debug!("No 'detail' field found in event object");
+                None
+            }
+        } else {
+            debug!("Not a valid JSON object, trying direct Message parse...");
+            None
+        }
+    }).or_else(|| {
+        debug!("Trying to parse body directly as Message...");
+        match serde_json::from_str::<Message>(body) {
+            Ok(msg) => {
+                debug!("Successfully parsed body directly as Message");
+                Some(msg)
+            }
+            Err(e) => {
+                debug!("Failed to parse body directly as Message: {}", e);
+                None
+            }
+        }
+    });
+
+    let message_result = message_result.ok_or_else(|| {
+        let last_error = serde_json::from_str::<Message>(body)
+            .map_err(|e| e)
+            .unwrap_err();
+        debug!("All parsing attempts failed. Last error: {}", last_error);
+        last_error
+    });
+
+    match message_result {
+        Ok(message) => {
+            info!("Successfully parsed EventBridge message for topic '{}'", topic);
+            info!("Message topic: {}, payload: {:?}", message.topic, message.payload);
+            info!("Calling handler for EventBridge message...");
+            if let Err(e) = handler.handle(message).await {
+                error!("Handler error for EventBridge topic '{}': {}", topic, e);
+            } else {
+                info!("Handler completed successfully for EventBridge topic '{}'", topic);
+            }
+
+            if let Some(receipt_handle) = sqs_message.receipt_handle() {
+                if let Err(e) = client
+                    .delete_message()
+                    .queue_url(queue_url)
+                    .receipt_handle(receipt_handle)
+                    .send()
+                    .await
+                {
+                    warn!(
+                        "Failed to delete message from EventBridge queue '{}': {}",
+                        queue_url, e
+                    );
+                }
+            }
+        }
+        Err(e) => {
+            error!(
+                "Failed to deserialize EventBridge message for topic '{}': {}. Body: {}",
+                topic, e, body
+            );
+            let message = Message::new(topic, serde_json::Value::String(body.to_string()))
+                .with_metadata(DECODE_ERROR, e.to_string());
+            if let Err(e) = handler.handle(message).await {
+                error!("Handler error for undecodable EventBridge message on topic '{}': {}", topic, e);
+            }
+            if let Some(receipt_handle) = sqs_message.receipt_handle() {
+                let _ = client
+                    .delete_message()
+                    .queue_url(queue_url)
+                    .receipt_handle(receipt_handle)
+                    .send()
+                    .await;
+            }
+        }
+    }
+}
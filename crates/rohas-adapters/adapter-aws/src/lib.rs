@@ -1,12 +1,15 @@
 pub mod sqs;
 pub mod eventbridge;
+pub mod s3;
 pub mod common;
 
 pub use common::{AwsConfig, Message, Result};
 pub use sqs::SqsAdapter;
 pub use eventbridge::EventBridgeAdapter;
+pub use s3::{S3BlobStore, S3Config};
 
 use serde_json::Value;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -38,6 +41,7 @@ impl AwsAdapter {
                     visibility_timeout_seconds: config.visibility_timeout_seconds,
                     message_retention_seconds: config.message_retention_seconds,
                     receive_wait_time_seconds: config.receive_wait_time_seconds,
+                    codec: config.codec,
                 };
                 Ok(AwsAdapter::Sqs(Arc::new(
                     SqsAdapter::new(sqs_config).await?
@@ -73,6 +77,7 @@ impl AwsAdapter {
             visibility_timeout_seconds: config.visibility_timeout_seconds,
             message_retention_seconds: config.message_retention_seconds,
             receive_wait_time_seconds: config.receive_wait_time_seconds,
+            codec: config.codec,
         };
         tracing::info!("AwsAdapter::new_with_both: Creating SQS adapter...");
         let sqs_adapter = Arc::new(SqsAdapter::new(sqs_config).await.map_err(|e| {
@@ -124,16 +129,28 @@ impl AwsAdapter {
         topic: impl Into<String>,
         payload: Value,
         adapter_type: Option<&str>,
+    ) -> common::Result<()> {
+        self.publish_with_metadata(topic, payload, adapter_type, HashMap::new()).await
+    }
+
+    /// Publishes through the adapter `adapter_type` names, or the default
+    /// one, with metadata carried in the message envelope.
+    pub async fn publish_with_metadata(
+        &self,
+        topic: impl Into<String>,
+        payload: Value,
+        adapter_type: Option<&str>,
+        metadata: HashMap<String, String>,
     ) -> common::Result<()> {
         let topic_str = topic.into();
         match self {
             AwsAdapter::Sqs(adapter) => {
                 tracing::info!("AwsAdapter::publish_with_type: Using SQS adapter for topic: {}", topic_str);
-                adapter.publish(topic_str, payload).await
+                adapter.publish_with_metadata(topic_str, payload, metadata).await
             }
             AwsAdapter::EventBridge(adapter) => {
                 tracing::info!("AwsAdapter::publish_with_type: Using EventBridge adapter for topic: {}", topic_str);
-                adapter.publish(topic_str, payload).await
+                adapter.publish_with_metadata(topic_str, payload, metadata).await
             }
             AwsAdapter::Both { sqs, eventbridge, default_type } => {
                 let use_type = adapter_type
@@ -153,11 +170,11 @@ impl AwsAdapter {
                 match use_type.as_str() {
                     "sqs" => {
                         tracing::info!("AwsAdapter::publish_with_type: Routing to SQS for topic: {}", topic_str);
-                        sqs.publish(topic_str, payload).await
+                        sqs.publish_with_metadata(topic_str, payload, metadata).await
                     }
                     "eventbridge" => {
                         tracing::info!("AwsAdapter::publish_with_type: Routing to EventBridge for topic: {}", topic_str);
-                        eventbridge.publish(topic_str, payload).await
+                        eventbridge.publish_with_metadata(topic_str, payload, metadata).await
                     }
                     _ => {
                         tracing::warn!(
@@ -166,8 +183,8 @@ impl AwsAdapter {
                             topic_str
                         );
                         match default_type {
-                            AwsAdapterType::Sqs => sqs.publish(topic_str, payload).await,
-                            AwsAdapterType::EventBridge => eventbridge.publish(topic_str, payload).await,
+                            AwsAdapterType::Sqs => sqs.publish_with_metadata(topic_str, payload, metadata).await,
+                            AwsAdapterType::EventBridge => eventbridge.publish_with_metadata(topic_str, payload, metadata).await,
                         }
                     }
                 }
@@ -189,14 +206,29 @@ impl AwsAdapter {
         handler: F,
         adapter_type: Option<&str>,
     ) -> common::Result<()>
+    where
+        F: Fn(common::Message) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = common::Result<()>> + Send + 'static,
+    {
+        self.subscribe_with_prefetch(topic, handler, adapter_type, 1).await
+    }
+
+    /// Subscribes with up to `prefetch` received messages in flight at once
+    pub async fn subscribe_with_prefetch<F, Fut>(
+        &self,
+        topic: impl Into<String>,
+        handler: F,
+        adapter_type: Option<&str>,
+        prefetch: usize,
+    ) -> common::Result<()>
     where
         F: Fn(common::Message) -> Fut + Send + Sync + 'static,
         Fut: std::future::Future<Output = common::Result<()>> + Send + 'static,
     {
         let topic_str = topic.into();
         match self {
-            AwsAdapter::Sqs(adapter) => adapter.subscribe_fn(topic_str, handler).await,
-            AwsAdapter::EventBridge(adapter) => adapter.subscribe_fn(topic_str, handler).await,
+            AwsAdapter::Sqs(adapter) => adapter.subscribe_fn_with_prefetch(topic_str, handler, prefetch).await,
+            AwsAdapter::EventBridge(adapter) => adapter.subscribe_fn_with_prefetch(topic_str, handler, prefetch).await,
             AwsAdapter::Both { sqs, eventbridge, default_type } => {
                 let use_type = adapter_type
                     .map(|s| s.to_lowercase())
@@ -215,11 +247,11 @@ impl AwsAdapter {
                 match use_type.as_str() {
                     "sqs" => {
                         tracing::info!("AwsAdapter::subscribe_with_type: Using SQS for subscription - topic: {}", topic_str);
-                        sqs.subscribe_fn(topic_str, handler).await
+                        sqs.subscribe_fn_with_prefetch(topic_str, handler, prefetch).await
                     }
                     "eventbridge" => {
                         tracing::info!("AwsAdapter::subscribe_with_type: Using EventBridge for subscription - topic: {}", topic_str);
-                        eventbridge.subscribe_fn(topic_str, handler).await
+                        eventbridge.subscribe_fn_with_prefetch(topic_str, handler, prefetch).await
                     }
                     _ => {
                         tracing::warn!(
@@ -228,8 +260,8 @@ impl AwsAdapter {
                             topic_str
                         );
                         match default_type {
-                            AwsAdapterType::Sqs => sqs.subscribe_fn(topic_str, handler).await,
-                            AwsAdapterType::EventBridge => eventbridge.subscribe_fn(topic_str, handler).await,
+                            AwsAdapterType::Sqs => sqs.subscribe_fn_with_prefetch(topic_str, handler, prefetch).await,
+                            AwsAdapterType::EventBridge => eventbridge.subscribe_fn_with_prefetch(topic_str, handler, prefetch).await,
                         }
                     }
                 }
@@ -251,5 +283,26 @@ impl AwsAdapter {
             }
         }
     }
+
+    /// Stops polling for every subscription.
+    pub fn close(&self) {
+        match self {
+            AwsAdapter::Sqs(adapter) => adapter.close(),
+            AwsAdapter::EventBridge(adapter) => adapter.close(),
+            AwsAdapter::Both { sqs, eventbridge, .. } => {
+                sqs.close();
+                eventbridge.close();
+            }
+        }
+    }
+
+    /// Approximate backlog of the topic's SQS queue. EventBridge has no
+    /// backlog to report.
+    pub async fn queue_depth(&self, topic: &str) -> common::Result<Option<u64>> {
+        match self {
+            AwsAdapter::Sqs(sqs) | AwsAdapter::Both { sqs, .. } => sqs.queue_depth(topic).await,
+            AwsAdapter::EventBridge(_) => Ok(None),
+        }
+    }
 }
 
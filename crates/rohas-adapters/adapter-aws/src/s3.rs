@@ -0,0 +1,88 @@
+use crate::common::{AdapterError, Result};
+use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_s3::Client as S3Client;
+use tracing::debug;
+
+#[derive(Debug, Clone)]
+pub struct S3Config {
+    pub region: String,
+    pub bucket: String,
+    pub prefix: String,
+}
+
+/// Stores opaque blobs under `<prefix><key>` in an S3 bucket.
+pub struct S3BlobStore {
+    client: S3Client,
+    config: S3Config,
+}
+
+impl S3BlobStore {
+    pub async fn new(config: S3Config) -> Result<Self> {
+        let aws_config = aws_config::defaults(aws_config::BehaviorVersion::latest())
+            .region(aws_sdk_s3::config::Region::new(config.region.clone()))
+            .load()
+            .await;
+
+        Ok(Self {
+            client: S3Client::new(&aws_config),
+            config,
+        })
+    }
+
+    pub fn bucket(&self) -> &str {
+        &self.config.bucket
+    }
+
+    fn object_key(&self, key: &str) -> String {
+        format!("{}{}", self.config.prefix, key)
+    }
+
+    pub async fn put(&self, key: &str, body: Vec<u8>) -> Result<()> {
+        let object_key = self.object_key(key);
+        debug!("Uploading s3://{}/{}", self.config.bucket, object_key);
+        self.client
+            .put_object()
+            .bucket(&self.config.bucket)
+            .key(&object_key)
+            .body(ByteStream::from(body))
+            .send()
+            .await
+            .map_err(|e| {
+                AdapterError::AwsS3(format!("Failed to upload s3://{}/{}: {}", self.config.bucket, object_key, e))
+            })?;
+        Ok(())
+    }
+
+    pub async fn get(&self, key: &str) -> Result<Vec<u8>> {
+        let object_key = self.object_key(key);
+        let response = self
+            .client
+            .get_object()
+            .bucket(&self.config.bucket)
+            .key(&object_key)
+            .send()
+            .await
+            .map_err(|e| {
+                AdapterError::AwsS3(format!("Failed to fetch s3://{}/{}: {}", self.config.bucket, object_key, e))
+            })?;
+
+        let body = response.body.collect().await.map_err(|e| {
+            AdapterError::AwsS3(format!("Failed to read s3://{}/{}: {}", self.config.bucket, object_key, e))
+        })?;
+        Ok(body.into_bytes().to_vec())
+    }
+
+    pub async fn delete(&self, key: &str) -> Result<()> {
+        let object_key = self.object_key(key);
+        self.client
+            .delete_object()
+            .bucket(&self.config.bucket)
+            .key(&object_key)
+            .send()
+            .await
+            .map_err(|e| {
+                AdapterError::AwsS3(format!("Failed to delete s3://{}/{}: {}", self.config.bucket, object_key, e))
+            })?;
+        Ok(())
+    }
+}
@@ -1,4 +1,5 @@
-use crate::common::{AdapterError, Message, MessageHandler, Result};
+use crate::common::{AdapterError, Message, MessageHandler, Prefetch, Result};
+use adapter_codec::{WireCodec, CONTENT_TYPE, DECODE_ERROR};
 use aws_sdk_sqs::{
     types::{MessageAttributeValue, QueueAttributeName},
     Client as SqsClient,
@@ -7,6 +8,7 @@ use async_trait::async_trait;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, warn};
 
 #[derive(Debug, Clone)]
@@ -16,6 +18,7 @@ pub struct SqsConfig {
     pub visibility_timeout_seconds: Option<i32>,
     pub message_retention_seconds: Option<i32>,
     pub receive_wait_time_seconds: Option<i32>, // Long polling wait time
+    pub codec: WireCodec,
 }
 
 impl Default for SqsConfig {
@@ -26,6 +29,7 @@ impl Default for SqsConfig {
             visibility_timeout_seconds: Some(30),
             message_retention_seconds: Some(345600), // 4 days
             receive_wait_time_seconds: Some(20),      // Long polling
+            codec: WireCodec::Json,
         }
     }
 }
@@ -34,6 +38,7 @@ pub struct SqsAdapter {
     client: SqsClient,
     config: SqsConfig,
     queue_urls: Arc<RwLock<HashMap<String, String>>>, // topic -> queue_url
+    shutdown: CancellationToken,
 }
 
 impl SqsAdapter {
@@ -54,9 +59,16 @@ impl SqsAdapter {
             client,
             config,
             queue_urls: Arc::new(RwLock::new(HashMap::new())),
+            shutdown: CancellationToken::new(),
         })
     }
 
+    /// Stops the polling loops of every subscription. Messages already
+    /// received finish handling; the rest stay in their queues.
+    pub fn close(&self) {
+        self.shutdown.cancel();
+    }
+
     async fn get_or_create_queue(&self, topic: &str) -> Result<String> {
         {
             let queue_urls = self.queue_urls.read().await;
@@ -155,16 +167,27 @@ impl SqsAdapter {
         &self,
         topic: impl Into<String>,
         payload: serde_json::Value,
+    ) -> Result<()> {
+        self.publish_with_metadata(topic, payload, HashMap::new()).await
+    }
+
+    /// Publishes a message whose metadata travels in the message body.
+    pub async fn publish_with_metadata(
+        &self,
+        topic: impl Into<String>,
+        payload: serde_json::Value,
+        metadata: HashMap<String, String>,
     ) -> Result<()> {
         let topic = topic.into();
         tracing::info!("SqsAdapter::publish: Starting publish for topic: {}", topic);
         
-        let message = Message::new(topic.clone(), payload);
+        let mut message = Message::new(topic.clone(), payload);
+        message.metadata = metadata;
 
-        let message_body = serde_json::to_string(&message)
+        let message_body = self.config.codec.encode_text(&message)
             .map_err(|e| {
                 tracing::error!("SqsAdapter::publish: Serialization error for topic {}: {}", topic, e);
-                AdapterError::Serialization(e)
+                AdapterError::Codec(e)
             })?;
 
         tracing::debug!("SqsAdapter::publish: Message serialized, getting/creating queue for topic: {}", topic);
@@ -193,6 +216,14 @@ impl SqsAdapter {
                 .build()
                 .map_err(|e| AdapterError::AwsSqs(format!("Failed to build attribute: {}", e)))?,
         );
+        attributes.insert(
+            CONTENT_TYPE.to_string(),
+            MessageAttributeValue::builder()
+                .data_type("String")
+                .string_value(self.config.codec.content_type())
+                .build()
+                .map_err(|e| AdapterError::AwsSqs(format!("Failed to build attribute: {}", e)))?,
+        );
 
         let send_result = self
             .client
@@ -225,22 +256,46 @@ impl SqsAdapter {
     }
 
     pub async fn subscribe<H>(&self, topic: impl Into<String>, handler: Arc<H>) -> Result<()>
+    where
+        H: MessageHandler + 'static,
+    {
+        self.subscribe_with_prefetch(topic, handler, 1).await
+    }
+
+    /// Subscribes with up to `prefetch` received messages in flight at once.
+    /// Each is handled on its own task and deleted once its handler returns.
+    pub async fn subscribe_with_prefetch<H>(
+        &self,
+        topic: impl Into<String>,
+        handler: Arc<H>,
+        prefetch: usize,
+    ) -> Result<()>
     where
         H: MessageHandler + 'static,
     {
         let topic = topic.into();
         let queue_url = self.get_or_create_queue(&topic).await?;
 
-        info!("Subscribing to topic: {} (queue: {})", topic, queue_url);
+        info!("Subscribing to topic: {} (queue: {}, prefetch: {})", topic, queue_url, prefetch);
 
         let client = self.client.clone();
         let handler = handler.clone();
         let topic_clone = topic.clone();
+        let shutdown = self.shutdown.clone();
+        let prefetch = Prefetch::new(prefetch);
 
         tokio::spawn(async move {
             info!("SQS subscription polling loop started for topic '{}' (queue: {})", topic_clone, queue_url);
             let mut poll_count = 0u64;
             loop {
+                let mut slots = tokio::select! {
+                    _ = shutdown.cancelled() => {
+                        info!("SQS subscription for topic '{}' closed", topic_clone);
+                        break;
+                    }
+                    slots = prefetch.claim() => slots,
+                };
+
                 poll_count += 1;
                 if poll_count % 5 == 0 {
                     info!("SQS polling loop still active for topic '{}' (poll #{}), queue: {}", topic_clone, poll_count, queue_url);
@@ -249,13 +304,20 @@ impl SqsAdapter {
                 } else {
                     debug!("Polling SQS queue for topic '{}' (poll #{})...", topic_clone, poll_count);
                 }
-                let receive_result = client
+                let receive = client
                     .receive_message()
                     .queue_url(&queue_url)
-                    .max_number_of_messages(10)
+                    .max_number_of_messages(slots.len() as i32)
                     .wait_time_seconds(20)
-                    .send()
-                    .await;
+                    .message_attribute_names(CONTENT_TYPE)
+                    .send();
+                let receive_result = tokio::select! {
+                    _ = shutdown.cancelled() => {
+                        info!("SQS subscription for topic '{}' closed", topic_clone);
+                        break;
+                    }
+                    result = receive => result,
+                };
 
                 match receive_result {
                     Ok(response) => {
@@ -263,50 +325,18 @@ impl SqsAdapter {
                         if !messages.is_empty() {
                             info!("Received {} message(s) from SQS queue for topic '{}'", messages.len(), topic_clone);
                             for sqs_message in messages {
-                                if let Some(body) = sqs_message.body() {
-                                    info!("Raw SQS message body for topic '{}': {}", topic_clone, body);
-                                    match serde_json::from_str::<Message>(body) {
-                                        Ok(message) => {
-                                            info!("Successfully parsed SQS message for topic '{}'", topic_clone);
-                                            info!("Message topic: {}, payload: {:?}", message.topic, message.payload);
-                                            info!("Calling handler for SQS message...");
-                                            if let Err(e) = handler.handle(message).await {
-                                                error!("Handler error for SQS topic '{}': {}", topic_clone, e);
-                                            } else {
-                                                info!("Handler completed successfully for SQS topic '{}'", topic_clone);
-                                            }
-
-                                            if let Some(receipt_handle) = sqs_message.receipt_handle() {
-                                                if let Err(e) = client
-                                                    .delete_message()
-                                                    .queue_url(&queue_url)
-                                                    .receipt_handle(receipt_handle)
-                                                    .send()
-                                                    .await
-                                                {
-                                                    warn!(
-                                                        "Failed to delete message from queue '{}': {}",
-                                                        queue_url, e
-                                                    );
-                                                }
-                                            }
-                                        }
-                                        Err(e) => {
-                                            error!(
-                                                "Failed to deserialize SQS message for topic '{}': {}. Body: {}",
-                                                topic_clone, e, body
-                                            );
-                                            if let Some(receipt_handle) = sqs_message.receipt_handle() {
-                                                let _ = client
-                                                    .delete_message()
-                                                    .queue_url(&queue_url)
-                                                    .receipt_handle(receipt_handle)
-                                                    .send()
-                                                    .await;
-                                            }
-                                        }
-                                    }
-                                }
+                                let Some(slot) = slots.pop() else {
+                                    break;
+                                };
+                                let client = client.clone();
+                                let queue_url = queue_url.clone();
+                                let topic = topic_clone.clone();
+                                let handler = handler.clone();
+                                let sqs_message = sqs_message.clone();
+                                tokio::spawn(async move {
+                                    handle_message(&client, &queue_url, &topic, handler.as_ref(), &sqs_message).await;
+                                    drop(slot);
+                                });
                             }
                         } else {
                             debug!("No messages received from SQS queue for topic '{}' (this is normal, continuing to poll...)", topic_clone);
@@ -327,6 +357,19 @@ impl SqsAdapter {
     }
 
     pub async fn subscribe_fn<F, Fut>(&self, topic: impl Into<String>, handler: F) -> Result<()>
+    where
+        F: Fn(Message) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<()>> + Send + 'static,
+    {
+        self.subscribe_fn_with_prefetch(topic, handler, 1).await
+    }
+
+    pub async fn subscribe_fn_with_prefetch<F, Fut>(
+        &self,
+        topic: impl Into<String>,
+        handler: F,
+        prefetch: usize,
+    ) -> Result<()>
     where
         F: Fn(Message) -> Fut + Send + Sync + 'static,
         Fut: std::future::Future<Output = Result<()>> + Send + 'static,
@@ -351,12 +394,107 @@ impl SqsAdapter {
         }
 
         let handler = Arc::new(ClosureHandler { func: handler });
-        self.subscribe(topic, handler).await
+        self.subscribe_with_prefetch(topic, handler, prefetch).await
     }
 
     pub async fn list_topics(&self) -> Vec<String> {
         let queue_urls = self.queue_urls.read().await;
         queue_urls.keys().cloned().collect()
     }
+
+    /// Approximate number of messages waiting in the topic's queue, or `None`
+    /// if no queue has been resolved for the topic yet.
+    pub async fn queue_depth(&self, topic: &str) -> Result<Option<u64>> {
+        let queue_url = match self.queue_urls.read().await.get(topic) {
+            Some(url) => url.clone(),
+            None => return Ok(None),
+        };
+
+        let response = self
+            .client
+            .get_queue_attributes()
+            .queue_url(&queue_url)
+            .attribute_names(QueueAttributeName::ApproximateNumberOfMessages)
+            .send()
+            .await
+            .map_err(|e| {
+                AdapterError::AwsSqs(format!("Failed to get attributes for queue '{}': {}", queue_url, e))
+            })?;
+
+        Ok(response
+            .attributes()
+            .and_then(|attrs| attrs.get(&QueueAttributeName::ApproximateNumberOfMessages))
+            .and_then(|depth| depth.parse().ok()))
+    }
 }
 
+/// Decodes and handles one received message, then deletes it from the queue.
+/// A body that fails to decode is handed over raw, flagged with
+/// [`DECODE_ERROR`], and deleted too, since it never will decode.
+async fn handle_message<H>(
+    client: &SqsClient,
+    queue_url: &str,
+    topic: &str,
+    handler: &H,
+    sqs_message: &aws_sdk_sqs::types::Message,
+) where
+    H: MessageHandler + ?Sized,
+{
+    let Some(body) = sqs_message.body() else {
+        return;
+    };
+    info!("Raw SQS message body for topic '{}': {}", topic, body);
+    // Messages without the attribute predate codecs and are JSON.
+    let codec = sqs_message
+        .message_attributes()
+        .and_then(|attrs| attrs.get(CONTENT_TYPE))
+        .and_then(|attr| attr.string_value())
+        .and_then(WireCodec::from_content_type)
+        .unwrap_or_default();
+    match codec.decode_text::<Message>(body) {
+        Ok(message) => {
+            info!("Successfully parsed SQS message for topic '{}'", topic);
+            info!("Message topic: {}, payload: {:?}", message.topic, message.payload);
+            info!("Calling handler for SQS message...");
+            if let Err(e) = handler.handle(message).await {
+                error!("Handler error for SQS topic '{}': {}", topic, e);
+            } else {
+                info!("Handler completed successfully for SQS topic '{}'", topic);
+            }
+
+            if let Some(receipt_handle) = sqs_message.receipt_handle() {
+                if let Err(e) = client
+                    .delete_message()
+                    .queue_url(queue_url)
+                    .receipt_handle(receipt_handle)
+                    .send()
+                    .await
+                {
+                    warn!(
+                        "Failed to delete message from queue '{}': {}",
+                        queue_url, e
+                    );
+                }
+            }
+        }
+        Err(e) => {
+            error!(
+                "Failed to deserialize SQS message for topic '{}': {}. Body: {}",
+                topic, e, body
+            );
+            let message = Message::new(topic, serde_json::Value::String(body.to_string()))
+                .with_metadata(DECODE_ERROR, e.to_string());
+            if let Err(e) = handler.handle(message).await {
+                error!("Handler error for undecodable SQS message on topic '{}': {}", topic, e);
+            }
+            if let Some(receipt_handle) = sqs_message.receipt_handle() {
+                let _ = client
+                    .delete_message()
+                    .queue_url(queue_url)
+                    .receipt_handle(receipt_handle)
+                    .send()
+                    .await;
+            }
+        }
+    }
+}
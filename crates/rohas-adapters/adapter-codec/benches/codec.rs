@@ -0,0 +1,79 @@
+//! Compares encoding and decoding a typical event envelope with each codec.
+//!
+//! Run with `cargo bench -p adapter-codec`. Encoded sizes are printed before
+//! the timings.
+
+use adapter_codec::WireCodec;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use serde_json::{json, Value};
+
+const CODECS: [WireCodec; 3] = [WireCodec::Json, WireCodec::MsgPack, WireCodec::Cbor];
+
+/// An `OrderPlaced`-style message as the SQS adapter puts it on the wire.
+fn sample_message() -> Value {
+    let items: Vec<Value> = (0..20)
+        .map(|i| {
+            json!({
+                "sku": format!("SKU-{:05}", i),
+                "quantity": i % 5 + 1,
+                "unitPrice": 9.99 + i as f64,
+                "discounted": i % 3 == 0,
+            })
+        })
+        .collect();
+
+    json!({
+        "topic": "OrderPlaced",
+        "payload": {
+            "orderId": 123456789,
+            "customerId": 987654,
+            "createdAt": "2024-01-01T00:00:00Z",
+            "currency": "USD",
+            "total": 1234.56,
+            "shipping": {
+                "name": "Jane Doe",
+                "street": "1 Main Street",
+                "city": "Springfield",
+                "postalCode": "12345",
+            },
+            "items": items,
+        },
+        "timestamp": "1704067200",
+        "metadata": { "trace_id": "4bf92f3577b34da6a3ce929d0e0e4736" },
+    })
+}
+
+fn codec_benchmarks(c: &mut Criterion) {
+    let message = sample_message();
+
+    for codec in CODECS {
+        let bytes = codec.encode(&message).unwrap();
+        let text = codec.encode_text(&message).unwrap();
+        println!(
+            "{:?}: {} bytes ({} bytes as SQS text)",
+            codec,
+            bytes.len(),
+            text.len()
+        );
+    }
+
+    let mut group = c.benchmark_group("encode");
+    for codec in CODECS {
+        group.bench_with_input(BenchmarkId::from_parameter(format!("{:?}", codec)), &message, |b, message| {
+            b.iter(|| codec.encode(black_box(message)).unwrap())
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("decode");
+    for codec in CODECS {
+        let bytes = codec.encode(&message).unwrap();
+        group.bench_with_input(BenchmarkId::from_parameter(format!("{:?}", codec)), &bytes, |b, bytes| {
+            b.iter(|| codec.decode::<Value>(black_box(bytes)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, codec_benchmarks);
+criterion_main!(benches);
@@ -0,0 +1,149 @@
+//! Wire codecs used by event adapters to put messages on a transport.
+//!
+//! Handlers always see JSON values; the codec only changes how the message
+//! envelope is encoded between publisher and subscriber. The codec in use is
+//! recorded next to the message (under [`CONTENT_TYPE`]) so consumers can
+//! decode messages written with a different setting.
+
+use base64::{engine::general_purpose, Engine as _};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Message metadata / attribute key carrying the codec's media type.
+pub const CONTENT_TYPE: &str = "content-type";
+
+/// Message metadata key set when a message's body could not be decoded. The
+/// message's payload then holds the raw body as a string, and this key the
+/// error, so consumers can set it aside instead of losing it.
+pub const DECODE_ERROR: &str = "decode_error";
+
+/// Message metadata key set by adapters that retry failed messages
+/// themselves, on a message's last delivery before it is dead-lettered.
+pub const FINAL_DELIVERY: &str = "final_delivery";
+
+pub type Result<T> = std::result::Result<T, CodecError>;
+
+#[derive(Error, Debug)]
+pub enum CodecError {
+    #[error("JSON codec error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("MessagePack encode error: {0}")]
+    MsgPackEncode(#[from] rmp_serde::encode::Error),
+
+    #[error("MessagePack decode error: {0}")]
+    MsgPackDecode(#[from] rmp_serde::decode::Error),
+
+    #[error("CBOR codec error: {0}")]
+    Cbor(String),
+
+    #[error("Invalid base64 body: {0}")]
+    Base64(#[from] base64::DecodeError),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WireCodec {
+    #[default]
+    Json,
+    MsgPack,
+    Cbor,
+}
+
+impl WireCodec {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "json" => Some(WireCodec::Json),
+            "msgpack" | "messagepack" => Some(WireCodec::MsgPack),
+            "cbor" => Some(WireCodec::Cbor),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            WireCodec::Json => "application/json",
+            WireCodec::MsgPack => "application/msgpack",
+            WireCodec::Cbor => "application/cbor",
+        }
+    }
+
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        match content_type.trim().to_lowercase().as_str() {
+            "application/json" => Some(WireCodec::Json),
+            "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack" => {
+                Some(WireCodec::MsgPack)
+            }
+            "application/cbor" => Some(WireCodec::Cbor),
+            _ => None,
+        }
+    }
+
+    pub fn encode<T: Serialize + ?Sized>(self, value: &T) -> Result<Vec<u8>> {
+        match self {
+            WireCodec::Json => Ok(serde_json::to_vec(value)?),
+            // Structs are written as maps so fields can be added later.
+            WireCodec::MsgPack => Ok(rmp_serde::to_vec_named(value)?),
+            WireCodec::Cbor => {
+                let mut out = Vec::new();
+                ciborium::into_writer(value, &mut out).map_err(|e| CodecError::Cbor(e.to_string()))?;
+                Ok(out)
+            }
+        }
+    }
+
+    pub fn decode<T: DeserializeOwned>(self, bytes: &[u8]) -> Result<T> {
+        match self {
+            WireCodec::Json => Ok(serde_json::from_slice(bytes)?),
+            WireCodec::MsgPack => Ok(rmp_serde::from_slice(bytes)?),
+            WireCodec::Cbor => ciborium::from_reader(bytes).map_err(|e| CodecError::Cbor(e.to_string())),
+        }
+    }
+
+    /// Encodes for transports whose message bodies must be text, such as SQS.
+    /// JSON is written as-is; binary codecs are base64 encoded.
+    pub fn encode_text<T: Serialize + ?Sized>(self, value: &T) -> Result<String> {
+        match self {
+            WireCodec::Json => Ok(serde_json::to_string(value)?),
+            _ => Ok(general_purpose::STANDARD.encode(self.encode(value)?)),
+        }
+    }
+
+    /// Reverses [`WireCodec::encode_text`].
+    pub fn decode_text<T: DeserializeOwned>(self, text: &str) -> Result<T> {
+        match self {
+            WireCodec::Json => Ok(serde_json::from_str(text)?),
+            _ => self.decode(&general_purpose::STANDARD.decode(text)?),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_round_trip() {
+        let value = json!({
+            "id": 42,
+            "negative": -7,
+            "price": 19.99,
+            "name": "Widget",
+            "tags": ["a", "b"],
+            "active": true,
+            "parent": null,
+        });
+
+        for codec in [WireCodec::Json, WireCodec::MsgPack, WireCodec::Cbor] {
+            let bytes = codec.encode(&value).unwrap();
+            assert_eq!(codec.decode::<serde_json::Value>(&bytes).unwrap(), value);
+
+            let text = codec.encode_text(&value).unwrap();
+            assert_eq!(codec.decode_text::<serde_json::Value>(&text).unwrap(), value);
+
+            assert_eq!(WireCodec::from_content_type(codec.content_type()), Some(codec));
+        }
+    }
+}
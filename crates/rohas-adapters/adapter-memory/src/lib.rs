@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 use thiserror::Error;
-use tokio::sync::{broadcast, RwLock};
+use tokio::sync::{broadcast, RwLock, Semaphore};
 use tracing::{debug, info};
 
 pub type Result<T> = std::result::Result<T, AdapterError>;
@@ -89,9 +89,20 @@ impl MemoryAdapter {
         &self,
         topic: impl Into<String>,
         payload: serde_json::Value,
+    ) -> Result<()> {
+        self.publish_with_metadata(topic, payload, HashMap::new()).await
+    }
+
+    /// Publish a message carrying metadata, e.g. trace context
+    pub async fn publish_with_metadata(
+        &self,
+        topic: impl Into<String>,
+        payload: serde_json::Value,
+        metadata: HashMap<String, String>,
     ) -> Result<()> {
         let topic = topic.into();
-        let message = Message::new(topic.clone(), payload);
+        let mut message = Message::new(topic.clone(), payload);
+        message.metadata = metadata;
 
         let sender = self.get_or_create_channel(&topic).await;
 
@@ -105,20 +116,43 @@ impl MemoryAdapter {
 
     /// Subscribe to a topic with a handler
     pub async fn subscribe<H>(&self, topic: impl Into<String>, handler: Arc<H>) -> Result<()>
+    where
+        H: MessageHandler + 'static,
+    {
+        self.subscribe_with_prefetch(topic, handler, 1).await
+    }
+
+    /// Subscribe with up to `prefetch` messages taken off the channel and
+    /// handled at once. With a prefetch of one, messages are handled in
+    /// publish order.
+    pub async fn subscribe_with_prefetch<H>(
+        &self,
+        topic: impl Into<String>,
+        handler: Arc<H>,
+        prefetch: usize,
+    ) -> Result<()>
     where
         H: MessageHandler + 'static,
     {
         let topic = topic.into();
         let sender = self.get_or_create_channel(&topic).await;
         let mut receiver = sender.subscribe();
+        let slots = Arc::new(Semaphore::new(prefetch.max(1)));
 
-        info!("Subscribed to topic: {}", topic);
+        info!("Subscribed to topic: {} (prefetch: {})", topic, prefetch);
 
         tokio::spawn(async move {
-            while let Ok(message) = receiver.recv().await {
-                if let Err(e) = handler.handle(message).await {
-                    tracing::error!("Handler error: {}", e);
-                }
+            while let Ok(slot) = slots.clone().acquire_owned().await {
+                let Ok(message) = receiver.recv().await else {
+                    break;
+                };
+                let handler = handler.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = handler.handle(message).await {
+                        tracing::error!("Handler error: {}", e);
+                    }
+                    drop(slot);
+                });
             }
         });
 
@@ -127,6 +161,20 @@ impl MemoryAdapter {
 
     /// Subscribe with a closure
     pub async fn subscribe_fn<F, Fut>(&self, topic: impl Into<String>, handler: F) -> Result<()>
+    where
+        F: Fn(Message) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<()>> + Send + 'static,
+    {
+        self.subscribe_fn_with_prefetch(topic, handler, 1).await
+    }
+
+    /// Subscribe with a closure, see [`MemoryAdapter::subscribe_with_prefetch`]
+    pub async fn subscribe_fn_with_prefetch<F, Fut>(
+        &self,
+        topic: impl Into<String>,
+        handler: F,
+        prefetch: usize,
+    ) -> Result<()>
     where
         F: Fn(Message) -> Fut + Send + Sync + 'static,
         Fut: std::future::Future<Output = Result<()>> + Send + 'static,
@@ -151,7 +199,7 @@ impl MemoryAdapter {
         }
 
         let handler = Arc::new(ClosureHandler { func: handler });
-        self.subscribe(topic, handler).await
+        self.subscribe_with_prefetch(topic, handler, prefetch).await
     }
 
     /// Get list of all topics
@@ -160,6 +208,12 @@ impl MemoryAdapter {
         channels.keys().cloned().collect()
     }
 
+    /// Drops every channel. Subscribers handle the messages already buffered
+    /// for them, then stop.
+    pub async fn close(&self) {
+        self.channels.write().await.clear();
+    }
+
     /// Get subscriber count for a topic
     pub async fn subscriber_count(&self, topic: &str) -> usize {
         let channels = self.channels.read().await;
@@ -212,4 +266,90 @@ mod tests {
         assert_eq!(messages.len(), 1);
         assert_eq!(messages[0]["value"], 42);
     }
+
+    #[tokio::test]
+    async fn test_publish_with_metadata() {
+        let adapter = MemoryAdapter::new(10);
+        let received = Arc::new(RwLock::new(None));
+        let received_clone = received.clone();
+
+        adapter
+            .subscribe_fn("test_topic", move |msg| {
+                let received = received_clone.clone();
+                async move {
+                    *received.write().await = Some(msg.metadata);
+                    Ok(())
+                }
+            })
+            .await
+            .unwrap();
+
+        let metadata = HashMap::from([("parent_trace_id".to_string(), "t1".to_string())]);
+        adapter
+            .publish_with_metadata("test_topic", serde_json::json!({}), metadata.clone())
+            .await
+            .unwrap();
+        sleep(Duration::from_millis(10)).await;
+
+        assert_eq!(*received.read().await, Some(metadata));
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_bounds_in_flight() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let adapter = MemoryAdapter::new(10);
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let done = Arc::new(AtomicUsize::new(0));
+        let (in_flight_clone, peak_clone, done_clone) = (in_flight.clone(), peak.clone(), done.clone());
+
+        adapter
+            .subscribe_fn_with_prefetch("test_topic", move |_| {
+                let (in_flight, peak, done) = (in_flight_clone.clone(), peak_clone.clone(), done_clone.clone());
+                async move {
+                    let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    sleep(Duration::from_millis(20)).await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    done.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                }
+            }, 2)
+            .await
+            .unwrap();
+
+        for value in 0..5 {
+            adapter
+                .publish("test_topic", serde_json::json!({"value": value}))
+                .await
+                .unwrap();
+        }
+        sleep(Duration::from_millis(150)).await;
+
+        assert_eq!(done.load(Ordering::SeqCst), 5);
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_close_stops_subscribers() {
+        let adapter = MemoryAdapter::new(10);
+        let handler_state = Arc::new(());
+        let state = handler_state.clone();
+
+        adapter
+            .subscribe_fn("test_topic", move |_| {
+                let _state = state.clone();
+                async { Ok(()) }
+            })
+            .await
+            .unwrap();
+        assert_eq!(Arc::strong_count(&handler_state), 2);
+
+        adapter.close().await;
+        sleep(Duration::from_millis(10)).await;
+
+        assert_eq!(Arc::strong_count(&handler_state), 1);
+        assert!(adapter.list_topics().await.is_empty());
+    }
 }
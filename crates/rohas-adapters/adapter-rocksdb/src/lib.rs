@@ -1,3 +1,5 @@
+pub mod queue;
+
 use rohas_telemetry::error::{Result, TelemetryError};
 use rohas_telemetry::storage::{IterateCallback, StorageAdapter};
 use async_trait::async_trait;
@@ -0,0 +1,891 @@
+//! A durable single-node message queue on RocksDB, for deployments without
+//! an external broker.
+//!
+//! Every published message is written to disk before `publish` returns and
+//! stays there until a subscriber's handler succeeds. A failed handler is
+//! retried with exponential backoff; after `max_retries` retries the message
+//! moves to the topic's dead-letter queue. Messages being handled when the
+//! process stops are delivered again on the next start, so handlers should be
+//! idempotent, as with SQS.
+//!
+//! Messages that are due sit under `q:` in publish order, and scheduled
+//! retries and delayed messages under `retry:` in due order, so a consumer
+//! only ever reads the head of each.
+
+use adapter_codec::{DECODE_ERROR, FINAL_DELIVERY};
+use rocksdb::{IteratorMode, Options, WriteBatch, DB};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+use tokio::sync::{Mutex, Notify, Semaphore};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
+
+pub type Result<T> = std::result::Result<T, QueueError>;
+
+#[derive(Error, Debug)]
+pub enum QueueError {
+    #[error("Queue storage error: {0}")]
+    Storage(String),
+
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    #[error("Handler error: {0}")]
+    Handler(String),
+}
+
+/// Longest wait between retries, however many attempts have failed.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(300);
+
+/// Longest an idle consumer waits before looking at its topic again.
+const IDLE_POLL: Duration = Duration::from_secs(1);
+
+const READY: &[u8] = b"q:";
+const DELAYED: &[u8] = b"retry:";
+const DEAD: &[u8] = b"dlq:";
+
+#[derive(Debug, Clone)]
+pub struct QueueConfig {
+    pub path: PathBuf,
+    /// Retries after the first failed delivery before a message is
+    /// dead-lettered.
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled for each one after it.
+    pub retry_delay: Duration,
+}
+
+/// A queued message as handed to subscribers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueuedMessage {
+    pub topic: String,
+    pub payload: Value,
+    pub timestamp: String,
+    pub metadata: HashMap<String, String>,
+    /// Failed deliveries so far.
+    pub attempts: u32,
+    /// Last handler error, kept for dead letters.
+    #[serde(default)]
+    pub last_error: Option<String>,
+}
+
+/// Wakes a topic's consumers and counts its messages, so the depth is known
+/// without reading the queue.
+#[derive(Default)]
+struct Topic {
+    notify: Notify,
+    /// Messages due or scheduled for a retry.
+    depth: AtomicU64,
+}
+
+impl Topic {
+    /// Counts a message as gone, acked or dead-lettered.
+    fn remove_one(&self) {
+        let _ = self.depth.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |depth| Some(depth.saturating_sub(1)));
+    }
+}
+
+/// The retry settings, shared with the consumers so that
+/// [`QueueAdapter::set_retries`] reaches topics already subscribed to.
+struct Retries {
+    max_retries: AtomicU32,
+    retry_delay_ms: AtomicU64,
+}
+
+impl Retries {
+    fn max_retries(&self) -> u32 {
+        self.max_retries.load(Ordering::SeqCst)
+    }
+
+    fn retry_delay(&self) -> Duration {
+        Duration::from_millis(self.retry_delay_ms.load(Ordering::SeqCst))
+    }
+}
+
+pub struct QueueAdapter {
+    db: Arc<DB>,
+    path: PathBuf,
+    retries: Arc<Retries>,
+    seq: AtomicU64,
+    topics: Mutex<HashMap<String, Arc<Topic>>>,
+    /// Keys handed to a handler and not yet acked, so competing subscribers
+    /// of a topic never get the same message.
+    in_flight: Arc<Mutex<HashSet<Vec<u8>>>>,
+    shutdown: CancellationToken,
+}
+
+impl QueueAdapter {
+    pub fn open(config: QueueConfig) -> Result<Self> {
+        std::fs::create_dir_all(&config.path).map_err(|e| QueueError::Storage(e.to_string()))?;
+
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        let db = DB::open(&opts, &config.path).map_err(|e| QueueError::Storage(e.to_string()))?;
+
+        // Continue the sequence after the highest key left from earlier runs,
+        // and count what each topic has waiting.
+        let mut last_seq = 0;
+        let mut pending = 0;
+        let mut topics: HashMap<String, Arc<Topic>> = HashMap::new();
+        for item in db.iterator(IteratorMode::Start) {
+            let (key, _) = item.map_err(|e| QueueError::Storage(e.to_string()))?;
+            if let Some((topic, seq)) = split_key(&key) {
+                last_seq = last_seq.max(seq);
+                let topic = topics.entry(topic).or_default();
+                if !key.starts_with(DEAD) {
+                    topic.depth.fetch_add(1, Ordering::SeqCst);
+                    pending += 1;
+                }
+            }
+        }
+
+        info!(
+            "Opened embedded queue at {} ({} pending messages)",
+            config.path.display(),
+            pending
+        );
+
+        Ok(Self {
+            db: Arc::new(db),
+            retries: Arc::new(Retries {
+                max_retries: AtomicU32::new(config.max_retries),
+                retry_delay_ms: AtomicU64::new(config.retry_delay.as_millis() as u64),
+            }),
+            path: config.path,
+            seq: AtomicU64::new(last_seq + 1),
+            topics: Mutex::new(topics),
+            in_flight: Arc::new(Mutex::new(HashSet::new())),
+            shutdown: CancellationToken::new(),
+        })
+    }
+
+    /// The directory the queue was opened in. RocksDB keeps it locked while
+    /// the queue is open.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Changes the retry settings of the open queue. Messages already
+    /// waiting for a retry keep the delay they were scheduled with.
+    pub fn set_retries(&self, max_retries: u32, retry_delay: Duration) {
+        self.retries.max_retries.store(max_retries, Ordering::SeqCst);
+        self.retries.retry_delay_ms.store(retry_delay.as_millis() as u64, Ordering::SeqCst);
+        info!(
+            "Embedded queue at {} now retries {} times starting after {:?}",
+            self.path.display(),
+            max_retries,
+            retry_delay
+        );
+    }
+
+    async fn topic(&self, topic: &str) -> Arc<Topic> {
+        self.topics.lock().await.entry(topic.to_string()).or_default().clone()
+    }
+
+    /// Writes a message to the topic's queue. It is on disk when this returns.
+    pub async fn publish(&self, topic: impl Into<String>, payload: Value) -> Result<()> {
+        self.publish_with_metadata(topic, payload, HashMap::new()).await
+    }
+
+    pub async fn publish_with_metadata(
+        &self,
+        topic: impl Into<String>,
+        payload: Value,
+        metadata: HashMap<String, String>,
+    ) -> Result<()> {
+        self.enqueue(topic.into(), payload, metadata, None).await
+    }
+
+    /// Writes a message that is delivered once `due` has passed. It waits
+    /// with the scheduled retries, so no consumer holds it until then.
+    pub async fn publish_with_metadata_at(
+        &self,
+        topic: impl Into<String>,
+        payload: Value,
+        metadata: HashMap<String, String>,
+        due: SystemTime,
+    ) -> Result<()> {
+        let due_ms = due.duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or_default();
+        self.enqueue(topic.into(), payload, metadata, Some(due_ms)).await
+    }
+
+    async fn enqueue(
+        &self,
+        topic: String,
+        payload: Value,
+        metadata: HashMap<String, String>,
+        due_ms: Option<u64>,
+    ) -> Result<()> {
+        let message = QueuedMessage {
+            topic: topic.clone(),
+            payload,
+            timestamp: (now_ms() / 1000).to_string(),
+            metadata,
+            attempts: 0,
+            last_error: None,
+        };
+
+        let seq = self.seq.fetch_add(1, Ordering::SeqCst);
+        let key = match due_ms {
+            Some(due_ms) => delayed_key(&topic, due_ms, seq),
+            None => key(READY, &topic, seq),
+        };
+        self.db
+            .put(key, serde_json::to_vec(&message)?)
+            .map_err(|e| QueueError::Storage(e.to_string()))?;
+        let state = self.topic(&topic).await;
+        state.depth.fetch_add(1, Ordering::SeqCst);
+        state.notify.notify_waiters();
+
+        debug!("Queued message {} on topic: {}", seq, topic);
+        Ok(())
+    }
+
+    /// Starts a consumer for the topic. A message is removed once `handler`
+    /// returns `Ok`; an error schedules a retry or dead-letters it. The last
+    /// delivery before a message would be dead-lettered carries
+    /// [`FINAL_DELIVERY`] in its metadata. Several subscribers of one topic
+    /// compete for its messages.
+    pub async fn subscribe_fn<F, Fut>(&self, topic: impl Into<String>, handler: F) -> Result<()>
+    where
+        F: Fn(QueuedMessage) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.subscribe_fn_with_prefetch(topic, handler, 1).await
+    }
+
+    /// Subscribes with up to `prefetch` messages claimed and handled at once.
+    /// Each stays in the queue until its own handler settles it.
+    pub async fn subscribe_fn_with_prefetch<F, Fut>(
+        &self,
+        topic: impl Into<String>,
+        handler: F,
+        prefetch: usize,
+    ) -> Result<()>
+    where
+        F: Fn(QueuedMessage) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        let topic = topic.into();
+        let consumer = Arc::new(Consumer {
+            db: self.db.clone(),
+            retries: self.retries.clone(),
+            ready: topic_prefix(READY, &topic),
+            delayed: topic_prefix(DELAYED, &topic),
+            state: self.topic(&topic).await,
+            topic: topic.clone(),
+            in_flight: self.in_flight.clone(),
+            slots: Arc::new(Semaphore::new(prefetch.max(1))),
+        });
+        let shutdown = self.shutdown.clone();
+
+        info!("Subscribed to embedded queue topic: {} (prefetch: {})", topic, prefetch);
+        tokio::spawn(async move { consumer.run(handler, shutdown).await });
+        Ok(())
+    }
+
+    pub async fn list_topics(&self) -> Vec<String> {
+        let mut topics: Vec<String> = self.topics.lock().await.keys().cloned().collect();
+        topics.sort();
+        topics
+    }
+
+    /// Messages waiting on the topic, including scheduled retries.
+    pub async fn queue_depth(&self, topic: &str) -> Result<u64> {
+        Ok(self
+            .topics
+            .lock()
+            .await
+            .get(topic)
+            .map_or(0, |topic| topic.depth.load(Ordering::SeqCst)))
+    }
+
+    /// Messages of the topic that exhausted their retries, oldest first.
+    pub async fn dead_letters(&self, topic: &str) -> Result<Vec<QueuedMessage>> {
+        let prefix = topic_prefix(DEAD, topic);
+        scan(&self.db, &prefix)?
+            .into_iter()
+            .map(|(_, value)| Ok(serde_json::from_slice(&value)?))
+            .collect()
+    }
+
+    /// Moves the topic's dead letters back onto its queue with their attempt
+    /// counts reset. Returns how many were moved.
+    pub async fn redrive(&self, topic: &str) -> Result<usize> {
+        let dead = scan(&self.db, &topic_prefix(DEAD, topic))?;
+        let mut batch = WriteBatch::default();
+        for (dead_key, value) in &dead {
+            let mut message: QueuedMessage = serde_json::from_slice(value)?;
+            message.attempts = 0;
+            message.last_error = None;
+            let seq = self.seq.fetch_add(1, Ordering::SeqCst);
+            batch.put(key(READY, topic, seq), serde_json::to_vec(&message)?);
+            batch.delete(dead_key);
+        }
+        self.db.write(batch).map_err(|e| QueueError::Storage(e.to_string()))?;
+        let state = self.topic(topic).await;
+        state.depth.fetch_add(dead.len() as u64, Ordering::SeqCst);
+        state.notify.notify_waiters();
+
+        info!("Moved {} dead letters back onto topic: {}", dead.len(), topic);
+        Ok(dead.len())
+    }
+
+    /// Stops every consumer once its current message is handled. Unacked
+    /// messages stay on disk.
+    pub fn close(&self) {
+        self.shutdown.cancel();
+    }
+}
+
+enum Next {
+    Ready(Vec<u8>, QueuedMessage),
+    Wait(Duration),
+}
+
+struct Consumer {
+    db: Arc<DB>,
+    retries: Arc<Retries>,
+    topic: String,
+    ready: Vec<u8>,
+    delayed: Vec<u8>,
+    state: Arc<Topic>,
+    in_flight: Arc<Mutex<HashSet<Vec<u8>>>>,
+    /// One permit per message this consumer may hold at once.
+    slots: Arc<Semaphore>,
+}
+
+impl Consumer {
+    async fn run<F, Fut>(self: Arc<Self>, handler: F, shutdown: CancellationToken)
+    where
+        F: Fn(QueuedMessage) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        let handler = Arc::new(handler);
+        loop {
+            let slot = tokio::select! {
+                biased;
+                _ = shutdown.cancelled() => break,
+                slot = self.slots.clone().acquire_owned() => slot.expect("consumer slots are never closed"),
+            };
+
+            // Register for wakeups before looking, so a publish between the
+            // scan and the wait is not missed.
+            let notified = self.state.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            let (key, message) = match self.claim_next().await {
+                Ok(Next::Ready(key, message)) => (key, message),
+                Ok(Next::Wait(wait)) => {
+                    tokio::select! {
+                        biased;
+                        _ = shutdown.cancelled() => break,
+                        _ = &mut notified => {}
+                        _ = tokio::time::sleep(wait) => {}
+                    }
+                    continue;
+                }
+                Err(e) => {
+                    error!("Failed to read embedded queue topic '{}': {}", self.topic, e);
+                    tokio::select! {
+                        biased;
+                        _ = shutdown.cancelled() => break,
+                        _ = tokio::time::sleep(IDLE_POLL) => {}
+                    }
+                    continue;
+                }
+            };
+
+            let consumer = self.clone();
+            let handler = handler.clone();
+            tokio::spawn(async move {
+                let mut delivery = message.clone();
+                if message.attempts >= consumer.retries.max_retries() {
+                    delivery.metadata.insert(FINAL_DELIVERY.to_string(), "true".to_string());
+                }
+                let result = handler(delivery).await;
+                if let Err(e) = consumer.settle(&key, message, result).await {
+                    error!("Failed to update embedded queue topic '{}': {}", consumer.topic, e);
+                }
+                consumer.in_flight.lock().await.remove(&key);
+                drop(slot);
+            });
+        }
+        debug!("Embedded queue consumer for topic '{}' stopped", self.topic);
+    }
+
+    /// Claims the oldest message that is due and not held by another
+    /// consumer, or says how long to wait for the next scheduled retry.
+    /// Retries that have come due are moved back to the ready messages
+    /// first; only in-flight messages are skipped over.
+    async fn claim_next(&self) -> Result<Next> {
+        let db_error = |e: rocksdb::Error| QueueError::Storage(e.to_string());
+        let now = now_ms();
+        let mut next_due = now + IDLE_POLL.as_millis() as u64;
+        let mut in_flight = self.in_flight.lock().await;
+
+        let mut due = WriteBatch::default();
+        for item in self.db.iterator(IteratorMode::From(&self.delayed, rocksdb::Direction::Forward)) {
+            let (key, value) = item.map_err(db_error)?;
+            let Some((due_ms, seq)) = key.strip_prefix(self.delayed.as_slice()).and_then(split_delayed) else {
+                break;
+            };
+            if due_ms > now {
+                next_due = next_due.min(due_ms);
+                break;
+            }
+            due.put(self::key(READY, &self.topic, seq), &value);
+            due.delete(&key);
+        }
+        if !due.is_empty() {
+            self.db.write(due).map_err(db_error)?;
+        }
+
+        for item in self.db.iterator(IteratorMode::From(&self.ready, rocksdb::Direction::Forward)) {
+            let (key, value) = item.map_err(db_error)?;
+            if !key.starts_with(&self.ready) {
+                break;
+            }
+            if in_flight.contains(&*key) {
+                continue;
+            }
+            let message = match serde_json::from_slice::<QueuedMessage>(&value) {
+                Ok(message) => message,
+                Err(e) => {
+                    warn!("Undecodable message on embedded queue topic '{}': {}", self.topic, e);
+                    undecodable(&self.topic, &value, e)
+                }
+            };
+            in_flight.insert(key.to_vec());
+            return Ok(Next::Ready(key.to_vec(), message));
+        }
+        Ok(Next::Wait(Duration::from_millis(next_due - now)))
+    }
+
+    /// Acks a handled message, or schedules its retry or dead-letters it.
+    async fn settle(&self, key: &[u8], mut message: QueuedMessage, result: Result<()>) -> Result<()> {
+        let db_error = |e: rocksdb::Error| QueueError::Storage(e.to_string());
+
+        let error = match result {
+            Ok(()) => {
+                self.db.delete(key).map_err(db_error)?;
+                self.state.remove_one();
+                return Ok(());
+            }
+            Err(e) => e.to_string(),
+        };
+
+        message.attempts += 1;
+        message.last_error = Some(error.clone());
+
+        if message.attempts > self.retries.max_retries() {
+            warn!(
+                "Message on topic '{}' failed {} times, moving it to the dead-letter queue: {}",
+                self.topic, message.attempts, error
+            );
+            let mut dead_key = DEAD.to_vec();
+            dead_key.extend_from_slice(&key[READY.len()..]);
+            let mut batch = WriteBatch::default();
+            batch.put(dead_key, serde_json::to_vec(&message)?);
+            batch.delete(key);
+            self.db.write(batch).map_err(db_error)?;
+            self.state.remove_one();
+            return Ok(());
+        }
+
+        let delay = retry_delay(self.retries.retry_delay(), message.attempts);
+        warn!(
+            "Message on topic '{}' failed (attempt {}), retrying in {:?}: {}",
+            self.topic, message.attempts, delay, error
+        );
+        let seq = u64::from_be_bytes(key[key.len() - 8..].try_into().expect("queue keys end in a sequence"));
+        let due_ms = now_ms() + delay.as_millis() as u64;
+        let mut batch = WriteBatch::default();
+        batch.put(delayed_key(&self.topic, due_ms, seq), serde_json::to_vec(&message)?);
+        batch.delete(key);
+        self.db.write(batch).map_err(db_error)
+    }
+}
+
+/// Stands in for a stored record that does not decode, so that it is handed
+/// to the handler (and settled like any other message) instead of blocking
+/// the topic.
+fn undecodable(topic: &str, value: &[u8], error: serde_json::Error) -> QueuedMessage {
+    QueuedMessage {
+        topic: topic.to_string(),
+        payload: Value::String(String::from_utf8_lossy(value).into_owned()),
+        timestamp: (now_ms() / 1000).to_string(),
+        metadata: HashMap::from([(DECODE_ERROR.to_string(), error.to_string())]),
+        attempts: 0,
+        last_error: None,
+    }
+}
+
+fn retry_delay(base: Duration, attempts: u32) -> Duration {
+    base.saturating_mul(2u32.saturating_pow(attempts.saturating_sub(1)))
+        .min(MAX_RETRY_DELAY)
+}
+
+/// `<space><topic>\0`; the topic is followed by a NUL so that one topic's
+/// prefix never matches another's.
+fn topic_prefix(space: &[u8], topic: &str) -> Vec<u8> {
+    let mut prefix = space.to_vec();
+    prefix.extend_from_slice(topic.as_bytes());
+    prefix.push(0);
+    prefix
+}
+
+/// `<space><topic>\0<seq>`, with the sequence big-endian so keys sort in
+/// publish order.
+fn key(space: &[u8], topic: &str, seq: u64) -> Vec<u8> {
+    let mut key = topic_prefix(space, topic);
+    key.extend_from_slice(&seq.to_be_bytes());
+    key
+}
+
+/// `retry:<topic>\0<due_ms><seq>`, both big-endian so retries sort by when
+/// they are due.
+fn delayed_key(topic: &str, due_ms: u64, seq: u64) -> Vec<u8> {
+    let mut key = topic_prefix(DELAYED, topic);
+    key.extend_from_slice(&due_ms.to_be_bytes());
+    key.extend_from_slice(&seq.to_be_bytes());
+    key
+}
+
+/// The due time and sequence of a delayed key, after its topic prefix.
+fn split_delayed(rest: &[u8]) -> Option<(u64, u64)> {
+    let due_ms = u64::from_be_bytes(rest.get(..8)?.try_into().ok()?);
+    let seq = u64::from_be_bytes(rest.get(8..16)?.try_into().ok()?);
+    Some((due_ms, seq))
+}
+
+fn split_key(key: &[u8]) -> Option<(String, u64)> {
+    if let Some(rest) = key.strip_prefix(DELAYED) {
+        let nul = rest.len().checked_sub(17)?;
+        let topic = std::str::from_utf8(&rest[..nul]).ok()?;
+        let (_, seq) = split_delayed(&rest[nul + 1..])?;
+        return Some((topic.to_string(), seq));
+    }
+    let rest = key.strip_prefix(READY).or_else(|| key.strip_prefix(DEAD))?;
+    let nul = rest.len().checked_sub(9)?;
+    let topic = std::str::from_utf8(&rest[..nul]).ok()?;
+    let seq = u64::from_be_bytes(rest[nul + 1..].try_into().ok()?);
+    Some((topic.to_string(), seq))
+}
+
+fn scan(db: &DB, prefix: &[u8]) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+    let mut entries = Vec::new();
+    for item in db.iterator(IteratorMode::From(prefix, rocksdb::Direction::Forward)) {
+        let (key, value) = item.map_err(|e| QueueError::Storage(e.to_string()))?;
+        if !key.starts_with(prefix) {
+            break;
+        }
+        entries.push((key.to_vec(), value.to_vec()));
+    }
+    Ok(entries)
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+    use tokio::sync::{mpsc, Barrier};
+    use tokio::time::{sleep, timeout};
+
+    /// How long a test waits for something the consumer does in the
+    /// background before failing.
+    const WAIT: Duration = Duration::from_secs(5);
+
+    fn open(dir: &TempDir, max_retries: u32) -> QueueAdapter {
+        QueueAdapter::open(QueueConfig {
+            path: dir.path().join("queue"),
+            max_retries,
+            retry_delay: Duration::from_millis(10),
+        })
+        .unwrap()
+    }
+
+    /// Waits for the next value a test handler passes on.
+    async fn next<T>(deliveries: &mut mpsc::UnboundedReceiver<T>) -> T {
+        timeout(WAIT, deliveries.recv())
+            .await
+            .expect("timed out waiting for a delivery")
+            .expect("handler was dropped")
+    }
+
+    /// Waits for the topic's depth, which changes once a handled message is
+    /// settled, after its handler returns.
+    async fn wait_for_depth(queue: &QueueAdapter, topic: &str, depth: u64) {
+        timeout(WAIT, async {
+            while queue.queue_depth(topic).await.unwrap() != depth {
+                sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await
+        .expect("timed out waiting for the queue depth");
+    }
+
+    #[tokio::test]
+    async fn test_acks_handled_messages() {
+        let dir = TempDir::new().unwrap();
+        let queue = open(&dir, 3);
+
+        queue.publish("orders", json!({"id": 1})).await.unwrap();
+        queue.publish("orders", json!({"id": 2})).await.unwrap();
+        queue.publish("other", json!({"id": 3})).await.unwrap();
+        assert_eq!(queue.queue_depth("orders").await.unwrap(), 2);
+
+        let (sink, mut received) = mpsc::unbounded_channel();
+        queue
+            .subscribe_fn("orders", move |message| {
+                let _ = sink.send(message.payload);
+                async { Ok(()) }
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(next(&mut received).await, json!({"id": 1}));
+        assert_eq!(next(&mut received).await, json!({"id": 2}));
+        wait_for_depth(&queue, "orders", 0).await;
+        assert_eq!(queue.queue_depth("other").await.unwrap(), 1);
+        assert_eq!(queue.list_topics().await, vec!["orders", "other"]);
+    }
+
+    #[tokio::test]
+    async fn test_retries_then_dead_letters() {
+        let dir = TempDir::new().unwrap();
+        let queue = open(&dir, 2);
+
+        let (sink, mut calls) = mpsc::unbounded_channel();
+        queue
+            .subscribe_fn("orders", move |_| {
+                let _ = sink.send(());
+                async { Err(QueueError::Handler("boom".to_string())) }
+            })
+            .await
+            .unwrap();
+        queue.publish("orders", json!({"id": 1})).await.unwrap();
+
+        for _ in 0..3 {
+            next(&mut calls).await;
+        }
+        wait_for_depth(&queue, "orders", 0).await;
+        let dead = queue.dead_letters("orders").await.unwrap();
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].attempts, 3);
+        assert_eq!(dead[0].last_error.as_deref(), Some("Handler error: boom"));
+        assert!(calls.try_recv().is_err());
+
+        queue.close();
+        assert_eq!(queue.redrive("orders").await.unwrap(), 1);
+        assert!(queue.dead_letters("orders").await.unwrap().is_empty());
+        assert_eq!(queue.queue_depth("orders").await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_set_retries_reaches_running_consumers() {
+        let dir = TempDir::new().unwrap();
+        let queue = open(&dir, 0);
+
+        let (sink, mut calls) = mpsc::unbounded_channel();
+        queue
+            .subscribe_fn("orders", move |_| {
+                let _ = sink.send(());
+                async { Err(QueueError::Handler("boom".to_string())) }
+            })
+            .await
+            .unwrap();
+        queue.set_retries(2, Duration::from_millis(1));
+        queue.publish("orders", json!({"id": 1})).await.unwrap();
+
+        for _ in 0..3 {
+            next(&mut calls).await;
+        }
+        wait_for_depth(&queue, "orders", 0).await;
+        assert_eq!(queue.dead_letters("orders").await.unwrap()[0].attempts, 3);
+        assert_eq!(queue.path(), dir.path().join("queue"));
+    }
+
+    #[tokio::test]
+    async fn test_marks_the_final_delivery() {
+        let dir = TempDir::new().unwrap();
+        let queue = open(&dir, 1);
+
+        let (sink, mut finals) = mpsc::unbounded_channel();
+        queue
+            .subscribe_fn("orders", move |message| {
+                let _ = sink.send(message.metadata.contains_key(FINAL_DELIVERY));
+                async { Err(QueueError::Handler("boom".to_string())) }
+            })
+            .await
+            .unwrap();
+        queue.publish("orders", json!({"id": 1})).await.unwrap();
+
+        assert!(!next(&mut finals).await);
+        assert!(next(&mut finals).await);
+        wait_for_depth(&queue, "orders", 0).await;
+        let dead = queue.dead_letters("orders").await.unwrap();
+        assert!(!dead[0].metadata.contains_key(FINAL_DELIVERY));
+    }
+
+    #[tokio::test]
+    async fn test_delivers_messages_behind_a_scheduled_retry() {
+        let dir = TempDir::new().unwrap();
+        let queue = QueueAdapter::open(QueueConfig {
+            path: dir.path().join("queue"),
+            max_retries: 3,
+            retry_delay: Duration::from_secs(60),
+        })
+        .unwrap();
+        queue.publish("orders", json!({"id": 1})).await.unwrap();
+        queue.publish("orders", json!({"id": 2})).await.unwrap();
+
+        let (sink, mut received) = mpsc::unbounded_channel();
+        queue
+            .subscribe_fn("orders", move |message| {
+                let first = message.payload == json!({"id": 1});
+                let _ = sink.send(message.payload);
+                async move {
+                    if first {
+                        Err(QueueError::Handler("boom".to_string()))
+                    } else {
+                        Ok(())
+                    }
+                }
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(next(&mut received).await, json!({"id": 1}));
+        assert_eq!(next(&mut received).await, json!({"id": 2}));
+        // The retry waits under its own prefix and still counts as queued.
+        wait_for_depth(&queue, "orders", 1).await;
+        assert_eq!(scan(&queue.db, &topic_prefix(READY, "orders")).unwrap().len(), 0);
+        assert_eq!(scan(&queue.db, &topic_prefix(DELAYED, "orders")).unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_delivers_a_delayed_message_once_due() {
+        let dir = TempDir::new().unwrap();
+        let queue = open(&dir, 3);
+        let due = SystemTime::now() + Duration::from_millis(200);
+        queue
+            .publish_with_metadata_at("orders", json!({"id": 1}), HashMap::new(), due)
+            .await
+            .unwrap();
+        queue.publish("orders", json!({"id": 2})).await.unwrap();
+
+        let (sink, mut received) = mpsc::unbounded_channel();
+        queue
+            .subscribe_fn("orders", move |message| {
+                let _ = sink.send((message.payload, SystemTime::now()));
+                async { Ok(()) }
+            })
+            .await
+            .unwrap();
+
+        // With a single slot, the ready message is not held up by the delayed one.
+        assert_eq!(next(&mut received).await.0, json!({"id": 2}));
+        let (payload, at) = next(&mut received).await;
+        assert_eq!(payload, json!({"id": 1}));
+        assert!(at + Duration::from_millis(1) >= due);
+        wait_for_depth(&queue, "orders", 0).await;
+    }
+
+    #[test]
+    fn test_delayed_keys_sort_by_due_time() {
+        let early = delayed_key("orders", 1_000, 9);
+        let late = delayed_key("orders", 2_000, 1);
+        assert!(early < late);
+        assert_eq!(split_key(&early), Some(("orders".to_string(), 9)));
+        let prefix = topic_prefix(DELAYED, "orders");
+        assert_eq!(split_delayed(&late[prefix.len()..]), Some((2_000, 1)));
+    }
+
+    #[tokio::test]
+    async fn test_counts_pending_messages_on_open() {
+        let dir = TempDir::new().unwrap();
+        {
+            let queue = open(&dir, 3);
+            queue.publish("orders", json!({"id": 1})).await.unwrap();
+            queue.db.put(delayed_key("orders", 0, 7), b"{}").unwrap();
+            queue.db.put(key(DEAD, "orders", 8), b"{}").unwrap();
+        }
+
+        let queue = open(&dir, 3);
+        assert_eq!(queue.queue_depth("orders").await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_handles_messages_in_parallel() {
+        let dir = TempDir::new().unwrap();
+        let queue = open(&dir, 3);
+        for id in 0..3 {
+            queue.publish("orders", json!({"id": id})).await.unwrap();
+        }
+
+        // Every handler waits for the other two, so this only drains when
+        // all three messages are handled at once.
+        let barrier = Arc::new(Barrier::new(3));
+        queue
+            .subscribe_fn_with_prefetch(
+                "orders",
+                move |_| {
+                    let barrier = barrier.clone();
+                    async move {
+                        barrier.wait().await;
+                        Ok(())
+                    }
+                },
+                3,
+            )
+            .await
+            .unwrap();
+
+        wait_for_depth(&queue, "orders", 0).await;
+    }
+
+    #[tokio::test]
+    async fn test_hands_over_undecodable_records() {
+        let dir = TempDir::new().unwrap();
+        open(&dir, 3).db.put(key(READY, "orders", u64::MAX - 1), b"not json").unwrap();
+        let queue = open(&dir, 3);
+
+        let (sink, mut received) = mpsc::unbounded_channel();
+        queue
+            .subscribe_fn("orders", move |message| {
+                let _ = sink.send(message);
+                async { Ok(()) }
+            })
+            .await
+            .unwrap();
+
+        let message = next(&mut received).await;
+        assert_eq!(message.payload, json!("not json"));
+        assert!(message.metadata.contains_key(DECODE_ERROR));
+        wait_for_depth(&queue, "orders", 0).await;
+    }
+
+    #[test]
+    fn test_retry_delay_backs_off() {
+        let base = Duration::from_secs(1);
+        assert_eq!(retry_delay(base, 1), Duration::from_secs(1));
+        assert_eq!(retry_delay(base, 3), Duration::from_secs(4));
+        assert_eq!(retry_delay(base, 40), MAX_RETRY_DELAY);
+    }
+}
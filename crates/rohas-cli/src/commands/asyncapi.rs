@@ -0,0 +1,41 @@
+use anyhow::Result;
+use rohas_engine::EngineConfig;
+use rohas_parser::{env::active_environment, Parser};
+use std::path::PathBuf;
+use tracing::info;
+
+use crate::utils::file_util::{find_config_file, parse_directory};
+
+/// Writes the AsyncAPI document the engine serves at `/asyncapi.json`.
+pub async fn execute(schema_path: PathBuf, output: PathBuf) -> Result<()> {
+    let schema = if schema_path.is_file() {
+        Parser::parse_file_for_environment(&schema_path, &active_environment())?
+    } else if schema_path.is_dir() {
+        parse_directory(&schema_path)?
+    } else {
+        anyhow::bail!("Schema path not found: {}", schema_path.display());
+    };
+
+    let config = match find_config_file(&schema_path) {
+        Some(config_path) => {
+            let mut config = EngineConfig::from_file(&config_path)?;
+            config.project_root = config_path
+                .parent()
+                .and_then(|p| p.parent())
+                .map(|p| p.to_path_buf())
+                .unwrap_or_default();
+            config
+        }
+        None => EngineConfig::default(),
+    };
+
+    let document = rohas_engine::asyncapi::document(&schema, &config);
+    std::fs::write(&output, serde_json::to_string_pretty(&document)? + "\n")?;
+    info!(
+        "Wrote AsyncAPI document for {} events and {} websockets to {}",
+        schema.events.len(),
+        schema.websockets.len(),
+        output.display()
+    );
+    Ok(())
+}
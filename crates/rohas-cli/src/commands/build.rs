@@ -0,0 +1,277 @@
+use anyhow::{Context, Result};
+use rohas_engine::config::Language;
+use rohas_engine::EngineConfig;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::process::Command;
+use tracing::{info, warn};
+
+use crate::utils::file_util::find_config_file;
+
+const ENGINE_GIT: &str = "https://github.com/rohas-dev/rohas.git";
+
+/// Builds deployment artifacts for a target: the engine binary, the Rust
+/// handler library and the project files the engine reads at startup.
+///
+/// Output layout, per target:
+/// - `bin/rohas`
+/// - `app/` with `schema/`, `config/` and either `src/` or `Cargo.toml` plus
+///   `target/release/lib<name>.so`
+/// - with `lambda`, a `bootstrap` that runs `rohas lambda` as a custom runtime
+pub async fn execute(
+    schema_path: PathBuf,
+    target: Option<String>,
+    output: PathBuf,
+    engine_path: Option<PathBuf>,
+    lambda: bool,
+) -> Result<()> {
+    let config_path = find_config_file(&schema_path)
+        .context("config/rohas.toml not found; run `rohas build` inside a project")?;
+    let config = EngineConfig::from_file(&config_path)?;
+    let project_root = config_path
+        .parent()
+        .and_then(|p| p.parent())
+        .map(|p| p.to_path_buf())
+        .context("Failed to resolve the project root")?;
+
+    let target = target.as_deref();
+    let musl = target.is_some_and(|t| t.contains("-musl"));
+    let features = engine_features(&config.language, musl)?;
+
+    let dist = output.join(target.unwrap_or("host"));
+    let app_dir = dist.join("app");
+    if app_dir.exists() {
+        std::fs::remove_dir_all(&app_dir)?;
+    }
+    std::fs::create_dir_all(&app_dir)?;
+
+    if musl && !features.contains(&"rocksdb") {
+        warn!("RocksDB is not built for musl targets; telemetry will be kept in memory");
+    }
+
+    if config.language == Language::Rust {
+        let library = build_handler_library(&project_root, target, musl).await?;
+        let release_dir = app_dir.join("target").join("release");
+        std::fs::create_dir_all(&release_dir)?;
+        std::fs::copy(&library, release_dir.join(library.file_name().unwrap_or_default()))?;
+        std::fs::copy(project_root.join("Cargo.toml"), app_dir.join("Cargo.toml"))?;
+    }
+
+    build_engine(&dist, target, musl, &features, engine_path.as_deref()).await?;
+
+    copy_project_files(&project_root, &app_dir, &config.language)?;
+
+    if lambda {
+        write_bootstrap(&dist)?;
+    }
+
+    info!("Artifacts written to {}", dist.display());
+    if lambda {
+        info!(
+            "Deploy the contents of {} as a zip to the provided.al2023 Lambda runtime",
+            dist.display()
+        );
+    } else {
+        info!(
+            "Run with: cd {} && ../bin/rohas dev --watch false",
+            app_dir.display()
+        );
+    }
+    if musl && config.language == Language::Rust {
+        info!("The engine loads the handler library through the musl loader; use an Alpine base image, or copy /lib/ld-musl-*.so.1 into scratch images");
+    }
+
+    Ok(())
+}
+
+/// Engine features for a project language. Python and TypeScript handlers
+/// embed libpython and V8, neither of which links against musl.
+fn engine_features(language: &Language, musl: bool) -> Result<Vec<&'static str>> {
+    let mut features = Vec::new();
+    match language {
+        Language::Python if musl => anyhow::bail!(
+            "Python handlers embed libpython, which cannot be linked into a musl build; \
+             build for a -gnu target and use a glibc base image such as debian:slim"
+        ),
+        Language::TypeScript if musl => anyhow::bail!(
+            "TypeScript handlers run on V8, which has no musl builds; \
+             build for a -gnu target and use a glibc base image such as debian:slim"
+        ),
+        Language::Python => features.push("python"),
+        Language::TypeScript => features.push("node"),
+        Language::Rust => {}
+    }
+    if !musl {
+        features.push("rocksdb");
+    }
+    Ok(features)
+}
+
+/// RUSTFLAGS for a build. On musl the engine and handler library link the C
+/// library dynamically, since a fully static binary cannot load a dylib.
+fn rustflags(musl: bool) -> String {
+    let existing = std::env::var("RUSTFLAGS").unwrap_or_default();
+    if musl {
+        format!("{} -C target-feature=-crt-static", existing).trim().to_string()
+    } else {
+        existing
+    }
+}
+
+async fn build_handler_library(project_root: &Path, target: Option<&str>, musl: bool) -> Result<PathBuf> {
+    info!("Building Rust handler library...");
+
+    let mut cmd = Command::new("cargo");
+    cmd.args(["build", "--release", "--lib"]);
+    if let Some(target) = target {
+        cmd.args(["--target", target]);
+    }
+    let status = cmd
+        .env("RUSTFLAGS", rustflags(musl))
+        .current_dir(project_root)
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .status()
+        .await
+        .context("Failed to run cargo")?;
+    if !status.success() {
+        anyhow::bail!("Rust handler library build failed");
+    }
+
+    let mut release_dir = project_root.join("target");
+    if let Some(target) = target {
+        release_dir = release_dir.join(target);
+    }
+    let library = release_dir
+        .join("release")
+        .join(library_file_name(&lib_name(project_root)?, target));
+    if !library.exists() {
+        anyhow::bail!("Handler library not found at {}", library.display());
+    }
+    Ok(library)
+}
+
+/// `[lib] name` of the project crate, falling back to its package name.
+fn lib_name(project_root: &Path) -> Result<String> {
+    let content = std::fs::read_to_string(project_root.join("Cargo.toml"))
+        .context("Failed to read Cargo.toml")?;
+    let manifest: toml::Value = toml::from_str(&content)?;
+    let name = manifest
+        .get("lib")
+        .and_then(|lib| lib.get("name"))
+        .or_else(|| manifest.get("package").and_then(|p| p.get("name")))
+        .and_then(|name| name.as_str())
+        .context("Cargo.toml has no package name")?;
+    Ok(name.replace('-', "_"))
+}
+
+fn library_file_name(lib_name: &str, target: Option<&str>) -> String {
+    let os = target.unwrap_or(std::env::consts::OS);
+    if os.contains("apple") || os == "macos" {
+        format!("lib{}.dylib", lib_name)
+    } else if os.contains("windows") {
+        format!("{}.dll", lib_name)
+    } else {
+        format!("lib{}.so", lib_name)
+    }
+}
+
+async fn build_engine(
+    dist: &Path,
+    target: Option<&str>,
+    musl: bool,
+    features: &[&str],
+    engine_path: Option<&Path>,
+) -> Result<()> {
+    info!("Building engine with features [{}]...", features.join(", "));
+
+    let mut cmd = Command::new("cargo");
+    cmd.arg("install");
+    match engine_path {
+        Some(path) => {
+            cmd.arg("--path").arg(path.join("crates").join("rohas-cli"));
+        }
+        None => {
+            cmd.args(["--git", ENGINE_GIT, "rohas-cli"]);
+        }
+    }
+    cmd.arg("--locked")
+        .arg("--force")
+        .arg("--root")
+        .arg(dist)
+        .arg("--no-default-features");
+    if !features.is_empty() {
+        cmd.arg("--features").arg(features.join(","));
+    }
+    if let Some(target) = target {
+        cmd.args(["--target", target]);
+    }
+
+    let status = cmd
+        .env("RUSTFLAGS", rustflags(musl))
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .status()
+        .await
+        .context("Failed to run cargo")?;
+    if !status.success() {
+        anyhow::bail!("Engine build failed");
+    }
+
+    for metadata in [".crates.toml", ".crates2.json"] {
+        let _ = std::fs::remove_file(dist.join(metadata));
+    }
+    Ok(())
+}
+
+/// Entry point of a Lambda custom runtime. The package is read-only and the
+/// engine keeps its state under the project, so it runs from a copy in /tmp.
+fn write_bootstrap(dist: &Path) -> Result<()> {
+    let path = dist.join("bootstrap");
+    std::fs::write(
+        &path,
+        "#!/bin/sh\n\
+         rm -rf /tmp/app && cp -R \"$LAMBDA_TASK_ROOT/app\" /tmp/app\n\
+         cd /tmp/app && exec \"$LAMBDA_TASK_ROOT/bin/rohas\" lambda\n",
+    )?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(())
+}
+
+fn copy_project_files(project_root: &Path, app_dir: &Path, language: &Language) -> Result<()> {
+    let mut entries = vec!["schema", "config"];
+    match language {
+        Language::Python => entries.extend(["src", "pyproject.toml", "requirements.txt"]),
+        Language::TypeScript => entries.extend(["src", "package.json", "tsconfig.json"]),
+        Language::Rust => {}
+    }
+
+    for entry in entries {
+        let from = project_root.join(entry);
+        if from.is_dir() {
+            copy_dir(&from, &app_dir.join(entry))?;
+        } else if from.is_file() {
+            std::fs::copy(&from, app_dir.join(entry))?;
+        }
+    }
+    Ok(())
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let path = entry.path();
+        let dest = to.join(entry.file_name());
+        if path.is_dir() {
+            copy_dir(&path, &dest)?;
+        } else {
+            std::fs::copy(&path, &dest)?;
+        }
+    }
+    Ok(())
+}
@@ -1,8 +1,9 @@
 use anyhow::Result;
 use base64::{engine::general_purpose, Engine as _};
-use rohas_codegen::{generate, Language};
+use rohas_codegen::event_schema::{self, EventFormat};
+use rohas_codegen::{Generator, Language};
 use rohas_engine::config::{EngineConfig, Language as EngineLanguage};
-use rohas_parser::Parser;
+use rohas_parser::{env::active_environment, Parser};
 use std::fs;
 use std::path::{Path, PathBuf};
 use toml_edit::{value, Array, DocumentMut, Item, Table};
@@ -23,9 +24,17 @@ pub async fn execute(
     schema_path: PathBuf,
     output_path: PathBuf,
     lang: Option<String>,
+    events_format: Option<String>,
 ) -> Result<()> {
     info!("Generating code from schema: {}", schema_path.display());
 
+    let events_format = match events_format.as_deref() {
+        Some(name) => Some(EventFormat::from_name(name).ok_or_else(|| {
+            anyhow::anyhow!("Unsupported events format: {} (expected avro or proto)", name)
+        })?),
+        None => None,
+    };
+
     let current_dir = std::env::current_dir().unwrap_or_default();
     let config_path = find_config_file(&current_dir);
 
@@ -33,26 +42,29 @@ pub async fn execute(
         ensure_workbench_config(config_path)?;
     }
 
+    // Stubs written to the default layout of a project with another would
+    // have to be moved by hand, so an invalid config is an error.
+    let config = match &config_path {
+        Some(config_path) => Some(EngineConfig::from_file(config_path)?),
+        None => {
+            info!("Config file not found, using defaults");
+            None
+        }
+    };
+
     let language = match lang.as_deref() {
         Some("typescript") | Some("ts") => Language::TypeScript,
         Some("python") | Some("py") => Language::Python,
         Some("rust") | Some("rs") => Language::Rust,
-        None => match &config_path {
-            Some(config_path) => match EngineConfig::from_file(config_path) {
-                Ok(config) => {
-                    info!("Using language from config: {:?}", config.language);
-                    engine_language_to_codegen_language(config.language)
-                }
-                Err(e) => {
-                    info!(
-                        "Could not parse config file, defaulting to TypeScript: {}",
-                        e
-                    );
-                    Language::TypeScript
-                }
-            },
+        Some("kotlin") | Some("kt") => Language::Kotlin,
+        Some("swift") => Language::Swift,
+        None => match &config {
+            Some(config) => {
+                info!("Using language from config: {:?}", config.language);
+                engine_language_to_codegen_language(config.language.clone())
+            }
             None => {
-                info!("Config file not found, defaulting to TypeScript");
+                info!("Defaulting to TypeScript");
                 Language::TypeScript
             }
         },
@@ -62,7 +74,7 @@ pub async fn execute(
     };
 
     let schema = if schema_path.is_file() {
-        Parser::parse_file(&schema_path)?
+        Parser::parse_file_for_environment(&schema_path, &active_environment())?
     } else if schema_path.is_dir() {
         parse_directory(&schema_path)?
     } else {
@@ -76,8 +88,15 @@ pub async fn execute(
     info!("  - {} events", schema.events.len());
     info!("  - {} cron jobs", schema.crons.len());
 
-    // Generate code
-    generate(&schema, &output_path, language)?;
+    let layout = config.map(|config| config.codegen.layout()).unwrap_or_default();
+    Generator::new(language)
+        .with_layout(layout)
+        .generate(&schema, &output_path)?;
+
+    if let Some(format) = events_format {
+        event_schema::export(&schema, &output_path, format)?;
+        info!("  Event payload schemas: {}", output_path.join("schemas").display());
+    }
 
     info!("Code generation completed successfully!");
     info!("  Output directory: {}", output_path.display());
@@ -0,0 +1,67 @@
+use anyhow::{Context, Result};
+use rohas_engine::EngineConfig;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+use crate::utils::file_util::find_config_file;
+
+/// Keys whose values are replaced in `config show --resolved` output.
+const SECRET_KEYS: &[&str] = &["api_key", "password", "webhook_url", "headers"];
+
+pub async fn validate(config: Option<PathBuf>) -> Result<()> {
+    let config_path = resolve_config_path(config)?;
+    info!("Validating config: {}", config_path.display());
+
+    EngineConfig::from_file(&config_path)?;
+
+    info!("Config validation passed!");
+    Ok(())
+}
+
+/// Prints the config file, or with `resolved` the effective configuration
+/// with defaults filled in and secrets masked.
+pub async fn show(config: Option<PathBuf>, resolved: bool) -> Result<()> {
+    let config_path = resolve_config_path(config)?;
+
+    if !resolved {
+        let content = std::fs::read_to_string(&config_path)
+            .with_context(|| format!("Failed to read {}", config_path.display()))?;
+        print!("{}", content);
+        return Ok(());
+    }
+
+    let mut config = EngineConfig::from_file(&config_path)?;
+    if let Some(project_root) = config_path.parent().and_then(|p| p.parent()) {
+        config.project_root = project_root.to_path_buf();
+    }
+
+    let mut value = toml::Value::try_from(&config).context("Failed to render the resolved config")?;
+    mask_secrets(&mut value);
+    print!("{}", toml::to_string_pretty(&value)?);
+    Ok(())
+}
+
+fn resolve_config_path(config: Option<PathBuf>) -> Result<PathBuf> {
+    match config {
+        Some(path) if path.is_file() => Ok(path),
+        Some(path) => anyhow::bail!("Config file not found: {}", path.display()),
+        None => find_config_file(Path::new("."))
+            .context("config/rohas.toml not found; pass --config or run inside a project"),
+    }
+}
+
+fn mask_secrets(value: &mut toml::Value) {
+    match value {
+        toml::Value::Table(table) => {
+            for (key, value) in table.iter_mut() {
+                if SECRET_KEYS.contains(&key.as_str()) {
+                    *value = toml::Value::String("***".to_string());
+                } else {
+                    mask_secrets(value);
+                }
+            }
+        }
+        toml::Value::Array(values) => values.iter_mut().for_each(mask_secrets),
+        _ => {}
+    }
+}
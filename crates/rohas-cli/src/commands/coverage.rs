@@ -0,0 +1,305 @@
+use anyhow::Result;
+use rohas_codegen::{templates, Layout};
+use rohas_engine::config::{EngineConfig, Language};
+use rohas_parser::{env::active_environment, Parser, Schema};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::utils::file_util::{find_config_file, parse_directory};
+
+/// Marker left in every handler stub produced by `rohas codegen`.
+const STUB_MARKER: &str = "TODO: Implement";
+
+#[derive(Debug, PartialEq, Eq)]
+enum HandlerStatus {
+    Implemented,
+    Stub,
+    Missing,
+    Unregistered,
+}
+
+struct Expected {
+    declared_by: String,
+    handler: String,
+    status: HandlerStatus,
+}
+
+struct Category {
+    title: &'static str,
+    dir: PathBuf,
+    expected: Vec<Expected>,
+    orphans: Vec<PathBuf>,
+}
+
+pub async fn execute(schema_path: PathBuf) -> Result<()> {
+    let schema = if schema_path.is_file() {
+        Parser::parse_file_for_environment(&schema_path, &active_environment())?
+    } else if schema_path.is_dir() {
+        parse_directory(&schema_path)?
+    } else {
+        anyhow::bail!("Schema path not found: {}", schema_path.display());
+    };
+
+    let (project_root, language, layout) = resolve_project(&schema_path)?;
+    let src_dir = project_root.join("src");
+    let extension = match language {
+        Language::TypeScript => "ts",
+        Language::Python => "py",
+        Language::Rust => "rs",
+    };
+
+    let registered = match language {
+        Language::Rust => Some(registered_rust_handlers(&src_dir)),
+        _ => None,
+    };
+
+    let mut categories = vec![
+        Category {
+            title: "APIs",
+            dir: layout.handlers(&src_dir, "api"),
+            expected: schema
+                .apis
+                .iter()
+                .map(|api| (api.name.clone(), api.name.clone()))
+                .map(expected)
+                .collect(),
+            orphans: Vec::new(),
+        },
+        Category {
+            title: "Event handlers",
+            dir: layout.handlers(&src_dir, "events"),
+            expected: schema
+                .events
+                .iter()
+                .flat_map(|event| event.handlers.iter().map(|h| (event.name.clone(), h.clone())))
+                .map(expected)
+                .collect(),
+            orphans: Vec::new(),
+        },
+        Category {
+            title: "Cron jobs",
+            dir: layout.handlers(&src_dir, "cron"),
+            expected: schema
+                .crons
+                .iter()
+                .map(|cron| (cron.name.clone(), cron.name.clone()))
+                .map(expected)
+                .collect(),
+            orphans: Vec::new(),
+        },
+        Category {
+            title: "WebSocket handlers",
+            dir: layout.handlers(&src_dir, "websockets"),
+            expected: schema
+                .websockets
+                .iter()
+                .flat_map(|ws| {
+                    ws.on_connect
+                        .iter()
+                        .chain(&ws.on_message)
+                        .chain(&ws.on_disconnect)
+                        .map(|h| (ws.name.clone(), h.clone()))
+                })
+                .map(expected)
+                .collect(),
+            orphans: Vec::new(),
+        },
+        Category {
+            title: "Middlewares",
+            dir: layout.middlewares(&src_dir),
+            expected: middleware_declarations(&schema).into_iter().map(expected).collect(),
+            orphans: Vec::new(),
+        },
+    ];
+
+    for category in &mut categories {
+        let files = handler_files(&category.dir, extension);
+        let mut claimed = HashSet::new();
+
+        for item in &mut category.expected {
+            let candidates = [item.handler.clone(), templates::to_snake_case(&item.handler)];
+            let found = files.iter().find(|path| {
+                path.file_stem()
+                    .and_then(|s| s.to_str())
+                    .map(|stem| candidates.iter().any(|c| c == stem))
+                    .unwrap_or(false)
+            });
+
+            item.status = match found {
+                None => HandlerStatus::Missing,
+                Some(path) => {
+                    claimed.insert(path.clone());
+                    let content = fs::read_to_string(path).unwrap_or_default();
+                    if content.contains(STUB_MARKER) {
+                        HandlerStatus::Stub
+                    } else if registered
+                        .as_ref()
+                        .map(|names| !candidates.iter().any(|c| names.contains(c)))
+                        .unwrap_or(false)
+                    {
+                        HandlerStatus::Unregistered
+                    } else {
+                        HandlerStatus::Implemented
+                    }
+                }
+            };
+        }
+
+        category.orphans = files.into_iter().filter(|f| !claimed.contains(f)).collect();
+    }
+
+    print_report(&categories, &project_root);
+
+    Ok(())
+}
+
+fn expected((declared_by, handler): (String, String)) -> Expected {
+    Expected {
+        declared_by,
+        handler,
+        status: HandlerStatus::Missing,
+    }
+}
+
+fn middleware_declarations(schema: &Schema) -> Vec<(String, String)> {
+    let mut seen = HashSet::new();
+    schema
+        .apis
+        .iter()
+        .flat_map(|api| api.middlewares.iter().map(move |m| (api.name.clone(), m.clone())))
+        .chain(
+            schema
+                .websockets
+                .iter()
+                .flat_map(|ws| ws.middleware_handlers().map(move |m| (ws.name.clone(), m.clone()))),
+        )
+        .filter(|(_, m)| seen.insert(m.clone()))
+        .collect()
+}
+
+/// The project root, its language and where its handlers are.
+fn resolve_project(schema_path: &Path) -> Result<(PathBuf, Language, Layout)> {
+    match find_config_file(schema_path) {
+        Some(config_path) => {
+            let project_root = config_path
+                .parent()
+                .and_then(|p| p.parent())
+                .map(|p| p.to_path_buf())
+                .unwrap_or_default();
+            let (language, layout) = EngineConfig::from_file(&config_path)
+                .map(|c| (c.language, c.codegen.layout()))
+                .unwrap_or((Language::TypeScript, Layout::default()));
+            Ok((project_root, language, layout))
+        }
+        None => {
+            let schema_dir = if schema_path.is_file() {
+                schema_path.parent().unwrap_or(schema_path)
+            } else {
+                schema_path
+            };
+            let project_root = schema_dir
+                .parent()
+                .map(|p| p.to_path_buf())
+                .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+            Ok((project_root, Language::TypeScript, Layout::default()))
+        }
+    }
+}
+
+fn handler_files(dir: &Path, extension: &str) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_file())
+        .filter(|p| p.extension().and_then(|e| e.to_str()) == Some(extension))
+        .filter(|p| {
+            let stem = p.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+            !matches!(stem, "mod" | "index" | "__init__")
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// Handler names registered in the generated Rust `handlers.rs`.
+fn registered_rust_handlers(src_dir: &Path) -> HashSet<String> {
+    let content = fs::read_to_string(src_dir.join("generated/handlers.rs")).unwrap_or_default();
+    let mut names = HashSet::new();
+    let mut lines = content.lines();
+
+    while let Some(line) = lines.next() {
+        if !line.contains("register_handler(") {
+            continue;
+        }
+        if let Some(name) = lines
+            .next()
+            .and_then(|next| next.trim().strip_prefix('"'))
+            .and_then(|rest| rest.split('"').next())
+        {
+            names.insert(name.to_string());
+        }
+    }
+
+    names
+}
+
+fn print_report(categories: &[Category], project_root: &Path) {
+    let mut total = 0;
+    let mut implemented_total = 0;
+
+    for category in categories {
+        let implemented = category
+            .expected
+            .iter()
+            .filter(|e| e.status == HandlerStatus::Implemented)
+            .count();
+        total += category.expected.len();
+        implemented_total += implemented;
+
+        println!(
+            "{}: {}/{} implemented ({})",
+            category.title,
+            implemented,
+            category.expected.len(),
+            percentage(implemented, category.expected.len())
+        );
+
+        for item in &category.expected {
+            let label = match item.status {
+                HandlerStatus::Implemented => continue,
+                HandlerStatus::Stub => "stub",
+                HandlerStatus::Missing => "missing",
+                HandlerStatus::Unregistered => "not registered",
+            };
+            if item.declared_by == item.handler {
+                println!("  - {} [{}]", item.handler, label);
+            } else {
+                println!("  - {} (from {}) [{}]", item.handler, item.declared_by, label);
+            }
+        }
+
+        for orphan in &category.orphans {
+            let display = orphan.strip_prefix(project_root).unwrap_or(orphan);
+            println!("  - {} [orphaned]", display.display());
+        }
+    }
+
+    println!(
+        "\nTotal: {}/{} handlers implemented ({})",
+        implemented_total,
+        total,
+        percentage(implemented_total, total)
+    );
+}
+
+fn percentage(part: usize, total: usize) -> String {
+    if total == 0 {
+        return "n/a".to_string();
+    }
+    format!("{:.1}%", part as f64 * 100.0 / total as f64)
+}
@@ -0,0 +1,36 @@
+use anyhow::Result;
+use rohas_parser::{env::active_environment, Parser};
+use std::path::PathBuf;
+use tracing::info;
+
+use crate::utils::file_util::{find_config_file, parse_directory};
+
+/// Builds the static documentation site for the schema into `output`.
+pub async fn build(schema_path: PathBuf, output: PathBuf, title: Option<String>) -> Result<()> {
+    let schema = if schema_path.is_file() {
+        Parser::parse_file_for_environment(&schema_path, &active_environment())?
+    } else if schema_path.is_dir() {
+        parse_directory(&schema_path)?
+    } else {
+        anyhow::bail!("Schema path not found: {}", schema_path.display());
+    };
+
+    // Defaults to the project directory, next to `config/rohas.toml`.
+    let title = title
+        .or_else(|| {
+            let config_path = find_config_file(&schema_path)?;
+            let project_root = config_path.parent()?.parent()?.canonicalize().ok()?;
+            Some(project_root.file_name()?.to_string_lossy().into_owned())
+        })
+        .unwrap_or_else(|| "rohas".to_string());
+
+    rohas_codegen::docs::build(&schema, &output, &title)?;
+    info!(
+        "Wrote documentation for {} models, {} APIs and {} events to {}",
+        schema.models.len() + schema.types.len() + schema.inputs.len(),
+        schema.apis.len(),
+        schema.events.len(),
+        output.display()
+    );
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use anyhow::Result;
+use rohas_codegen::graph::{Graph, GraphFormat};
+use rohas_engine::log_level::{set_log_level, LogScope};
+use rohas_parser::{env::active_environment, Parser};
+use std::path::PathBuf;
+use tracing::info;
+
+use crate::utils::file_util::parse_directory;
+
+/// Prints the schema graph as a text diagram, or writes it to `output`.
+pub async fn export(schema_path: PathBuf, format: String, output: Option<PathBuf>) -> Result<()> {
+    let format = GraphFormat::from_name(&format)
+        .ok_or_else(|| anyhow::anyhow!("Unknown graph format '{}': use mermaid, d2 or dot", format))?;
+
+    // Keep schema parsing logs out of a diagram printed to stdout.
+    if output.is_none() {
+        for target in ["rohas_cli", "rohas_parser"] {
+            let _ = set_log_level(LogScope::Target(target.to_string()), Some("warn"));
+        }
+    }
+
+    let schema = if schema_path.is_file() {
+        Parser::parse_file_for_environment(&schema_path, &active_environment())?
+    } else if schema_path.is_dir() {
+        parse_directory(&schema_path)?
+    } else {
+        anyhow::bail!("Schema path not found: {}", schema_path.display());
+    };
+
+    let graph = Graph::from_schema(&schema);
+    let diagram = graph.render(format);
+    match output {
+        Some(output) => {
+            std::fs::write(&output, diagram)?;
+            info!(
+                "Wrote graph of {} nodes and {} edges to {}",
+                graph.nodes.len(),
+                graph.edges.len(),
+                output.display()
+            );
+        }
+        None => print!("{}", diagram),
+    }
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use anyhow::Result;
+use rohas_codegen::impact::{DependencyIndex, Impact};
+use rohas_engine::config::EngineConfig;
+use rohas_parser::{env::active_environment, Parser};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use crate::utils::file_util::{find_config_file, parse_directory};
+
+/// Reports what changing `target`, a file or a model, type or input name,
+/// affects.
+pub async fn execute(target: String, schema_path: PathBuf) -> Result<()> {
+    let schema = if schema_path.is_file() {
+        Parser::parse_file_for_environment(&schema_path, &active_environment())?
+    } else if schema_path.is_dir() {
+        parse_directory(&schema_path)?
+    } else {
+        anyhow::bail!("Schema path not found: {}", schema_path.display());
+    };
+
+    let project_root = project_root(&schema_path)?;
+    let layout = find_config_file(&schema_path)
+        .and_then(|config_path| EngineConfig::from_file(config_path).ok())
+        .map(|config| config.codegen.layout())
+        .unwrap_or_default();
+    let index = DependencyIndex::build(&schema, &project_root, &layout)?;
+
+    let target_path = Path::new(&target);
+    let impact = if target_path.exists() {
+        index.impact_of_file(&project_root, &target_path.canonicalize()?)?
+    } else if index.is_record(&target) {
+        index.impact_of_record(&target)
+    } else {
+        anyhow::bail!("'{}' is neither a file nor a model, type or input", target);
+    };
+
+    print_impact(&target, &impact);
+    Ok(())
+}
+
+fn project_root(schema_path: &Path) -> Result<PathBuf> {
+    let root = match find_config_file(schema_path) {
+        Some(config_path) => config_path
+            .parent()
+            .and_then(|p| p.parent())
+            .map(|p| p.to_path_buf())
+            .unwrap_or_default(),
+        None => {
+            let schema_dir = if schema_path.is_file() {
+                schema_path.parent().unwrap_or(schema_path)
+            } else {
+                schema_path
+            };
+            schema_dir.canonicalize()?.parent().map(|p| p.to_path_buf()).unwrap_or_default()
+        }
+    };
+    Ok(root.canonicalize()?)
+}
+
+fn print_impact(target: &str, impact: &Impact) {
+    if impact.is_empty() {
+        println!("Nothing depends on {}", target);
+        return;
+    }
+
+    println!("Changing {} affects:", target);
+    let rows = [
+        ("Models", &impact.records),
+        ("APIs", &impact.apis),
+        ("Events", &impact.events),
+        ("Crons", &impact.crons),
+        ("WebSockets", &impact.websockets),
+        ("Handlers", &impact.handlers),
+    ];
+    for (label, names) in rows {
+        if !names.is_empty() {
+            println!("  {:<11} {}", format!("{}:", label), join(names));
+        }
+    }
+    if !impact.files.is_empty() {
+        println!("  Files:");
+        for file in &impact.files {
+            println!("    - {}", file.display());
+        }
+    }
+}
+
+fn join(names: &BTreeSet<String>) -> String {
+    names.iter().cloned().collect::<Vec<_>>().join(", ")
+}
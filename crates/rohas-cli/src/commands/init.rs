@@ -65,43 +65,7 @@ pub async fn execute(name: String, lang: String, _example: Option<String>) -> Re
 
     // Create rohas.toml
     let workbench_api_key = generate_workbench_api_key();
-    let config = format!(
-        r#"[project]
-name = "{}"
-version = "0.1.0"
-language = "{}"
-
-[server]
-host = "127.0.0.1"
-port = 3000
-enable_cors = true
-
-[adapter]
-type = "memory"
-buffer_size = 1000
-
-[telemetry]
-# Telemetry adapter type: rocksdb (default), prometheus, influxdb, timescaledb
-type = "rocksdb"
-# Path to telemetry storage (relative to project root or absolute)
-path = ".rohas/telemetry"
-# Retention period for traces in days (0 = keep forever)
-retention_days = 30
-# Maximum number of traces to keep in memory cache
-max_cache_size = 1000
-# Enable metrics collection
-enable_metrics = true
-# Enable logs collection
-enable_logs = true
-# Enable traces collection
-enable_traces = true
-
-[workbench]
-api_key = "{}"
-allowed_origins = []
-"#,
-        name, lang, workbench_api_key
-    );
+    let config = config_template(&name, &lang, &workbench_api_key);
 
     fs::write(project_dir.join("config/rohas.toml"), config)?;
 
@@ -153,6 +117,214 @@ Rohas project initialized with {} handlers.
     Ok(())
 }
 
+/// The `config/rohas.toml` of a new project, with the optional sections
+/// commented out.
+fn config_template(name: &str, lang: &str, workbench_api_key: &str) -> String {
+    format!(
+        r#"[project]
+name = "{}"
+version = "0.1.0"
+language = "{}"
+
+[server]
+host = "127.0.0.1"
+port = 3000
+enable_cors = true
+# Fail on startup check errors (missing handlers, duplicate routes) instead of warning
+# strict_startup = true
+# API error bodies: problem (application/problem+json, default) or legacy ({{"error": "..."}})
+# error_format = "legacy"
+# Base URL of problem type URIs, e.g. https://example.com/problems/not-found
+# problem_type_base = "https://example.com/problems"
+
+# WebSocket keep-alive and resumption (0 disables each)
+# [server.websocket]
+# heartbeat_interval_secs = 30
+# idle_timeout_secs = 90
+# resume_window_secs = 60
+# resume_buffer_size = 100
+
+[adapter]
+# Adapter type: memory (default), embedded (durable queue on disk), aws
+type = "memory"
+buffer_size = 1000
+# Embedded queue: where messages are kept, and how often a failed handler is
+# retried (with exponential backoff) before its message is dead-lettered
+# path = ".rohas/queue"
+# max_retries = 3
+# retry_delay_ms = 1000
+
+# Wire encoding for queued messages (SQS): json (default), msgpack, cbor
+# codec = "msgpack"
+
+# Claim check: store event payloads above the threshold in a blob store and
+# send a reference instead (SQS and EventBridge cap messages at 256 KiB)
+# [adapter.claim_check]
+# threshold_bytes = 204800
+# store = "local"          # local, s3
+# path = ".rohas/blobs"
+# bucket = "my-bucket"     # s3 only
+# delete_after_read = false
+
+[telemetry]
+# Telemetry adapter type: rocksdb (default), prometheus, influxdb, timescaledb
+type = "rocksdb"
+# Path to telemetry storage (relative to project root or absolute)
+path = ".rohas/telemetry"
+# Retention period for traces in days (0 = keep forever)
+retention_days = 30
+# Maximum number of traces to keep in memory cache
+max_cache_size = 1000
+# Enable metrics collection
+enable_metrics = true
+# Enable logs collection
+enable_logs = true
+# Enable traces collection
+enable_traces = true
+
+# Trace sampling: keep a share of traces, plus every failed or slow one
+# [telemetry.sampling]
+# rate = 1.0
+# keep_errors = true
+# slow_threshold_ms = 1000
+# routes = {{ "GET /health" = 0.0 }}
+
+# Log retention and export (view live logs with `rohas logs tail --follow`)
+# [telemetry.logs]
+# max_entries = 1000
+# max_age_seconds = 3600
+# export_level = "info"
+# file = {{ path = ".rohas/logs/rohas.ndjson", max_size_mb = 10, rotation = "daily", max_files = 5 }}
+# syslog = {{ address = "127.0.0.1:514" }}
+# http = {{ url = "https://logs.example.com/ingest", batch_size = 100 }}
+
+[workbench]
+api_key = "{}"
+allowed_origins = []
+
+# Alerting: rules over telemetry that notify Slack, webhooks or email
+# [alerting]
+# check_interval_seconds = 30
+#
+# [[alerting.channels]]
+# name = "ops"
+# type = "slack"          # slack, webhook, email
+# webhook_url = "https://hooks.slack.com/services/..."
+#
+# [[alerting.rules]]
+# name = "high-error-rate"
+# type = "error_rate"     # error_rate, cron_failure
+# threshold = 0.05
+# window_seconds = 300
+# cooldown_seconds = 900
+# channels = ["ops"]
+
+# Chaos mode: inject faults to exercise retries and error handling (dev/test only).
+# Rates are probabilities between 0.0 and 1.0; tune them at runtime via
+# PUT /api/workbench/chaos.
+# [chaos]
+# enabled = true
+# latency_rate = 0.1
+# latency_min_ms = 50
+# latency_max_ms = 500
+# handler_error_rate = 0.05
+# publish_failure_rate = 0.05
+# drop_delivery_rate = 0.0
+# targets = []           # handler/event names; empty = all
+
+# Handler calls scheduled from code with state.schedule(handler, when, payload),
+# and the last run times cron jobs use to catch up after downtime (misfire: run_once)
+# [scheduler]
+# store_path = ".rohas/jobs.json"
+# run_times_path = ".rohas/cron-runs.json"
+
+# Poison messages: events that fail to decode, or that a handler fails on
+# max_attempts times in a row, are kept here for the workbench's Quarantine page
+# [quarantine]
+# max_attempts = 3
+# store_path = ".rohas/quarantine.json"
+# max_entries = 1000
+
+# Handler inputs and outputs recorded per trace, re-run locally with
+# `rohas replay <trace-id>`
+# [recording]
+# enabled = true
+# failed_only = false
+# path = ".rohas/recordings"
+# max_recordings = 100
+
+# Python environment handlers import packages from; by default a .venv or
+# venv directory, or poetry's environment. `rohas dev` installs
+# requirements.txt or poetry's dependencies into it on startup
+# [python]
+# interpreter = ".venv/bin/python"
+# install_requirements = true
+
+# Where codegen writes handler stubs; the directories must be inside src.
+# file_naming = "snake_case" names every handler file in snake_case, and
+# handler_stubs = false only generates the types
+# [codegen]
+# handlers_dir = "src/handlers"
+# middlewares_dir = "src/middlewares"
+# file_naming = "schema"
+# handler_stubs = true
+
+# Rust hot reload in `rohas dev`: "debug" builds incrementally and reloads in
+# seconds instead of a clean release build; sccache caches dependency builds
+# [dev]
+# rust_profile = "debug"   # "release" (default) or "debug"
+# sccache = true
+
+# Deployment written by `rohas k8s generate`; probes use /healthz and /readyz
+# [kubernetes]
+# image = "registry.example.com/my-app:0.1.0"   # default: <name>:<version>
+# replicas = 2                                  # used when autoscaling is off
+# env_secret = "my-app-secrets"                 # Secret added to the environment
+# [kubernetes.env]
+# AWS_REGION = "us-east-1"
+# [kubernetes.resources]
+# cpu_request = "100m"
+# memory_request = "128Mi"
+# memory_limit = "512Mi"
+# [kubernetes.autoscaling]
+# enabled = true
+# min_replicas = 1
+# max_replicas = 5
+# target_cpu_utilization = 80
+"#,
+        name, lang, workbench_api_key
+    )
+}
+
 fn generate_workbench_api_key() -> String {
     general_purpose::STANDARD.encode(Uuid::new_v4().into_bytes())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rohas_engine::config::EngineConfig;
+
+    #[test]
+    fn test_config_template_parses() {
+        for lang in ["typescript", "python", "rust"] {
+            let config = config_template("my-app", lang, &generate_workbench_api_key());
+            EngineConfig::from_toml_str(&config).unwrap_or_else(|e| panic!("{}: {}", lang, e));
+        }
+    }
+
+    /// The commented-out sections show real settings, so they must parse
+    /// once uncommented.
+    #[test]
+    fn test_config_template_examples_parse() {
+        let config: String = config_template("my-app", "typescript", "key")
+            .lines()
+            .map(|line| match line.strip_prefix("# ") {
+                Some(setting) if setting.starts_with('[') || toml::from_str::<toml::Table>(setting).is_ok() => setting,
+                _ => line,
+            })
+            .flat_map(|line| [line, "\n"])
+            .collect();
+        EngineConfig::from_toml_str(&config).unwrap_or_else(|e| panic!("{}\n{}", e, config));
+    }
+}
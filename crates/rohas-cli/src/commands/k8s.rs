@@ -0,0 +1,642 @@
+use anyhow::{Context, Result};
+use rohas_engine::config::{AdapterType, KubernetesConfig, KubernetesResources};
+use rohas_engine::EngineConfig;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use toml_edit::{value, DocumentMut};
+use tracing::{info, warn};
+
+use crate::utils::file_util::find_config_file;
+
+/// Where the image holds the `rohas build` output of its target.
+const IMAGE_ROOT: &str = "/rohas";
+
+/// Helm templates; everything project-specific is in the generated values.
+const HELM_SECRET: &str = r#"apiVersion: v1
+kind: Secret
+metadata:
+  name: {{ .Release.Name }}-config
+  labels:
+    app.kubernetes.io/name: {{ .Chart.Name }}
+    app.kubernetes.io/instance: {{ .Release.Name }}
+type: Opaque
+stringData:
+  rohas.toml: |
+    {{- .Values.config | nindent 4 }}
+"#;
+
+const HELM_DEPLOYMENT: &str = r#"apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: {{ .Release.Name }}
+  labels:
+    app.kubernetes.io/name: {{ .Chart.Name }}
+    app.kubernetes.io/instance: {{ .Release.Name }}
+spec:
+  {{- if not .Values.autoscaling.enabled }}
+  replicas: {{ .Values.replicaCount }}
+  {{- end }}
+  selector:
+    matchLabels:
+      app.kubernetes.io/name: {{ .Chart.Name }}
+      app.kubernetes.io/instance: {{ .Release.Name }}
+  template:
+    metadata:
+      labels:
+        app.kubernetes.io/name: {{ .Chart.Name }}
+        app.kubernetes.io/instance: {{ .Release.Name }}
+      annotations:
+        checksum/config: {{ .Values.config | sha256sum }}
+    spec:
+      containers:
+        - name: rohas
+          image: {{ .Values.image | quote }}
+          workingDir: /rohas/app
+          command: ["/rohas/bin/rohas", "dev", "--watch", "false"]
+          ports:
+            - name: http
+              containerPort: {{ .Values.port }}
+          {{- with .Values.env }}
+          env:
+            {{- range $name, $value := . }}
+            - name: {{ $name }}
+              value: {{ $value | quote }}
+            {{- end }}
+          {{- end }}
+          {{- with .Values.envSecret }}
+          envFrom:
+            - secretRef:
+                name: {{ . }}
+          {{- end }}
+          {{- with .Values.resources }}
+          resources:
+            {{- toYaml . | nindent 12 }}
+          {{- end }}
+          startupProbe:
+            httpGet:
+              path: /healthz
+              port: http
+            periodSeconds: 2
+            failureThreshold: 30
+          livenessProbe:
+            httpGet:
+              path: /healthz
+              port: http
+            periodSeconds: 10
+          readinessProbe:
+            httpGet:
+              path: /readyz
+              port: http
+            periodSeconds: 5
+          volumeMounts:
+            - name: config
+              mountPath: /rohas/app/config/rohas.toml
+              subPath: rohas.toml
+      volumes:
+        - name: config
+          secret:
+            secretName: {{ .Release.Name }}-config
+"#;
+
+const HELM_SERVICE: &str = r#"apiVersion: v1
+kind: Service
+metadata:
+  name: {{ .Release.Name }}
+  labels:
+    app.kubernetes.io/name: {{ .Chart.Name }}
+    app.kubernetes.io/instance: {{ .Release.Name }}
+spec:
+  type: {{ .Values.service.type }}
+  selector:
+    app.kubernetes.io/name: {{ .Chart.Name }}
+    app.kubernetes.io/instance: {{ .Release.Name }}
+  ports:
+    - name: http
+      port: {{ .Values.service.port }}
+      targetPort: http
+"#;
+
+const HELM_HPA: &str = r#"{{- if .Values.autoscaling.enabled }}
+apiVersion: autoscaling/v2
+kind: HorizontalPodAutoscaler
+metadata:
+  name: {{ .Release.Name }}
+  labels:
+    app.kubernetes.io/name: {{ .Chart.Name }}
+    app.kubernetes.io/instance: {{ .Release.Name }}
+spec:
+  scaleTargetRef:
+    apiVersion: apps/v1
+    kind: Deployment
+    name: {{ .Release.Name }}
+  minReplicas: {{ .Values.autoscaling.minReplicas }}
+  maxReplicas: {{ .Values.autoscaling.maxReplicas }}
+  metrics:
+    - type: Resource
+      resource:
+        name: cpu
+        target:
+          type: Utilization
+          averageUtilization: {{ .Values.autoscaling.targetCPUUtilizationPercentage }}
+{{- end }}
+"#;
+
+/// The project as deployed: names, image and the config the pods load.
+struct App {
+    name: String,
+    version: String,
+    image: String,
+    port: u16,
+    kubernetes: KubernetesConfig,
+    /// rohas.toml with the server listening on all interfaces, as a pod
+    /// must to be reached through its Service.
+    rohas_toml: String,
+}
+
+impl App {
+    fn load(config_path: &Path) -> Result<Self> {
+        let config = EngineConfig::from_file(config_path)?;
+        let content = std::fs::read_to_string(config_path)
+            .with_context(|| format!("Failed to read {}", config_path.display()))?;
+        let mut document: DocumentMut = content
+            .parse()
+            .with_context(|| format!("Invalid {}", config_path.display()))?;
+
+        let project = |key: &str| document["project"][key].as_str().unwrap_or_default().to_string();
+        let name = resource_name(&project("name"));
+        let version = project("version");
+        if name.is_empty() {
+            anyhow::bail!("project.name in {} has no letters or digits to name resources with", config_path.display());
+        }
+
+        let pods = if config.kubernetes.autoscaling.enabled {
+            config.kubernetes.autoscaling.max_replicas
+        } else {
+            config.kubernetes.replicas
+        };
+        if pods > 1 && matches!(config.adapter.adapter_type, AdapterType::Memory | AdapterType::Embedded { .. }) {
+            warn!("The memory and embedded adapters keep events inside each pod; use a broker adapter to share them across replicas");
+        }
+        if config.kubernetes.autoscaling.enabled && config.kubernetes.resources.cpu_request.is_none() {
+            warn!("kubernetes.resources.cpu_request is unset; the autoscaler needs it to measure CPU utilization");
+        }
+
+        document["server"]["host"] = value("0.0.0.0");
+
+        Ok(Self {
+            image: config
+                .kubernetes
+                .image
+                .clone()
+                .unwrap_or_else(|| format!("{}:{}", name, if version.is_empty() { "latest" } else { &version })),
+            name,
+            version,
+            port: config.server.port,
+            kubernetes: config.kubernetes,
+            rohas_toml: document.to_string(),
+        })
+    }
+
+    fn metadata(&self, name: &str) -> String {
+        let mut metadata = format!("metadata:\n  name: {}\n", name);
+        if let Some(namespace) = &self.kubernetes.namespace {
+            let _ = writeln!(metadata, "  namespace: {}", namespace);
+        }
+        let _ = write!(metadata, "  labels:\n    app.kubernetes.io/name: {}\n", self.name);
+        metadata
+    }
+
+    fn secret(&self) -> String {
+        format!(
+            "apiVersion: v1\nkind: Secret\n{}type: Opaque\nstringData:\n  rohas.toml: |\n{}",
+            self.metadata(&format!("{}-config", self.name)),
+            indent(&self.rohas_toml, 4)
+        )
+    }
+
+    fn deployment(&self) -> String {
+        let mut yaml = format!("apiVersion: apps/v1\nkind: Deployment\n{}spec:\n", self.metadata(&self.name));
+        if !self.kubernetes.autoscaling.enabled {
+            let _ = writeln!(yaml, "  replicas: {}", self.kubernetes.replicas);
+        }
+        let _ = write!(
+            yaml,
+            "  selector:
+    matchLabels:
+      app.kubernetes.io/name: {name}
+  template:
+    metadata:
+      labels:
+        app.kubernetes.io/name: {name}
+    spec:
+      containers:
+        - name: rohas
+          image: {image}
+          workingDir: {root}/app
+          command: [\"{root}/bin/rohas\", \"dev\", \"--watch\", \"false\"]
+          ports:
+            - name: http
+              containerPort: {port}
+",
+            name = self.name,
+            image = quote(&self.image),
+            root = IMAGE_ROOT,
+            port = self.port,
+        );
+        if !self.kubernetes.env.is_empty() {
+            yaml.push_str("          env:\n");
+            for (name, value) in &self.kubernetes.env {
+                let _ = write!(yaml, "            - name: {}\n              value: {}\n", name, quote(value));
+            }
+        }
+        if let Some(secret) = &self.kubernetes.env_secret {
+            let _ = write!(yaml, "          envFrom:\n            - secretRef:\n                name: {}\n", secret);
+        }
+        if let Some(resources) = resources(&self.kubernetes.resources) {
+            let _ = write!(yaml, "          resources:\n{}", indent(&resources, 12));
+        }
+        let _ = write!(
+            yaml,
+            "          startupProbe:
+            httpGet:
+              path: /healthz
+              port: http
+            periodSeconds: 2
+            failureThreshold: 30
+          livenessProbe:
+            httpGet:
+              path: /healthz
+              port: http
+            periodSeconds: 10
+          readinessProbe:
+            httpGet:
+              path: /readyz
+              port: http
+            periodSeconds: 5
+          volumeMounts:
+            - name: config
+              mountPath: {root}/app/config/rohas.toml
+              subPath: rohas.toml
+      volumes:
+        - name: config
+          secret:
+            secretName: {name}-config
+",
+            root = IMAGE_ROOT,
+            name = self.name,
+        );
+        yaml
+    }
+
+    fn service(&self) -> String {
+        format!(
+            "apiVersion: v1\nkind: Service\n{}spec:\n  selector:\n    app.kubernetes.io/name: {}\n  ports:\n    - name: http\n      port: 80\n      targetPort: http\n",
+            self.metadata(&self.name),
+            self.name
+        )
+    }
+
+    fn autoscaler(&self) -> String {
+        let autoscaling = &self.kubernetes.autoscaling;
+        format!(
+            "apiVersion: autoscaling/v2
+kind: HorizontalPodAutoscaler
+{metadata}spec:
+  scaleTargetRef:
+    apiVersion: apps/v1
+    kind: Deployment
+    name: {name}
+  minReplicas: {min}
+  maxReplicas: {max}
+  metrics:
+    - type: Resource
+      resource:
+        name: cpu
+        target:
+          type: Utilization
+          averageUtilization: {target}
+",
+            metadata = self.metadata(&self.name),
+            name = self.name,
+            min = autoscaling.min_replicas,
+            max = autoscaling.max_replicas,
+            target = autoscaling.target_cpu_utilization,
+        )
+    }
+
+    fn chart(&self) -> String {
+        format!(
+            "apiVersion: v2\nname: {}\ndescription: Rohas engine for {}\ntype: application\nversion: 0.1.0\nappVersion: {}\n",
+            self.name,
+            self.name,
+            quote(&self.version)
+        )
+    }
+
+    fn values(&self) -> String {
+        let autoscaling = &self.kubernetes.autoscaling;
+        let mut yaml = format!(
+            "image: {}\nreplicaCount: {}\nport: {}\n\nservice:\n  type: ClusterIP\n  port: 80\n\n",
+            quote(&self.image),
+            self.kubernetes.replicas,
+            self.port
+        );
+        if self.kubernetes.env.is_empty() {
+            yaml.push_str("env: {}\n");
+        } else {
+            yaml.push_str("env:\n");
+            for (name, value) in &self.kubernetes.env {
+                let _ = writeln!(yaml, "  {}: {}", name, quote(value));
+            }
+        }
+        let _ = writeln!(
+            yaml,
+            "envSecret: {}\n",
+            self.kubernetes.env_secret.as_deref().map(quote).unwrap_or_else(|| "\"\"".to_string())
+        );
+        match resources(&self.kubernetes.resources) {
+            Some(resources) => {
+                let _ = write!(yaml, "resources:\n{}\n", indent(&resources, 2));
+            }
+            None => yaml.push_str("resources: {}\n\n"),
+        }
+        let _ = write!(
+            yaml,
+            "autoscaling:\n  enabled: {}\n  minReplicas: {}\n  maxReplicas: {}\n  targetCPUUtilizationPercentage: {}\n\n",
+            autoscaling.enabled, autoscaling.min_replicas, autoscaling.max_replicas, autoscaling.target_cpu_utilization
+        );
+        let _ = write!(yaml, "# config/rohas.toml as the pods load it\nconfig: |\n{}", indent(&self.rohas_toml, 2));
+        yaml
+    }
+}
+
+/// Writes Kubernetes manifests running the project's engine, or with `helm`
+/// a Helm chart with the same resources, parameterized by rohas.toml.
+pub async fn generate(schema_path: PathBuf, output: PathBuf, helm: bool) -> Result<()> {
+    let config_path = find_config_file(&schema_path)
+        .context("config/rohas.toml not found; run `rohas k8s generate` inside a project")?;
+    let app = App::load(&config_path)?;
+
+    let files = if helm {
+        let chart = output.join(&app.name);
+        vec![
+            (chart.join("Chart.yaml"), app.chart()),
+            (chart.join("values.yaml"), app.values()),
+            (chart.join("templates").join("secret.yaml"), HELM_SECRET.to_string()),
+            (chart.join("templates").join("deployment.yaml"), HELM_DEPLOYMENT.to_string()),
+            (chart.join("templates").join("service.yaml"), HELM_SERVICE.to_string()),
+            (chart.join("templates").join("hpa.yaml"), HELM_HPA.to_string()),
+        ]
+    } else {
+        let mut files = vec![
+            (output.join("secret.yaml"), app.secret()),
+            (output.join("deployment.yaml"), app.deployment()),
+            (output.join("service.yaml"), app.service()),
+        ];
+        if app.kubernetes.autoscaling.enabled {
+            files.push((output.join("hpa.yaml"), app.autoscaler()));
+        }
+        files
+    };
+
+    for (path, content) in &files {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+
+    info!("Wrote {} files to {}", files.len(), output.display());
+    info!("The image {} must hold the `rohas build` output of its target in {}", app.image, IMAGE_ROOT);
+    if helm {
+        info!("Install with: helm install {} {}", app.name, output.join(&app.name).display());
+    } else {
+        info!("Apply with: kubectl apply -f {}", output.display());
+    }
+    Ok(())
+}
+
+/// A DNS-1123 label from a project name: lowercase letters, digits and
+/// dashes, at most 63 characters.
+fn resource_name(name: &str) -> String {
+    let name: String = name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let name = name.trim_matches('-');
+    name[..name.len().min(63)].trim_end_matches('-').to_string()
+}
+
+/// The requests and limits block, or None when nothing is set.
+fn resources(resources: &KubernetesResources) -> Option<String> {
+    let section = |entries: [(&str, &Option<String>); 2]| {
+        entries
+            .iter()
+            .filter_map(|(key, quantity)| quantity.as_ref().map(|quantity| format!("  {}: {}\n", key, quote(quantity))))
+            .collect::<String>()
+    };
+    let requests = section([("cpu", &resources.cpu_request), ("memory", &resources.memory_request)]);
+    let limits = section([("cpu", &resources.cpu_limit), ("memory", &resources.memory_limit)]);
+
+    let mut yaml = String::new();
+    if !requests.is_empty() {
+        let _ = write!(yaml, "requests:\n{}", requests);
+    }
+    if !limits.is_empty() {
+        let _ = write!(yaml, "limits:\n{}", limits);
+    }
+    (!yaml.is_empty()).then_some(yaml)
+}
+
+fn indent(text: &str, spaces: usize) -> String {
+    let prefix = " ".repeat(spaces);
+    text.lines()
+        .map(|line| if line.is_empty() { "\n".to_string() } else { format!("{}{}\n", prefix, line) })
+        .collect()
+}
+
+/// A YAML double-quoted scalar; JSON string escapes are valid YAML.
+fn quote(text: &str) -> String {
+    serde_json::Value::from(text).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const CONFIG: &str = r#"
+[project]
+name = "My Shop"
+version = "1.2.0"
+language = "typescript"
+
+[server]
+host = "127.0.0.1"
+port = 4000
+enable_cors = false
+
+[adapter]
+type = "memory"
+buffer_size = 1000
+"#;
+
+    fn project(kubernetes: &str) -> TempDir {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("schema")).unwrap();
+        fs::create_dir_all(dir.path().join("config")).unwrap();
+        fs::write(dir.path().join("config/rohas.toml"), format!("{}\n{}", CONFIG, kubernetes)).unwrap();
+        dir
+    }
+
+    async fn generate_files(kubernetes: &str, helm: bool) -> (TempDir, PathBuf) {
+        let dir = project(kubernetes);
+        let output = dir.path().join("k8s");
+        generate(dir.path().join("schema"), output.clone(), helm).await.unwrap();
+        (dir, output)
+    }
+
+    fn read(path: &Path) -> String {
+        fs::read_to_string(path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e))
+    }
+
+    /// Indentation YAML accepts for the block style the generator writes:
+    /// spaces only, in steps of two.
+    fn assert_block_yaml(yaml: &str) {
+        for line in yaml.lines() {
+            let indent = line.len() - line.trim_start_matches(' ').len();
+            assert!(!line.contains('\t'), "tab in {:?}", line);
+            assert_eq!(indent % 2, 0, "odd indentation in {:?}", line);
+        }
+    }
+
+    #[test]
+    fn test_resource_name() {
+        assert_eq!(resource_name("My Shop"), "my-shop");
+        assert_eq!(resource_name("__orders.api__"), "orders-api");
+        assert_eq!(resource_name("!!!"), "");
+        let long = format!("{}-{}", "a".repeat(62), "b");
+        assert_eq!(resource_name(&long), "a".repeat(62));
+    }
+
+    #[tokio::test]
+    async fn test_manifests_with_autoscaling() {
+        let (_dir, output) = generate_files(
+            r#"
+[kubernetes]
+namespace = "shop"
+env_secret = "shop-secrets"
+
+[kubernetes.env]
+LOG_LEVEL = "debug: all"
+"#,
+            false,
+        )
+        .await;
+
+        let deployment = read(&output.join("deployment.yaml"));
+        assert_block_yaml(&deployment);
+        assert!(deployment.starts_with("apiVersion: apps/v1\nkind: Deployment\nmetadata:\n  name: my-shop\n  namespace: shop\n"));
+        assert!(!deployment.contains("replicas:"), "the autoscaler owns the replica count");
+        assert!(deployment.contains("          image: \"my-shop:1.2.0\"\n"));
+        assert!(deployment.contains("              containerPort: 4000\n"));
+        assert!(deployment.contains("            - name: LOG_LEVEL\n              value: \"debug: all\"\n"));
+        assert!(deployment.contains("          envFrom:\n            - secretRef:\n                name: shop-secrets\n"));
+        assert!(deployment.contains(
+            "          resources:\n            requests:\n              cpu: \"100m\"\n              memory: \"128Mi\"\n            limits:\n              memory: \"512Mi\"\n"
+        ));
+        assert!(deployment.contains("          startupProbe:\n            httpGet:\n              path: /healthz\n"));
+        assert!(deployment.contains("          livenessProbe:\n            httpGet:\n              path: /healthz\n"));
+        assert!(deployment.contains("          readinessProbe:\n            httpGet:\n              path: /readyz\n"));
+        assert!(deployment.contains("            secretName: my-shop-config\n"));
+
+        let secret = read(&output.join("secret.yaml"));
+        assert_block_yaml(&secret);
+        assert!(secret.contains("  name: my-shop-config\n  namespace: shop\n"));
+        assert!(secret.contains("  rohas.toml: |\n"));
+        assert!(secret.contains("    host = \"0.0.0.0\"\n"));
+        assert!(!secret.contains("127.0.0.1"));
+        assert!(secret.contains("    name = \"My Shop\"\n"));
+
+        let service = read(&output.join("service.yaml"));
+        assert_block_yaml(&service);
+        assert!(service.contains("kind: Service\n"));
+        assert!(service.contains("      targetPort: http\n"));
+
+        let hpa = read(&output.join("hpa.yaml"));
+        assert_block_yaml(&hpa);
+        assert!(hpa.contains("kind: HorizontalPodAutoscaler\n"));
+        assert!(hpa.contains("    name: my-shop\n  minReplicas: 1\n  maxReplicas: 5\n"));
+        assert!(hpa.contains("          averageUtilization: 80\n"));
+    }
+
+    #[tokio::test]
+    async fn test_manifests_without_autoscaling() {
+        let (_dir, output) = generate_files(
+            r#"
+[kubernetes]
+image = "registry.example.com/shop:abc123"
+replicas = 3
+
+[kubernetes.resources]
+cpu_request = "250m"
+memory_request = "256Mi"
+cpu_limit = "1"
+memory_limit = "1Gi"
+
+[kubernetes.autoscaling]
+enabled = false
+"#,
+            false,
+        )
+        .await;
+
+        let deployment = read(&output.join("deployment.yaml"));
+        assert_block_yaml(&deployment);
+        assert!(deployment.contains("spec:\n  replicas: 3\n  selector:\n"));
+        assert!(deployment.contains("          image: \"registry.example.com/shop:abc123\"\n"));
+        assert!(deployment.contains("              cpu: \"1\"\n              memory: \"1Gi\"\n"));
+        assert!(!deployment.contains("namespace:"));
+        assert!(!deployment.contains("env:"));
+        assert!(!deployment.contains("envFrom:"));
+        assert!(!output.join("hpa.yaml").exists());
+    }
+
+    #[tokio::test]
+    async fn test_helm_chart() {
+        let (_dir, output) = generate_files("", true).await;
+        let chart = output.join("my-shop");
+
+        let chart_yaml = read(&chart.join("Chart.yaml"));
+        assert!(chart_yaml.contains("name: my-shop\n"));
+        assert!(chart_yaml.contains("appVersion: \"1.2.0\"\n"));
+
+        let values = read(&chart.join("values.yaml"));
+        assert_block_yaml(&values);
+        assert!(values.starts_with("image: \"my-shop:1.2.0\"\nreplicaCount: 1\nport: 4000\n"));
+        assert!(values.contains("env: {}\n"));
+        assert!(values.contains("envSecret: \"\"\n"));
+        assert!(values.contains("resources:\n  requests:\n    cpu: \"100m\"\n"));
+        assert!(values.contains("autoscaling:\n  enabled: true\n  minReplicas: 1\n  maxReplicas: 5\n"));
+        assert!(values.contains("config: |\n"));
+        assert!(values.contains("  host = \"0.0.0.0\"\n"));
+
+        for template in ["secret.yaml", "deployment.yaml", "service.yaml", "hpa.yaml"] {
+            assert!(chart.join("templates").join(template).exists(), "missing {}", template);
+        }
+        let deployment = read(&chart.join("templates/deployment.yaml"));
+        assert!(deployment.contains("path: /healthz"));
+        assert!(deployment.contains("path: /readyz"));
+    }
+
+    #[test]
+    fn test_config_without_a_usable_name_is_rejected() {
+        let dir = TempDir::new().unwrap();
+        let config_path = dir.path().join("rohas.toml");
+        fs::write(&config_path, CONFIG.replace("My Shop", "***")).unwrap();
+        let error = App::load(&config_path).err().unwrap().to_string();
+        assert!(error.contains("project.name"), "{}", error);
+    }
+}
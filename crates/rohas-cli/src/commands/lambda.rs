@@ -0,0 +1,182 @@
+use anyhow::{Context, Result};
+use rohas_engine::lambda::LambdaHandler;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tracing::{error, info};
+
+use super::run::load_engine;
+
+/// Set by the Lambda runtime to the host of its runtime API.
+const RUNTIME_API: &str = "AWS_LAMBDA_RUNTIME_API";
+
+/// Serves Lambda invocations without the HTTP server: from the runtime API
+/// when running in Lambda, otherwise one JSON event per stdin line with the
+/// response written as one stdout line.
+pub async fn execute(schema_path: PathBuf) -> Result<()> {
+    let engine = load_engine(&schema_path).await?;
+    let handler = engine.lambda();
+
+    match std::env::var(RUNTIME_API) {
+        Ok(runtime_api) => serve_runtime_api(&handler, &runtime_api).await,
+        Err(_) => serve_stdio(&handler).await,
+    }
+}
+
+async fn serve_runtime_api(handler: &LambdaHandler<'_>, runtime_api: &str) -> Result<()> {
+    let client = reqwest::Client::new();
+    let base = format!("http://{}/2018-06-01/runtime/invocation", runtime_api);
+    info!("Waiting for Lambda invocations");
+
+    loop {
+        let next = client
+            .get(format!("{}/next", base))
+            .send()
+            .await
+            .context("Failed to fetch the next invocation")?;
+        let request_id = next
+            .headers()
+            .get("lambda-runtime-aws-request-id")
+            .and_then(|id| id.to_str().ok())
+            .context("Invocation without a request id")?
+            .to_string();
+        let event: Value = next.json().await.context("Invalid invocation event")?;
+
+        let (outcome, body) = match handler.handle(event).await {
+            Ok(response) => ("response", response),
+            Err(e) => {
+                error!("Invocation {} failed: {}", request_id, e);
+                ("error", error_body(&e.to_string()))
+            }
+        };
+        client
+            .post(format!("{}/{}/{}", base, request_id, outcome))
+            .json(&body)
+            .send()
+            .await
+            .with_context(|| format!("Failed to report invocation {}", request_id))?;
+    }
+}
+
+async fn serve_stdio(handler: &LambdaHandler<'_>) -> Result<()> {
+    serve_lines(handler, BufReader::new(tokio::io::stdin()), tokio::io::stdout()).await
+}
+
+/// Answers each JSON event line of `input` with a response line on `output`.
+async fn serve_lines(
+    handler: &LambdaHandler<'_>,
+    input: impl AsyncBufRead + Unpin,
+    mut output: impl AsyncWrite + Unpin,
+) -> Result<()> {
+    let mut lines = input.lines();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str(&line) {
+            Ok(event) => handler.handle(event).await.map_err(|e| e.to_string()),
+            Err(e) => Err(format!("Invalid JSON event: {}", e)),
+        };
+        let response = response.unwrap_or_else(|message| error_body(&message));
+        output.write_all(format!("{}\n", response).as_bytes()).await?;
+        output.flush().await?;
+    }
+    Ok(())
+}
+
+/// Error response in the shape the Lambda runtime reports.
+fn error_body(message: &str) -> Value {
+    json!({ "errorMessage": message, "errorType": "RohasError" })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// A project whose `OrderPlaced` event and `/health` API have no handler
+    /// files, so every invocation that reaches a handler fails.
+    fn project() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("schema")).unwrap();
+        fs::create_dir_all(dir.path().join("config")).unwrap();
+        fs::write(
+            dir.path().join("schema/index.ro"),
+            r#"
+            model Order {
+                id Int
+            }
+
+            api Health {
+                method: GET
+                path: "/health"
+                response: String
+            }
+
+            event OrderPlaced {
+                payload: Order
+                handler: [charge_card]
+            }
+            "#,
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("config/rohas.toml"),
+            r#"
+            [project]
+            name = "app"
+            version = "0.1.0"
+            language = "python"
+
+            [server]
+            host = "127.0.0.1"
+            port = 3000
+            enable_cors = false
+
+            [adapter]
+            type = "memory"
+            buffer_size = 100
+            "#,
+        )
+        .unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_stdio_answers_each_event_line() {
+        let dir = project();
+        let engine = load_engine(&dir.path().join("schema")).await.unwrap();
+        let input = [
+            json!({
+                "version": "2.0",
+                "rawPath": "/health",
+                "rawQueryString": "",
+                "headers": {},
+                "requestContext": { "stage": "$default", "http": { "method": "GET", "sourceIp": "127.0.0.1" } },
+            })
+            .to_string(),
+            String::new(),
+            json!({ "Records": [{ "messageId": "m1", "body": "not a message" }] }).to_string(),
+            "{ not json".to_string(),
+            json!({ "unexpected": true }).to_string(),
+        ]
+        .join("\n");
+
+        let mut output = Vec::new();
+        serve_lines(&engine.lambda(), input.as_bytes(), &mut output).await.unwrap();
+
+        let responses: Vec<Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(responses.len(), 4, "{:?}", responses);
+        assert!(responses[0]["statusCode"].is_u64(), "{}", responses[0]);
+        assert!(responses[0].get("cookies").is_some());
+        assert_eq!(responses[1], json!({ "batchItemFailures": [{ "itemIdentifier": "m1" }] }));
+        assert_eq!(responses[2]["errorType"], "RohasError");
+        assert!(responses[2]["errorMessage"].as_str().unwrap().starts_with("Invalid JSON event"));
+        assert!(responses[3]["errorMessage"].as_str().unwrap().contains("Unsupported Lambda event"));
+    }
+}
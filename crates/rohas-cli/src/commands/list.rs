@@ -1,12 +1,12 @@
 use anyhow::Result;
-use rohas_parser::Parser;
+use rohas_parser::{env::active_environment, Parser};
 use std::path::PathBuf;
 
 use crate::utils::file_util::parse_directory;
 
 pub async fn list_handlers(schema_path: PathBuf) -> Result<()> {
     let schema = if schema_path.is_file() {
-        Parser::parse_file(&schema_path)?
+        Parser::parse_file_for_environment(&schema_path, &active_environment())?
     } else if schema_path.is_dir() {
         parse_directory(&schema_path)?
     } else {
@@ -33,7 +33,7 @@ pub async fn list_handlers(schema_path: PathBuf) -> Result<()> {
 
 pub async fn list_events(schema_path: PathBuf) -> Result<()> {
     let schema = if schema_path.is_file() {
-        Parser::parse_file(&schema_path)?
+        Parser::parse_file_for_environment(&schema_path, &active_environment())?
     } else if schema_path.is_dir() {
         parse_directory(&schema_path)?
     } else {
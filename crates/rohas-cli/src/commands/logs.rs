@@ -0,0 +1,87 @@
+use anyhow::{Context, Result};
+use rohas_engine::config::EngineConfig;
+use rohas_engine::tracing_log::{level_rank, TracingLogEntry};
+use std::time::Duration;
+
+/// Seconds the engine holds a follow request open while waiting for new logs.
+const POLL_TIMEOUT_SECONDS: u64 = 30;
+
+/// Prints recent logs from a running engine, then keeps streaming new ones
+/// when `follow` is set.
+pub async fn tail(follow: bool, level: Option<String>, lines: usize, url: Option<String>) -> Result<()> {
+    if let Some(level) = level.as_deref().filter(|level| level_rank(level).is_none()) {
+        anyhow::bail!("Unknown log level {}, expected trace, debug, info, warn or error", level);
+    }
+    let config = EngineConfig::from_project_root().unwrap_or_default();
+    let base_url = url.unwrap_or_else(|| {
+        let host = match config.server.host.as_str() {
+            "0.0.0.0" | "::" => "127.0.0.1",
+            host => host,
+        };
+        format!("http://{}:{}", host, config.server.port)
+    });
+    let base_url = base_url.trim_end_matches('/');
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(POLL_TIMEOUT_SECONDS + 10))
+        .build()?;
+
+    let mut query = vec![("limit", lines.to_string())];
+    if let Some(level) = &level {
+        query.push(("min_level", level.clone()));
+    }
+    let logs = fetch(&client, &config, &format!("{}/api/workbench/logs", base_url), &query).await?;
+    let mut since = print_logs(logs);
+    if !follow {
+        return Ok(());
+    }
+
+    loop {
+        let mut query = vec![("timeout", POLL_TIMEOUT_SECONDS.to_string())];
+        if let Some(level) = &level {
+            query.push(("min_level", level.clone()));
+        }
+        if let Some(since) = &since {
+            query.push(("since", since.clone()));
+        }
+        let logs = fetch(&client, &config, &format!("{}/api/workbench/logs/poll", base_url), &query).await?;
+        if let Some(latest) = print_logs(logs) {
+            since = Some(latest);
+        }
+    }
+}
+
+async fn fetch(
+    client: &reqwest::Client,
+    config: &EngineConfig,
+    url: &str,
+    query: &[(&str, String)],
+) -> Result<Vec<TracingLogEntry>> {
+    let response = client
+        .get(url)
+        .bearer_auth(&config.workbench.api_key)
+        .query(query)
+        .send()
+        .await
+        .with_context(|| format!("Failed to reach the engine at {} (is `rohas dev` running?)", url))?;
+
+    if !response.status().is_success() {
+        anyhow::bail!("Engine returned HTTP {} for {}", response.status(), url);
+    }
+    Ok(response.json().await?)
+}
+
+/// Prints logs oldest first and returns the newest timestamp.
+fn print_logs(logs: Vec<TracingLogEntry>) -> Option<String> {
+    let latest = logs.first().map(|log| log.timestamp.clone());
+    for log in logs.into_iter().rev() {
+        println!(
+            "{} {:>5} {}: {}",
+            log.timestamp,
+            log.level.to_uppercase(),
+            log.target,
+            log.message
+        );
+    }
+    latest
+}
@@ -1,5 +1,18 @@
+pub mod asyncapi;
+pub mod build;
 pub mod codegen;
+pub mod config;
+pub mod coverage;
 pub mod dev;
+pub mod docs;
+pub mod graph;
+pub mod impact;
 pub mod init;
+pub mod k8s;
+pub mod lambda;
 pub mod list;
+pub mod logs;
+pub mod replay;
+pub mod run;
+pub mod telemetry;
 pub mod validate;
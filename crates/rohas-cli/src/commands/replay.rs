@@ -0,0 +1,33 @@
+use anyhow::Result;
+use std::path::PathBuf;
+use tracing::info;
+
+use super::run::load_engine;
+
+/// Runs the handlers recorded for a trace again and reports which ones
+/// returned something different.
+pub async fn execute(trace_id: String, schema_path: PathBuf) -> Result<()> {
+    let engine = load_engine(&schema_path).await?;
+    let steps = engine.replay(&trace_id).await?;
+
+    let mut differing = 0;
+    for step in &steps {
+        if step.matches() {
+            info!("{} matches the recording", step.handler);
+            continue;
+        }
+        differing += 1;
+        println!("{} differs from the recording", step.handler);
+        println!("  recorded: {}", serde_json::to_string_pretty(&step.recorded)?.replace('\n', "\n  "));
+        println!("  replayed: {}", serde_json::to_string_pretty(&step.replayed)?.replace('\n', "\n  "));
+        for call in &step.unanswered_calls {
+            println!("  not in the recording: {}", call);
+        }
+    }
+
+    if differing > 0 {
+        anyhow::bail!("{} of {} handler run(s) differ from the recording", differing, steps.len());
+    }
+    info!("Replayed {} handler run(s) of trace {}", steps.len(), trace_id);
+    Ok(())
+}
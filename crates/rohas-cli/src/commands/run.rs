@@ -0,0 +1,135 @@
+use anyhow::{Context, Result};
+use rohas_engine::invoke::EmittedEvent;
+use rohas_engine::{Engine, EngineConfig};
+use rohas_parser::{env::active_environment, Parser};
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+use crate::utils::file_util::{find_config_file, parse_directory};
+
+/// Most handler runs `--follow-triggers` makes before giving up, so a
+/// trigger cycle cannot run forever.
+const MAX_FOLLOWED_RUNS: usize = 1000;
+
+/// Runs one handler and exits, without the HTTP server or the cron scheduler.
+/// The events it triggers are published but only handled with
+/// `follow_triggers`, which runs their handlers inline before exiting.
+pub async fn execute(
+    handler: String,
+    payload: Option<PathBuf>,
+    follow_triggers: bool,
+    schema_path: PathBuf,
+) -> Result<()> {
+    let payload: Value = match &payload {
+        Some(path) => {
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read payload {}", path.display()))?;
+            serde_json::from_str(&content).with_context(|| format!("Invalid JSON in {}", path.display()))?
+        }
+        None => Value::Object(Default::default()),
+    };
+
+    let engine = load_engine(&schema_path).await?;
+    let invocation = engine.invoke(&handler, payload).await?;
+    let result = &invocation.result;
+
+    info!(
+        "{} finished in {}ms (trace {})",
+        handler, result.execution_time_ms, invocation.trace_id
+    );
+    for event in &invocation.triggered_events {
+        if follow_triggers {
+            info!("Triggered {}", event.event_name);
+        } else {
+            info!(
+                "Triggered {} (published only; pass --follow-triggers to run its handlers)",
+                event.event_name
+            );
+        }
+    }
+
+    if !result.success {
+        anyhow::bail!(
+            "{} failed: {}",
+            handler,
+            result.error.as_deref().unwrap_or("unknown error")
+        );
+    }
+
+    if follow_triggers {
+        follow(&engine, invocation.emitted.clone()).await?;
+    }
+
+    let output = result.data.clone().unwrap_or(Value::Null);
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+/// Runs the handlers of `emitted` and of everything they trigger in turn,
+/// failing on the first handler that fails.
+async fn follow(engine: &Engine, emitted: Vec<EmittedEvent>) -> Result<()> {
+    let mut pending = VecDeque::from(emitted);
+    let mut runs = 0;
+
+    while let Some(event) = pending.pop_front() {
+        let invocations = engine
+            .invoke_event(&event.event_name, event.payload, &event.metadata)
+            .await
+            .with_context(|| format!("Failed to handle {}", event.event_name))?;
+
+        for invocation in invocations {
+            runs += 1;
+            let result = &invocation.result;
+            if !result.success {
+                anyhow::bail!(
+                    "{} handler failed: {}",
+                    event.event_name,
+                    result.error.as_deref().unwrap_or("unknown error")
+                );
+            }
+            info!(
+                "Handled {} in {}ms (trace {})",
+                event.event_name, result.execution_time_ms, invocation.trace_id
+            );
+            if runs >= MAX_FOLLOWED_RUNS && !invocation.emitted.is_empty() {
+                warn!("Stopped following triggers after {} handler runs", runs);
+                return Ok(());
+            }
+            pending.extend(invocation.emitted);
+        }
+    }
+
+    Ok(())
+}
+
+/// Builds the engine for the project of `schema_path` without initializing
+/// it, so no adapter subscriptions, scheduler or server are started.
+pub async fn load_engine(schema_path: &Path) -> Result<Engine> {
+    let schema = if schema_path.is_file() {
+        Parser::parse_file_for_environment(schema_path, &active_environment())?
+    } else if schema_path.is_dir() {
+        parse_directory(&schema_path.to_path_buf())?
+    } else {
+        anyhow::bail!("Schema path not found: {}", schema_path.display());
+    };
+
+    let config = match find_config_file(schema_path) {
+        Some(config_path) => {
+            let mut config = EngineConfig::from_file(&config_path)?;
+            config.project_root = config_path
+                .parent()
+                .and_then(|p| p.parent())
+                .map(|p| p.to_path_buf())
+                .unwrap_or_default();
+            config
+        }
+        None => EngineConfig {
+            project_root: std::env::current_dir()?,
+            ..EngineConfig::default()
+        },
+    };
+
+    Ok(Engine::from_schema(schema, config).await?)
+}
@@ -0,0 +1,99 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use rohas_engine::config::EngineConfig;
+use rohas_engine::telemetry::TelemetryManager;
+use rohas_telemetry::{export_traces, ExportFormat};
+use std::fs;
+use std::path::PathBuf;
+use tracing::info;
+
+pub async fn export(
+    format: String,
+    from: Option<String>,
+    to: Option<String>,
+    output: PathBuf,
+    limit: Option<usize>,
+) -> Result<()> {
+    let format: ExportFormat = format.parse().map_err(anyhow::Error::msg)?;
+
+    let project_root = std::env::current_dir()?;
+    let config_path = project_root.join("config").join("rohas.toml");
+    let mut config = if config_path.exists() {
+        EngineConfig::from_file(&config_path)?
+    } else {
+        EngineConfig::default()
+    };
+    config.project_root = project_root.clone();
+
+    let telemetry_path = if config.telemetry.path.starts_with('/') {
+        PathBuf::from(&config.telemetry.path)
+    } else {
+        project_root.join(&config.telemetry.path)
+    };
+    if !telemetry_path.exists() {
+        anyhow::bail!("No telemetry data found at {}", telemetry_path.display());
+    }
+
+    let to = match to {
+        Some(to) => parse_time("--to", &to)?,
+        None => Utc::now(),
+    };
+    let from = match from {
+        Some(from) => parse_time("--from", &from)?,
+        None if config.telemetry.retention_days > 0 => {
+            to - Duration::days(config.telemetry.retention_days as i64)
+        }
+        None => DateTime::<Utc>::UNIX_EPOCH,
+    };
+    if from > to {
+        anyhow::bail!("--from must be before --to");
+    }
+
+    let telemetry = TelemetryManager::new(telemetry_path.clone(), config.telemetry.retention_days)
+        .await
+        .map_err(|e| anyhow::anyhow!("{}", e))
+        .with_context(|| {
+            format!(
+                "Failed to open telemetry at {} (is `rohas dev` still running?)",
+                telemetry_path.display()
+            )
+        })?;
+
+    let traces = telemetry
+        .trace_store()
+        .query_range(from, to, limit)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to read traces: {}", e))?;
+
+    let service_name = project_root
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "rohas".to_string());
+
+    let document = export_traces(&traces, format, &service_name);
+    fs::write(&output, serde_json::to_string_pretty(&document)?)?;
+
+    info!("Exported {} traces to {}", traces.len(), output.display());
+
+    Ok(())
+}
+
+fn parse_time(flag: &str, value: &str) -> Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&Utc))
+        .with_context(|| format!("Invalid {} time '{}': expected RFC 3339, e.g. 2024-01-01T00:00:00Z", flag, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_time() {
+        let time = parse_time("--from", "2024-05-01T14:00:00+02:00").unwrap();
+        assert_eq!(time, "2024-05-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap());
+
+        let error = format!("{:#}", parse_time("--to", "yesterday").unwrap_err());
+        assert!(error.contains("Invalid --to time 'yesterday'"), "{}", error);
+    }
+}
@@ -1,28 +1,111 @@
-use crate::utils::file_util::parse_directory;
+use crate::utils::file_util::{parse_directory, visit_ro_files};
 use anyhow::Result;
-use rohas_parser::Parser;
-use std::path::PathBuf;
-use tracing::info;
+use rohas_parser::{env::active_environment, Parser};
+use std::path::{Path, PathBuf};
+use tracing::{error, info};
 
-pub async fn execute(schema_path: PathBuf) -> Result<()> {
-    info!("Validating schema: {}", schema_path.display());
+pub async fn execute(schema_path: PathBuf, next: usize) -> Result<()> {
+    info!(
+        "Validating schema: {} ({} environment)",
+        schema_path.display(),
+        active_environment()
+    );
 
-    let schema = if schema_path.is_file() {
-        Parser::parse_file(&schema_path)?
+    let mut problems = 0;
+    if schema_path.is_file() {
+        problems += report_diagnostics(&schema_path, false)?;
+    } else if schema_path.is_dir() {
+        visit_ro_files(&schema_path, &mut |path| {
+            problems += report_diagnostics(path, true)?;
+            Ok(())
+        })?;
+    }
+    if problems > 0 {
+        anyhow::bail!("Schema has {} problem(s)", problems);
+    }
+
+    let mut schema = if schema_path.is_file() {
+        Parser::parse_file_for_environment(&schema_path, &active_environment())?
     } else if schema_path.is_dir() {
         parse_directory(&schema_path)?
     } else {
         anyhow::bail!("Schema path not found: {}", schema_path.display());
     };
 
+    let env_vars = schema.env_vars();
+    schema.resolve_process_env()?;
     schema.validate()?;
 
+    let mut cron_errors = Vec::new();
+    for cron in &schema.crons {
+        let job = rohas_engine::engine::cron_job_config(cron);
+        match (job.describe(), job.next_runs(chrono::Utc::now(), next)) {
+            (Ok(description), Ok(runs)) => {
+                info!("Cron {}: {}", cron.name, description);
+                for run in runs {
+                    info!("    next: {}", run.to_rfc3339());
+                }
+            }
+            (Err(e), _) | (_, Err(e)) => cron_errors.push(format!("cron {}: {}", cron.name, e)),
+        }
+    }
+    if !cron_errors.is_empty() {
+        anyhow::bail!("{}", cron_errors.join("\n"));
+    }
+
     info!("Schema validation passed!");
     info!("  - {} models", schema.models.len());
     info!("  - {} inputs", schema.inputs.len());
     info!("  - {} APIs", schema.apis.len());
     info!("  - {} events", schema.events.len());
     info!("  - {} cron jobs", schema.crons.len());
+    if !env_vars.is_empty() {
+        info!("  - environment variables: {}", env_vars.join(", "));
+    }
 
     Ok(())
 }
+
+/// Logs every problem of the schema file at `path` as `path:line:column`,
+/// and returns how many there are. For a file that is `part` of a schema
+/// directory, references to other files are left to the check of the whole
+/// directory that follows.
+fn report_diagnostics(path: &Path, part: bool) -> Result<usize> {
+    let content = std::fs::read_to_string(path)?;
+    let (_, diagnostics) = if part {
+        Parser::parse_part_with_diagnostics(&content, &active_environment())
+    } else {
+        Parser::parse_with_diagnostics(&content, &active_environment())
+    };
+    for diagnostic in &diagnostics {
+        error!("{}:{}", path.display(), diagnostic);
+    }
+    Ok(diagnostics.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_validates_references_across_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let events = dir.path().join("events.ro");
+        std::fs::write(
+            dir.path().join("feeds.ro"),
+            "ws OrderFeed {\n  path: \"/ws/orders\"\n}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            &events,
+            "event OrderUpdated {\n  payload: Order\n  forward_to: OrderFeed\n}\n",
+        )
+        .unwrap();
+
+        assert_eq!(report_diagnostics(&events, true).unwrap(), 0);
+        assert!(execute(dir.path().to_path_buf(), 0).await.is_ok());
+
+        std::fs::remove_file(dir.path().join("feeds.ro")).unwrap();
+        assert!(execute(dir.path().to_path_buf(), 0).await.is_err());
+    }
+}
@@ -1,4 +1,5 @@
 use clap::{Parser, Subcommand};
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
 use std::path::PathBuf;
@@ -54,11 +55,25 @@ enum Commands {
 
         #[arg(short, long)]
         lang: Option<String>,
+
+        /// Also export event payloads as Avro (.avsc) or Protobuf (.proto) definitions
+        #[arg(long, value_name = "avro|proto")]
+        events_format: Option<String>,
     },
 
     Validate {
         #[arg(default_value = "schema")]
         schema: PathBuf,
+
+        /// Number of upcoming runs to preview for each cron job
+        #[arg(long, default_value = "3")]
+        next: usize,
+    },
+
+    /// Check or print config/rohas.toml
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
     },
 
     Dev {
@@ -78,6 +93,68 @@ enum Commands {
         workbench_dev: bool,
     },
 
+    /// Write the AsyncAPI document describing events and websockets
+    Asyncapi {
+        #[arg(short, long, default_value = "schema")]
+        schema: PathBuf,
+
+        #[arg(short, long, default_value = "asyncapi.json")]
+        output: PathBuf,
+    },
+
+    /// Build the engine and handler library for deployment
+    Build {
+        /// Target triple, e.g. x86_64-unknown-linux-musl; defaults to the host
+        #[arg(short, long)]
+        target: Option<String>,
+
+        #[arg(short, long, default_value = "dist")]
+        output: PathBuf,
+
+        /// Local rohas checkout to build the engine from instead of the git repository
+        #[arg(long)]
+        engine_path: Option<PathBuf>,
+
+        #[arg(short, long, default_value = "schema")]
+        schema: PathBuf,
+
+        /// Also write a bootstrap that runs the engine as an AWS Lambda custom runtime
+        #[arg(long)]
+        lambda: bool,
+    },
+
+    /// Run one API, event or cron handler and exit
+    Run {
+        /// API name, event handler name or cron name
+        handler: String,
+
+        /// JSON file with the payload to pass; defaults to {}
+        #[arg(short, long)]
+        payload: Option<PathBuf>,
+
+        /// Also run the handlers of the events it triggers, and theirs
+        #[arg(long)]
+        follow_triggers: bool,
+
+        #[arg(short, long, default_value = "schema")]
+        schema: PathBuf,
+    },
+
+    /// Re-run the handlers recorded for a trace and compare their results
+    Replay {
+        /// Trace ID of a recording made with [recording] enabled
+        trace_id: String,
+
+        #[arg(short, long, default_value = "schema")]
+        schema: PathBuf,
+    },
+
+    /// Serve AWS Lambda invocations instead of running the HTTP server
+    Lambda {
+        #[arg(short, long, default_value = "schema")]
+        schema: PathBuf,
+    },
+
     ListHandlers {
         #[arg(default_value = "schema")]
         schema: PathBuf,
@@ -88,9 +165,166 @@ enum Commands {
         schema: PathBuf,
     },
 
+    Coverage {
+        #[arg(default_value = "schema")]
+        schema: PathBuf,
+    },
+
+    /// Report what a change to a file or model affects
+    Impact {
+        /// Schema or handler file, or a model, type or input name
+        target: String,
+
+        #[arg(short, long, default_value = "schema")]
+        schema: PathBuf,
+    },
+
+    /// Static documentation site generated from the schema
+    Docs {
+        #[command(subcommand)]
+        command: DocsCommands,
+    },
+
+    /// Schema graph as a text diagram
+    Graph {
+        #[command(subcommand)]
+        command: GraphCommands,
+    },
+
+    /// Kubernetes manifests for deploying the project
+    K8s {
+        #[command(subcommand)]
+        command: K8sCommands,
+    },
+
+    Telemetry {
+        #[command(subcommand)]
+        command: TelemetryCommands,
+    },
+
+    Logs {
+        #[command(subcommand)]
+        command: LogsCommands,
+    },
+
     Version,
 }
 
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Report unknown keys and invalid values
+    Validate {
+        /// Defaults to config/rohas.toml of the enclosing project
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+    },
+
+    /// Print the config file
+    Show {
+        /// Print the effective config with defaults applied and secrets masked
+        #[arg(long)]
+        resolved: bool,
+
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum DocsCommands {
+    /// Write model, API and event reference pages as static HTML
+    Build {
+        #[arg(short, long, default_value = "schema")]
+        schema: PathBuf,
+
+        #[arg(short, long, default_value = "site")]
+        output: PathBuf,
+
+        /// Site title; defaults to the project directory name
+        #[arg(long)]
+        title: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum GraphCommands {
+    /// Print models, APIs, events, handlers and trigger edges as a diagram
+    Export {
+        /// mermaid, d2 or dot
+        #[arg(short, long, default_value = "mermaid")]
+        format: String,
+
+        #[arg(short, long, default_value = "schema")]
+        schema: PathBuf,
+
+        /// File to write; defaults to stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum K8sCommands {
+    /// Write Deployment, Service and HorizontalPodAutoscaler manifests from config/rohas.toml
+    Generate {
+        #[arg(short, long, default_value = "schema")]
+        schema: PathBuf,
+
+        #[arg(short, long, default_value = "k8s")]
+        output: PathBuf,
+
+        /// Write a Helm chart instead of plain manifests
+        #[arg(long)]
+        helm: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum TelemetryCommands {
+    /// Export stored traces for use in other tracing tools
+    Export {
+        /// otlp, jaeger-json or zipkin
+        #[arg(short, long, default_value = "otlp")]
+        format: String,
+
+        /// Start of the time range (RFC 3339); defaults to the retention window
+        #[arg(long)]
+        from: Option<String>,
+
+        /// End of the time range (RFC 3339); defaults to now
+        #[arg(long)]
+        to: Option<String>,
+
+        #[arg(short, long, default_value = "traces.json")]
+        output: PathBuf,
+
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+}
+
+#[derive(Subcommand)]
+enum LogsCommands {
+    /// Print recent logs from the running engine
+    Tail {
+        /// Keep streaming new logs
+        #[arg(short, long)]
+        follow: bool,
+
+        /// Minimum level to show
+        #[arg(short, long, value_parser = ["trace", "debug", "info", "warn", "error"])]
+        level: Option<String>,
+
+        /// Number of recent lines to print first
+        #[arg(short = 'n', long, default_value = "50")]
+        lines: usize,
+
+        /// Engine URL; defaults to the server address in config/rohas.toml
+        #[arg(long)]
+        url: Option<String>,
+    },
+}
+
 use std::sync::Arc;
 use tracing_subscriber::reload::Handle;
 
@@ -105,23 +339,43 @@ pub fn register_tracing_log_layer(layer: rohas_engine::TracingLogLayer) -> anyho
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    let env_filter = tracing_subscriber::EnvFilter::try_from_default_env()
-        .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info"));
+    let cli = Cli::parse();
+
+    let base_filter = std::env::var(tracing_subscriber::EnvFilter::DEFAULT_ENV)
+        .ok()
+        .filter(|filter| tracing_subscriber::EnvFilter::try_new(filter).is_ok())
+        .unwrap_or_else(|| "info".to_string());
+    let (env_filter, filter_handle) =
+        tracing_subscriber::reload::Layer::new(tracing_subscriber::EnvFilter::new(&base_filter));
+    rohas_engine::log_level::set_log_filter_handle(base_filter, filter_handle);
     
     let (custom_layer, reload_handle) = tracing_subscriber::reload::Layer::new(None::<rohas_engine::TracingLogLayer>);
     
     let _ = TRACING_LOG_LAYER_HANDLE.set(Arc::new(reload_handle.clone()));
     rohas_engine::tracing_log::set_tracing_layer_handle(Arc::new(reload_handle));
     
+    // Lambda responses are written to stdout, so logs go to stderr there.
+    let log_writer = if matches!(cli.command, Commands::Lambda { .. }) {
+        BoxMakeWriter::new(std::io::stderr)
+    } else {
+        BoxMakeWriter::new(std::io::stdout)
+    };
     tracing_subscriber::registry()
         .with(custom_layer)
         .with(env_filter)
-        .with(tracing_subscriber::fmt::Layer::default())
+        .with(tracing_subscriber::fmt::Layer::default().with_writer(log_writer))
         .init();
 
-    let cli = Cli::parse();
-
-    if !matches!(cli.command, Commands::Version) {
+    if !matches!(
+        cli.command,
+        Commands::Version
+            | Commands::Logs { .. }
+            | Commands::Run { .. }
+            | Commands::Replay { .. }
+            | Commands::Lambda { .. }
+            | Commands::Graph { .. }
+            | Commands::Config { .. }
+    ) {
         print_banner();
     }
 
@@ -137,12 +391,21 @@ async fn main() -> anyhow::Result<()> {
             schema,
             output,
             lang,
+            events_format,
         } => {
-            commands::codegen::execute(schema, output, lang).await?;
+            commands::codegen::execute(schema, output, lang, events_format).await?;
         }
-        Commands::Validate { schema } => {
-            commands::validate::execute(schema).await?;
+        Commands::Validate { schema, next } => {
+            commands::validate::execute(schema, next).await?;
         }
+        Commands::Config { command } => match command {
+            ConfigCommands::Validate { config } => {
+                commands::config::validate(config).await?;
+            }
+            ConfigCommands::Show { resolved, config } => {
+                commands::config::show(config, resolved).await?;
+            }
+        },
         Commands::Dev {
             schema,
             port,
@@ -152,12 +415,88 @@ async fn main() -> anyhow::Result<()> {
         } => {
             commands::dev::execute(schema, port, watch, workbench, workbench_dev).await?;
         }
+        Commands::Asyncapi { schema, output } => {
+            commands::asyncapi::execute(schema, output).await?;
+        }
+        Commands::Build {
+            target,
+            output,
+            engine_path,
+            schema,
+            lambda,
+        } => {
+            commands::build::execute(schema, target, output, engine_path, lambda).await?;
+        }
+        Commands::Run {
+            handler,
+            payload,
+            follow_triggers,
+            schema,
+        } => {
+            commands::run::execute(handler, payload, follow_triggers, schema).await?;
+        }
+        Commands::Replay { trace_id, schema } => {
+            commands::replay::execute(trace_id, schema).await?;
+        }
+        Commands::Lambda { schema } => {
+            commands::lambda::execute(schema).await?;
+        }
         Commands::ListHandlers { schema } => {
             commands::list::list_handlers(schema).await?;
         }
         Commands::ListEvents { schema } => {
             commands::list::list_events(schema).await?;
         }
+        Commands::Coverage { schema } => {
+            commands::coverage::execute(schema).await?;
+        }
+        Commands::Impact { target, schema } => {
+            commands::impact::execute(target, schema).await?;
+        }
+        Commands::Logs { command } => match command {
+            LogsCommands::Tail {
+                follow,
+                level,
+                lines,
+                url,
+            } => {
+                commands::logs::tail(follow, level, lines, url).await?;
+            }
+        },
+        Commands::Docs { command } => match command {
+            DocsCommands::Build {
+                schema,
+                output,
+                title,
+            } => {
+                commands::docs::build(schema, output, title).await?;
+            }
+        },
+        Commands::Graph { command } => match command {
+            GraphCommands::Export {
+                format,
+                schema,
+                output,
+            } => {
+                commands::graph::export(schema, format, output).await?;
+            }
+        },
+        Commands::K8s { command } => match command {
+            K8sCommands::Generate { schema, output, helm } => {
+                commands::k8s::generate(schema, output, helm).await?;
+            }
+        },
+        Commands::Telemetry { command } => match command {
+            TelemetryCommands::Export {
+                format,
+                from,
+                to,
+                output,
+                limit,
+            } => {
+                commands::telemetry::export(format, from, to, output, limit).await?;
+            }
+        },
         Commands::Version => {
             println!("rohas {}", env!("CARGO_PKG_VERSION"));
         }
@@ -1,6 +1,6 @@
 use std::path::{Path, PathBuf};
 
-use rohas_parser::{Parser, Schema};
+use rohas_parser::{env::active_environment, Parser, Schema};
 use tracing::info;
 
 pub fn find_config_file(start_dir: &Path) -> Option<PathBuf> {
@@ -48,39 +48,25 @@ pub fn find_config_file(start_dir: &Path) -> Option<PathBuf> {
 }
 
 pub fn parse_directory(dir: &PathBuf) -> anyhow::Result<Schema> {
-    let mut combined_schema = Schema::new();
-    let mut file_count = 0;
-
+    let mut files = Vec::new();
     visit_ro_files(dir, &mut |path| {
-        info!("Parsing: {}", path.display());
-        match Parser::parse_file(path) {
-            Ok(schema) => {
-                // Merge schemas
-                combined_schema.models.extend(schema.models);
-                combined_schema.types.extend(schema.types);
-                combined_schema.inputs.extend(schema.inputs);
-                combined_schema.apis.extend(schema.apis);
-                combined_schema.events.extend(schema.events);
-                combined_schema.crons.extend(schema.crons);
-                combined_schema.websockets.extend(schema.websockets);
-                file_count += 1;
-                Ok(())
-            }
-            Err(e) => Err(anyhow::anyhow!("Failed to parse {}: {}", path.display(), e)),
-        }
+        files.push(path.clone());
+        Ok(())
     })?;
 
-    if file_count == 0 {
+    if files.is_empty() {
         anyhow::bail!("No .ro files found in {}", dir.display());
     }
+    files.sort();
 
-    info!("Parsed {} schema files", file_count);
+    // The files are parsed together, as constants and attribute groups
+    // apply across them.
+    let schema = Parser::parse_files_for_environment(&files, &active_environment())
+        .map_err(|e| anyhow::anyhow!("Failed to parse {}: {}", dir.display(), e))?;
 
-    combined_schema
-        .validate()
-        .map_err(|e| anyhow::anyhow!("Schema validation failed: {}", e))?;
+    info!("Parsed {} schema files", files.len());
 
-    Ok(combined_schema)
+    Ok(schema)
 }
 
 pub fn visit_ro_files<F>(dir: &PathBuf, callback: &mut F) -> anyhow::Result<()>
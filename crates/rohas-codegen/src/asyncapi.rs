@@ -0,0 +1,396 @@
+//! AsyncAPI 3.0 document describing a schema's events and websockets.
+//!
+//! Events become channels addressed by the event name, bound to the message
+//! broker the engine is configured with. Websockets become channels on the
+//! engine server, carrying their message type and the events forwarded to
+//! them.
+
+use rohas_parser::{FieldType, Schema, TokenSource};
+use serde_json::{json, Map, Value};
+
+/// Message broker events travel through.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Broker {
+    /// AsyncAPI protocol: `kafka`, `amqp`, `nats`, `sqs`, ...
+    pub protocol: String,
+    pub host: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocumentInfo {
+    pub title: String,
+    pub version: String,
+    /// Engine address (`host:port`) serving the websockets.
+    pub host: String,
+    /// None when events stay in process (memory adapter).
+    pub broker: Option<Broker>,
+}
+
+pub fn document(schema: &Schema, info: &DocumentInfo) -> Value {
+    let mut servers = Map::new();
+    servers.insert(
+        "engine".to_string(),
+        json!({
+            "host": info.host,
+            "protocol": "ws",
+            "description": "Rohas engine websocket endpoints",
+        }),
+    );
+    if let Some(broker) = &info.broker {
+        servers.insert(
+            "broker".to_string(),
+            json!({
+                "host": broker.host,
+                "protocol": broker.protocol,
+                "description": "Message broker carrying events",
+            }),
+        );
+    }
+
+    let mut channels = Map::new();
+    let mut operations = Map::new();
+    let mut messages = Map::new();
+
+    for event in &schema.events {
+        messages.insert(
+            event.name.clone(),
+            json!({
+                "name": event.name,
+                "contentType": "application/json",
+                "payload": type_schema(&FieldType::from_str(&event.payload)),
+            }),
+        );
+
+        // Per-event adapter overrides (`type: sqs`) take precedence.
+        let protocol = match event.adapter_type.as_deref() {
+            Some(adapter) => Some(adapter),
+            None => info.broker.as_ref().map(|b| b.protocol.as_str()),
+        };
+        let mut channel = json!({
+            "address": event.name,
+            "messages": { event.name.clone(): message_ref(&event.name) },
+        });
+        if info.broker.is_some() {
+            channel["servers"] = json!([{ "$ref": "#/servers/broker" }]);
+        }
+        if let Some(bindings) = protocol.and_then(|p| channel_bindings(p, &event.name)) {
+            channel["bindings"] = bindings;
+        }
+        channels.insert(event.name.clone(), channel);
+
+        let producers = producers(schema, &event.name);
+        if !producers.is_empty() {
+            operations.insert(
+                format!("publish{}", event.name),
+                json!({
+                    "action": "send",
+                    "channel": channel_ref(&event.name),
+                    "description": format!("Triggered by {}", producers.join(", ")),
+                    "messages": [channel_message_ref(&event.name, &event.name)],
+                }),
+            );
+        }
+        if !event.handlers.is_empty() {
+            operations.insert(
+                format!("on{}", event.name),
+                json!({
+                    "action": "receive",
+                    "channel": channel_ref(&event.name),
+                    "description": format!("Handled by {}", event.handlers.join(", ")),
+                    "messages": [channel_message_ref(&event.name, &event.name)],
+                }),
+            );
+        }
+    }
+
+    for ws in &schema.websockets {
+        let message_name = format!("{}Message", ws.name);
+        let payload = ws
+            .message
+            .as_deref()
+            .map(|message_type| type_schema(&FieldType::from_str(message_type)))
+            .unwrap_or_else(|| json!({}));
+        messages.insert(
+            message_name.clone(),
+            json!({
+                "name": message_name,
+                "contentType": "application/json",
+                "payload": payload,
+            }),
+        );
+
+        let forwarded: Vec<&str> = schema
+            .events
+            .iter()
+            .filter(|e| e.forward_to.as_ref().is_some_and(|f| f.websocket == ws.name))
+            .map(|e| e.name.as_str())
+            .collect();
+
+        let mut channel_messages = Map::new();
+        channel_messages.insert(message_name.clone(), message_ref(&message_name));
+        for event in &forwarded {
+            channel_messages.insert(event.to_string(), message_ref(event));
+        }
+
+        let mut channel = json!({
+            "address": ws.path,
+            "servers": [{ "$ref": "#/servers/engine" }],
+            "messages": channel_messages,
+        });
+        if let Some(auth) = &ws.auth {
+            let location = match auth.from {
+                TokenSource::Query => "query",
+                TokenSource::Header => "headers",
+            };
+            channel["bindings"] = json!({
+                "ws": {
+                    location: {
+                        "type": "object",
+                        "properties": { auth.name.clone(): { "type": "string" } },
+                        "required": [auth.name],
+                    },
+                    "bindingVersion": "0.1.0",
+                }
+            });
+        }
+        channels.insert(ws.name.clone(), channel);
+
+        operations.insert(
+            format!("send{}", ws.name),
+            json!({
+                "action": "receive",
+                "channel": channel_ref(&ws.name),
+                "description": "Messages clients send to the websocket",
+                "messages": [channel_message_ref(&ws.name, &message_name)],
+            }),
+        );
+        if ws.broadcast || !forwarded.is_empty() {
+            let mut pushed = Vec::new();
+            if ws.broadcast {
+                pushed.push(channel_message_ref(&ws.name, &message_name));
+            }
+            pushed.extend(forwarded.iter().map(|event| channel_message_ref(&ws.name, event)));
+            operations.insert(
+                format!("receive{}", ws.name),
+                json!({
+                    "action": "send",
+                    "channel": channel_ref(&ws.name),
+                    "description": "Messages the server pushes to connected clients",
+                    "messages": pushed,
+                }),
+            );
+        }
+    }
+
+    json!({
+        "asyncapi": "3.0.0",
+        "info": {
+            "title": info.title,
+            "version": info.version,
+        },
+        "defaultContentType": "application/json",
+        "servers": servers,
+        "channels": channels,
+        "operations": operations,
+        "components": {
+            "messages": messages,
+            "schemas": record_schemas(schema),
+        },
+    })
+}
+
+fn channel_ref(channel: &str) -> Value {
+    json!({ "$ref": format!("#/channels/{}", channel) })
+}
+
+fn channel_message_ref(channel: &str, message: &str) -> Value {
+    json!({ "$ref": format!("#/channels/{}/messages/{}", channel, message) })
+}
+
+fn message_ref(message: &str) -> Value {
+    json!({ "$ref": format!("#/components/messages/{}", message) })
+}
+
+/// Channel bindings for a broker protocol, where AsyncAPI defines any.
+fn channel_bindings(protocol: &str, event: &str) -> Option<Value> {
+    match protocol {
+        "kafka" => Some(json!({ "kafka": { "topic": event, "bindingVersion": "0.5.0" } })),
+        "amqp" => Some(json!({
+            "amqp": { "is": "queue", "queue": { "name": event }, "bindingVersion": "0.3.0" }
+        })),
+        "sqs" => Some(json!({ "sqs": { "queue": { "name": event }, "bindingVersion": "0.2.0" } })),
+        _ => None,
+    }
+}
+
+/// APIs, events, crons and websockets that trigger `event`.
+fn producers(schema: &Schema, event: &str) -> Vec<String> {
+    let triggers = |triggers: &[String]| triggers.iter().any(|t| t == event);
+    schema
+        .apis
+        .iter()
+        .filter(|a| triggers(&a.triggers))
+        .map(|a| a.name.clone())
+        .chain(schema.events.iter().filter(|e| triggers(&e.triggers)).map(|e| e.name.clone()))
+        .chain(schema.crons.iter().filter(|c| triggers(&c.triggers)).map(|c| c.name.clone()))
+        .chain(schema.websockets.iter().filter(|w| triggers(&w.triggers)).map(|w| w.name.clone()))
+        .collect()
+}
+
+/// JSON Schema of a field type; named types refer to `components/schemas`.
+fn type_schema(field_type: &FieldType) -> Value {
+    match field_type {
+        FieldType::Int => json!({ "type": "integer" }),
+        FieldType::Float => json!({ "type": "number" }),
+        FieldType::String => json!({ "type": "string" }),
+        FieldType::Boolean => json!({ "type": "boolean" }),
+        FieldType::DateTime => json!({ "type": "string", "format": "date-time" }),
+        FieldType::Json => json!({}),
+        FieldType::Bytes => json!({ "type": "string", "format": "byte" }),
+        FieldType::Array(inner) => json!({ "type": "array", "items": type_schema(inner) }),
+        FieldType::Custom(name) => json!({ "$ref": format!("#/components/schemas/{}", name) }),
+    }
+}
+
+/// A JSON Schema for every model, type and input.
+fn record_schemas(schema: &Schema) -> Map<String, Value> {
+    let records = schema
+        .models
+        .iter()
+        .map(|m| (&m.name, &m.fields, &m.example))
+        .chain(schema.types.iter().map(|t| (&t.name, &t.fields, &t.example)))
+        .chain(schema.inputs.iter().map(|i| (&i.name, &i.fields, &i.example)));
+
+    let mut schemas = Map::new();
+    for (name, fields, example) in records {
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+        for field in fields {
+            let mut property = type_schema(&field.field_type);
+            if field.computed().is_some() {
+                property["readOnly"] = json!(true);
+            }
+            properties.insert(field.name.clone(), property);
+            if !field.optional {
+                required.push(field.name.clone());
+            }
+        }
+        let mut record = json!({
+            "type": "object",
+            "properties": properties,
+            "required": required,
+        });
+        if let Some(example) = example {
+            record["examples"] = json!([example]);
+        }
+        schemas.insert(name.clone(), record);
+    }
+    schemas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rohas_parser::Parser;
+
+    const SCHEMA: &str = r#"
+model Order {
+  id       Int
+  customer String
+  note     String?
+}
+
+api CreateOrder {
+  method: POST
+  path: "/orders"
+  body: Order
+  response: Order
+  triggers: [OrderUpdated]
+}
+
+ws OrderFeed {
+  path: "/ws/orders"
+}
+
+event OrderUpdated {
+  payload: Order
+  handler: [notify_warehouse]
+  forward_to: OrderFeed
+}
+"#;
+
+    fn info(broker: Option<Broker>) -> DocumentInfo {
+        DocumentInfo {
+            title: "shop".to_string(),
+            version: "1.0.0".to_string(),
+            host: "127.0.0.1:3000".to_string(),
+            broker,
+        }
+    }
+
+    #[test]
+    fn test_events_and_websockets_become_channels() {
+        let schema = Parser::parse_string(SCHEMA).unwrap();
+        let doc = document(
+            &schema,
+            &info(Some(Broker {
+                protocol: "kafka".to_string(),
+                host: "localhost:9092".to_string(),
+            })),
+        );
+
+        assert_eq!(doc["asyncapi"], "3.0.0");
+        assert_eq!(doc["servers"]["broker"]["protocol"], "kafka");
+
+        let channel = &doc["channels"]["OrderUpdated"];
+        assert_eq!(channel["address"], "OrderUpdated");
+        assert_eq!(channel["bindings"]["kafka"]["topic"], "OrderUpdated");
+        assert_eq!(
+            doc["components"]["messages"]["OrderUpdated"]["payload"]["$ref"],
+            "#/components/schemas/Order"
+        );
+        assert_eq!(doc["operations"]["publishOrderUpdated"]["description"], "Triggered by CreateOrder");
+        assert_eq!(doc["operations"]["onOrderUpdated"]["action"], "receive");
+
+        let order = &doc["components"]["schemas"]["Order"];
+        assert_eq!(order["properties"]["id"]["type"], "integer");
+        assert_eq!(order["required"], json!(["id", "customer"]));
+
+        let ws = &doc["channels"]["OrderFeed"];
+        assert_eq!(ws["address"], "/ws/orders");
+        assert!(ws["messages"]["OrderUpdated"].is_object());
+        assert_eq!(
+            doc["operations"]["receiveOrderFeed"]["messages"][0]["$ref"],
+            "#/channels/OrderFeed/messages/OrderUpdated"
+        );
+    }
+
+    #[test]
+    fn test_memory_adapter_has_no_broker() {
+        let schema = Parser::parse_string(SCHEMA).unwrap();
+        let doc = document(&schema, &info(None));
+
+        assert!(doc["servers"].get("broker").is_none());
+        assert!(doc["channels"]["OrderUpdated"].get("bindings").is_none());
+        assert!(doc["channels"]["OrderUpdated"].get("servers").is_none());
+    }
+
+    #[test]
+    fn test_computed_fields_are_read_only() {
+        let schema = Parser::parse_string(
+            r#"
+model Customer {
+  first_name String
+  last_name  String
+  full_name  String @computed("first_name || ' ' || last_name")
+}
+"#,
+        )
+        .unwrap();
+        let doc = document(&schema, &info(None));
+
+        let customer = &doc["components"]["schemas"]["Customer"];
+        assert_eq!(customer["properties"]["full_name"]["readOnly"], true);
+        assert!(customer["properties"]["first_name"].get("readOnly").is_none());
+    }
+}
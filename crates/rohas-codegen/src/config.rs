@@ -21,16 +21,19 @@ pub fn generate_package_json(_schema: &Schema, output_dir: &Path) -> Result<()>
     "compile:watch": "rspack build --watch",
     "start": "node .rohas/index.js",
     "codegen": "rohas codegen",
-    "validate": "rohas validate"
+    "validate": "rohas validate",
+    "test": "vitest run"
   }},
   "dependencies": {{
+    "@rohas/sdk": "^0.1.0",
     "typescript": "^5.3.3",
     "zod": "^3.22.4"
   }},
   "devDependencies": {{
     "@types/node": "^20.10.0",
     "@rspack/cli": "^1.1.7",
-    "@rspack/core": "^1.1.7"
+    "@rspack/core": "^1.1.7",
+    "vitest": "^1.6.0"
   }},
   "engines": {{
     "node": ">=18.0.0"
@@ -40,7 +43,11 @@ pub fn generate_package_json(_schema: &Schema, output_dir: &Path) -> Result<()>
         project_name
     );
 
-    fs::write(project_root.join("package.json"), content)?;
+    // Projects add their own dependencies, such as workspace packages.
+    let package_json = project_root.join("package.json");
+    if !package_json.exists() {
+        fs::write(package_json, content)?;
+    }
     Ok(())
 }
 
@@ -83,27 +90,42 @@ pub fn generate_tsconfig_json(_schema: &Schema, output_dir: &Path) -> Result<()>
 }
 "#;
 
-    fs::write(project_root.join("tsconfig.json"), content)?;
+    // Projects may extend a shared config or add paths and references.
+    let tsconfig = project_root.join("tsconfig.json");
+    if !tsconfig.exists() {
+        fs::write(tsconfig, content)?;
+    }
     Ok(())
 }
 
-pub fn generate_requirements_txt(_schema: &Schema, output_dir: &Path) -> Result<()> {
+pub fn generate_requirements_txt(schema: &Schema, output_dir: &Path) -> Result<()> {
     let project_root = get_project_root(output_dir)?;
-    let content = r#"# Python dependencies for Rohas project
+    let mut content = r#"# Python dependencies for Rohas project
 # Add your project-specific dependencies here
 
 # Common dependencies
+rohas-sdk>=0.1.0
 pydantic>=2.0.0
 typing-extensions>=4.0.0
-"#;
+"#
+    .to_string();
+    if !schema.websockets.is_empty() {
+        // Used by the generated websocket clients.
+        content.push_str("websockets>=12.0\n");
+    }
 
     fs::write(project_root.join("requirements.txt"), content)?;
     Ok(())
 }
 
-pub fn generate_pyproject_toml(_schema: &Schema, output_dir: &Path) -> Result<()> {
+pub fn generate_pyproject_toml(schema: &Schema, output_dir: &Path) -> Result<()> {
     let project_root = get_project_root(output_dir)?;
     let project_name = extract_project_name(&project_root);
+    let websockets_dependency = if schema.websockets.is_empty() {
+        ""
+    } else {
+        "\n    \"websockets>=12.0\","
+    };
 
     let content = format!(
         r#"[project]
@@ -112,8 +134,9 @@ version = "0.1.0"
 description = "Rohas event-driven application"
 requires-python = ">=3.9"
 dependencies = [
+    "rohas-sdk>=0.1.0",
     "pydantic>=2.0.0",
-    "typing-extensions>=4.0.0",
+    "typing-extensions>=4.0.0",{}
 ]
 
 [project.optional-dependencies]
@@ -137,51 +160,100 @@ warn_unused_configs = true
 [tool.ruff]
 line-length = 100
 target-version = "py39"
+
+[tool.pytest.ini_options]
+pythonpath = ["src"]
+testpaths = ["tests"]
 "#,
-        project_name
+        project_name, websockets_dependency
     );
 
     fs::write(project_root.join("pyproject.toml"), content)?;
     Ok(())
 }
 
+/// Dependencies of the generated Rust crate and its handler group crates.
+const RUST_DEPENDENCIES: &str = r#"rohas-runtime = { version = "*", default-features = false }
+rohas-sdk = { version = "*" }
+serde = { version = "1.0", features = ["derive"] }
+serde_json = "1.0"
+tokio = { version = "1.0", features = ["full"] }
+chrono = { version = "0.4", features = ["serde"] }
+tracing = "0.1"
+"#;
+
 pub fn generate_cargo_toml(_schema: &Schema, output_dir: &Path) -> Result<()> {
     let project_root = get_project_root(output_dir)?;
     let project_name = extract_project_name(&project_root);
 
     let lib_name = project_name.replace('-', "_");
 
+    let members = crate::rust::workspace_members(&project_root);
+    let workspace = if members.is_empty() {
+        "[workspace]\n".to_string()
+    } else {
+        let members: Vec<String> = members.iter().map(|m| format!("\"{}\"", m)).collect();
+        format!("[workspace]\nmembers = [{}]\n", members.join(", "))
+    };
+
     let content = format!(
         r#"[package]
 name = "{}"
 version = "0.1.0"
 edition = "2021"
 
-[workspace]
-
+{}
 [lib]
 name = "{}"
 path = "src/lib.rs"
 
 [dependencies]
-rohas-runtime = {{ version = "*" }}
-serde = {{ version = "1.0", features = ["derive"] }}
-serde_json = "1.0"
-tokio = {{ version = "1.0", features = ["full"] }}
-chrono = {{ version = "0.4", features = ["serde"] }}
-tracing = "0.1"
-
+{}
 [dev-dependencies]
 tokio-test = "0.4"
 "#,
         project_name,
-        lib_name
+        workspace,
+        lib_name,
+        RUST_DEPENDENCIES
     );
 
     fs::write(project_root.join("Cargo.toml"), content)?;
     Ok(())
 }
 
+/// Write the manifest of a crate under `.rohas/crates`, whose root is
+/// `lib.rs` next to it. `dependencies` are added to the usual set.
+pub(crate) fn generate_member_cargo_toml(
+    crate_dir: &Path,
+    package: &str,
+    dylib: bool,
+    dependencies: &str,
+) -> Result<()> {
+    let crate_type = if dylib { "crate-type = [\"dylib\"]\n" } else { "" };
+    let content = format!(
+        r#"# Auto-generated by rohas codegen
+# DO NOT EDIT MANUALLY
+
+[package]
+name = "{}"
+version = "0.1.0"
+edition = "2021"
+publish = false
+
+[lib]
+path = "lib.rs"
+{}
+[dependencies]
+{}{}"#,
+        package, crate_type, RUST_DEPENDENCIES, dependencies
+    );
+
+    fs::create_dir_all(crate_dir)?;
+    fs::write(crate_dir.join("Cargo.toml"), content)?;
+    Ok(())
+}
+
 pub fn generate_gitignore(_schema: &Schema, output_dir: &Path) -> Result<()> {
     let project_root = get_project_root(output_dir)
         .map_err(|e| crate::error::CodegenError::GenerationFailed(format!(
@@ -361,6 +433,7 @@ Rohas event-driven application
 │       ├── api/    # API handlers
 │       ├── events/ # Event handlers
 │       └── cron/   # Cron job handlers
+├── tests/          # Handler tests (scaffolded with each new handler)
 └── config/         # Configuration files
 ```
 
@@ -411,7 +484,8 @@ The `src/generated/` directory contains auto-generated TypeScript types and inte
 1. Define your schema in `schema/` directory
 2. Run `rohas codegen` to generate types and handler stubs
 3. Implement your handler logic in `src/handlers/`
-4. Test with `rohas dev`
+4. Fill in the scaffolded tests in `tests/` and run them (`npm test`, `pytest` or `cargo test`)
+5. Try it out with `rohas dev`
 
 ## Configuration
 
@@ -463,6 +537,29 @@ pub fn generate_nvmrc(_schema: &Schema, output_dir: &Path) -> Result<()> {
     Ok(())
 }
 
+pub fn generate_vitest_config(_schema: &Schema, output_dir: &Path) -> Result<()> {
+    let project_root = get_project_root(output_dir)?;
+    let content = r#"import { defineConfig } from 'vitest/config';
+import path from 'path';
+
+export default defineConfig({
+  resolve: {
+    alias: {
+      '@generated': path.resolve(__dirname, 'src/generated'),
+      '@handlers': path.resolve(__dirname, 'src/handlers'),
+      '@': path.resolve(__dirname, 'src'),
+    },
+  },
+  test: {
+    include: ['tests/**/*.test.ts'],
+  },
+});
+"#;
+
+    fs::write(project_root.join("vitest.config.ts"), content)?;
+    Ok(())
+}
+
 pub fn generate_prettierrc(_schema: &Schema, output_dir: &Path) -> Result<()> {
     let project_root = get_project_root(output_dir)?;
     let content = r#"{
@@ -538,10 +635,10 @@ module.exports = {
   target: 'node',
   resolve: {
     extensions: ['.ts', '.tsx', '.js', '.jsx'],
-    alias: {
-      '@generated': path.resolve(__dirname, 'src/generated'),
-      '@handlers': path.resolve(__dirname, 'src/handlers'),
-      '@': path.resolve(__dirname, 'src'),
+    // Path aliases and project references come from tsconfig.json.
+    tsConfig: {
+      configFile: path.resolve(__dirname, 'tsconfig.json'),
+      references: 'auto',
     },
   },
   module: {
@@ -573,13 +670,23 @@ module.exports = {
     ],
   },
   externals: [
-    // Don't bundle node_modules, treat them as externals
-    function ({ request }, callback) {
-      // If it's a node module (starts with a letter/@ and not a relative path)
-      if (/^[a-z@]/i.test(request)) {
-        return callback(null, 'commonjs ' + request);
+    // Packages installed from a registry stay external. Path aliases and
+    // workspace packages resolve to source outside node_modules, so they are
+    // bundled like the handlers' own files.
+    function ({ context, request, getResolve }, callback) {
+      if (!/^[a-z@]/i.test(request)) {
+        return callback();
       }
-      callback();
+      getResolve()(context, request, (err, resolved) => {
+        if (err || !resolved) {
+          return callback(null, 'commonjs ' + request);
+        }
+        const real = fs.realpathSync(resolved);
+        if (real.split(path.sep).includes('node_modules')) {
+          return callback(null, 'commonjs ' + request);
+        }
+        callback();
+      });
     },
   ],
   devtool: 'source-map',
@@ -597,7 +704,7 @@ module.exports = {
     Ok(())
 }
 
-fn get_project_root(output_dir: &Path) -> Result<PathBuf> {
+pub(crate) fn get_project_root(output_dir: &Path) -> Result<PathBuf> {
     let project_root = if output_dir.file_name().and_then(|s| s.to_str()) == Some("src") {
         match output_dir.parent() {
             Some(parent) => {
@@ -656,7 +763,7 @@ fn get_project_root(output_dir: &Path) -> Result<PathBuf> {
     Ok(project_root)
 }
 
-fn extract_project_name(project_root: &Path) -> String {
+pub(crate) fn extract_project_name(project_root: &Path) -> String {
     project_root
         .file_name()
         .and_then(|s| s.to_str())
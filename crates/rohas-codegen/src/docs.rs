@@ -0,0 +1,640 @@
+//! Static HTML documentation site for a schema.
+//!
+//! Pages cover models, APIs with example requests and responses, an event
+//! catalog with the trigger chain of each event as a sequence diagram, and a
+//! graph of how declarations refer to one another. Diagrams are Mermaid
+//! sources rendered in the browser, so the site is plain files that can be
+//! published as-is (e.g. to GitHub Pages).
+
+use crate::error::Result;
+use crate::example::Example;
+use crate::graph::{base_type, Graph};
+use rohas_parser::{Api, Field, FieldType, Schema};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+const MERMAID: &str = "https://cdn.jsdelivr.net/npm/mermaid@11/dist/mermaid.esm.min.mjs";
+
+const PAGES: &[(&str, &str)] = &[
+    ("index.html", "Overview"),
+    ("models.html", "Models"),
+    ("apis.html", "APIs"),
+    ("events.html", "Events"),
+];
+
+const STYLE: &str = r#"body { margin: 0; font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", sans-serif; color: #1f2328; line-height: 1.5; }
+header { background: #24292f; padding: 0.75rem 2rem; display: flex; gap: 1.5rem; align-items: baseline; }
+header .title { color: #fff; font-weight: 600; font-size: 1.1rem; }
+header a { color: #d0d7de; text-decoration: none; }
+header a.active { color: #fff; border-bottom: 2px solid #fff; }
+main { max-width: 960px; margin: 0 auto; padding: 1rem 2rem 4rem; }
+section { border-top: 1px solid #d0d7de; padding-top: 0.5rem; margin-top: 2rem; }
+h2 .kind { font-size: 0.8rem; font-weight: normal; color: #57606a; margin-left: 0.5rem; }
+table { border-collapse: collapse; margin: 0.5rem 0; }
+th, td { border: 1px solid #d0d7de; padding: 0.3rem 0.75rem; text-align: left; vertical-align: top; }
+code, pre { font-family: ui-monospace, SFMono-Regular, Menlo, monospace; font-size: 0.85rem; }
+pre { background: #f6f8fa; padding: 0.75rem; overflow-x: auto; }
+.doc { white-space: pre-line; }
+.method { font-family: ui-monospace, monospace; font-weight: 600; padding: 0.1rem 0.4rem; border-radius: 4px; background: #ddf4ff; }
+.optional { color: #57606a; }
+"#;
+
+/// Writes the site into `output_dir`, titled `title`.
+pub fn build(schema: &Schema, output_dir: &Path, title: &str) -> Result<()> {
+    fs::create_dir_all(output_dir)?;
+    fs::write(output_dir.join("style.css"), STYLE)?;
+    // Serve files as they are on GitHub Pages.
+    fs::write(output_dir.join(".nojekyll"), "")?;
+
+    let pages = [
+        index_page(schema),
+        models_page(schema),
+        apis_page(schema),
+        events_page(schema),
+    ];
+    for ((file, heading), body) in PAGES.iter().zip(pages) {
+        fs::write(output_dir.join(file), layout(title, file, heading, &body))?;
+    }
+    Ok(())
+}
+
+fn layout(title: &str, current: &str, heading: &str, body: &str) -> String {
+    let nav: String = PAGES
+        .iter()
+        .map(|(file, label)| {
+            let class = if *file == current { " class=\"active\"" } else { "" };
+            format!("<a href=\"{}\"{}>{}</a>", file, class, label)
+        })
+        .collect();
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{heading} · {title}</title>
+<link rel="stylesheet" href="style.css">
+</head>
+<body>
+<header><span class="title">{title}</span>{nav}</header>
+<main>
+<h1>{heading}</h1>
+{body}</main>
+<script type="module">
+import mermaid from "{mermaid}";
+mermaid.initialize({{ startOnLoad: true }});
+</script>
+</body>
+</html>
+"#,
+        title = escape(title),
+        heading = heading,
+        nav = nav,
+        body = body,
+        mermaid = MERMAID,
+    )
+}
+
+fn index_page(schema: &Schema) -> String {
+    let counts = [
+        ("models.html", "Models", schema.models.len()),
+        ("models.html", "Types", schema.types.len()),
+        ("models.html", "Inputs", schema.inputs.len()),
+        ("apis.html", "APIs", schema.apis.len()),
+        ("events.html", "Events", schema.events.len()),
+        ("events.html", "Crons", schema.crons.len()),
+        ("events.html", "WebSockets", schema.websockets.len()),
+    ];
+    let mut out = String::from("<table>\n");
+    for (page, label, count) in counts {
+        out.push_str(&format!(
+            "<tr><td><a href=\"{}\">{}</a></td><td>{}</td></tr>\n",
+            page, label, count
+        ));
+    }
+    out.push_str("</table>\n<h2>Schema graph</h2>\n");
+    out.push_str(&mermaid(&Graph::from_schema(schema).to_mermaid()));
+    out
+}
+
+fn models_page(schema: &Schema) -> String {
+    let records = schema
+        .models
+        .iter()
+        .map(|m| ("model", &m.name, &m.doc, &m.fields, &m.example))
+        .chain(schema.types.iter().map(|t| ("type", &t.name, &t.doc, &t.fields, &t.example)))
+        .chain(schema.inputs.iter().map(|i| ("input", &i.name, &i.doc, &i.fields, &i.example)));
+
+    let mut out = String::new();
+    for (kind, name, doc, fields, example) in records {
+        out.push_str(&section_start(name, kind, doc.as_deref()));
+        out.push_str(&fields_table(schema, fields));
+        let example = match example {
+            Some(example) => example.clone(),
+            None => Example::for_fields(fields, schema).to_json(),
+        };
+        out.push_str("<h3>Example</h3>\n");
+        out.push_str(&json_block(&example));
+
+        let used_by = used_by(schema, name);
+        if !used_by.is_empty() {
+            out.push_str(&format!("<p>Used by: {}</p>\n", used_by.join(", ")));
+        }
+        out.push_str("</section>\n");
+    }
+    out
+}
+
+fn apis_page(schema: &Schema) -> String {
+    let mut out = String::new();
+    for api in &schema.apis {
+        out.push_str(&section_start(&api.name, "api", api.doc.as_deref()));
+        out.push_str(&format!(
+            "<p><span class=\"method\">{}</span> <code>{}</code></p>\n<table>\n",
+            api.method,
+            escape(&api.path)
+        ));
+        if let Some(body) = &api.body {
+            out.push_str(&row("Body", &type_link(schema, &FieldType::from_str(body))));
+        }
+        let response = type_link(schema, &FieldType::from_str(&api.response));
+        if api.paginated {
+            out.push_str(&row("Response", &format!("Page&lt;{}&gt;", response)));
+            out.push_str(&row("Query", "<code>limit</code>, <code>cursor</code>"));
+        } else {
+            out.push_str(&row("Response", &response));
+        }
+        if !api.consumes.is_json() {
+            out.push_str(&row("Consumes", &format!("<code>{}</code>", api.consumes.mime())));
+        }
+        if !api.produces.is_json() {
+            out.push_str(&row("Produces", &format!("<code>{}</code>", api.produces.mime())));
+        }
+        if !api.middlewares.is_empty() {
+            out.push_str(&row("Middlewares", &code_list(&api.middlewares)));
+        }
+        if !api.triggers.is_empty() {
+            out.push_str(&row("Triggers", &event_links(&api.triggers)));
+        }
+        out.push_str("</table>\n");
+
+        out.push_str("<h3>Example request</h3>\n");
+        out.push_str(&format!("<pre>{}</pre>\n", escape(&example_request(api, schema))));
+        out.push_str("<h3>Example response</h3>\n");
+        let response = match api.example.as_ref().and_then(|e| e.response.as_ref()) {
+            Some(response) => response.clone(),
+            None => Example::for_api_response(api, schema).to_json(),
+        };
+        out.push_str(&json_block(&response));
+
+        if !api.triggers.is_empty() {
+            out.push_str("<h3>Trigger chain</h3>\n");
+            let mut diagram = Sequence::new();
+            diagram.request("api", &api.name, &format!("{} {}", api.method, api.path));
+            diagram.triggers(schema, "api", &api.name, &api.triggers);
+            out.push_str(&mermaid(&diagram.finish()));
+        }
+        out.push_str("</section>\n");
+    }
+    out
+}
+
+fn events_page(schema: &Schema) -> String {
+    let mut out = String::new();
+    for event in &schema.events {
+        out.push_str(&section_start(&event.name, "event", event.doc.as_deref()));
+        out.push_str("<table>\n");
+        out.push_str(&row("Payload", &type_link(schema, &FieldType::from_str(&event.payload))));
+        if !event.handlers.is_empty() {
+            out.push_str(&row("Handlers", &code_list(&event.handlers)));
+        }
+        if !event.triggers.is_empty() {
+            out.push_str(&row("Triggers", &event_links(&event.triggers)));
+        }
+        if let Some(adapter) = &event.adapter_type {
+            out.push_str(&row("Adapter", &format!("<code>{}</code>", escape(adapter))));
+        }
+        if let Some(forward) = &event.forward_to {
+            let mut target = format!("<a href=\"#{0}\">{0}</a>", forward.websocket);
+            if let Some(filter) = &forward.filter {
+                target.push_str(&format!(" where <code>{}</code>", escape(filter)));
+            }
+            out.push_str(&row("Forwarded to", &target));
+        }
+        let producers = producers(schema, &event.name);
+        if !producers.is_empty() {
+            out.push_str(&row("Triggered by", &producers.join(", ")));
+        }
+        out.push_str("</table>\n");
+
+        out.push_str("<h3>Example payload</h3>\n");
+        out.push_str(&json_block(&Example::for_type(&event.payload, schema).to_json()));
+
+        out.push_str("<h3>Trigger chain</h3>\n");
+        let mut diagram = Sequence::new();
+        diagram.event(schema, &event.name, &mut BTreeSet::new());
+        out.push_str(&mermaid(&diagram.finish()));
+        out.push_str("</section>\n");
+    }
+
+    for cron in &schema.crons {
+        out.push_str(&section_start(&cron.name, "cron", cron.doc.as_deref()));
+        out.push_str("<table>\n");
+        out.push_str(&row("Schedule", &format!("<code>{}</code>", escape(&cron.schedule))));
+        if !cron.triggers.is_empty() {
+            out.push_str(&row("Triggers", &event_links(&cron.triggers)));
+        }
+        out.push_str("</table>\n");
+        if !cron.triggers.is_empty() {
+            let mut diagram = Sequence::new();
+            diagram.participant("cron", &cron.name);
+            diagram.triggers(schema, "cron", &cron.name, &cron.triggers);
+            out.push_str(&mermaid(&diagram.finish()));
+        }
+        out.push_str("</section>\n");
+    }
+
+    for ws in &schema.websockets {
+        out.push_str(&section_start(&ws.name, "websocket", ws.doc.as_deref()));
+        out.push_str(&format!("<p><code>{}</code></p>\n<table>\n", escape(&ws.path)));
+        if let Some(message) = &ws.message {
+            out.push_str(&row("Message", &type_link(schema, &FieldType::from_str(message))));
+        }
+        for (label, handlers) in [
+            ("On connect", &ws.on_connect),
+            ("On message", &ws.on_message),
+            ("On disconnect", &ws.on_disconnect),
+            ("Middlewares", &ws.middlewares),
+        ] {
+            if !handlers.is_empty() {
+                out.push_str(&row(label, &code_list(handlers)));
+            }
+        }
+        if let Some(auth) = &ws.auth {
+            out.push_str(&row(
+                "Auth",
+                &format!("<code>{}</code>, token in <code>{}</code>", escape(&auth.handler), escape(&auth.name)),
+            ));
+        }
+        if ws.broadcast {
+            out.push_str(&row("Broadcast", "yes"));
+        }
+        if !ws.triggers.is_empty() {
+            out.push_str(&row("Triggers", &event_links(&ws.triggers)));
+        }
+        out.push_str("</table>\n");
+        if !ws.triggers.is_empty() {
+            let mut diagram = Sequence::new();
+            diagram.request("ws", &ws.name, "message");
+            diagram.triggers(schema, "ws", &ws.name, &ws.triggers);
+            out.push_str(&mermaid(&diagram.finish()));
+        }
+        out.push_str("</section>\n");
+    }
+    out
+}
+
+/// Mermaid sequence diagram following events through their handlers and the
+/// events they trigger in turn.
+struct Sequence {
+    participants: Vec<String>,
+    lines: Vec<String>,
+}
+
+impl Sequence {
+    fn new() -> Self {
+        Self {
+            participants: Vec::new(),
+            lines: Vec::new(),
+        }
+    }
+
+    /// Declares a participant on first use and returns its id. Ids are
+    /// prefixed by kind so an API and an event may share a name.
+    fn participant(&mut self, kind: &str, name: &str) -> String {
+        let id = format!("{}_{}", kind, name);
+        let declaration = format!("participant {} as {}", id, name);
+        if !self.participants.contains(&declaration) {
+            self.participants.push(declaration);
+        }
+        id
+    }
+
+    fn line(&mut self, line: String) {
+        self.lines.push(line);
+    }
+
+    /// A client calling the API or websocket `name`.
+    fn request(&mut self, kind: &str, name: &str, label: &str) {
+        self.participants.push("actor Client".to_string());
+        let target = self.participant(kind, name);
+        self.line(format!("Client->>{}: {}", target, label));
+    }
+
+    fn triggers(&mut self, schema: &Schema, kind: &str, name: &str, events: &[String]) {
+        let source = self.participant(kind, name);
+        let mut visited = BTreeSet::new();
+        for event in events {
+            let target = self.participant("event", event);
+            self.line(format!("{}-){}: trigger", source, target));
+            self.event(schema, event, &mut visited);
+        }
+    }
+
+    /// Handlers of `name`, then the events it triggers. Cycles stop at the
+    /// first repeated event.
+    fn event(&mut self, schema: &Schema, name: &str, visited: &mut BTreeSet<String>) {
+        let source = self.participant("event", name);
+        if !visited.insert(name.to_string()) {
+            return;
+        }
+        let Some(event) = schema.events.iter().find(|e| e.name == name) else {
+            return;
+        };
+        for handler in &event.handlers {
+            let target = self.participant("handler", handler);
+            self.line(format!("{}->>{}: {}", source, target, event.payload));
+        }
+        if let Some(forward) = &event.forward_to {
+            let target = self.participant("ws", &forward.websocket);
+            self.line(format!("{}-){}: forward", source, target));
+        }
+        for next in &event.triggers {
+            let target = self.participant("event", next);
+            self.line(format!("{}-){}: trigger", source, target));
+            self.event(schema, next, visited);
+        }
+    }
+
+    fn finish(self) -> String {
+        let mut out = String::from("sequenceDiagram\n");
+        for line in self.participants.iter().chain(&self.lines) {
+            out.push_str("    ");
+            out.push_str(line);
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Declarations referring to the record `name`.
+fn used_by(schema: &Schema, name: &str) -> Vec<String> {
+    let refers = |type_name: &str| base_type(&FieldType::from_str(type_name)).is_some_and(|t| t == name);
+    let mut users = Vec::new();
+    for api in &schema.apis {
+        if api.body.as_deref().is_some_and(refers) || refers(&api.response) {
+            users.push(format!("<a href=\"apis.html#{0}\">{0}</a>", api.name));
+        }
+    }
+    for event in &schema.events {
+        if refers(&event.payload) {
+            users.push(format!("<a href=\"events.html#{0}\">{0}</a>", event.name));
+        }
+    }
+    for ws in &schema.websockets {
+        if ws.message.as_deref().is_some_and(refers) {
+            users.push(format!("<a href=\"events.html#{0}\">{0}</a>", ws.name));
+        }
+    }
+    users
+}
+
+/// APIs, events, crons and websockets that trigger `event`, as links.
+fn producers(schema: &Schema, event: &str) -> Vec<String> {
+    let triggers = |triggers: &[String]| triggers.iter().any(|t| t == event);
+    schema
+        .apis
+        .iter()
+        .filter(|a| triggers(&a.triggers))
+        .map(|a| format!("<a href=\"apis.html#{0}\">{0}</a>", a.name))
+        .chain(
+            schema
+                .events
+                .iter()
+                .filter(|e| triggers(&e.triggers))
+                .map(|e| format!("<a href=\"#{0}\">{0}</a>", e.name)),
+        )
+        .chain(
+            schema
+                .crons
+                .iter()
+                .filter(|c| triggers(&c.triggers))
+                .map(|c| format!("<a href=\"#{0}\">{0}</a>", c.name)),
+        )
+        .chain(
+            schema
+                .websockets
+                .iter()
+                .filter(|w| triggers(&w.triggers))
+                .map(|w| format!("<a href=\"#{0}\">{0}</a>", w.name)),
+        )
+        .collect()
+}
+
+/// `curl` invocation for an API, with path parameters and body filled in
+/// from its examples.
+fn example_request(api: &Api, schema: &Schema) -> String {
+    let path: Vec<String> = api
+        .path
+        .split('/')
+        .map(|segment| match segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+            Some(_) => "1".to_string(),
+            None => segment.to_string(),
+        })
+        .collect();
+    let mut request = format!("curl -X {} http://localhost:3000{}", api.method, path.join("/"));
+    if !api.consumes.is_json() {
+        return format!("{} \\\n  -H 'Content-Type: {}' \\\n  --data-binary @body", request, api.consumes.mime());
+    }
+    if let Some(body) = Example::for_api_body(api, schema) {
+        let body = serde_json::to_string(&body.to_json()).unwrap_or_default();
+        request.push_str(&format!(
+            " \\\n  -H 'Content-Type: application/json' \\\n  -d '{}'",
+            body.replace('\'', "'\\''")
+        ));
+    }
+    request
+}
+
+fn section_start(name: &str, kind: &str, doc: Option<&str>) -> String {
+    let mut out = format!(
+        "<section id=\"{0}\">\n<h2>{0}<span class=\"kind\">{1}</span></h2>\n",
+        name, kind
+    );
+    if let Some(doc) = doc {
+        out.push_str(&format!("<p class=\"doc\">{}</p>\n", escape(doc)));
+    }
+    out
+}
+
+fn fields_table(schema: &Schema, fields: &[Field]) -> String {
+    let mut out = String::from("<table>\n<tr><th>Field</th><th>Type</th><th>Description</th></tr>\n");
+    for field in fields {
+        let mut field_type = type_link(schema, &field.field_type);
+        if field.optional {
+            field_type.push_str(" <span class=\"optional\">optional</span>");
+        }
+        let attributes: Vec<String> = field
+            .attributes
+            .iter()
+            .map(|a| {
+                if a.args.is_empty() {
+                    format!("<code>@{}</code>", a.name)
+                } else {
+                    format!("<code>@{}({})</code>", a.name, escape(&a.args.join(", ")))
+                }
+            })
+            .collect();
+        let mut description = field.doc.as_deref().map(escape).unwrap_or_default();
+        if !attributes.is_empty() {
+            if !description.is_empty() {
+                description.push(' ');
+            }
+            description.push_str(&attributes.join(" "));
+        }
+        out.push_str(&format!(
+            "<tr><td><code>{}</code></td><td>{}</td><td class=\"doc\">{}</td></tr>\n",
+            field.name, field_type, description
+        ));
+    }
+    out.push_str("</table>\n");
+    out
+}
+
+/// A field type, linking named records to their section.
+fn type_link(schema: &Schema, field_type: &FieldType) -> String {
+    match field_type {
+        FieldType::Array(inner) => format!("{}[]", type_link(schema, inner)),
+        FieldType::Custom(name) => {
+            let is_record = schema.models.iter().any(|m| &m.name == name)
+                || schema.types.iter().any(|t| &t.name == name)
+                || schema.inputs.iter().any(|i| &i.name == name);
+            if is_record {
+                format!("<a href=\"models.html#{0}\"><code>{0}</code></a>", name)
+            } else {
+                format!("<code>{}</code>", escape(name))
+            }
+        }
+        FieldType::Int => "<code>Int</code>".to_string(),
+        FieldType::Float => "<code>Float</code>".to_string(),
+        FieldType::String => "<code>String</code>".to_string(),
+        FieldType::Boolean => "<code>Boolean</code>".to_string(),
+        FieldType::DateTime => "<code>DateTime</code>".to_string(),
+        FieldType::Json => "<code>Json</code>".to_string(),
+        FieldType::Bytes => "<code>Bytes</code>".to_string(),
+    }
+}
+
+fn event_links(events: &[String]) -> String {
+    events
+        .iter()
+        .map(|e| format!("<a href=\"events.html#{0}\">{0}</a>", e))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn code_list(items: &[String]) -> String {
+    items
+        .iter()
+        .map(|item| format!("<code>{}</code>", escape(item)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn row(label: &str, value: &str) -> String {
+    format!("<tr><th>{}</th><td>{}</td></tr>\n", label, value)
+}
+
+fn json_block(value: &serde_json::Value) -> String {
+    let json = serde_json::to_string_pretty(value).unwrap_or_default();
+    format!("<pre>{}</pre>\n", escape(&json))
+}
+
+fn mermaid(diagram: &str) -> String {
+    format!("<pre class=\"mermaid\">\n{}</pre>\n", escape(diagram))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rohas_parser::Parser;
+
+    const SCHEMA: &str = r#"
+/// A customer order.
+model Order {
+  /// Order number.
+  id       Int
+  customer String
+  lines    OrderLine[]
+}
+
+type OrderLine {
+  sku: String
+  quantity: Int
+}
+
+/// Places an order.
+api CreateOrder {
+  method: POST
+  path: "/orders"
+  body: Order
+  response: Order
+  triggers: [OrderPlaced]
+}
+
+event OrderPlaced {
+  payload: Order
+  handler: [reserve_stock]
+  triggers: [StockReserved]
+}
+
+event StockReserved {
+  payload: Order
+  handler: [notify_customer]
+  triggers: [OrderPlaced]
+}
+"#;
+
+    #[test]
+    fn test_trigger_chain_follows_events_once() {
+        let schema = Parser::parse_string(SCHEMA).unwrap();
+        let mut diagram = Sequence::new();
+        diagram.triggers(&schema, "api", "CreateOrder", &["OrderPlaced".to_string()]);
+        let diagram = diagram.finish();
+
+        assert!(diagram.contains("participant api_CreateOrder as CreateOrder"));
+        assert!(diagram.contains("api_CreateOrder-)event_OrderPlaced: trigger"));
+        assert!(diagram.contains("event_OrderPlaced->>handler_reserve_stock: Order"));
+        assert!(diagram.contains("event_StockReserved->>handler_notify_customer: Order"));
+        // The cycle back to OrderPlaced is drawn once and not followed again.
+        assert_eq!(diagram.matches("->>handler_reserve_stock").count(), 1);
+        assert!(diagram.contains("event_StockReserved-)event_OrderPlaced: trigger"));
+    }
+
+    #[test]
+    fn test_build_writes_pages_with_docs() {
+        let schema = Parser::parse_string(SCHEMA).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        build(&schema, dir.path(), "shop").unwrap();
+
+        let models = fs::read_to_string(dir.path().join("models.html")).unwrap();
+        assert!(models.contains("A customer order."));
+        assert!(models.contains("Order number."));
+        assert!(models.contains("<a href=\"models.html#OrderLine\"><code>OrderLine</code></a>[]"));
+
+        let apis = fs::read_to_string(dir.path().join("apis.html")).unwrap();
+        assert!(apis.contains("Places an order."));
+        assert!(apis.contains("curl -X POST http://localhost:3000/orders"));
+        let index = fs::read_to_string(dir.path().join("index.html")).unwrap();
+        assert!(index.contains("api_CreateOrder -- body --&gt; model_Order"));
+        assert!(dir.path().join("events.html").exists());
+    }
+}
@@ -0,0 +1,517 @@
+//! Avro and Protobuf definitions of event payloads, for consumers on an
+//! existing Kafka stack (`rohas codegen --events-format avro|proto`).
+//!
+//! Protobuf field numbers are kept in `field-numbers.json` next to the
+//! generated `.proto` file, with the type each was assigned for, so a field
+//! keeps its number across runs while its type is unchanged and the numbers
+//! of removed or retyped fields are reserved instead of reused.
+
+use crate::error::Result;
+use rohas_parser::{Field, FieldType, Schema};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+const NAMESPACE: &str = "rohas.events";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventFormat {
+    Avro,
+    Proto,
+}
+
+impl EventFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "avro" => Some(EventFormat::Avro),
+            "proto" | "protobuf" => Some(EventFormat::Proto),
+            _ => None,
+        }
+    }
+}
+
+/// Writes the event payload definitions to `<output_dir>/schemas/avro` or
+/// `<output_dir>/schemas/proto`.
+pub fn export(schema: &Schema, output_dir: &Path, format: EventFormat) -> Result<()> {
+    match format {
+        EventFormat::Avro => {
+            let dir = output_dir.join("schemas/avro");
+            fs::create_dir_all(&dir)?;
+            for event in &schema.events {
+                let avsc = avro_schema(schema, &event.payload);
+                fs::write(
+                    dir.join(format!("{}.avsc", event.name)),
+                    serde_json::to_string_pretty(&avsc)? + "\n",
+                )?;
+            }
+        }
+        EventFormat::Proto => {
+            let dir = output_dir.join("schemas/proto");
+            fs::create_dir_all(&dir)?;
+            let numbers_path = dir.join("field-numbers.json");
+            let mut numbers = match fs::read_to_string(&numbers_path) {
+                Ok(content) => serde_json::from_str(&content)?,
+                Err(_) => FieldNumbers::default(),
+            };
+            let proto = proto_file(schema, &mut numbers);
+            fs::write(dir.join("events.proto"), proto)?;
+            fs::write(&numbers_path, serde_json::to_string_pretty(&numbers)? + "\n")?;
+        }
+    }
+    Ok(())
+}
+
+/// Fields of a model, type or input named `name`.
+fn record_fields<'a>(schema: &'a Schema, name: &str) -> Option<&'a [Field]> {
+    schema
+        .models
+        .iter()
+        .find(|m| m.name == name)
+        .map(|m| m.fields.as_slice())
+        .or_else(|| schema.types.iter().find(|t| t.name == name).map(|t| t.fields.as_slice()))
+        .or_else(|| schema.inputs.iter().find(|i| i.name == name).map(|i| i.fields.as_slice()))
+}
+
+/// The Avro schema of an event payload type. Records are defined where they
+/// are first used and referenced by name afterwards.
+fn avro_schema(schema: &Schema, payload: &str) -> Value {
+    let mut defined = BTreeSet::new();
+    avro_type(schema, &FieldType::from_str(payload), &mut defined)
+}
+
+fn avro_type(schema: &Schema, field_type: &FieldType, defined: &mut BTreeSet<String>) -> Value {
+    match field_type {
+        FieldType::Int => json!("long"),
+        FieldType::Float => json!("double"),
+        FieldType::String => json!("string"),
+        FieldType::Boolean => json!("boolean"),
+        FieldType::DateTime => json!({ "type": "long", "logicalType": "timestamp-millis" }),
+        // Free-form JSON travels as its serialized text.
+        FieldType::Json => json!("string"),
+        FieldType::Bytes => json!("bytes"),
+        FieldType::Array(inner) => json!({ "type": "array", "items": avro_type(schema, inner, defined) }),
+        FieldType::Custom(name) => {
+            let Some(fields) = record_fields(schema, name) else {
+                return json!("string");
+            };
+            if !defined.insert(name.clone()) {
+                return json!(format!("{}.{}", NAMESPACE, name));
+            }
+            let fields: Vec<Value> = fields
+                .iter()
+                .map(|field| {
+                    let field_type = avro_type(schema, &field.field_type, defined);
+                    if field.optional {
+                        json!({ "name": field.name, "type": ["null", field_type], "default": null })
+                    } else {
+                        json!({ "name": field.name, "type": field_type })
+                    }
+                })
+                .collect();
+            json!({
+                "type": "record",
+                "name": name,
+                "namespace": NAMESPACE,
+                "fields": fields,
+            })
+        }
+    }
+}
+
+/// Protobuf field numbers assigned so far, by message and field name.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FieldNumbers {
+    #[serde(default)]
+    pub messages: BTreeMap<String, MessageNumbers>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MessageNumbers {
+    #[serde(default)]
+    pub fields: BTreeMap<String, FieldNumber>,
+    /// Fields that were removed, with the number and type they had.
+    #[serde(default)]
+    pub reserved: BTreeMap<String, FieldNumber>,
+    /// Numbers of fields whose type changed, which are never used again.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub retired: BTreeSet<u32>,
+}
+
+/// A field's number and the protobuf type it was assigned for.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "StoredFieldNumber")]
+pub struct FieldNumber {
+    pub number: u32,
+    /// Unknown for numbers written before types were kept.
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub field_type: Option<String>,
+}
+
+/// `field-numbers.json` entries, which used to be bare numbers.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredFieldNumber {
+    Number(u32),
+    Field {
+        number: u32,
+        #[serde(rename = "type", default)]
+        field_type: Option<String>,
+    },
+}
+
+impl From<StoredFieldNumber> for FieldNumber {
+    fn from(stored: StoredFieldNumber) -> Self {
+        match stored {
+            StoredFieldNumber::Number(number) => Self { number, field_type: None },
+            StoredFieldNumber::Field { number, field_type } => Self { number, field_type },
+        }
+    }
+}
+
+impl MessageNumbers {
+    /// Numbers `fields`, given as name and protobuf type, in order. Known
+    /// fields keep their number while their type stays the same, and the
+    /// numbers of removed fields are reserved. A field that changes type
+    /// gets a new number, since the old one is encoded differently on the wire.
+    fn assign(&mut self, fields: &[(&str, String)]) -> Vec<u32> {
+        let removed: Vec<String> = self
+            .fields
+            .keys()
+            .filter(|name| !fields.iter().any(|(field, _)| field == name))
+            .cloned()
+            .collect();
+        for name in removed {
+            if let Some(number) = self.fields.remove(&name) {
+                self.reserved.insert(name, number);
+            }
+        }
+
+        let mut next = self
+            .fields
+            .values()
+            .chain(self.reserved.values())
+            .map(|field| field.number)
+            .chain(self.retired.iter().copied())
+            .max()
+            .unwrap_or(0)
+            + 1;
+        fields
+            .iter()
+            .map(|(name, field_type)| {
+                if let Some(known) = self.fields.get_mut(*name) {
+                    // Files written before types were kept: assume unchanged.
+                    let known_type = known.field_type.get_or_insert_with(|| field_type.clone());
+                    if known_type == field_type {
+                        return known.number;
+                    }
+                    self.retired.insert(known.number);
+                } else if let Some(reserved) = self.reserved.remove(*name) {
+                    // A field that comes back with its old type gets its old number.
+                    if reserved.field_type.as_ref() == Some(field_type) {
+                        self.fields.insert(name.to_string(), reserved.clone());
+                        return reserved.number;
+                    }
+                    self.retired.insert(reserved.number);
+                }
+
+                next += 1;
+                let number = next - 1;
+                self.fields.insert(
+                    name.to_string(),
+                    FieldNumber {
+                        number,
+                        field_type: Some(field_type.clone()),
+                    },
+                );
+                number
+            })
+            .collect()
+    }
+}
+
+/// Records reachable from the event payloads, in order of first use.
+fn payload_records(schema: &Schema) -> Vec<String> {
+    fn visit(schema: &Schema, field_type: &FieldType, records: &mut Vec<String>) {
+        match field_type {
+            FieldType::Array(inner) => visit(schema, inner, records),
+            FieldType::Custom(name) => {
+                if records.contains(name) {
+                    return;
+                }
+                if let Some(fields) = record_fields(schema, name) {
+                    records.push(name.clone());
+                    for field in fields {
+                        visit(schema, &field.field_type, records);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut records = Vec::new();
+    for event in &schema.events {
+        visit(schema, &FieldType::from_str(&event.payload), &mut records);
+    }
+    records
+}
+
+fn proto_type(schema: &Schema, field_type: &FieldType) -> String {
+    match field_type {
+        FieldType::Int => "int64".to_string(),
+        FieldType::Float => "double".to_string(),
+        FieldType::String => "string".to_string(),
+        FieldType::Boolean => "bool".to_string(),
+        FieldType::DateTime => "google.protobuf.Timestamp".to_string(),
+        FieldType::Json => "google.protobuf.Value".to_string(),
+        FieldType::Bytes => "bytes".to_string(),
+        // Nested lists have no direct protobuf equivalent.
+        FieldType::Array(_) => "google.protobuf.ListValue".to_string(),
+        FieldType::Custom(name) if record_fields(schema, name).is_some() => name.clone(),
+        FieldType::Custom(_) => "string".to_string(),
+    }
+}
+
+fn proto_field(schema: &Schema, field: &Field, number: u32) -> String {
+    match &field.field_type {
+        FieldType::Array(inner) => format!(
+            "  repeated {} {} = {};\n",
+            proto_type(schema, inner),
+            field.name,
+            number
+        ),
+        FieldType::Custom(_) | FieldType::DateTime | FieldType::Json => {
+            format!("  {} {} = {};\n", proto_type(schema, &field.field_type), field.name, number)
+        }
+        field_type if field.optional => {
+            format!("  optional {} {} = {};\n", proto_type(schema, field_type), field.name, number)
+        }
+        field_type => format!("  {} {} = {};\n", proto_type(schema, field_type), field.name, number),
+    }
+}
+
+/// The protobuf type of `field` as far as the wire format is concerned.
+fn proto_wire_type(schema: &Schema, field: &Field) -> String {
+    match &field.field_type {
+        FieldType::Array(inner) => format!("repeated {}", proto_type(schema, inner)),
+        field_type => proto_type(schema, field_type),
+    }
+}
+
+fn proto_message(schema: &Schema, name: &str, fields: &[Field], numbers: &mut FieldNumbers) -> String {
+    let message_numbers = numbers.messages.entry(name.to_string()).or_default();
+    let typed: Vec<(&str, String)> = fields
+        .iter()
+        .map(|f| (f.name.as_str(), proto_wire_type(schema, f)))
+        .collect();
+    let assigned = message_numbers.assign(&typed);
+
+    let mut content = format!("message {} {{\n", name);
+    let reserved_numbers: BTreeSet<u32> = message_numbers
+        .reserved
+        .values()
+        .map(|field| field.number)
+        .chain(message_numbers.retired.iter().copied())
+        .collect();
+    if !reserved_numbers.is_empty() {
+        let reserved_numbers: Vec<String> = reserved_numbers.iter().map(|n| n.to_string()).collect();
+        content.push_str(&format!("  reserved {};\n", reserved_numbers.join(", ")));
+    }
+    if !message_numbers.reserved.is_empty() {
+        let reserved_names: Vec<String> = message_numbers.reserved.keys().map(|n| format!("\"{}\"", n)).collect();
+        content.push_str(&format!("  reserved {};\n", reserved_names.join(", ")));
+    }
+    for (field, number) in fields.iter().zip(assigned) {
+        content.push_str(&proto_field(schema, field, number));
+    }
+    content.push_str("}\n");
+    content
+}
+
+/// `events.proto`: a message per record reachable from an event payload,
+/// and a wrapper message for events whose payload is a primitive.
+fn proto_file(schema: &Schema, numbers: &mut FieldNumbers) -> String {
+    let mut content = String::new();
+    content.push_str("// Generated by Rohas - Do not edit\n");
+    content.push_str("// Field numbers are kept in field-numbers.json; commit it with this file.\n\n");
+    content.push_str("syntax = \"proto3\";\n\n");
+    content.push_str(&format!("package {};\n\n", NAMESPACE));
+    content.push_str("import \"google/protobuf/struct.proto\";\n");
+    content.push_str("import \"google/protobuf/timestamp.proto\";\n");
+
+    content.push_str("\n// Payload message of each event:\n");
+    for event in &schema.events {
+        let message = match FieldType::from_str(&event.payload) {
+            FieldType::Custom(name) if record_fields(schema, &name).is_some() => name,
+            _ => format!("{}Payload", event.name),
+        };
+        content.push_str(&format!("//   {} -> {}\n", event.name, message));
+    }
+
+    for record in payload_records(schema) {
+        let fields = record_fields(schema, &record).unwrap_or_default();
+        content.push('\n');
+        content.push_str(&proto_message(schema, &record, fields, numbers));
+    }
+
+    for event in &schema.events {
+        let payload = FieldType::from_str(&event.payload);
+        if matches!(&payload, FieldType::Custom(name) if record_fields(schema, name).is_some()) {
+            continue;
+        }
+        let value = Field {
+            name: "value".to_string(),
+            field_type: payload,
+            optional: false,
+            attributes: Vec::new(),
+            doc: None,
+        };
+        content.push('\n');
+        content.push_str(&proto_message(
+            schema,
+            &format!("{}Payload", event.name),
+            std::slice::from_ref(&value),
+            numbers,
+        ));
+    }
+
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rohas_parser::Parser;
+
+    const SCHEMA: &str = r#"
+model Address {
+  street String
+  city   String
+}
+
+model User {
+  id        Int
+  email     String
+  nickname  String?
+  tags      String[]
+  address   Address
+  createdAt DateTime
+}
+
+event UserCreated {
+  payload: User
+  handler: [notify]
+}
+
+event Ping {
+  payload: String
+  handler: [pong]
+}
+"#;
+
+    #[test]
+    fn test_avro_schema_defines_records_once() {
+        let schema = Parser::parse_string(SCHEMA).unwrap();
+        let avsc = avro_schema(&schema, "User");
+
+        assert_eq!(avsc["type"], "record");
+        assert_eq!(avsc["namespace"], NAMESPACE);
+        let fields = avsc["fields"].as_array().unwrap();
+        assert_eq!(fields[0], json!({ "name": "id", "type": "long" }));
+        assert_eq!(
+            fields[2],
+            json!({ "name": "nickname", "type": ["null", "string"], "default": null })
+        );
+        assert_eq!(fields[3]["type"], json!({ "type": "array", "items": "string" }));
+        assert_eq!(fields[4]["type"]["name"], "Address");
+        assert_eq!(fields[5]["type"]["logicalType"], "timestamp-millis");
+
+        assert_eq!(avro_schema(&schema, "String"), json!("string"));
+    }
+
+    #[test]
+    fn test_proto_field_numbers_are_stable() {
+        let schema = Parser::parse_string(SCHEMA).unwrap();
+        let mut numbers = FieldNumbers::default();
+        let proto = proto_file(&schema, &mut numbers);
+
+        assert!(proto.contains("message User {\n  int64 id = 1;\n  string email = 2;\n  optional string nickname = 3;\n  repeated string tags = 4;\n  Address address = 5;\n  google.protobuf.Timestamp createdAt = 6;\n}"));
+        assert!(proto.contains("message PingPayload {\n  string value = 1;\n}"));
+        assert!(proto.contains("//   UserCreated -> User"));
+
+        // Dropping `nickname` and adding `age` keeps the other numbers and
+        // reserves the old one.
+        let changed = SCHEMA.replace("  nickname  String?\n", "").replace("  id        Int\n", "  id        Int\n  age       Int\n");
+        let schema = Parser::parse_string(&changed).unwrap();
+        let proto = proto_file(&schema, &mut numbers);
+        assert!(proto.contains("message User {\n  reserved 3;\n  reserved \"nickname\";\n  int64 id = 1;\n  int64 age = 7;\n  string email = 2;\n"));
+
+        // Restoring `nickname` brings back its number.
+        let schema = Parser::parse_string(SCHEMA).unwrap();
+        let proto = proto_file(&schema, &mut numbers);
+        assert!(proto.contains("  optional string nickname = 3;\n"));
+        assert!(!proto.contains("reserved 3"));
+    }
+
+    #[test]
+    fn test_export_persists_field_numbers() {
+        let schema = Parser::parse_string(SCHEMA).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+
+        export(&schema, dir.path(), EventFormat::Proto).unwrap();
+        export(&schema, dir.path(), EventFormat::Avro).unwrap();
+
+        let numbers: FieldNumbers = serde_json::from_str(
+            &fs::read_to_string(dir.path().join("schemas/proto/field-numbers.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(numbers.messages["User"].fields["createdAt"].number, 6);
+        assert_eq!(
+            numbers.messages["User"].fields["createdAt"].field_type.as_deref(),
+            Some("google.protobuf.Timestamp")
+        );
+        assert!(dir.path().join("schemas/avro/UserCreated.avsc").exists());
+        assert!(dir.path().join("schemas/avro/Ping.avsc").exists());
+    }
+
+    #[test]
+    fn test_proto_field_numbers_follow_types() {
+        let schema = Parser::parse_string(SCHEMA).unwrap();
+        let mut numbers = FieldNumbers::default();
+        proto_file(&schema, &mut numbers);
+
+        // Retyping a field retires its number instead of reusing it.
+        let retyped = SCHEMA.replace("  email     String\n", "  email     Int\n");
+        let proto = proto_file(&Parser::parse_string(&retyped).unwrap(), &mut numbers);
+        assert!(proto.contains("message User {\n  reserved 2;\n  int64 id = 1;\n  int64 email = 7;\n"));
+
+        // A removed field that returns with another type gets a new number.
+        let removed = retyped.replace("  nickname  String?\n", "");
+        proto_file(&Parser::parse_string(&removed).unwrap(), &mut numbers);
+        let returned = retyped.replace("  nickname  String?\n", "  nickname  Int?\n");
+        let proto = proto_file(&Parser::parse_string(&returned).unwrap(), &mut numbers);
+        assert!(proto.contains("  reserved 2, 3;\n"));
+        assert!(proto.contains("  optional int64 nickname = 8;\n"));
+        assert!(!proto.contains("reserved \"nickname\""));
+    }
+
+    #[test]
+    fn test_field_numbers_without_types_are_read() {
+        let mut numbers: FieldNumbers = serde_json::from_str(
+            r#"{ "messages": { "User": { "fields": { "id": 1, "email": 2 }, "reserved": { "nickname": 3 } } } }"#,
+        )
+        .unwrap();
+        let user = numbers.messages.get_mut("User").unwrap();
+        let fields = [
+            ("id", "int64".to_string()),
+            ("email", "string".to_string()),
+            ("nickname", "string".to_string()),
+        ];
+        // Reserved numbers of unknown type are not reused.
+        assert_eq!(user.assign(&fields), vec![1, 2, 4]);
+        assert_eq!(user.fields["email"].field_type.as_deref(), Some("string"));
+        assert!(user.retired.contains(&3));
+    }
+}
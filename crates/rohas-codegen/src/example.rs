@@ -0,0 +1,357 @@
+//! Example values derived from schema types, used to fill in generated tests.
+//! Values from `example:` blocks in the schema take precedence.
+
+use rohas_parser::{Api, Field, FieldType, Schema};
+use serde_json::{Map, Value};
+
+/// How deep nested models are expanded before falling back to `null`.
+const MAX_DEPTH: usize = 3;
+
+const EXAMPLE_DATETIME: &str = "2024-01-01T00:00:00Z";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Example {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    DateTime(String),
+    Bytes(Vec<u8>),
+    Array(Vec<Example>),
+    Object(Vec<(String, Example)>),
+}
+
+impl Example {
+    /// Builds an example for a type name as written in the schema
+    /// (`String`, `User`, ...).
+    pub fn for_type(type_name: &str, schema: &Schema) -> Self {
+        Self::for_field_type(&FieldType::from_str(type_name), "", schema, 0)
+    }
+
+    /// Builds an example request for an API, shaped like the generated
+    /// TypeScript and Python request types: path parameters plus `body`.
+    pub fn for_api_request(api: &Api, schema: &Schema) -> Self {
+        let mut fields: Vec<(String, Example)> = api
+            .path
+            .split('/')
+            .filter_map(|segment| segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')))
+            .map(|param| (param.to_string(), Example::String("1".to_string())))
+            .collect();
+
+        if let Some(body) = Self::for_api_body(api, schema) {
+            fields.push(("body".to_string(), body));
+        }
+
+        Example::Object(fields)
+    }
+
+    /// Builds an example response for an API, wrapped in the page envelope
+    /// for `response: Page<T>` APIs.
+    pub fn for_api_response(api: &Api, schema: &Schema) -> Self {
+        let response = Self::for_type(api.response_type(), schema);
+        if !api.paginated {
+            return response;
+        }
+        Example::Object(vec![
+            ("items".to_string(), Example::Array(vec![response])),
+            ("total".to_string(), Example::Int(1)),
+            ("next_cursor".to_string(), Example::Null),
+        ])
+    }
+
+    /// Builds an example request body for an API, preferring the request of
+    /// its `example:` block.
+    pub fn for_api_body(api: &Api, schema: &Schema) -> Option<Self> {
+        let body = api.body_type()?;
+        match api.example.as_ref().and_then(|e| e.request.as_ref()) {
+            Some(request) => Some(Self::from_json(request, &FieldType::from_str(&body), schema)),
+            None => Some(Self::for_type(&body, schema)),
+        }
+    }
+
+    /// Converts a JSON value, typing dates and bytes by `field_type` so they
+    /// render like generated values.
+    pub fn from_json(value: &Value, field_type: &FieldType, schema: &Schema) -> Self {
+        match (field_type, value) {
+            (_, Value::Null) => Example::Null,
+            (FieldType::DateTime, Value::String(s)) => Example::DateTime(s.clone()),
+            (FieldType::Bytes, Value::String(s)) => Example::Bytes(s.as_bytes().to_vec()),
+            (FieldType::Float, Value::Number(n)) => Example::Float(n.as_f64().unwrap_or_default()),
+            (FieldType::Array(inner), Value::Array(items)) => {
+                Example::Array(items.iter().map(|item| Self::from_json(item, inner, schema)).collect())
+            }
+            (FieldType::Custom(name), Value::Object(map)) => {
+                // Declared fields first, in schema order, then any others.
+                let fields = find_fields(name, schema).unwrap_or_default();
+                let declared = fields.iter().filter_map(|field| {
+                    let value = map.get(&field.name)?;
+                    Some((field.name.clone(), Self::from_json(value, &field.field_type, schema)))
+                });
+                let others = map
+                    .iter()
+                    .filter(|(key, _)| !fields.iter().any(|f| &f.name == *key))
+                    .map(|(key, value)| (key.clone(), Self::from_json(value, &FieldType::Json, schema)));
+                Example::Object(declared.chain(others).collect())
+            }
+            (_, Value::Bool(b)) => Example::Bool(*b),
+            (_, Value::Number(n)) => match n.as_i64() {
+                Some(i) => Example::Int(i),
+                None => Example::Float(n.as_f64().unwrap_or_default()),
+            },
+            (_, Value::String(s)) => Example::String(s.clone()),
+            (_, Value::Array(items)) => Example::Array(
+                items.iter().map(|item| Self::from_json(item, &FieldType::Json, schema)).collect(),
+            ),
+            (_, Value::Object(map)) => Example::Object(
+                map.iter()
+                    .map(|(key, value)| (key.clone(), Self::from_json(value, &FieldType::Json, schema)))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Builds an example object from a list of fields. Optional fields are
+    /// left out so the example stays minimal.
+    pub fn for_fields(fields: &[Field], schema: &Schema) -> Self {
+        Self::object(fields, schema, 0)
+    }
+
+    /// Builds an example for a single field, ignoring whether it is optional.
+    pub fn for_field(field: &Field, schema: &Schema) -> Self {
+        Self::for_field_type(&field.field_type, &field.name, schema, 0)
+    }
+
+    fn object(fields: &[Field], schema: &Schema, depth: usize) -> Self {
+        Example::Object(
+            fields
+                .iter()
+                .filter(|f| !f.optional)
+                .map(|f| {
+                    (
+                        f.name.clone(),
+                        Self::for_field_type(&f.field_type, &f.name, schema, depth),
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    fn for_field_type(field_type: &FieldType, field_name: &str, schema: &Schema, depth: usize) -> Self {
+        match field_type {
+            FieldType::Int => Example::Int(1),
+            FieldType::Float => Example::Float(1.5),
+            FieldType::Boolean => Example::Bool(true),
+            FieldType::String => Example::String(example_string(field_name)),
+            FieldType::DateTime => Example::DateTime(EXAMPLE_DATETIME.to_string()),
+            FieldType::Json => Example::Object(Vec::new()),
+            FieldType::Bytes => Example::Bytes(b"example".to_vec()),
+            FieldType::Array(inner) => match inner.as_ref() {
+                // Nested collections of models are usually relations; keep them empty.
+                FieldType::Custom(_) => Example::Array(Vec::new()),
+                inner => Example::Array(vec![Self::for_field_type(inner, field_name, schema, depth)]),
+            },
+            FieldType::Custom(name) => {
+                if let Some(example) = schema.example_for(name) {
+                    return Self::from_json(example, field_type, schema);
+                }
+                if depth >= MAX_DEPTH {
+                    return Example::Null;
+                }
+                match find_fields(name, schema) {
+                    Some(fields) => Self::object(fields, schema, depth + 1),
+                    None => Example::Null,
+                }
+            }
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        match self {
+            Example::Null => Value::Null,
+            Example::Bool(b) => Value::Bool(*b),
+            Example::Int(i) => Value::from(*i),
+            Example::Float(f) => Value::from(*f),
+            Example::String(s) | Example::DateTime(s) => Value::String(s.clone()),
+            Example::Bytes(bytes) => Value::Array(bytes.iter().map(|b| Value::from(*b)).collect()),
+            Example::Array(items) => Value::Array(items.iter().map(|i| i.to_json()).collect()),
+            Example::Object(fields) => {
+                let mut map = Map::new();
+                for (name, value) in fields {
+                    map.insert(name.clone(), value.to_json());
+                }
+                Value::Object(map)
+            }
+        }
+    }
+
+    /// Renders the example as a TypeScript expression. Dates become `Date`
+    /// instances and bytes `Uint8Array`s to match the generated interfaces.
+    pub fn to_typescript(&self) -> String {
+        match self {
+            Example::Null => "null".to_string(),
+            Example::DateTime(s) => format!("new Date('{}')", s),
+            Example::Bytes(_) => format!("new Uint8Array({})", self.to_json()),
+            Example::Array(items) => format!(
+                "[{}]",
+                items.iter().map(|i| i.to_typescript()).collect::<Vec<_>>().join(", ")
+            ),
+            Example::Object(fields) if fields.is_empty() => "{}".to_string(),
+            Example::Object(fields) => format!(
+                "{{ {} }}",
+                fields
+                    .iter()
+                    .map(|(name, value)| format!("{}: {}", name, value.to_typescript()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            other => other.to_json().to_string(),
+        }
+    }
+
+    /// Renders the example as a Python literal.
+    pub fn to_python(&self) -> String {
+        match self {
+            Example::Null => "None".to_string(),
+            Example::Bool(true) => "True".to_string(),
+            Example::Bool(false) => "False".to_string(),
+            Example::Bytes(bytes) => format!("b\"{}\"", bytes.escape_ascii()),
+            Example::Array(items) => format!(
+                "[{}]",
+                items.iter().map(|i| i.to_python()).collect::<Vec<_>>().join(", ")
+            ),
+            Example::Object(fields) => format!(
+                "{{{}}}",
+                fields
+                    .iter()
+                    .map(|(name, value)| format!("{:?}: {}", name, value.to_python()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            other => other.to_json().to_string(),
+        }
+    }
+}
+
+fn find_fields<'a>(name: &str, schema: &'a Schema) -> Option<&'a [Field]> {
+    schema
+        .models
+        .iter()
+        .find(|m| m.name == name)
+        .map(|m| m.fields.as_slice())
+        .or_else(|| schema.inputs.iter().find(|i| i.name == name).map(|i| i.fields.as_slice()))
+        .or_else(|| schema.types.iter().find(|t| t.name == name).map(|t| t.fields.as_slice()))
+}
+
+fn example_string(field_name: &str) -> String {
+    let lower = field_name.to_lowercase();
+    if lower.contains("email") {
+        "user@example.com".to_string()
+    } else if lower.contains("url") {
+        "https://example.com".to_string()
+    } else if lower.is_empty() {
+        "example".to_string()
+    } else {
+        format!("example {}", field_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rohas_parser::Parser;
+
+    #[test]
+    fn test_example_for_model() {
+        let schema = Parser::parse_string(
+            r#"
+            model User {
+              id        Int       @id
+              email     String
+              nickname  String?
+              createdAt DateTime
+            }
+            "#,
+        )
+        .unwrap();
+
+        let example = Example::for_type("User", &schema);
+        assert_eq!(
+            example.to_json(),
+            serde_json::json!({
+                "id": 1,
+                "email": "user@example.com",
+                "createdAt": "2024-01-01T00:00:00Z"
+            })
+        );
+        assert_eq!(
+            example.to_typescript(),
+            "{ id: 1, email: \"user@example.com\", createdAt: new Date('2024-01-01T00:00:00Z') }"
+        );
+        assert_eq!(
+            example.to_python(),
+            "{\"id\": 1, \"email\": \"user@example.com\", \"createdAt\": \"2024-01-01T00:00:00Z\"}"
+        );
+    }
+
+    #[test]
+    fn test_schema_examples_take_precedence() {
+        let schema = Parser::parse_string(
+            r#"
+            model Event {
+              title    String
+              startsAt DateTime
+              example: { title: "Launch", startsAt: "2025-08-01T09:00:00Z" }
+            }
+
+            input CreateEvent {
+              title: String
+              at: DateTime
+            }
+
+            api CreateEvent {
+              method: POST
+              path: "/events/{id}"
+              body: CreateEvent
+              response: Event
+              example: {
+                request: { title: "Launch", at: "2025-08-01T09:00:00Z", extra: [1, 2.5] }
+              }
+            }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            Example::for_type("Event", &schema).to_typescript(),
+            "{ title: \"Launch\", startsAt: new Date('2025-08-01T09:00:00Z') }"
+        );
+        assert_eq!(
+            Example::for_api_request(&schema.apis[0], &schema).to_typescript(),
+            "{ id: \"1\", body: { title: \"Launch\", at: new Date('2025-08-01T09:00:00Z'), extra: [1, 2.5] } }"
+        );
+    }
+
+    #[test]
+    fn test_paginated_response_example() {
+        let schema = Parser::parse_string(
+            r#"
+            model User {
+              id Int @id
+            }
+
+            api ListUsers {
+              method: GET
+              path: "/users"
+              response: Page<User>
+            }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            Example::for_api_response(&schema.apis[0], &schema).to_json(),
+            serde_json::json!({ "items": [{ "id": 1 }], "total": 1, "next_cursor": null })
+        );
+    }
+}
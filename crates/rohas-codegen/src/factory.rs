@@ -0,0 +1,250 @@
+//! Fake field values for the generated model factories.
+//!
+//! Unlike [`Example`], which yields one fixed value per type, factory values
+//! may depend on a per-build sequence number `n` so that `@id` and `@unique`
+//! fields stay distinct across builds.
+
+use crate::example::Example;
+use crate::templates;
+use rohas_parser::{Field, FieldType, Model, Schema};
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FakeValue {
+    /// The build's sequence number.
+    Sequence,
+    /// A string containing the build's sequence number: `{prefix}{n}{suffix}`.
+    SequencedString { prefix: String, suffix: String },
+    /// The current time at build.
+    Now,
+    /// A value built by the factory of another model.
+    Factory(String),
+    Example(Example),
+    Null,
+}
+
+impl FakeValue {
+    pub fn for_field(model: &Model, field: &Field, schema: &Schema) -> Self {
+        if field.optional {
+            return FakeValue::Null;
+        }
+
+        if let Some(value) = from_default(field) {
+            return value;
+        }
+
+        let has_attr = |name: &str| field.attributes.iter().any(|a| a.name == name);
+        let lower = field.name.to_lowercase();
+
+        match &field.field_type {
+            FieldType::Int if has_attr("id") || has_attr("unique") => FakeValue::Sequence,
+            FieldType::String if lower.contains("email") => FakeValue::SequencedString {
+                prefix: templates::to_snake_case(&model.name),
+                suffix: "@example.com".to_string(),
+            },
+            FieldType::String if has_attr("id") => FakeValue::SequencedString {
+                prefix: format!("{}-", templates::to_snake_case(&model.name)),
+                suffix: String::new(),
+            },
+            FieldType::String if has_attr("unique") => FakeValue::SequencedString {
+                prefix: format!("{}-", field.name),
+                suffix: String::new(),
+            },
+            FieldType::DateTime => FakeValue::Now,
+            // A factory building a model that leads back to this one would
+            // recurse forever, so the cycle is cut on every edge of it.
+            FieldType::Custom(name) if builds(schema, name, &model.name, &mut HashSet::new()) => FakeValue::Null,
+            FieldType::Custom(name) if schema.models.iter().any(|m| &m.name == name) => {
+                FakeValue::Factory(name.clone())
+            }
+            _ => FakeValue::Example(Example::for_field(field, schema)),
+        }
+    }
+
+    /// Whether rendering this value refers to the sequence number `n`.
+    pub fn uses_sequence(&self) -> bool {
+        matches!(self, FakeValue::Sequence | FakeValue::SequencedString { .. })
+    }
+
+    pub fn to_typescript(&self) -> String {
+        match self {
+            FakeValue::Sequence => "n".to_string(),
+            FakeValue::SequencedString { prefix, suffix } => format!("`{}${{n}}{}`", prefix, suffix),
+            FakeValue::Now => "new Date()".to_string(),
+            FakeValue::Factory(model) => format!("new {}Factory().build()", model),
+            FakeValue::Example(example) => example.to_typescript(),
+            FakeValue::Null => "undefined".to_string(),
+        }
+    }
+
+    pub fn to_python(&self) -> String {
+        match self {
+            FakeValue::Sequence => "n".to_string(),
+            FakeValue::SequencedString { prefix, suffix } => format!("f\"{}{{n}}{}\"", prefix, suffix),
+            FakeValue::Now => "datetime.now()".to_string(),
+            FakeValue::Factory(model) => format!("{}Factory().build()", model),
+            FakeValue::Example(example) => example.to_python(),
+            FakeValue::Null => "None".to_string(),
+        }
+    }
+
+    /// Renders the value as a Rust expression of the field's generated type.
+    pub fn to_rust(&self, field_type: &FieldType) -> String {
+        match self {
+            FakeValue::Sequence => "n".to_string(),
+            FakeValue::SequencedString { prefix, suffix } => {
+                format!("format!(\"{}{{}}{}\", n)", prefix, suffix)
+            }
+            FakeValue::Now => "chrono::Utc::now()".to_string(),
+            FakeValue::Factory(model) => format!("{}Factory::new().build()", model),
+            FakeValue::Example(example) => rust_literal(example, field_type),
+            FakeValue::Null => "None".to_string(),
+        }
+    }
+}
+
+/// Whether building a `from` model builds a `to` model, through the required
+/// model fields its factory fills in.
+fn builds(schema: &Schema, from: &str, to: &str, visited: &mut HashSet<String>) -> bool {
+    if from == to {
+        return true;
+    }
+    if !visited.insert(from.to_string()) {
+        return false;
+    }
+    let Some(model) = schema.models.iter().find(|m| m.name == from) else {
+        return false;
+    };
+    model.fields.iter().filter(|f| !f.optional).any(|f| match &f.field_type {
+        FieldType::Custom(next) => builds(schema, next, to, visited),
+        _ => false,
+    })
+}
+
+/// Uses the field's `@default(...)` value when it can be expressed as a
+/// literal of the field's type.
+fn from_default(field: &Field) -> Option<FakeValue> {
+    let arg = field
+        .attributes
+        .iter()
+        .find(|a| a.name == "default")
+        .and_then(|a| a.args.first())?
+        .trim();
+
+    match (&field.field_type, arg) {
+        (FieldType::DateTime, "now" | "now()") => Some(FakeValue::Now),
+        (FieldType::Int, "autoincrement()") => Some(FakeValue::Sequence),
+        (FieldType::Boolean, "true") => Some(FakeValue::Example(Example::Bool(true))),
+        (FieldType::Boolean, "false") => Some(FakeValue::Example(Example::Bool(false))),
+        (FieldType::Int, _) => arg.parse().ok().map(|i| FakeValue::Example(Example::Int(i))),
+        (FieldType::Float, _) => arg.parse().ok().map(|f| FakeValue::Example(Example::Float(f))),
+        // Generated ids (`uuid()`, `cuid()`) fall through to the `@id` handling.
+        (FieldType::String, _) => arg
+            .strip_prefix('"')
+            .and_then(|s| s.strip_suffix('"'))
+            .map(|s| FakeValue::Example(Example::String(s.to_string()))),
+        _ => None,
+    }
+}
+
+fn rust_literal(example: &Example, field_type: &FieldType) -> String {
+    match (field_type, example) {
+        (FieldType::Json, _) => format!("serde_json::json!({})", example.to_json()),
+        (FieldType::Custom(_), _) => format!(
+            "serde_json::from_value(serde_json::json!({})).expect(\"valid example\")",
+            example.to_json()
+        ),
+        (FieldType::Array(_), Example::Array(items)) if items.is_empty() => "Vec::new()".to_string(),
+        (FieldType::Array(inner), Example::Array(items)) => format!(
+            "vec![{}]",
+            items
+                .iter()
+                .map(|i| rust_literal(i, inner))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        (FieldType::String, Example::String(s)) => format!("{:?}.to_string()", s),
+        (FieldType::Float, Example::Float(f)) => format!("{:?}", f),
+        (FieldType::DateTime, _) => "chrono::Utc::now()".to_string(),
+        _ => example.to_json().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rohas_parser::Parser;
+
+    #[test]
+    fn test_fake_values_for_model() {
+        let schema = Parser::parse_string(
+            r#"
+            model User {
+              id        Int       @id
+              email     String    @unique
+              name      String
+              active    Boolean   @default(false)
+              nickname  String?
+              createdAt DateTime  @default(now)
+            }
+            "#,
+        )
+        .unwrap();
+
+        let model = &schema.models[0];
+        let values: Vec<FakeValue> = model
+            .fields
+            .iter()
+            .map(|f| FakeValue::for_field(model, f, &schema))
+            .collect();
+
+        assert_eq!(values[0], FakeValue::Sequence);
+        assert_eq!(values[1].to_typescript(), "`user${n}@example.com`");
+        assert_eq!(values[1].to_python(), "f\"user{n}@example.com\"");
+        assert_eq!(
+            values[1].to_rust(&FieldType::String),
+            "format!(\"user{}@example.com\", n)"
+        );
+        assert_eq!(values[2].to_rust(&FieldType::String), "\"example name\".to_string()");
+        assert_eq!(values[3].to_python(), "False");
+        assert_eq!(values[4], FakeValue::Null);
+        assert_eq!(values[5], FakeValue::Now);
+    }
+
+    #[test]
+    fn test_model_cycles_are_cut() {
+        let schema = Parser::parse_string(
+            r#"
+            model Author {
+              id   Int  @id
+              book Book
+            }
+
+            model Book {
+              id     Int    @id
+              author Author
+              cover  Image
+            }
+
+            model Image {
+              url String
+            }
+
+            model Node {
+              next Node
+            }
+            "#,
+        )
+        .unwrap();
+
+        let value = |model: &str, field: &str| {
+            let model = schema.models.iter().find(|m| m.name == model).unwrap();
+            let field = model.fields.iter().find(|f| f.name == field).unwrap();
+            FakeValue::for_field(model, field, &schema)
+        };
+        assert_eq!(value("Author", "book"), FakeValue::Null);
+        assert_eq!(value("Book", "author"), FakeValue::Null);
+        assert_eq!(value("Book", "cover"), FakeValue::Factory("Image".to_string()));
+        assert_eq!(value("Node", "next"), FakeValue::Null);
+    }
+}
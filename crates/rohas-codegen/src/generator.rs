@@ -1,5 +1,6 @@
-use crate::error::Result;
-use crate::{config, python, rust, typescript, Language};
+use crate::error::{CodegenError, Result};
+use crate::layout::Layout;
+use crate::{config, kotlin, python, rust, swift, typescript, Language};
 use rohas_parser::Schema;
 use std::fs;
 use std::path::Path;
@@ -7,11 +8,20 @@ use tracing::{debug, info};
 
 pub struct Generator {
     language: Language,
+    layout: Layout,
 }
 
 impl Generator {
     pub fn new(language: Language) -> Self {
-        Self { language }
+        Self {
+            language,
+            layout: Layout::default(),
+        }
+    }
+
+    pub fn with_layout(mut self, layout: Layout) -> Self {
+        self.layout = layout;
+        self
     }
 
     pub fn generate(&self, schema: &Schema, output_dir: &Path) -> Result<()> {
@@ -21,19 +31,31 @@ impl Generator {
             output_dir.display()
         );
 
+        // Rust handlers are modules of the crate, at fixed paths.
+        if self.language == Language::Rust && !self.layout.has_default_paths() {
+            return Err(CodegenError::GenerationFailed(
+                "Rust handlers cannot be moved or renamed; only handler stubs can be turned off".to_string(),
+            ));
+        }
+
         if let Some(parent) = output_dir.parent() {
             fs::create_dir_all(parent)?;
         }
         fs::create_dir_all(output_dir)?;
 
-        self.create_directory_structure(output_dir)?;
+        // Client targets have no handler directories or project files.
+        if !matches!(self.language, Language::Kotlin | Language::Swift) {
+            self.create_directory_structure(output_dir)?;
 
-        self.generate_common_configs(schema, output_dir)?;
+            self.generate_common_configs(schema, output_dir)?;
+        }
 
         match self.language {
             Language::TypeScript => self.generate_typescript(schema, output_dir)?,
             Language::Python => self.generate_python(schema, output_dir)?,
             Language::Rust => self.generate_rust(schema, output_dir)?,
+            Language::Kotlin => kotlin::generate(schema, output_dir)?,
+            Language::Swift => swift::generate(schema, output_dir)?,
         }
 
         info!("Code generation completed successfully");
@@ -49,16 +71,16 @@ impl Generator {
             "generated/events",
             "generated/cron",
             "generated/websockets",
-            "handlers",
-            "handlers/api",
-            "handlers/events",
-            "handlers/cron",
-            "handlers/websockets",
-            "middlewares",
         ];
-
-        for dir in &dirs {
-            let path = output_dir.join(dir);
+        let handler_dirs = ["api", "events", "cron", "websockets"]
+            .map(|kind| self.layout.handlers(output_dir, kind));
+
+        let paths = dirs
+            .iter()
+            .map(|dir| output_dir.join(dir))
+            .chain(handler_dirs)
+            .chain([self.layout.middlewares(output_dir)]);
+        for path in paths {
             if !path.exists() {
                 fs::create_dir_all(&path)?;
                 debug!("Created directory: {}", path.display());
@@ -111,19 +133,24 @@ impl Generator {
 
     fn generate_typescript(&self, schema: &Schema, output_dir: &Path) -> Result<()> {
         typescript::generate_state(output_dir)?;
+        typescript::generate_errors(output_dir)?;
+        typescript::generate_page(output_dir)?;
         typescript::generate_models(schema, output_dir)?;
+        typescript::generate_factories(schema, output_dir)?;
         typescript::generate_dtos(schema, output_dir)?;
-        typescript::generate_apis(schema, output_dir)?;
-        typescript::generate_events(schema, output_dir)?;
-        typescript::generate_crons(schema, output_dir)?;
-        typescript::generate_websockets(schema, output_dir)?;
-        typescript::generate_middlewares(schema, output_dir)?;
+        typescript::generate_apis(schema, output_dir, &self.layout)?;
+        typescript::generate_events(schema, output_dir, &self.layout)?;
+        typescript::generate_test_engine(schema, output_dir, &self.layout)?;
+        typescript::generate_crons(schema, output_dir, &self.layout)?;
+        typescript::generate_websockets(schema, output_dir, &self.layout)?;
+        typescript::generate_middlewares(schema, output_dir, &self.layout)?;
         typescript::generate_index(schema, output_dir)?;
 
         info!("Generating TypeScript configuration files");
         config::generate_package_json(schema, output_dir)?;
         config::generate_tsconfig_json(schema, output_dir)?;
         config::generate_rspack_config(schema, output_dir)?;
+        config::generate_vitest_config(schema, output_dir)?;
         config::generate_nvmrc(schema, output_dir)?;
         config::generate_prettierrc(schema, output_dir)?;
         config::generate_prettierignore(schema, output_dir)?;
@@ -133,13 +160,17 @@ impl Generator {
 
     fn generate_python(&self, schema: &Schema, output_dir: &Path) -> Result<()> {
         python::generate_state(output_dir)?;
+        python::generate_errors(output_dir)?;
+        python::generate_page(output_dir)?;
         python::generate_models(schema, output_dir)?;
+        python::generate_factories(schema, output_dir)?;
         python::generate_dtos(schema, output_dir)?;
-        python::generate_apis(schema, output_dir)?;
-        python::generate_events(schema, output_dir)?;
-        python::generate_crons(schema, output_dir)?;
-        python::generate_websockets(schema, output_dir)?;
-        python::generate_middlewares(schema, output_dir)?;
+        python::generate_apis(schema, output_dir, &self.layout)?;
+        python::generate_events(schema, output_dir, &self.layout)?;
+        python::generate_test_engine(schema, output_dir, &self.layout)?;
+        python::generate_crons(schema, output_dir, &self.layout)?;
+        python::generate_websockets(schema, output_dir, &self.layout)?;
+        python::generate_middlewares(schema, output_dir, &self.layout)?;
         python::generate_init(schema, output_dir)?;
 
         info!("Generating Python configuration files");
@@ -155,18 +186,24 @@ impl Generator {
         info!("Generating Rust code...");
         info!("Generating state...");
         rust::generate_state(output_dir)?;
+        rust::generate_errors(output_dir)?;
+        rust::generate_page(output_dir)?;
         info!("Generating models...");
         rust::generate_models(schema, output_dir)?;
+        info!("Generating factories...");
+        rust::generate_factories(schema, output_dir)?;
         info!("Generating DTOs...");
         rust::generate_dtos(schema, output_dir)?;
         info!("Generating APIs...");
-        rust::generate_apis(schema, output_dir)?;
+        rust::generate_apis(schema, output_dir, &self.layout)?;
         info!("Generating events...");
-        rust::generate_events(schema, output_dir)?;
+        rust::generate_events(schema, output_dir, &self.layout)?;
+        info!("Generating test engine...");
+        rust::generate_test_engine(schema, output_dir)?;
         info!("Generating crons...");
-        rust::generate_crons(schema, output_dir)?;
+        rust::generate_crons(schema, output_dir, &self.layout)?;
         info!("Generating websockets...");
-        rust::generate_websockets(schema, output_dir)
+        rust::generate_websockets(schema, output_dir, &self.layout)
             .map_err(|e| {
                 error!("Failed to generate websockets: {}", e);
                 crate::error::CodegenError::GenerationFailed(format!(
@@ -175,9 +212,11 @@ impl Generator {
                 ))
             })?;
         info!("Generating middlewares...");
-        rust::generate_middlewares(schema, output_dir)?;
+        rust::generate_middlewares(schema, output_dir, &self.layout)?;
         info!("Generating lib.rs...");
         rust::generate_lib_rs(schema, output_dir)?;
+        info!("Generating handler crates...");
+        rust::generate_handler_crates(schema, output_dir)?;
 
         info!("Generating Rust configuration files");
         config::generate_cargo_toml(schema, output_dir)?;
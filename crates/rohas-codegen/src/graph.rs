@@ -0,0 +1,375 @@
+//! Graph of a schema's declarations and how they refer to one another,
+//! rendered as Mermaid, D2 or Graphviz DOT text diagrams.
+//!
+//! Nodes are models, types, inputs, APIs, events, crons, websockets and the
+//! handlers events and websockets dispatch to. Solid edges are type
+//! references and handler calls; dashed edges are triggered or forwarded
+//! events.
+
+use rohas_parser::{FieldType, Schema};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    Mermaid,
+    D2,
+    Dot,
+}
+
+impl GraphFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "mermaid" | "mmd" => Some(GraphFormat::Mermaid),
+            "d2" => Some(GraphFormat::D2),
+            "dot" | "graphviz" => Some(GraphFormat::Dot),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    Model,
+    Type,
+    Input,
+    Api,
+    Event,
+    Cron,
+    WebSocket,
+    Handler,
+}
+
+impl NodeKind {
+    fn prefix(self) -> &'static str {
+        match self {
+            NodeKind::Model => "model",
+            NodeKind::Type => "type",
+            NodeKind::Input => "input",
+            NodeKind::Api => "api",
+            NodeKind::Event => "event",
+            NodeKind::Cron => "cron",
+            NodeKind::WebSocket => "ws",
+            NodeKind::Handler => "handler",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub id: String,
+    pub label: String,
+    pub kind: NodeKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edge {
+    pub source: String,
+    pub target: String,
+    pub label: Option<String>,
+    /// Asynchronous: a triggered or forwarded event.
+    pub dashed: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Graph {
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+}
+
+impl Graph {
+    pub fn from_schema(schema: &Schema) -> Self {
+        let mut graph = Graph::default();
+
+        let records = schema
+            .models
+            .iter()
+            .map(|m| (NodeKind::Model, &m.name, &m.fields))
+            .chain(schema.types.iter().map(|t| (NodeKind::Type, &t.name, &t.fields)))
+            .chain(schema.inputs.iter().map(|i| (NodeKind::Input, &i.name, &i.fields)));
+        for (kind, name, _) in records.clone() {
+            graph.node(kind, name);
+        }
+        for (kind, name, fields) in records {
+            let source = node_id(kind, name);
+            for field in fields {
+                if let Some(target) = base_type(&field.field_type).and_then(|t| graph.record(&t)) {
+                    graph.edge(&source, &target, Some(&field.name), false);
+                }
+            }
+        }
+
+        for api in &schema.apis {
+            let source = graph.node(NodeKind::Api, &api.name);
+            if let Some(target) = api.body.as_deref().and_then(|body| graph.record(body)) {
+                graph.edge(&source, &target, Some("body"), false);
+            }
+            if let Some(target) = graph.record(&api.response) {
+                graph.edge(&source, &target, Some("response"), false);
+            }
+            graph.triggers(&source, &api.triggers);
+        }
+
+        for event in &schema.events {
+            let source = graph.node(NodeKind::Event, &event.name);
+            if let Some(target) = graph.record(&event.payload) {
+                graph.edge(&source, &target, Some("payload"), false);
+            }
+            for handler in &event.handlers {
+                let target = graph.node(NodeKind::Handler, handler);
+                graph.edge(&source, &target, Some("handler"), false);
+            }
+            graph.triggers(&source, &event.triggers);
+            if let Some(forward) = &event.forward_to {
+                let target = node_id(NodeKind::WebSocket, &forward.websocket);
+                graph.edge(&source, &target, Some("forward"), true);
+            }
+        }
+
+        for cron in &schema.crons {
+            let source = graph.node(NodeKind::Cron, &cron.name);
+            graph.triggers(&source, &cron.triggers);
+        }
+
+        for ws in &schema.websockets {
+            let source = graph.node(NodeKind::WebSocket, &ws.name);
+            if let Some(target) = ws.message.as_deref().and_then(|message| graph.record(message)) {
+                graph.edge(&source, &target, Some("message"), false);
+            }
+            for handler in ws.on_connect.iter().chain(&ws.on_message).chain(&ws.on_disconnect) {
+                let target = graph.node(NodeKind::Handler, handler);
+                graph.edge(&source, &target, Some("handler"), false);
+            }
+            graph.triggers(&source, &ws.triggers);
+        }
+
+        graph
+    }
+
+    /// Adds a node once and returns its id.
+    fn node(&mut self, kind: NodeKind, name: &str) -> String {
+        let id = node_id(kind, name);
+        if !self.nodes.iter().any(|n| n.id == id) {
+            self.nodes.push(Node {
+                id: id.clone(),
+                label: name.to_string(),
+                kind,
+            });
+        }
+        id
+    }
+
+    fn edge(&mut self, source: &str, target: &str, label: Option<&str>, dashed: bool) {
+        self.edges.push(Edge {
+            source: source.to_string(),
+            target: target.to_string(),
+            label: label.map(str::to_string),
+            dashed,
+        });
+    }
+
+    fn triggers(&mut self, source: &str, events: &[String]) {
+        for event in events {
+            let target = node_id(NodeKind::Event, event);
+            self.edge(source, &target, None, true);
+        }
+    }
+
+    /// Id of the model, type or input a type name refers to.
+    fn record(&self, type_name: &str) -> Option<String> {
+        let name = base_type(&FieldType::from_str(type_name))?;
+        self.nodes
+            .iter()
+            .find(|n| n.label == name && matches!(n.kind, NodeKind::Model | NodeKind::Type | NodeKind::Input))
+            .map(|n| n.id.clone())
+    }
+
+    pub fn render(&self, format: GraphFormat) -> String {
+        match format {
+            GraphFormat::Mermaid => self.to_mermaid(),
+            GraphFormat::D2 => self.to_d2(),
+            GraphFormat::Dot => self.to_dot(),
+        }
+    }
+
+    pub fn to_mermaid(&self) -> String {
+        let mut out = String::from("flowchart LR\n");
+        for node in &self.nodes {
+            let (open, close) = match node.kind {
+                NodeKind::Model | NodeKind::Type | NodeKind::Input => ("[", "]"),
+                NodeKind::Api => ("([", "])"),
+                NodeKind::Event => ("{{", "}}"),
+                NodeKind::Cron => ("[/", "/]"),
+                NodeKind::WebSocket => (">", "]"),
+                NodeKind::Handler => ("[[", "]]"),
+            };
+            out.push_str(&format!("    {}{}{}{}\n", node.id, open, node.label, close));
+        }
+        for edge in &self.edges {
+            let arrow = match (&edge.label, edge.dashed) {
+                (Some(label), false) => format!("-- {} -->", label),
+                (Some(label), true) => format!("-. {} .->", label),
+                (None, false) => "-->".to_string(),
+                (None, true) => "-.->".to_string(),
+            };
+            out.push_str(&format!("    {} {} {}\n", edge.source, arrow, edge.target));
+        }
+        out
+    }
+
+    pub fn to_d2(&self) -> String {
+        let mut out = String::from("direction: right\n\n");
+        for node in &self.nodes {
+            let shape = match node.kind {
+                NodeKind::Model | NodeKind::Type | NodeKind::Input => "rectangle",
+                NodeKind::Api => "oval",
+                NodeKind::Event => "hexagon",
+                NodeKind::Cron => "parallelogram",
+                NodeKind::WebSocket => "queue",
+                NodeKind::Handler => "page",
+            };
+            out.push_str(&format!("{}: {} {{shape: {}}}\n", node.id, node.label, shape));
+        }
+        out.push('\n');
+        for edge in &self.edges {
+            out.push_str(&format!("{} -> {}", edge.source, edge.target));
+            if let Some(label) = &edge.label {
+                out.push_str(&format!(": {}", label));
+            }
+            if edge.dashed {
+                out.push_str(" {style.stroke-dash: 3}");
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph schema {\n    rankdir=LR;\n    node [fontname=\"Helvetica\"];\n");
+        for node in &self.nodes {
+            let shape = match node.kind {
+                NodeKind::Model | NodeKind::Type | NodeKind::Input => "box",
+                NodeKind::Api => "ellipse",
+                NodeKind::Event => "hexagon",
+                NodeKind::Cron => "parallelogram",
+                NodeKind::WebSocket => "cds",
+                NodeKind::Handler => "component",
+            };
+            out.push_str(&format!(
+                "    \"{}\" [label=\"{}\", shape={}];\n",
+                node.id, node.label, shape
+            ));
+        }
+        for edge in &self.edges {
+            let mut attributes = Vec::new();
+            if let Some(label) = &edge.label {
+                attributes.push(format!("label=\"{}\"", label));
+            }
+            if edge.dashed {
+                attributes.push("style=dashed".to_string());
+            }
+            out.push_str(&format!("    \"{}\" -> \"{}\"", edge.source, edge.target));
+            if !attributes.is_empty() {
+                out.push_str(&format!(" [{}]", attributes.join(", ")));
+            }
+            out.push_str(";\n");
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+fn node_id(kind: NodeKind, name: &str) -> String {
+    format!("{}_{}", kind.prefix(), name)
+}
+
+/// The named type inside a (possibly array) field type.
+pub(crate) fn base_type(field_type: &FieldType) -> Option<String> {
+    match field_type {
+        FieldType::Array(inner) => base_type(inner),
+        FieldType::Custom(name) => Some(name.clone()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rohas_parser::Parser;
+
+    const SCHEMA: &str = r#"
+model Order {
+  id    Int
+  lines OrderLine[]
+}
+
+type OrderLine {
+  sku: String
+}
+
+api CreateOrder {
+  method: POST
+  path: "/orders"
+  body: Order
+  response: Order
+  triggers: [OrderPlaced]
+}
+
+ws OrderFeed {
+  path: "/ws/orders"
+  onConnect: [subscribe]
+}
+
+event OrderPlaced {
+  payload: Order
+  handler: [reserve_stock]
+  forward_to: OrderFeed
+}
+"#;
+
+    #[test]
+    fn test_graph_from_schema() {
+        let schema = Parser::parse_string(SCHEMA).unwrap();
+        let graph = Graph::from_schema(&schema);
+
+        let edge = |source: &str, target: &str| {
+            graph
+                .edges
+                .iter()
+                .find(|e| e.source == source && e.target == target)
+                .unwrap_or_else(|| panic!("missing edge {} -> {}", source, target))
+        };
+        assert_eq!(edge("model_Order", "type_OrderLine").label.as_deref(), Some("lines"));
+        assert_eq!(edge("api_CreateOrder", "model_Order").label.as_deref(), Some("body"));
+        assert!(edge("api_CreateOrder", "event_OrderPlaced").dashed);
+        assert_eq!(edge("event_OrderPlaced", "handler_reserve_stock").label.as_deref(), Some("handler"));
+        assert!(edge("event_OrderPlaced", "ws_OrderFeed").dashed);
+        let handlers: Vec<&str> = graph
+            .nodes
+            .iter()
+            .filter(|n| n.kind == NodeKind::Handler)
+            .map(|n| n.label.as_str())
+            .collect();
+        assert_eq!(handlers, ["reserve_stock", "subscribe"]);
+    }
+
+    #[test]
+    fn test_render_formats() {
+        let schema = Parser::parse_string(SCHEMA).unwrap();
+        let graph = Graph::from_schema(&schema);
+
+        let mermaid = graph.render(GraphFormat::Mermaid);
+        assert!(mermaid.starts_with("flowchart LR\n"));
+        assert!(mermaid.contains("    event_OrderPlaced{{OrderPlaced}}\n"));
+        assert!(mermaid.contains("    api_CreateOrder -.-> event_OrderPlaced\n"));
+        assert!(mermaid.contains("    event_OrderPlaced -. forward .-> ws_OrderFeed\n"));
+
+        let d2 = graph.render(GraphFormat::D2);
+        assert!(d2.contains("api_CreateOrder: CreateOrder {shape: oval}\n"));
+        assert!(d2.contains("api_CreateOrder -> event_OrderPlaced {style.stroke-dash: 3}\n"));
+        assert!(d2.contains("model_Order -> type_OrderLine: lines\n"));
+
+        let dot = graph.render(GraphFormat::Dot);
+        assert!(dot.starts_with("digraph schema {"));
+        assert!(dot.contains("    \"api_CreateOrder\" -> \"model_Order\" [label=\"body\"];\n"));
+        assert!(dot.contains("    \"event_OrderPlaced\" -> \"ws_OrderFeed\" [label=\"forward\", style=dashed];\n"));
+    }
+}
@@ -0,0 +1,807 @@
+//! Dependency index of a project's handlers and what a change affects.
+//!
+//! The schema says which models each handler receives and returns and which
+//! events it triggers. Import statements of the handler sources (Python,
+//! TypeScript, Rust) add the models and project files they use directly, so
+//! a change to a shared helper reaches every handler importing it.
+
+use crate::error::{CodegenError, Result};
+use crate::graph::base_type;
+use crate::layout::Layout;
+use crate::templates;
+use rohas_parser::{FieldType, Schema};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+const SOURCE_EXTENSIONS: &[&str] = &["py", "ts", "tsx", "rs"];
+
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target", "dist", "__pycache__", ".venv"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HandlerKind {
+    Api,
+    Event,
+    Cron,
+    WebSocket,
+    Middleware,
+}
+
+impl HandlerKind {
+    /// Directory of the kind's handlers, relative to the project root.
+    fn dir(self, layout: &Layout) -> PathBuf {
+        let src = Path::new("src");
+        match self {
+            HandlerKind::Api => layout.handlers(src, "api"),
+            HandlerKind::Event => layout.handlers(src, "events"),
+            HandlerKind::Cron => layout.handlers(src, "cron"),
+            HandlerKind::WebSocket => layout.handlers(src, "websockets"),
+            HandlerKind::Middleware => layout.middlewares(src),
+        }
+    }
+}
+
+/// What one handler depends on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HandlerDeps {
+    pub name: String,
+    pub kind: HandlerKind,
+    /// APIs, events, crons or websockets the handler runs for.
+    pub declarations: BTreeSet<String>,
+    /// Source file, relative to the project root; None before it is written.
+    pub file: Option<PathBuf>,
+    /// Models, types and inputs it receives: request bodies, event payloads
+    /// and websocket messages.
+    pub reads: BTreeSet<String>,
+    /// Models, types and inputs it produces: responses and the payloads of
+    /// the events it triggers.
+    pub writes: BTreeSet<String>,
+    /// Models, types and inputs its source imports.
+    pub imports: BTreeSet<String>,
+    pub triggers: BTreeSet<String>,
+}
+
+impl HandlerDeps {
+    fn uses(&self, record: &str) -> bool {
+        self.reads.contains(record) || self.writes.contains(record) || self.imports.contains(record)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DependencyIndex {
+    schema: Schema,
+    layout: Layout,
+    pub handlers: Vec<HandlerDeps>,
+    /// Project source files and the project files they import, relative to
+    /// the project root.
+    pub imports: BTreeMap<PathBuf, BTreeSet<PathBuf>>,
+}
+
+/// Everything a change reaches.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Impact {
+    pub records: BTreeSet<String>,
+    pub apis: BTreeSet<String>,
+    pub events: BTreeSet<String>,
+    pub crons: BTreeSet<String>,
+    pub websockets: BTreeSet<String>,
+    pub handlers: BTreeSet<String>,
+    /// Source files that change or import a changed file, i.e. what has to
+    /// be rebuilt or reloaded.
+    pub files: BTreeSet<PathBuf>,
+}
+
+impl Impact {
+    pub fn is_empty(&self) -> bool {
+        *self == Impact::default()
+    }
+}
+
+impl DependencyIndex {
+    /// Indexes the handlers of `schema` and the sources under
+    /// `project_root/src`, finding handler files where `layout` puts them.
+    pub fn build(schema: &Schema, project_root: &Path, layout: &Layout) -> Result<Self> {
+        let mut sources = Vec::new();
+        collect_sources(project_root, &project_root.join("src"), &mut sources)?;
+
+        let mut imports = BTreeMap::new();
+        let mut imported_records = BTreeMap::new();
+        let records = record_names(schema);
+        for file in &sources {
+            let content = fs::read_to_string(project_root.join(file))?;
+            let (files, names) = parse_imports(project_root, file, &content);
+            let used: BTreeSet<String> = records
+                .iter()
+                .filter(|record| {
+                    names.contains(**record)
+                        || files.iter().any(|f| {
+                            f.starts_with("src/generated")
+                                && f.file_stem().is_some_and(|s| *s == *templates::to_snake_case(record))
+                        })
+                })
+                .map(|record| record.to_string())
+                .collect();
+            imported_records.insert(file.clone(), used);
+            imports.insert(file.clone(), files);
+        }
+
+        let mut index = DependencyIndex {
+            schema: schema.clone(),
+            layout: layout.clone(),
+            handlers: Vec::new(),
+            imports,
+        };
+        index.index_handlers(&sources);
+        for handler in &mut index.handlers {
+            if let Some(used) = handler.file.as_ref().and_then(|f| imported_records.get(f)) {
+                handler.imports = used.clone();
+            }
+        }
+        Ok(index)
+    }
+
+    fn index_handlers(&mut self, sources: &[PathBuf]) {
+        let schema = self.schema.clone();
+        for api in &schema.apis {
+            let handler = self.handler(HandlerKind::Api, &api.name, &api.name, sources);
+            handler.reads.extend(record(&schema, api.body.as_deref()));
+            handler.writes.extend(record(&schema, Some(&api.response)));
+            self.triggers(HandlerKind::Api, &api.name, &api.triggers);
+            for middleware in &api.middlewares {
+                self.handler(HandlerKind::Middleware, middleware, &api.name, sources);
+            }
+        }
+        for event in &schema.events {
+            for name in &event.handlers {
+                let handler = self.handler(HandlerKind::Event, name, &event.name, sources);
+                handler.reads.extend(record(&schema, Some(&event.payload)));
+                self.triggers(HandlerKind::Event, name, &event.triggers);
+            }
+        }
+        for cron in &schema.crons {
+            self.handler(HandlerKind::Cron, &cron.name, &cron.name, sources);
+            self.triggers(HandlerKind::Cron, &cron.name, &cron.triggers);
+        }
+        for ws in &schema.websockets {
+            for name in ws.on_connect.iter().chain(&ws.on_message).chain(&ws.on_disconnect) {
+                let handler = self.handler(HandlerKind::WebSocket, name, &ws.name, sources);
+                handler.reads.extend(record(&schema, ws.message.as_deref()));
+                self.triggers(HandlerKind::WebSocket, name, &ws.triggers);
+            }
+            for middleware in ws.middleware_handlers() {
+                self.handler(HandlerKind::Middleware, middleware, &ws.name, sources);
+            }
+        }
+    }
+
+    /// The entry for handler `name`, created on first use, now also serving
+    /// `declaration`.
+    fn handler(&mut self, kind: HandlerKind, name: &str, declaration: &str, sources: &[PathBuf]) -> &mut HandlerDeps {
+        let position = match self.handlers.iter().position(|h| h.kind == kind && h.name == name) {
+            Some(position) => position,
+            None => {
+                let snake = templates::to_snake_case(name);
+                let dir = kind.dir(&self.layout);
+                let file = sources
+                    .iter()
+                    .find(|f| {
+                        f.parent() == Some(dir.as_path())
+                            && f.file_stem().is_some_and(|s| *s == *name || *s == *snake)
+                    })
+                    .cloned();
+                self.handlers.push(HandlerDeps {
+                    name: name.to_string(),
+                    kind,
+                    declarations: BTreeSet::new(),
+                    file,
+                    reads: BTreeSet::new(),
+                    writes: BTreeSet::new(),
+                    imports: BTreeSet::new(),
+                    triggers: BTreeSet::new(),
+                });
+                self.handlers.len() - 1
+            }
+        };
+        let handler = &mut self.handlers[position];
+        handler.declarations.insert(declaration.to_string());
+        handler
+    }
+
+    fn triggers(&mut self, kind: HandlerKind, name: &str, events: &[String]) {
+        let payloads: Vec<String> = events
+            .iter()
+            .filter_map(|event| self.schema.events.iter().find(|e| &e.name == event))
+            .filter_map(|event| record(&self.schema, Some(&event.payload)))
+            .collect();
+        if let Some(handler) = self.handlers.iter_mut().find(|h| h.kind == kind && h.name == name) {
+            handler.triggers.extend(events.iter().cloned());
+            handler.writes.extend(payloads);
+        }
+    }
+
+    /// What changing the model, type or input `name` affects: records
+    /// embedding it, declarations and handlers using any of them, and the
+    /// events those trigger.
+    pub fn impact_of_record(&self, name: &str) -> Impact {
+        let mut impact = Impact::default();
+        self.add_record(name, &mut impact);
+        self.close(&mut impact);
+        impact
+    }
+
+    /// What changing a file affects. Schema files affect what they declare;
+    /// source files affect the handlers importing them, directly or not.
+    /// A schema file is read for the active environment, and may use
+    /// constants and definitions from the other files of the schema.
+    pub fn impact_of_file(&self, project_root: &Path, path: &Path) -> Result<Impact> {
+        let mut impact = Impact::default();
+        if path.extension().is_some_and(|e| e == "ro" || e == "roh") {
+            let source = fs::read_to_string(path)?;
+            let (declared, diagnostics) = rohas_parser::Parser::parse_part_with_diagnostics(
+                &source,
+                &rohas_parser::env::active_environment(),
+            );
+            if !diagnostics.is_empty() {
+                let problems: Vec<String> = diagnostics.iter().map(|d| format!("{}:{}", path.display(), d)).collect();
+                return Err(CodegenError::InvalidSchema(problems.join("; ")));
+            }
+            for record in record_names(&declared) {
+                self.add_record(record, &mut impact);
+            }
+            impact.apis.extend(declared.apis.iter().map(|a| a.name.clone()));
+            impact.events.extend(declared.events.iter().map(|e| e.name.clone()));
+            impact.crons.extend(declared.crons.iter().map(|c| c.name.clone()));
+            impact.websockets.extend(declared.websockets.iter().map(|w| w.name.clone()));
+        } else {
+            let relative = relative_to(project_root, path);
+            let mut pending = vec![relative];
+            while let Some(file) = pending.pop() {
+                if !impact.files.insert(file.clone()) {
+                    continue;
+                }
+                pending.extend(
+                    self.imports
+                        .iter()
+                        .filter(|(_, imported)| imported.contains(&file))
+                        .map(|(importer, _)| importer.clone()),
+                );
+            }
+            for handler in &self.handlers {
+                if handler.file.as_ref().is_some_and(|f| impact.files.contains(f)) {
+                    self.add_handler(handler, &mut impact);
+                }
+            }
+        }
+        self.close(&mut impact);
+        Ok(impact)
+    }
+
+    fn add_record(&self, name: &str, impact: &mut Impact) {
+        let mut pending = vec![name.to_string()];
+        while let Some(name) = pending.pop() {
+            if !impact.records.insert(name.clone()) {
+                continue;
+            }
+            pending.extend(
+                self.records()
+                    .filter(|(_, fields)| {
+                        fields.iter().any(|f| base_type(&f.field_type).is_some_and(|t| t == name))
+                    })
+                    .map(|(record, _)| record.clone()),
+            );
+        }
+
+        for handler in &self.handlers {
+            if impact.records.iter().any(|r| handler.uses(r)) {
+                self.add_handler(handler, impact);
+            }
+        }
+        let uses = |type_name: Option<&str>| {
+            record(&self.schema, type_name).is_some_and(|r| impact.records.contains(&r))
+        };
+        let apis: Vec<String> = self
+            .schema
+            .apis
+            .iter()
+            .filter(|a| uses(a.body.as_deref()) || uses(Some(&a.response)))
+            .map(|a| a.name.clone())
+            .collect();
+        let events: Vec<String> = self
+            .schema
+            .events
+            .iter()
+            .filter(|e| uses(Some(&e.payload)))
+            .map(|e| e.name.clone())
+            .collect();
+        let websockets: Vec<String> = self
+            .schema
+            .websockets
+            .iter()
+            .filter(|w| uses(w.message.as_deref()))
+            .map(|w| w.name.clone())
+            .collect();
+        impact.apis.extend(apis);
+        impact.events.extend(events);
+        impact.websockets.extend(websockets);
+    }
+
+    fn add_handler(&self, handler: &HandlerDeps, impact: &mut Impact) {
+        impact.handlers.insert(handler.name.clone());
+        if let Some(file) = &handler.file {
+            impact.files.insert(file.clone());
+        }
+        let declarations = handler.declarations.iter().cloned();
+        match handler.kind {
+            HandlerKind::Api => impact.apis.extend(declarations),
+            HandlerKind::Event => impact.events.extend(declarations),
+            HandlerKind::Cron => impact.crons.extend(declarations),
+            HandlerKind::WebSocket => impact.websockets.extend(declarations),
+            // A middleware affects every API and websocket it guards.
+            HandlerKind::Middleware => {
+                for declaration in declarations {
+                    if self.schema.apis.iter().any(|a| a.name == declaration) {
+                        impact.apis.insert(declaration);
+                    } else {
+                        impact.websockets.insert(declaration);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Adds the handlers of affected declarations and follows the events
+    /// they trigger.
+    fn close(&self, impact: &mut Impact) {
+        let schema = &self.schema;
+        let mut pending: Vec<String> = schema
+            .apis
+            .iter()
+            .filter(|a| impact.apis.contains(&a.name))
+            .flat_map(|a| a.triggers.clone())
+            .chain(
+                schema
+                    .crons
+                    .iter()
+                    .filter(|c| impact.crons.contains(&c.name))
+                    .flat_map(|c| c.triggers.clone()),
+            )
+            .chain(
+                schema
+                    .websockets
+                    .iter()
+                    .filter(|w| impact.websockets.contains(&w.name))
+                    .flat_map(|w| w.triggers.clone()),
+            )
+            .chain(impact.events.iter().cloned())
+            .collect();
+
+        let mut visited = BTreeSet::new();
+        while let Some(name) = pending.pop() {
+            if !visited.insert(name.clone()) {
+                continue;
+            }
+            impact.events.insert(name.clone());
+            if let Some(event) = schema.events.iter().find(|e| e.name == name) {
+                pending.extend(event.triggers.iter().cloned());
+            }
+        }
+
+        for handler in &self.handlers {
+            let serves = |set: &BTreeSet<String>| handler.declarations.iter().any(|d| set.contains(d));
+            let affected = match handler.kind {
+                HandlerKind::Api => serves(&impact.apis),
+                HandlerKind::Event => serves(&impact.events),
+                HandlerKind::Cron => serves(&impact.crons),
+                HandlerKind::WebSocket => serves(&impact.websockets),
+                HandlerKind::Middleware => false,
+            };
+            if affected {
+                impact.handlers.insert(handler.name.clone());
+            }
+        }
+    }
+
+    fn records(&self) -> impl Iterator<Item = (&String, &Vec<rohas_parser::Field>)> {
+        self.schema
+            .models
+            .iter()
+            .map(|m| (&m.name, &m.fields))
+            .chain(self.schema.types.iter().map(|t| (&t.name, &t.fields)))
+            .chain(self.schema.inputs.iter().map(|i| (&i.name, &i.fields)))
+    }
+
+    pub fn is_record(&self, name: &str) -> bool {
+        self.records().any(|(record, _)| record == name)
+    }
+}
+
+fn record_names(schema: &Schema) -> Vec<&str> {
+    schema
+        .models
+        .iter()
+        .map(|m| m.name.as_str())
+        .chain(schema.types.iter().map(|t| t.name.as_str()))
+        .chain(schema.inputs.iter().map(|i| i.name.as_str()))
+        .collect()
+}
+
+/// The model, type or input a type name refers to, if any.
+fn record(schema: &Schema, type_name: Option<&str>) -> Option<String> {
+    let name = base_type(&FieldType::from_str(type_name?))?;
+    record_names(schema).contains(&name.as_str()).then_some(name)
+}
+
+fn collect_sources(project_root: &Path, dir: &Path, sources: &mut Vec<PathBuf>) -> Result<()> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            let skipped = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| SKIPPED_DIRS.contains(&n));
+            if !skipped {
+                collect_sources(project_root, &path, sources)?;
+            }
+        } else if path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| SOURCE_EXTENSIONS.contains(&e))
+        {
+            sources.push(relative_to(project_root, &path));
+        }
+    }
+    sources.sort();
+    Ok(())
+}
+
+fn relative_to(project_root: &Path, path: &Path) -> PathBuf {
+    let path = path.strip_prefix(project_root).unwrap_or(path);
+    normalize(path)
+}
+
+/// Resolves `.` and `..` without touching the filesystem.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// Project files `file` imports and the names it imports, from its import
+/// statements.
+fn parse_imports(project_root: &Path, file: &Path, content: &str) -> (BTreeSet<PathBuf>, BTreeSet<String>) {
+    let dir = file.parent().unwrap_or(Path::new(""));
+    let mut files = BTreeSet::new();
+    let mut names = BTreeSet::new();
+    let mut resolve = |candidates: Vec<PathBuf>| {
+        if let Some(found) = candidates.into_iter().map(|c| normalize(&c)).find(|c| project_root.join(c).is_file()) {
+            files.insert(found);
+        }
+    };
+
+    match file.extension().and_then(|e| e.to_str()) {
+        Some("py") => {
+            for line in content.lines().map(str::trim) {
+                let (module, imported) = if let Some(rest) = line.strip_prefix("from ") {
+                    match rest.split_once(" import ") {
+                        Some((module, imported)) => (module.trim(), imported),
+                        None => continue,
+                    }
+                } else if let Some(rest) = line.strip_prefix("import ") {
+                    (rest.split(" as ").next().unwrap_or(rest).trim(), "")
+                } else {
+                    continue;
+                };
+                names.extend(identifiers(imported));
+
+                let dots = module.chars().take_while(|c| *c == '.').count();
+                let base = if dots == 0 {
+                    PathBuf::from("src")
+                } else {
+                    (1..dots).fold(dir.to_path_buf(), |d, _| d.join(".."))
+                };
+                let module_path = base.join(module[dots..].replace('.', "/"));
+                let mut candidates = vec![module_path.with_extension("py"), module_path.join("__init__.py")];
+                // `from package import module`
+                for name in identifiers(imported) {
+                    candidates.insert(0, module_path.join(format!("{}.py", name)));
+                }
+                resolve(candidates);
+            }
+        }
+        Some("ts" | "tsx") => {
+            for (names_text, specifier) in ts_imports(content) {
+                names.extend(identifiers(names_text));
+                let base = if let Some(alias) = specifier.strip_prefix('@') {
+                    // tsconfig paths: `@generated/...` is `src/generated/...`
+                    PathBuf::from("src").join(alias)
+                } else if specifier.starts_with('.') {
+                    dir.join(specifier)
+                } else {
+                    continue;
+                };
+                resolve(vec![
+                    base.with_extension("ts"),
+                    base.with_extension("tsx"),
+                    base.join("index.ts"),
+                ]);
+            }
+        }
+        Some("rs") => {
+            for statement in content.split(';') {
+                // Skip the comments and attributes before the item.
+                let Some(start) = statement
+                    .lines()
+                    .position(|line| {
+                        let line = line.trim_start();
+                        line.starts_with("use crate::") || line.starts_with("pub use crate::") || line.starts_with("mod ") || line.starts_with("pub mod ")
+                    })
+                else {
+                    continue;
+                };
+                let item = statement.lines().skip(start).collect::<Vec<_>>().join(" ");
+                let item = item.trim().trim_start_matches("pub ");
+                if let Some(module) = item.strip_prefix("mod ") {
+                    let module = module.trim();
+                    if module.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                        resolve(vec![dir.join(format!("{}.rs", module)), dir.join(module).join("mod.rs")]);
+                    }
+                    continue;
+                }
+                let Some(path) = item.strip_prefix("use crate::") else {
+                    continue;
+                };
+                names.extend(identifiers(path));
+                // The longest prefix of the path that is a module file.
+                let segments: Vec<&str> = path
+                    .split("::")
+                    .map(str::trim)
+                    .take_while(|s| !s.starts_with('{'))
+                    .collect();
+                let candidates = (1..=segments.len())
+                    .rev()
+                    .flat_map(|n| {
+                        let module = PathBuf::from("src").join(segments[..n].join("/"));
+                        [module.with_extension("rs"), module.join("mod.rs")]
+                    })
+                    .collect();
+                resolve(candidates);
+            }
+        }
+        _ => {}
+    }
+    (files, names)
+}
+
+/// `(imported names, module specifier)` of each `import`/`export ... from`
+/// statement, which may span lines.
+fn ts_imports(content: &str) -> Vec<(&str, &str)> {
+    let mut imports = Vec::new();
+    for (start, _) in content.match_indices("import").chain(content.match_indices("export")) {
+        // Only statements at the start of a line.
+        if !content[..start].trim_end_matches([' ', '\t']).ends_with('\n') && start != 0 {
+            continue;
+        }
+        let statement = &content[start..];
+        let Some(open) = statement.find(['\'', '"']) else {
+            continue;
+        };
+        let head = &statement[..open];
+        // The quote belongs to a later statement.
+        if head.contains(';') {
+            continue;
+        }
+        let quote = &statement[open..=open];
+        let Some(length) = statement[open + 1..].find(quote) else {
+            continue;
+        };
+        let names = head.split_once('{').and_then(|(_, rest)| rest.split_once('}')).map(|(names, _)| names).unwrap_or("");
+        imports.push((names, &statement[open + 1..open + 1 + length]));
+    }
+    imports
+}
+
+fn identifiers(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|word| word.chars().next().is_some_and(|c| c.is_alphabetic()))
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rohas_parser::Parser;
+
+    const SCHEMA: &str = r#"
+model Address {
+  city String
+}
+
+model User {
+  id      Int
+  address Address
+}
+
+model Audit {
+  action String
+}
+
+api CreateUser {
+  method: POST
+  path: "/users"
+  body: User
+  response: User
+  triggers: [UserCreated]
+}
+
+api ListAudits {
+  method: GET
+  path: "/audits"
+  response: Audit
+}
+
+event UserCreated {
+  payload: User
+  handler: [send_welcome_email]
+  triggers: [WelcomeSent]
+}
+
+event WelcomeSent {
+  payload: Audit
+  handler: [record_audit]
+}
+"#;
+
+    fn project() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let files = [
+            ("src/handlers/api/create_user.py", "from generated.api.create_user import CreateUserRequest\nfrom utils.mailer import send\n"),
+            ("src/handlers/api/list_audits.py", "from generated.models.audit import Audit\n"),
+            ("src/handlers/events/send_welcome_email.py", "from utils.mailer import send\n"),
+            ("src/handlers/events/record_audit.py", "from generated.models import Audit\n"),
+            ("src/utils/mailer.py", "from .smtp import connect\n"),
+            ("src/utils/smtp.py", "import smtplib\n"),
+            ("src/generated/models/audit.py", "class Audit: ...\n"),
+        ];
+        for (path, content) in files {
+            let path = dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_index_reads_writes_and_imports() {
+        let dir = project();
+        let schema = Parser::parse_string(SCHEMA).unwrap();
+        let index = DependencyIndex::build(&schema, dir.path(), &Layout::default()).unwrap();
+
+        let handler = |name: &str| index.handlers.iter().find(|h| h.name == name).unwrap();
+        let create_user = handler("CreateUser");
+        assert_eq!(create_user.file, Some(PathBuf::from("src/handlers/api/create_user.py")));
+        assert_eq!(create_user.reads, BTreeSet::from(["User".to_string()]));
+        assert_eq!(create_user.triggers, BTreeSet::from(["UserCreated".to_string()]));
+        assert_eq!(handler("send_welcome_email").writes, BTreeSet::from(["Audit".to_string()]));
+        assert_eq!(handler("ListAudits").imports, BTreeSet::from(["Audit".to_string()]));
+        assert_eq!(handler("record_audit").imports, BTreeSet::from(["Audit".to_string()]));
+
+        assert_eq!(
+            index.imports[Path::new("src/utils/mailer.py")],
+            BTreeSet::from([PathBuf::from("src/utils/smtp.py")])
+        );
+    }
+
+    #[test]
+    fn test_index_finds_handlers_in_a_custom_layout() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("src/app/routes/api/create_user.py");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "").unwrap();
+        let schema = Parser::parse_string(SCHEMA).unwrap();
+        let layout = Layout {
+            handlers_dir: PathBuf::from("app/routes"),
+            ..Layout::default()
+        };
+
+        let index = DependencyIndex::build(&schema, dir.path(), &layout).unwrap();
+        let create_user = index.handlers.iter().find(|h| h.name == "CreateUser").unwrap();
+        assert_eq!(create_user.file, Some(PathBuf::from("src/app/routes/api/create_user.py")));
+    }
+
+    #[test]
+    fn test_impact_of_record_follows_embedding_and_triggers() {
+        let dir = project();
+        let schema = Parser::parse_string(SCHEMA).unwrap();
+        let index = DependencyIndex::build(&schema, dir.path(), &Layout::default()).unwrap();
+
+        let impact = index.impact_of_record("Address");
+        assert_eq!(impact.records, BTreeSet::from(["Address".to_string(), "User".to_string()]));
+        assert_eq!(impact.apis, BTreeSet::from(["CreateUser".to_string()]));
+        assert_eq!(
+            impact.events,
+            BTreeSet::from(["UserCreated".to_string(), "WelcomeSent".to_string()])
+        );
+        assert!(impact.handlers.contains("send_welcome_email"));
+        assert!(impact.handlers.contains("record_audit"));
+        assert!(!impact.handlers.contains("ListAudits"));
+    }
+
+    #[test]
+    fn test_impact_of_shared_file_reaches_importers() {
+        let dir = project();
+        let schema = Parser::parse_string(SCHEMA).unwrap();
+        let index = DependencyIndex::build(&schema, dir.path(), &Layout::default()).unwrap();
+
+        let impact = index
+            .impact_of_file(dir.path(), &dir.path().join("src/utils/smtp.py"))
+            .unwrap();
+        assert_eq!(
+            impact.files,
+            BTreeSet::from([
+                PathBuf::from("src/handlers/api/create_user.py"),
+                PathBuf::from("src/handlers/events/send_welcome_email.py"),
+                PathBuf::from("src/utils/mailer.py"),
+                PathBuf::from("src/utils/smtp.py"),
+            ])
+        );
+        assert!(impact.handlers.contains("CreateUser"));
+        assert!(impact.handlers.contains("send_welcome_email"));
+        assert!(!impact.handlers.contains("ListAudits"));
+    }
+
+    #[test]
+    fn test_impact_of_schema_file_using_a_constant_from_another_file() {
+        let dir = project();
+        let constants = dir.path().join("schema/constants.ro");
+        let uploads = dir.path().join("schema/uploads.ro");
+        fs::create_dir_all(constants.parent().unwrap()).unwrap();
+        fs::write(&constants, "const DEFAULT_REGION = \"us-east-1\"\n").unwrap();
+        fs::write(&uploads, "model Upload {\n  id     Int\n  region String @default(DEFAULT_REGION)\n}\n").unwrap();
+        let schema = Parser::parse_files_for_environment(&[&constants, &uploads], rohas_parser::env::DEFAULT_ENVIRONMENT)
+            .unwrap();
+        let index = DependencyIndex::build(&schema, dir.path(), &Layout::default()).unwrap();
+
+        let impact = index.impact_of_file(dir.path(), &uploads).unwrap();
+        assert_eq!(impact.records, BTreeSet::from(["Upload".to_string()]));
+
+        fs::write(&uploads, "model Upload {\n  id Int\n").unwrap();
+        assert!(index.impact_of_file(dir.path(), &uploads).is_err());
+    }
+
+    #[test]
+    fn test_parse_typescript_and_rust_imports() {
+        let dir = tempfile::tempdir().unwrap();
+        for path in ["src/generated/models/user.ts", "src/lib/db.ts", "src/generated/mod.rs", "src/util.rs"] {
+            let path = dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+
+        let typescript = "import {\n  User,\n  UserSchema,\n} from '@generated/models/user';\nimport { query } from \"../../lib/db\";\nimport express from 'express';\n";
+        let (files, names) = parse_imports(dir.path(), Path::new("src/handlers/api/get_user.ts"), typescript);
+        assert_eq!(
+            files,
+            BTreeSet::from([PathBuf::from("src/generated/models/user.ts"), PathBuf::from("src/lib/db.ts")])
+        );
+        assert!(names.contains("User") && names.contains("query"));
+
+        let rust = "// Handler\nuse crate::generated::{User, State};\nuse crate::util::retry;\nuse std::time::Duration;\n";
+        let (files, names) = parse_imports(dir.path(), Path::new("src/handlers/api/get_user.rs"), rust);
+        assert_eq!(
+            files,
+            BTreeSet::from([PathBuf::from("src/generated/mod.rs"), PathBuf::from("src/util.rs")])
+        );
+        assert!(names.contains("User"));
+    }
+}
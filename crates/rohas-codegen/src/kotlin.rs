@@ -0,0 +1,484 @@
+//! Kotlin client target: `kotlinx.serialization` data classes for the
+//! schema's models, types, inputs and events, and a Ktor client for its APIs.
+//!
+//! Unlike the handler languages it emits no handler stubs or project files;
+//! the output directory is a Kotlin source root and everything is written to
+//! the `rohas.generated` package.
+
+use crate::error::Result;
+use rohas_parser::{Api, ContentType, Field, FieldType, Schema};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+const PACKAGE: &str = "rohas.generated";
+
+const KEYWORDS: &[&str] = &[
+    "as", "break", "class", "continue", "do", "else", "false", "for", "fun", "if", "in",
+    "interface", "is", "null", "object", "package", "return", "super", "this", "throw", "true",
+    "try", "typealias", "typeof", "val", "var", "when", "while",
+];
+
+pub fn generate(schema: &Schema, output_dir: &Path) -> Result<()> {
+    let package_dir = output_dir.join(PACKAGE.replace('.', "/"));
+    fs::create_dir_all(&package_dir)?;
+
+    fs::write(package_dir.join("Models.kt"), generate_models(schema))?;
+    fs::write(package_dir.join("Inputs.kt"), generate_inputs(schema))?;
+    fs::write(package_dir.join("Events.kt"), generate_events(schema))?;
+    fs::write(package_dir.join("Errors.kt"), generate_errors())?;
+    fs::write(package_dir.join("Client.kt"), generate_client(schema))?;
+
+    Ok(())
+}
+
+fn header(imports: &BTreeSet<&str>) -> String {
+    let mut content = String::new();
+    content.push_str("// Generated by Rohas - Do not edit\n\n");
+    content.push_str(&format!("package {}\n\n", PACKAGE));
+    for import in imports {
+        content.push_str(&format!("import {}\n", import));
+    }
+    content.push('\n');
+    content
+}
+
+/// Imports the Kotlin types of `field_type` need, besides the serializer.
+fn collect_imports(field_type: &FieldType, imports: &mut BTreeSet<&'static str>) {
+    match field_type {
+        FieldType::DateTime => {
+            imports.insert("kotlinx.datetime.Instant");
+        }
+        FieldType::Json => {
+            imports.insert("kotlinx.serialization.json.JsonElement");
+        }
+        FieldType::Array(inner) => collect_imports(inner, imports),
+        _ => {}
+    }
+}
+
+fn escape_keyword(name: &str) -> String {
+    if KEYWORDS.contains(&name) {
+        format!("`{}`", name)
+    } else {
+        name.to_string()
+    }
+}
+
+fn data_class(name: &str, fields: &[Field]) -> String {
+    let mut content = String::new();
+    content.push_str("@Serializable\n");
+    if fields.is_empty() {
+        content.push_str(&format!("class {}\n", name));
+        return content;
+    }
+
+    content.push_str(&format!("data class {}(\n", name));
+    for field in fields {
+        let kotlin_type = field.field_type.to_kotlin();
+        if field.optional {
+            content.push_str(&format!(
+                "    val {}: {}? = null,\n",
+                escape_keyword(&field.name),
+                kotlin_type
+            ));
+        } else {
+            content.push_str(&format!("    val {}: {},\n", escape_keyword(&field.name), kotlin_type));
+        }
+    }
+    content.push_str(")\n");
+    content
+}
+
+fn data_classes<'a>(classes: impl Iterator<Item = (&'a str, &'a [Field])> + Clone) -> String {
+    let mut imports = BTreeSet::from(["kotlinx.serialization.Serializable"]);
+    for (_, fields) in classes.clone() {
+        for field in fields {
+            collect_imports(&field.field_type, &mut imports);
+        }
+    }
+
+    let mut content = header(&imports);
+    let classes: Vec<String> = classes.map(|(name, fields)| data_class(name, fields)).collect();
+    content.push_str(&classes.join("\n"));
+    content
+}
+
+/// `Models.kt`: models and `type` declarations.
+fn generate_models(schema: &Schema) -> String {
+    data_classes(
+        schema
+            .models
+            .iter()
+            .map(|m| (m.name.as_str(), m.fields.as_slice()))
+            .chain(schema.types.iter().map(|t| (t.name.as_str(), t.fields.as_slice()))),
+    )
+}
+
+/// `Inputs.kt`: request bodies.
+fn generate_inputs(schema: &Schema) -> String {
+    data_classes(schema.inputs.iter().map(|i| (i.name.as_str(), i.fields.as_slice())))
+}
+
+/// `Events.kt`: one class per event with its payload, plus the event names.
+fn generate_events(schema: &Schema) -> String {
+    let mut imports = BTreeSet::from([
+        "kotlinx.datetime.Instant",
+        "kotlinx.serialization.Serializable",
+    ]);
+    for event in &schema.events {
+        collect_imports(&FieldType::from_str(&event.payload), &mut imports);
+    }
+
+    let mut content = header(&imports);
+    for event in &schema.events {
+        content.push_str("@Serializable\n");
+        content.push_str(&format!("data class {}(\n", event.name));
+        content.push_str(&format!(
+            "    val payload: {},\n",
+            FieldType::from_str(&event.payload).to_kotlin()
+        ));
+        content.push_str("    val timestamp: Instant,\n");
+        content.push_str(")\n\n");
+    }
+
+    content.push_str("object EventNames {\n");
+    for event in &schema.events {
+        content.push_str(&format!("    const val {} = \"{}\"\n", event.name, event.name));
+    }
+    content.push_str("}\n");
+    content
+}
+
+/// `Errors.kt`: the engine's `application/problem+json` error responses.
+fn generate_errors() -> String {
+    let imports = BTreeSet::from([
+        "io.ktor.client.statement.HttpResponse",
+        "io.ktor.client.statement.bodyAsText",
+        "kotlinx.serialization.Serializable",
+        "kotlinx.serialization.json.jsonObject",
+        "kotlinx.serialization.json.jsonPrimitive",
+    ]);
+    let mut content = header(&imports);
+    content.push_str(
+        r#"/** A request body field that failed validation. */
+@Serializable
+data class FieldError(
+    val field: String,
+    val message: String,
+)
+
+/** An API error response (RFC 7807). */
+@Serializable
+data class ProblemDetails(
+    val type: String = "about:blank",
+    val title: String,
+    val status: Int,
+    val detail: String? = null,
+    val instance: String? = null,
+    /** Present on validation failures (status 422). */
+    val errors: List<FieldError> = emptyList(),
+)
+
+/** Thrown by [RohasClient] for error responses. */
+class ProblemException(val problem: ProblemDetails) : Exception(problem.detail ?: problem.title) {
+    val status: Int get() = problem.status
+
+    companion object {
+        /** Reads an error response, accepting the legacy `{ "error": ... }` body as well. */
+        suspend fun from(response: HttpResponse): ProblemException {
+            val text = response.bodyAsText()
+            val problem = runCatching { RohasJson.decodeFromString(ProblemDetails.serializer(), text) }
+                .getOrElse {
+                    val legacy = runCatching {
+                        RohasJson.parseToJsonElement(text).jsonObject["error"]?.jsonPrimitive?.content
+                    }.getOrNull()
+                    ProblemDetails(
+                        title = response.status.description,
+                        status = response.status.value,
+                        detail = legacy,
+                    )
+                }
+            return ProblemException(problem)
+        }
+    }
+}
+"#,
+    );
+    content
+}
+
+/// The Kotlin type of an API body or response type name.
+fn api_type(type_name: &str) -> String {
+    FieldType::from_str(type_name).to_kotlin()
+}
+
+fn method_name(api_name: &str) -> String {
+    let mut chars = api_name.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Kotlin string template for `path`, with `{param}` segments filled from
+/// the method's parameters.
+fn path_template(path: &str) -> (String, Vec<String>) {
+    let mut template = String::new();
+    let mut params = Vec::new();
+    let mut rest = path;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        let param = &rest[start + 1..start + len];
+        template.push_str(&rest[..start]);
+        template.push_str(&format!("${{{}.encodeURLPathPart()}}", escape_keyword(param)));
+        params.push(param.to_string());
+        rest = &rest[start + len + 1..];
+    }
+    template.push_str(rest);
+    (template, params)
+}
+
+fn generate_api_method(api: &Api) -> String {
+    let mut content = String::new();
+    let (path, path_params) = path_template(&api.path);
+
+    if api.consumes == ContentType::MsgPack || api.produces == ContentType::MsgPack {
+        content.push_str(&format!(
+            "    // {}: MessagePack bodies are not supported by the generated client.\n",
+            api.name
+        ));
+        return content;
+    }
+
+    let mut params: Vec<String> = path_params
+        .iter()
+        .map(|p| format!("{}: String", escape_keyword(p)))
+        .collect();
+    if let Some(body) = api.body_type() {
+        params.push(format!("body: {}", api_type(&body)));
+    }
+    params.push("query: Map<String, String> = emptyMap()".to_string());
+
+    let response = match api.produces {
+        ContentType::OctetStream => "ByteArray".to_string(),
+        ContentType::Csv => "String".to_string(),
+        _ if api.paginated => format!("Page<{}>", api_type(&api.response)),
+        _ => api_type(&api.response),
+    };
+
+    let method = match api.method {
+        rohas_parser::HttpMethod::GET => "Get",
+        rohas_parser::HttpMethod::POST => "Post",
+        rohas_parser::HttpMethod::PUT => "Put",
+        rohas_parser::HttpMethod::PATCH => "Patch",
+        rohas_parser::HttpMethod::DELETE => "Delete",
+    };
+
+    let send = match api.produces {
+        ContentType::OctetStream | ContentType::Csv => "sendRaw",
+        _ => "send",
+    };
+
+    content.push_str(&format!("    /** `{:?} {}` */\n", api.method, api.path));
+    content.push_str(&format!(
+        "    suspend fun {}({}): {} =\n",
+        method_name(&api.name),
+        params.join(", "),
+        response
+    ));
+    match api.body_type() {
+        Some(_) => {
+            content.push_str(&format!(
+                "        {}(HttpMethod.{}, \"{}\", query) {{\n",
+                send, method, path
+            ));
+            content.push_str(&format!(
+                "            contentType(ContentType.parse(\"{}\"))\n",
+                api.consumes.mime()
+            ));
+            content.push_str("            setBody(body)\n");
+            content.push_str("        }\n");
+        }
+        None => {
+            content.push_str(&format!(
+                "        {}(HttpMethod.{}, \"{}\", query)\n",
+                send, method, path
+            ));
+        }
+    }
+    content
+}
+
+/// `Client.kt`: a Ktor client with one suspend function per API.
+fn generate_client(schema: &Schema) -> String {
+    let mut imports = BTreeSet::from([
+        "io.ktor.client.HttpClient",
+        "io.ktor.client.call.body",
+        "io.ktor.client.plugins.contentnegotiation.ContentNegotiation",
+        "io.ktor.client.request.HttpRequestBuilder",
+        "io.ktor.client.request.parameter",
+        "io.ktor.client.request.request",
+        "io.ktor.client.request.setBody",
+        "io.ktor.http.ContentType",
+        "io.ktor.http.HttpMethod",
+        "io.ktor.http.contentType",
+        "io.ktor.http.encodeURLPathPart",
+        "io.ktor.http.isSuccess",
+        "io.ktor.serialization.kotlinx.json.json",
+        "kotlinx.serialization.Serializable",
+        "kotlinx.serialization.json.Json",
+    ]);
+    for api in &schema.apis {
+        if let Some(body) = api.body_type() {
+            collect_imports(&FieldType::from_str(&body), &mut imports);
+        }
+        collect_imports(&FieldType::from_str(&api.response), &mut imports);
+    }
+
+    let mut content = header(&imports);
+    content.push_str(
+        r#"/** JSON settings shared by the client and [ProblemException]. */
+val RohasJson = Json {
+    ignoreUnknownKeys = true
+    explicitNulls = false
+}
+
+/** The `{ "data": ... }` envelope handlers respond with. */
+@Serializable
+data class ApiResponse<T>(val data: T)
+
+/** A page of a paginated list API. */
+@Serializable
+data class Page<T>(
+    val items: List<T>,
+    /** Number of items across all pages. */
+    val total: Long,
+    /** Passed back as the `cursor` query parameter for the next page; null on the last page. */
+    val next_cursor: String? = null,
+)
+
+/**
+ * Calls the schema's APIs. Error responses are thrown as [ProblemException].
+ *
+ * Requires `io.ktor:ktor-client-content-negotiation`,
+ * `io.ktor:ktor-serialization-kotlinx-json` and `org.jetbrains.kotlinx:kotlinx-datetime`.
+ */
+class RohasClient(
+    baseUrl: String,
+    private val http: HttpClient = HttpClient {
+        install(ContentNegotiation) { json(RohasJson) }
+    },
+) {
+    private val baseUrl = baseUrl.trimEnd('/')
+
+"#,
+    );
+
+    let methods: Vec<String> = schema.apis.iter().map(generate_api_method).collect();
+    content.push_str(&methods.join("\n"));
+    if !methods.is_empty() {
+        content.push('\n');
+    }
+
+    content.push_str(
+        r#"    private suspend inline fun <reified T> send(
+        method: HttpMethod,
+        path: String,
+        query: Map<String, String>,
+        block: HttpRequestBuilder.() -> Unit = {},
+    ): T = sendRaw<ApiResponse<T>>(method, path, query, block).data
+
+    private suspend inline fun <reified T> sendRaw(
+        method: HttpMethod,
+        path: String,
+        query: Map<String, String>,
+        block: HttpRequestBuilder.() -> Unit = {},
+    ): T {
+        val response = http.request(baseUrl + path) {
+            this.method = method
+            query.forEach { (key, value) -> parameter(key, value) }
+            block()
+        }
+        if (!response.status.isSuccess()) {
+            throw ProblemException.from(response)
+        }
+        return response.body()
+    }
+}
+"#,
+    );
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rohas_parser::Parser;
+
+    const SCHEMA: &str = r#"
+model Order {
+  id        Int
+  object    String
+  in        String[]
+  note      String?
+  createdAt DateTime
+}
+
+input UpdateOrderInput {
+  note: String?
+}
+
+api UpdateOrder {
+  method: PATCH
+  path: "/orders/{id}"
+  body: UpdateOrderInput
+  response: Order
+}
+
+api GetOrder {
+  method: GET
+  path: "/orders/{class}"
+  response: Order
+}
+"#;
+
+    #[test]
+    fn test_models_are_serializable_data_classes() {
+        let schema = Parser::parse_string(SCHEMA).unwrap();
+        let models = generate_models(&schema);
+
+        assert!(models.starts_with(
+            "// Generated by Rohas - Do not edit\n\npackage rohas.generated\n\nimport kotlinx.datetime.Instant\nimport kotlinx.serialization.Serializable\n\n"
+        ));
+        assert!(models.contains(
+            "@Serializable\ndata class Order(\n    val id: Long,\n    val `object`: String,\n    val `in`: List<String>,\n    val note: String? = null,\n    val createdAt: Instant,\n)\n"
+        ));
+    }
+
+    #[test]
+    fn test_client_has_a_method_per_api() {
+        let schema = Parser::parse_string(SCHEMA).unwrap();
+        let client = generate_client(&schema);
+
+        assert!(client.contains(
+            "    /** `PATCH /orders/{id}` */\n    suspend fun updateOrder(id: String, body: UpdateOrderInput, query: Map<String, String> = emptyMap()): Order =\n        send(HttpMethod.Patch, \"/orders/${id.encodeURLPathPart()}\", query) {\n            contentType(ContentType.parse(\"application/json\"))\n            setBody(body)\n        }\n"
+        ));
+        assert!(client.contains("class RohasClient(\n    baseUrl: String,\n"));
+        assert!(client.contains("import io.ktor.http.encodeURLPathPart\n"));
+    }
+
+    #[test]
+    fn test_keywords_are_escaped() {
+        let schema = Parser::parse_string(SCHEMA).unwrap();
+        let client = generate_client(&schema);
+
+        assert!(client.contains(
+            "    suspend fun getOrder(`class`: String, query: Map<String, String> = emptyMap()): Order =\n        send(HttpMethod.Get, \"/orders/${`class`.encodeURLPathPart()}\", query)\n"
+        ));
+        assert_eq!(escape_keyword("when"), "`when`");
+        assert_eq!(escape_keyword("whenever"), "whenever");
+    }
+}
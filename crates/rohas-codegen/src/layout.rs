@@ -0,0 +1,114 @@
+//! Where handler stubs are generated and how their files are named.
+//!
+//! The generated types always go to `generated/` under the output directory,
+//! since handlers import them from there; the handler directories can be
+//! moved so that projects keep their own conventions.
+
+use crate::templates;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// How the files of handlers and middlewares are named.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileNaming {
+    /// Each language's own convention, e.g. `Health.ts` and `health.py` for
+    /// the `Health` API.
+    #[default]
+    Schema,
+    /// snake_case for every handler, e.g. `health.ts`.
+    SnakeCase,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout {
+    /// Directory of handlers, relative to the output directory.
+    pub handlers_dir: PathBuf,
+    /// Directory of middlewares, relative to the output directory.
+    pub middlewares_dir: PathBuf,
+    pub file_naming: FileNaming,
+    /// Write a stub, and its test, for each handler without a file.
+    pub handler_stubs: bool,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self {
+            handlers_dir: PathBuf::from("handlers"),
+            middlewares_dir: PathBuf::from("middlewares"),
+            file_naming: FileNaming::Schema,
+            handler_stubs: true,
+        }
+    }
+}
+
+impl Layout {
+    /// Whether handlers are where the Rust crate's module tree expects them.
+    pub fn has_default_paths(&self) -> bool {
+        let default = Self::default();
+        self.handlers_dir == default.handlers_dir
+            && self.middlewares_dir == default.middlewares_dir
+            && self.file_naming == default.file_naming
+    }
+
+    /// Directory of the `kind` handlers (`api`, `events`, `cron` or
+    /// `websockets`).
+    pub fn handlers(&self, output_dir: &Path, kind: &str) -> PathBuf {
+        output_dir.join(&self.handlers_dir).join(kind)
+    }
+
+    pub fn middlewares(&self, output_dir: &Path) -> PathBuf {
+        output_dir.join(&self.middlewares_dir)
+    }
+
+    /// File name, without extension, of a handler whose file the language
+    /// names `name` by convention.
+    pub fn file_stem(&self, name: &str) -> String {
+        match self.file_naming {
+            FileNaming::Schema => name.to_string(),
+            FileNaming::SnakeCase => templates::to_snake_case(name),
+        }
+    }
+
+    /// Relative import of a handler module from `generated/`, e.g.
+    /// `../handlers/api/Health`.
+    pub fn import_from_generated(&self, kind: &str, stem: &str) -> String {
+        format!("../{}/{}/{}", slash_path(&self.handlers_dir), kind, stem)
+    }
+
+    /// Python module of a handler, e.g. `handlers.api.health`.
+    pub fn python_module(&self, kind: &str, stem: &str) -> String {
+        format!("{}.{}.{}", slash_path(&self.handlers_dir).replace('/', "."), kind, stem)
+    }
+}
+
+fn slash_path(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handler_paths() {
+        let layout = Layout {
+            handlers_dir: PathBuf::from("app/routes"),
+            file_naming: FileNaming::SnakeCase,
+            ..Layout::default()
+        };
+
+        assert_eq!(
+            layout.handlers(Path::new("src"), "api"),
+            PathBuf::from("src/app/routes/api")
+        );
+        assert_eq!(layout.file_stem("CreateUser"), "create_user");
+        assert_eq!(layout.import_from_generated("api", "create_user"), "../app/routes/api/create_user");
+        assert_eq!(layout.python_module("events", "on_created"), "app.routes.events.on_created");
+        assert!(!layout.has_default_paths());
+        assert!(Layout::default().has_default_paths());
+    }
+}
@@ -1,13 +1,24 @@
+pub mod asyncapi;
 pub mod config;
+pub mod docs;
 pub mod error;
+pub mod event_schema;
+pub mod example;
+pub mod factory;
 pub mod generator;
+pub mod graph;
+pub mod impact;
+pub mod kotlin;
+pub mod layout;
 pub mod python;
 pub mod rust;
+pub mod swift;
 pub mod templates;
 pub mod typescript;
 
 pub use error::{CodegenError, Result};
 pub use generator::Generator;
+pub use layout::{FileNaming, Layout};
 
 use rohas_parser::Schema;
 use std::path::Path;
@@ -17,6 +28,9 @@ pub enum Language {
     TypeScript,
     Python,
     Rust,
+    /// Client-only targets: models and API clients, no handlers.
+    Kotlin,
+    Swift,
 }
 
 pub fn generate(schema: &Schema, output_dir: &Path, lang: Language) -> Result<()> {
@@ -1,6 +1,9 @@
 use crate::error::Result;
-use crate::templates;
-use rohas_parser::{Api, Event, FieldType, Model, Schema, Type, WebSocket};
+use crate::example::Example;
+use crate::factory::FakeValue;
+use crate::layout::Layout;
+use crate::{config, templates};
+use rohas_parser::{Api, Event, Field, FieldType, Model, Schema, TokenSource, Type, WebSocket};
 use std::fs;
 use std::path::Path;
 
@@ -16,10 +19,96 @@ pub fn generate_models(schema: &Schema, output_dir: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Generate `generated/factories.py`, with a builder per model that fills
+/// every field with fake data.
+pub fn generate_factories(schema: &Schema, output_dir: &Path) -> Result<()> {
+    let mut content = String::new();
+
+    content.push_str("import itertools\n");
+    content.push_str("from datetime import datetime\n");
+    content.push_str("from typing import Any, Dict, List\n\n");
+
+    for model in &schema.models {
+        content.push_str(&format!(
+            "from .models.{} import {}\n",
+            templates::to_snake_case(&model.name),
+            model.name
+        ));
+    }
+
+    content.push_str(
+        r#"
+
+_sequence = itertools.count(1)
+
+
+def _next_sequence() -> int:
+    return next(_sequence)
+
+
+def reset_factory_sequence() -> None:
+    """Restart the sequence used for ids and unique fields."""
+    global _sequence
+    _sequence = itertools.count(1)
+"#,
+    );
+
+    for model in &schema.models {
+        let values: Vec<(&Field, FakeValue)> = model
+            .fields
+            .iter()
+            .map(|f| (f, FakeValue::for_field(model, f, schema)))
+            .collect();
+
+        content.push_str(&format!("\n\nclass {}Factory:\n", model.name));
+        content.push_str(&format!(
+            "    \"\"\"Builds `{}` values with fake data.\"\"\"\n\n",
+            model.name
+        ));
+        content.push_str("    def __init__(self) -> None:\n");
+        content.push_str("        self._overrides: Dict[str, Any] = {}\n");
+
+        for field in &model.fields {
+            content.push_str(&format!(
+                "\n    def with_{}(self, value: Any) -> \"{}Factory\":\n",
+                templates::to_snake_case(&field.name),
+                model.name
+            ));
+            content.push_str(&format!("        self._overrides[\"{}\"] = value\n", field.name));
+            content.push_str("        return self\n");
+        }
+
+        content.push_str(&format!("\n    def build(self) -> {}:\n", model.name));
+        if values.iter().any(|(_, v)| v.uses_sequence()) {
+            content.push_str("        n = _next_sequence()\n");
+        }
+        content.push_str("        values: Dict[str, Any] = {\n");
+        for (field, value) in &values {
+            content.push_str(&format!("            \"{}\": {},\n", field.name, value.to_python()));
+        }
+        content.push_str("        }\n");
+        content.push_str("        values.update(self._overrides)\n");
+        content.push_str(&format!("        return {}(**values)\n", model.name));
+
+        content.push_str(&format!(
+            "\n    def build_list(self, count: int) -> List[{}]:\n",
+            model.name
+        ));
+        content.push_str("        return [self.build() for _ in range(count)]\n");
+    }
+
+    fs::write(output_dir.join("generated/factories.py"), content)?;
+    Ok(())
+}
+
 fn generate_model_content(model: &Model) -> String {
     let mut content = String::new();
 
-    content.push_str("from pydantic import BaseModel\n");
+    if model.fields.iter().any(|f| f.computed().is_some()) {
+        content.push_str("from pydantic import BaseModel, Field\n");
+    } else {
+        content.push_str("from pydantic import BaseModel\n");
+    }
     content.push_str("from typing import Optional\n");
     content.push_str("from datetime import datetime\n\n");
 
@@ -32,7 +121,9 @@ fn generate_model_content(model: &Model) -> String {
         } else {
             py_type
         };
-        content.push_str(&format!("    {}: {}\n", field.name, type_hint));
+        // Computed fields come from the database and cannot be reassigned.
+        let frozen = if field.computed().is_some() { " = Field(frozen=True)" } else { "" };
+        content.push_str(&format!("    {}: {}{}\n", field.name, type_hint, frozen));
     }
 
     if model.fields.is_empty() {
@@ -53,6 +144,8 @@ pub fn generate_dtos(schema: &Schema, output_dir: &Path) -> Result<()> {
             name: input.name.clone(),
             fields: input.fields.clone(),
             attributes: vec![],
+            example: input.example.clone(),
+            doc: input.doc.clone(),
         });
         let file_name = format!("{}.py", templates::to_snake_case(&input.name));
         fs::write(dto_dir.join(file_name), content)?;
@@ -63,6 +156,8 @@ pub fn generate_dtos(schema: &Schema, output_dir: &Path) -> Result<()> {
             name: type_def.name.clone(),
             fields: type_def.fields.clone(),
             attributes: vec![],
+            example: type_def.example.clone(),
+            doc: type_def.doc.clone(),
         });
         let file_name = format!("{}.py", templates::to_snake_case(&type_def.name));
         fs::write(dto_dir.join(file_name), content)?;
@@ -71,7 +166,7 @@ pub fn generate_dtos(schema: &Schema, output_dir: &Path) -> Result<()> {
     Ok(())
 }
 
-pub fn generate_apis(schema: &Schema, output_dir: &Path) -> Result<()> {
+pub fn generate_apis(schema: &Schema, output_dir: &Path, layout: &Layout) -> Result<()> {
     let api_dir = output_dir.join("generated/api");
 
     for api in &schema.apis {
@@ -80,7 +175,11 @@ pub fn generate_apis(schema: &Schema, output_dir: &Path) -> Result<()> {
         fs::write(api_dir.join(file_name), content)?;
     }
 
-    let handlers_dir = output_dir.join("handlers/api");
+    if !layout.handler_stubs {
+        return Ok(());
+    }
+
+    let handlers_dir = layout.handlers(output_dir, "api");
     for api in &schema.apis {
         let file_name = format!("{}.py", templates::to_snake_case(&api.name));
         let handler_path = handlers_dir.join(&file_name);
@@ -88,6 +187,7 @@ pub fn generate_apis(schema: &Schema, output_dir: &Path) -> Result<()> {
         if !handler_path.exists() {
             let content = generate_api_handler_stub(api);
             fs::write(handler_path, content)?;
+            generate_api_test(api, schema, output_dir)?;
         }
     }
 
@@ -130,36 +230,44 @@ fn generate_api_content(api: &Api, schema: &Schema) -> String {
     content.push_str("from pydantic import BaseModel\n");
     content.push_str("from typing import Callable, Awaitable, Dict, Optional\n");
 
-    let response_field_type = FieldType::from_str(&api.response);
-    let response_py_type = response_field_type.to_python();
+    let body = api.body_type();
+    let response = api.response_type();
+    let response_field_type = FieldType::from_str(response);
+    let mut response_py_type = response_field_type.to_python();
+    if api.paginated {
+        content.push_str("from ..page import Page\n");
+        response_py_type = format!("Page[{}]", response_py_type);
+    }
 
     let is_custom_type = matches!(response_field_type, FieldType::Custom(_));
     if is_custom_type {
         // Check if it's a type (DTO) or a model
-        let is_type = schema.types.iter().any(|t| t.name == api.response);
-        let is_input = schema.inputs.iter().any(|i| i.name == api.response);
+        let is_type = schema.types.iter().any(|t| t.name == response);
+        let is_input = schema.inputs.iter().any(|i| i.name == response);
         
         if is_type || is_input {
             content.push_str(&format!(
                 "from ..dto.{} import {}\n",
-                templates::to_snake_case(&api.response),
-                api.response
+                templates::to_snake_case(response),
+                response
             ));
         } else {
             content.push_str(&format!(
                 "from ..models.{} import {}\n",
-                templates::to_snake_case(&api.response),
-                api.response
+                templates::to_snake_case(response),
+                response
             ));
         }
     }
 
-    if let Some(body) = &api.body {
-        content.push_str(&format!(
-            "from ..dto.{} import {}\n",
-            templates::to_snake_case(body),
-            body
-        ));
+    if let Some(body) = &body {
+        if matches!(FieldType::from_str(body), FieldType::Custom(_)) {
+            content.push_str(&format!(
+                "from ..dto.{} import {}\n",
+                templates::to_snake_case(body),
+                body
+            ));
+        }
     }
 
     let path_params = extract_path_params(&api.path);
@@ -170,13 +278,14 @@ fn generate_api_content(api: &Api, schema: &Schema) -> String {
         content.push_str(&format!("    {}: str\n", param));
     }
 
-    if let Some(body) = &api.body {
-        content.push_str(&format!("    body: {}\n", body));
+    if let Some(body) = &body {
+        content.push_str(&format!("    body: {}\n", FieldType::from_str(body).to_python()));
     }
 
     content.push_str("    query_params: Dict[str, str] = {}\n");
+    content.push_str("    cookies: Dict[str, str] = {}\n");
 
-    if path_params.is_empty() && api.body.is_none() {
+    if path_params.is_empty() && body.is_none() {
         // We still have query_params, so no pass needed
     }
 
@@ -222,7 +331,7 @@ fn generate_api_handler_stub(api: &Api) -> String {
     content
 }
 
-pub fn generate_events(schema: &Schema, output_dir: &Path) -> Result<()> {
+pub fn generate_events(schema: &Schema, output_dir: &Path, layout: &Layout) -> Result<()> {
     let events_dir = output_dir.join("generated/events");
 
     for event in &schema.events {
@@ -231,17 +340,27 @@ pub fn generate_events(schema: &Schema, output_dir: &Path) -> Result<()> {
         fs::write(events_dir.join(file_name), content)?;
     }
 
-    let handlers_dir = output_dir.join("handlers/events");
+    if !layout.handler_stubs {
+        return Ok(());
+    }
+
+    let handlers_dir = layout.handlers(output_dir, "events");
     for event in &schema.events {
+        let mut created_stub = false;
         for handler in &event.handlers {
-            let file_name = format!("{}.py", handler);
+            let file_name = format!("{}.py", layout.file_stem(handler));
             let handler_path = handlers_dir.join(&file_name);
 
             if !handler_path.exists() {
                 let content = generate_event_handler_stub(event, handler);
                 fs::write(handler_path, content)?;
+                created_stub = true;
             }
         }
+
+        if created_stub {
+            generate_event_test(event, schema, output_dir)?;
+        }
     }
 
     Ok(())
@@ -300,8 +419,12 @@ fn generate_event_handler_stub(event: &Event, handler_name: &str) -> String {
     content
 }
 
-pub fn generate_crons(schema: &Schema, output_dir: &Path) -> Result<()> {
-    let handlers_dir = output_dir.join("handlers/cron");
+pub fn generate_crons(schema: &Schema, output_dir: &Path, layout: &Layout) -> Result<()> {
+    if !layout.handler_stubs {
+        return Ok(());
+    }
+
+    let handlers_dir = layout.handlers(output_dir, "cron");
 
     for cron in &schema.crons {
         let file_name = format!("{}.py", templates::to_snake_case(&cron.name));
@@ -320,7 +443,7 @@ pub fn generate_crons(schema: &Schema, output_dir: &Path) -> Result<()> {
     Ok(())
 }
 
-pub fn generate_websockets(schema: &Schema, output_dir: &Path) -> Result<()> {
+pub fn generate_websockets(schema: &Schema, output_dir: &Path, layout: &Layout) -> Result<()> {
     let ws_dir = output_dir.join("generated/websockets");
 
     for ws in &schema.websockets {
@@ -328,12 +451,19 @@ pub fn generate_websockets(schema: &Schema, output_dir: &Path) -> Result<()> {
         let file_name = format!("{}.py", templates::to_snake_case(&ws.name));
         fs::write(ws_dir.join(file_name), content)?;
     }
+    if !schema.websockets.is_empty() {
+        fs::write(ws_dir.join("client.py"), generate_websocket_clients(schema))?;
+    }
+
+    if !layout.handler_stubs {
+        return Ok(());
+    }
 
-    let handlers_dir = output_dir.join("handlers/websockets");
+    let handlers_dir = layout.handlers(output_dir, "websockets");
     for ws in &schema.websockets {
         if !ws.on_connect.is_empty() {
             for handler in &ws.on_connect {
-                let file_name = format!("{}.py", handler);
+                let file_name = format!("{}.py", layout.file_stem(handler));
                 let handler_path = handlers_dir.join(&file_name);
                 if !handler_path.exists() {
                     let content = generate_websocket_handler_stub(ws, "onConnect", handler);
@@ -343,7 +473,7 @@ pub fn generate_websockets(schema: &Schema, output_dir: &Path) -> Result<()> {
         }
         if !ws.on_message.is_empty() {
             for handler in &ws.on_message {
-                let file_name = format!("{}.py", handler);
+                let file_name = format!("{}.py", layout.file_stem(handler));
                 let handler_path = handlers_dir.join(&file_name);
                 if !handler_path.exists() {
                     let content = generate_websocket_handler_stub(ws, "onMessage", handler);
@@ -353,7 +483,7 @@ pub fn generate_websockets(schema: &Schema, output_dir: &Path) -> Result<()> {
         }
         if !ws.on_disconnect.is_empty() {
             for handler in &ws.on_disconnect {
-                let file_name = format!("{}.py", handler);
+                let file_name = format!("{}.py", layout.file_stem(handler));
                 let handler_path = handlers_dir.join(&file_name);
                 if !handler_path.exists() {
                     let content = generate_websocket_handler_stub(ws, "onDisconnect", handler);
@@ -366,8 +496,12 @@ pub fn generate_websockets(schema: &Schema, output_dir: &Path) -> Result<()> {
     Ok(())
 }
 
-pub fn generate_middlewares(schema: &Schema, output_dir: &Path) -> Result<()> {
+pub fn generate_middlewares(schema: &Schema, output_dir: &Path, layout: &Layout) -> Result<()> {
     use std::collections::HashSet;
+
+    if !layout.handler_stubs {
+        return Ok(());
+    }
    
     let mut middleware_names = HashSet::new();
     
@@ -378,7 +512,7 @@ pub fn generate_middlewares(schema: &Schema, output_dir: &Path) -> Result<()> {
     }
     
     for ws in &schema.websockets {
-        for middleware in &ws.middlewares {
+        for middleware in ws.middleware_handlers() {
             middleware_names.insert(middleware.clone());
         }
     }
@@ -387,7 +521,7 @@ pub fn generate_middlewares(schema: &Schema, output_dir: &Path) -> Result<()> {
         return Ok(());
     }
     
-    let middlewares_dir = output_dir.join("middlewares");
+    let middlewares_dir = layout.middlewares(output_dir);
     for middleware_name in middleware_names {
         let file_name = format!("{}.py", templates::to_snake_case(&middleware_name));
         let middleware_path = middlewares_dir.join(&file_name);
@@ -419,6 +553,7 @@ fn generate_middleware_stub(middleware_name: &str) -> String {
     content.push_str("            - query_params: Query parameters (for APIs)\n");
     content.push_str("            - connection: WebSocket connection info (for WebSockets)\n");
     content.push_str("            - websocket_name: WebSocket name (for WebSockets)\n");
+    content.push_str("            - token: Token to validate (for WebSocket auth handlers)\n");
     content.push_str("            - api_name: API name (for APIs)\n");
     content.push_str("            - trace_id: Trace ID\n");
     content.push_str("        state: State object for logging and triggering events\n\n");
@@ -426,6 +561,8 @@ fn generate_middleware_stub(middleware_name: &str) -> String {
     content.push_str("        Optional[Dict[str, Any]]: Modified context with 'payload' and/or 'query_params' keys,\n");
     content.push_str("        or None to pass through unchanged. Return a dict with 'error' key to reject the request.\n\n");
     content.push_str("    To reject the request, raise an exception \n");
+    content.push_str("    As a websocket `auth` handler, validate context['token'] and return\n");
+    content.push_str("    {'principal': ...} to accept the connection.\n");
     content.push_str("    \"\"\"\n");
     content.push_str("    # TODO: Implement middleware logic\n");
     content.push_str("    # Example: Validate authentication\n");
@@ -482,13 +619,194 @@ fn generate_websocket_content(ws: &WebSocket) -> String {
     content.push_str(&format!("class {}Connection(BaseModel):\n", ws.name));
     content.push_str("    connection_id: str\n");
     content.push_str("    path: str\n");
-    content.push_str("    connected_at: datetime\n\n");
+    content.push_str("    connected_at: datetime\n");
+    content.push_str("    ip_address: Optional[str] = None\n");
+    content.push_str("    principal: Optional[Any] = None\n\n");
     content.push_str("    class Config:\n");
     content.push_str("        from_attributes = True\n");
 
     content
 }
 
+/// Generate `generated/websockets/client.py`: one typed client class per
+/// websocket over a shared implementation of the wire protocol.
+fn generate_websocket_clients(schema: &Schema) -> String {
+    let mut content = String::new();
+
+    content.push_str("# Generated by Rohas - Do not edit\n\n");
+    content.push_str("import asyncio\n");
+    content.push_str("import inspect\n");
+    content.push_str("import json\n");
+    content.push_str("import random\n");
+    content.push_str("from typing import Any, Awaitable, Callable, Dict, Generic, List, Optional, Type, TypeVar, Union\n");
+    content.push_str("from urllib.parse import urlencode\n\n");
+    content.push_str("import websockets\n");
+    content.push_str("from pydantic import BaseModel\n\n");
+
+    let mut imported = std::collections::HashSet::new();
+    for ws in &schema.websockets {
+        content.push_str(&format!(
+            "from .{} import {}Message\n",
+            templates::to_snake_case(&ws.name),
+            ws.name
+        ));
+        if let Some(message_type) = &ws.message {
+            if matches!(FieldType::from_str(message_type), FieldType::Custom(_)) && imported.insert(message_type) {
+                content.push_str(&format!(
+                    "from ..dto.{} import {}\n",
+                    templates::to_snake_case(message_type),
+                    message_type
+                ));
+            }
+        }
+    }
+
+    content.push_str(
+        r#"
+
+Send = TypeVar('Send')
+Receive = TypeVar('Receive', bound=BaseModel)
+Listener = Callable[..., Union[None, Awaitable[None]]]
+
+
+class SocketClient(Generic[Send, Receive]):
+    """Connection handling shared by the generated clients.
+
+    Resumes the server session after a drop, so messages sent meanwhile are
+    still delivered.
+    """
+
+    path: str = ''
+    message_model: Type[BaseModel] = BaseModel
+
+    def __init__(
+        self,
+        url: str,
+        query: Optional[Dict[str, str]] = None,
+        reconnect: bool = True,
+        min_backoff: float = 0.5,
+        max_backoff: float = 30.0,
+    ) -> None:
+        self.url = url.rstrip('/')
+        self.query = dict(query or {})
+        self.reconnect = reconnect
+        self.min_backoff = min_backoff
+        self.max_backoff = max_backoff
+        self.connection_id: Optional[str] = None
+        self._reconnect_token: Optional[str] = None
+        self._socket: Any = None
+        self._closing = False
+        self._listeners: Dict[str, List[Listener]] = {'open': [], 'message': [], 'event': [], 'close': []}
+
+    def on_open(self, listener: Listener) -> None:
+        self._listeners['open'].append(listener)
+
+    def on_message(self, listener: Callable[[Receive], Union[None, Awaitable[None]]]) -> None:
+        self._listeners['message'].append(listener)
+
+    def on_event(self, listener: Callable[[str, Any, str], Union[None, Awaitable[None]]]) -> None:
+        """Events the server forwards to this websocket, called with the
+        event name, its payload and timestamp."""
+        self._listeners['event'].append(listener)
+
+    def on_close(self, listener: Listener) -> None:
+        self._listeners['close'].append(listener)
+
+    async def send(self, message: Send) -> None:
+        if self._socket is None:
+            raise RuntimeError('WebSocket is not open')
+        data = message.model_dump(mode='json') if isinstance(message, BaseModel) else message
+        await self._socket.send(json.dumps(data))
+
+    async def close(self) -> None:
+        self._closing = True
+        if self._socket is not None:
+            await self._socket.close()
+
+    async def run(self) -> None:
+        """Connect and dispatch messages until `close()` is called,
+        reconnecting with exponential backoff when the connection drops."""
+        self._closing = False
+        attempts = 0
+        while not self._closing:
+            try:
+                async with websockets.connect(self._connect_url()) as socket:
+                    self._socket = socket
+                    attempts = 0
+                    await self._emit('open')
+                    async for raw in socket:
+                        await self._dispatch(raw)
+            except (OSError, websockets.exceptions.WebSocketException):
+                pass
+            finally:
+                self._socket = None
+
+            await self._emit('close')
+            if self._closing or not self.reconnect:
+                break
+            delay = min(self.max_backoff, self.min_backoff * 2 ** attempts) * random.uniform(0.5, 1.0)
+            attempts += 1
+            await asyncio.sleep(delay)
+
+    def _connect_url(self) -> str:
+        query = dict(self.query)
+        if self._reconnect_token:
+            query['reconnect_token'] = self._reconnect_token
+        search = urlencode(query)
+        return f"{self.url}{self.path}{'?' + search if search else ''}"
+
+    async def _dispatch(self, raw: Union[str, bytes]) -> None:
+        message = json.loads(raw)
+        if isinstance(message, dict) and '$session' in message:
+            self.connection_id = message['$session']['connection_id']
+            self._reconnect_token = message['$session']['reconnect_token']
+            return
+        if isinstance(message, dict) and '$event' in message:
+            await self._emit('event', message['$event'], message.get('data'), message.get('timestamp'))
+            return
+        await self._emit('message', self.message_model.model_validate(message))
+
+    async def _emit(self, kind: str, *args: Any) -> None:
+        for listener in list(self._listeners[kind]):
+            result = listener(*args)
+            if inspect.isawaitable(result):
+                await result
+"#,
+    );
+
+    for ws in &schema.websockets {
+        let send_type = ws
+            .message
+            .as_deref()
+            .map(|message_type| FieldType::from_str(message_type).to_python())
+            .unwrap_or_else(|| "Dict[str, Any]".to_string());
+        let token = ws.auth.as_ref().filter(|auth| auth.from == TokenSource::Query);
+
+        content.push_str("\n\n");
+        content.push_str(&format!(
+            "class {}Client(SocketClient[{}, {}Message]):\n",
+            ws.name, send_type, ws.name
+        ));
+        content.push_str(&format!("    \"\"\"Client for the `{}` websocket (`{}`).\"\"\"\n\n", ws.name, ws.path));
+        content.push_str(&format!("    path = '{}'\n", ws.path));
+        content.push_str(&format!("    message_model = {}Message\n", ws.name));
+        if let Some(auth) = token {
+            content.push('\n');
+            content.push_str("    def __init__(self, url: str, token: str, **options: Any) -> None:\n");
+            content.push_str(&format!(
+                "        # Checked by the `{}` handler.\n",
+                auth.handler
+            ));
+            content.push_str(&format!(
+                "        super().__init__(url, query={{'{}': token}}, **options)\n",
+                auth.name
+            ));
+        }
+    }
+
+    content
+}
+
 fn generate_websocket_handler_stub(
     ws: &WebSocket,
     handler_type: &str,
@@ -556,131 +874,118 @@ fn generate_websocket_handler_stub(
 
 pub fn generate_state(output_dir: &Path) -> Result<()> {
     let generated_dir = output_dir.join("generated");
-    let content = r#"from typing import Any, Dict, List, Optional
-from pydantic import BaseModel
+    let content = r#"# Generated by Rohas - Do not edit
+# The handler API lives in the rohas-sdk package.
+
+from rohas_sdk import (
+    Cookies,
+    HttpClient,
+    HttpClientError,
+    HttpClientResponse,
+    HttpResponse,
+    Kv,
+    Logger,
+    State,
+    TriggeredEvent,
+)
+
+__all__ = [
+    "Cookies",
+    "HttpClient",
+    "HttpClientError",
+    "HttpClientResponse",
+    "HttpResponse",
+    "Kv",
+    "Logger",
+    "State",
+    "TriggeredEvent",
+]
+"#;
 
+    fs::write(generated_dir.join("state.py"), content)?;
+    Ok(())
+}
 
-class TriggeredEvent(BaseModel):
-    event_name: str
-    payload: Dict[str, Any]
+/// Generate `generated/errors.py`: the engine's `application/problem+json`
+/// error responses as types for API clients.
+pub fn generate_errors(output_dir: &Path) -> Result<()> {
+    let content = r#"# Generated by Rohas - Do not edit
 
+from typing import Any, Dict, List, Optional
+from pydantic import BaseModel, Field
 
-class Logger:
-    """Logger for handlers to emit structured logs."""
-    
-    def __init__(self, handler_name: str, log_fn: Any):
-        self._handler_name = handler_name
-        self._log_fn = log_fn
-    
-    def info(self, message: str, **kwargs: Any) -> None:
-        """Log an info message.
-        
-        Args:
-            message: Log message
-            **kwargs: Additional fields to include in the log
-        """
-        if self._log_fn:
-            self._log_fn("info", self._handler_name, message, kwargs)
-    
-    def error(self, message: str, **kwargs: Any) -> None:
-        """Log an error message.
-        
-        Args:
-            message: Log message
-            **kwargs: Additional fields to include in the log
-        """
-        if self._log_fn:
-            self._log_fn("error", self._handler_name, message, kwargs)
-    
-    def warning(self, message: str, **kwargs: Any) -> None:
-        """Log a warning message.
-        
-        Args:
-            message: Log message
-            **kwargs: Additional fields to include in the log
-        """
-        if self._log_fn:
-            self._log_fn("warn", self._handler_name, message, kwargs)
-    
-    def warn(self, message: str, **kwargs: Any) -> None:
-        """Log a warning message (alias for warning).
-        
-        Args:
-            message: Log message
-            **kwargs: Additional fields to include in the log
-        """
-        self.warning(message, **kwargs)
-    
-    def debug(self, message: str, **kwargs: Any) -> None:
-        """Log a debug message.
-        
-        Args:
-            message: Log message
-            **kwargs: Additional fields to include in the log
-        """
-        if self._log_fn:
-            self._log_fn("debug", self._handler_name, message, kwargs)
-    
-    def trace(self, message: str, **kwargs: Any) -> None:
-        """Log a trace message.
-        
-        Args:
-            message: Log message
-            **kwargs: Additional fields to include in the log
-        """
-        if self._log_fn:
-            self._log_fn("trace", self._handler_name, message, kwargs)
+PROBLEM_CONTENT_TYPE = "application/problem+json"
 
 
-class State:
-    """Context object for handlers to trigger events and access runtime state."""
-    
-    def __init__(self, handler_name: Optional[str] = None, log_fn: Optional[Any] = None):
-        self._triggers: List[TriggeredEvent] = []
-        self._auto_trigger_payloads: Dict[str, Dict[str, Any]] = {}
-        self.logger = Logger(handler_name or "unknown", log_fn)
-    
-    def trigger_event(self, event_name: str, payload: Dict[str, Any]) -> None:
-        """Manually trigger an event with the given payload.
-        
-        Use this for events that are NOT defined in the schema's triggers list.
-        
-        Args:
-            event_name: Name of the event to trigger
-            payload: Event payload data (will be serialized to JSON)
-        """
-        self._triggers.append(TriggeredEvent(
-            event_name=event_name,
-            payload=payload
+class FieldError(BaseModel):
+    """A request body field that failed validation."""
+    field: str
+    message: str
+
+
+class ProblemDetails(BaseModel):
+    """An API error response (RFC 7807)."""
+    type: str = "about:blank"
+    title: str
+    status: int
+    detail: Optional[str] = None
+    instance: Optional[str] = None
+    # Present on validation failures (status 422).
+    errors: List[FieldError] = Field(default_factory=list)
+
+
+class ProblemError(Exception):
+    """Raised for error responses; carries the parsed problem document."""
+
+    def __init__(self, problem: ProblemDetails):
+        super().__init__(problem.detail or problem.title)
+        self.problem = problem
+
+    @property
+    def status(self) -> int:
+        return self.problem.status
+
+    @property
+    def field_errors(self) -> List[FieldError]:
+        return self.problem.errors
+
+    @classmethod
+    def from_body(cls, status: int, body: Dict[str, Any]) -> "ProblemError":
+        """Reads an error body, accepting the legacy `{"error": ...}` form as well."""
+        if "title" in body and "status" in body:
+            return cls(ProblemDetails(**body))
+        return cls(ProblemDetails(
+            title="Error",
+            status=status,
+            detail=body.get("error"),
+            errors=body.get("errors", []),
         ))
-    
-    def set_payload(self, event_name: str, payload: Dict[str, Any]) -> None:
-        """Set the payload for an auto-triggered event.
-        
-        Use this for events that ARE defined in the schema's triggers list.
-        The event will be automatically triggered after the handler completes,
-        using the payload you set here.
-        
-        Args:
-            event_name: Name of the event (must match a trigger in schema)
-            payload: Event payload data (will be serialized to JSON)
-        """
-        self._auto_trigger_payloads[event_name] = payload
-    
-    def get_triggers(self) -> List[TriggeredEvent]:
-        """Get all manually triggered events. Used internally by the runtime."""
-        return self._triggers.copy()
-    
-    def get_auto_trigger_payload(self, event_name: str) -> Optional[Dict[str, Any]]:
-        """Get payload for an auto-triggered event. Used internally by the runtime."""
-        return self._auto_trigger_payloads.get(event_name)
-    
-    def get_all_auto_trigger_payloads(self) -> Dict[str, Dict[str, Any]]:
-        """Get all auto-trigger payloads. Used internally by the runtime."""
-        return self._auto_trigger_payloads.copy()
 "#;
 
-    fs::write(generated_dir.join("state.py"), content)?;
+    fs::write(output_dir.join("generated/errors.py"), content)?;
+    Ok(())
+}
+
+/// Generate `generated/page.py`: the envelope of `response: Page<T>` APIs.
+pub fn generate_page(output_dir: &Path) -> Result<()> {
+    let content = r#"# Generated by Rohas - Do not edit
+
+from typing import Generic, List, Optional, TypeVar
+from pydantic import BaseModel
+
+T = TypeVar("T")
+
+
+class Page(BaseModel, Generic[T]):
+    """A page of a paginated list API."""
+    items: List[T]
+    # Number of items across all pages.
+    total: int
+    # Passed back as `?cursor=` for the next page; None on the last page.
+    next_cursor: Optional[str] = None
+"#;
+
+    fs::write(output_dir.join("generated/page.py"), content)?;
     Ok(())
 }
 
@@ -695,7 +1000,9 @@ pub fn generate_init(schema: &Schema, output_dir: &Path) -> Result<()> {
     let mut content = String::new();
     content.push_str("# Generated by Rohas - Do not edit\n\n");
 
-    content.push_str("from .state import State, TriggeredEvent\n");
+    content.push_str("from .state import Cookies, HttpResponse, State, TriggeredEvent\n");
+    content.push_str("from .errors import FieldError, ProblemDetails, ProblemError\n");
+    content.push_str("from .page import Page\n");
 
     for model in &schema.models {
         content.push_str(&format!(
@@ -709,3 +1016,300 @@ pub fn generate_init(schema: &Schema, output_dir: &Path) -> Result<()> {
 
     Ok(())
 }
+
+/// Generate `generated/testing.py`, an in-process engine for handler tests.
+///
+/// It calls handlers directly, collects the events they trigger and dispatches
+/// them to the schema's event handlers, following event trigger chains.
+/// Handlers without a file are left out.
+pub fn generate_test_engine(schema: &Schema, output_dir: &Path, layout: &Layout) -> Result<()> {
+    let api_handlers_dir = layout.handlers(output_dir, "api");
+    let apis: Vec<_> = schema
+        .apis
+        .iter()
+        .filter(|api| api_handlers_dir.join(format!("{}.py", templates::to_snake_case(&api.name))).exists())
+        .collect();
+    let event_handlers_dir = layout.handlers(output_dir, "events");
+    let has_event_handler =
+        |handler: &String| event_handlers_dir.join(format!("{}.py", layout.file_stem(handler))).exists();
+
+    let mut content = String::new();
+
+    content.push_str("import asyncio\n");
+    content.push_str("from dataclasses import dataclass\n");
+    content.push_str("from datetime import datetime\n");
+    content.push_str("from typing import Any, Awaitable, Callable, Dict, List, Optional\n\n");
+    content.push_str("from .state import State, TriggeredEvent\n");
+
+    for api in &apis {
+        let snake_name = templates::to_snake_case(&api.name);
+        content.push_str(&format!(
+            "from .api.{} import {}Request, {}Response\n",
+            snake_name, api.name, api.name
+        ));
+        content.push_str(&format!(
+            "from {} import handle_{}\n",
+            layout.python_module("api", &snake_name),
+            snake_name
+        ));
+    }
+
+    let mut imported = std::collections::HashSet::new();
+    for event in &schema.events {
+        content.push_str(&format!(
+            "from .events.{} import {}\n",
+            templates::to_snake_case(&event.name),
+            event.name
+        ));
+        for handler in event.handlers.iter().filter(|handler| has_event_handler(handler)) {
+            if imported.insert(handler.clone()) {
+                content.push_str(&format!(
+                    "from {} import {}\n",
+                    layout.python_module("events", &layout.file_stem(handler)),
+                    handler
+                ));
+            }
+        }
+    }
+
+    content.push_str(
+        r#"
+
+EventHandler = Callable[[Dict[str, Any]], Awaitable[None]]
+
+# Guards against event trigger cycles in the schema.
+MAX_DISPATCHES = 1000
+
+DEFAULT_EVENT_TIMEOUT = 1.0
+
+
+@dataclass
+class ApiCallResult:
+    response: Any
+    events: List[TriggeredEvent]
+
+
+class TestEngine:
+    """In-process engine for tests.
+
+    Runs handlers directly and dispatches the events they trigger to
+    registered event handlers.
+    """
+
+    __test__ = False  # not a pytest test class
+
+    def __init__(self) -> None:
+        self._handlers: Dict[str, List[EventHandler]] = {}
+        self._chains: Dict[str, List[str]] = {}
+        self._waiters: Dict[str, List["asyncio.Future[TriggeredEvent]"]] = {}
+        self.published: List[TriggeredEvent] = []
+
+    def on(self, event_name: str, handler: EventHandler) -> None:
+        self._handlers.setdefault(event_name, []).append(handler)
+
+    def chain(self, event_name: str, triggers: List[str]) -> None:
+        self._chains[event_name] = triggers
+
+    async def call_api(
+        self,
+        handler_name: str,
+        handler: Callable[[Any, State], Awaitable[Any]],
+        req: Any,
+        triggers: Optional[List[str]] = None,
+    ) -> ApiCallResult:
+        """Call an API handler and dispatch the events it triggers.
+
+        `triggers` are the API's schema triggers; they use the payload set
+        with `state.set_payload` or fall back to the response.
+        """
+        state = State(handler_name)
+        response = await handler(req, state)
+
+        events = state.get_triggers()
+        for event_name in triggers or []:
+            payload = state.get_auto_trigger_payload(event_name)
+            if payload is None:
+                payload = response.model_dump() if hasattr(response, "model_dump") else response
+            events.append(TriggeredEvent(event_name=event_name, payload=payload))
+
+        for event in events:
+            await self.publish(event.event_name, event.payload)
+
+        return ApiCallResult(response=response, events=events)
+
+    async def publish(self, event_name: str, payload: Dict[str, Any]) -> None:
+        """Publish an event, run its handlers and follow its schema triggers."""
+        queue = [TriggeredEvent(event_name=event_name, payload=payload)]
+        dispatched = 0
+
+        while queue:
+            event = queue.pop(0)
+            dispatched += 1
+            if dispatched > MAX_DISPATCHES:
+                raise RuntimeError(f"Event chain starting at {event_name} did not settle")
+
+            self.published.append(event)
+            for waiter in self._waiters.pop(event.event_name, []):
+                if not waiter.done():
+                    waiter.set_result(event)
+            for handler in self._handlers.get(event.event_name, []):
+                await handler(event.payload)
+            for next_event in self._chains.get(event.event_name, []):
+                queue.append(TriggeredEvent(event_name=next_event, payload=event.payload))
+
+    async def expect_event(
+        self, event_name: str, timeout: float = DEFAULT_EVENT_TIMEOUT
+    ) -> TriggeredEvent:
+        """Return the first published event named `event_name`, waiting up to
+        `timeout` seconds for it if it has not been published yet."""
+        for event in self.published:
+            if event.event_name == event_name:
+                return event
+
+        waiter: "asyncio.Future[TriggeredEvent]" = asyncio.get_running_loop().create_future()
+        self._waiters.setdefault(event_name, []).append(waiter)
+        try:
+            return await asyncio.wait_for(waiter, timeout)
+        except asyncio.TimeoutError:
+            seen = ", ".join(e.event_name for e in self.published) or "none"
+            raise AssertionError(
+                f"Expected event {event_name} within {timeout}s (published: {seen})"
+            ) from None
+
+
+def create_test_engine() -> TestEngine:
+    """Create a test engine wired with the schema's event handlers."""
+    engine = TestEngine()
+"#,
+    );
+
+    for event in &schema.events {
+        for handler in event.handlers.iter().filter(|handler| has_event_handler(handler)) {
+            content.push_str(&format!(
+                "    engine.on(\"{}\", lambda payload: {}({}(payload=payload, timestamp=datetime.now())))\n",
+                event.name, handler, event.name
+            ));
+        }
+        if !event.triggers.is_empty() {
+            let triggers: Vec<String> = event.triggers.iter().map(|t| format!("\"{}\"", t)).collect();
+            content.push_str(&format!(
+                "    engine.chain(\"{}\", [{}])\n",
+                event.name,
+                triggers.join(", ")
+            ));
+        }
+    }
+
+    content.push_str("    return engine\n");
+
+    content.push_str(
+        r#"
+
+class TestClient:
+    """Typed client over the test engine, with one method per API."""
+
+    __test__ = False  # not a pytest test class
+
+    def __init__(self, engine: Optional[TestEngine] = None) -> None:
+        self.engine = engine or create_test_engine()
+
+    async def expect_event(self, event_name: str, timeout: float = DEFAULT_EVENT_TIMEOUT) -> TriggeredEvent:
+        """Wait for an event triggered by earlier calls."""
+        return await self.engine.expect_event(event_name, timeout)
+
+    async def publish(self, event_name: str, payload: Dict[str, Any]) -> None:
+        """Publish an event directly, as if another service had emitted it."""
+        await self.engine.publish(event_name, payload)
+"#,
+    );
+
+    for api in &apis {
+        let snake_name = templates::to_snake_case(&api.name);
+        let triggers: Vec<String> = api.triggers.iter().map(|t| format!("\"{}\"", t)).collect();
+        content.push_str(&format!(
+            "\n    async def {}(self, req: {}Request) -> {}Response:\n",
+            snake_name, api.name, api.name
+        ));
+        content.push_str(&format!("        \"\"\"{} {}\"\"\"\n", api.method, api.path));
+        content.push_str(&format!(
+            "        result = await self.engine.call_api(\"{}\", handle_{}, req, [{}])\n",
+            snake_name,
+            snake_name,
+            triggers.join(", ")
+        ));
+        content.push_str("        return result.response\n");
+    }
+
+    fs::write(output_dir.join("generated/testing.py"), content)?;
+    Ok(())
+}
+
+fn generate_api_test(api: &Api, schema: &Schema, output_dir: &Path) -> Result<()> {
+    let tests_dir = config::get_project_root(output_dir)?.join("tests/api");
+    let snake_name = templates::to_snake_case(&api.name);
+    let test_path = tests_dir.join(format!("test_{}.py", snake_name));
+    if test_path.exists() {
+        return Ok(());
+    }
+    fs::create_dir_all(&tests_dir)?;
+
+    let mut content = String::new();
+    content.push_str("import asyncio\n\n");
+    content.push_str(&format!(
+        "from generated.api.{} import {}Request\n",
+        snake_name, api.name
+    ));
+    content.push_str("from generated.testing import TestClient\n\n\n");
+
+    content.push_str(&format!("def test_{}_handles_example_request() -> None:\n", snake_name));
+    content.push_str("    async def scenario() -> None:\n");
+    content.push_str("        client = TestClient()\n");
+    content.push_str(&format!(
+        "        req = {}Request.model_validate({})\n\n",
+        api.name,
+        Example::for_api_request(api, schema).to_python()
+    ));
+    content.push_str(&format!("        response = await client.{}(req)\n\n", snake_name));
+    content.push_str("        assert response.data is not None\n");
+    for trigger in &api.triggers {
+        content.push_str(&format!("        await client.expect_event(\"{}\")\n", trigger));
+    }
+    content.push_str("\n    asyncio.run(scenario())\n");
+
+    fs::write(test_path, content)?;
+    Ok(())
+}
+
+fn generate_event_test(event: &Event, schema: &Schema, output_dir: &Path) -> Result<()> {
+    let tests_dir = config::get_project_root(output_dir)?.join("tests/events");
+    let snake_name = templates::to_snake_case(&event.name);
+    let test_path = tests_dir.join(format!("test_{}.py", snake_name));
+    if test_path.exists() {
+        return Ok(());
+    }
+    fs::create_dir_all(&tests_dir)?;
+
+    let mut content = String::new();
+    content.push_str("import asyncio\n\n");
+    content.push_str("from generated.testing import TestClient\n\n\n");
+
+    content.push_str(&format!("def test_{}_runs_handlers() -> None:\n", snake_name));
+    content.push_str("    async def scenario() -> None:\n");
+    content.push_str("        client = TestClient()\n");
+    content.push_str(&format!(
+        "        payload = {}\n\n",
+        Example::for_type(&event.payload, schema).to_python()
+    ));
+    content.push_str(&format!(
+        "        await client.publish(\"{}\", payload)\n\n",
+        event.name
+    ));
+    content.push_str(&format!("        await client.expect_event(\"{}\")\n", event.name));
+    for trigger in &event.triggers {
+        content.push_str(&format!("        await client.expect_event(\"{}\")\n", trigger));
+    }
+    content.push_str("\n    asyncio.run(scenario())\n");
+
+    fs::write(test_path, content)?;
+    Ok(())
+}
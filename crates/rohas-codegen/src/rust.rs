@@ -1,6 +1,9 @@
 use crate::error::Result;
-use crate::templates;
-use rohas_parser::{Api, Event, FieldType, Model, Schema, Type, WebSocket};
+use crate::example::Example;
+use crate::factory::FakeValue;
+use crate::layout::Layout;
+use crate::{config, templates};
+use rohas_parser::{Api, ContentType, Event, FieldType, Model, Schema, Type, WebSocket};
 use std::fs;
 use std::path::Path;
 
@@ -41,6 +44,104 @@ pub fn generate_models(schema: &Schema, output_dir: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Generate `generated/factories.rs`, with a builder per model that fills
+/// every field with fake data.
+pub fn generate_factories(schema: &Schema, output_dir: &Path) -> Result<()> {
+    let mut content = String::new();
+    content.push_str("// Auto-generated Rust code from Rohas schema\n");
+    content.push_str("// DO NOT EDIT MANUALLY\n\n");
+    content.push_str("#![allow(dead_code)]\n\n");
+    content.push_str("use std::sync::atomic::{AtomicI64, Ordering};\n\n");
+    content.push_str("#[allow(unused_imports)]\n");
+    content.push_str("use crate::generated::models::*;\n\n");
+    content.push_str("static SEQUENCE: AtomicI64 = AtomicI64::new(0);\n\n");
+    content.push_str("fn next_sequence() -> i64 {\n");
+    content.push_str("    SEQUENCE.fetch_add(1, Ordering::Relaxed) + 1\n");
+    content.push_str("}\n\n");
+    content.push_str("/// Restart the sequence used for ids and unique fields.\n");
+    content.push_str("pub fn reset_factory_sequence() {\n");
+    content.push_str("    SEQUENCE.store(0, Ordering::Relaxed);\n");
+    content.push_str("}\n");
+
+    for model in &schema.models {
+        let values: Vec<(&rohas_parser::Field, FakeValue)> = model
+            .fields
+            .iter()
+            .map(|f| (f, FakeValue::for_field(model, f, schema)))
+            .collect();
+
+        content.push_str(&format!("\n/// Builds `{}` values with fake data.\n", model.name));
+        content.push_str("#[derive(Debug, Clone, Default)]\n");
+        content.push_str(&format!("pub struct {}Factory {{\n", model.name));
+        for field in &model.fields {
+            content.push_str(&format!(
+                "    {}: Option<{}>,\n",
+                escape_rust_keyword(&templates::to_snake_case(&field.name)),
+                rust_field_type(field)
+            ));
+        }
+        content.push_str("}\n\n");
+
+        content.push_str(&format!("impl {}Factory {{\n", model.name));
+        content.push_str("    pub fn new() -> Self {\n");
+        content.push_str("        Self::default()\n");
+        content.push_str("    }\n");
+
+        for field in &model.fields {
+            let snake_name = templates::to_snake_case(&field.name);
+            let slot = escape_rust_keyword(&snake_name);
+            let (param_type, value) = match (&field.field_type, field.optional) {
+                (FieldType::String, false) => ("impl Into<String>".to_string(), "value.into()".to_string()),
+                (FieldType::String, true) => ("impl Into<String>".to_string(), "Some(value.into())".to_string()),
+                (field_type, false) => (field_type.to_rust(), "value".to_string()),
+                (field_type, true) => (field_type.to_rust(), "Some(value)".to_string()),
+            };
+            content.push_str(&format!(
+                "\n    pub fn with_{}(mut self, value: {}) -> Self {{\n",
+                snake_name, param_type
+            ));
+            content.push_str(&format!("        self.{} = Some({});\n", slot, value));
+            content.push_str("        self\n");
+            content.push_str("    }\n");
+        }
+
+        content.push_str(&format!("\n    pub fn build(&self) -> {} {{\n", model.name));
+        if values.iter().any(|(_, v)| v.uses_sequence()) {
+            content.push_str("        let n = next_sequence();\n");
+        }
+        content.push_str(&format!("        {} {{\n", model.name));
+        for (field, value) in &values {
+            content.push_str(&format!(
+                "            {}: self.{}.clone().unwrap_or_else(|| {}),\n",
+                escape_rust_keyword(&field.name),
+                escape_rust_keyword(&templates::to_snake_case(&field.name)),
+                value.to_rust(&field.field_type)
+            ));
+        }
+        content.push_str("        }\n");
+        content.push_str("    }\n");
+
+        content.push_str(&format!(
+            "\n    pub fn build_list(&self, count: usize) -> Vec<{}> {{\n",
+            model.name
+        ));
+        content.push_str("        (0..count).map(|_| self.build()).collect()\n");
+        content.push_str("    }\n");
+        content.push_str("}\n");
+    }
+
+    fs::write(output_dir.join("generated/factories.rs"), content)?;
+    Ok(())
+}
+
+fn rust_field_type(field: &rohas_parser::Field) -> String {
+    if field.optional {
+        format!("Option<{}>", field.field_type.to_rust())
+    } else {
+        field.field_type.to_rust()
+    }
+}
+
 fn generate_model_content(model: &Model) -> String {
     let mut content = String::new();
 
@@ -63,6 +164,12 @@ fn generate_model_content(model: &Model) -> String {
         } else {
             String::new()
         };
+        if let Some(expression) = field.computed() {
+            content.push_str(&format!(
+                "    /// Computed by the database as `{}`; read-only, and rejected in request bodies.\n",
+                expression
+            ));
+        }
         content.push_str(&serde_attr);
         content.push_str(&format!("    pub {}: {},\n", field_name, type_hint));
     }
@@ -84,6 +191,8 @@ pub fn generate_dtos(schema: &Schema, output_dir: &Path) -> Result<()> {
             name: input.name.clone(),
             fields: input.fields.clone(),
             attributes: vec![],
+            example: input.example.clone(),
+            doc: input.doc.clone(),
         });
         let file_name = format!("{}.rs", templates::to_snake_case(&input.name));
         fs::write(dto_dir.join(file_name), content)?;
@@ -94,6 +203,8 @@ pub fn generate_dtos(schema: &Schema, output_dir: &Path) -> Result<()> {
             name: type_def.name.clone(),
             fields: type_def.fields.clone(),
             attributes: vec![],
+            example: type_def.example.clone(),
+            doc: type_def.doc.clone(),
         });
         let file_name = format!("{}.rs", templates::to_snake_case(&type_def.name));
         fs::write(dto_dir.join(file_name), content)?;
@@ -116,7 +227,7 @@ pub fn generate_dtos(schema: &Schema, output_dir: &Path) -> Result<()> {
     Ok(())
 }
 
-pub fn generate_apis(schema: &Schema, output_dir: &Path) -> Result<()> {
+pub fn generate_apis(schema: &Schema, output_dir: &Path, layout: &Layout) -> Result<()> {
     let api_dir = output_dir.join("generated/api");
 
     for api in &schema.apis {
@@ -134,6 +245,10 @@ pub fn generate_apis(schema: &Schema, output_dir: &Path) -> Result<()> {
     }
     fs::write(api_dir.join("mod.rs"), mod_content)?;
 
+    if !layout.handler_stubs {
+        return Ok(());
+    }
+
     let handlers_dir = output_dir.join("handlers/api");
     for api in &schema.apis {
         let file_name = format!("{}.rs", templates::to_snake_case(&api.name));
@@ -142,6 +257,7 @@ pub fn generate_apis(schema: &Schema, output_dir: &Path) -> Result<()> {
         if !handler_path.exists() {
             let content = generate_api_handler_stub(api);
             fs::write(handler_path, content)?;
+            generate_api_test(api, schema, output_dir)?;
         }
     }
 
@@ -153,7 +269,10 @@ fn generate_api_content(api: &Api, schema: &Schema) -> String {
 
     content.push_str("use serde::{Deserialize, Serialize};\n");
 
-    if let Some(body_type) = &api.body {
+    let body = api.body_type();
+    let response = api.response_type();
+
+    if let Some(body_type) = body.as_ref().filter(|b| matches!(FieldType::from_str(b), FieldType::Custom(_))) {
         let body_type_snake = templates::to_snake_case(body_type);
         if body_type.ends_with("Input") {
             content.push_str(&format!("use crate::generated::dto::{}::{};\n", body_type_snake, body_type));
@@ -162,26 +281,30 @@ fn generate_api_content(api: &Api, schema: &Schema) -> String {
         }
     }
 
-    let response_field_type = rohas_parser::FieldType::from_str(&api.response);
+    let response_field_type = rohas_parser::FieldType::from_str(response);
     let is_custom_response = matches!(response_field_type, rohas_parser::FieldType::Custom(_));
     if is_custom_response {
-        let response_type_snake = templates::to_snake_case(&api.response);
+        let response_type_snake = templates::to_snake_case(response);
 
-        let is_type = schema.types.iter().any(|t| t.name == api.response);
-        let is_input = schema.inputs.iter().any(|i| i.name == api.response);
+        let is_type = schema.types.iter().any(|t| t.name == response);
+        let is_input = schema.inputs.iter().any(|i| i.name == response);
         
         if is_type || is_input {
-            content.push_str(&format!("use crate::generated::dto::{}::{};\n", response_type_snake, api.response));
+            content.push_str(&format!("use crate::generated::dto::{}::{};\n", response_type_snake, response));
         } else {
-            content.push_str(&format!("use crate::generated::models::{}::{};\n", response_type_snake, api.response));
+            content.push_str(&format!("use crate::generated::models::{}::{};\n", response_type_snake, response));
         }
     }
+    if api.paginated {
+        content.push_str("use crate::generated::page::Page;\n");
+    }
     content.push_str("\n");
 
-    if let Some(body_type) = &api.body {
+    if let Some(body_type) = &body {
         content.push_str(&format!(
             "pub type {}Request = {};\n\n",
-            api.name, body_type
+            api.name,
+            rohas_parser::FieldType::from_str(body_type).to_rust()
         ));
     } else {
         content.push_str(&format!(
@@ -193,7 +316,10 @@ fn generate_api_content(api: &Api, schema: &Schema) -> String {
         content.push_str("}\n\n");
     }
 
-    let response_rust_type = response_field_type.to_rust();
+    let mut response_rust_type = response_field_type.to_rust();
+    if api.paginated {
+        response_rust_type = format!("Page<{}>", response_rust_type);
+    }
     content.push_str(&format!(
         "pub type {}Response = {};\n",
         api.name, response_rust_type
@@ -215,7 +341,7 @@ fn generate_api_handler_stub(api: &Api) -> String {
         module_name, request_type, response_type
     ));
     content.push_str("use crate::generated::state::State;\n");
-    content.push_str("use rohas_runtime::{HandlerContext, HandlerResult, Result};\n\n");
+    content.push_str("use rohas_sdk::{HandlerContext, HandlerResult, Result};\n\n");
 
     content.push_str(&format!(
         "/// Rust handler for {} API.\n",
@@ -233,14 +359,14 @@ fn generate_api_handler_stub(api: &Api) -> String {
     content.push_str("    // For manual triggers: use state.trigger_event(\"EventName\", value)\n");
     content.push_str("    // Use state.logger for structured logging\n");
     content.push_str(&format!(
-        "    Err(rohas_runtime::RuntimeError::ExecutionFailed(\"Handler not implemented\".into()))\n"
+        "    Err(rohas_sdk::RuntimeError::ExecutionFailed(\"Handler not implemented\".into()))\n"
     ));
     content.push_str("}\n");
 
     content
 }
 
-pub fn generate_events(schema: &Schema, output_dir: &Path) -> Result<()> {
+pub fn generate_events(schema: &Schema, output_dir: &Path, layout: &Layout) -> Result<()> {
     let events_dir = output_dir.join("generated/events");
 
     for event in &schema.events {
@@ -258,8 +384,13 @@ pub fn generate_events(schema: &Schema, output_dir: &Path) -> Result<()> {
     }
     fs::write(events_dir.join("mod.rs"), mod_content)?;
 
+    if !layout.handler_stubs {
+        return Ok(());
+    }
+
     let handlers_dir = output_dir.join("handlers/events");
     for event in &schema.events {
+        let mut created_stub = false;
         for handler in &event.handlers {
             let file_name = format!("{}.rs", handler);
             let handler_path = handlers_dir.join(&file_name);
@@ -267,8 +398,13 @@ pub fn generate_events(schema: &Schema, output_dir: &Path) -> Result<()> {
             if !handler_path.exists() {
                 let content = generate_event_handler_stub(event, handler);
                 fs::write(handler_path, content)?;
+                created_stub = true;
             }
         }
+
+        if created_stub {
+            generate_event_test(event, schema, output_dir)?;
+        }
     }
 
     Ok(())
@@ -311,7 +447,7 @@ fn generate_event_handler_stub(event: &Event, handler_name: &str) -> String {
         "use crate::generated::events::{}::{};\n",
         event_module, event.name
     ));
-    content.push_str("use rohas_runtime::{HandlerContext, HandlerResult, Result};\n\n");
+    content.push_str("use rohas_sdk::{HandlerContext, HandlerResult, Result};\n\n");
 
     content.push_str(&format!(
         "/// High-performance Rust event handler.\n"
@@ -332,7 +468,228 @@ fn generate_event_handler_stub(event: &Event, handler_name: &str) -> String {
     content
 }
 
-pub fn generate_crons(schema: &Schema, output_dir: &Path) -> Result<()> {
+/// Generate `generated/testing.rs`, which wires the schema's event handlers
+/// into a `rohas_sdk::TestEngine` for handler tests.
+pub fn generate_test_engine(schema: &Schema, output_dir: &Path) -> Result<()> {
+    let handlers_dir = output_dir.join("handlers/events");
+
+    let mut content = String::new();
+    content.push_str("// Auto-generated Rust code from Rohas schema\n");
+    content.push_str("// DO NOT EDIT MANUALLY\n\n");
+    content.push_str("pub use rohas_sdk::TestEngine;\n\n");
+    content.push_str("/// Create a test engine wired with the schema's event handlers.\n");
+    content.push_str("pub fn test_engine() -> TestEngine {\n");
+    content.push_str("    #[allow(unused_mut)]\n");
+    content.push_str("    let mut engine = TestEngine::new();\n");
+
+    for event in &schema.events {
+        for handler in &event.handlers {
+            if !handlers_dir.join(format!("{}.rs", handler)).exists() {
+                continue;
+            }
+            content.push_str(&format!(
+                "    engine.on_event(\"{}\", |payload| async move {{\n",
+                event.name
+            ));
+            content.push_str(&format!(
+                "        let event: crate::generated::events::{} = serde_json::from_value(serde_json::json!({{\n",
+                event.name
+            ));
+            content.push_str("            \"payload\": payload,\n");
+            content.push_str("            \"timestamp\": chrono::Utc::now(),\n");
+            content.push_str("        }))?;\n");
+            content.push_str(&format!(
+                "        crate::handlers::events::{}::{}(event).await.map(|_| ())\n",
+                handler, handler
+            ));
+            content.push_str("    });\n");
+        }
+        if !event.triggers.is_empty() {
+            let triggers: Vec<String> = event.triggers.iter().map(|t| format!("\"{}\"", t)).collect();
+            content.push_str(&format!(
+                "    engine.chain(\"{}\", &[{}]);\n",
+                event.name,
+                triggers.join(", ")
+            ));
+        }
+    }
+
+    content.push_str("    engine\n");
+    content.push_str("}\n");
+
+    content.push_str(
+        r#"
+/// How long `TestClient::expect_event` waits before failing.
+pub const DEFAULT_EVENT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Typed client over the test engine, with one method per API.
+pub struct TestClient {
+    engine: TestEngine,
+}
+
+impl Default for TestClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TestClient {
+    pub fn new() -> Self {
+        Self::with_engine(test_engine())
+    }
+
+    pub fn with_engine(engine: TestEngine) -> Self {
+        Self { engine }
+    }
+
+    pub fn engine(&self) -> &TestEngine {
+        &self.engine
+    }
+
+    /// Wait for an event triggered by earlier calls.
+    pub async fn expect_event(&self, event_name: &str) -> rohas_sdk::Result<rohas_sdk::TriggeredEvent> {
+        self.engine.expect_event(event_name, DEFAULT_EVENT_TIMEOUT).await
+    }
+
+    /// Like `expect_event`, with an explicit timeout.
+    pub async fn expect_event_within(
+        &self,
+        event_name: &str,
+        timeout: std::time::Duration,
+    ) -> rohas_sdk::Result<rohas_sdk::TriggeredEvent> {
+        self.engine.expect_event(event_name, timeout).await
+    }
+
+    /// Publish an event directly, as if another service had emitted it.
+    pub async fn publish(&self, event_name: &str, payload: serde_json::Value) -> rohas_sdk::Result<()> {
+        self.engine.publish(event_name, payload).await
+    }
+"#,
+    );
+
+    let api_handlers_dir = output_dir.join("handlers/api");
+    for api in &schema.apis {
+        let snake_name = templates::to_snake_case(&api.name);
+        if !api_handlers_dir.join(format!("{}.rs", snake_name)).exists() {
+            continue;
+        }
+        let triggers: Vec<String> = api.triggers.iter().map(|t| format!("\"{}\"", t)).collect();
+        content.push_str(&format!("\n    /// {} {}\n", api.method, api.path));
+        content.push_str(&format!(
+            "    pub async fn {}(\n        &self,\n        req: crate::generated::api::{}Request,\n    ) -> rohas_sdk::Result<crate::generated::api::{}Response> {{\n",
+            snake_name, api.name, api.name
+        ));
+        content.push_str(&format!("        let mut state = self.engine.state(\"{}\");\n", snake_name));
+        content.push_str(&format!(
+            "        let response = crate::handlers::api::{}::handle_{}(req, &mut state).await?;\n",
+            snake_name, snake_name
+        ));
+        content.push_str(&format!(
+            "        self.engine.complete(&state, &[{}], &response).await?;\n",
+            triggers.join(", ")
+        ));
+        content.push_str("        Ok(response)\n");
+        content.push_str("    }\n");
+    }
+
+    content.push_str("}\n");
+
+    fs::write(output_dir.join("generated/testing.rs"), content)?;
+    Ok(())
+}
+
+fn crate_name(output_dir: &Path) -> Result<String> {
+    let project_root = config::get_project_root(output_dir)?;
+    Ok(config::extract_project_name(&project_root).replace('-', "_"))
+}
+
+fn generate_api_test(api: &Api, schema: &Schema, output_dir: &Path) -> Result<()> {
+    let tests_dir = config::get_project_root(output_dir)?.join("tests");
+    let snake_name = templates::to_snake_case(&api.name);
+    let test_path = tests_dir.join(format!("api_{}.rs", snake_name));
+    if test_path.exists() {
+        return Ok(());
+    }
+    fs::create_dir_all(&tests_dir)?;
+
+    let crate_name = crate_name(output_dir)?;
+    // Rust request types are the body itself.
+    let request = Example::for_api_body(api, schema).unwrap_or(Example::Object(Vec::new()));
+
+    let mut content = String::new();
+    content.push_str(&format!(
+        "use {}::generated::api::{}::{}Request;\n",
+        crate_name, snake_name, api.name
+    ));
+    content.push_str(&format!("use {}::generated::testing::TestClient;\n\n", crate_name));
+
+    content.push_str("#[tokio::test]\n");
+    content.push_str(&format!("async fn {}_handles_example_request() {{\n", snake_name));
+    content.push_str("    let client = TestClient::new();\n");
+    content.push_str(&format!(
+        "    let req: {}Request = serde_json::from_value(serde_json::json!({}))\n",
+        api.name,
+        request.to_json()
+    ));
+    content.push_str("        .expect(\"example request should deserialize\");\n\n");
+    content.push_str(&format!(
+        "    let _response = client.{}(req).await.expect(\"handler failed\");\n",
+        snake_name
+    ));
+    for trigger in &api.triggers {
+        content.push_str(&format!(
+            "    client.expect_event(\"{}\").await.expect(\"event not triggered\");\n",
+            trigger
+        ));
+    }
+    content.push_str("}\n");
+
+    fs::write(test_path, content)?;
+    Ok(())
+}
+
+fn generate_event_test(event: &Event, schema: &Schema, output_dir: &Path) -> Result<()> {
+    let tests_dir = config::get_project_root(output_dir)?.join("tests");
+    let snake_name = templates::to_snake_case(&event.name);
+    let test_path = tests_dir.join(format!("event_{}.rs", snake_name));
+    if test_path.exists() {
+        return Ok(());
+    }
+    fs::create_dir_all(&tests_dir)?;
+
+    let crate_name = crate_name(output_dir)?;
+
+    let mut content = String::new();
+    content.push_str(&format!("use {}::generated::testing::TestClient;\n\n", crate_name));
+
+    content.push_str("#[tokio::test]\n");
+    content.push_str(&format!("async fn {}_runs_handlers() {{\n", snake_name));
+    content.push_str("    let client = TestClient::new();\n");
+    content.push_str(&format!(
+        "    let payload = serde_json::json!({});\n\n",
+        Example::for_type(&event.payload, schema).to_json()
+    ));
+    content.push_str(&format!(
+        "    client.publish(\"{}\", payload).await.expect(\"event handlers failed\");\n\n",
+        event.name
+    ));
+    for trigger in &event.triggers {
+        content.push_str(&format!(
+            "    client.expect_event(\"{}\").await.expect(\"event not triggered\");\n",
+            trigger
+        ));
+    }
+    content.push_str("}\n");
+
+    fs::write(test_path, content)?;
+    Ok(())
+}
+
+pub fn generate_crons(schema: &Schema, output_dir: &Path, layout: &Layout) -> Result<()> {
+    if !layout.handler_stubs {
+        return Ok(());
+    }
+
     let handlers_dir = output_dir.join("handlers/cron");
 
     for cron in &schema.crons {
@@ -353,7 +710,7 @@ fn generate_cron_handler_stub(cron: &rohas_parser::Cron) -> String {
 
     let handler_name = format!("handle_{}", templates::to_snake_case(&cron.name));
 
-    content.push_str("use rohas_runtime::{HandlerContext, HandlerResult, Result};\n");
+    content.push_str("use rohas_sdk::{HandlerContext, HandlerResult, Result};\n");
     content.push_str("use crate::generated::state::State;\n\n");
 
     content.push_str(&format!(
@@ -376,7 +733,7 @@ fn generate_cron_handler_stub(cron: &rohas_parser::Cron) -> String {
     content
 }
 
-pub fn generate_websockets(schema: &Schema, output_dir: &Path) -> Result<()> {
+pub fn generate_websockets(schema: &Schema, output_dir: &Path, layout: &Layout) -> Result<()> {
     let ws_dir = output_dir.join("generated/websockets");
     
     fs::create_dir_all(&ws_dir)?;
@@ -410,6 +767,10 @@ pub fn generate_websockets(schema: &Schema, output_dir: &Path) -> Result<()> {
     }
     fs::write(ws_dir.join("mod.rs"), mod_content)?;
 
+    if !layout.handler_stubs {
+        return Ok(());
+    }
+
     let handlers_dir = output_dir.join("handlers/websockets");
     fs::create_dir_all(&handlers_dir)?;
     
@@ -520,7 +881,14 @@ fn generate_websocket_content(ws: &WebSocket, schema: &Schema) -> String {
     ));
     content.push_str(&format!("pub struct {}Connection\n", ws.name));
     content.push_str("{\n");
-    content.push_str("    // Connection metadata\n");
+    content.push_str("    pub connection_id: String,\n");
+    content.push_str("    pub path: String,\n");
+    content.push_str("    pub connected_at: chrono::DateTime<chrono::Utc>,\n");
+    content.push_str("    #[serde(default)]\n");
+    content.push_str("    pub ip_address: Option<String>,\n");
+    content.push_str("    /// Set by the websocket's `auth` handler.\n");
+    content.push_str("    #[serde(default)]\n");
+    content.push_str("    pub principal: Option<serde_json::Value>,\n");
     content.push_str("}\n");
 
     content
@@ -543,7 +911,7 @@ fn generate_websocket_handler_stub(ws: &WebSocket, handler_name: &str, event_typ
         ));
     }
 
-    content.push_str("use rohas_runtime::{HandlerContext, HandlerResult, Result};\n");
+    content.push_str("use rohas_sdk::{HandlerContext, HandlerResult, Result};\n");
     content.push_str("use crate::generated::state::State;\n\n");
 
     content.push_str(&format!(
@@ -576,7 +944,7 @@ fn generate_websocket_handler_stub(ws: &WebSocket, handler_name: &str, event_typ
     content
 }
 
-pub fn generate_middlewares(schema: &Schema, output_dir: &Path) -> Result<()> {
+pub fn generate_middlewares(schema: &Schema, output_dir: &Path, layout: &Layout) -> Result<()> {
     let mut middleware_names = std::collections::HashSet::new();
 
     for api in &schema.apis {
@@ -586,13 +954,17 @@ pub fn generate_middlewares(schema: &Schema, output_dir: &Path) -> Result<()> {
     }
 
     for ws in &schema.websockets {
-        for mw in &ws.middlewares {
+        for mw in ws.middleware_handlers() {
             middleware_names.insert(mw.clone());
         }
     }
 
     let middlewares_dir = output_dir.join("middlewares");
     fs::create_dir_all(&middlewares_dir)?;
+
+    if !layout.handler_stubs {
+        return Ok(());
+    }
     
     for mw_name in middleware_names {
         let file_name = format!("{}.rs", templates::to_snake_case(&mw_name));
@@ -621,7 +993,7 @@ fn generate_middleware_stub(mw_name: &str) -> String {
 
     let handler_name = format!("{}_middleware", templates::to_snake_case(mw_name));
 
-    content.push_str("use rohas_runtime::{HandlerContext, HandlerResult, Result};\n");
+    content.push_str("use rohas_sdk::{HandlerContext, HandlerResult, Result};\n");
     content.push_str("use crate::generated::state::State;\n\n");
 
     content.push_str(&format!(
@@ -633,6 +1005,8 @@ fn generate_middleware_stub(mw_name: &str) -> String {
     content.push_str(") -> Result<HandlerResult> {\n");
     content.push_str("    // TODO: Implement middleware logic\n");
     content.push_str("    // Return Ok to continue, Err to abort\n");
+    content.push_str("    // As a websocket auth handler, validate ctx.payload[\"token\"] and\n");
+    content.push_str("    // return {\"principal\": ...} to accept the connection\n");
     content.push_str(&format!(
         "    tracing::info!(\"Middleware {} executed\");\n",
         mw_name
@@ -645,98 +1019,88 @@ fn generate_middleware_stub(mw_name: &str) -> String {
 
 pub fn generate_state(output_dir: &Path) -> Result<()> {
     let generated_dir = output_dir.join("generated");
-    let content = r#"use serde_json::Value;
-use std::collections::HashMap;
-use tracing::{error, warn, info, debug, trace};
-
-/// State struct for Rust handlers.
-#[derive(Debug, Clone)]
-pub struct State {
-    handler_name: String,
-    triggers: Vec<TriggeredEvent>,
-    auto_trigger_payloads: HashMap<String, Value>,
-}
-
-#[derive(Debug, Clone)]
-pub struct TriggeredEvent {
-    pub event_name: String,
-    pub payload: Value,
-}
+    let content = r#"// Auto-generated Rust code from Rohas schema
+// DO NOT EDIT MANUALLY
 
-impl State {
-    /// Create a new State instance.
-    pub fn new(handler_name: impl Into<String>) -> Self {
-        Self {
-            handler_name: handler_name.into(),
-            triggers: Vec::new(),
-            auto_trigger_payloads: HashMap::new(),
-        }
-    }
+pub use rohas_sdk::{
+    ClientRequest, ClientResponse, CookieOptions, Cookies, HttpClient, HttpResponse, Kv, Logger, SameSite, State,
+    TriggeredEvent,
+};
+"#;
 
-    /// Manually trigger an event (for events NOT in schema triggers).
-    pub fn trigger_event(&mut self, event_name: impl Into<String>, payload: Value) {
-        self.triggers.push(TriggeredEvent {
-            event_name: event_name.into(),
-            payload,
-        });
-    }
+    fs::write(generated_dir.join("state.rs"), content)?;
+    Ok(())
+}
 
-    /// Set payload for an auto-triggered event (for events IN schema triggers).
-    pub fn set_payload(&mut self, event_name: impl Into<String>, payload: Value) {
-        self.auto_trigger_payloads.insert(event_name.into(), payload);
-    }
+/// Generate `generated/errors.rs`: the engine's `application/problem+json`
+/// error responses as types for API clients.
+pub fn generate_errors(output_dir: &Path) -> Result<()> {
+    let content = r#"// Auto-generated Rust code from Rohas schema
+// DO NOT EDIT MANUALLY
 
-    /// Get all manually triggered events (internal use).
-    pub fn get_triggers(&self) -> &[TriggeredEvent] {
-        &self.triggers
-    }
+use serde::{Deserialize, Serialize};
 
-    /// Get all auto-trigger payloads (internal use).
-    pub fn get_all_auto_trigger_payloads(&self) -> &HashMap<String, Value> {
-        &self.auto_trigger_payloads
-    }
+pub const PROBLEM_CONTENT_TYPE: &str = "application/problem+json";
 
-    /// Get a logger instance for this handler.
-    pub fn logger(&self) -> Logger {
-        Logger::new(&self.handler_name)
-    }
+/// A request body field that failed validation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
 }
 
-/// Structured logger for handlers.
-pub struct Logger {
-    handler_name: String,
+/// An API error response (RFC 7807).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProblemDetails {
+    #[serde(rename = "type", default = "about_blank")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    #[serde(default)]
+    pub detail: Option<String>,
+    #[serde(default)]
+    pub instance: Option<String>,
+    /// Present on validation failures (status 422).
+    #[serde(default)]
+    pub errors: Vec<FieldError>,
 }
 
-impl Logger {
-    pub fn new(handler_name: impl Into<String>) -> Self {
-        Self {
-            handler_name: handler_name.into(),
-        }
-    }
-
-    pub fn info(&self, message: &str) {
-        info!(handler = %self.handler_name, %message);
-    }
+fn about_blank() -> String {
+    "about:blank".to_string()
+}
 
-    pub fn error(&self, message: &str) {
-        error!(handler = %self.handler_name, %message);
+impl std::fmt::Display for ProblemDetails {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.detail.as_deref().unwrap_or(&self.title))
     }
+}
 
-    pub fn warn(&self, message: &str) {
-        warn!(handler = %self.handler_name, %message);
-    }
+impl std::error::Error for ProblemDetails {}
+"#;
 
-    pub fn debug(&self, message: &str) {
-        debug!(handler = %self.handler_name, %message);
-    }
+    fs::write(output_dir.join("generated/errors.rs"), content)?;
+    Ok(())
+}
 
-    pub fn trace(&self, message: &str) {
-        trace!(handler = %self.handler_name, %message);
-    }
+/// Generate `generated/page.rs`: the envelope of `response: Page<T>` APIs.
+pub fn generate_page(output_dir: &Path) -> Result<()> {
+    let content = r#"// Auto-generated Rust code from Rohas schema
+// DO NOT EDIT MANUALLY
+
+use serde::{Deserialize, Serialize};
+
+/// A page of a paginated list API.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Number of items across all pages.
+    pub total: i64,
+    /// Passed back as `?cursor=` for the next page; `None` on the last page.
+    pub next_cursor: Option<String>,
 }
 "#;
 
-    fs::write(generated_dir.join("state.rs"), content)?;
+    fs::write(output_dir.join("generated/page.rs"), content)?;
     Ok(())
 }
 
@@ -750,12 +1114,16 @@ pub fn generate_lib_rs(schema: &Schema, output_dir: &Path) -> Result<()> {
 
     // Generate module declarations
     content.push_str("pub mod state;\n");
+    content.push_str("pub mod errors;\n");
+    content.push_str("pub mod page;\n");
     content.push_str("pub mod models;\n");
     content.push_str("pub mod dto;\n");
     content.push_str("pub mod api;\n");
     content.push_str("pub mod events;\n");
     content.push_str("pub mod websockets;\n");
-    content.push_str("pub mod handlers;\n\n");
+    content.push_str("pub mod handlers;\n");
+    content.push_str("pub mod testing;\n");
+    content.push_str("pub mod factories;\n\n");
 
     // Re-export commonly used types
     content.push_str("pub use state::State;\n");
@@ -792,45 +1160,13 @@ pub fn generate_lib_rs(schema: &Schema, output_dir: &Path) -> Result<()> {
     main_lib_content.push_str("/// Initialize and register all handlers with the Rust runtime.\n");
     main_lib_content.push_str("/// This function should be called during engine startup.\n");
     main_lib_content.push_str("/// It will automatically register all handlers using the global registry.\n");
-    main_lib_content.push_str("pub async fn init_handlers(runtime: std::sync::Arc<rohas_runtime::RustRuntime>) -> rohas_runtime::Result<()> {\n");
+    main_lib_content.push_str("pub async fn init_handlers(runtime: std::sync::Arc<rohas_sdk::RustRuntime>) -> rohas_sdk::Result<()> {\n");
     main_lib_content.push_str("    generated::register_all_handlers(runtime).await\n");
     main_lib_content.push_str("}\n\n");
 
     // Add a C-compatible FFI function that can be called from the engine
     // This allows the engine to automatically register handlers
-    main_lib_content.push_str("/// C-compatible FFI function for automatic handler registration.\n");
-    main_lib_content.push_str("/// This is called automatically by the engine.\n");
-    main_lib_content.push_str("/// Returns 0 on success, non-zero on error.\n");
-    main_lib_content.push_str("#[no_mangle]\n");
-    main_lib_content.push_str("pub extern \"C\" fn rohas_set_runtime(runtime_ptr: *mut std::ffi::c_void) -> i32 {\n");
-    main_lib_content.push_str("    use std::sync::Arc;\n");
-    main_lib_content.push_str("    \n");
-    main_lib_content.push_str("    if runtime_ptr.is_null() {\n");
-    main_lib_content.push_str("        return 1; // Error: null pointer\n");
-    main_lib_content.push_str("    }\n");
-    main_lib_content.push_str("    \n");
-    main_lib_content.push_str("    // Safety: The engine passes a valid Arc<RustRuntime> pointer that was created with Arc::into_raw.\n");
-    main_lib_content.push_str("    // We reconstruct the Arc temporarily to clone it, then forget it so the engine retains ownership.\n");
-    main_lib_content.push_str("    unsafe {\n");
-    main_lib_content.push_str("        // Convert the raw pointer back to Arc<RustRuntime>\n");
-    main_lib_content.push_str("        // The engine created this with Arc::into_raw, so we reconstruct it temporarily\n");
-    main_lib_content.push_str("        let runtime: Arc<rohas_runtime::RustRuntime> = Arc::from_raw(runtime_ptr as *const rohas_runtime::RustRuntime);\n");
-    main_lib_content.push_str("        \n");
-    main_lib_content.push_str("        // Clone the Arc - this increments the reference count\n");
-    main_lib_content.push_str("        let runtime_clone = runtime.clone();\n");
-    main_lib_content.push_str("        \n");
-    main_lib_content.push_str("        // Forget the reconstructed Arc - we don't want to drop it here since the engine still owns it\n");
-    main_lib_content.push_str("        // The engine will manage the original Arc's lifetime\n");
-    main_lib_content.push_str("        std::mem::forget(runtime);\n");
-    main_lib_content.push_str("        \n");
-    main_lib_content.push_str("        // Call the generated set_runtime function which will register all handlers\n");
-    main_lib_content.push_str("        // This will store the cloned Arc in a OnceLock and register handlers synchronously\n");
-    main_lib_content.push_str("        // Note: If registration fails, set_runtime will panic (via .expect())\n");
-    main_lib_content.push_str("        generated::set_runtime(runtime_clone);\n");
-    main_lib_content.push_str("        \n");
-    main_lib_content.push_str("        0 // Success\n");
-    main_lib_content.push_str("    }\n");
-    main_lib_content.push_str("}\n");
+    main_lib_content.push_str(&set_runtime_ffi("generated::set_runtime"));
 
     fs::write(output_dir.join("lib.rs"), main_lib_content)?;
 
@@ -842,6 +1178,46 @@ pub fn generate_lib_rs(schema: &Schema, output_dir: &Path) -> Result<()> {
     Ok(())
 }
 
+/// The `rohas_set_runtime` entry point the dev server calls after loading a
+/// dylib; it hands the runtime to `set_runtime`, given as a path.
+fn set_runtime_ffi(set_runtime: &str) -> String {
+    let mut content = String::new();
+    content.push_str("/// C-compatible FFI function for automatic handler registration.\n");
+    content.push_str("/// This is called automatically by the engine.\n");
+    content.push_str("/// Returns 0 on success, non-zero on error.\n");
+    content.push_str("#[no_mangle]\n");
+    content.push_str("pub extern \"C\" fn rohas_set_runtime(runtime_ptr: *mut std::ffi::c_void) -> i32 {\n");
+    content.push_str("    use std::sync::Arc;\n");
+    content.push_str("    \n");
+    content.push_str("    if runtime_ptr.is_null() {\n");
+    content.push_str("        return 1; // Error: null pointer\n");
+    content.push_str("    }\n");
+    content.push_str("    \n");
+    content.push_str("    // Safety: The engine passes a valid Arc<RustRuntime> pointer that was created with Arc::into_raw.\n");
+    content.push_str("    // We reconstruct the Arc temporarily to clone it, then forget it so the engine retains ownership.\n");
+    content.push_str("    unsafe {\n");
+    content.push_str("        // Convert the raw pointer back to Arc<RustRuntime>\n");
+    content.push_str("        // The engine created this with Arc::into_raw, so we reconstruct it temporarily\n");
+    content.push_str("        let runtime: Arc<rohas_sdk::RustRuntime> = Arc::from_raw(runtime_ptr as *const rohas_sdk::RustRuntime);\n");
+    content.push_str("        \n");
+    content.push_str("        // Clone the Arc - this increments the reference count\n");
+    content.push_str("        let runtime_clone = runtime.clone();\n");
+    content.push_str("        \n");
+    content.push_str("        // Forget the reconstructed Arc - we don't want to drop it here since the engine still owns it\n");
+    content.push_str("        // The engine will manage the original Arc's lifetime\n");
+    content.push_str("        std::mem::forget(runtime);\n");
+    content.push_str("        \n");
+    content.push_str("        // Call the generated set_runtime function which will register all handlers\n");
+    content.push_str("        // This will store the cloned Arc in a OnceLock and register handlers synchronously\n");
+    content.push_str("        // Note: If registration fails, set_runtime will panic (via .expect())\n");
+    content.push_str(&format!("        {}(runtime_clone);\n", set_runtime));
+    content.push_str("        \n");
+    content.push_str("        0 // Success\n");
+    content.push_str("    }\n");
+    content.push_str("}\n");
+    content
+}
+
 fn generate_handlers_mod(schema: &Schema, output_dir: &Path) -> Result<()> {
     let handlers_dir = output_dir.join("handlers");
     let middlewares_dir = output_dir.join("middlewares");
@@ -932,7 +1308,7 @@ fn generate_handlers_mod(schema: &Schema, output_dir: &Path) -> Result<()> {
             }
         }
         for ws in &schema.websockets {
-            for mw in &ws.middlewares {
+            for mw in ws.middleware_handlers() {
                 middleware_names.insert(mw.clone());
             }
         }
@@ -952,14 +1328,22 @@ fn generate_handlers_mod(schema: &Schema, output_dir: &Path) -> Result<()> {
 }
 
 fn generate_handlers_registration(schema: &Schema, output_dir: &Path) -> Result<()> {
-    let generated_dir = output_dir.join("generated");
+    let content = handlers_registration_source(schema, output_dir, None);
+    fs::write(output_dir.join("generated").join("handlers.rs"), content)?;
+    Ok(())
+}
+
+/// Source of the handler registration module, for every handler or only
+/// those of one handler `group`.
+fn handlers_registration_source(schema: &Schema, output_dir: &Path, group: Option<&str>) -> String {
     let handlers_dir = output_dir.join("handlers");
+    let in_group = |name: &str| group.is_none_or(|group| group == name);
 
     let mut content = String::new();
     content.push_str("// Auto-generated handler registration\n");
     content.push_str("// DO NOT EDIT MANUALLY\n\n");
 
-    content.push_str("use rohas_runtime::{RustRuntime, HandlerContext, HandlerResult, Result};\n");
+    content.push_str("use rohas_sdk::{RustRuntime, HandlerContext, HandlerResult, Result};\n");
     content.push_str("use std::sync::Arc;\n");
     content.push_str("use std::sync::OnceLock;\n\n");
 
@@ -1002,6 +1386,10 @@ fn generate_handlers_registration(schema: &Schema, output_dir: &Path) -> Result<
         }
     }
 
+    if let Some(group) = group {
+        has_handlers = !group_handler_files(schema, output_dir, group).is_empty();
+    }
+
     if !has_handlers {
         content.push_str("/// Register all handlers with the Rust runtime.\n");
         content.push_str("/// No handlers found - implement handlers in src/handlers/ to register them.\n");
@@ -1011,8 +1399,7 @@ fn generate_handlers_registration(schema: &Schema, output_dir: &Path) -> Result<
         content.push_str("fn register_all_handlers_internal() -> Result<()> {\n");
         content.push_str("    Ok(())\n");
         content.push_str("}\n");
-        fs::write(generated_dir.join("handlers.rs"), content)?;
-        return Ok(());
+        return content;
     }
 
     content.push_str("// Import handler functions\n");
@@ -1021,7 +1408,7 @@ fn generate_handlers_registration(schema: &Schema, output_dir: &Path) -> Result<
         let handler_name = templates::to_snake_case(&api.name);
         let handler_file = handlers_dir.join("api").join(format!("{}.rs", handler_name));
 
-        if handler_file.exists() {
+        if in_group("api") && handler_file.exists() {
             content.push_str(&format!(
                 "use crate::handlers::api::{}::handle_{};\n",
                 handler_name, handler_name
@@ -1033,7 +1420,7 @@ fn generate_handlers_registration(schema: &Schema, output_dir: &Path) -> Result<
         for handler in &event.handlers {
             let handler_file = handlers_dir.join("events").join(format!("{}.rs", handler));
 
-            if handler_file.exists() {
+            if in_group("events") && handler_file.exists() {
                 content.push_str(&format!(
                     "use crate::handlers::events::{}::{};\n",
                     handler, handler
@@ -1046,7 +1433,7 @@ fn generate_handlers_registration(schema: &Schema, output_dir: &Path) -> Result<
     for ws in &schema.websockets {
         for handler in &ws.on_connect {
             let handler_file = websockets_handlers_dir.join(format!("{}.rs", handler));
-            if handler_file.exists() {
+            if in_group("websockets") && handler_file.exists() {
                 content.push_str(&format!(
                     "use crate::handlers::websockets::{}::{};\n",
                     handler, handler
@@ -1055,7 +1442,7 @@ fn generate_handlers_registration(schema: &Schema, output_dir: &Path) -> Result<
         }
         for handler in &ws.on_message {
             let handler_file = websockets_handlers_dir.join(format!("{}.rs", handler));
-            if handler_file.exists() {
+            if in_group("websockets") && handler_file.exists() {
                 content.push_str(&format!(
                     "use crate::handlers::websockets::{}::{};\n",
                     handler, handler
@@ -1064,7 +1451,7 @@ fn generate_handlers_registration(schema: &Schema, output_dir: &Path) -> Result<
         }
         for handler in &ws.on_disconnect {
             let handler_file = websockets_handlers_dir.join(format!("{}.rs", handler));
-            if handler_file.exists() {
+            if in_group("websockets") && handler_file.exists() {
                 content.push_str(&format!(
                     "use crate::handlers::websockets::{}::{};\n",
                     handler, handler
@@ -1081,7 +1468,7 @@ fn generate_handlers_registration(schema: &Schema, output_dir: &Path) -> Result<
         }
     }
     for ws in &schema.websockets {
-        for mw in &ws.middlewares {
+        for mw in ws.middleware_handlers() {
             middleware_names.insert(mw.clone());
         }
     }
@@ -1089,7 +1476,7 @@ fn generate_handlers_registration(schema: &Schema, output_dir: &Path) -> Result<
     for mw_name in &middleware_names {
         let mw_snake = templates::to_snake_case(mw_name);
         let handler_file = middlewares_dir.join(format!("{}.rs", mw_snake));
-        if handler_file.exists() {
+        if in_group("middlewares") && handler_file.exists() {
             let handler_fn_name = format!("{}_middleware", mw_snake);
             content.push_str(&format!(
                 "use crate::middlewares::{}::{};\n",
@@ -1110,15 +1497,15 @@ fn generate_handlers_registration(schema: &Schema, output_dir: &Path) -> Result<
     content.push_str("fn register_all_handlers_internal() -> Result<()> {\n");
     content.push_str("    use tracing::info;\n");
     content.push_str("    info!(\"Registering Rust handlers from dylib...\");\n");
-    content.push_str("    let runtime = RUNTIME_REGISTRY.get().ok_or_else(|| rohas_runtime::RuntimeError::ExecutionFailed(\"Runtime not set\".into()))?;\n");
-    content.push_str("    let rt = tokio::runtime::Runtime::new().map_err(|e| rohas_runtime::RuntimeError::ExecutionFailed(e.to_string()))?;\n");
+    content.push_str("    let runtime = RUNTIME_REGISTRY.get().ok_or_else(|| rohas_sdk::RuntimeError::ExecutionFailed(\"Runtime not set\".into()))?;\n");
+    content.push_str("    let rt = tokio::runtime::Runtime::new().map_err(|e| rohas_sdk::RuntimeError::ExecutionFailed(e.to_string()))?;\n");
     content.push_str("    rt.block_on(async {\n");
 
     for api in &schema.apis {
         let handler_name = templates::to_snake_case(&api.name);
         let handler_file = handlers_dir.join("api").join(format!("{}.rs", handler_name));
 
-        if handler_file.exists() {
+        if in_group("api") && handler_file.exists() {
             content.push_str(&format!(
                 "        // Register API handler: {}\n",
                 api.name
@@ -1136,19 +1523,24 @@ fn generate_handlers_registration(schema: &Schema, output_dir: &Path) -> Result<
             content.push_str(&format!(
                 "                // Parse request from context\n"
             ));
+            let parse_request = match api.consumes {
+                ContentType::OctetStream => "match ctx.raw_body.clone() { Some(rohas_sdk::RawBody::Bytes(bytes)) => bytes, _ => Vec::new() }",
+                ContentType::Csv => "match ctx.raw_body.clone() { Some(rohas_sdk::RawBody::Text(text)) => text, _ => String::new() }",
+                ContentType::Json | ContentType::MsgPack => "serde_json::from_value(ctx.payload.clone())?",
+            };
             content.push_str(&format!(
-                "                let req: crate::generated::api::{}::{}Request = serde_json::from_value(ctx.payload.clone())?;\n",
-                handler_name, api.name
+                "                let req: crate::generated::api::{}::{}Request = {};\n",
+                handler_name, api.name, parse_request
             ));
             content.push_str(&format!(
-                "                let mut state = crate::generated::state::State::new(&ctx.handler_name);\n"
+                "                let mut state = crate::generated::state::State::new(&ctx.handler_name).with_cookies(ctx.cookies.clone());\n"
             ));
             content.push_str(&format!(
                 "                let response = handle_{}(req, &mut state).await?;\n",
                 handler_name
             ));
             content.push_str(&format!(
-                "                Ok(HandlerResult::success(serde_json::to_value(response)?, 0))\n"
+                "                Ok(HandlerResult::success(serde_json::to_value(response)?, 0).with_response(state.get_response().clone()))\n"
             ));
             content.push_str(&format!(
                 "            }}\n"
@@ -1169,7 +1561,7 @@ fn generate_handlers_registration(schema: &Schema, output_dir: &Path) -> Result<
         
         for handler in &ws.on_connect {
             let handler_file = websockets_handlers_dir.join(format!("{}.rs", handler));
-            if handler_file.exists() {
+            if in_group("websockets") && handler_file.exists() {
                 content.push_str(&format!(
                     "        // Register WebSocket connect handler: {}\n",
                     handler
@@ -1216,7 +1608,7 @@ fn generate_handlers_registration(schema: &Schema, output_dir: &Path) -> Result<
         
         for handler in &ws.on_message {
             let handler_file = websockets_handlers_dir.join(format!("{}.rs", handler));
-            if handler_file.exists() {
+            if in_group("websockets") && handler_file.exists() {
                 content.push_str(&format!(
                     "        // Register WebSocket message handler: {}\n",
                     handler
@@ -1279,7 +1671,7 @@ fn generate_handlers_registration(schema: &Schema, output_dir: &Path) -> Result<
         
         for handler in &ws.on_disconnect {
             let handler_file = websockets_handlers_dir.join(format!("{}.rs", handler));
-            if handler_file.exists() {
+            if in_group("websockets") && handler_file.exists() {
                 content.push_str(&format!(
                     "        // Register WebSocket disconnect handler: {}\n",
                     handler
@@ -1331,7 +1723,7 @@ fn generate_handlers_registration(schema: &Schema, output_dir: &Path) -> Result<
         }
     }
     for ws in &schema.websockets {
-        for mw in &ws.middlewares {
+        for mw in ws.middleware_handlers() {
             middleware_names.insert(mw.clone());
         }
     }
@@ -1339,7 +1731,7 @@ fn generate_handlers_registration(schema: &Schema, output_dir: &Path) -> Result<
     for mw_name in middleware_names {
         let mw_snake = templates::to_snake_case(&mw_name);
         let handler_file = middlewares_dir.join(format!("{}.rs", mw_snake));
-        if handler_file.exists() {
+        if in_group("middlewares") && handler_file.exists() {
             let handler_fn_name = format!("{}_middleware", mw_snake);
             content.push_str(&format!(
                 "        // Register middleware handler: {}\n",
@@ -1375,12 +1767,189 @@ fn generate_handlers_registration(schema: &Schema, output_dir: &Path) -> Result<
         }
     }
 
-    content.push_str("        Ok::<(), rohas_runtime::RuntimeError>(())\n");
+    content.push_str("        Ok::<(), rohas_sdk::RuntimeError>(())\n");
     content.push_str("    })?;\n");
     content.push_str("    Ok(())\n");
     content.push_str("}\n");
 
-    fs::write(generated_dir.join("handlers.rs"), content)?;
+    content
+}
+
+/// Directory, relative to the project root, holding the workspace crates
+/// `rohas dev` builds handler groups from.
+pub const HANDLER_CRATES_DIR: &str = ".rohas/crates";
+
+/// Handler groups built as their own dylib, so a change in one group only
+/// rebuilds and reloads that group.
+pub const HANDLER_GROUPS: [&str; 3] = ["api", "websockets", "middlewares"];
+
+/// The source tree as seen from a crate under `HANDLER_CRATES_DIR`.
+const SRC_FROM_CRATE: &str = "../../../src";
+
+/// Handler group owning a source file, given relative to the project root.
+pub fn handler_group(relative_path: &Path) -> Option<&'static str> {
+    let mut components = relative_path
+        .components()
+        .map(|c| c.as_os_str().to_str().unwrap_or_default());
+    if components.next() != Some("src") {
+        return None;
+    }
+    match (components.next()?, components.next()?) {
+        ("middlewares", _) => Some("middlewares"),
+        ("handlers", "api") => Some("api"),
+        ("handlers", "websockets") => Some("websockets"),
+        _ => None,
+    }
+}
+
+/// Package name of a handler group crate.
+pub fn handler_crate_name(project_name: &str, group: &str) -> String {
+    format!("{}-{}", project_name, group)
+}
+
+/// Crates under `HANDLER_CRATES_DIR`, as workspace member paths.
+pub fn workspace_members(project_root: &Path) -> Vec<String> {
+    let mut members: Vec<String> = fs::read_dir(project_root.join(HANDLER_CRATES_DIR))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().join("Cargo.toml").exists())
+        .filter_map(|entry| entry.file_name().to_str().map(|name| format!("{}/{}", HANDLER_CRATES_DIR, name)))
+        .collect();
+    members.sort();
+    members
+}
+
+/// Handler files of a group that exist in the source tree.
+fn group_handler_files(schema: &Schema, output_dir: &Path, group: &str) -> Vec<std::path::PathBuf> {
+    let mut files = Vec::new();
+    match group {
+        "api" => {
+            for api in &schema.apis {
+                let handler_name = templates::to_snake_case(&api.name);
+                files.push(output_dir.join("handlers/api").join(format!("{}.rs", handler_name)));
+            }
+        }
+        "websockets" => {
+            for ws in &schema.websockets {
+                for handler in ws.on_connect.iter().chain(&ws.on_message).chain(&ws.on_disconnect) {
+                    files.push(output_dir.join("handlers/websockets").join(format!("{}.rs", handler)));
+                }
+            }
+        }
+        "middlewares" => {
+            let names = schema
+                .apis
+                .iter()
+                .flat_map(|api| api.middlewares.iter())
+                .chain(schema.websockets.iter().flat_map(|ws| ws.middleware_handlers()));
+            for name in names {
+                let mw_snake = templates::to_snake_case(name);
+                files.push(output_dir.join("middlewares").join(format!("{}.rs", mw_snake)));
+            }
+        }
+        _ => {}
+    }
+    files.retain(|file| file.exists());
+    files.sort();
+    files.dedup();
+    files
+}
+
+/// Module file declaring a group's handlers, relative to the source tree.
+fn group_module(group: &str) -> String {
+    match group {
+        "middlewares" => "middlewares/mod.rs".to_string(),
+        _ => format!("handlers/{}/mod.rs", group),
+    }
+}
+
+/// Generate the workspace crates `rohas dev` rebuilds handlers from: a
+/// `shared` crate with the generated types, and a dylib per handler group
+/// compiling that group's handler files in place.
+pub fn generate_handler_crates(schema: &Schema, output_dir: &Path) -> Result<()> {
+    let project_root = config::get_project_root(output_dir)?;
+    let project_name = config::extract_project_name(&project_root);
+    let crates_dir = project_root.join(HANDLER_CRATES_DIR);
+
+    let groups: Vec<&str> = HANDLER_GROUPS
+        .iter()
+        .copied()
+        .filter(|group| output_dir.join(group_module(group)).exists())
+        .filter(|group| !group_handler_files(schema, output_dir, group).is_empty())
+        .collect();
+
+    for group in HANDLER_GROUPS.iter().chain(&["shared"]) {
+        let stale = !groups.contains(group) && (*group != "shared" || groups.is_empty());
+        if stale && crates_dir.join(group).exists() {
+            fs::remove_dir_all(crates_dir.join(group))?;
+        }
+    }
+    if groups.is_empty() {
+        return Ok(());
+    }
+
+    let shared_name = handler_crate_name(&project_name, "shared");
+    let shared_dir = crates_dir.join("shared");
+    config::generate_member_cargo_toml(&shared_dir, &shared_name, false, "")?;
+
+    let mut shared_lib = String::new();
+    shared_lib.push_str("// Auto-generated Rust code from Rohas schema\n");
+    shared_lib.push_str("// DO NOT EDIT MANUALLY\n\n");
+    shared_lib.push_str("// Generated types shared by the handler group crates\n\n");
+    let modules = [
+        ("state", "state.rs"),
+        ("errors", "errors.rs"),
+        ("page", "page.rs"),
+        ("models", "models/mod.rs"),
+        ("dto", "dto/mod.rs"),
+        ("api", "api/mod.rs"),
+        ("events", "events/mod.rs"),
+        ("websockets", "websockets/mod.rs"),
+        ("factories", "factories.rs"),
+    ];
+    for (module, file) in modules {
+        shared_lib.push_str(&format!("#[path = \"{}/generated/{}\"]\n", SRC_FROM_CRATE, file));
+        shared_lib.push_str(&format!("pub mod {};\n", module));
+    }
+    // Generated code refers to its types through `crate::generated`.
+    let names: Vec<&str> = modules.iter().map(|(module, _)| *module).collect();
+    shared_lib.push_str("\npub mod generated {\n");
+    shared_lib.push_str(&format!("    pub use crate::{{{}}};\n", names.join(", ")));
+    shared_lib.push_str("    pub use crate::state::State;\n");
+    shared_lib.push_str("}\n");
+    fs::write(shared_dir.join("lib.rs"), shared_lib)?;
+
+    for group in groups {
+        let group_dir = crates_dir.join(group);
+        config::generate_member_cargo_toml(
+            &group_dir,
+            &handler_crate_name(&project_name, group),
+            true,
+            &format!("{} = {{ path = \"../shared\" }}\n", shared_name),
+        )?;
+
+        let mut lib = String::new();
+        lib.push_str("// Auto-generated Rust code from Rohas schema\n");
+        lib.push_str("// DO NOT EDIT MANUALLY\n\n");
+        lib.push_str(&format!("// Handlers of the `{}` group, built as their own dylib\n\n", group));
+        lib.push_str(&format!("pub use {}::generated;\n\n", shared_name.replace('-', "_")));
+        lib.push_str(&format!("#[path = \"{}/{}\"]\n", SRC_FROM_CRATE, group_module(group)));
+        lib.push_str(&format!("pub mod {};\n\n", group));
+        if group != "middlewares" {
+            // Handlers are reached as `crate::handlers::<group>`, as in the main crate.
+            lib.push_str("pub mod handlers {\n");
+            lib.push_str(&format!("    pub use crate::{};\n", group));
+            lib.push_str("}\n\n");
+        }
+        lib.push_str("pub mod registration;\n\n");
+        lib.push_str(&set_runtime_ffi("registration::set_runtime"));
+        fs::write(group_dir.join("lib.rs"), lib)?;
+
+        let registration = handlers_registration_source(schema, output_dir, Some(group));
+        fs::write(group_dir.join("registration.rs"), registration)?;
+    }
+
     Ok(())
 }
 
@@ -1546,3 +2115,75 @@ validate:
     Ok(())
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rohas_parser::Parser;
+
+    const SCHEMA: &str = r#"
+model Order {
+  id Int
+}
+
+api CreateOrder {
+  method: POST
+  path: "/orders"
+  body: Order
+  response: Order
+}
+
+ws OrderFeed {
+  path: "/ws/orders"
+  onConnect: [on_connect_handler]
+}
+"#;
+
+    #[test]
+    fn test_handler_group_of_source_files() {
+        assert_eq!(handler_group(Path::new("src/handlers/api/create_order.rs")), Some("api"));
+        assert_eq!(handler_group(Path::new("src/handlers/websockets/mod.rs")), Some("websockets"));
+        assert_eq!(handler_group(Path::new("src/middlewares/auth.rs")), Some("middlewares"));
+        assert_eq!(handler_group(Path::new("src/handlers/events/notify.rs")), None);
+        assert_eq!(handler_group(Path::new("src/generated/api/mod.rs")), None);
+        assert_eq!(handler_group(Path::new("tests/api_create_order.rs")), None);
+    }
+
+    #[test]
+    fn test_handler_crates_cover_groups_with_handlers() {
+        let schema = Parser::parse_string(SCHEMA).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let project_root = dir.path().join("shop");
+        let src = project_root.join("src");
+        fs::create_dir_all(src.join("handlers/api")).unwrap();
+        fs::write(src.join("handlers/api/mod.rs"), "pub mod create_order;\n").unwrap();
+        fs::write(src.join("handlers/api/create_order.rs"), "").unwrap();
+        // Left over from a schema that had middlewares.
+        fs::create_dir_all(project_root.join(HANDLER_CRATES_DIR).join("middlewares")).unwrap();
+
+        generate_handler_crates(&schema, &src).unwrap();
+
+        let crates_dir = project_root.join(HANDLER_CRATES_DIR);
+        let manifest = fs::read_to_string(crates_dir.join("api/Cargo.toml")).unwrap();
+        assert!(manifest.contains("name = \"shop-api\""));
+        assert!(manifest.contains("crate-type = [\"dylib\"]"));
+        assert!(manifest.contains("shop-shared = { path = \"../shared\" }"));
+
+        let lib = fs::read_to_string(crates_dir.join("api/lib.rs")).unwrap();
+        assert!(lib.contains("pub use shop_shared::generated;"));
+        assert!(lib.contains("#[path = \"../../../src/handlers/api/mod.rs\"]"));
+        assert!(lib.contains("registration::set_runtime(runtime_clone);"));
+
+        let registration = fs::read_to_string(crates_dir.join("api/registration.rs")).unwrap();
+        assert!(registration.contains("use crate::handlers::api::create_order::handle_create_order;"));
+
+        let shared = fs::read_to_string(crates_dir.join("shared/lib.rs")).unwrap();
+        assert!(shared.contains("#[path = \"../../../src/generated/models/mod.rs\"]"));
+        assert!(shared.contains("pub use crate::{state, errors, page, models, dto, api, events, websockets, factories};"));
+
+        assert_eq!(
+            workspace_members(&project_root),
+            vec![".rohas/crates/api".to_string(), ".rohas/crates/shared".to_string()]
+        );
+    }
+}
@@ -0,0 +1,740 @@
+//! Swift client target: `Codable` structs for the schema's models, types,
+//! inputs and events, an async `URLSession` client for its APIs and a
+//! client per websocket.
+//!
+//! Like the Kotlin target it emits no handler stubs or project files; the
+//! output directory is the sources folder of a Swift package target.
+
+use crate::error::Result;
+use rohas_parser::{Api, ContentType, Field, FieldType, Schema, TokenSource, WebSocket};
+use std::fs;
+use std::path::Path;
+
+const KEYWORDS: &[&str] = &[
+    "associatedtype", "case", "class", "default", "defer", "deinit", "do", "else", "enum",
+    "extension", "fallthrough", "false", "for", "func", "guard", "if", "import", "in", "init",
+    "inout", "internal", "is", "let", "nil", "operator", "private", "protocol", "public",
+    "repeat", "return", "self", "Self", "static", "struct", "subscript", "super", "switch",
+    "throw", "throws", "true", "try", "typealias", "var", "where", "while",
+];
+
+pub fn generate(schema: &Schema, output_dir: &Path) -> Result<()> {
+    fs::create_dir_all(output_dir)?;
+
+    fs::write(output_dir.join("Coding.swift"), generate_coding())?;
+    fs::write(output_dir.join("Models.swift"), generate_models(schema))?;
+    fs::write(output_dir.join("Inputs.swift"), generate_inputs(schema))?;
+    fs::write(output_dir.join("Events.swift"), generate_events(schema))?;
+    fs::write(output_dir.join("Errors.swift"), generate_errors())?;
+    fs::write(output_dir.join("Client.swift"), generate_client(schema))?;
+    fs::write(output_dir.join("WebSockets.swift"), generate_websockets(schema))?;
+
+    Ok(())
+}
+
+const HEADER: &str = "// Generated by Rohas - Do not edit\n\nimport Foundation\n\n";
+
+fn escape_keyword(name: &str) -> String {
+    if KEYWORDS.contains(&name) {
+        format!("`{}`", name)
+    } else {
+        name.to_string()
+    }
+}
+
+fn lower_camel(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// A public struct with a public memberwise initializer.
+fn codable_struct(name: &str, fields: &[(String, String)]) -> String {
+    let mut content = String::new();
+    content.push_str(&format!("public struct {}: Codable, Equatable {{\n", name));
+    for (field, swift_type) in fields {
+        content.push_str(&format!("    public var {}: {}\n", escape_keyword(field), swift_type));
+    }
+
+    let params: Vec<String> = fields
+        .iter()
+        .map(|(field, swift_type)| {
+            let default = if swift_type.ends_with('?') { " = nil" } else { "" };
+            format!("{}: {}{}", escape_keyword(field), swift_type, default)
+        })
+        .collect();
+    content.push('\n');
+    content.push_str(&format!("    public init({}) {{\n", params.join(", ")));
+    for (field, _) in fields {
+        content.push_str(&format!("        self.{} = {}\n", escape_keyword(field), escape_keyword(field)));
+    }
+    content.push_str("    }\n");
+    content.push_str("}\n");
+    content
+}
+
+fn field_members(fields: &[Field]) -> Vec<(String, String)> {
+    fields
+        .iter()
+        .map(|field| {
+            let swift_type = field.field_type.to_swift();
+            let swift_type = if field.optional { format!("{}?", swift_type) } else { swift_type };
+            (field.name.clone(), swift_type)
+        })
+        .collect()
+}
+
+fn structs<'a>(classes: impl Iterator<Item = (&'a str, &'a [Field])>) -> String {
+    let mut content = HEADER.to_string();
+    let structs: Vec<String> = classes
+        .map(|(name, fields)| codable_struct(name, &field_members(fields)))
+        .collect();
+    content.push_str(&structs.join("\n"));
+    content
+}
+
+/// `Models.swift`: models and `type` declarations.
+fn generate_models(schema: &Schema) -> String {
+    structs(
+        schema
+            .models
+            .iter()
+            .map(|m| (m.name.as_str(), m.fields.as_slice()))
+            .chain(schema.types.iter().map(|t| (t.name.as_str(), t.fields.as_slice()))),
+    )
+}
+
+/// `Inputs.swift`: request bodies.
+fn generate_inputs(schema: &Schema) -> String {
+    structs(schema.inputs.iter().map(|i| (i.name.as_str(), i.fields.as_slice())))
+}
+
+/// `Events.swift`: one struct per event with its payload, plus the event names.
+fn generate_events(schema: &Schema) -> String {
+    let mut content = HEADER.to_string();
+    for event in &schema.events {
+        let fields = vec![
+            ("payload".to_string(), FieldType::from_str(&event.payload).to_swift()),
+            ("timestamp".to_string(), "Date".to_string()),
+        ];
+        content.push_str(&codable_struct(&event.name, &fields));
+        content.push('\n');
+    }
+
+    content.push_str("public enum EventNames {\n");
+    for event in &schema.events {
+        content.push_str(&format!(
+            "    public static let {} = \"{}\"\n",
+            escape_keyword(&lower_camel(&event.name)),
+            event.name
+        ));
+    }
+    content.push_str("}\n");
+    content
+}
+
+/// `Coding.swift`: JSON settings shared by the clients and `JSONValue` for
+/// `Json` fields.
+fn generate_coding() -> String {
+    let mut content = HEADER.to_string();
+    content.push_str(
+        r#"/// Encoders and decoders matching the engine: dates are RFC 3339 strings,
+/// with or without fractional seconds.
+public enum RohasCoding {
+    public static let encoder: JSONEncoder = {
+        let encoder = JSONEncoder()
+        encoder.dateEncodingStrategy = .custom { date, encoder in
+            var container = encoder.singleValueContainer()
+            try container.encode(fractionalFormatter.string(from: date))
+        }
+        return encoder
+    }()
+
+    public static let decoder: JSONDecoder = {
+        let decoder = JSONDecoder()
+        decoder.dateDecodingStrategy = .custom { decoder in
+            let container = try decoder.singleValueContainer()
+            let text = try container.decode(String.self)
+            if let date = fractionalFormatter.date(from: text) ?? plainFormatter.date(from: text) {
+                return date
+            }
+            throw DecodingError.dataCorruptedError(
+                in: container,
+                debugDescription: "Expected an RFC 3339 date, got \(text)"
+            )
+        }
+        return decoder
+    }()
+
+    private static let fractionalFormatter: ISO8601DateFormatter = {
+        let formatter = ISO8601DateFormatter()
+        formatter.formatOptions = [.withInternetDateTime, .withFractionalSeconds]
+        return formatter
+    }()
+
+    private static let plainFormatter = ISO8601DateFormatter()
+}
+
+/// Any JSON value, for `Json` fields and untyped payloads.
+public enum JSONValue: Codable, Equatable {
+    case null
+    case bool(Bool)
+    case number(Double)
+    case string(String)
+    case array([JSONValue])
+    case object([String: JSONValue])
+
+    public init(from decoder: Decoder) throws {
+        let container = try decoder.singleValueContainer()
+        if container.decodeNil() {
+            self = .null
+        } else if let value = try? container.decode(Bool.self) {
+            self = .bool(value)
+        } else if let value = try? container.decode(Double.self) {
+            self = .number(value)
+        } else if let value = try? container.decode(String.self) {
+            self = .string(value)
+        } else if let value = try? container.decode([JSONValue].self) {
+            self = .array(value)
+        } else {
+            self = .object(try container.decode([String: JSONValue].self))
+        }
+    }
+
+    public func encode(to encoder: Encoder) throws {
+        var container = encoder.singleValueContainer()
+        switch self {
+        case .null: try container.encodeNil()
+        case .bool(let value): try container.encode(value)
+        case .number(let value): try container.encode(value)
+        case .string(let value): try container.encode(value)
+        case .array(let value): try container.encode(value)
+        case .object(let value): try container.encode(value)
+        }
+    }
+}
+"#,
+    );
+    content
+}
+
+/// `Errors.swift`: the engine's `application/problem+json` error responses.
+fn generate_errors() -> String {
+    let mut content = HEADER.to_string();
+    content.push_str(
+        r#"/// A request body field that failed validation.
+public struct FieldError: Codable, Equatable {
+    public var field: String
+    public var message: String
+}
+
+/// An API error response (RFC 7807).
+public struct ProblemDetails: Codable, Equatable {
+    public var type: String
+    public var title: String
+    public var status: Int
+    public var detail: String?
+    public var instance: String?
+    /// Present on validation failures (status 422).
+    public var errors: [FieldError]?
+}
+
+/// Thrown by `RohasClient` for error responses.
+public struct ProblemError: Error, LocalizedError {
+    public let problem: ProblemDetails
+
+    public var status: Int { problem.status }
+    public var fieldErrors: [FieldError] { problem.errors ?? [] }
+    public var errorDescription: String? { problem.detail ?? problem.title }
+
+    /// Reads an error response, accepting the legacy `{ "error": ... }` body as well.
+    init(status: Int, body: Data) {
+        if let problem = try? RohasCoding.decoder.decode(ProblemDetails.self, from: body) {
+            self.problem = problem
+            return
+        }
+        let legacy = try? JSONSerialization.jsonObject(with: body) as? [String: Any]
+        self.problem = ProblemDetails(
+            type: "about:blank",
+            title: HTTPURLResponse.localizedString(forStatusCode: status),
+            status: status,
+            detail: legacy?["error"] as? String,
+            instance: nil,
+            errors: nil
+        )
+    }
+}
+"#,
+    );
+    content
+}
+
+/// Swift string interpolation for `path`, with `{param}` segments filled
+/// from the method's parameters.
+fn path_template(path: &str) -> (String, Vec<String>) {
+    let mut template = String::new();
+    let mut params = Vec::new();
+    let mut rest = path;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        let param = &rest[start + 1..start + len];
+        template.push_str(&rest[..start]);
+        template.push_str(&format!("\\(Self.escape({}))", escape_keyword(param)));
+        params.push(param.to_string());
+        rest = &rest[start + len + 1..];
+    }
+    template.push_str(rest);
+    (template, params)
+}
+
+fn generate_api_method(api: &Api) -> String {
+    let mut content = String::new();
+    if api.consumes == ContentType::MsgPack || api.produces == ContentType::MsgPack {
+        content.push_str(&format!(
+            "    // {}: MessagePack bodies are not supported by the generated client.\n",
+            api.name
+        ));
+        return content;
+    }
+
+    let (path, path_params) = path_template(&api.path);
+    let mut params: Vec<String> = path_params
+        .iter()
+        .map(|p| format!("{}: String", escape_keyword(p)))
+        .collect();
+    let body = api.body_type();
+    if let Some(body) = &body {
+        params.push(format!("body: {}", FieldType::from_str(body).to_swift()));
+    }
+    params.push("query: [String: String] = [:]".to_string());
+
+    let response = match api.produces {
+        ContentType::OctetStream => "Data".to_string(),
+        ContentType::Csv => "String".to_string(),
+        _ if api.paginated => format!("Page<{}>", FieldType::from_str(&api.response).to_swift()),
+        _ => FieldType::from_str(&api.response).to_swift(),
+    };
+
+    let body_arg = match (&body, api.consumes) {
+        (None, _) => String::new(),
+        (Some(_), ContentType::OctetStream) => ", body: body, contentType: \"application/octet-stream\"".to_string(),
+        (Some(_), ContentType::Csv) => ", body: Data(body.utf8), contentType: \"text/csv\"".to_string(),
+        (Some(_), _) => ", body: try RohasCoding.encoder.encode(body), contentType: \"application/json\"".to_string(),
+    };
+    let call = format!("let data = try await sendRaw(\"{:?}\", \"{}\", query: query{})", api.method, path, body_arg);
+
+    content.push_str(&format!("    /// `{:?} {}`\n", api.method, api.path));
+    content.push_str(&format!(
+        "    public func {}({}) async throws -> {} {{\n",
+        escape_keyword(&lower_camel(&api.name)),
+        params.join(", "),
+        response
+    ));
+    content.push_str(&format!("        {}\n", call));
+    match api.produces {
+        ContentType::OctetStream => content.push_str("        return data\n"),
+        ContentType::Csv => content.push_str("        return String(decoding: data, as: UTF8.self)\n"),
+        _ => content.push_str(&format!(
+            "        return try RohasCoding.decoder.decode(ApiResponse<{}>.self, from: data).data\n",
+            response
+        )),
+    }
+    content.push_str("    }\n");
+    content
+}
+
+/// `Client.swift`: an async `URLSession` client with one method per API.
+fn generate_client(schema: &Schema) -> String {
+    let mut content = HEADER.to_string();
+    content.push_str(
+        r#"/// The `{ "data": ... }` envelope handlers respond with.
+public struct ApiResponse<T: Decodable>: Decodable {
+    public let data: T
+}
+
+/// A page of a paginated list API.
+public struct Page<T: Codable>: Codable {
+    public var items: [T]
+    /// Number of items across all pages.
+    public var total: Int
+    /// Passed back as the `cursor` query parameter for the next page; nil on the last page.
+    public var next_cursor: String?
+}
+
+/// Calls the schema's APIs. Error responses are thrown as `ProblemError`.
+public final class RohasClient {
+    public let baseURL: URL
+    private let session: URLSession
+
+    public init(baseURL: URL, session: URLSession = .shared) {
+        self.baseURL = baseURL
+        self.session = session
+    }
+
+"#,
+    );
+
+    let methods: Vec<String> = schema.apis.iter().map(generate_api_method).collect();
+    content.push_str(&methods.join("\n"));
+    if !methods.is_empty() {
+        content.push('\n');
+    }
+
+    content.push_str(
+        r#"    private static let pathSegmentAllowed = CharacterSet.urlPathAllowed.subtracting(CharacterSet(charactersIn: "/"))
+
+    private static func escape(_ segment: String) -> String {
+        segment.addingPercentEncoding(withAllowedCharacters: pathSegmentAllowed) ?? segment
+    }
+
+    private func sendRaw(
+        _ method: String,
+        _ path: String,
+        query: [String: String],
+        body: Data? = nil,
+        contentType: String? = nil
+    ) async throws -> Data {
+        let base = baseURL.absoluteString.hasSuffix("/") ? String(baseURL.absoluteString.dropLast()) : baseURL.absoluteString
+        guard var components = URLComponents(string: base + path) else {
+            throw URLError(.badURL)
+        }
+        if !query.isEmpty {
+            components.queryItems = query.map { URLQueryItem(name: $0.key, value: $0.value) }
+        }
+        guard let url = components.url else {
+            throw URLError(.badURL)
+        }
+
+        var request = URLRequest(url: url)
+        request.httpMethod = method
+        request.httpBody = body
+        if let contentType {
+            request.setValue(contentType, forHTTPHeaderField: "Content-Type")
+        }
+
+        let (data, response) = try await session.data(for: request)
+        guard let http = response as? HTTPURLResponse else {
+            throw URLError(.badServerResponse)
+        }
+        guard (200..<300).contains(http.statusCode) else {
+            throw ProblemError(status: http.statusCode, body: data)
+        }
+        return data
+    }
+}
+"#,
+    );
+    content
+}
+
+fn generate_websocket_client(ws: &WebSocket) -> String {
+    let mut content = String::new();
+    let data_type = ws
+        .message
+        .as_deref()
+        .map(|message_type| FieldType::from_str(message_type).to_swift())
+        .unwrap_or_else(|| "JSONValue".to_string());
+
+    content.push_str(&format!("/// A message from the `{}` websocket.\n", ws.name));
+    content.push_str(&codable_struct(
+        &format!("{}Message", ws.name),
+        &[
+            ("data".to_string(), data_type.clone()),
+            ("timestamp".to_string(), "Date?".to_string()),
+        ],
+    ));
+    content.push('\n');
+
+    content.push_str(&format!("/// Client for the `{}` websocket (`{}`).\n", ws.name, ws.path));
+    content.push_str(&format!(
+        "public final class {}Client: SocketClient<{}, {}Message> {{\n",
+        ws.name, data_type, ws.name
+    ));
+    match &ws.auth {
+        Some(auth) => {
+            content.push_str(&format!(
+                "    /// `token` is sent as `{}` and checked by the `{}` handler.\n",
+                auth.name, auth.handler
+            ));
+            content.push_str("    public init(url: URL, token: String, session: URLSession = .shared) {\n");
+            match auth.from {
+                TokenSource::Query => content.push_str(&format!(
+                    "        super.init(url: url, path: \"{}\", query: [\"{}\": token], session: session)\n",
+                    ws.path, auth.name
+                )),
+                TokenSource::Header => content.push_str(&format!(
+                    "        super.init(url: url, path: \"{}\", headers: [\"{}\": token], session: session)\n",
+                    ws.path, auth.name
+                )),
+            }
+        }
+        None => {
+            content.push_str("    public init(url: URL, session: URLSession = .shared) {\n");
+            content.push_str(&format!(
+                "        super.init(url: url, path: \"{}\", session: session)\n",
+                ws.path
+            ));
+        }
+    }
+    content.push_str("    }\n");
+    content.push_str("}\n");
+    content
+}
+
+/// `WebSockets.swift`: one typed client per websocket over a shared
+/// implementation of the wire protocol.
+fn generate_websockets(schema: &Schema) -> String {
+    let mut content = HEADER.to_string();
+    content.push_str(
+        r#"/// An event pushed by the server for a `forward_to:` declaration.
+public struct ForwardedEvent: Equatable {
+    public let event: String
+    public let data: JSONValue
+    public let timestamp: String
+}
+
+public enum SocketEvent<Message> {
+    case message(Message)
+    case event(ForwardedEvent)
+}
+
+private struct SocketEnvelope: Decodable {
+    struct Session: Decodable {
+        let connectionId: String
+        let reconnectToken: String
+
+        enum CodingKeys: String, CodingKey {
+            case connectionId = "connection_id"
+            case reconnectToken = "reconnect_token"
+        }
+    }
+
+    let session: Session?
+    let event: String?
+    let data: JSONValue?
+    let timestamp: String?
+
+    enum CodingKeys: String, CodingKey {
+        case session = "$session"
+        case event = "$event"
+        case data
+        case timestamp
+    }
+}
+
+/// Connection handling shared by the generated clients. Resumes the server
+/// session after a drop, so messages sent meanwhile are still delivered.
+open class SocketClient<Send: Encodable, Receive: Decodable> {
+    /// Reconnect after the connection drops.
+    public var reconnect = true
+    /// Delay before the first reconnection attempt, doubled on each retry.
+    public var minBackoff: TimeInterval = 0.5
+    public var maxBackoff: TimeInterval = 30
+    /// Set once the server has announced the session.
+    public private(set) var connectionId: String?
+
+    private let url: URL
+    private let path: String
+    private let query: [String: String]
+    private let headers: [String: String]
+    private let session: URLSession
+    private var task: URLSessionWebSocketTask?
+    private var reconnectToken: String?
+    private var closing = false
+
+    public init(
+        url: URL,
+        path: String,
+        query: [String: String] = [:],
+        headers: [String: String] = [:],
+        session: URLSession = .shared
+    ) {
+        self.url = url
+        self.path = path
+        self.query = query
+        self.headers = headers
+        self.session = session
+    }
+
+    /// Connects and yields what the server sends until `close()` is called
+    /// or the stream is cancelled.
+    public func events() -> AsyncThrowingStream<SocketEvent<Receive>, Error> {
+        AsyncThrowingStream { continuation in
+            let runner = Task {
+                closing = false
+                var attempts = 0
+                while !Task.isCancelled && !closing {
+                    do {
+                        let task = session.webSocketTask(with: try connectionRequest())
+                        self.task = task
+                        task.resume()
+                        while true {
+                            let message = try await task.receive()
+                            attempts = 0
+                            if let event = try handle(message) {
+                                continuation.yield(event)
+                            }
+                        }
+                    } catch {
+                        self.task = nil
+                        if closing {
+                            break
+                        }
+                        if !reconnect {
+                            continuation.finish(throwing: error)
+                            return
+                        }
+                    }
+                    // Exponential backoff with jitter, so clients dropped together spread out.
+                    let delay = min(maxBackoff, minBackoff * pow(2, Double(attempts))) * Double.random(in: 0.5...1)
+                    attempts += 1
+                    try? await Task.sleep(nanoseconds: UInt64(delay * 1_000_000_000))
+                }
+                continuation.finish()
+            }
+            continuation.onTermination = { _ in
+                runner.cancel()
+                self.close()
+            }
+        }
+    }
+
+    public func send(_ message: Send) async throws {
+        guard let task else {
+            throw URLError(.networkConnectionLost)
+        }
+        let data = try RohasCoding.encoder.encode(message)
+        try await task.send(.string(String(decoding: data, as: UTF8.self)))
+    }
+
+    public func close() {
+        closing = true
+        task?.cancel(with: .normalClosure, reason: nil)
+        task = nil
+    }
+
+    private func connectionRequest() throws -> URLRequest {
+        let base = url.absoluteString.hasSuffix("/") ? String(url.absoluteString.dropLast()) : url.absoluteString
+        guard var components = URLComponents(string: base + path) else {
+            throw URLError(.badURL)
+        }
+        var items = query.map { URLQueryItem(name: $0.key, value: $0.value) }
+        if let reconnectToken {
+            items.append(URLQueryItem(name: "reconnect_token", value: reconnectToken))
+        }
+        if !items.isEmpty {
+            components.queryItems = items
+        }
+        guard let url = components.url else {
+            throw URLError(.badURL)
+        }
+        var request = URLRequest(url: url)
+        for (name, value) in headers {
+            request.setValue(value, forHTTPHeaderField: name)
+        }
+        return request
+    }
+
+    private func handle(_ message: URLSessionWebSocketTask.Message) throws -> SocketEvent<Receive>? {
+        let data: Data
+        switch message {
+        case .string(let text): data = Data(text.utf8)
+        case .data(let bytes): data = bytes
+        @unknown default: return nil
+        }
+
+        let envelope = try RohasCoding.decoder.decode(SocketEnvelope.self, from: data)
+        if let session = envelope.session {
+            connectionId = session.connectionId
+            reconnectToken = session.reconnectToken
+            return nil
+        }
+        if let event = envelope.event {
+            return .event(ForwardedEvent(event: event, data: envelope.data ?? .null, timestamp: envelope.timestamp ?? ""))
+        }
+        return .message(try RohasCoding.decoder.decode(Receive.self, from: data))
+    }
+}
+"#,
+    );
+
+    for ws in &schema.websockets {
+        content.push('\n');
+        content.push_str(&generate_websocket_client(ws));
+    }
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rohas_parser::Parser;
+
+    const SCHEMA: &str = r#"
+model Order {
+  id        Int
+  default   String
+  in        String[]
+  note      String?
+  createdAt DateTime
+}
+
+input UpdateOrderInput {
+  note: String?
+}
+
+api UpdateOrder {
+  method: PATCH
+  path: "/orders/{id}"
+  body: UpdateOrderInput
+  response: Order
+}
+
+api GetOrder {
+  method: GET
+  path: "/orders/{class}"
+  response: Order
+}
+"#;
+
+    #[test]
+    fn test_models_are_codable_structs() {
+        let schema = Parser::parse_string(SCHEMA).unwrap();
+        let models = generate_models(&schema);
+
+        assert!(models.starts_with(HEADER));
+        assert!(models.contains(
+            "public struct Order: Codable, Equatable {\n    public var id: Int\n    public var `default`: String\n    public var `in`: [String]\n    public var note: String?\n    public var createdAt: Date\n"
+        ));
+        assert!(models.contains(
+            "    public init(id: Int, `default`: String, `in`: [String], note: String? = nil, createdAt: Date) {\n        self.id = id\n        self.`default` = `default`\n        self.`in` = `in`\n        self.note = note\n        self.createdAt = createdAt\n    }\n"
+        ));
+    }
+
+    #[test]
+    fn test_client_has_a_method_per_api() {
+        let schema = Parser::parse_string(SCHEMA).unwrap();
+        let client = generate_client(&schema);
+
+        assert!(client.contains(
+            "    /// `PATCH /orders/{id}`\n    public func updateOrder(id: String, body: UpdateOrderInput, query: [String: String] = [:]) async throws -> Order {\n        let data = try await sendRaw(\"PATCH\", \"/orders/\\(Self.escape(id))\", query: query, body: try RohasCoding.encoder.encode(body), contentType: \"application/json\")\n        return try RohasCoding.decoder.decode(ApiResponse<Order>.self, from: data).data\n    }\n"
+        ));
+        assert!(client.contains("public final class RohasClient {\n"));
+    }
+
+    #[test]
+    fn test_keywords_are_escaped() {
+        let schema = Parser::parse_string(SCHEMA).unwrap();
+        let client = generate_client(&schema);
+
+        assert!(client.contains(
+            "    public func getOrder(`class`: String, query: [String: String] = [:]) async throws -> Order {\n        let data = try await sendRaw(\"GET\", \"/orders/\\(Self.escape(`class`))\", query: query)\n"
+        ));
+        assert_eq!(escape_keyword("Self"), "`Self`");
+        assert_eq!(escape_keyword("object"), "object");
+    }
+}
@@ -1,6 +1,9 @@
 use crate::error::Result;
-use crate::templates;
-use rohas_parser::{Api, Event, FieldType, Model, Schema, Type, WebSocket};
+use crate::example::Example;
+use crate::factory::FakeValue;
+use crate::layout::Layout;
+use crate::{config, templates};
+use rohas_parser::{Api, Event, Field, FieldType, Model, Schema, TokenSource, Type, WebSocket};
 use std::fs;
 use std::path::Path;
 
@@ -16,6 +19,83 @@ pub fn generate_models(schema: &Schema, output_dir: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Generate `generated/factories.ts`, with a builder per model that fills
+/// every field with fake data.
+pub fn generate_factories(schema: &Schema, output_dir: &Path) -> Result<()> {
+    let mut content = String::new();
+
+    for model in &schema.models {
+        content.push_str(&format!(
+            "import {{ {} }} from './models/{}';\n",
+            model.name,
+            templates::to_snake_case(&model.name)
+        ));
+    }
+
+    content.push_str(
+        r#"
+let sequence = 0;
+
+function nextSequence(): number {
+  sequence += 1;
+  return sequence;
+}
+
+/** Restart the sequence used for ids and unique fields. */
+export function resetFactorySequence(): void {
+  sequence = 0;
+}
+"#,
+    );
+
+    for model in &schema.models {
+        let values: Vec<(&Field, FakeValue)> = model
+            .fields
+            .iter()
+            .map(|f| (f, FakeValue::for_field(model, f, schema)))
+            .collect();
+
+        content.push_str(&format!(
+            "\n/** Builds `{}` values with fake data. */\n",
+            model.name
+        ));
+        content.push_str(&format!("export class {}Factory {{\n", model.name));
+        content.push_str(&format!("  private overrides: Partial<{}> = {{}};\n", model.name));
+
+        for field in &model.fields {
+            content.push_str(&format!(
+                "\n  with{}(value: {}['{}']): this {{\n",
+                templates::to_pascal_case(&field.name),
+                model.name,
+                field.name
+            ));
+            content.push_str(&format!("    this.overrides.{} = value;\n", field.name));
+            content.push_str("    return this;\n");
+            content.push_str("  }\n");
+        }
+
+        content.push_str(&format!("\n  build(): {} {{\n", model.name));
+        if values.iter().any(|(_, v)| v.uses_sequence()) {
+            content.push_str("    const n = nextSequence();\n");
+        }
+        content.push_str("    return {\n");
+        for (field, value) in &values {
+            content.push_str(&format!("      {}: {},\n", field.name, value.to_typescript()));
+        }
+        content.push_str("      ...this.overrides,\n");
+        content.push_str("    };\n");
+        content.push_str("  }\n");
+
+        content.push_str(&format!("\n  buildList(count: number): {}[] {{\n", model.name));
+        content.push_str("    return Array.from({ length: count }, () => this.build());\n");
+        content.push_str("  }\n");
+        content.push_str("}\n");
+    }
+
+    fs::write(output_dir.join("generated/factories.ts"), content)?;
+    Ok(())
+}
+
 fn generate_model_content(model: &Model) -> String {
     let mut content = String::new();
 
@@ -26,7 +106,8 @@ fn generate_model_content(model: &Model) -> String {
     for field in &model.fields {
         let ts_type = field.field_type.to_typescript();
         let optional = if field.optional { "?" } else { "" };
-        content.push_str(&format!("  {}{}: {};\n", field.name, optional, ts_type));
+        let readonly = if field.computed().is_some() { "readonly " } else { "" };
+        content.push_str(&format!("  {}{}{}: {};\n", readonly, field.name, optional, ts_type));
     }
 
     content.push_str("}\n\n");
@@ -64,6 +145,7 @@ fn field_type_to_zod(field_type: &rohas_parser::FieldType, optional: bool) -> St
         FieldType::Boolean => "z.boolean()".to_string(),
         FieldType::DateTime => "z.date()".to_string(),
         FieldType::Json => "z.any()".to_string(),
+        FieldType::Bytes => "z.instanceof(Uint8Array)".to_string(),
         FieldType::Custom(name) => format!("{}Schema", name),
         FieldType::Array(inner) => {
             let inner_zod = field_type_to_zod(inner, false);
@@ -86,6 +168,8 @@ pub fn generate_dtos(schema: &Schema, output_dir: &Path) -> Result<()> {
             name: input.name.clone(),
             fields: input.fields.clone(),
             attributes: vec![],
+            example: input.example.clone(),
+            doc: input.doc.clone(),
         });
         let file_name = format!("{}.ts", templates::to_snake_case(&input.name));
         fs::write(dto_dir.join(file_name), content)?;
@@ -96,6 +180,8 @@ pub fn generate_dtos(schema: &Schema, output_dir: &Path) -> Result<()> {
             name: type_def.name.clone(),
             fields: type_def.fields.clone(),
             attributes: vec![],
+            example: type_def.example.clone(),
+            doc: type_def.doc.clone(),
         });
         let file_name = format!("{}.ts", templates::to_snake_case(&type_def.name));
         fs::write(dto_dir.join(file_name), content)?;
@@ -104,7 +190,7 @@ pub fn generate_dtos(schema: &Schema, output_dir: &Path) -> Result<()> {
     Ok(())
 }
 
-pub fn generate_apis(schema: &Schema, output_dir: &Path) -> Result<()> {
+pub fn generate_apis(schema: &Schema, output_dir: &Path, layout: &Layout) -> Result<()> {
     let api_dir = output_dir.join("generated/api");
 
     for api in &schema.apis {
@@ -113,14 +199,19 @@ pub fn generate_apis(schema: &Schema, output_dir: &Path) -> Result<()> {
         fs::write(api_dir.join(file_name), content)?;
     }
 
-    let handlers_dir = output_dir.join("handlers/api");
+    if !layout.handler_stubs {
+        return Ok(());
+    }
+
+    let handlers_dir = layout.handlers(output_dir, "api");
     for api in &schema.apis {
-        let file_name = format!("{}.ts", &api.name);
+        let file_name = format!("{}.ts", layout.file_stem(&api.name));
         let handler_path = handlers_dir.join(&file_name);
 
         if !handler_path.exists() {
             let content = generate_api_handler_stub(api);
             fs::write(handler_path, content)?;
+            generate_api_test(api, schema, output_dir)?;
         }
     }
 
@@ -135,31 +226,36 @@ fn generate_api_content(api: &Api, schema: &Schema) -> String {
     let request_type = format!("{}Request", api.name);
     let response_type = format!("{}Response", api.name);
     let handler_type = format!("{}Handler", api.name);
+    let body = api.body_type();
+    let response = api.response_type();
 
-    let response_is_primitive = is_primitive_type(&api.response);
+    let response_is_primitive = is_primitive_type(response);
 
+    if api.paginated {
+        content.push_str("import { Page, pageSchema } from '@generated/page';\n");
+    }
     if !response_is_primitive {
-        let is_type = schema.types.iter().any(|t| t.name == api.response);
-        let is_input = schema.inputs.iter().any(|i| i.name == api.response);
+        let is_type = schema.types.iter().any(|t| t.name == response);
+        let is_input = schema.inputs.iter().any(|i| i.name == response);
         
         if is_type || is_input {
             content.push_str(&format!(
                 "import {{ {}, {}Schema }} from '@generated/dto/{}';\n",
-                api.response,
-                api.response,
-                templates::to_snake_case(&api.response)
+                response,
+                response,
+                templates::to_snake_case(response)
             ));
         } else {
             content.push_str(&format!(
                 "import {{ {}, {}Schema }} from '@generated/models/{}';\n",
-                api.response,
-                api.response,
-                templates::to_snake_case(&api.response)
+                response,
+                response,
+                templates::to_snake_case(response)
             ));
         }
     }
 
-    if let Some(body) = &api.body {
+    if let Some(body) = &body {
         let body_is_primitive = is_primitive_type(body);
         if !body_is_primitive {
             if body.ends_with("Input") {
@@ -192,7 +288,7 @@ fn generate_api_content(api: &Api, schema: &Schema) -> String {
         content.push_str(&format!("  {}: string;\n", param));
     }
 
-    if let Some(body) = &api.body {
+    if let Some(body) = &body {
         let ts_type = if is_primitive_type(body) {
             primitive_to_typescript(body)
         } else {
@@ -202,6 +298,7 @@ fn generate_api_content(api: &Api, schema: &Schema) -> String {
     }
 
     content.push_str("  queryParams?: Record<string, string>;\n");
+    content.push_str("  cookies?: Record<string, string>;\n");
 
     content.push_str("}\n\n");
 
@@ -213,7 +310,7 @@ fn generate_api_content(api: &Api, schema: &Schema) -> String {
     for param in &path_params {
         content.push_str(&format!("  {}: z.string(),\n", param));
     }
-    if let Some(body) = &api.body {
+    if let Some(body) = &body {
         let body_is_primitive = is_primitive_type(body);
         if body_is_primitive {
             let zod_type = match body.as_str() {
@@ -221,6 +318,7 @@ fn generate_api_content(api: &Api, schema: &Schema) -> String {
                 "Int" | "Float" => "z.number()",
                 "Boolean" => "z.boolean()",
                 "DateTime" | "Date" => "z.date()",
+                "Bytes" => "z.instanceof(Uint8Array)",
                 _ => "z.any()",
             };
             content.push_str(&format!("  body: {},\n", zod_type));
@@ -233,13 +331,17 @@ fn generate_api_content(api: &Api, schema: &Schema) -> String {
         }
     }
     content.push_str("  queryParams: z.record(z.string()).optional(),\n");
+    content.push_str("  cookies: z.record(z.string()).optional(),\n");
     content.push_str("});\n\n");
 
-    let response_ts_type = if response_is_primitive {
-        primitive_to_typescript(&api.response)
+    let mut response_ts_type = if response_is_primitive {
+        primitive_to_typescript(response)
     } else {
-        api.response.clone()
+        response.to_string()
     };
+    if api.paginated {
+        response_ts_type = format!("Page<{}>", response_ts_type);
+    }
 
     content.push_str(&format!("export interface {} {{\n", response_type));
     content.push_str(&format!("  data: {};\n", response_ts_type));
@@ -247,15 +349,21 @@ fn generate_api_content(api: &Api, schema: &Schema) -> String {
 
     // Generate zod schema for response
     let response_zod_type = if response_is_primitive {
-        match api.response.as_str() {
+        match response {
             "String" => "z.string()".to_string(),
             "Int" | "Float" => "z.number()".to_string(),
             "Boolean" => "z.boolean()".to_string(),
             "DateTime" | "Date" => "z.date()".to_string(),
+            "Bytes" => "z.instanceof(Uint8Array)".to_string(),
             _ => "z.any()".to_string(),
         }
     } else {
-        format!("{}Schema", api.response)
+        format!("{}Schema", response)
+    };
+    let response_zod_type = if api.paginated {
+        format!("pageSchema({})", response_zod_type)
+    } else {
+        response_zod_type
     };
     content.push_str(&format!(
         "export const {}Schema = z.object({{\n",
@@ -275,7 +383,7 @@ fn generate_api_content(api: &Api, schema: &Schema) -> String {
 fn is_primitive_type(type_name: &str) -> bool {
     matches!(
         type_name,
-        "String" | "Int" | "Float" | "Boolean" | "DateTime" | "Date"
+        "String" | "Int" | "Float" | "Boolean" | "DateTime" | "Date" | "Bytes"
     )
 }
 
@@ -285,6 +393,7 @@ fn primitive_to_typescript(type_name: &str) -> String {
         "Int" | "Float" => "number".to_string(),
         "Boolean" => "boolean".to_string(),
         "DateTime" | "Date" => "Date".to_string(),
+        "Bytes" => "Uint8Array".to_string(),
         _ => type_name.to_string(),
     }
 }
@@ -347,7 +456,7 @@ fn generate_api_handler_stub(api: &Api) -> String {
     content
 }
 
-pub fn generate_events(schema: &Schema, output_dir: &Path) -> Result<()> {
+pub fn generate_events(schema: &Schema, output_dir: &Path, layout: &Layout) -> Result<()> {
     let events_dir = output_dir.join("generated/events");
 
     for event in &schema.events {
@@ -356,18 +465,28 @@ pub fn generate_events(schema: &Schema, output_dir: &Path) -> Result<()> {
         fs::write(events_dir.join(file_name), content)?;
     }
 
+    if !layout.handler_stubs {
+        return Ok(());
+    }
+
     // Generate handler stubs
-    let handlers_dir = output_dir.join("handlers/events");
+    let handlers_dir = layout.handlers(output_dir, "events");
     for event in &schema.events {
+        let mut created_stub = false;
         for handler in &event.handlers {
-            let file_name = format!("{}.ts", handler);
+            let file_name = format!("{}.ts", layout.file_stem(handler));
             let handler_path = handlers_dir.join(&file_name);
 
             if !handler_path.exists() {
                 let content = generate_event_handler_stub(event, handler);
                 fs::write(handler_path, content)?;
+                created_stub = true;
             }
         }
+
+        if created_stub {
+            generate_event_test(event, schema, output_dir)?;
+        }
     }
 
     Ok(())
@@ -450,7 +569,7 @@ fn generate_event_handler_stub(event: &Event, handler_name: &str) -> String {
     content
 }
 
-pub fn generate_crons(schema: &Schema, output_dir: &Path) -> Result<()> {
+pub fn generate_crons(schema: &Schema, output_dir: &Path, layout: &Layout) -> Result<()> {
     let cron_dir = output_dir.join("generated/cron");
 
     for cron in &schema.crons {
@@ -462,8 +581,12 @@ pub fn generate_crons(schema: &Schema, output_dir: &Path) -> Result<()> {
         fs::write(cron_dir.join(file_name), content)?;
     }
 
+    if !layout.handler_stubs {
+        return Ok(());
+    }
+
     // Generate handler stubs
-    let handlers_dir = output_dir.join("handlers/cron");
+    let handlers_dir = layout.handlers(output_dir, "cron");
     for cron in &schema.crons {
         let file_name = format!("{}.ts", templates::to_snake_case(&cron.name));
         let handler_path = handlers_dir.join(&file_name);
@@ -480,7 +603,7 @@ pub fn generate_crons(schema: &Schema, output_dir: &Path) -> Result<()> {
     Ok(())
 }
 
-pub fn generate_websockets(schema: &Schema, output_dir: &Path) -> Result<()> {
+pub fn generate_websockets(schema: &Schema, output_dir: &Path, layout: &Layout) -> Result<()> {
     let ws_dir = output_dir.join("generated/websockets");
 
     for ws in &schema.websockets {
@@ -488,12 +611,19 @@ pub fn generate_websockets(schema: &Schema, output_dir: &Path) -> Result<()> {
         let file_name = format!("{}.ts", templates::to_snake_case(&ws.name));
         fs::write(ws_dir.join(file_name), content)?;
     }
+    if !schema.websockets.is_empty() {
+        fs::write(ws_dir.join("client.ts"), generate_websocket_clients(schema))?;
+    }
+
+    if !layout.handler_stubs {
+        return Ok(());
+    }
 
-    let handlers_dir = output_dir.join("handlers/websockets");
+    let handlers_dir = layout.handlers(output_dir, "websockets");
     for ws in &schema.websockets {
         if !ws.on_connect.is_empty() {
             for handler in &ws.on_connect {
-                let file_name = format!("{}.ts", handler);
+                let file_name = format!("{}.ts", layout.file_stem(handler));
                 let handler_path = handlers_dir.join(&file_name);
                 if !handler_path.exists() {
                     let content = generate_websocket_handler_stub(ws, "onConnect", handler);
@@ -503,7 +633,7 @@ pub fn generate_websockets(schema: &Schema, output_dir: &Path) -> Result<()> {
         }
         if !ws.on_message.is_empty() {
             for handler in &ws.on_message {
-                let file_name = format!("{}.ts", handler);
+                let file_name = format!("{}.ts", layout.file_stem(handler));
                 let handler_path = handlers_dir.join(&file_name);
                 if !handler_path.exists() {
                     let content = generate_websocket_handler_stub(ws, "onMessage", handler);
@@ -513,7 +643,7 @@ pub fn generate_websockets(schema: &Schema, output_dir: &Path) -> Result<()> {
         }
         if !ws.on_disconnect.is_empty() {
             for handler in &ws.on_disconnect {
-                let file_name = format!("{}.ts", handler);
+                let file_name = format!("{}.ts", layout.file_stem(handler));
                 let handler_path = handlers_dir.join(&file_name);
                 if !handler_path.exists() {
                     let content = generate_websocket_handler_stub(ws, "onDisconnect", handler);
@@ -526,8 +656,12 @@ pub fn generate_websockets(schema: &Schema, output_dir: &Path) -> Result<()> {
     Ok(())
 }
 
-pub fn generate_middlewares(schema: &Schema, output_dir: &Path) -> Result<()> {
+pub fn generate_middlewares(schema: &Schema, output_dir: &Path, layout: &Layout) -> Result<()> {
     use std::collections::HashSet;
+
+    if !layout.handler_stubs {
+        return Ok(());
+    }
     
     let mut middleware_names = HashSet::new();
     
@@ -538,7 +672,7 @@ pub fn generate_middlewares(schema: &Schema, output_dir: &Path) -> Result<()> {
     }
     
     for ws in &schema.websockets {
-        for middleware in &ws.middlewares {
+        for middleware in ws.middleware_handlers() {
             middleware_names.insert(middleware.clone());
         }
     }
@@ -547,9 +681,9 @@ pub fn generate_middlewares(schema: &Schema, output_dir: &Path) -> Result<()> {
         return Ok(());
     }
     
-    let middlewares_dir = output_dir.join("middlewares");
+    let middlewares_dir = layout.middlewares(output_dir);
     for middleware_name in middleware_names {
-        let file_name = format!("{}.ts", middleware_name);
+        let file_name = format!("{}.ts", layout.file_stem(&middleware_name));
         let middleware_path = middlewares_dir.join(&file_name);
         
         if !middleware_path.exists() {
@@ -571,6 +705,7 @@ fn generate_middleware_stub(middleware_name: &str) -> String {
     content.push_str("  query_params?: Record<string, string>;\n");
     content.push_str("  connection?: any;\n");
     content.push_str("  websocket_name?: string;\n");
+    content.push_str("  token?: string;\n");
     content.push_str("  api_name?: string;\n");
     content.push_str("  trace_id?: string;\n");
     content.push_str("}\n\n");
@@ -605,6 +740,9 @@ fn generate_middleware_stub(middleware_name: &str) -> String {
     content.push_str("   *     payload: modifiedPayload,\n");
     content.push_str("   *     query_params: modifiedQueryParams\n");
     content.push_str("   *   };\n");
+    content.push_str("   * \n");
+    content.push_str("   * As a websocket `auth` handler, validate `context.token` and\n");
+    content.push_str("   * return `{ principal }` to accept the connection.\n");
     content.push_str("   */\n");
     content.push_str("  // TODO: Implement middleware logic\n");
     content.push_str("  // Example: Validate authentication\n");
@@ -680,6 +818,8 @@ fn generate_websocket_content(ws: &WebSocket) -> String {
     content.push_str("  connectionId: string;\n");
     content.push_str("  path: string;\n");
     content.push_str("  connectedAt: Date;\n");
+    content.push_str("  ipAddress?: string;\n");
+    content.push_str("  principal?: any;\n");
     content.push_str("}\n\n");
 
     content.push_str(&format!(
@@ -689,11 +829,239 @@ fn generate_websocket_content(ws: &WebSocket) -> String {
     content.push_str("  connectionId: z.string(),\n");
     content.push_str("  path: z.string(),\n");
     content.push_str("  connectedAt: z.date(),\n");
+    content.push_str("  ipAddress: z.string().optional(),\n");
+    content.push_str("  principal: z.any().optional(),\n");
     content.push_str("});\n");
 
     content
 }
 
+/// Generate `generated/websockets/client.ts`: one typed client class per
+/// websocket over a shared implementation of the wire protocol.
+fn generate_websocket_clients(schema: &Schema) -> String {
+    let mut content = String::new();
+
+    content.push_str("// Generated by Rohas - Do not edit\n");
+    let mut imported = std::collections::HashSet::new();
+    for ws in &schema.websockets {
+        content.push_str(&format!(
+            "import {{ {}Message }} from './{}';\n",
+            ws.name,
+            templates::to_snake_case(&ws.name)
+        ));
+        if let Some(message_type) = &ws.message {
+            if matches!(FieldType::from_str(message_type), FieldType::Custom(_)) && imported.insert(message_type) {
+                content.push_str(&format!(
+                    "import {{ {} }} from '@generated/dto/{}';\n",
+                    message_type,
+                    templates::to_snake_case(message_type)
+                ));
+            }
+        }
+    }
+
+    content.push_str(
+        r#"
+export interface SocketClientOptions {
+  /** Server origin, e.g. `ws://localhost:3000`. */
+  url: string;
+  /** Reconnect after the connection drops. Defaults to true. */
+  reconnect?: boolean;
+  /** Delay before the first reconnection attempt, doubled on each retry. */
+  minBackoffMs?: number;
+  maxBackoffMs?: number;
+  /** WebSocket implementation, for runtimes without a global one. */
+  WebSocket?: typeof WebSocket;
+}
+
+type Listener<T> = (value: T) => void;
+
+/** An event pushed by the server for a `forward_to:` declaration. */
+export interface ForwardedEvent<T = unknown> {
+  event: string;
+  data: T;
+  timestamp: string;
+}
+
+/**
+ * Connection handling shared by the generated clients. Resumes the server
+ * session after a drop, so messages sent meanwhile are still delivered.
+ */
+export class SocketClient<Send, Receive> {
+  /** Set once the server has announced the session. */
+  connectionId: string | null = null;
+  private socket: WebSocket | null = null;
+  private reconnectToken: string | null = null;
+  private attempts = 0;
+  private closing = false;
+  private timer: ReturnType<typeof setTimeout> | null = null;
+  private listeners = {
+    open: [] as Listener<void>[],
+    message: [] as Listener<Receive>[],
+    event: [] as Listener<ForwardedEvent>[],
+    close: [] as Listener<CloseEvent>[],
+    error: [] as Listener<Event>[],
+  };
+
+  constructor(
+    private readonly path: string,
+    private readonly options: SocketClientOptions,
+    private readonly query: Record<string, string> = {},
+  ) {}
+
+  connect(): void {
+    this.closing = false;
+    const params = new URLSearchParams(this.query);
+    if (this.reconnectToken) {
+      params.set('reconnect_token', this.reconnectToken);
+    }
+    const search = params.toString();
+    const url = `${this.options.url.replace(/\/$/, '')}${this.path}${search ? `?${search}` : ''}`;
+    const Impl = this.options.WebSocket ?? WebSocket;
+    const socket = new Impl(url);
+    this.socket = socket;
+
+    socket.onopen = () => {
+      this.attempts = 0;
+      this.emit(this.listeners.open, undefined);
+    };
+    socket.onmessage = (event) => {
+      const message = JSON.parse(String(event.data));
+      if (message && typeof message === 'object' && '$session' in message) {
+        this.connectionId = message.$session.connection_id;
+        this.reconnectToken = message.$session.reconnect_token;
+        return;
+      }
+      if (message && typeof message === 'object' && '$event' in message) {
+        const { $event, data, timestamp } = message;
+        this.emit(this.listeners.event, { event: $event, data, timestamp });
+        return;
+      }
+      this.emit(this.listeners.message, message as Receive);
+    };
+    socket.onerror = (event) => this.emit(this.listeners.error, event);
+    socket.onclose = (event) => {
+      this.socket = null;
+      this.emit(this.listeners.close, event);
+      if (!this.closing && (this.options.reconnect ?? true)) {
+        this.scheduleReconnect();
+      }
+    };
+  }
+
+  send(message: Send): void {
+    if (!this.socket || this.socket.readyState !== 1) {
+      throw new Error('WebSocket is not open');
+    }
+    this.socket.send(JSON.stringify(message));
+  }
+
+  close(): void {
+    this.closing = true;
+    if (this.timer) {
+      clearTimeout(this.timer);
+      this.timer = null;
+    }
+    this.socket?.close();
+  }
+
+  /** Each `on*` method returns a function that removes the listener. */
+  onOpen(listener: Listener<void>): () => void {
+    return this.listen(this.listeners.open, listener);
+  }
+
+  onMessage(listener: Listener<Receive>): () => void {
+    return this.listen(this.listeners.message, listener);
+  }
+
+  /** Events the server forwards to this websocket. */
+  onEvent(listener: Listener<ForwardedEvent>): () => void {
+    return this.listen(this.listeners.event, listener);
+  }
+
+  onClose(listener: Listener<CloseEvent>): () => void {
+    return this.listen(this.listeners.close, listener);
+  }
+
+  onError(listener: Listener<Event>): () => void {
+    return this.listen(this.listeners.error, listener);
+  }
+
+  private listen<T>(listeners: Listener<T>[], listener: Listener<T>): () => void {
+    listeners.push(listener);
+    return () => {
+      const index = listeners.indexOf(listener);
+      if (index >= 0) {
+        listeners.splice(index, 1);
+      }
+    };
+  }
+
+  private emit<T>(listeners: Listener<T>[], value: T): void {
+    for (const listener of [...listeners]) {
+      listener(value);
+    }
+  }
+
+  private scheduleReconnect(): void {
+    const min = this.options.minBackoffMs ?? 500;
+    const max = this.options.maxBackoffMs ?? 30000;
+    // Exponential backoff with jitter, so clients dropped together spread out.
+    const delay = Math.min(max, min * 2 ** this.attempts) * (0.5 + Math.random() / 2);
+    this.attempts += 1;
+    this.timer = setTimeout(() => this.connect(), delay);
+  }
+}
+"#,
+    );
+
+    for ws in &schema.websockets {
+        let send_type = ws
+            .message
+            .as_deref()
+            .map(|message_type| FieldType::from_str(message_type).to_typescript())
+            .unwrap_or_else(|| "any".to_string());
+        let token = ws.auth.as_ref().filter(|auth| auth.from == TokenSource::Query);
+
+        content.push('\n');
+        if let Some(auth) = token {
+            content.push_str(&format!(
+                "export interface {}ClientOptions extends SocketClientOptions {{\n",
+                ws.name
+            ));
+            content.push_str(&format!(
+                "  /** Sent as `{}` and checked by the `{}` handler. */\n",
+                auth.name, auth.handler
+            ));
+            content.push_str("  token: string;\n");
+            content.push_str("}\n\n");
+        } else {
+            content.push_str(&format!(
+                "export type {}ClientOptions = SocketClientOptions;\n\n",
+                ws.name
+            ));
+        }
+
+        content.push_str(&format!("/** Client for the `{}` websocket (`{}`). */\n", ws.name, ws.path));
+        content.push_str(&format!(
+            "export class {}Client extends SocketClient<{}, {}Message> {{\n",
+            ws.name, send_type, ws.name
+        ));
+        content.push_str(&format!("  constructor(options: {}ClientOptions) {{\n", ws.name));
+        match token {
+            Some(auth) => content.push_str(&format!(
+                "    super('{}', options, {{ '{}': options.token }});\n",
+                ws.path, auth.name
+            )),
+            None => content.push_str(&format!("    super('{}', options);\n", ws.path)),
+        }
+        content.push_str("  }\n");
+        content.push_str("}\n");
+    }
+
+    content
+}
+
 fn generate_websocket_handler_stub(
     ws: &WebSocket,
     handler_type: &str,
@@ -765,176 +1133,124 @@ fn generate_websocket_handler_stub(
 
 pub fn generate_state(output_dir: &Path) -> Result<()> {
     let generated_dir = output_dir.join("generated");
-    let content = r#"export interface TriggeredEvent {
-  eventName: string;
-  payload: any;
+    let content = r#"// Generated by Rohas - Do not edit
+// The handler API lives in the @rohas/sdk package.
+
+export * from '@rohas/sdk';
+"#;
+
+    fs::write(generated_dir.join("state.ts"), content)?;
+    Ok(())
 }
 
-/**
- * Logger for handlers to emit structured logs.
- */
-export class Logger {
-  private handlerName: string;
-  private logFn?: (level: string, handler: string, message: string, fields: Record<string, any>) => void;
+/// Generate `generated/errors.ts`: the engine's `application/problem+json`
+/// error responses as types for API clients.
+pub fn generate_errors(output_dir: &Path) -> Result<()> {
+    let content = r#"// Generated by Rohas - Do not edit
 
-  constructor(handlerName: string, logFn?: (level: string, handler: string, message: string, fields: Record<string, any>) => void) {
-    this.handlerName = handlerName;
-    this.logFn = logFn;
-  }
+export const PROBLEM_CONTENT_TYPE = 'application/problem+json';
 
-  /**
-   * Log an info message.
-   * 
-   * @param message - Log message
-   * @param fields - Additional fields to include in the log
-   */
-  info(message: string, fields?: Record<string, any>): void {
-    if (this.logFn) {
-      this.logFn("info", this.handlerName, message, fields || {});
-    } else {
-      console.log(`[${this.handlerName}] ${message}`, fields || {});
-    }
-  }
+/** A request body field that failed validation. */
+export interface FieldError {
+  field: string;
+  message: string;
+}
 
-  /**
-   * Log an error message.
-   * 
-   * @param message - Log message
-   * @param fields - Additional fields to include in the log
-   */
-  error(message: string, fields?: Record<string, any>): void {
-    if (this.logFn) {
-      this.logFn("error", this.handlerName, message, fields || {});
-    } else {
-      console.error(`[${this.handlerName}] ${message}`, fields || {});
-    }
-  }
+/** An API error response (RFC 7807). */
+export interface ProblemDetails {
+  type: string;
+  title: string;
+  status: number;
+  detail?: string;
+  instance?: string;
+  /** Present on validation failures (status 422). */
+  errors?: FieldError[];
+}
 
-  /**
-   * Log a warning message.
-   * 
-   * @param message - Log message
-   * @param fields - Additional fields to include in the log
-   */
-  warning(message: string, fields?: Record<string, any>): void {
-    if (this.logFn) {
-      this.logFn("warn", this.handlerName, message, fields || {});
-    } else {
-      console.warn(`[${this.handlerName}] ${message}`, fields || {});
-    }
-  }
+export function isProblemDetails(value: unknown): value is ProblemDetails {
+  return (
+    typeof value === 'object' &&
+    value !== null &&
+    typeof (value as ProblemDetails).title === 'string' &&
+    typeof (value as ProblemDetails).status === 'number'
+  );
+}
 
-  /**
-   * Log a warning message (alias for warning).
-   * 
-   * @param message - Log message
-   * @param fields - Additional fields to include in the log
-   */
-  warn(message: string, fields?: Record<string, any>): void {
-    this.warning(message, fields);
-  }
+/** Thrown for error responses; carries the parsed problem document. */
+export class ProblemError extends Error {
+  readonly problem: ProblemDetails;
 
-  /**
-   * Log a debug message.
-   * 
-   * @param message - Log message
-   * @param fields - Additional fields to include in the log
-   */
-  debug(message: string, fields?: Record<string, any>): void {
-    if (this.logFn) {
-      this.logFn("debug", this.handlerName, message, fields || {});
-    } else {
-      console.debug(`[${this.handlerName}] ${message}`, fields || {});
-    }
+  constructor(problem: ProblemDetails) {
+    super(problem.detail ?? problem.title);
+    this.name = 'ProblemError';
+    this.problem = problem;
   }
 
-  /**
-   * Log a trace message.
-   * 
-   * @param message - Log message
-   * @param fields - Additional fields to include in the log
-   */
-  trace(message: string, fields?: Record<string, any>): void {
-    if (this.logFn) {
-      this.logFn("trace", this.handlerName, message, fields || {});
-    } else {
-      console.trace(`[${this.handlerName}] ${message}`, fields || {});
-    }
+  get status(): number {
+    return this.problem.status;
   }
-}
 
-/**
- * Context object for handlers to trigger events and access runtime state.
- */
-export class State {
-  private triggers: TriggeredEvent[] = [];
-  private autoTriggerPayloads: Map<string, any> = new Map();
-  public logger: Logger;
-
-  constructor(handlerName?: string, logFn?: (level: string, handler: string, message: string, fields: Record<string, any>) => void) {
-    this.logger = new Logger(handlerName || "unknown", logFn);
+  get fieldErrors(): FieldError[] {
+    return this.problem.errors ?? [];
   }
 
   /**
-   * Manually trigger an event with the given payload.
-   * 
-   * Use this for events that are NOT defined in the schema's triggers list.
-   * 
-   * @param eventName - Name of the event to trigger
-   * @param payload - Event payload data (will be serialized to JSON)
+   * Reads an error response, accepting the legacy `{ error }` body as well.
    */
-  triggerEvent(eventName: string, payload: any): void {
-    this.triggers.push({
-      eventName,
-      payload,
+  static async fromResponse(response: Response): Promise<ProblemError> {
+    const body = await response.json().catch(() => ({}));
+    if (isProblemDetails(body)) {
+      return new ProblemError(body);
+    }
+    return new ProblemError({
+      type: 'about:blank',
+      title: response.statusText,
+      status: response.status,
+      detail: typeof body?.error === 'string' ? body.error : undefined,
+      errors: Array.isArray(body?.errors) ? body.errors : undefined,
     });
   }
+}
+"#;
 
-  /**
-   * Set the payload for an auto-triggered event.
-   * 
-   * Use this for events that ARE defined in the schema's triggers list.
-   * The event will be automatically triggered after the handler completes,
-   * using the payload you set here.
-   * 
-   * @param eventName - Name of the event (must match a trigger in schema)
-   * @param payload - Event payload data (will be serialized to JSON)
-   */
-  setPayload(eventName: string, payload: any): void {
-    this.autoTriggerPayloads.set(eventName, payload);
-  }
+    fs::write(output_dir.join("generated/errors.ts"), content)?;
+    Ok(())
+}
 
-  /**
-   * Get all manually triggered events. Used internally by the runtime.
-   */
-  getTriggers(): TriggeredEvent[] {
-    return [...this.triggers];
-  }
+/// Generate `generated/page.ts`: the envelope of `response: Page<T>` APIs.
+pub fn generate_page(output_dir: &Path) -> Result<()> {
+    let content = r#"// Generated by Rohas - Do not edit
 
-  /**
-   * Get payload for an auto-triggered event. Used internally by the runtime.
-   */
-  getAutoTriggerPayload(eventName: string): any | undefined {
-    return this.autoTriggerPayloads.get(eventName);
-  }
+import { z } from 'zod';
 
-  /**
-   * Get all auto-trigger payloads. Used internally by the runtime.
-   */
-  getAllAutoTriggerPayloads(): Map<string, any> {
-    return new Map(this.autoTriggerPayloads);
-  }
+/** A page of a paginated list API. */
+export interface Page<T> {
+  items: T[];
+  /** Number of items across all pages. */
+  total: number;
+  /** Passed back as `?cursor=` for the next page; null on the last page. */
+  next_cursor: string | null;
+}
+
+export function pageSchema<T extends z.ZodTypeAny>(item: T) {
+  return z.object({
+    items: z.array(item),
+    total: z.number(),
+    next_cursor: z.string().nullable(),
+  });
 }
 "#;
 
-    fs::write(generated_dir.join("state.ts"), content)?;
+    fs::write(output_dir.join("generated/page.ts"), content)?;
     Ok(())
 }
 
 pub fn generate_index(schema: &Schema, output_dir: &Path) -> Result<()> {
     let mut content = String::new();
 
-    content.push_str("export * from './state';\n\n");
+    content.push_str("export * from './state';\n");
+    content.push_str("export * from './errors';\n");
+    content.push_str("export * from './page';\n\n");
 
     content.push_str("// Models\n");
     for model in &schema.models {
@@ -975,8 +1291,314 @@ pub fn generate_index(schema: &Schema, output_dir: &Path) -> Result<()> {
             templates::to_snake_case(&ws.name)
         ));
     }
+    if !schema.websockets.is_empty() {
+        content.push_str("export * from './websockets/client';\n");
+    }
 
     fs::write(output_dir.join("generated/index.ts"), content)?;
 
     Ok(())
 }
+
+/// Generate `generated/testing.ts`, an in-process engine for handler tests.
+///
+/// It calls handlers directly, collects the events they trigger and dispatches
+/// them to the schema's event handlers, following event trigger chains.
+/// Handlers without a file are left out.
+pub fn generate_test_engine(schema: &Schema, output_dir: &Path, layout: &Layout) -> Result<()> {
+    let api_handlers_dir = layout.handlers(output_dir, "api");
+    let apis: Vec<_> = schema
+        .apis
+        .iter()
+        .filter(|api| api_handlers_dir.join(format!("{}.ts", layout.file_stem(&api.name))).exists())
+        .collect();
+    let event_handlers_dir = layout.handlers(output_dir, "events");
+    let has_event_handler =
+        |handler: &String| event_handlers_dir.join(format!("{}.ts", layout.file_stem(handler))).exists();
+
+    let mut content = String::new();
+
+    content.push_str("import { State, TriggeredEvent } from './state';\n");
+
+    for api in &apis {
+        content.push_str(&format!(
+            "import {{ {}Request, {}Response }} from './api/{}';\n",
+            api.name,
+            api.name,
+            templates::to_snake_case(&api.name)
+        ));
+        content.push_str(&format!(
+            "import {{ handle{} }} from '{}';\n",
+            api.name,
+            layout.import_from_generated("api", &layout.file_stem(&api.name))
+        ));
+    }
+
+    let mut imported = std::collections::HashSet::new();
+    for event in &schema.events {
+        for handler in event.handlers.iter().filter(|handler| has_event_handler(handler)) {
+            if imported.insert(handler.clone()) {
+                content.push_str(&format!(
+                    "import {{ {} }} from '{}';\n",
+                    handler,
+                    layout.import_from_generated("events", &layout.file_stem(handler))
+                ));
+            }
+        }
+    }
+
+    content.push_str(
+        r#"
+export type EventHandler = (event: { payload: any; timestamp: Date }) => Promise<void>;
+
+export interface ApiCallResult<Res> {
+  response: Res;
+  events: TriggeredEvent[];
+}
+
+/** Guards against event trigger cycles in the schema. */
+const MAX_DISPATCHES = 1000;
+
+const DEFAULT_EVENT_TIMEOUT_MS = 1000;
+
+/**
+ * In-process engine for tests. Runs handlers directly and dispatches the
+ * events they trigger to registered event handlers.
+ */
+export class TestEngine {
+  private handlers = new Map<string, EventHandler[]>();
+  private chains = new Map<string, string[]>();
+  private waiters: Array<{ eventName: string; resolve: (event: TriggeredEvent) => void }> = [];
+  /** Every event published so far, in order. */
+  readonly published: TriggeredEvent[] = [];
+
+  on(eventName: string, handler: EventHandler): void {
+    const handlers = this.handlers.get(eventName) ?? [];
+    handlers.push(handler);
+    this.handlers.set(eventName, handlers);
+  }
+
+  chain(eventName: string, triggers: string[]): void {
+    this.chains.set(eventName, triggers);
+  }
+
+  /**
+   * Call an API handler and dispatch the events it triggers. `triggers` are
+   * the API's schema triggers; they use the payload set with
+   * `state.setPayload` or fall back to the response.
+   */
+  async callApi<Req, Res>(
+    handlerName: string,
+    handler: (req: Req, state: State) => Promise<Res>,
+    req: Req,
+    triggers: string[] = [],
+  ): Promise<ApiCallResult<Res>> {
+    const state = new State(handlerName);
+    const response = await handler(req, state);
+
+    const events: TriggeredEvent[] = state.getTriggers();
+    for (const eventName of triggers) {
+      events.push({ eventName, payload: state.getAutoTriggerPayload(eventName) ?? response });
+    }
+
+    for (const event of events) {
+      await this.publish(event.eventName, event.payload);
+    }
+
+    return { response, events };
+  }
+
+  /** Publish an event, run its handlers and follow its schema triggers. */
+  async publish(eventName: string, payload: any): Promise<void> {
+    const queue: TriggeredEvent[] = [{ eventName, payload }];
+    let dispatched = 0;
+
+    while (queue.length > 0) {
+      const event = queue.shift()!;
+      if (++dispatched > MAX_DISPATCHES) {
+        throw new Error(`Event chain starting at ${eventName} did not settle`);
+      }
+
+      this.published.push(event);
+      this.notify(event);
+      for (const handler of this.handlers.get(event.eventName) ?? []) {
+        await handler({ payload: event.payload, timestamp: new Date() });
+      }
+      for (const next of this.chains.get(event.eventName) ?? []) {
+        queue.push({ eventName: next, payload: event.payload });
+      }
+    }
+  }
+
+  /**
+   * Resolve with the first published event named `eventName`, waiting up to
+   * `timeoutMs` for it if it has not been published yet.
+   */
+  expectEvent(eventName: string, timeoutMs = DEFAULT_EVENT_TIMEOUT_MS): Promise<TriggeredEvent> {
+    const existing = this.published.find((e) => e.eventName === eventName);
+    if (existing) {
+      return Promise.resolve(existing);
+    }
+
+    return new Promise((resolve, reject) => {
+      const waiter = {
+        eventName,
+        resolve: (event: TriggeredEvent) => {
+          clearTimeout(timer);
+          resolve(event);
+        },
+      };
+      const timer = setTimeout(() => {
+        this.waiters = this.waiters.filter((w) => w !== waiter);
+        const seen = this.published.map((e) => e.eventName).join(', ') || 'none';
+        reject(new Error(`Expected event ${eventName} within ${timeoutMs}ms (published: ${seen})`));
+      }, timeoutMs);
+      this.waiters.push(waiter);
+    });
+  }
+
+  private notify(event: TriggeredEvent): void {
+    const matching = this.waiters.filter((w) => w.eventName === event.eventName);
+    this.waiters = this.waiters.filter((w) => w.eventName !== event.eventName);
+    for (const waiter of matching) {
+      waiter.resolve(event);
+    }
+  }
+}
+
+/** Create a test engine wired with the schema's event handlers. */
+export function createTestEngine(): TestEngine {
+  const engine = new TestEngine();
+"#,
+    );
+
+    for event in &schema.events {
+        for handler in event.handlers.iter().filter(|handler| has_event_handler(handler)) {
+            content.push_str(&format!("  engine.on('{}', {});\n", event.name, handler));
+        }
+        if !event.triggers.is_empty() {
+            let triggers: Vec<String> = event.triggers.iter().map(|t| format!("'{}'", t)).collect();
+            content.push_str(&format!(
+                "  engine.chain('{}', [{}]);\n",
+                event.name,
+                triggers.join(", ")
+            ));
+        }
+    }
+
+    content.push_str("  return engine;\n}\n");
+
+    content.push_str(
+        r#"
+/**
+ * Typed client over the test engine, with one method per API.
+ */
+export class TestClient {
+  constructor(readonly engine: TestEngine = createTestEngine()) {}
+
+  /** Wait for an event triggered by earlier calls. */
+  expectEvent(eventName: string, timeoutMs?: number): Promise<TriggeredEvent> {
+    return this.engine.expectEvent(eventName, timeoutMs);
+  }
+
+  /** Publish an event directly, as if another service had emitted it. */
+  publish(eventName: string, payload: any): Promise<void> {
+    return this.engine.publish(eventName, payload);
+  }
+"#,
+    );
+
+    for api in &apis {
+        let triggers: Vec<String> = api.triggers.iter().map(|t| format!("'{}'", t)).collect();
+        content.push_str(&format!("\n  /** {} {} */\n", api.method, api.path));
+        content.push_str(&format!(
+            "  async {}(req: {}Request): Promise<{}Response> {{\n",
+            method_name(&api.name),
+            api.name,
+            api.name
+        ));
+        content.push_str(&format!(
+            "    const {{ response }} = await this.engine.callApi('{}', handle{}, req, [{}]);\n",
+            api.name,
+            api.name,
+            triggers.join(", ")
+        ));
+        content.push_str("    return response;\n");
+        content.push_str("  }\n");
+    }
+
+    content.push_str("}\n");
+
+    fs::write(output_dir.join("generated/testing.ts"), content)?;
+    Ok(())
+}
+
+fn generate_api_test(api: &Api, schema: &Schema, output_dir: &Path) -> Result<()> {
+    let tests_dir = config::get_project_root(output_dir)?.join("tests/api");
+    let test_path = tests_dir.join(format!("{}.test.ts", api.name));
+    if test_path.exists() {
+        return Ok(());
+    }
+    fs::create_dir_all(&tests_dir)?;
+
+    let mut content = String::new();
+    content.push_str("import { describe, expect, it } from 'vitest';\n");
+    content.push_str("import { TestClient } from '@generated/testing';\n\n");
+
+    content.push_str(&format!("describe('{} {} {}', () => {{\n", api.name, api.method, api.path));
+    content.push_str("  it('handles an example request', async () => {\n");
+    content.push_str("    const client = new TestClient();\n\n");
+    content.push_str(&format!(
+        "    const response = await client.{}({});\n\n",
+        method_name(&api.name),
+        Example::for_api_request(api, schema).to_typescript()
+    ));
+    content.push_str("    expect(response.data).toBeDefined();\n");
+    for trigger in &api.triggers {
+        content.push_str(&format!("    await client.expectEvent('{}');\n", trigger));
+    }
+    content.push_str("  });\n");
+    content.push_str("});\n");
+
+    fs::write(test_path, content)?;
+    Ok(())
+}
+
+fn generate_event_test(event: &Event, schema: &Schema, output_dir: &Path) -> Result<()> {
+    let tests_dir = config::get_project_root(output_dir)?.join("tests/events");
+    let test_path = tests_dir.join(format!("{}.test.ts", event.name));
+    if test_path.exists() {
+        return Ok(());
+    }
+    fs::create_dir_all(&tests_dir)?;
+
+    let mut content = String::new();
+    content.push_str("import { describe, it } from 'vitest';\n");
+    content.push_str("import { TestClient } from '@generated/testing';\n\n");
+
+    content.push_str(&format!("describe('{}', () => {{\n", event.name));
+    content.push_str("  it('runs its handlers for an example payload', async () => {\n");
+    content.push_str("    const client = new TestClient();\n\n");
+    content.push_str(&format!(
+        "    await client.publish('{}', {});\n",
+        event.name,
+        Example::for_type(&event.payload, schema).to_typescript()
+    ));
+    for trigger in &event.triggers {
+        content.push_str(&format!("    await client.expectEvent('{}');\n", trigger));
+    }
+    content.push_str("  });\n");
+    content.push_str("});\n");
+
+    fs::write(test_path, content)?;
+    Ok(())
+}
+
+/// `CreateUser` -> `createUser`
+fn method_name(api_name: &str) -> String {
+    let mut chars = api_name.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
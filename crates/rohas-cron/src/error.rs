@@ -16,6 +16,9 @@ pub enum CronError {
     #[error("Scheduler error: {0}")]
     SchedulerError(String),
 
+    #[error("Job store error: {0}")]
+    Store(String),
+
     #[error("Parse error: {0}")]
     ParseError(#[from] cron::error::Error),
 }
@@ -0,0 +1,383 @@
+use crate::error::{CronError, Result};
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+use cron::{Schedule, TimeUnitSpec};
+use std::str::FromStr;
+
+/// Fields of a cron expression in order: name, allowed values, and a value
+/// that is always valid, used to find the field an expression fails on.
+const FIELDS: [(&str, &str, &str); 7] = [
+    ("second", "0-59", "0"),
+    ("minute", "0-59", "0"),
+    ("hour", "0-23", "0"),
+    ("day of month", "1-31", "*"),
+    ("month", "1-12 or JAN-DEC", "*"),
+    ("day of week", "1-7 (1 = Sunday) or SUN-SAT", "*"),
+    ("year", "1970-2100", "*"),
+];
+
+/// Longest interval accepted, so that run times stay within what a
+/// `DateTime` can hold.
+pub const MAX_INTERVAL: Duration = Duration::from_secs(3650 * 86_400);
+
+const WEEKDAYS: [&str; 7] = ["Sunday", "Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday"];
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// Parses a cron expression, naming the offending field when it is invalid.
+pub fn parse(expression: &str) -> Result<Schedule> {
+    let trimmed = expression.trim();
+    if trimmed.starts_with('@') {
+        return Schedule::from_str(trimmed).map_err(|_| {
+            CronError::InvalidExpression(format!(
+                "'{}': unknown shorthand, expected @yearly, @monthly, @weekly, @daily or @hourly",
+                trimmed
+            ))
+        });
+    }
+
+    let fields: Vec<&str> = trimmed.split_whitespace().collect();
+    if fields.len() != 6 && fields.len() != 7 {
+        let hint = if fields.len() == 5 {
+            format!("; add a leading seconds field, e.g. '0 {}'", trimmed)
+        } else {
+            String::new()
+        };
+        return Err(CronError::InvalidExpression(format!(
+            "'{}': expected 6 or 7 fields (second minute hour day-of-month month day-of-week [year]), found {}{}",
+            trimmed,
+            fields.len(),
+            hint
+        )));
+    }
+
+    Schedule::from_str(trimmed).map_err(|e| {
+        for (index, field) in fields.iter().enumerate() {
+            let probe: Vec<&str> = FIELDS
+                .iter()
+                .take(fields.len())
+                .enumerate()
+                .map(|(i, (_, _, valid))| if i == index { *field } else { *valid })
+                .collect();
+            if Schedule::from_str(&probe.join(" ")).is_err() {
+                let (name, allowed, _) = FIELDS[index];
+                return CronError::InvalidExpression(format!(
+                    "'{}': invalid {} field '{}' (allowed: {})",
+                    trimmed, name, field, allowed
+                ));
+            }
+        }
+        CronError::InvalidExpression(format!("'{}': {}", trimmed, e))
+    })
+}
+
+/// Parses an interval such as `500ms`, `30s`, `5m`, `2h` or `1d`.
+pub fn parse_interval(interval: &str) -> Result<Duration> {
+    let trimmed = interval.trim();
+    let split = trimmed.find(|c: char| !c.is_ascii_digit()).unwrap_or(trimmed.len());
+    let (number, unit) = trimmed.split_at(split);
+    let invalid = || {
+        CronError::InvalidExpression(format!(
+            "'{}': expected an interval such as 500ms, 30s, 5m, 2h or 1d",
+            trimmed
+        ))
+    };
+    let number: u64 = number.parse().map_err(|_| invalid())?;
+    let millis = match unit {
+        "ms" => 1,
+        "s" => 1_000,
+        "m" => 60_000,
+        "h" => 3_600_000,
+        "d" => 86_400_000,
+        _ => return Err(invalid()),
+    };
+    if number == 0 {
+        return Err(CronError::InvalidExpression(format!("'{}': interval must be positive", trimmed)));
+    }
+    number
+        .checked_mul(millis)
+        .map(Duration::from_millis)
+        .filter(|interval| *interval <= MAX_INTERVAL)
+        .ok_or_else(|| {
+            CronError::InvalidExpression(format!(
+                "'{}': interval must be at most {}d",
+                trimmed,
+                MAX_INTERVAL.as_secs() / 86_400
+            ))
+        })
+}
+
+/// Parses an RFC 3339 time such as `2025-08-01T00:00:00Z`.
+pub fn parse_time(time: &str) -> Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(time.trim())
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|e| CronError::InvalidExpression(format!("'{}': expected an RFC 3339 time: {}", time.trim(), e)))
+}
+
+/// Describes an interval in words, e.g. "every 30 seconds".
+pub fn describe_interval(interval: Duration) -> String {
+    let millis = interval.as_millis() as u64;
+    let units = [(86_400_000, "day"), (3_600_000, "hour"), (60_000, "minute"), (1_000, "second"), (1, "millisecond")];
+    let (size, unit) = units
+        .into_iter()
+        .find(|(size, _)| millis.is_multiple_of(*size))
+        .unwrap_or((1, "millisecond"));
+    match millis / size {
+        1 => format!("every {}", unit),
+        n => format!("every {} {}s", n, unit),
+    }
+}
+
+/// The next `count` times the schedule fires after `after`.
+pub fn next_runs(schedule: &Schedule, after: DateTime<Utc>, count: usize) -> Vec<DateTime<Utc>> {
+    schedule.after(&after).take(count).collect()
+}
+
+/// Describes a schedule in words, e.g. "every weekday at 09:00 UTC".
+pub fn describe(schedule: &Schedule) -> String {
+    let seconds: Vec<u32> = schedule.seconds().iter().collect();
+    let minutes: Vec<u32> = schedule.minutes().iter().collect();
+    let hours: Vec<u32> = schedule.hours().iter().collect();
+
+    let at_fixed_times = seconds.len() == 1 && minutes.len() == 1 && hours.len() <= 4;
+    let mut parts = Vec::new();
+
+    if at_fixed_times {
+        parts.push(days(schedule, true).unwrap_or_else(|| "every day".to_string()));
+        parts.extend(calendar(schedule));
+        let times: Vec<String> = hours.iter().map(|h| clock(*h, minutes[0], seconds[0])).collect();
+        parts.push(format!("at {} UTC", join(&times)));
+        return parts.join(" ");
+    }
+
+    parts.push(recurrence(&seconds, &minutes, schedule.hours().is_all()));
+    if !schedule.hours().is_all() {
+        parts.push(hour_window(&hours));
+    }
+    parts.extend(days(schedule, false));
+    parts.extend(calendar(schedule));
+    parts.join(" ")
+}
+
+fn recurrence(seconds: &[u32], minutes: &[u32], every_hour: bool) -> String {
+    let at_second = |phrase: &str| match seconds {
+        [0] => phrase.to_string(),
+        [s] => format!("{} at second {}", phrase, s),
+        _ => format!("{} at seconds {}", phrase, join_numbers(seconds)),
+    };
+
+    if seconds.len() == 60 {
+        return "every second".to_string();
+    }
+    if seconds.len() == 1 && minutes.len() == 60 {
+        return at_second("every minute");
+    }
+    if let Some(step) = step(seconds, 60) {
+        if minutes.len() == 60 {
+            return format!("every {} seconds", step);
+        }
+    }
+    if let Some(step) = step(minutes, 60) {
+        return at_second(&format!("every {} minutes", step));
+    }
+    if let [minute] = minutes {
+        let phrase = if every_hour {
+            format!("every hour at minute {}", minute)
+        } else {
+            format!("at minute {}", minute)
+        };
+        return at_second(&phrase);
+    }
+    at_second(&format!("at minutes {}", join_numbers(minutes)))
+}
+
+fn hour_window(hours: &[u32]) -> String {
+    let first = hours[0];
+    let last = hours[hours.len() - 1];
+    if hours.len() as u32 == last - first + 1 {
+        return format!("between {} and {} UTC", clock(first, 0, 0), clock(last, 59, 0));
+    }
+    if let Some(step) = step(hours, 24) {
+        return format!("every {} hours", step);
+    }
+    let hours: Vec<String> = hours.iter().map(|h| format!("{:02}", h)).collect();
+    format!("during hours {} UTC", join(&hours))
+}
+
+/// Day-of-month and day-of-week restrictions. `every` selects the form used
+/// before a time of day ("every Monday") over the one used after a
+/// recurrence ("on Monday").
+fn days(schedule: &Schedule, every: bool) -> Option<String> {
+    let mut parts = Vec::new();
+
+    if !schedule.days_of_month().is_all() {
+        let days: Vec<u32> = schedule.days_of_month().iter().collect();
+        let noun = if days.len() == 1 { "day" } else { "days" };
+        parts.push(format!("on {} {} of the month", noun, join_numbers(&days)));
+    }
+
+    if !schedule.days_of_week().is_all() {
+        let weekdays: Vec<u32> = schedule.days_of_week().iter().collect();
+        let phrase = match (weekdays.as_slice(), every) {
+            ([2, 3, 4, 5, 6], true) => "every weekday".to_string(),
+            ([2, 3, 4, 5, 6], false) => "on weekdays".to_string(),
+            ([1, 7], true) => "every weekend day".to_string(),
+            ([1, 7], false) => "on weekends".to_string(),
+            _ => {
+                let names: Vec<String> = weekdays.iter().map(|d| WEEKDAYS[*d as usize - 1].to_string()).collect();
+                let prefix = if every && parts.is_empty() { "every" } else { "on" };
+                format!("{} {}", prefix, join(&names))
+            }
+        };
+        parts.push(phrase);
+    }
+
+    if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join(" and "))
+    }
+}
+
+fn calendar(schedule: &Schedule) -> Vec<String> {
+    let mut parts = Vec::new();
+    if !schedule.months().is_all() {
+        let months: Vec<String> = schedule
+            .months()
+            .iter()
+            .map(|m| MONTHS[m as usize - 1].to_string())
+            .collect();
+        parts.push(format!("in {}", join(&months)));
+    }
+    if !schedule.years().is_all() {
+        let years: Vec<u32> = schedule.years().iter().collect();
+        parts.push(format!("in {}", join_numbers(&years)));
+    }
+    parts
+}
+
+/// The step of values that start at zero and repeat evenly up to `max`,
+/// such as `*/15`.
+fn step(values: &[u32], max: u32) -> Option<u32> {
+    if values.len() < 2 || values[0] != 0 {
+        return None;
+    }
+    let step = values[1] - values[0];
+    let even = values.windows(2).all(|w| w[1] - w[0] == step);
+    (even && values[values.len() - 1] + step >= max).then_some(step)
+}
+
+fn clock(hour: u32, minute: u32, second: u32) -> String {
+    if second == 0 {
+        format!("{:02}:{:02}", hour, minute)
+    } else {
+        format!("{:02}:{:02}:{:02}", hour, minute, second)
+    }
+}
+
+fn join_numbers(values: &[u32]) -> String {
+    let values: Vec<String> = values.iter().map(u32::to_string).collect();
+    join(&values)
+}
+
+fn join(items: &[String]) -> String {
+    match items {
+        [] => String::new(),
+        [one] => one.clone(),
+        [init @ .., last] => format!("{} and {}", init.join(", "), last),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_parse_names_invalid_field() {
+        assert!(parse("0 0 9 * * MON-FRI").is_ok());
+        assert!(parse("@daily").is_ok());
+
+        let err = parse("0 61 9 * * *").unwrap_err().to_string();
+        assert!(err.contains("invalid minute field '61'"), "{}", err);
+
+        let err = parse("0 0 9 * * FUNDAY").unwrap_err().to_string();
+        assert!(err.contains("invalid day of week field 'FUNDAY'"), "{}", err);
+
+        let err = parse("0 9 * * *").unwrap_err().to_string();
+        assert!(err.contains("found 5"), "{}", err);
+
+        assert!(parse("@sometimes").is_err());
+    }
+
+    #[test]
+    fn test_describe() {
+        let cases = [
+            ("0 0 9 * * MON-FRI", "every weekday at 09:00 UTC"),
+            ("@daily", "every day at 00:00 UTC"),
+            ("0 30 8,17 * * *", "every day at 08:30 and 17:30 UTC"),
+            ("0 */15 * * * *", "every 15 minutes"),
+            ("* * * * * *", "every second"),
+            ("0 * * * * *", "every minute"),
+            ("0 5 * * * *", "every hour at minute 5"),
+            ("0 */5 9-17 * * *", "every 5 minutes between 09:00 and 17:59 UTC"),
+            ("0 0 0 1 JAN *", "on day 1 of the month in January at 00:00 UTC"),
+            ("0 0 12 * * SUN", "every Sunday at 12:00 UTC"),
+        ];
+        for (expression, expected) in cases {
+            assert_eq!(describe(&parse(expression).unwrap()), expected, "{}", expression);
+        }
+    }
+
+    #[test]
+    fn test_intervals_and_times() {
+        assert_eq!(parse_interval("30s").unwrap(), Duration::from_secs(30));
+        assert_eq!(parse_interval("1d").unwrap(), Duration::from_secs(86_400));
+        assert!(parse_interval("0s").is_err());
+        assert!(parse_interval("30x").is_err());
+        assert!(parse_interval("1.5m").is_err());
+        assert_eq!(parse_interval("3650d").unwrap(), MAX_INTERVAL);
+        assert!(parse_interval("3651d").is_err());
+        assert!(parse_interval("1000000000d").is_err());
+        assert!(parse_interval("18446744073709551615ms").is_err());
+        assert!(parse_interval("99999999999999999999s").is_err());
+
+        assert_eq!(describe_interval(Duration::from_secs(30)), "every 30 seconds");
+        assert_eq!(describe_interval(Duration::from_secs(3600)), "every hour");
+        assert_eq!(describe_interval(Duration::from_millis(90_000)), "every 90 seconds");
+
+        assert_eq!(
+            parse_time("2025-08-01T02:00:00+02:00").unwrap(),
+            Utc.with_ymd_and_hms(2025, 8, 1, 0, 0, 0).unwrap()
+        );
+        assert!(parse_time("tomorrow").is_err());
+    }
+
+    #[test]
+    fn test_next_runs() {
+        let schedule = parse("0 0 9 * * MON-FRI").unwrap();
+        // A Friday afternoon, so the next runs skip the weekend.
+        let after = Utc.with_ymd_and_hms(2024, 6, 7, 12, 0, 0).unwrap();
+        let runs = next_runs(&schedule, after, 2);
+        assert_eq!(
+            runs,
+            vec![
+                Utc.with_ymd_and_hms(2024, 6, 10, 9, 0, 0).unwrap(),
+                Utc.with_ymd_and_hms(2024, 6, 11, 9, 0, 0).unwrap(),
+            ]
+        );
+    }
+}
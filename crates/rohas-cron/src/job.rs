@@ -1,10 +1,52 @@
 use crate::error::{CronError, Result};
+use crate::expression;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
+/// How a job's `schedule` is read.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScheduleKind {
+    /// A cron expression.
+    #[default]
+    Cron,
+    /// A fixed interval such as `30s`, counted from when the scheduler starts.
+    Every,
+    /// A single RFC 3339 time.
+    At,
+}
+
+/// What to do when a run is due while the previous one is still going.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OverlapPolicy {
+    /// Drop the new run.
+    #[default]
+    Skip,
+    /// Run once the previous run finishes. At most one run waits.
+    Queue,
+    /// Start the new run alongside the previous one.
+    Concurrent,
+}
+
+/// What to do with runs that fell due while the scheduler was stopped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MisfirePolicy {
+    /// Run once as soon as the scheduler starts, however many runs were missed.
+    RunOnce,
+    /// Wait for the next scheduled run.
+    #[default]
+    Ignore,
+}
+
+/// Number of runs kept in a job's history.
+const HISTORY_LIMIT: usize = 50;
+
 /// Job configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JobConfig {
@@ -14,9 +56,18 @@ pub struct JobConfig {
     /// Job name
     pub name: String,
 
-    /// Cron expression (e.g., "0 0 * * *")
+    /// Cron expression (e.g., "0 0 * * *"), interval or time, per `kind`
     pub schedule: String,
 
+    #[serde(default)]
+    pub kind: ScheduleKind,
+
+    #[serde(default)]
+    pub overlap: OverlapPolicy,
+
+    #[serde(default)]
+    pub misfire: MisfirePolicy,
+
     /// Whether the job is enabled
     pub enabled: bool,
 
@@ -33,6 +84,9 @@ impl JobConfig {
             id: Uuid::new_v4().to_string(),
             name: name.into(),
             schedule: schedule.into(),
+            kind: ScheduleKind::Cron,
+            overlap: OverlapPolicy::default(),
+            misfire: MisfirePolicy::default(),
             enabled: true,
             timeout_seconds: 300, // 5 minutes default
             triggers: Vec::new(),
@@ -49,13 +103,102 @@ impl JobConfig {
         self
     }
 
-    /// Validate the cron expression
+    pub fn with_kind(mut self, kind: ScheduleKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub fn with_overlap(mut self, overlap: OverlapPolicy) -> Self {
+        self.overlap = overlap;
+        self
+    }
+
+    pub fn with_misfire(mut self, misfire: MisfirePolicy) -> Self {
+        self.misfire = misfire;
+        self
+    }
+
+    /// Validate the schedule
     pub fn validate(&self) -> Result<()> {
-        use std::str::FromStr;
-        cron::Schedule::from_str(&self.schedule)
-            .map_err(|e| CronError::InvalidExpression(e.to_string()))?;
+        Timing::parse(self.kind, &self.schedule)?;
         Ok(())
     }
+
+    /// The schedule in words, e.g. "every weekday at 09:00 UTC".
+    pub fn describe(&self) -> Result<String> {
+        Ok(match Timing::parse(self.kind, &self.schedule)? {
+            Timing::Cron(schedule) => expression::describe(&schedule),
+            Timing::Every(interval) => expression::describe_interval(interval),
+            Timing::At(time) => format!("once at {}", time.format("%Y-%m-%d %H:%M:%S UTC")),
+        })
+    }
+
+    /// The next `count` runs after `after`. Interval jobs count from `after`.
+    pub fn next_runs(&self, after: DateTime<Utc>, count: usize) -> Result<Vec<DateTime<Utc>>> {
+        Ok(match Timing::parse(self.kind, &self.schedule)? {
+            Timing::Cron(schedule) => expression::next_runs(&schedule, after, count),
+            Timing::Every(interval) => {
+                let interval = chrono::Duration::from_std(interval).ok();
+                (1..=count)
+                    .map(|i| {
+                        interval
+                            .zip(i32::try_from(i).ok())
+                            .and_then(|(interval, i)| interval.checked_mul(i))
+                            .and_then(|offset| after.checked_add_signed(offset))
+                            .ok_or_else(|| {
+                                CronError::InvalidExpression(format!(
+                                    "'{}': run {} falls past the latest supported time",
+                                    self.schedule, i
+                                ))
+                            })
+                    })
+                    .collect::<Result<_>>()?
+            }
+            Timing::At(time) if time > after && count > 0 => vec![time],
+            Timing::At(_) => Vec::new(),
+        })
+    }
+}
+
+enum Timing {
+    Cron(Box<cron::Schedule>),
+    Every(std::time::Duration),
+    At(DateTime<Utc>),
+}
+
+impl Timing {
+    fn parse(kind: ScheduleKind, schedule: &str) -> Result<Self> {
+        Ok(match kind {
+            ScheduleKind::Cron => Timing::Cron(Box::new(expression::parse(schedule)?)),
+            ScheduleKind::Every => Timing::Every(expression::parse_interval(schedule)?),
+            ScheduleKind::At => Timing::At(expression::parse_time(schedule)?),
+        })
+    }
+
+    /// The first run after `now`, given that the job last fired at `last`.
+    fn next_after(&self, now: DateTime<Utc>, last: Option<DateTime<Utc>>) -> Option<DateTime<Utc>> {
+        match self {
+            Timing::Cron(schedule) => schedule.after(&now).next(),
+            Timing::Every(interval) => {
+                let interval = chrono::Duration::from_std(*interval).ok()?;
+                match last.and_then(|last| last.checked_add_signed(interval)).filter(|next| *next > now) {
+                    Some(next) => Some(next),
+                    None => now.checked_add_signed(interval),
+                }
+            }
+            Timing::At(time) if *time > now && last.is_none_or(|last| last < *time) => Some(*time),
+            Timing::At(_) => None,
+        }
+    }
+
+    /// The first run strictly after `since`, whether or not it is past.
+    fn first_after(&self, since: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Timing::Cron(schedule) => schedule.after(&since).next(),
+            Timing::Every(interval) => since.checked_add_signed(chrono::Duration::from_std(*interval).ok()?),
+            Timing::At(time) => (*time > since).then_some(*time),
+        }
+    }
 }
 
 /// Job status
@@ -66,6 +209,8 @@ pub enum JobStatus {
     Completed,
     Failed,
     Disabled,
+    /// The run was not started, see the record's `reason`.
+    Skipped,
 }
 
 /// Execution record
@@ -77,6 +222,12 @@ pub struct ExecutionRecord {
     pub status: JobStatus,
     pub error: Option<String>,
     pub duration_ms: Option<u64>,
+    /// The time the run was due.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scheduled_for: Option<DateTime<Utc>>,
+    /// Why the scheduler skipped, delayed or caught up on this run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
 }
 
 impl ExecutionRecord {
@@ -88,6 +239,23 @@ impl ExecutionRecord {
             status: JobStatus::Running,
             error: None,
             duration_ms: None,
+            scheduled_for: None,
+            reason: None,
+        }
+    }
+
+    /// A run the scheduler decided not to start.
+    pub fn skipped(job_id: String, scheduled_for: DateTime<Utc>, reason: impl Into<String>) -> Self {
+        let now = Utc::now();
+        Self {
+            job_id,
+            started_at: now,
+            completed_at: Some(now),
+            status: JobStatus::Skipped,
+            error: None,
+            duration_ms: Some(0),
+            scheduled_for: Some(scheduled_for),
+            reason: Some(reason.into()),
         }
     }
 
@@ -107,29 +275,44 @@ impl ExecutionRecord {
     }
 }
 
+/// Outcome of [`CronJob::claim`].
+#[derive(Debug, PartialEq)]
+pub(crate) enum Claim {
+    Run,
+    Queued,
+    Skip(&'static str),
+}
+
+#[derive(Default)]
+struct RunSlot {
+    running: usize,
+    queued: Option<DateTime<Utc>>,
+}
+
 /// Cron job definition
 pub struct CronJob {
     config: JobConfig,
-    schedule: cron::Schedule,
-    last_execution: Arc<RwLock<Option<ExecutionRecord>>>,
+    timing: Timing,
+    history: Arc<RwLock<VecDeque<ExecutionRecord>>>,
     next_run: Arc<RwLock<Option<DateTime<Utc>>>>,
+    slot: Mutex<RunSlot>,
+    /// The missed run a `run_once` misfire is catching up on.
+    catch_up: RwLock<Option<DateTime<Utc>>>,
 }
 
 impl CronJob {
     pub fn new(config: JobConfig) -> Result<Self> {
-        use std::str::FromStr;
-        config.validate()?;
-
-        let schedule = cron::Schedule::from_str(&config.schedule)
-            .map_err(|e| CronError::InvalidExpression(e.to_string()))?;
+        let timing = Timing::parse(config.kind, &config.schedule)?;
 
-        let next_run = schedule.upcoming(Utc).next();
+        let next_run = timing.next_after(Utc::now(), None);
 
         Ok(Self {
             config,
-            schedule,
-            last_execution: Arc::new(RwLock::new(None)),
+            timing,
+            history: Arc::new(RwLock::new(VecDeque::new())),
             next_run: Arc::new(RwLock::new(next_run)),
+            slot: Mutex::new(RunSlot::default()),
+            catch_up: RwLock::new(None),
         })
     }
 
@@ -154,17 +337,91 @@ impl CronJob {
     }
 
     pub async fn update_next_run(&self) {
-        let next = self.schedule.upcoming(Utc).next();
+        let last = self.last_execution().await.map(|r| r.scheduled_for.unwrap_or(r.started_at));
+        let next = self.timing.next_after(Utc::now(), last);
         *self.next_run.write().await = next;
     }
 
+    /// Moves on to the run after the one due at `fired_at`.
+    pub async fn advance(&self, fired_at: DateTime<Utc>) {
+        *self.next_run.write().await = self.timing.next_after(Utc::now(), Some(fired_at));
+    }
+
+    /// Picks up from a previous process, whose last run was due at `since`.
+    /// A run missed in between is handled per the job's misfire policy.
+    pub async fn resume(&self, since: DateTime<Utc>) {
+        let now = Utc::now();
+        let Some(missed) = self.timing.first_after(since).filter(|missed| *missed <= now) else {
+            *self.next_run.write().await = self.timing.next_after(now, Some(since));
+            return;
+        };
+
+        match self.config.misfire {
+            MisfirePolicy::RunOnce => {
+                *self.catch_up.write().await = Some(missed);
+                *self.next_run.write().await = Some(now);
+            }
+            MisfirePolicy::Ignore => {
+                let reason = "missed while the scheduler was stopped";
+                self.record_execution(ExecutionRecord::skipped(self.config.id.clone(), missed, reason))
+                    .await;
+                *self.next_run.write().await = self.timing.next_after(now, Some(missed));
+            }
+        }
+    }
+
+    /// The missed run the next run catches up on, if any.
+    pub async fn take_catch_up(&self) -> Option<DateTime<Utc>> {
+        self.catch_up.write().await.take()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.slot.lock().unwrap().running > 0
+    }
+
+    /// Decides per the overlap policy whether a run due at `due` starts now.
+    pub(crate) fn claim(&self, due: DateTime<Utc>) -> Claim {
+        let mut slot = self.slot.lock().unwrap();
+        if slot.running == 0 || self.config.overlap == OverlapPolicy::Concurrent {
+            slot.running += 1;
+            return Claim::Run;
+        }
+        match self.config.overlap {
+            OverlapPolicy::Queue if slot.queued.is_none() => {
+                slot.queued = Some(due);
+                Claim::Queued
+            }
+            OverlapPolicy::Queue => Claim::Skip("a run is already queued"),
+            _ => Claim::Skip("the previous run is still in progress"),
+        }
+    }
+
+    /// Ends a run started by [`CronJob::claim`]. Returns the due time of the
+    /// queued run, which then takes over the slot.
+    pub(crate) fn release(&self) -> Option<DateTime<Utc>> {
+        let mut slot = self.slot.lock().unwrap();
+        let queued = slot.queued.take();
+        if queued.is_none() {
+            slot.running -= 1;
+        }
+        queued
+    }
+
     pub async fn last_execution(&self) -> Option<ExecutionRecord> {
-        self.last_execution.read().await.clone()
+        self.history.read().await.back().cloned()
+    }
+
+    /// Recent runs and skipped runs, oldest first.
+    pub async fn history(&self) -> Vec<ExecutionRecord> {
+        self.history.read().await.iter().cloned().collect()
     }
 
     pub async fn record_execution(&self, record: ExecutionRecord) {
-        *self.last_execution.write().await = Some(record);
-        self.update_next_run().await;
+        let mut history = self.history.write().await;
+        if history.len() >= HISTORY_LIMIT {
+            history.pop_front();
+        }
+        history.push_back(record);
     }
 
     pub async fn should_run(&self) -> bool {
@@ -203,6 +460,85 @@ mod tests {
         assert!(job.next_run().await.is_some());
     }
 
+    #[tokio::test]
+    async fn test_interval_and_one_shot_jobs() {
+        let every = JobConfig::new("poll", "30s").with_kind(ScheduleKind::Every);
+        assert_eq!(every.describe().unwrap(), "every 30 seconds");
+        let job = CronJob::new(every).unwrap();
+        let next = job.next_run().await.unwrap();
+        assert!(next > Utc::now() + chrono::Duration::seconds(29));
+
+        let at = JobConfig::new("launch", "2999-08-01T00:00:00Z").with_kind(ScheduleKind::At);
+        let job = CronJob::new(at).unwrap();
+        let due = job.next_run().await.unwrap();
+        job.advance(due).await;
+        assert!(job.next_run().await.is_none());
+
+        let past = JobConfig::new("launch", "2000-01-01T00:00:00Z").with_kind(ScheduleKind::At);
+        assert!(CronJob::new(past).unwrap().next_run().await.is_none());
+
+        assert!(JobConfig::new("bad", "soon").with_kind(ScheduleKind::At).validate().is_err());
+    }
+
+    #[test]
+    fn test_interval_overflow_is_an_error() {
+        let huge = JobConfig::new("rare", "1000000000d").with_kind(ScheduleKind::Every);
+        assert!(huge.validate().is_err());
+        assert!(huge.next_runs(Utc::now(), 3).is_err());
+
+        let longest = JobConfig::new("rare", "3650d").with_kind(ScheduleKind::Every);
+        assert_eq!(longest.next_runs(Utc::now(), 3).unwrap().len(), 3);
+        assert!(longest.next_runs(DateTime::<Utc>::MAX_UTC - chrono::Duration::days(1), 1).is_err());
+        assert!(longest.next_runs(Utc::now(), 100_000).is_err());
+    }
+
+    #[test]
+    fn test_overlap_policies() {
+        let due = Utc::now();
+        let skip = CronJob::new(JobConfig::new("sync", "0 0 0 * * *")).unwrap();
+        assert_eq!(skip.claim(due), Claim::Run);
+        assert!(matches!(skip.claim(due), Claim::Skip(_)));
+        assert_eq!(skip.release(), None);
+        assert!(!skip.is_running());
+
+        let queue = CronJob::new(JobConfig::new("sync", "0 0 0 * * *").with_overlap(OverlapPolicy::Queue)).unwrap();
+        assert_eq!(queue.claim(due), Claim::Run);
+        assert_eq!(queue.claim(due), Claim::Queued);
+        assert!(matches!(queue.claim(due), Claim::Skip(_)));
+        assert_eq!(queue.release(), Some(due));
+        assert!(queue.is_running());
+        assert_eq!(queue.release(), None);
+        assert!(!queue.is_running());
+
+        let concurrent =
+            CronJob::new(JobConfig::new("sync", "0 0 0 * * *").with_overlap(OverlapPolicy::Concurrent)).unwrap();
+        assert_eq!(concurrent.claim(due), Claim::Run);
+        assert_eq!(concurrent.claim(due), Claim::Run);
+    }
+
+    #[tokio::test]
+    async fn test_misfire_policies() {
+        let since = Utc::now() - chrono::Duration::hours(2);
+        let hourly = JobConfig::new("report", "0 0 * * * *");
+
+        let run_once = CronJob::new(hourly.clone().with_misfire(MisfirePolicy::RunOnce)).unwrap();
+        run_once.resume(since).await;
+        assert!(run_once.should_run().await);
+        assert!(run_once.take_catch_up().await.is_some());
+
+        let ignore = CronJob::new(hourly.clone()).unwrap();
+        ignore.resume(since).await;
+        assert!(!ignore.should_run().await);
+        let skipped = ignore.last_execution().await.unwrap();
+        assert_eq!(skipped.status, JobStatus::Skipped);
+        assert!(skipped.scheduled_for.unwrap() > since);
+
+        let up_to_date = CronJob::new(hourly.with_misfire(MisfirePolicy::RunOnce)).unwrap();
+        up_to_date.resume(Utc::now()).await;
+        assert!(!up_to_date.should_run().await);
+        assert!(up_to_date.last_execution().await.is_none());
+    }
+
     #[test]
     fn test_execution_record() {
         let mut record = ExecutionRecord::new("job-123".to_string());
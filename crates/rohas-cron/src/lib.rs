@@ -1,7 +1,10 @@
 pub mod error;
+pub mod expression;
 pub mod job;
 pub mod scheduler;
+pub mod store;
 
 pub use error::{CronError, Result};
-pub use job::{CronJob, JobConfig, JobStatus};
+pub use job::{CronJob, ExecutionRecord, JobConfig, JobStatus, MisfirePolicy, OverlapPolicy, ScheduleKind};
 pub use scheduler::Scheduler;
+pub use store::{JobStore, RunTimes, ScheduledCall};
@@ -1,5 +1,7 @@
 use crate::error::{CronError, Result};
-use crate::job::{CronJob, ExecutionRecord, JobConfig, JobStatus};
+use crate::job::{Claim, CronJob, ExecutionRecord, JobConfig, JobStatus};
+use crate::store::RunTimes;
+use chrono::{DateTime, Utc};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -16,6 +18,7 @@ pub struct Scheduler {
     jobs: Arc<RwLock<HashMap<String, Arc<CronJob>>>>,
     handlers: Arc<RwLock<HashMap<String, JobHandler>>>,
     running: Arc<RwLock<bool>>,
+    run_times: Arc<RunTimes>,
 }
 
 impl Scheduler {
@@ -24,13 +27,32 @@ impl Scheduler {
             jobs: Arc::new(RwLock::new(HashMap::new())),
             handlers: Arc::new(RwLock::new(HashMap::new())),
             running: Arc::new(RwLock::new(false)),
+            run_times: Arc::new(RunTimes::in_memory()),
         }
     }
 
+    /// Keeps the times jobs fell due in `run_times`, so that jobs added later
+    /// apply their misfire policy to runs missed since the last process.
+    pub fn with_run_times(mut self, run_times: Arc<RunTimes>) -> Self {
+        self.run_times = run_times;
+        self
+    }
+
     pub async fn add_job(&self, config: JobConfig) -> Result<String> {
         let job = Arc::new(CronJob::new(config)?);
         let job_id = job.id().to_string();
 
+        match self.run_times.get(job.name()).await {
+            Some(since) => job.resume(since).await,
+            // Count missed runs from now on, so a one-shot job that falls due
+            // while the process is down is noticed.
+            None => {
+                if let Err(e) = self.run_times.set(job.name(), Utc::now()).await {
+                    warn!("Failed to record cron job {}: {}", job.name(), e);
+                }
+            }
+        }
+
         let mut jobs = self.jobs.write().await;
         jobs.insert(job_id.clone(), job);
 
@@ -82,10 +104,11 @@ impl Scheduler {
         let jobs = self.jobs.clone();
         let handlers = self.handlers.clone();
         let running_flag = self.running.clone();
+        let run_times = self.run_times.clone();
 
         tokio::spawn(async move {
             while *running_flag.read().await {
-                Self::tick(&jobs, &handlers).await;
+                Self::tick(&jobs, &handlers, &run_times).await;
                 sleep(Duration::from_secs(1)).await;
             }
         });
@@ -102,55 +125,92 @@ impl Scheduler {
     async fn tick(
         jobs: &Arc<RwLock<HashMap<String, Arc<CronJob>>>>,
         handlers: &Arc<RwLock<HashMap<String, JobHandler>>>,
+        run_times: &RunTimes,
     ) {
         let jobs_map = jobs.read().await;
         let handlers_map = handlers.read().await;
 
         for (_job_id, job) in jobs_map.iter() {
-            if job.should_run().await {
-                let job_name = job.name().to_string();
-                debug!("Job should run: {}", job_name);
+            if !job.should_run().await {
+                continue;
+            }
+            let Some(due) = job.next_run().await else {
+                continue;
+            };
+            let job_name = job.name().to_string();
+            debug!("Job should run: {}", job_name);
+
+            job.advance(due).await;
+            if let Err(e) = run_times.set(&job_name, due).await {
+                warn!("Failed to record run of cron job {}: {}", job_name, e);
+            }
 
-                if let Some(handler) = handlers_map.get(&job_name) {
+            let Some(handler) = handlers_map.get(&job_name) else {
+                warn!("No handler registered for job: {}", job_name);
+                continue;
+            };
+
+            match job.claim(due) {
+                Claim::Run => {
+                    let reason = job
+                        .take_catch_up()
+                        .await
+                        .map(|missed| format!("catching up on the run missed at {}", missed.to_rfc3339()));
                     let job = Arc::clone(job);
                     let handler = Arc::clone(handler);
 
                     tokio::spawn(async move {
-                        Self::execute_job(job, handler).await;
+                        Self::execute_job(job, handler, due, reason).await;
                     });
-                } else {
-                    warn!("No handler registered for job: {}", job_name);
+                }
+                Claim::Queued => {
+                    info!("Queued cron job {} behind its previous run", job_name);
+                }
+                Claim::Skip(reason) => {
+                    info!("Skipped cron job {}: {}", job_name, reason);
+                    job.record_execution(ExecutionRecord::skipped(job.id().to_string(), due, reason))
+                        .await;
                 }
             }
         }
     }
 
-    async fn execute_job(job: Arc<CronJob>, handler: JobHandler) {
+    /// Runs the job, then any run queued behind it.
+    async fn execute_job(job: Arc<CronJob>, handler: JobHandler, due: DateTime<Utc>, reason: Option<String>) {
         let config = job.config();
-        let mut record = ExecutionRecord::new(config.id.clone());
+        let mut next = Some((due, reason));
 
-        info!("Executing cron job: {}", config.name);
+        while let Some((due, reason)) = next.take() {
+            let mut record = ExecutionRecord::new(config.id.clone());
+            record.scheduled_for = Some(due);
+            record.reason = reason;
 
-        match tokio::time::timeout(Duration::from_secs(config.timeout_seconds), handler(config))
-            .await
-        {
-            Ok(Ok(())) => {
-                record.complete();
-                info!("Job completed successfully: {}", config.name);
-            }
-            Ok(Err(e)) => {
-                let error_msg = format!("Job failed: {}", e);
-                error!("{}", error_msg);
-                record.fail(error_msg);
-            }
-            Err(_) => {
-                let error_msg = format!("Job timed out after {} seconds", config.timeout_seconds);
-                error!("{}", error_msg);
-                record.fail(error_msg);
+            info!("Executing cron job: {}", config.name);
+
+            match tokio::time::timeout(Duration::from_secs(config.timeout_seconds), handler(config))
+                .await
+            {
+                Ok(Ok(())) => {
+                    record.complete();
+                    info!("Job completed successfully: {}", config.name);
+                }
+                Ok(Err(e)) => {
+                    let error_msg = format!("Job failed: {}", e);
+                    error!("{}", error_msg);
+                    record.fail(error_msg);
+                }
+                Err(_) => {
+                    let error_msg = format!("Job timed out after {} seconds", config.timeout_seconds);
+                    error!("{}", error_msg);
+                    record.fail(error_msg);
+                }
             }
-        }
 
-        job.record_execution(record).await;
+            job.record_execution(record).await;
+            next = job
+                .release()
+                .map(|queued| (queued, Some("queued while the previous run was in progress".to_string())));
+        }
     }
 
     pub async fn list_jobs(&self) -> Vec<JobConfig> {
@@ -176,6 +236,16 @@ impl Scheduler {
         }
     }
 
+    /// Recent runs of the job named `job_name`, including the ones the
+    /// overlap and misfire policies skipped. Oldest first.
+    pub async fn history(&self, job_name: &str) -> Vec<ExecutionRecord> {
+        let job = self.jobs.read().await.values().find(|job| job.name() == job_name).cloned();
+        match job {
+            Some(job) => job.history().await,
+            None => Vec::new(),
+        }
+    }
+
     pub async fn get_execution_record(&self, job_id: &str) -> Result<Option<ExecutionRecord>> {
         let jobs = self.jobs.read().await;
 
@@ -224,4 +294,23 @@ mod tests {
         let handlers = scheduler.handlers.read().await;
         assert!(handlers.contains_key("test_job"));
     }
+
+    #[tokio::test]
+    async fn test_misfire_uses_recorded_run_times() {
+        let run_times = Arc::new(RunTimes::in_memory());
+        run_times
+            .set("report", Utc::now() - chrono::Duration::hours(3))
+            .await
+            .unwrap();
+        let scheduler = Scheduler::new().with_run_times(run_times.clone());
+
+        let config = JobConfig::new("report", "0 0 * * * *").with_misfire(crate::job::MisfirePolicy::RunOnce);
+        let job_id = scheduler.add_job(config).await.unwrap();
+        let job = scheduler.jobs.read().await.get(&job_id).cloned().unwrap();
+        assert!(job.should_run().await);
+
+        scheduler.add_job(JobConfig::new("cleanup", "0 0 * * * *")).await.unwrap();
+        assert!(run_times.get("cleanup").await.is_some());
+        assert!(scheduler.history("cleanup").await.is_empty());
+    }
 }
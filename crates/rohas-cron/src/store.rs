@@ -0,0 +1,178 @@
+use crate::error::{CronError, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tokio::sync::Mutex;
+use tracing::debug;
+use uuid::Uuid;
+
+/// A one-off handler call scheduled from handler code.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledCall {
+    pub id: String,
+    pub handler: String,
+    pub payload: Value,
+    pub run_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Pending scheduled calls, kept in a JSON file so they survive restarts.
+pub struct JobStore {
+    path: Option<PathBuf>,
+    calls: Mutex<Vec<ScheduledCall>>,
+}
+
+impl JobStore {
+    /// A store that is lost when the process exits.
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            calls: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Opens the store at `path`, loading the calls still pending there.
+    pub async fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let calls = read_json(&path).await?.unwrap_or_default();
+
+        Ok(Self {
+            path: Some(path),
+            calls: Mutex::new(calls),
+        })
+    }
+
+    pub async fn add(&self, handler: impl Into<String>, run_at: DateTime<Utc>, payload: Value) -> Result<ScheduledCall> {
+        let call = ScheduledCall {
+            id: Uuid::new_v4().to_string(),
+            handler: handler.into(),
+            payload,
+            run_at,
+            created_at: Utc::now(),
+        };
+
+        let mut calls = self.calls.lock().await;
+        calls.push(call.clone());
+        self.persist(&calls).await?;
+        debug!("Scheduled {} for {} ({})", call.handler, call.run_at, call.id);
+        Ok(call)
+    }
+
+    /// Removes and returns the calls due at `now`. A call is handed out
+    /// once, so it is lost if the process dies while it runs.
+    pub async fn take_due(&self, now: DateTime<Utc>) -> Result<Vec<ScheduledCall>> {
+        let mut calls = self.calls.lock().await;
+        if !calls.iter().any(|call| call.run_at <= now) {
+            return Ok(Vec::new());
+        }
+
+        let (due, pending): (Vec<_>, Vec<_>) = calls.drain(..).partition(|call| call.run_at <= now);
+        *calls = pending;
+        self.persist(&calls).await?;
+        Ok(due)
+    }
+
+    pub async fn list(&self) -> Vec<ScheduledCall> {
+        self.calls.lock().await.clone()
+    }
+
+    async fn persist(&self, calls: &[ScheduledCall]) -> Result<()> {
+        match &self.path {
+            Some(path) => write_json(path, &calls).await,
+            None => Ok(()),
+        }
+    }
+}
+
+/// When each cron job last fell due, by job name, so runs missed while the
+/// process was down can be spotted on the next start.
+pub struct RunTimes {
+    path: Option<PathBuf>,
+    times: Mutex<HashMap<String, DateTime<Utc>>>,
+}
+
+impl RunTimes {
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            times: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let times = read_json(&path).await?.unwrap_or_default();
+
+        Ok(Self {
+            path: Some(path),
+            times: Mutex::new(times),
+        })
+    }
+
+    pub async fn get(&self, job_name: &str) -> Option<DateTime<Utc>> {
+        self.times.lock().await.get(job_name).copied()
+    }
+
+    pub async fn set(&self, job_name: &str, at: DateTime<Utc>) -> Result<()> {
+        let mut times = self.times.lock().await;
+        times.insert(job_name.to_string(), at);
+        match &self.path {
+            Some(path) => write_json(path, &*times).await,
+            None => Ok(()),
+        }
+    }
+}
+
+async fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> Result<Option<T>> {
+    match tokio::fs::read(path).await {
+        Ok(bytes) => serde_json::from_slice(&bytes)
+            .map(Some)
+            .map_err(|e| CronError::Store(format!("Invalid job store {}: {}", path.display(), e))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(CronError::Store(format!("Failed to read {}: {}", path.display(), e))),
+    }
+}
+
+async fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    let store_error = |e: std::io::Error| CronError::Store(format!("Failed to write {}: {}", path.display(), e));
+
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await.map_err(store_error)?;
+    }
+    let body = serde_json::to_vec_pretty(value).map_err(|e| CronError::Store(e.to_string()))?;
+    // Write a sibling file first so a crash never leaves a partial store.
+    let tmp = path.with_extension("json.tmp");
+    tokio::fs::write(&tmp, body).await.map_err(store_error)?;
+    tokio::fs::rename(&tmp, path).await.map_err(store_error)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_store_persists_pending_calls() {
+        let path = std::env::temp_dir().join(format!("rohas-jobs-{}.json", Uuid::new_v4()));
+        let now = Utc::now();
+
+        let store = JobStore::open(&path).await.unwrap();
+        store.add("send_reminder", now - chrono::Duration::seconds(1), json!({"id": 1})).await.unwrap();
+        store.add("send_reminder", now + chrono::Duration::hours(1), json!({"id": 2})).await.unwrap();
+
+        let due = store.take_due(now).await.unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].payload, json!({"id": 1}));
+        assert!(store.take_due(now).await.unwrap().is_empty());
+
+        let reopened = JobStore::open(&path).await.unwrap();
+        let pending = reopened.list().await;
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].payload, json!({"id": 2}));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -3,13 +3,18 @@ mod ts_compiler;
 
 use notify::RecursiveMode;
 use notify_debouncer_full::{new_debouncer, DebounceEventResult};
-use rohas_codegen::{self, Language as CodegenLanguage};
-use rohas_engine::{config::Language as EngineLanguage, Engine, EngineConfig};
-use rohas_parser::{Parser, Schema};
+use rohas_codegen::{self, impact::DependencyIndex, Language as CodegenLanguage};
+use rohas_engine::{
+    config::{AdapterConfig, Language as EngineLanguage},
+    Engine, EngineConfig,
+};
+use rohas_parser::{env::active_environment, Parser, Schema};
+use rohas_runtime::PythonEnvironment;
 use rust_compiler::RustCompiler;
 use tracing::debug;
 use std::fs;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::RwLock;
@@ -19,25 +24,39 @@ use ts_compiler::TypeScriptCompiler;
 pub struct DevServer {
     schema_path: PathBuf,
     config: EngineConfig,
+    /// Adapter settings from the latest valid `rohas.toml`, which can change
+    /// while the server runs.
+    adapter_config: Arc<RwLock<AdapterConfig>>,
     watch: bool,
     engine: Arc<RwLock<Option<Engine>>>,
     ts_compiler: Arc<RwLock<Option<TypeScriptCompiler>>>,
     rust_compiler: Arc<RwLock<Option<RustCompiler>>>,
     rust_library: Arc<tokio::sync::Mutex<Option<libloading::Library>>>,
+    /// Dylibs of handler groups reloaded on their own, every build since
+    /// the last full reload.
+    rust_group_libraries: Arc<tokio::sync::Mutex<Vec<libloading::Library>>>,
     last_loaded_dylib_hash: Arc<tokio::sync::Mutex<Option<[u8; 32]>>>,
+    /// Content hash of each Python file as last reloaded, so saves that
+    /// change nothing skip the reload.
+    python_file_hashes: Arc<tokio::sync::Mutex<HashMap<PathBuf, [u8; 32]>>>,
+    schema: Arc<RwLock<Option<Schema>>>,
 }
 
 impl DevServer {
     pub fn new(schema_path: PathBuf, config: EngineConfig, watch: bool) -> Self {
         Self {
             schema_path,
+            adapter_config: Arc::new(RwLock::new(config.adapter.clone())),
             config,
             watch,
             engine: Arc::new(RwLock::new(None)),
             ts_compiler: Arc::new(RwLock::new(None)),
             rust_compiler: Arc::new(RwLock::new(None)),
             rust_library: Arc::new(tokio::sync::Mutex::new(None)),
+            rust_group_libraries: Arc::new(tokio::sync::Mutex::new(Vec::new())),
             last_loaded_dylib_hash: Arc::new(tokio::sync::Mutex::new(None)),
+            python_file_hashes: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+            schema: Arc::new(RwLock::new(None)),
         }
     }
 
@@ -65,10 +84,7 @@ impl DevServer {
     }
 
     fn is_typescript_project(&self) -> bool {
-        let project_root = self.get_project_root();
-        let package_json = project_root.join("package.json");
-        let swcrc = project_root.join(".swcrc");
-        package_json.exists() && swcrc.exists()
+        TypeScriptCompiler::is_typescript_project(&self.get_project_root())
     }
 
     fn is_rust_project(&self) -> bool {
@@ -92,6 +108,10 @@ impl DevServer {
             self.setup_rust_compiler().await?;
         }
 
+        if self.config.language == EngineLanguage::Python {
+            self.setup_python_environment();
+        }
+
         self.reload_engine().await?;
 
         if self.watch {
@@ -136,6 +156,36 @@ impl DevServer {
         Ok(())
     }
 
+    /// Installs the declared dependencies into the project's environment,
+    /// which the engine then imports handler packages from.
+    fn setup_python_environment(&self) {
+        let project_root = self.get_project_root();
+        let python = &self.config.python;
+        let environment = match PythonEnvironment::detect(&project_root, python.interpreter.as_deref()) {
+            Ok(Some(environment)) => environment,
+            Ok(None) => {
+                if project_root.join("requirements.txt").exists() {
+                    warn!(
+                        "No Python environment found; create one with `python3 -m venv .venv` so rohas dev installs requirements.txt into it"
+                    );
+                }
+                return;
+            }
+            Err(e) => {
+                error!("Failed to inspect the project's Python environment: {}", e);
+                return;
+            }
+        };
+
+        info!("Detected Python {}", environment);
+        if python.install_requirements {
+            if let Err(e) = environment.install_requirements(&project_root) {
+                error!("{}", e);
+                warn!("Handlers importing packages that are not installed will fail");
+            }
+        }
+    }
+
     async fn setup_rust_compiler(&self) -> anyhow::Result<()> {
         let project_root = self.get_project_root();
         info!(
@@ -143,9 +193,13 @@ impl DevServer {
             project_root.display()
         );
 
-        let compiler = RustCompiler::new(project_root);
+        let compiler = RustCompiler::new(project_root).with_dev_config(&self.config.dev);
 
-        compiler.compile()?;
+        if RustCompiler::cargo_available() {
+            compiler.compile()?;
+        } else {
+            info!("cargo not found; using the prebuilt Rust handler library");
+        }
 
         let mut rust_compiler = self.rust_compiler.write().await;
         *rust_compiler = Some(compiler);
@@ -157,7 +211,7 @@ impl DevServer {
         info!("Loading engine...");
 
         let schema = if self.schema_path.is_file() {
-            Parser::parse_file(&self.schema_path)?
+            Parser::parse_file_for_environment(&self.schema_path, &active_environment())?
         } else if self.schema_path.is_dir() {
             parse_directory(&self.schema_path)?
         } else {
@@ -165,8 +219,13 @@ impl DevServer {
         };
 
         self.run_codegen(&schema)?;
+        *self.schema.write().await = Some(schema.clone());
 
-        let engine = Engine::from_schema(schema, self.config.clone()).await?;
+        let mut config = self.config.clone();
+        config.adapter = self.adapter_config.read().await.clone();
+        // Saves from the workbench editor are only picked up while watching.
+        config.dev.file_editing = self.watch;
+        let engine = Engine::from_schema(schema, config).await?;
 
         let layer = engine.create_tracing_log_layer();
         if let Err(e) = rohas_engine::tracing_log::register_tracing_log_layer(layer) {
@@ -181,9 +240,10 @@ impl DevServer {
             let mut rust_lib = self.rust_library.lock().await;
             *rust_lib = None;
         }
+        self.rust_group_libraries.lock().await.clear();
 
         if self.is_rust_project() {
-            self.register_rust_handlers(&engine, true).await?;
+            self.register_rust_handlers(&engine, RustCompiler::cargo_available()).await?;
         }
 
         let mut engine_lock = self.engine.write().await;
@@ -229,23 +289,25 @@ impl DevServer {
             info!("Updated Cargo.toml to build as dylib");
         }
 
-        let compiler = crate::rust_compiler::RustCompiler::new(project_root.clone());
+        let compiler = crate::rust_compiler::RustCompiler::new(project_root.clone())
+            .with_dev_config(&self.config.dev);
         if should_build {
             info!("Building Rust project as dylib...");
-            compiler.build_release().await?;
+            compiler.build().await?;
         } else {
             info!("Skipping build (already built)...");
         }
 
-        let dylib_path = compiler.get_library_path_for_profile("release")?;
+        let dylib_path = compiler.get_library_path_for_profile(compiler.profile())?;
 
         if !dylib_path.exists() {
-            let debug_dylib = compiler.get_library_path_for_profile("debug")?;
+            let other_profile = if compiler.profile() == "release" { "debug" } else { "release" };
+            let other_dylib = compiler.get_library_path_for_profile(other_profile)?;
 
-            if debug_dylib.exists() {
-                return self.load_and_register_handlers(&debug_dylib, rust_runtime).await;
+            if other_dylib.exists() {
+                return self.load_and_register_handlers(&other_dylib, rust_runtime).await;
             } else {
-                warn!("Rust dylib not found at: {} or {}", dylib_path.display(), debug_dylib.display());
+                warn!("Rust dylib not found at: {} or {}", dylib_path.display(), other_dylib.display());
                 return Ok(());
             }
         }
@@ -451,7 +513,8 @@ impl DevServer {
                                 .map(|s| s.to_ascii_lowercase());
 
                             if let Some(ext_str) = ext.as_deref() {
-                                if ext_str == "ro" || ext_str == "roh" {
+                                let is_config = ext_str == "toml" && path.ends_with("config/rohas.toml");
+                                if ext_str == "ro" || ext_str == "roh" || is_config {
                                     if tx.blocking_send((path.clone(), ext_str.to_string())).is_err() {
                                         eprintln!("[File Watcher] Channel full, dropping event for: {}", path.display());
                                     }
@@ -482,6 +545,20 @@ impl DevServer {
             info!("Watching for handler changes in: {}", src_dir.display());
         }
 
+        if let Some(compiler) = self.ts_compiler.read().await.as_ref() {
+            for dir in compiler.linked_package_dirs() {
+                debouncer.watch(&dir, RecursiveMode::Recursive)?;
+                info!("Watching for workspace package changes in: {}", dir.display());
+            }
+        }
+
+        let config_path = self.config_path();
+        let mut file_config = EngineConfig::from_file(&config_path).ok();
+        if let Some(config_dir) = config_path.parent().filter(|dir| dir.exists()) {
+            debouncer.watch(config_dir, RecursiveMode::NonRecursive)?;
+            info!("Watching for config changes in: {}", config_dir.display());
+        }
+
 
         let _debouncer_guard = debouncer;
 
@@ -598,6 +675,11 @@ impl DevServer {
                                 warn!("Continuing to watch for changes...");
                             }
                         }
+                    } else if ext == "toml" {
+                        if let Err(e) = self.reload_config(&mut file_config).await {
+                            error!("Failed to apply config change: {}", e);
+                            warn!("Keeping the current adapter; continuing to watch for changes...");
+                        }
                     } else if ext == "ts" || ext == "tsx" {
                         let path_str = path.to_string_lossy();
                         let is_generated = path_str.contains("/generated/") || path_str.contains("\\generated\\");
@@ -614,7 +696,8 @@ impl DevServer {
                         } else {
                             warn!("Handler file changed - reloading handler runtime...");
 
-                            match self.reload_typescript_handler().await {
+                            let affected = self.affected_files(&path).await;
+                            match self.reload_typescript_handler(affected).await {
                                 Ok(_) => {
                                     info!("Handler reloaded successfully");
                                 }
@@ -638,12 +721,13 @@ impl DevServer {
                                 }
                             }
                         } else {
-                            warn!("Python handler file changed - clearing cache...");
-                            if let Some(eng) = self.engine.read().await.as_ref() {
-                                if let Err(e) = eng.clear_handler_cache().await {
-                                    error!("Failed to clear handler cache: {}", e);
-                                } else {
-                                    info!("Handler cache cleared");
+                            self.affected_files(&path).await;
+                            match self.reload_python_handler(&path).await {
+                                Ok(true) => info!("Python handlers reloaded after {} changed", path.display()),
+                                Ok(false) => debug!("{} is unchanged, skipping reload", path.display()),
+                                Err(e) => {
+                                    error!("Failed to reload Python handler {}: {}", path.display(), e);
+                                    warn!("Handlers keep running the last code that loaded; continuing to watch for changes...");
                                 }
                             }
                         }
@@ -671,6 +755,7 @@ impl DevServer {
                             }
                         } else {
                             warn!("Rust handler file changed - recompiling...");
+                            self.affected_files(&path).await;
                             if let Err(e) = self.reload_rust_handler_with_file(Some(path.as_path())).await {
                                 error!("Failed to recompile Rust handlers: {}", e);
                                 warn!("Continuing to watch for changes...");
@@ -686,7 +771,75 @@ impl DevServer {
         Ok(())
     }
 
-    async fn reload_typescript_handler(&self) -> anyhow::Result<()> {
+    fn config_path(&self) -> PathBuf {
+        self.config.project_root.join("config").join("rohas.toml")
+    }
+
+    /// Applies a `rohas.toml` change. A changed `[adapter]` section swaps the
+    /// engine's adapter and re-subscribes its topics; the HTTP server keeps
+    /// running. Other sections are only read at startup. `previous` is the
+    /// file's last valid contents, used to tell which sections changed.
+    async fn reload_config(&self, previous: &mut Option<EngineConfig>) -> anyhow::Result<()> {
+        let config = EngineConfig::from_file(self.config_path())?;
+
+        if let Some(previous) = previous.as_ref() {
+            let mut other = serde_json::to_value(&config)?;
+            let mut previous_other = serde_json::to_value(previous)?;
+            for value in [&mut other, &mut previous_other] {
+                if let Some(table) = value.as_object_mut() {
+                    table.remove("adapter");
+                }
+            }
+            if other != previous_other {
+                warn!("rohas.toml changes outside [adapter] take effect after restarting rohas dev");
+            }
+        }
+        *previous = Some(config.clone());
+
+        if *self.adapter_config.read().await == config.adapter {
+            debug!("Adapter settings unchanged");
+            return Ok(());
+        }
+        if self.adapter_config.read().await.claim_check != config.adapter.claim_check {
+            warn!("[adapter.claim_check] changes take effect after restarting rohas dev");
+        }
+
+        warn!("Adapter settings changed - swapping the event adapter...");
+        if let Some(engine) = self.engine.read().await.as_ref() {
+            engine.reload_adapter(&config.adapter).await?;
+        }
+        *self.adapter_config.write().await = config.adapter;
+        info!("Event adapter reloaded; topics re-subscribed");
+        Ok(())
+    }
+
+    /// Handler sources affected by a change to `path`: the file and every
+    /// file importing it, per the dependency index. None when the index
+    /// cannot be built.
+    async fn affected_files(&self, path: &std::path::Path) -> Option<Vec<PathBuf>> {
+        let schema = self.schema.read().await.clone()?;
+        let project_root = self.get_project_root();
+        // Rebuilt on every change, so imports added since the last reload count.
+        let index = match DependencyIndex::build(&schema, &project_root, &self.config.codegen.layout()) {
+            Ok(index) => index,
+            Err(e) => {
+                warn!("Failed to index handler dependencies: {}", e);
+                return None;
+            }
+        };
+        let impact = index.impact_of_file(&project_root, path).ok()?;
+        if !impact.handlers.is_empty() {
+            info!(
+                "Change affects handlers: {}",
+                impact.handlers.iter().cloned().collect::<Vec<_>>().join(", ")
+            );
+        }
+        Some(impact.files.iter().map(|file| project_root.join(file)).collect())
+    }
+
+    /// Recompiles, then drops the cached modules of `affected` files, or of
+    /// every handler when the affected files are unknown.
+    async fn reload_typescript_handler(&self, affected: Option<Vec<PathBuf>>) -> anyhow::Result<()> {
         {
             let ts_compiler = self.ts_compiler.read().await;
             if let Some(compiler) = ts_compiler.as_ref() {
@@ -697,18 +850,49 @@ impl DevServer {
         {
             let engine = self.engine.read().await;
             if let Some(eng) = engine.as_ref() {
-                eng.clear_handler_cache().await?;
+                match affected {
+                    Some(files) => {
+                        for file in files {
+                            eng.executor().reload_node_module(&file.to_string_lossy()).await?;
+                        }
+                    }
+                    None => eng.clear_handler_cache().await?,
+                }
             }
         }
 
         Ok(())
     }
 
+    /// Reloads Python handlers after `path` changed, unless its content is
+    /// the same as at the last reload. Returns whether it reloaded.
+    async fn reload_python_handler(&self, path: &Path) -> anyhow::Result<bool> {
+        use sha2::{Digest, Sha256};
+
+        let hash: [u8; 32] = Sha256::digest(fs::read(path)?).into();
+        if self.python_file_hashes.lock().await.get(path) == Some(&hash) {
+            return Ok(false);
+        }
+
+        if let Some(eng) = self.engine.read().await.as_ref() {
+            eng.executor().reload_python_module(path).await?;
+        }
+        self.python_file_hashes.lock().await.insert(path.to_path_buf(), hash);
+        Ok(true)
+    }
+
     async fn reload_rust_handler(&self) -> anyhow::Result<()> {
         self.reload_rust_handler_with_file(None).await
     }
 
-    async fn reload_rust_handler_with_file(&self, _changed_file: Option<&std::path::Path>) -> anyhow::Result<()> {
+    async fn reload_rust_handler_with_file(&self, changed_file: Option<&std::path::Path>) -> anyhow::Result<()> {
+        if let Some(group) = changed_file.and_then(|file| self.rust_handler_group(file)) {
+            match self.reload_rust_group(group).await {
+                Ok(()) => return Ok(()),
+                Err(e) => warn!("Reloading the {} handler group failed, rebuilding the whole project: {}", group, e),
+            }
+        }
+
         {
             let rust_compiler = self.rust_compiler.read().await;
             if let Some(compiler) = rust_compiler.as_ref() {
@@ -741,10 +925,10 @@ impl DevServer {
                     }
                 }
 
-                let build_result = compiler.build_release().await;
+                let build_result = compiler.build().await;
                 build_result?;
 
-                let dylib_path = compiler.get_library_path_for_profile("release")?;
+                let dylib_path = compiler.get_library_path_for_profile(compiler.profile())?;
                 if let Ok(metadata) = dylib_path.metadata() {
                     if let Ok(modified) = metadata.modified() {
                         info!("Dylib last modified: {:?}", modified);
@@ -799,6 +983,7 @@ impl DevServer {
                 info!("No old Rust dylib to drop");
             }
         }
+        self.rust_group_libraries.lock().await.clear();
 
         info!("Waiting for OS to fully unload old dylib...");
         tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
@@ -810,7 +995,7 @@ impl DevServer {
 
             let rust_compiler = self.rust_compiler.read().await;
             let (dylib_path, new_dylib_hash) = if let Some(compiler) = rust_compiler.as_ref() {
-                let dylib_path = compiler.get_library_path_for_profile("release")?;
+                let dylib_path = compiler.get_library_path_for_profile(compiler.profile())?;
                 if !dylib_path.exists() {
                     return Err(anyhow::anyhow!("Dylib not found at expected path: {}. Build may have failed.", dylib_path.display()));
                 }
@@ -917,6 +1102,84 @@ impl DevServer {
         Ok(())
     }
 
+    /// Handler group of a changed Rust file, when the group is built as its
+    /// own crate.
+    fn rust_handler_group(&self, path: &std::path::Path) -> Option<&'static str> {
+        let project_root = self.get_project_root();
+        let relative = path
+            .strip_prefix(&project_root)
+            .ok()
+            .map(|p| p.to_path_buf())
+            .or_else(|| {
+                let root = project_root.canonicalize().ok()?;
+                path.canonicalize().ok()?.strip_prefix(root).ok().map(|p| p.to_path_buf())
+            })?;
+        let group = rohas_codegen::rust::handler_group(&relative)?;
+        let compiler = RustCompiler::new(project_root);
+        compiler.has_group_crate(group).then_some(group)
+    }
+
+    /// Rebuilds one handler group's crate and registers its handlers from
+    /// the new dylib over the previous ones. Handlers of other groups keep
+    /// running from the dylibs already loaded.
+    async fn reload_rust_group(&self, group: &str) -> anyhow::Result<()> {
+        use libloading::{Library, Symbol};
+        use std::ffi::c_void;
+
+        let dylib_path = {
+            let rust_compiler = self.rust_compiler.read().await;
+            let compiler = rust_compiler
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!("Rust compiler not available"))?;
+            compiler.build_group(group).await?
+        };
+
+        // A fresh path per build, so the loader cannot return the library
+        // it already has mapped for the previous build.
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_nanos();
+        let file_name = dylib_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("handlers");
+        let load_path = std::env::temp_dir().join(format!("{}-{}", timestamp, file_name));
+        fs::copy(&dylib_path, &load_path)?;
+
+        let engine = self.engine.read().await;
+        let Some(eng) = engine.as_ref() else {
+            let _ = fs::remove_file(&load_path);
+            return Ok(());
+        };
+        let runtime = eng.executor().rust_runtime().clone();
+
+        let loaded = unsafe { Library::new(&load_path) };
+        // The mapping outlives the file.
+        let _ = fs::remove_file(&load_path);
+        let lib = loaded?;
+
+        let result = unsafe {
+            type SetRuntimeFn = unsafe extern "C" fn(*mut c_void) -> i32;
+            let set_runtime: Symbol<SetRuntimeFn> = lib.get(b"rohas_set_runtime")?;
+            let runtime_ptr = Arc::into_raw(runtime) as *mut c_void;
+            let result = set_runtime(runtime_ptr);
+            // The dylib keeps its own clone; release the one handed over.
+            drop(Arc::from_raw(runtime_ptr as *const rohas_runtime::RustRuntime));
+            result
+        };
+        if result != 0 {
+            return Err(anyhow::anyhow!("rohas_set_runtime returned {}", result));
+        }
+
+        eng.clear_handler_cache().await?;
+        // Earlier builds stay loaded: handlers dropped from the group may
+        // still point into them until the next full reload.
+        self.rust_group_libraries.lock().await.push(lib);
+
+        info!("Reloaded {} handler group from {}", group, dylib_path.display());
+        Ok(())
+    }
+
     fn run_codegen(&self, schema: &Schema) -> anyhow::Result<()> {
         let output_dir = self.config.project_root.join("src");
 
@@ -932,7 +1195,9 @@ impl DevServer {
             output_dir.display()
         );
 
-        rohas_codegen::generate(schema, &output_dir, lang)?;
+        rohas_codegen::Generator::new(lang)
+            .with_layout(self.config.codegen.layout())
+            .generate(schema, &output_dir)?;
 
         info!("Codegen completed");
 
@@ -941,38 +1206,25 @@ impl DevServer {
 }
 
 fn parse_directory(dir: &PathBuf) -> anyhow::Result<Schema> {
-    let mut combined_schema = Schema::new();
-    let mut file_count = 0;
-
+    let mut files = Vec::new();
     visit_ro_files(dir, &mut |path| {
-        info!("Parsing: {}", path.display());
-        match Parser::parse_file(path) {
-            Ok(schema) => {
-                combined_schema.models.extend(schema.models);
-                combined_schema.types.extend(schema.types);
-                combined_schema.inputs.extend(schema.inputs);
-                combined_schema.apis.extend(schema.apis);
-                combined_schema.events.extend(schema.events);
-                combined_schema.crons.extend(schema.crons);
-                combined_schema.websockets.extend(schema.websockets);
-                file_count += 1;
-                Ok(())
-            }
-            Err(e) => Err(anyhow::anyhow!("Failed to parse {}: {}", path.display(), e)),
-        }
+        files.push(path.clone());
+        Ok(())
     })?;
 
-    if file_count == 0 {
+    if files.is_empty() {
         anyhow::bail!("No .ro files found in {}", dir.display());
     }
+    files.sort();
 
-    info!("Parsed {} schema files", file_count);
+    // The files are parsed together, as constants and attribute groups
+    // apply across them.
+    let schema = Parser::parse_files_for_environment(&files, &active_environment())
+        .map_err(|e| anyhow::anyhow!("Failed to parse {}: {}", dir.display(), e))?;
 
-    combined_schema
-        .validate()
-        .map_err(|e| anyhow::anyhow!("Schema validation failed: {}", e))?;
+    info!("Parsed {} schema files", files.len());
 
-    Ok(combined_schema)
+    Ok(schema)
 }
 
 fn visit_ro_files<F>(dir: &PathBuf, callback: &mut F) -> anyhow::Result<()>
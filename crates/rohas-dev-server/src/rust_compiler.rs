@@ -1,3 +1,4 @@
+use rohas_engine::config::{DevConfig, RustProfile};
 use std::path::PathBuf;
 use std::process::Command as StdCommand;
 use tokio::process::Command;
@@ -7,17 +8,107 @@ use tracing::{error, info, warn};
 
 pub struct RustCompiler {
     project_root: PathBuf,
+    profile: RustProfile,
+    sccache: bool,
 }
 
 impl RustCompiler {
     pub fn new(project_root: PathBuf) -> Self {
-        Self { project_root }
+        Self {
+            project_root,
+            profile: RustProfile::Release,
+            sccache: false,
+        }
+    }
+
+    /// Applies the `[dev]` build settings. sccache is only used when it is
+    /// installed.
+    pub fn with_dev_config(mut self, dev: &DevConfig) -> Self {
+        self.profile = dev.rust_profile;
+        self.sccache = dev.sccache && {
+            let available = StdCommand::new("sccache").arg("--version").output().is_ok();
+            if !available {
+                warn!("dev.sccache is set but sccache was not found on the PATH; building without it");
+            }
+            available
+        };
+        self
+    }
+
+    /// Cargo profile builds and dylib lookups use.
+    pub fn profile(&self) -> &'static str {
+        self.profile.as_str()
+    }
+
+    /// `cargo build --lib` for the configured profile. Debug builds keep
+    /// incremental compilation on even where it is disabled globally.
+    fn cargo_build(&self) -> Command {
+        let mut cmd = Command::new("cargo");
+        cmd.arg("build").arg("--message-format=short").arg("--lib");
+        match self.profile {
+            RustProfile::Release => {
+                cmd.arg("--release");
+            }
+            RustProfile::Debug => {
+                cmd.env("CARGO_INCREMENTAL", "1");
+            }
+        }
+        if self.sccache {
+            cmd.env("RUSTC_WRAPPER", "sccache");
+        }
+        cmd.current_dir(self.project_root());
+        cmd
+    }
+
+    /// Builds the project dylib with the configured profile.
+    pub async fn build(&self) -> anyhow::Result<()> {
+        match self.profile {
+            RustProfile::Release => self.build_release().await,
+            RustProfile::Debug => self.build_debug().await,
+        }
+    }
+
+    /// Incremental debug build. Unlike `build_release`, nothing is cleaned
+    /// first, so only the crates that changed are recompiled.
+    async fn build_debug(&self) -> anyhow::Result<()> {
+        info!(
+            "Building Rust project in debug mode{}: {}",
+            if self.sccache { " with sccache" } else { "" },
+            self.project_root().display()
+        );
+
+        self.ensure_dylib_config()?;
+
+        let started = std::time::Instant::now();
+        let output = self.cargo_build().output().await?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            error!("Rust debug build failed:\n{}", stderr);
+            return Err(anyhow::anyhow!("Rust debug build failed"));
+        }
+
+        let dylib_path = self.get_library_path_for_profile("debug")?;
+        if !dylib_path.exists() {
+            return Err(anyhow::anyhow!(
+                "Dylib was not created at expected path: {}",
+                dylib_path.display()
+            ));
+        }
+
+        info!("Rust project rebuilt (debug) in {:.1}s", started.elapsed().as_secs_f64());
+        Ok(())
     }
 
     pub fn project_root(&self) -> &PathBuf {
         &self.project_root
     }
 
+    /// Whether cargo is on the PATH. Deployments built with `rohas build`
+    /// ship a prebuilt handler library and no toolchain.
+    pub fn cargo_available() -> bool {
+        StdCommand::new("cargo").arg("--version").output().is_ok()
+    }
+
     pub fn is_rust_project(project_root: &PathBuf) -> bool {
         project_root.join("Cargo.toml").exists()
     }
@@ -188,6 +279,10 @@ impl RustCompiler {
             .arg("--lib")
             .env("CARGO_INCREMENTAL", "0")
             .current_dir(self.project_root());
+        // Dependencies come back from the cache after the clean above.
+        if self.sccache {
+            build_cmd.env("RUSTC_WRAPPER", "sccache");
+        }
 
         let output = build_cmd.output().await?;
 
@@ -430,10 +525,46 @@ impl RustCompiler {
     }
 
     pub fn get_library_path_for_profile(&self, profile: &str) -> anyhow::Result<PathBuf> {
-        let target_dir = self.project_root().join("target");
-
         // Use lib name if specified, otherwise fall back to package name
         let lib_name = self.get_lib_name()?;
+        Ok(self.library_path(&lib_name, profile))
+    }
+
+    /// Whether codegen produced a crate of its own for a handler group.
+    pub fn has_group_crate(&self, group: &str) -> bool {
+        self.project_root()
+            .join(rohas_codegen::rust::HANDLER_CRATES_DIR)
+            .join(group)
+            .join("Cargo.toml")
+            .exists()
+    }
+
+    /// Builds only a handler group's crate and returns its dylib. Other
+    /// groups and the main crate are left untouched.
+    pub async fn build_group(&self, group: &str) -> anyhow::Result<PathBuf> {
+        let package = rohas_codegen::rust::handler_crate_name(&self.get_package_name()?, group);
+        info!("Building handler group {} (package {})", group, package);
+
+        let output = self.cargo_build().arg("--package").arg(&package).output().await?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            error!("Build of handler group {} failed:\n{}", group, stderr);
+            return Err(anyhow::anyhow!("Build of handler group {} failed", group));
+        }
+
+        let dylib_path = self.library_path(&package, self.profile());
+        if !dylib_path.exists() {
+            return Err(anyhow::anyhow!(
+                "Dylib was not created at expected path: {}",
+                dylib_path.display()
+            ));
+        }
+        Ok(dylib_path)
+    }
+
+    fn library_path(&self, lib_name: &str, profile: &str) -> PathBuf {
+        let target_dir = self.project_root().join("target");
         let project_name = lib_name.replace('-', "_");
 
         #[cfg(target_os = "macos")]
@@ -445,7 +576,7 @@ impl RustCompiler {
         #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
         let dylib_name = format!("lib{}.so", project_name);
 
-        Ok(target_dir.join(profile).join(&dylib_name))
+        target_dir.join(profile).join(&dylib_name)
     }
 
     fn ensure_dylib_config(&self) -> anyhow::Result<()> {
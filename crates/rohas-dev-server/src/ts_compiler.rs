@@ -1,24 +1,65 @@
 use std::path::{Path, PathBuf};
 use std::process::{Child, Command, Stdio};
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, warn};
+
+/// The package manager of a project, per the lockfile at its workspace root.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackageManager {
+    Npm,
+    Pnpm,
+    Yarn,
+}
+
+impl PackageManager {
+    pub fn detect(workspace_root: &Path) -> Self {
+        if workspace_root.join("pnpm-lock.yaml").exists() || workspace_root.join("pnpm-workspace.yaml").exists() {
+            PackageManager::Pnpm
+        } else if workspace_root.join("yarn.lock").exists() {
+            PackageManager::Yarn
+        } else {
+            PackageManager::Npm
+        }
+    }
+
+    pub fn command(&self) -> &'static str {
+        match self {
+            PackageManager::Npm => "npm",
+            PackageManager::Pnpm => "pnpm",
+            PackageManager::Yarn => "yarn",
+        }
+    }
+}
 
 pub struct TypeScriptCompiler {
     project_root: PathBuf,
+    /// Root of the pnpm, yarn or npm workspace the project is part of, or the
+    /// project root itself.
+    workspace_root: PathBuf,
+    package_manager: PackageManager,
     watch_process: Option<Child>,
 }
 
 impl TypeScriptCompiler {
     pub fn new(project_root: PathBuf) -> Self {
+        let workspace_root = find_workspace_root(&project_root);
+        let package_manager = PackageManager::detect(&workspace_root);
         Self {
             project_root,
+            workspace_root,
+            package_manager,
             watch_process: None,
         }
     }
 
+    pub fn is_typescript_project(project_root: &Path) -> bool {
+        project_root.join("package.json").exists()
+            && (project_root.join("tsconfig.json").exists() || project_root.join(".swcrc").exists())
+    }
+
     pub fn compile(&self) -> anyhow::Result<()> {
         info!("Compiling TypeScript to JavaScript using SWC...");
 
-        let status = Command::new("npm")
+        let status = Command::new(self.package_manager.command())
             .arg("run")
             .arg("compile")
             .current_dir(&self.project_root)
@@ -39,7 +80,7 @@ impl TypeScriptCompiler {
 
         self.ensure_swc_installed()?;
 
-        let child = Command::new("npm")
+        let child = Command::new(self.package_manager.command())
             .arg("run")
             .arg("compile:watch")
             .current_dir(&self.project_root)
@@ -68,15 +109,16 @@ impl TypeScriptCompiler {
             anyhow::bail!("package.json not found in project root");
         }
 
-        let node_modules = self.project_root.join("node_modules");
-        if !node_modules.exists() {
-            warn!("node_modules not found, installing dependencies...");
-            self.install_dependencies()?;
-        }
-
-        let swc_cli = node_modules.join("@swc").join("cli");
-        if !swc_cli.exists() {
-            warn!("@swc/cli not found, installing dependencies...");
+        // Workspaces hoist dependencies to the workspace root.
+        let installed = |package: &str| {
+            [&self.project_root, &self.workspace_root]
+                .iter()
+                .any(|root| root.join("node_modules").join(package).exists())
+        };
+        // The generated state.ts re-exports @rohas/sdk.
+        let missing = ["@rspack/cli", "@rohas/sdk"].into_iter().find(|package| !installed(package));
+        if let Some(package) = missing {
+            warn!("{} not found, installing dependencies...", package);
             self.install_dependencies()?;
         }
 
@@ -84,23 +126,59 @@ impl TypeScriptCompiler {
     }
 
     fn install_dependencies(&self) -> anyhow::Result<()> {
-        info!("Installing npm dependencies...");
+        let command = self.package_manager.command();
+        info!("Installing dependencies with {} in {}...", command, self.workspace_root.display());
 
-        let status = Command::new("npm")
+        let status = Command::new(command)
             .arg("install")
-            .current_dir(&self.project_root)
+            .current_dir(&self.workspace_root)
             .stdout(Stdio::inherit())
             .stderr(Stdio::inherit())
             .status()?;
 
         if !status.success() {
-            anyhow::bail!("npm install failed");
+            anyhow::bail!("{} install failed", command);
         }
 
-        info!("npm dependencies installed");
+        info!("Dependencies installed");
         Ok(())
     }
 
+    /// Source directories of the workspace packages the project depends on,
+    /// which the package manager links into `node_modules`. Handlers bundle
+    /// them, so a change to one needs a recompile.
+    pub fn linked_package_dirs(&self) -> Vec<PathBuf> {
+        let Ok(content) = std::fs::read_to_string(self.project_root.join("package.json")) else {
+            return Vec::new();
+        };
+        let Ok(package) = serde_json::from_str::<serde_json::Value>(&content) else {
+            return Vec::new();
+        };
+
+        let mut dirs = Vec::new();
+        let dependencies = ["dependencies", "devDependencies"]
+            .iter()
+            .filter_map(|field| package.get(field).and_then(|deps| deps.as_object()))
+            .flat_map(|deps| deps.keys());
+        for name in dependencies {
+            let linked = [&self.project_root, &self.workspace_root]
+                .iter()
+                .map(|root| root.join("node_modules").join(name))
+                .find(|path| path.is_symlink());
+            let Some(dir) = linked.and_then(|path| path.canonicalize().ok()) else {
+                continue;
+            };
+            // pnpm links registry packages into its store under node_modules.
+            if dir.components().any(|component| component.as_os_str() == "node_modules") {
+                continue;
+            }
+            let src = dir.join("src");
+            debug!("Found workspace package {} in {}", name, dir.display());
+            dirs.push(if src.is_dir() { src } else { dir });
+        }
+        dirs
+    }
+
     #[allow(dead_code)]
     pub fn get_output_dir(&self) -> PathBuf {
         self.project_root.join(".rohas")
@@ -139,6 +217,24 @@ impl TypeScriptCompiler {
     }
 }
 
+/// The closest ancestor of `project_root` that declares a workspace, through
+/// `pnpm-workspace.yaml` or the `workspaces` field of its `package.json`.
+fn find_workspace_root(project_root: &Path) -> PathBuf {
+    for dir in project_root.ancestors() {
+        if dir.join("pnpm-workspace.yaml").exists() {
+            return dir.to_path_buf();
+        }
+        let declares_workspaces = std::fs::read_to_string(dir.join("package.json"))
+            .ok()
+            .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+            .is_some_and(|package| package.get("workspaces").is_some());
+        if declares_workspaces {
+            return dir.to_path_buf();
+        }
+    }
+    project_root.to_path_buf()
+}
+
 impl Drop for TypeScriptCompiler {
     fn drop(&mut self) {
         if let Err(e) = self.stop_watch() {
@@ -176,4 +272,23 @@ mod tests {
             PathBuf::from("/project/.rohas/handlers/api/Health.js")
         );
     }
+
+    #[test]
+    fn test_detects_workspace_root_and_package_manager() {
+        let root = std::env::temp_dir().join(format!("rohas-ts-workspace-{}", std::process::id()));
+        let project = root.join("apps").join("api");
+        std::fs::create_dir_all(&project).unwrap();
+        std::fs::write(root.join("package.json"), r#"{"private": true, "workspaces": ["apps/*"]}"#).unwrap();
+        std::fs::write(root.join("yarn.lock"), "").unwrap();
+        std::fs::write(project.join("package.json"), r#"{"name": "api"}"#).unwrap();
+
+        let compiler = TypeScriptCompiler::new(project.clone());
+        assert_eq!(compiler.workspace_root, root);
+        assert_eq!(compiler.package_manager, PackageManager::Yarn);
+
+        std::fs::write(root.join("pnpm-workspace.yaml"), "packages:\n  - apps/*\n").unwrap();
+        assert_eq!(TypeScriptCompiler::new(project).package_manager, PackageManager::Pnpm);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
 }
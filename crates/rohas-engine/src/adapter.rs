@@ -1,14 +1,264 @@
-use crate::error::Result;
+use crate::config::{AdapterConfig, AdapterType};
+use crate::error::{EngineError, Result};
 use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::info;
+
+/// The event adapter the engine publishes and subscribes through. Its
+/// backend can be replaced while the engine runs (see [`Adapter::replace`]).
+pub struct Adapter {
+    backend: RwLock<Arc<AdapterBackend>>,
+}
+
+impl Adapter {
+    pub fn new(backend: AdapterBackend) -> Self {
+        Self {
+            backend: RwLock::new(Arc::new(backend)),
+        }
+    }
+
+    pub async fn from_config(config: &AdapterConfig, project_root: &Path) -> Result<Self> {
+        Ok(Self::new(AdapterBackend::from_config(config, project_root).await?))
+    }
+
+    async fn backend(&self) -> Arc<AdapterBackend> {
+        self.backend.read().await.clone()
+    }
+
+    /// Swaps in a new backend and closes the old one. Publishes go to the new
+    /// backend right away; subscriptions on the old one finish the messages
+    /// they have received and stop, so callers re-subscribe afterwards.
+    pub async fn replace(&self, backend: AdapterBackend) {
+        let old = std::mem::replace(&mut *self.backend.write().await, Arc::new(backend));
+        old.close().await;
+    }
+
+    /// Applies `config` to the running backend when it only changes
+    /// settings the backend can take in place, which is the case for an
+    /// embedded queue kept at the same path: RocksDB holds that directory
+    /// locked, so a second queue cannot open it while the first one runs.
+    /// Returns whether the config was applied.
+    pub async fn reconfigure(&self, config: &AdapterConfig, project_root: &Path) -> bool {
+        self.backend().await.reconfigure(config, project_root)
+    }
+
+    /// Publish a message to a topic
+    pub async fn publish(&self, topic: impl Into<String>, payload: Value) -> Result<()> {
+        self.backend().await.publish(topic, payload).await
+    }
+
+    /// Publish a message to a topic with optional adapter type override
+    pub async fn publish_with_type(
+        &self,
+        topic: impl Into<String>,
+        payload: Value,
+        adapter_type: Option<&str>,
+    ) -> Result<()> {
+        self.backend()
+            .await
+            .publish_with_type(topic, payload, adapter_type)
+            .await
+    }
+
+    /// Publish a message carrying metadata, such as trace context
+    pub async fn publish_with_metadata(
+        &self,
+        topic: impl Into<String>,
+        payload: Value,
+        adapter_type: Option<&str>,
+        metadata: HashMap<String, String>,
+    ) -> Result<()> {
+        self.backend()
+            .await
+            .publish_with_metadata(topic, payload, adapter_type, metadata)
+            .await
+    }
+
+    /// Publish a message that is delivered once `due` has passed, on adapters
+    /// that [redeliver](Adapter::redelivers); others deliver it right away.
+    pub async fn publish_with_metadata_at(
+        &self,
+        topic: impl Into<String>,
+        payload: Value,
+        adapter_type: Option<&str>,
+        metadata: HashMap<String, String>,
+        due: std::time::SystemTime,
+    ) -> Result<()> {
+        self.backend()
+            .await
+            .publish_with_metadata_at(topic, payload, adapter_type, metadata, due)
+            .await
+    }
+
+    /// Subscribe to a topic with a closure handler
+    pub async fn subscribe_fn<F, Fut>(&self, topic: impl Into<String>, handler: F) -> Result<()>
+    where
+        F: Fn(adapter_memory::Message) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<()>> + Send + 'static,
+    {
+        self.backend().await.subscribe_fn(topic, handler).await
+    }
+
+    /// Subscribe to a topic with a closure handler and optional adapter type
+    pub async fn subscribe_with_type<F, Fut>(
+        &self,
+        topic: impl Into<String>,
+        handler: F,
+        adapter_type: Option<&str>,
+    ) -> Result<()>
+    where
+        F: Fn(adapter_memory::Message) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<()>> + Send + 'static,
+    {
+        self.subscribe_with_prefetch(topic, handler, adapter_type, 1).await
+    }
+
+    /// Subscribe with up to `prefetch` messages received and handled at once
+    pub async fn subscribe_with_prefetch<F, Fut>(
+        &self,
+        topic: impl Into<String>,
+        handler: F,
+        adapter_type: Option<&str>,
+        prefetch: usize,
+    ) -> Result<()>
+    where
+        F: Fn(adapter_memory::Message) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<()>> + Send + 'static,
+    {
+        self.backend()
+            .await
+            .subscribe_with_prefetch(topic, handler, adapter_type, prefetch)
+            .await
+    }
+
+    /// Get list of all topics
+    pub async fn list_topics(&self) -> Vec<String> {
+        self.backend().await.list_topics().await
+    }
+
+    /// Number of messages waiting on a topic, for adapters backed by a queue
+    /// that can report it.
+    pub async fn queue_depth(&self, topic: &str) -> Result<Option<u64>> {
+        self.backend().await.queue_depth(topic).await
+    }
+
+    /// Whether a message whose handler returns an error is delivered again.
+    pub async fn redelivers(&self) -> bool {
+        self.backend().await.redelivers()
+    }
+
+    /// The topic's dead letters, for adapters that keep them in-process.
+    pub async fn dead_letters(&self, topic: &str) -> Result<Option<Vec<DeadLetter>>> {
+        self.backend().await.dead_letters(topic).await
+    }
+
+}
+
+/// A message that failed on every delivery and was set aside by its adapter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeadLetter {
+    pub topic: String,
+    pub payload: Value,
+    pub timestamp: String,
+    pub metadata: HashMap<String, String>,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+}
 
 /// Enum wrapper for different adapter types
-pub enum Adapter {
+pub enum AdapterBackend {
     Memory(Arc<adapter_memory::MemoryAdapter>),
+    #[cfg(feature = "rocksdb")]
+    Embedded(Arc<adapter_rocksdb::queue::QueueAdapter>),
     Aws(Arc<adapter_aws::AwsAdapter>),
 }
 
-impl Adapter {
+impl AdapterBackend {
+    /// Builds the configured backend. Relative embedded queue paths are
+    /// under `project_root`.
+    #[cfg_attr(not(feature = "rocksdb"), allow(unused_variables))]
+    pub async fn from_config(config: &AdapterConfig, project_root: &Path) -> Result<Self> {
+        match &config.adapter_type {
+            AdapterType::Memory => {
+                info!("Using Memory adapter for event bus");
+                Ok(AdapterBackend::Memory(Arc::new(adapter_memory::MemoryAdapter::new(config.buffer_size))))
+            }
+            #[cfg(feature = "rocksdb")]
+            AdapterType::Embedded { path, max_retries, retry_delay_ms } => {
+                info!("Using embedded queue adapter for event bus");
+                let queue = adapter_rocksdb::queue::QueueAdapter::open(adapter_rocksdb::queue::QueueConfig {
+                    path: project_root.join(path),
+                    max_retries: *max_retries,
+                    retry_delay: std::time::Duration::from_millis(*retry_delay_ms),
+                })
+                .map_err(|e| EngineError::Initialization(format!("Failed to open embedded queue: {}", e)))?;
+                Ok(AdapterBackend::Embedded(Arc::new(queue)))
+            }
+            #[cfg(not(feature = "rocksdb"))]
+            AdapterType::Embedded { .. } => Err(EngineError::Initialization(
+                "The embedded adapter needs RocksDB; build rohas with the `rocksdb` feature".to_string(),
+            )),
+            AdapterType::Aws { region, aws_type, queue_prefix, event_bus_name, source } => {
+                info!("Initializing AWS adapter - region: {}, default type: {}", region, aws_type);
+                let adapter_type = match aws_type.as_str() {
+                    "sqs" => adapter_aws::AwsAdapterType::Sqs,
+                    "eventbridge" => adapter_aws::AwsAdapterType::EventBridge,
+                    _ => return Err(EngineError::Initialization(format!("Unsupported AWS adapter type: {}", aws_type))),
+                };
+                let aws_config = adapter_aws::AwsConfig {
+                    region: region.clone(),
+                    queue_prefix: queue_prefix.clone(),
+                    event_bus_name: event_bus_name.clone(),
+                    source: source.clone(),
+                    codec: config.codec,
+                    ..Default::default()
+                };
+                let aws_adapter = adapter_aws::AwsAdapter::new_with_both(adapter_type, aws_config)
+                    .await
+                    .map_err(|e| EngineError::Initialization(format!("Failed to initialize AWS adapter: {}", e)))?;
+                info!("AWS adapter (both SQS and EventBridge) initialized successfully with default type: {}", aws_type);
+                Ok(AdapterBackend::Aws(Arc::new(aws_adapter)))
+            }
+            AdapterType::Nats { .. } => {
+                Err(EngineError::Initialization("NATS adapter not yet implemented".to_string()))
+            }
+            AdapterType::Kafka { .. } => {
+                Err(EngineError::Initialization("Kafka adapter not yet implemented".to_string()))
+            }
+            AdapterType::RabbitMQ { .. } => {
+                Err(EngineError::Initialization("RabbitMQ adapter not yet implemented".to_string()))
+            }
+        }
+    }
+
+    #[cfg_attr(not(feature = "rocksdb"), allow(unused_variables))]
+    fn reconfigure(&self, config: &AdapterConfig, project_root: &Path) -> bool {
+        match (self, &config.adapter_type) {
+            #[cfg(feature = "rocksdb")]
+            (AdapterBackend::Embedded(adapter), AdapterType::Embedded { path, max_retries, retry_delay_ms })
+                if adapter.path() == project_root.join(path) =>
+            {
+                adapter.set_retries(*max_retries, std::time::Duration::from_millis(*retry_delay_ms));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Stops the backend's subscriptions once their current messages are
+    /// handled.
+    async fn close(&self) {
+        match self {
+            AdapterBackend::Memory(adapter) => adapter.close().await,
+            #[cfg(feature = "rocksdb")]
+            AdapterBackend::Embedded(adapter) => adapter.close(),
+            AdapterBackend::Aws(adapter) => adapter.close(),
+        }
+    }
+
     /// Publish a message to a topic
     pub async fn publish(&self, topic: impl Into<String>, payload: Value) -> Result<()> {
         self.publish_with_type(topic, payload, None).await
@@ -20,23 +270,41 @@ impl Adapter {
         topic: impl Into<String>,
         payload: Value,
         adapter_type: Option<&str>,
+    ) -> Result<()> {
+        self.publish_with_metadata(topic, payload, adapter_type, HashMap::new()).await
+    }
+
+    /// Publish a message carrying metadata, such as trace context
+    pub async fn publish_with_metadata(
+        &self,
+        topic: impl Into<String>,
+        payload: Value,
+        adapter_type: Option<&str>,
+        metadata: HashMap<String, String>,
     ) -> Result<()> {
         let topic_str = topic.into();
         match self {
-            Adapter::Memory(adapter) => {
+            AdapterBackend::Memory(adapter) => {
                 tracing::debug!("Publishing to Memory adapter - topic: {}", topic_str);
-                adapter.publish(topic_str, payload)
+                adapter.publish_with_metadata(topic_str, payload, metadata)
                     .await
                     .map_err(|e| crate::error::EngineError::Adapter(e.to_string()))
             }
-            Adapter::Aws(adapter) => {
+            #[cfg(feature = "rocksdb")]
+            AdapterBackend::Embedded(adapter) => {
+                tracing::debug!("Publishing to embedded queue - topic: {}", topic_str);
+                adapter.publish_with_metadata(topic_str, payload, metadata)
+                    .await
+                    .map_err(|e| crate::error::EngineError::Adapter(e.to_string()))
+            }
+            AdapterBackend::Aws(adapter) => {
                 let topic_clone = topic_str.clone();
                 if let Some(adapter_type) = adapter_type {
                     tracing::info!("Publishing to AWS adapter (type: {}) - topic: {}", adapter_type, topic_str);
                 } else {
                     tracing::info!("Publishing to AWS adapter - topic: {}", topic_str);
                 }
-                adapter.publish_with_type(topic_str, payload, adapter_type)
+                adapter.publish_with_metadata(topic_str, payload, adapter_type, metadata)
                     .await
                     .map_err(|e| {
                         tracing::error!("AWS adapter publish failed for topic {}: {}", topic_clone, e);
@@ -46,6 +314,31 @@ impl Adapter {
         }
     }
 
+    /// Publish a message that is delivered once `due` has passed. Only the
+    /// embedded queue schedules messages; other adapters deliver it now.
+    #[cfg_attr(not(feature = "rocksdb"), allow(unused_variables))]
+    pub async fn publish_with_metadata_at(
+        &self,
+        topic: impl Into<String>,
+        payload: Value,
+        adapter_type: Option<&str>,
+        metadata: HashMap<String, String>,
+        due: std::time::SystemTime,
+    ) -> Result<()> {
+        match self {
+            #[cfg(feature = "rocksdb")]
+            AdapterBackend::Embedded(adapter) => {
+                let topic = topic.into();
+                tracing::debug!("Scheduling a message on embedded queue - topic: {}", topic);
+                adapter
+                    .publish_with_metadata_at(topic, payload, metadata, due)
+                    .await
+                    .map_err(|e| crate::error::EngineError::Adapter(e.to_string()))
+            }
+            _ => self.publish_with_metadata(topic, payload, adapter_type, metadata).await,
+        }
+    }
+
     /// Subscribe to a topic with a closure handler
     pub async fn subscribe_fn<F, Fut>(&self, topic: impl Into<String>, handler: F) -> Result<()>
     where
@@ -62,26 +355,60 @@ impl Adapter {
         handler: F,
         adapter_type: Option<&str>,
     ) -> Result<()>
+    where
+        F: Fn(adapter_memory::Message) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<()>> + Send + 'static,
+    {
+        self.subscribe_with_prefetch(topic, handler, adapter_type, 1).await
+    }
+
+    /// Subscribe with up to `prefetch` messages received and handled at
+    /// once. Every backend acks a message only after its handler returns.
+    pub async fn subscribe_with_prefetch<F, Fut>(
+        &self,
+        topic: impl Into<String>,
+        handler: F,
+        adapter_type: Option<&str>,
+        prefetch: usize,
+    ) -> Result<()>
     where
         F: Fn(adapter_memory::Message) -> Fut + Send + Sync + 'static,
         Fut: std::future::Future<Output = Result<()>> + Send + 'static,
     {
         match self {
-            Adapter::Memory(adapter) => {
-                adapter.subscribe_fn(topic, move |msg| {
+            AdapterBackend::Memory(adapter) => {
+                adapter.subscribe_fn_with_prefetch(topic, move |msg| {
                     let fut = handler(msg);
                     async move {
                         fut.await.map_err(|e| {
                             adapter_memory::AdapterError::ChannelError(e.to_string())
                         })
                     }
-                })
+                }, prefetch)
                 .await
                 .map_err(|e| crate::error::EngineError::Adapter(e.to_string()))
             }
-            Adapter::Aws(adapter) => {
+            #[cfg(feature = "rocksdb")]
+            AdapterBackend::Embedded(adapter) => {
+                adapter.subscribe_fn_with_prefetch(topic, move |queued| {
+                    let fut = handler(adapter_memory::Message {
+                        topic: queued.topic,
+                        payload: queued.payload,
+                        timestamp: queued.timestamp,
+                        metadata: queued.metadata,
+                    });
+                    async move {
+                        fut.await.map_err(|e| {
+                            adapter_rocksdb::queue::QueueError::Handler(e.to_string())
+                        })
+                    }
+                }, prefetch)
+                .await
+                .map_err(|e| crate::error::EngineError::Adapter(e.to_string()))
+            }
+            AdapterBackend::Aws(adapter) => {
                 // Convert adapter_memory::Message to adapter_aws::Message
-                adapter.subscribe_with_type(topic, move |aws_msg| {
+                adapter.subscribe_with_prefetch(topic, move |aws_msg| {
                     let fut = handler(adapter_memory::Message {
                         topic: aws_msg.topic,
                         payload: aws_msg.payload,
@@ -93,7 +420,7 @@ impl Adapter {
                             adapter_aws::common::AdapterError::AwsSqs(e.to_string())
                         })
                     }
-                }, adapter_type)
+                }, adapter_type, prefetch)
                 .await
                 .map_err(|e| crate::error::EngineError::Adapter(e.to_string()))
             }
@@ -103,8 +430,67 @@ impl Adapter {
     /// Get list of all topics
     pub async fn list_topics(&self) -> Vec<String> {
         match self {
-            Adapter::Memory(adapter) => adapter.list_topics().await,
-            Adapter::Aws(adapter) => adapter.list_topics().await,
+            AdapterBackend::Memory(adapter) => adapter.list_topics().await,
+            #[cfg(feature = "rocksdb")]
+            AdapterBackend::Embedded(adapter) => adapter.list_topics().await,
+            AdapterBackend::Aws(adapter) => adapter.list_topics().await,
+        }
+    }
+
+    /// Number of messages waiting on a topic, for adapters backed by a queue
+    /// that can report it.
+    pub async fn queue_depth(&self, topic: &str) -> Result<Option<u64>> {
+        match self {
+            AdapterBackend::Memory(_) => Ok(None),
+            #[cfg(feature = "rocksdb")]
+            AdapterBackend::Embedded(adapter) => adapter
+                .queue_depth(topic)
+                .await
+                .map(Some)
+                .map_err(|e| crate::error::EngineError::Adapter(e.to_string())),
+            AdapterBackend::Aws(adapter) => adapter
+                .queue_depth(topic)
+                .await
+                .map_err(|e| crate::error::EngineError::Adapter(e.to_string())),
+        }
+    }
+
+    /// Only the embedded queue delivers a message again when its handler
+    /// returns an error; memory subscriptions drop it and SQS deletes it.
+    pub fn redelivers(&self) -> bool {
+        match self {
+            #[cfg(feature = "rocksdb")]
+            AdapterBackend::Embedded(_) => true,
+            _ => false,
+        }
+    }
+
+    /// The topic's dead letters, oldest first. Only the embedded queue keeps
+    /// any.
+    #[cfg_attr(not(feature = "rocksdb"), allow(unused_variables))]
+    pub async fn dead_letters(&self, topic: &str) -> Result<Option<Vec<DeadLetter>>> {
+        match self {
+            #[cfg(feature = "rocksdb")]
+            AdapterBackend::Embedded(adapter) => {
+                let messages = adapter
+                    .dead_letters(topic)
+                    .await
+                    .map_err(|e| crate::error::EngineError::Adapter(e.to_string()))?;
+                Ok(Some(
+                    messages
+                        .into_iter()
+                        .map(|message| DeadLetter {
+                            topic: message.topic,
+                            payload: message.payload,
+                            timestamp: message.timestamp,
+                            metadata: message.metadata,
+                            attempts: message.attempts,
+                            last_error: message.last_error,
+                        })
+                        .collect(),
+                ))
+            }
+            _ => Ok(None),
         }
     }
 }
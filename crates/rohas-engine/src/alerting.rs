@@ -0,0 +1,649 @@
+use crate::adapter::Adapter;
+use crate::config::{AlertChannelConfig, AlertChannelType, AlertRuleConfig, AlertRuleType, AlertingConfig};
+use crate::quarantine::{QuarantineStore, QuarantinedMessage};
+use crate::telemetry::TraceStore;
+use crate::trace::{TraceEntryType, TraceRecord, TraceStatus};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tracing::{error, info, warn};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Alert {
+    pub rule: String,
+    pub message: String,
+    pub fired_at: String,
+}
+
+#[derive(Default)]
+struct RuleState {
+    firing: bool,
+    last_notified: Option<Instant>,
+}
+
+impl RuleState {
+    /// Records whether the rule fires at `now` and returns whether to notify:
+    /// when it starts firing, then once per `cooldown` while it keeps firing.
+    fn observe(&mut self, firing: bool, cooldown: Duration, now: Instant) -> bool {
+        if !firing {
+            self.firing = false;
+            return false;
+        }
+        let notify = !self.firing
+            || self
+                .last_notified
+                .map(|at| now.duration_since(at) >= cooldown)
+                .unwrap_or(true);
+        self.firing = true;
+        if notify {
+            self.last_notified = Some(now);
+        }
+        notify
+    }
+}
+
+/// Dead letter counts of a topic, oldest first, going back to the last one
+/// taken before the window.
+#[derive(Default)]
+struct DeadLetterSamples(VecDeque<(DateTime<Utc>, usize)>);
+
+impl DeadLetterSamples {
+    /// Records `count` at `now` and returns how much it grew since
+    /// `window_start`, or since the first sample when there is none that old.
+    fn growth(&mut self, count: usize, now: DateTime<Utc>, window_start: DateTime<Utc>) -> usize {
+        // Dead letters were replayed or discarded: count from here.
+        if self.0.back().is_some_and(|(_, last)| count < *last) {
+            self.0.clear();
+        }
+        self.0.push_back((now, count));
+        while self.0.get(1).is_some_and(|(at, _)| *at <= window_start) {
+            self.0.pop_front();
+        }
+        count.saturating_sub(self.0.front().map(|(_, count)| *count).unwrap_or(count))
+    }
+}
+
+/// Periodically evaluates the alert rules from `rohas.toml` against recent
+/// traces, quarantined messages and dead letters, and sends notifications to
+/// the configured channels.
+pub struct AlertManager {
+    config: AlertingConfig,
+    trace_store: Arc<TraceStore>,
+    quarantine: Option<Arc<QuarantineStore>>,
+    adapter: Option<Arc<Adapter>>,
+    client: reqwest::Client,
+    state: Mutex<HashMap<String, RuleState>>,
+    dead_letters: Mutex<HashMap<String, DeadLetterSamples>>,
+}
+
+impl AlertManager {
+    pub fn new(config: AlertingConfig, trace_store: Arc<TraceStore>) -> Self {
+        Self {
+            config,
+            trace_store,
+            quarantine: None,
+            adapter: None,
+            client: reqwest::Client::new(),
+            state: Mutex::new(HashMap::new()),
+            dead_letters: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The quarantine `quarantine` rules count messages in.
+    pub fn with_quarantine(mut self, quarantine: Arc<QuarantineStore>) -> Self {
+        self.quarantine = Some(quarantine);
+        self
+    }
+
+    /// The adapter whose dead letters `dead_letter_growth` rules watch.
+    pub fn with_adapter(mut self, adapter: Arc<Adapter>) -> Self {
+        self.adapter = Some(adapter);
+        self
+    }
+
+    pub fn start(self: Arc<Self>) {
+        let interval = Duration::from_secs(self.config.check_interval_seconds.max(1));
+        info!(
+            "Started alerting task ({} rules, checking every {}s)",
+            self.config.rules.len(),
+            interval.as_secs()
+        );
+
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                self.evaluate().await;
+            }
+        });
+    }
+
+    pub async fn evaluate(&self) {
+        let traces = self.trace_store.get_traces(None).await;
+        let quarantined = match &self.quarantine {
+            Some(quarantine) => quarantine.list().await,
+            None => Vec::new(),
+        };
+        let now = Utc::now();
+
+        for rule in &self.config.rules {
+            let window_start = now - chrono::Duration::seconds(rule.window_seconds as i64);
+            let dead_letter_growth = match &rule.rule_type {
+                AlertRuleType::DeadLetterGrowth { topic, .. } => self.dead_letter_growth(topic, now, window_start).await,
+                _ => 0,
+            };
+            let message = check_rule(
+                rule,
+                &started_since(&traces, window_start),
+                &quarantined_since(&quarantined, window_start),
+                dead_letter_growth,
+            );
+            let should_notify = {
+                let mut state = self.state.lock().await;
+                let rule_state = state.entry(rule.name.clone()).or_default();
+                if message.is_none() && rule_state.firing {
+                    info!("Alert '{}' resolved", rule.name);
+                }
+                rule_state.observe(
+                    message.is_some(),
+                    Duration::from_secs(rule.cooldown_seconds),
+                    Instant::now(),
+                )
+            };
+
+            if let (true, Some(message)) = (should_notify, message) {
+                let alert = Alert {
+                    rule: rule.name.clone(),
+                    message,
+                    fired_at: now.to_rfc3339(),
+                };
+                warn!("Alert '{}' fired: {}", alert.rule, alert.message);
+                self.dispatch(rule, &alert).await;
+            }
+        }
+    }
+
+    /// How many dead letters `topic` gained since `window_start`. Adapters
+    /// that keep no dead letters never gain any.
+    async fn dead_letter_growth(&self, topic: &str, now: DateTime<Utc>, window_start: DateTime<Utc>) -> usize {
+        let Some(adapter) = &self.adapter else {
+            return 0;
+        };
+        let count = match adapter.dead_letters(topic).await {
+            Ok(Some(dead_letters)) => dead_letters.len(),
+            Ok(None) => return 0,
+            Err(e) => {
+                error!("Failed to read the dead letters of {}: {}", topic, e);
+                return 0;
+            }
+        };
+        self.dead_letters
+            .lock()
+            .await
+            .entry(topic.to_string())
+            .or_default()
+            .growth(count, now, window_start)
+    }
+
+    async fn dispatch(&self, rule: &AlertRuleConfig, alert: &Alert) {
+        for channel_name in &rule.channels {
+            let Some(channel) = self.config.channels.iter().find(|c| &c.name == channel_name) else {
+                continue;
+            };
+
+            if let Err(e) = self.send(channel, alert).await {
+                error!("Failed to send alert '{}' to channel '{}': {}", alert.rule, channel.name, e);
+            }
+        }
+    }
+
+    async fn send(&self, channel: &AlertChannelConfig, alert: &Alert) -> anyhow::Result<()> {
+        match &channel.channel_type {
+            AlertChannelType::Slack { webhook_url } => {
+                let body = serde_json::json!({
+                    "text": format!(":rotating_light: *{}*\n{}", alert.rule, alert.message),
+                });
+                self.client
+                    .post(webhook_url)
+                    .json(&body)
+                    .send()
+                    .await?
+                    .error_for_status()?;
+            }
+            AlertChannelType::Webhook { url, headers } => {
+                let mut request = self.client.post(url).json(alert);
+                for (name, value) in headers {
+                    request = request.header(name, value);
+                }
+                request.send().await?.error_for_status()?;
+            }
+            AlertChannelType::Email {
+                smtp_host,
+                smtp_port,
+                username,
+                password,
+                from,
+                to,
+            } => {
+                use lettre::transport::smtp::authentication::Credentials;
+                use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+                let mut builder = Message::builder()
+                    .from(from.parse()?)
+                    .subject(format!("[rohas] Alert: {}", alert.rule));
+                for recipient in to {
+                    builder = builder.to(recipient.parse()?);
+                }
+                let email = builder.body(format!("{}\n\nFired at {}", alert.message, alert.fired_at))?;
+
+                let mut transport =
+                    AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(smtp_host)?.port(*smtp_port);
+                if let (Some(username), Some(password)) = (username, password) {
+                    transport = transport.credentials(Credentials::new(username.clone(), password.clone()));
+                }
+                transport.build().send(email).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn started_since(traces: &[TraceRecord], since: DateTime<Utc>) -> Vec<&TraceRecord> {
+    traces
+        .iter()
+        .filter(|t| {
+            DateTime::parse_from_rfc3339(&t.started_at)
+                .map(|started| started >= since)
+                .unwrap_or(false)
+        })
+        .collect()
+}
+
+fn quarantined_since(messages: &[QuarantinedMessage], since: DateTime<Utc>) -> Vec<&QuarantinedMessage> {
+    messages.iter().filter(|m| m.quarantined_at >= since).collect()
+}
+
+fn check_rule(
+    rule: &AlertRuleConfig,
+    traces: &[&TraceRecord],
+    quarantined: &[&QuarantinedMessage],
+    dead_letter_growth: usize,
+) -> Option<String> {
+    match &rule.rule_type {
+        AlertRuleType::ErrorRate {
+            threshold,
+            min_requests,
+            handler,
+        } => {
+            let matching: Vec<&&TraceRecord> = traces
+                .iter()
+                .filter(|t| !matches!(t.status, TraceStatus::Running))
+                .filter(|t| match handler {
+                    Some(handler) => {
+                        &t.entry_point == handler || t.steps.iter().any(|s| &s.handler_name == handler)
+                    }
+                    None => true,
+                })
+                .collect();
+
+            if matching.is_empty() || matching.len() < *min_requests {
+                return None;
+            }
+
+            let failed = matching
+                .iter()
+                .filter(|t| matches!(t.status, TraceStatus::Failed))
+                .count();
+            let rate = failed as f64 / matching.len() as f64;
+
+            (rate > *threshold).then(|| {
+                format!(
+                    "Error rate {:.1}% over the last {}s exceeds {:.1}% ({} of {} requests failed{})",
+                    rate * 100.0,
+                    rule.window_seconds,
+                    threshold * 100.0,
+                    failed,
+                    matching.len(),
+                    handler
+                        .as_ref()
+                        .map(|h| format!(", handler {}", h))
+                        .unwrap_or_default()
+                )
+            })
+        }
+        AlertRuleType::CronFailure { cron } => {
+            let failures: Vec<&str> = traces
+                .iter()
+                .filter(|t| matches!(t.entry_type, TraceEntryType::Cron))
+                .filter(|t| matches!(t.status, TraceStatus::Failed))
+                .filter(|t| cron.as_ref().map(|c| &t.entry_point == c).unwrap_or(true))
+                .map(|t| t.entry_point.as_str())
+                .collect();
+
+            if failures.is_empty() {
+                return None;
+            }
+
+            let mut names: Vec<&str> = failures.clone();
+            names.sort_unstable();
+            names.dedup();
+
+            Some(format!(
+                "{} cron execution(s) failed in the last {}s: {}",
+                failures.len(),
+                rule.window_seconds,
+                names.join(", ")
+            ))
+        }
+        AlertRuleType::Quarantine { event, threshold } => {
+            let mut events: Vec<&str> = quarantined
+                .iter()
+                .filter(|m| event.as_ref().map(|e| &m.event == e).unwrap_or(true))
+                .map(|m| m.event.as_str())
+                .collect();
+            let count = events.len();
+            if count <= *threshold {
+                return None;
+            }
+
+            events.sort_unstable();
+            events.dedup();
+            Some(format!(
+                "{} message(s) quarantined in the last {}s, more than {}: {}",
+                count,
+                rule.window_seconds,
+                threshold,
+                events.join(", ")
+            ))
+        }
+        AlertRuleType::DeadLetterGrowth { topic, threshold } => (dead_letter_growth > *threshold).then(|| {
+            format!(
+                "{} dead letter(s) added to {} in the last {}s, more than {}",
+                dead_letter_growth, topic, rule.window_seconds, threshold
+            )
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TraceSamplingConfig;
+    use crate::quarantine::PoisonKind;
+    use crate::telemetry::TelemetryManager;
+    use crate::trace::TraceStep;
+
+    fn trace(entry_point: &str, entry_type: TraceEntryType, status: TraceStatus) -> TraceRecord {
+        TraceRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            entry_point: entry_point.to_string(),
+            entry_type,
+            status,
+            duration_ms: 5,
+            started_at: Utc::now().to_rfc3339(),
+            completed_at: None,
+            steps: Vec::new(),
+            error: None,
+            metadata: HashMap::new(),
+        }
+    }
+
+    fn api(entry_point: &str, failed: bool) -> TraceRecord {
+        let status = if failed { TraceStatus::Failed } else { TraceStatus::Success };
+        trace(entry_point, TraceEntryType::Api, status)
+    }
+
+    fn rule(rule_type: AlertRuleType) -> AlertRuleConfig {
+        AlertRuleConfig {
+            name: "errors".to_string(),
+            rule_type,
+            channels: Vec::new(),
+            window_seconds: 300,
+            cooldown_seconds: 900,
+        }
+    }
+
+    fn error_rate(threshold: f64, min_requests: usize, handler: Option<&str>) -> AlertRuleConfig {
+        rule(AlertRuleType::ErrorRate {
+            threshold,
+            min_requests,
+            handler: handler.map(str::to_string),
+        })
+    }
+
+    fn check(rule: &AlertRuleConfig, traces: &[TraceRecord]) -> Option<String> {
+        check_rule(rule, &traces.iter().collect::<Vec<_>>(), &[], 0)
+    }
+
+    fn quarantined(event: &str) -> QuarantinedMessage {
+        QuarantinedMessage::new(event, PoisonKind::Handler, serde_json::json!({}), "declined", 3)
+    }
+
+    fn check_quarantine(rule: &AlertRuleConfig, messages: &[QuarantinedMessage]) -> Option<String> {
+        check_rule(rule, &[], &messages.iter().collect::<Vec<_>>(), 0)
+    }
+
+    #[test]
+    fn test_error_rate_fires_above_the_threshold() {
+        let traces = [api("CreateOrder", true), api("CreateOrder", false), api("CreateOrder", false)];
+
+        let message = check(&error_rate(0.25, 1, None), &traces).unwrap();
+        assert_eq!(
+            message,
+            "Error rate 33.3% over the last 300s exceeds 25.0% (1 of 3 requests failed)"
+        );
+        assert_eq!(check(&error_rate(0.5, 1, None), &traces), None);
+    }
+
+    #[test]
+    fn test_error_rate_at_the_threshold_does_not_fire() {
+        let traces = [api("CreateOrder", true), api("CreateOrder", false)];
+        assert_eq!(check(&error_rate(0.5, 1, None), &traces), None);
+    }
+
+    #[test]
+    fn test_error_rate_waits_for_min_requests() {
+        let traces = [api("CreateOrder", true), api("CreateOrder", true)];
+        assert_eq!(check(&error_rate(0.1, 3, None), &traces), None);
+        assert!(check(&error_rate(0.1, 2, None), &traces).is_some());
+        assert_eq!(check(&error_rate(0.1, 0, None), &[]), None);
+    }
+
+    #[test]
+    fn test_error_rate_ignores_running_traces() {
+        let traces = [
+            api("CreateOrder", true),
+            trace("CreateOrder", TraceEntryType::Api, TraceStatus::Running),
+            trace("CreateOrder", TraceEntryType::Api, TraceStatus::Running),
+        ];
+        let message = check(&error_rate(0.5, 1, None), &traces).unwrap();
+        assert!(message.contains("(1 of 1 requests failed)"), "{}", message);
+    }
+
+    #[test]
+    fn test_error_rate_of_one_handler() {
+        let mut charged = trace("OrderPlaced", TraceEntryType::Event, TraceStatus::Failed);
+        charged.steps.push(TraceStep {
+            name: "charge_card".to_string(),
+            handler_name: "charge_card".to_string(),
+            duration_ms: 5,
+            success: false,
+            error: Some("declined".to_string()),
+            timestamp: Utc::now().to_rfc3339(),
+            triggered_events: Vec::new(),
+        });
+        let traces = [charged, api("CreateOrder", false), api("ListOrders", true)];
+
+        let message = check(&error_rate(0.5, 1, Some("charge_card")), &traces).unwrap();
+        assert!(message.ends_with("(1 of 1 requests failed, handler charge_card)"), "{}", message);
+        assert_eq!(check(&error_rate(0.5, 1, Some("CreateOrder")), &traces), None);
+        assert!(check(&error_rate(0.5, 1, Some("ListOrders")), &traces).is_some());
+    }
+
+    #[test]
+    fn test_cron_failure_lists_each_failed_job_once() {
+        let traces = [
+            trace("nightly_report", TraceEntryType::Cron, TraceStatus::Failed),
+            trace("cleanup", TraceEntryType::Cron, TraceStatus::Failed),
+            trace("nightly_report", TraceEntryType::Cron, TraceStatus::Failed),
+            trace("hourly_sync", TraceEntryType::Cron, TraceStatus::Success),
+            api("nightly_report", true),
+        ];
+
+        let any = rule(AlertRuleType::CronFailure { cron: None });
+        assert_eq!(
+            check(&any, &traces).unwrap(),
+            "3 cron execution(s) failed in the last 300s: cleanup, nightly_report"
+        );
+
+        let sync = rule(AlertRuleType::CronFailure {
+            cron: Some("hourly_sync".to_string()),
+        });
+        assert_eq!(check(&sync, &traces), None);
+    }
+
+    #[test]
+    fn test_quarantine_fires_above_the_threshold() {
+        let messages = [quarantined("OrderPlaced"), quarantined("OrderShipped"), quarantined("OrderPlaced")];
+
+        let any = rule(AlertRuleType::Quarantine { event: None, threshold: 2 });
+        assert_eq!(
+            check_quarantine(&any, &messages).unwrap(),
+            "3 message(s) quarantined in the last 300s, more than 2: OrderPlaced, OrderShipped"
+        );
+        let placed = rule(AlertRuleType::Quarantine {
+            event: Some("OrderPlaced".to_string()),
+            threshold: 2,
+        });
+        assert_eq!(check_quarantine(&placed, &messages), None);
+        let shipped = rule(AlertRuleType::Quarantine {
+            event: Some("OrderShipped".to_string()),
+            threshold: 0,
+        });
+        assert!(check_quarantine(&shipped, &messages).is_some());
+    }
+
+    #[test]
+    fn test_quarantined_since_drops_older_messages() {
+        let now = Utc::now();
+        let mut old = quarantined("OrderPlaced");
+        old.quarantined_at = now - chrono::Duration::seconds(301);
+        let messages = [old, quarantined("OrderShipped")];
+
+        let recent = quarantined_since(&messages, now - chrono::Duration::seconds(300));
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].event, "OrderShipped");
+    }
+
+    #[test]
+    fn test_dead_letter_growth_fires_above_the_threshold() {
+        let growth = rule(AlertRuleType::DeadLetterGrowth {
+            topic: "OrderPlaced".to_string(),
+            threshold: 2,
+        });
+        assert_eq!(
+            check_rule(&growth, &[], &[], 3).unwrap(),
+            "3 dead letter(s) added to OrderPlaced in the last 300s, more than 2"
+        );
+        assert_eq!(check_rule(&growth, &[], &[], 2), None);
+    }
+
+    #[test]
+    fn test_dead_letter_growth_is_measured_over_the_window() {
+        let start = Utc::now();
+        let at = |seconds| start + chrono::Duration::seconds(seconds);
+        let window = chrono::Duration::seconds(60);
+        let mut samples = DeadLetterSamples::default();
+
+        // Dead letters from before the first sample are not growth.
+        assert_eq!(samples.growth(10, at(0), at(0) - window), 0);
+        assert_eq!(samples.growth(12, at(30), at(30) - window), 2);
+        assert_eq!(samples.growth(15, at(60), at(60) - window), 5);
+        // Once a sample is older than the window, the growth is counted from it.
+        assert_eq!(samples.growth(15, at(90), at(90) - window), 3);
+        assert_eq!(samples.growth(15, at(150), at(150) - window), 0);
+        // Growth after dead letters are replayed counts from the replay.
+        assert_eq!(samples.growth(4, at(160), at(160) - window), 0);
+        assert_eq!(samples.growth(6, at(170), at(170) - window), 2);
+    }
+
+    #[test]
+    fn test_rule_state_notifies_once_per_cooldown() {
+        let cooldown = Duration::from_secs(60);
+        let start = Instant::now();
+        let mut state = RuleState::default();
+
+        assert!(!state.observe(false, cooldown, start));
+        assert!(state.observe(true, cooldown, start));
+        assert!(!state.observe(true, cooldown, start + Duration::from_secs(59)));
+        assert!(state.observe(true, cooldown, start + Duration::from_secs(60)));
+        assert!(!state.observe(true, cooldown, start + Duration::from_secs(61)));
+
+        // Resolving and firing again notifies without waiting out the cooldown.
+        assert!(!state.observe(false, cooldown, start + Duration::from_secs(62)));
+        assert!(state.observe(true, cooldown, start + Duration::from_secs(63)));
+    }
+
+    #[test]
+    fn test_started_since_drops_older_and_unparsable_traces() {
+        let now = Utc::now();
+        let mut old = api("old", true);
+        old.started_at = (now - chrono::Duration::seconds(301)).to_rfc3339();
+        let mut garbled = api("garbled", true);
+        garbled.started_at = "yesterday".to_string();
+        let traces = [old, garbled, api("new", true)];
+
+        let recent = started_since(&traces, now - chrono::Duration::seconds(300));
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].entry_point, "new");
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_fires_and_resolves() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let telemetry = Arc::new(TelemetryManager::new(dir.path().to_path_buf(), 0).await.unwrap());
+        let store = Arc::new(TraceStore::new(telemetry, TraceSamplingConfig::default()));
+        let config = AlertingConfig {
+            enabled: true,
+            rules: vec![error_rate(0.5, 1, None)],
+            ..AlertingConfig::default()
+        };
+        let manager = AlertManager::new(config, store.clone());
+
+        let id = store.start_trace("CreateOrder".to_string(), TraceEntryType::Api, HashMap::new()).await;
+        store.complete_trace(&id, TraceStatus::Failed, Some("boom".to_string())).await;
+        manager.evaluate().await;
+        assert!(manager.state.lock().await["errors"].firing);
+
+        for _ in 0..2 {
+            let id = store.start_trace("CreateOrder".to_string(), TraceEntryType::Api, HashMap::new()).await;
+            store.complete_trace(&id, TraceStatus::Success, None).await;
+        }
+        manager.evaluate().await;
+        assert!(!manager.state.lock().await["errors"].firing);
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_counts_quarantined_messages() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let telemetry = Arc::new(TelemetryManager::new(dir.path().to_path_buf(), 0).await.unwrap());
+        let store = Arc::new(TraceStore::new(telemetry, TraceSamplingConfig::default()));
+        let quarantine = Arc::new(QuarantineStore::in_memory(10));
+        let config = AlertingConfig {
+            enabled: true,
+            rules: vec![rule(AlertRuleType::Quarantine { event: None, threshold: 0 })],
+            ..AlertingConfig::default()
+        };
+        let manager = AlertManager::new(config, store).with_quarantine(quarantine.clone());
+
+        manager.evaluate().await;
+        assert!(!manager.state.lock().await["errors"].firing);
+
+        quarantine.add(quarantined("OrderPlaced")).await.unwrap();
+        manager.evaluate().await;
+        assert!(manager.state.lock().await["errors"].firing);
+    }
+}
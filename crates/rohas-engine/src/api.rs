@@ -1,7 +1,9 @@
 use crate::ws;
+use adapter_codec::WireCodec;
 use axum::{
-    extract::{ws::WebSocketUpgrade, ConnectInfo, MatchedPath, Request, State},
-    http::StatusCode,
+    body::{Body, HttpBody},
+    extract::{ws::WebSocketUpgrade, ConnectInfo, MatchedPath, Query, Request, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
     routing::{delete, get, patch, post, put},
@@ -10,12 +12,13 @@ use axum::{
 use std::net::SocketAddr;
 use chrono::Utc;
 use rohas_codegen::templates;
-use rohas_parser::{HttpMethod, Schema};
-use rohas_runtime::Executor;
+use rohas_parser::{ContentType, HttpMethod, Schema};
+use rohas_runtime::{Executor, HandlerContext, HttpResponse, RawBody, SameSite, SetCookie};
 use serde_json::{json, Value};
 use std::{collections::HashMap, sync::Arc};
 use tracing::{debug, info_span};
 
+use crate::problem::{FieldError, Problem};
 use crate::{config, EngineConfig};
 
 #[derive(Clone)]
@@ -27,6 +30,10 @@ pub struct ApiState {
     pub trace_store: Arc<crate::telemetry::TraceStore>,
     pub tracing_log_store: Arc<crate::tracing_log::TracingLogStore>,
     pub workbench_auth: Arc<tokio::sync::RwLock<crate::workbench_auth::WorkbenchAuthConfig>>,
+    pub chaos: Arc<crate::chaos::ChaosController>,
+    pub event_metrics: Arc<crate::event_metrics::EventMetrics>,
+    pub ws_sessions: Arc<crate::ws_session::SessionRegistry>,
+    pub scheduler: Arc<rohas_cron::Scheduler>,
 }
 
 pub fn build_router(
@@ -36,19 +43,25 @@ pub fn build_router(
     event_bus: Arc<crate::event::EventBus>,
     trace_store: Arc<crate::telemetry::TraceStore>,
     tracing_log_store: Arc<crate::tracing_log::TracingLogStore>,
+    scheduler: Arc<rohas_cron::Scheduler>,
 ) -> Router {
     let mut router = Router::new();
     let workbench_auth_config =
         crate::workbench_auth::WorkbenchAuthConfig::from_engine_config(&config);
     let workbench_auth = Arc::new(tokio::sync::RwLock::new(workbench_auth_config));
+    let file_editing = config.dev.file_editing;
     let state = ApiState {
         executor,
         schema: schema.clone(),
         config,
+        chaos: event_bus.chaos(),
+        event_metrics: event_bus.metrics(),
+        ws_sessions: event_bus.ws_sessions(),
         event_bus,
         trace_store,
         tracing_log_store,
         workbench_auth: workbench_auth.clone(),
+        scheduler,
     };
     
 
@@ -82,21 +95,35 @@ pub fn build_router(
         );
 
         let ws_name = ws.name.clone();
-        let handler_router =
-            Router::new().route(
-                &route_path,
-                get(move |ws: WebSocketUpgrade, State(state): State<ApiState>| {
+        let handler_router = Router::new().route(
+            &route_path,
+            get(
+                move |upgrade: WebSocketUpgrade,
+                      State(state): State<ApiState>,
+                      ConnectInfo(addr): ConnectInfo<SocketAddr>,
+                      Query(query): Query<HashMap<String, String>>,
+                      headers: HeaderMap| {
                     let ws_name = ws_name.clone();
-                    async move {
-                        ws.on_upgrade(move |socket| ws::websocket_handler(socket, state, ws_name))
-                    }
-                }),
-            );
+                    async move { ws::websocket_upgrade(upgrade, state, ws_name, addr, headers, query).await }
+                },
+            ),
+        );
 
         router = router.merge(handler_router);
     }
 
-    let workbench_router = crate::workbench::workbench_routes();
+    router = router.route("/asyncapi.json", get(asyncapi_handler));
+
+    // Probes for orchestrators, unless the schema has APIs on those paths.
+    // The server only starts once events are subscribed, so it is ready as
+    // soon as it answers.
+    for (path, status) in [("/healthz", "ok"), ("/readyz", "ready")] {
+        if !schema.apis.iter().any(|api| normalize_path(&api.path) == path) {
+            router = router.route(path, get(move || async move { Json(json!({ "status": status })) }));
+        }
+    }
+
+    let workbench_router = crate::workbench::workbench_routes(file_editing);
     let auth_config_for_middleware = workbench_auth.clone();
     let workbench_router = workbench_router.layer(axum::middleware::from_fn(move |request: Request, next: Next| {
         let auth_config = auth_config_for_middleware.clone();
@@ -109,6 +136,23 @@ pub fn build_router(
     router.with_state(state)
 }
 
+async fn asyncapi_handler(State(state): State<ApiState>) -> Json<Value> {
+    Json(crate::asyncapi::document(&state.schema, &state.config))
+}
+
+/// Client IP address - proxy headers first, then the remote address of the
+/// connection.
+pub(crate) fn client_ip(headers: &HeaderMap, addr: SocketAddr) -> String {
+    let header_value = |name: &str| headers.get(name).and_then(|h| h.to_str().ok());
+
+    header_value("x-forwarded-for")
+        .and_then(|s| s.split(',').next())
+        .map(|s| s.trim().to_string())
+        .or_else(|| header_value("x-real-ip").map(|s| s.to_string()))
+        .or_else(|| header_value("cf-connecting-ip").map(|s| s.to_string()))
+        .unwrap_or_else(|| addr.ip().to_string())
+}
+
 /// Converts "/users/{id}" to "/users/:id" (Axum uses :param syntax)
 fn normalize_path(path: &str) -> String {
     let mut result = String::new();
@@ -132,7 +176,29 @@ fn normalize_path(path: &str) -> String {
     result
 }
 
+/// Serves an API route, rendering errors in the configured error format
+/// with the request path as the problem `instance`.
 async fn api_handler(
+    State(state): State<ApiState>,
+    matched_path: Option<MatchedPath>,
+    method: axum::http::Method,
+    connect_info: ConnectInfo<SocketAddr>,
+    request: Request,
+) -> Response {
+    let instance = request.uri().path().to_string();
+    let format = state.config.server.error_format;
+    let type_base = state.config.server.problem_type_base.clone();
+
+    match handle_api_request(State(state), matched_path, method, connect_info, request).await {
+        Ok(response) => response,
+        Err(e) => e
+            .into_problem(type_base.as_deref())
+            .with_instance(instance)
+            .into_response_as(format),
+    }
+}
+
+async fn handle_api_request(
     State(state): State<ApiState>,
     matched_path: Option<MatchedPath>,
     method: axum::http::Method,
@@ -158,32 +224,8 @@ async fn api_handler(
     metadata.insert("path".to_string(), path_pattern.to_string());
     metadata.insert("datetime_utc".to_string(), Utc::now().to_rfc3339());
 
-    // Extract IP address - check headers first, then fall back to remote address from extensions
-    let ip_address = if let Some(ip) = request.headers().get("x-forwarded-for")
-        .and_then(|h| h.to_str().ok())
-        .and_then(|s| s.split(',').next())
-        .map(|s| s.trim().to_string())
-    {
-        Some(ip)
-    } else if let Some(ip) = request.headers().get("x-real-ip")
-        .and_then(|h| h.to_str().ok())
-        .map(|s| s.to_string())
-    {
-        Some(ip)
-    } else if let Some(ip) = request.headers().get("cf-connecting-ip")
-        .and_then(|h| h.to_str().ok())
-        .map(|s| s.to_string())
-    {
-        Some(ip)
-    } else {
-        // Fallback to remote address from ConnectInfo
-        Some(addr.ip().to_string())
-    };
-    
-    if let Some(ip) = ip_address {
-        metadata.insert("ip_address".to_string(), ip);
-    }
-    
+    metadata.insert("ip_address".to_string(), client_ip(request.headers(), addr));
+
     if let Some(user_agent) = request.headers().get("user-agent")
         .and_then(|h| h.to_str().ok())
         .map(|s| s.to_string())
@@ -271,11 +313,28 @@ async fn api_handler(
     let normalized_api_path = normalize_path(&api_path);
     let path_params = extract_path_params(&normalized_api_path, request.uri().path());
 
-    let query_params = request
+    let mut query_params = request
         .uri()
         .query()
         .map(|q| parse_query_string(q))
         .unwrap_or_default();
+    if api.paginated {
+        if let Err(e) = apply_page_limit(&mut query_params) {
+            state
+                .trace_store
+                .complete_trace(&trace_id, crate::trace::TraceStatus::Failed, Some(e.message()))
+                .await;
+            return Err(e);
+        }
+    }
+
+    let cookies = parse_cookies(request.headers());
+
+    let request_content_type = request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|h| h.to_str().ok())
+        .map(|s| s.to_string());
 
     let body_bytes = match axum::body::to_bytes(request.into_body(), usize::MAX).await {
         Ok(bytes) => bytes,
@@ -289,12 +348,26 @@ async fn api_handler(
         }
     };
 
-    let body_value = if body_bytes.is_empty() {
-        Value::Object(serde_json::Map::new())
-    } else {
-        serde_json::from_slice(&body_bytes)
-            .unwrap_or_else(|_| Value::Object(serde_json::Map::new()))
+    let decoded_body = check_content_type(api.consumes, request_content_type.as_deref(), &body_bytes)
+        .and_then(|_| decode_body(api.consumes, &body_bytes));
+    let (body_value, raw_body) = match decoded_body {
+        Ok(decoded) => decoded,
+        Err(e) => {
+            state
+                .trace_store
+                .complete_trace(&trace_id, crate::trace::TraceStatus::Failed, Some(e.message()))
+                .await;
+            return Err(e);
+        }
+    };
+
+    // Bodies that are not objects are rejected as they were sent, since the
+    // middlewares only ever see an object payload.
+    let rejected_body = match &body_value {
+        Value::Object(_) | Value::Null => None,
+        other => Some(other.clone()),
     };
+    let path_param_names: Vec<String> = path_params.keys().cloned().collect();
 
     let mut payload = if let Value::Object(map) = body_value {
         Value::Object(map)
@@ -328,11 +401,38 @@ async fn api_handler(
 
     let (final_payload, final_query_params) = middleware_result.unwrap();
 
+    // Validated after the middlewares so that fields they fill in count.
+    if let (Some(body_type), None) = (&api.body, &raw_body) {
+        let errors = validate_request_body(
+            &state.schema,
+            body_type,
+            rejected_body.as_ref().unwrap_or(&final_payload),
+            &path_param_names,
+            &final_query_params,
+        );
+        if !errors.is_empty() {
+            let e = ApiError::Validation(errors);
+            state
+                .trace_store
+                .complete_trace(&trace_id, crate::trace::TraceStatus::Failed, Some(e.message()))
+                .await;
+            return Err(e);
+        }
+    }
+
+    let mut context =
+        HandlerContext::new(&handler_name, final_payload).with_metadata(HandlerContext::TRACE_ID_KEY, &trace_id);
+    context.query_params = final_query_params;
+    context.cookies = cookies;
+    if let Some(raw_body) = raw_body {
+        context = context.with_raw_body(raw_body);
+    }
+
     let result = execute_handler(
         state.clone(),
-        handler_name.clone(),
-        final_payload,
-        final_query_params,
+        context,
+        api.produces,
+        &method,
         api_triggers,
         api_name,
         trace_id.clone(),
@@ -347,14 +447,9 @@ async fn api_handler(
                 .await;
         }
         Err(e) => {
-            let error_msg = match e {
-                ApiError::BadRequest(msg) => Some(msg.clone()),
-                ApiError::NotFound(msg) => Some(msg.clone()),
-                ApiError::Internal(msg) => Some(msg.clone()),
-            };
             state
                 .trace_store
-                .complete_trace(&trace_id, crate::trace::TraceStatus::Failed, error_msg)
+                .complete_trace(&trace_id, crate::trace::TraceStatus::Failed, Some(e.message()))
                 .await;
         }
     }
@@ -362,9 +457,33 @@ async fn api_handler(
     result
 }
 
+/// Checks the payload a handler would receive against the API's body type.
+/// Path and query parameters count as provided fields. Path parameters are
+/// left out of the type check, as the engine inserts them as strings.
+fn validate_request_body(
+    schema: &Schema,
+    body_type: &str,
+    payload: &Value,
+    path_params: &[String],
+    query_params: &HashMap<String, String>,
+) -> Vec<FieldError> {
+    let mut checked = payload.clone();
+    if let Some(object) = checked.as_object_mut() {
+        for name in path_params {
+            object.remove(name);
+        }
+    }
+    let provided: Vec<&str> = path_params
+        .iter()
+        .chain(query_params.keys())
+        .map(String::as_str)
+        .collect();
+    crate::problem::validate_body(schema, body_type, &checked, &provided)
+}
+
 fn method_matches(api_method: &HttpMethod, request_method: &axum::http::Method) -> bool {
     match api_method {
-        HttpMethod::GET => request_method == axum::http::Method::GET,
+        HttpMethod::GET => request_method == axum::http::Method::GET || request_method == axum::http::Method::HEAD,
         HttpMethod::POST => request_method == axum::http::Method::POST,
         HttpMethod::PUT => request_method == axum::http::Method::PUT,
         HttpMethod::PATCH => request_method == axum::http::Method::PATCH,
@@ -372,6 +491,209 @@ fn method_matches(api_method: &HttpMethod, request_method: &axum::http::Method)
     }
 }
 
+/// Rejects bodies sent with a media type other than the one the API
+/// consumes. Requests without a `Content-Type` header are accepted.
+fn check_content_type(consumes: ContentType, header: Option<&str>, body: &[u8]) -> Result<(), ApiError> {
+    let Some(header) = header else {
+        return Ok(());
+    };
+    if body.is_empty() || consumes.is_json() {
+        return Ok(());
+    }
+
+    let mime = header.split(';').next().unwrap_or_default();
+    if ContentType::from_mime(mime) == Some(consumes) {
+        Ok(())
+    } else {
+        Err(ApiError::UnsupportedMediaType(format!(
+            "Expected Content-Type {}, got {}",
+            consumes.mime(),
+            mime.trim()
+        )))
+    }
+}
+
+/// Decodes the request body into the JSON payload. Raw media types are
+/// returned separately and leave the payload empty.
+fn decode_body(consumes: ContentType, body: &[u8]) -> Result<(Value, Option<RawBody>), ApiError> {
+    let empty = || Value::Object(serde_json::Map::new());
+
+    match consumes {
+        ContentType::Json => {
+            let value = if body.is_empty() {
+                empty()
+            } else {
+                serde_json::from_slice(body).unwrap_or_else(|_| empty())
+            };
+            Ok((value, None))
+        }
+        ContentType::MsgPack => {
+            if body.is_empty() {
+                return Ok((empty(), None));
+            }
+            let value = WireCodec::MsgPack
+                .decode(body)
+                .map_err(|e| ApiError::BadRequest(format!("Invalid MessagePack body: {}", e)))?;
+            Ok((value, None))
+        }
+        ContentType::OctetStream => Ok((empty(), Some(RawBody::Bytes(body.to_vec())))),
+        ContentType::Csv => {
+            let text = String::from_utf8(body.to_vec())
+                .map_err(|_| ApiError::BadRequest("CSV body is not valid UTF-8".into()))?;
+            Ok((empty(), Some(RawBody::Text(text))))
+        }
+    }
+}
+
+/// Encodes handler output as the media type the API produces. Byte responses
+/// come back from the runtimes as arrays of numbers; raw bodies may also be
+/// wrapped in a `{ data }` response object.
+fn encode_response(produces: ContentType, data: Value) -> Result<Response, ApiError> {
+    let data = match (produces, data) {
+        (ContentType::OctetStream | ContentType::Csv, Value::Object(mut map)) if map.contains_key("data") => {
+            map.remove("data").unwrap_or(Value::Null)
+        }
+        (_, data) => data,
+    };
+
+    let body = match produces {
+        ContentType::Json => return Ok((StatusCode::OK, Json(data)).into_response()),
+        ContentType::MsgPack => WireCodec::MsgPack
+            .encode(&data)
+            .map_err(|e| ApiError::Internal(e.to_string()))?,
+        ContentType::Csv => match data {
+            Value::String(text) => text.into_bytes(),
+            Value::Null => Vec::new(),
+            _ => {
+                return Err(ApiError::Internal(
+                    "Handler for a text/csv API must return a string".into(),
+                ))
+            }
+        },
+        ContentType::OctetStream => match data {
+            Value::String(text) => text.into_bytes(),
+            Value::Null => Vec::new(),
+            Value::Array(items) => items
+                .iter()
+                .map(|item| item.as_u64().and_then(|b| u8::try_from(b).ok()))
+                .collect::<Option<Vec<u8>>>()
+                .ok_or_else(|| {
+                    ApiError::Internal("Handler returned an array that is not a byte array".into())
+                })?,
+            _ => {
+                return Err(ApiError::Internal(
+                    "Handler for an application/octet-stream API must return bytes".into(),
+                ))
+            }
+        },
+    };
+
+    Ok((StatusCode::OK, [(header::CONTENT_TYPE, produces.mime())], body).into_response())
+}
+
+/// Sets the status and headers the handler chose with `state.response()`.
+/// 204 and 304 responses are sent without a body, and HEAD responses
+/// with only the length of the body a GET would get.
+fn apply_handler_response(
+    mut response: Response,
+    handler_response: &HttpResponse,
+    method: &axum::http::Method,
+) -> Result<Response, ApiError> {
+    if let Some(status) = handler_response.status {
+        *response.status_mut() = StatusCode::from_u16(status)
+            .ok()
+            .filter(|status| !status.is_informational())
+            .ok_or_else(|| ApiError::Internal(format!("Handler set an invalid response status {}", status)))?;
+    }
+    for (name, value) in &handler_response.headers {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| ApiError::Internal(format!("Handler set an invalid response header '{}'", name)))?;
+        let value = HeaderValue::from_str(value)
+            .map_err(|_| ApiError::Internal(format!("Handler set an invalid value for response header '{}'", name)))?;
+        response.headers_mut().insert(name, value);
+    }
+    for cookie in &handler_response.cookies {
+        let value = HeaderValue::from_str(&set_cookie_header(cookie)?)
+            .map_err(|_| ApiError::Internal(format!("Handler set an invalid cookie '{}'", cookie.name)))?;
+        response.headers_mut().append(header::SET_COOKIE, value);
+    }
+
+    if matches!(response.status(), StatusCode::NO_CONTENT | StatusCode::NOT_MODIFIED) {
+        response.headers_mut().remove(header::CONTENT_LENGTH);
+        if response.status() == StatusCode::NO_CONTENT {
+            response.headers_mut().remove(header::CONTENT_TYPE);
+        }
+        *response.body_mut() = Body::empty();
+    } else if method == axum::http::Method::HEAD {
+        if let Some(length) = HttpBody::size_hint(response.body()).exact() {
+            response.headers_mut().insert(header::CONTENT_LENGTH, HeaderValue::from(length));
+        }
+        *response.body_mut() = Body::empty();
+    }
+    Ok(response)
+}
+
+/// Cookies of the request's `Cookie` headers, by name.
+fn parse_cookies(headers: &HeaderMap) -> HashMap<String, String> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| {
+            let (name, value) = pair.split_once('=')?;
+            let value = value.trim();
+            let value = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')).unwrap_or(value);
+            Some((name.trim().to_string(), value.to_string()))
+        })
+        .filter(|(name, _)| !name.is_empty())
+        .fold(HashMap::new(), |mut cookies, (name, value)| {
+            // The first of several cookies with one name has the most
+            // specific path.
+            cookies.entry(name).or_insert(value);
+            cookies
+        })
+}
+
+/// The `Set-Cookie` header value of a cookie a handler set.
+fn set_cookie_header(cookie: &SetCookie) -> Result<String, ApiError> {
+    let invalid = |reason: &str| ApiError::Internal(format!("Handler set cookie '{}' {}", cookie.name, reason));
+    let is_token = |c: char| c.is_ascii_graphic() && !"()<>@,;:\\\"/[]?={}".contains(c);
+    if cookie.name.is_empty() || !cookie.name.chars().all(is_token) {
+        return Err(invalid("with an invalid name"));
+    }
+    if !cookie.value.chars().all(|c| c.is_ascii_graphic() && !"\",;\\".contains(c)) {
+        return Err(invalid("with a value that must be encoded first"));
+    }
+
+    let options = &cookie.options;
+    let attributes = options.path.iter().chain(&options.domain);
+    if attributes.flat_map(|attribute| attribute.chars()).any(|c| c == ';' || c.is_ascii_control()) {
+        return Err(invalid("with a path or domain containing ';'"));
+    }
+    let mut header = format!("{}={}; Path={}", cookie.name, cookie.value, options.path.as_deref().unwrap_or("/"));
+    if let Some(domain) = &options.domain {
+        header.push_str(&format!("; Domain={}", domain));
+    }
+    if let Some(max_age) = options.max_age {
+        header.push_str(&format!("; Max-Age={}", max_age.max(0)));
+    }
+    if options.secure {
+        header.push_str("; Secure");
+    }
+    if options.http_only {
+        header.push_str("; HttpOnly");
+    }
+    match options.same_site {
+        Some(SameSite::Strict) => header.push_str("; SameSite=Strict"),
+        Some(SameSite::Lax) => header.push_str("; SameSite=Lax"),
+        Some(SameSite::None) if !options.secure => return Err(invalid("with SameSite=None but not Secure")),
+        Some(SameSite::None) => header.push_str("; SameSite=None"),
+        None => {}
+    }
+    Ok(header)
+}
+
 /// Example: pattern="/users/:id", path="/users/123" -> {"id": "123"}
 fn extract_path_params(pattern: &str, path: &str) -> HashMap<String, String> {
     let mut params = HashMap::new();
@@ -391,6 +713,31 @@ fn extract_path_params(pattern: &str, path: &str) -> HashMap<String, String> {
     params
 }
 
+/// Page size of a paginated API when the request gives no `limit`.
+pub const PAGE_DEFAULT_LIMIT: u32 = 20;
+
+/// Largest `limit` a paginated API accepts.
+pub const PAGE_MAX_LIMIT: u32 = 100;
+
+/// Checks the `limit` of a paginated API's request, setting the default when
+/// it is absent. `cursor` is passed to the handler as given.
+fn apply_page_limit(query_params: &mut HashMap<String, String>) -> Result<(), ApiError> {
+    let limit = match query_params.get("limit") {
+        None => PAGE_DEFAULT_LIMIT,
+        Some(limit) => match limit.parse::<u32>() {
+            Ok(limit) if (1..=PAGE_MAX_LIMIT).contains(&limit) => limit,
+            _ => {
+                return Err(ApiError::BadRequest(format!(
+                    "limit must be an integer from 1 to {}, got '{}'",
+                    PAGE_MAX_LIMIT, limit
+                )))
+            }
+        },
+    };
+    query_params.insert("limit".to_string(), limit.to_string());
+    Ok(())
+}
+
 /// Example: "key1=value1&key2=value2" -> {"key1": "value1", "key2": "value2"}
 fn parse_query_string(query: &str) -> HashMap<String, String> {
     query
@@ -437,10 +784,13 @@ async fn execute_middlewares(
         let mut context = rohas_runtime::HandlerContext::new(&middleware_handler_name, middleware_context);
         context.metadata.insert("middleware".to_string(), "true".to_string());
         context.metadata.insert("api_name".to_string(), api_name.to_string());
+        context.metadata.insert(HandlerContext::TRACE_ID_KEY.to_string(), trace_id.to_string());
 
         let start = std::time::Instant::now();
-        let result = state.executor.execute_with_context(context).await;
+        let snapshot = state.trace_store.snapshot(&context);
+        let result = crate::recording::with_calls(&snapshot, state.executor.execute_with_context(context)).await;
         let duration_ms = start.elapsed().as_millis() as u64;
+        state.trace_store.record(trace_id, snapshot, &result).await;
 
         if let Ok(ref exec_result) = result {
             state
@@ -499,13 +849,14 @@ async fn execute_middlewares(
 
 async fn execute_handler(
     state: ApiState,
-    handler_name: String,
-    payload: Value,
-    query_params: HashMap<String, String>,
+    context: HandlerContext,
+    produces: ContentType,
+    method: &axum::http::Method,
     api_triggers: Vec<String>,
     api_name: String,
     trace_id: String,
 ) -> Result<Response, ApiError> {
+    let handler_name = context.handler_name.clone();
     let handler_span = info_span!(
         "handler_execution",
         handler = %handler_name,
@@ -514,12 +865,14 @@ async fn execute_handler(
     let _enter = handler_span.enter();
 
     let start = std::time::Instant::now();
-    let execution_result = state
-        .executor
-        .execute_with_params(&handler_name, payload, query_params)
-        .await;
+    let snapshot = state.trace_store.snapshot(&context);
+    let execution_result = match state.chaos.before_handler(&handler_name).await {
+        Some(error) => Ok(rohas_runtime::HandlerResult::error(error, 0)),
+        None => crate::recording::with_calls(&snapshot, state.executor.execute_with_context(context)).await,
+    };
 
     let duration_ms = start.elapsed().as_millis() as u64;
+    state.trace_store.record(&trace_id, snapshot, &execution_result).await;
 
     let exec_result = match execution_result {
         Ok(exec_result) => exec_result,
@@ -555,7 +908,7 @@ async fn execute_handler(
             let trigger_start = std::time::Instant::now();
             let emit_result = state
                 .event_bus
-                .emit(&triggered_event.event_name, triggered_event.payload.clone())
+                .emit_from(&trace_id, &triggered_event.event_name, triggered_event.payload.clone())
                 .await;
             let trigger_duration = trigger_start.elapsed().as_millis() as u64;
             let trigger_timestamp = chrono::Utc::now().to_rfc3339();
@@ -584,7 +937,7 @@ async fn execute_handler(
                 .cloned()
                 .unwrap_or_else(|| response_data.clone());
             
-            let emit_result = state.event_bus.emit(trigger, payload).await;
+            let emit_result = state.event_bus.emit_from(&trace_id, trigger, payload).await;
             let trigger_duration = trigger_start.elapsed().as_millis() as u64;
             let trigger_timestamp = chrono::Utc::now().to_rfc3339();
             
@@ -603,6 +956,8 @@ async fn execute_handler(
                 duration_ms: trigger_duration,
             });
         }
+
+        crate::jobs::schedule(&state.event_bus.jobs(), &handler_name, &result.scheduled).await;
     }
 
     state
@@ -623,7 +978,7 @@ async fn execute_handler(
         for triggered_event in &result.triggers {
             if let Err(e) = state
                 .event_bus
-                .emit(&triggered_event.event_name, triggered_event.payload.clone())
+                .emit_from(&trace_id, &triggered_event.event_name, triggered_event.payload.clone())
                 .await
             {
                 tracing::error!(
@@ -642,7 +997,7 @@ async fn execute_handler(
                 .cloned()
                 .unwrap_or_else(|| response_data.clone());
 
-            if let Err(e) = state.event_bus.emit(trigger, payload).await {
+            if let Err(e) = state.event_bus.emit_from(&trace_id, trigger, payload).await {
                 tracing::error!(
                     "Failed to emit auto-triggered event {} from API {}: {}",
                     trigger,
@@ -652,7 +1007,8 @@ async fn execute_handler(
             }
         }
 
-        Ok((StatusCode::OK, Json(response_data)).into_response())
+        encode_response(produces, response_data)
+            .and_then(|response| apply_handler_response(response, &result.response, method))
     } else {
         let error_msg = result.error.unwrap_or_else(|| "Unknown error".to_string());
         Err(ApiError::Internal(error_msg))
@@ -663,21 +1019,315 @@ async fn execute_handler(
 pub enum ApiError {
     BadRequest(String),
     NotFound(String),
+    UnsupportedMediaType(String),
+    /// The request body does not match its declared input.
+    Validation(Vec<FieldError>),
     Internal(String),
 }
 
+impl ApiError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ApiError::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// One-line description, as recorded on the request's trace.
+    pub fn message(&self) -> String {
+        match self {
+            ApiError::BadRequest(msg)
+            | ApiError::NotFound(msg)
+            | ApiError::UnsupportedMediaType(msg)
+            | ApiError::Internal(msg) => msg.clone(),
+            ApiError::Validation(errors) => {
+                let fields: Vec<String> = errors
+                    .iter()
+                    .map(|e| if e.field.is_empty() { e.message.clone() } else { format!("{} {}", e.field, e.message) })
+                    .collect();
+                format!("Validation failed: {}", fields.join(", "))
+            }
+        }
+    }
+
+    /// The problem document for this error. `type_base` is the configured
+    /// `server.problem_type_base`.
+    pub fn into_problem(self, type_base: Option<&str>) -> Problem {
+        let detail = self.message();
+        let problem = Problem::new(self.status());
+        match self {
+            ApiError::BadRequest(_) => problem.with_type(type_base, "bad-request").with_detail(detail),
+            ApiError::NotFound(_) => problem.with_type(type_base, "not-found").with_detail(detail),
+            ApiError::UnsupportedMediaType(_) => problem
+                .with_type(type_base, "unsupported-media-type")
+                .with_detail(detail),
+            ApiError::Validation(errors) => problem
+                .with_type(type_base, "validation-error")
+                .with_title("Validation Failed")
+                .with_detail("The request body does not match the API's input")
+                .with_errors(errors),
+            ApiError::Internal(_) => problem.with_type(type_base, "internal-error").with_detail(detail),
+        }
+    }
+}
+
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
-        let (status, message) = match self {
-            ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
-            ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
-            ApiError::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+        self.into_problem(None).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Method;
+
+    async fn body_of(response: Response) -> Vec<u8> {
+        axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap().to_vec()
+    }
+
+    fn handler_response(status: Option<u16>, headers: &[(&str, &str)]) -> HttpResponse {
+        HttpResponse {
+            status,
+            headers: headers.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+            cookies: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_apply_handler_response_sets_status_and_headers() {
+        let response = encode_response(ContentType::Json, json!({ "id": 1 })).unwrap();
+        let response = apply_handler_response(
+            response,
+            &handler_response(Some(201), &[("location", "/users/1")]),
+            &Method::POST,
+        )
+        .unwrap();
+
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(response.headers()[header::LOCATION], "/users/1");
+        assert_eq!(body_of(response).await, br#"{"id":1}"#);
+    }
+
+    #[tokio::test]
+    async fn test_apply_handler_response_clears_bodies_that_must_be_empty() {
+        for status in [204, 304] {
+            let response = encode_response(ContentType::Json, Value::Null).unwrap();
+            let response =
+                apply_handler_response(response, &handler_response(Some(status), &[]), &Method::GET).unwrap();
+            assert_eq!(response.status().as_u16(), status);
+            assert!(response.headers().get(header::CONTENT_LENGTH).is_none());
+            assert!(body_of(response).await.is_empty(), "{} has a body", status);
+        }
+
+        let response = encode_response(ContentType::Json, json!({ "id": 1 })).unwrap();
+        let response = apply_handler_response(response, &HttpResponse::default(), &Method::HEAD).unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_LENGTH], "8");
+        assert!(body_of(response).await.is_empty());
+    }
+
+    #[test]
+    fn test_apply_handler_response_rejects_invalid_status_and_headers() {
+        let apply = |handler: HttpResponse| {
+            let response = encode_response(ContentType::Json, Value::Null).unwrap();
+            apply_handler_response(response, &handler, &Method::GET)
         };
 
-        let body = serde_json::json!({
-            "error": message,
-        });
+        for status in [0, 99, 100, 101, 1000] {
+            assert!(
+                matches!(apply(handler_response(Some(status), &[])), Err(ApiError::Internal(_))),
+                "status {} was accepted",
+                status
+            );
+        }
+        assert!(matches!(apply(handler_response(None, &[("bad header", "x")])), Err(ApiError::Internal(_))));
+        assert!(matches!(apply(handler_response(None, &[("x-trace", "a\nb")])), Err(ApiError::Internal(_))));
+    }
+
+    #[test]
+    fn test_parse_cookies() {
+        // Cookie headers, and the cookies parsed from them.
+        type Case = (&'static [&'static str], &'static [(&'static str, &'static str)]);
+        let cases: &[Case] = &[
+            (&["session=abc; theme=dark"], &[("session", "abc"), ("theme", "dark")]),
+            (&[" session = abc ;theme=dark;"], &[("session", "abc"), ("theme", "dark")]),
+            (&["name=\"quoted value\""], &[("name", "quoted value")]),
+            (&["name=\"unterminated"], &[("name", "\"unterminated")]),
+            (&["token=a=b=c"], &[("token", "a=b=c")]),
+            (&["empty="], &[("empty", "")]),
+            // Browsers send the cookie with the most specific path first.
+            (&["id=specific; id=general"], &[("id", "specific")]),
+            (&["id=first", "id=second; other=1"], &[("id", "first"), ("other", "1")]),
+            (&["=nameless; novalue; ok=1"], &[("ok", "1")]),
+            (&[""], &[]),
+        ];
+
+        for (headers, expected) in cases {
+            let mut map = HeaderMap::new();
+            for value in *headers {
+                map.append(header::COOKIE, HeaderValue::from_str(value).unwrap());
+            }
+            let expected: HashMap<String, String> =
+                expected.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+            assert_eq!(parse_cookies(&map), expected, "{:?}", headers);
+        }
+
+        let mut map = HeaderMap::new();
+        map.insert(header::COOKIE, HeaderValue::from_bytes(b"bin=\xff; ok=1").unwrap());
+        assert!(parse_cookies(&map).is_empty(), "non-UTF-8 header values are skipped");
+    }
+
+    #[test]
+    fn test_set_cookie_header() {
+        use rohas_runtime::CookieOptions;
+
+        let cookie = |name: &str, value: &str, options: CookieOptions| SetCookie {
+            name: name.to_string(),
+            value: value.to_string(),
+            options,
+        };
+        let cases: Vec<(SetCookie, Option<&str>)> = vec![
+            (cookie("session", "abc", CookieOptions::default()), Some("session=abc; Path=/")),
+            (
+                cookie(
+                    "session",
+                    "abc",
+                    CookieOptions {
+                        path: Some("/app".into()),
+                        domain: Some("example.com".into()),
+                        max_age: Some(3600),
+                        secure: true,
+                        http_only: true,
+                        same_site: Some(SameSite::Strict),
+                    },
+                ),
+                Some("session=abc; Path=/app; Domain=example.com; Max-Age=3600; Secure; HttpOnly; SameSite=Strict"),
+            ),
+            (
+                cookie("old", "", CookieOptions { max_age: Some(-5), ..Default::default() }),
+                Some("old=; Path=/; Max-Age=0"),
+            ),
+            (
+                cookie(
+                    "cross",
+                    "1",
+                    CookieOptions {
+                        secure: true,
+                        same_site: Some(SameSite::None),
+                        ..Default::default()
+                    },
+                ),
+                Some("cross=1; Path=/; Secure; SameSite=None"),
+            ),
+            (
+                cookie("cross", "1", CookieOptions { same_site: Some(SameSite::None), ..Default::default() }),
+                None,
+            ),
+            (cookie("", "abc", CookieOptions::default()), None),
+            (cookie("bad name", "abc", CookieOptions::default()), None),
+            (cookie("bad;name", "abc", CookieOptions::default()), None),
+            (cookie("name=", "abc", CookieOptions::default()), None),
+            (cookie("session", "a b", CookieOptions::default()), None),
+            (cookie("session", "a;b", CookieOptions::default()), None),
+            (cookie("session", "\"quoted\"", CookieOptions::default()), None),
+            (cookie("session", "caf\u{e9}", CookieOptions::default()), None),
+            (
+                cookie("session", "abc", CookieOptions { path: Some("/; Domain=evil.com".into()), ..Default::default() }),
+                None,
+            ),
+            (
+                cookie("session", "abc", CookieOptions { domain: Some("example.com;Secure".into()), ..Default::default() }),
+                None,
+            ),
+            (
+                cookie("session", "abc", CookieOptions { path: Some("/\r\nX-Injected: 1".into()), ..Default::default() }),
+                None,
+            ),
+        ];
+
+        for (cookie, expected) in cases {
+            match (set_cookie_header(&cookie), expected) {
+                (Ok(header), Some(expected)) => assert_eq!(header, expected),
+                (Err(ApiError::Internal(_)), None) => {}
+                (result, expected) => panic!("{:?}: got {:?}, expected {:?}", cookie, result, expected),
+            }
+        }
+    }
+
+    #[test]
+    fn test_validate_request_body_counts_path_and_query_params() {
+        let schema = rohas_parser::Parser::parse_string(
+            "input UpdateUserInput {\n  id: Int\n  name: String\n  tenant: String\n}\n",
+        )
+        .unwrap();
+        // The engine inserts path parameters as strings before middlewares run.
+        let payload = json!({ "id": "7", "name": "Ada" });
+        let query = HashMap::from([("tenant".to_string(), "acme".to_string())]);
+
+        let path = vec!["id".to_string()];
+        assert!(validate_request_body(&schema, "UpdateUserInput", &payload, &path, &query).is_empty());
+
+        let errors = validate_request_body(&schema, "UpdateUserInput", &payload, &path, &HashMap::new());
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "tenant");
+    }
 
-        (status, Json(body)).into_response()
+    /// Answers every call with `"up"`.
+    struct Up;
+
+    #[async_trait::async_trait]
+    impl rohas_runtime::Handler for Up {
+        async fn execute(&self, _context: HandlerContext) -> rohas_runtime::Result<rohas_runtime::HandlerResult> {
+            Ok(rohas_runtime::HandlerResult::success(json!("up"), 0))
+        }
+
+        fn name(&self) -> &str {
+            "health"
+        }
+    }
+
+    async fn probe(schema: &str, path: &str) -> (StatusCode, Value) {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = EngineConfig {
+            project_root: dir.path().to_path_buf(),
+            language: config::Language::Rust,
+            ..EngineConfig::default()
+        };
+        let engine = crate::engine::Engine::from_schema(rohas_parser::Parser::parse_string(schema).unwrap(), config)
+            .await
+            .unwrap();
+        engine.executor().register_handler(Arc::new(Up)).await;
+
+        let mut request = Request::builder().uri(path).body(axum::body::Body::empty()).unwrap();
+        request
+            .extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from(([127, 0, 0, 1], 40000))));
+        let response = tower::ServiceExt::oneshot(engine.router(), request).await.unwrap();
+        let status = response.status();
+        (status, serde_json::from_slice(&body_of(response).await).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_probes_answer_without_schema_apis() {
+        let schema = "model Order {\n  id Int\n}\n";
+        assert_eq!(probe(schema, "/healthz").await, (StatusCode::OK, json!({ "status": "ok" })));
+        assert_eq!(probe(schema, "/readyz").await, (StatusCode::OK, json!({ "status": "ready" })));
+    }
+
+    #[tokio::test]
+    async fn test_schema_api_on_a_probe_path_replaces_the_probe() {
+        let schema = r#"
+            api Health {
+                method: GET
+                path: "/healthz"
+                response: String
+            }
+        "#;
+        assert_eq!(probe(schema, "/healthz").await, (StatusCode::OK, json!("up")));
+        assert_eq!(probe(schema, "/readyz").await, (StatusCode::OK, json!({ "status": "ready" })));
     }
 }
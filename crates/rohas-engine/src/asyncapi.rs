@@ -0,0 +1,50 @@
+//! The AsyncAPI document served at `/asyncapi.json`.
+
+use crate::config::{AdapterType, EngineConfig};
+use rohas_codegen::asyncapi::{self, Broker, DocumentInfo};
+use rohas_parser::Schema;
+use serde_json::Value;
+
+/// Describes the schema's events and websockets, with the configured adapter
+/// as the broker of event channels.
+pub fn document(schema: &Schema, config: &EngineConfig) -> Value {
+    let title = config
+        .project_root
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "rohas".to_string());
+
+    let info = DocumentInfo {
+        title,
+        version: "1.0.0".to_string(),
+        host: format!("{}:{}", config.server.host, config.server.port),
+        broker: broker(&config.adapter.adapter_type),
+    };
+    asyncapi::document(schema, &info)
+}
+
+fn broker(adapter: &AdapterType) -> Option<Broker> {
+    let strip_scheme = |url: &str| url.split_once("://").map_or(url, |(_, host)| host).to_string();
+    match adapter {
+        AdapterType::Memory | AdapterType::Embedded { .. } => None,
+        AdapterType::Nats { url } => Some(Broker {
+            protocol: "nats".to_string(),
+            host: strip_scheme(url),
+        }),
+        AdapterType::Kafka { brokers } => Some(Broker {
+            protocol: "kafka".to_string(),
+            host: brokers.clone(),
+        }),
+        AdapterType::RabbitMQ { url } => Some(Broker {
+            protocol: "amqp".to_string(),
+            host: strip_scheme(url),
+        }),
+        AdapterType::Aws { region, aws_type, .. } => {
+            let service = if aws_type == "eventbridge" { "events" } else { "sqs" };
+            Some(Broker {
+                protocol: aws_type.clone(),
+                host: format!("{}.{}.amazonaws.com", service, region),
+            })
+        }
+    }
+}
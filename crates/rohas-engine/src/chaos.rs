@@ -0,0 +1,124 @@
+use crate::config::ChaosConfig;
+use rand::Rng;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+use std::time::Duration;
+use tracing::warn;
+
+/// Counts of faults injected since startup.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ChaosStats {
+    pub delayed_handlers: u64,
+    pub failed_handlers: u64,
+    pub failed_publishes: u64,
+    pub dropped_deliveries: u64,
+}
+
+/// Injects faults into handler execution and event delivery according to the
+/// `[chaos]` config. Settings can be changed at runtime through the workbench,
+/// but only when chaos was enabled in `rohas.toml`.
+pub struct ChaosController {
+    allowed: bool,
+    settings: RwLock<ChaosConfig>,
+    delayed_handlers: AtomicU64,
+    failed_handlers: AtomicU64,
+    failed_publishes: AtomicU64,
+    dropped_deliveries: AtomicU64,
+}
+
+impl ChaosController {
+    pub fn new(settings: ChaosConfig) -> Self {
+        Self {
+            allowed: settings.enabled,
+            settings: RwLock::new(settings),
+            delayed_handlers: AtomicU64::new(0),
+            failed_handlers: AtomicU64::new(0),
+            failed_publishes: AtomicU64::new(0),
+            dropped_deliveries: AtomicU64::new(0),
+        }
+    }
+
+    /// Whether runtime changes are permitted.
+    pub fn allowed(&self) -> bool {
+        self.allowed
+    }
+
+    pub fn settings(&self) -> ChaosConfig {
+        self.settings.read().unwrap().clone()
+    }
+
+    pub fn update(&self, settings: ChaosConfig) -> Result<(), String> {
+        settings.validate()?;
+        *self.settings.write().unwrap() = settings;
+        Ok(())
+    }
+
+    pub fn stats(&self) -> ChaosStats {
+        ChaosStats {
+            delayed_handlers: self.delayed_handlers.load(Ordering::Relaxed),
+            failed_handlers: self.failed_handlers.load(Ordering::Relaxed),
+            failed_publishes: self.failed_publishes.load(Ordering::Relaxed),
+            dropped_deliveries: self.dropped_deliveries.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Runs before a handler executes. May sleep, and returns an error
+    /// message when the handler should fail without running.
+    pub async fn before_handler(&self, handler_name: &str) -> Option<String> {
+        let (delay, fail) = {
+            let settings = self.settings.read().unwrap();
+            if !applies(&settings, handler_name) {
+                return None;
+            }
+            let delay = roll(settings.latency_rate).then(|| {
+                rand::thread_rng().gen_range(settings.latency_min_ms..=settings.latency_max_ms)
+            });
+            (delay, roll(settings.handler_error_rate))
+        };
+
+        if let Some(ms) = delay {
+            self.delayed_handlers.fetch_add(1, Ordering::Relaxed);
+            warn!("chaos: delaying handler {} by {}ms", handler_name, ms);
+            tokio::time::sleep(Duration::from_millis(ms)).await;
+        }
+
+        if fail {
+            self.failed_handlers.fetch_add(1, Ordering::Relaxed);
+            warn!("chaos: failing handler {}", handler_name);
+            return Some(format!("chaos: injected failure in handler {}", handler_name));
+        }
+
+        None
+    }
+
+    /// Whether publishing to `topic` should fail.
+    pub fn fail_publish(&self, topic: &str) -> bool {
+        let settings = self.settings.read().unwrap();
+        if applies(&settings, topic) && roll(settings.publish_failure_rate) {
+            self.failed_publishes.fetch_add(1, Ordering::Relaxed);
+            warn!("chaos: failing publish to {}", topic);
+            return true;
+        }
+        false
+    }
+
+    /// Whether a delivery of `event_name` should be dropped.
+    pub fn drop_delivery(&self, event_name: &str) -> bool {
+        let settings = self.settings.read().unwrap();
+        if applies(&settings, event_name) && roll(settings.drop_delivery_rate) {
+            self.dropped_deliveries.fetch_add(1, Ordering::Relaxed);
+            warn!("chaos: dropping delivery of {}", event_name);
+            return true;
+        }
+        false
+    }
+}
+
+fn applies(settings: &ChaosConfig, target: &str) -> bool {
+    settings.enabled && (settings.targets.is_empty() || settings.targets.iter().any(|t| t == target))
+}
+
+fn roll(rate: f64) -> bool {
+    rate > 0.0 && rand::thread_rng().gen_bool(rate.min(1.0))
+}
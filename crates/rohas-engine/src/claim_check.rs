@@ -0,0 +1,210 @@
+use crate::config::{BlobStoreType, ClaimCheckConfig};
+use crate::error::{EngineError, Result};
+use serde_json::{json, Value};
+use std::path::{Component, Path, PathBuf};
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+/// Field of the message payload that holds the reference to an offloaded
+/// payload.
+pub const REFERENCE_KEY: &str = "$claim_check";
+
+enum BlobStore {
+    Local(PathBuf),
+    S3(adapter_aws::S3BlobStore),
+}
+
+impl BlobStore {
+    fn name(&self) -> &'static str {
+        match self {
+            BlobStore::Local(_) => "local",
+            BlobStore::S3(_) => "s3",
+        }
+    }
+
+    async fn put(&self, key: &str, body: Vec<u8>) -> Result<()> {
+        match self {
+            BlobStore::Local(root) => {
+                let path = root.join(key);
+                if let Some(parent) = path.parent() {
+                    tokio::fs::create_dir_all(parent).await?;
+                }
+                tokio::fs::write(path, body).await?;
+                Ok(())
+            }
+            BlobStore::S3(store) => store.put(key, body).await.map_err(|e| EngineError::Adapter(e.to_string())),
+        }
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>> {
+        match self {
+            BlobStore::Local(root) => Ok(tokio::fs::read(root.join(key)).await?),
+            BlobStore::S3(store) => store.get(key).await.map_err(|e| EngineError::Adapter(e.to_string())),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        match self {
+            BlobStore::Local(root) => Ok(tokio::fs::remove_file(root.join(key)).await?),
+            BlobStore::S3(store) => store.delete(key).await.map_err(|e| EngineError::Adapter(e.to_string())),
+        }
+    }
+}
+
+/// Moves large event payloads out of messages and back in on delivery.
+pub struct ClaimCheck {
+    config: ClaimCheckConfig,
+    store: BlobStore,
+}
+
+impl ClaimCheck {
+    /// `region` is the AWS adapter's region, used when the config sets none.
+    pub async fn new(config: ClaimCheckConfig, project_root: &Path, region: Option<&str>) -> Result<Self> {
+        let store = match config.store {
+            BlobStoreType::Local => {
+                let path = Path::new(&config.path);
+                BlobStore::Local(if path.is_absolute() {
+                    path.to_path_buf()
+                } else {
+                    project_root.join(path)
+                })
+            }
+            BlobStoreType::S3 => {
+                let s3_config = adapter_aws::S3Config {
+                    region: config
+                        .region
+                        .clone()
+                        .or_else(|| region.map(str::to_string))
+                        .unwrap_or_else(|| "us-east-1".to_string()),
+                    bucket: config.bucket.clone().unwrap_or_default(),
+                    prefix: config.prefix.clone(),
+                };
+                let store = adapter_aws::S3BlobStore::new(s3_config)
+                    .await
+                    .map_err(|e| EngineError::Initialization(format!("Failed to initialize S3 blob store: {}", e)))?;
+                BlobStore::S3(store)
+            }
+        };
+
+        Ok(Self { config, store })
+    }
+
+    /// Returns the payload unchanged when it fits the threshold, otherwise
+    /// stores it and returns a reference to it.
+    pub async fn offload(&self, topic: &str, payload: Value) -> Result<Value> {
+        let body = serde_json::to_vec(&payload).map_err(|e| EngineError::EventDispatch(e.to_string()))?;
+        if body.len() <= self.config.threshold_bytes {
+            return Ok(payload);
+        }
+
+        let topic_dir: String = topic
+            .chars()
+            .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '-' })
+            .collect();
+        let key = format!("{}/{}.json", topic_dir, Uuid::new_v4());
+        let size = body.len();
+        self.store.put(&key, body).await?;
+        debug!("Offloaded {} byte payload of {} to {} store as {}", size, topic, self.store.name(), key);
+
+        Ok(json!({
+            REFERENCE_KEY: {
+                "store": self.store.name(),
+                "key": key,
+                "size": size,
+            }
+        }))
+    }
+
+    /// Replaces a claim-check reference by the stored payload. Other payloads
+    /// are returned unchanged.
+    pub async fn resolve(&self, payload: Value) -> Result<Value> {
+        let Some(reference) = reference(&payload) else {
+            return Ok(payload);
+        };
+
+        let store = reference.get("store").and_then(Value::as_str).unwrap_or_default();
+        if store != self.store.name() {
+            return Err(EngineError::EventDispatch(format!(
+                "Claim-check payload is in the {} store but this engine uses the {} store",
+                store,
+                self.store.name()
+            )));
+        }
+        let key = reference
+            .get("key")
+            .and_then(Value::as_str)
+            .filter(|key| is_relative_key(key))
+            .ok_or_else(|| EngineError::EventDispatch("Invalid claim-check reference".to_string()))?
+            .to_string();
+
+        let body = self.store.get(&key).await?;
+        serde_json::from_slice(&body)
+            .map_err(|e| EngineError::EventDispatch(format!("Invalid claim-check payload {}: {}", key, e)))
+    }
+
+    /// Deletes the payload a claim-check reference points to, when
+    /// `delete_after_read` is set. Called once every handler of the message
+    /// has succeeded, so that a retry or redelivery still finds it.
+    pub async fn release(&self, payload: &Value) {
+        if !self.config.delete_after_read {
+            return;
+        }
+        let Some(key) = reference(payload)
+            .and_then(|reference| reference.get("key"))
+            .and_then(Value::as_str)
+            .filter(|key| is_relative_key(key))
+        else {
+            return;
+        };
+        if let Err(e) = self.store.delete(key).await {
+            warn!("Failed to delete claim-check payload {}: {}", key, e);
+        }
+    }
+}
+
+/// Whether the payload is a reference to an offloaded payload.
+pub fn is_reference(payload: &Value) -> bool {
+    reference(payload).is_some()
+}
+
+fn reference(payload: &Value) -> Option<&Value> {
+    let map = payload.as_object()?;
+    if map.len() != 1 {
+        return None;
+    }
+    map.get(REFERENCE_KEY)
+}
+
+/// Keys are generated by `offload`; reject anything that could escape the
+/// store's directory.
+fn is_relative_key(key: &str) -> bool {
+    !key.is_empty() && Path::new(key).components().all(|c| matches!(c, Component::Normal(_)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_deletes_payload_only_once_released() {
+        let dir = TempDir::new().unwrap();
+        let config = ClaimCheckConfig {
+            threshold_bytes: 8,
+            delete_after_read: true,
+            ..Default::default()
+        };
+        let claim_check = ClaimCheck::new(config, dir.path(), None).await.unwrap();
+
+        let payload = json!({"body": "more than eight bytes"});
+        let reference = claim_check.offload("Order Placed", payload.clone()).await.unwrap();
+        assert!(is_reference(&reference));
+
+        // A failed handler leaves the payload for the next delivery.
+        assert_eq!(claim_check.resolve(reference.clone()).await.unwrap(), payload);
+        assert_eq!(claim_check.resolve(reference.clone()).await.unwrap(), payload);
+
+        claim_check.release(&reference).await;
+        assert!(claim_check.resolve(reference).await.is_err());
+    }
+}
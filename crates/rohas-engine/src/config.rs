@@ -1,7 +1,9 @@
+use adapter_codec::WireCodec;
 use base64::{engine::general_purpose, Engine as _};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +19,32 @@ pub struct EngineConfig {
     pub telemetry: TelemetryConfig,
 
     pub workbench: WorkbenchConfig,
+
+    pub alerting: AlertingConfig,
+
+    #[serde(default)]
+    pub chaos: ChaosConfig,
+
+    #[serde(default)]
+    pub scheduler: SchedulerConfig,
+
+    #[serde(default)]
+    pub quarantine: QuarantineConfig,
+
+    #[serde(default)]
+    pub recording: RecordingConfig,
+
+    #[serde(default)]
+    pub python: PythonConfig,
+
+    #[serde(default)]
+    pub codegen: CodegenConfig,
+
+    #[serde(default)]
+    pub dev: DevConfig,
+
+    #[serde(default)]
+    pub kubernetes: KubernetesConfig,
 }
 
 impl Default for EngineConfig {
@@ -28,6 +56,15 @@ impl Default for EngineConfig {
             adapter: AdapterConfig::default(),
             telemetry: TelemetryConfig::default(),
             workbench: WorkbenchConfig::default(),
+            alerting: AlertingConfig::default(),
+            chaos: ChaosConfig::default(),
+            scheduler: SchedulerConfig::default(),
+            quarantine: QuarantineConfig::default(),
+            recording: RecordingConfig::default(),
+            python: PythonConfig::default(),
+            codegen: CodegenConfig::default(),
+            dev: DevConfig::default(),
+            kubernetes: KubernetesConfig::default(),
         }
     }
 }
@@ -36,9 +73,17 @@ impl EngineConfig {
     pub fn from_file<P: AsRef<std::path::Path>>(path: P) -> anyhow::Result<Self> {
         let path = path.as_ref();
         let content = fs::read_to_string(path)?;
-        let toml_config: TomlConfig = toml::from_str(&content)?;
+        Self::from_toml_str(&content)
+            .map_err(|e| anyhow::anyhow!("Invalid {}: {}", path.display(), e))
+    }
+
+    /// Parses and validates the contents of a `rohas.toml`. Unknown keys are
+    /// rejected, with the closest known key suggested.
+    pub fn from_toml_str(content: &str) -> anyhow::Result<Self> {
+        let toml_config: TomlConfig =
+            toml::from_str(content).map_err(|e| anyhow::anyhow!("{}", explain_toml_error(&e)))?;
 
-        Ok(toml_config.into_engine_config()?)
+        toml_config.into_engine_config()
     }
 
     pub fn from_project_root() -> anyhow::Result<Self> {
@@ -75,6 +120,30 @@ pub struct ServerConfig {
     pub host: String,
     pub port: u16,
     pub enable_cors: bool,
+    /// Refuse to start when the startup check finds errors instead of only
+    /// logging them.
+    #[serde(default)]
+    pub strict_startup: bool,
+    #[serde(default)]
+    pub websocket: WebSocketConfig,
+    /// Body format of API error responses.
+    #[serde(default)]
+    pub error_format: ErrorFormat,
+    /// Base URL of problem `type` URIs (`<base>/not-found`). Problems are
+    /// typed `about:blank` when unset.
+    #[serde(default)]
+    pub problem_type_base: Option<String>,
+}
+
+/// Body format of API error responses (`server.error_format`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ErrorFormat {
+    /// `application/problem+json` (RFC 7807).
+    #[default]
+    Problem,
+    /// `{ "error": "..." }`, as returned before problem details.
+    Legacy,
 }
 
 impl Default for ServerConfig {
@@ -83,14 +152,67 @@ impl Default for ServerConfig {
             host: "127.0.0.1".to_string(),
             port: 3000,
             enable_cors: true,
+            strict_startup: false,
+            websocket: WebSocketConfig::default(),
+            error_format: ErrorFormat::default(),
+            problem_type_base: None,
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Keep-alive and resumption of websocket connections (`[server.websocket]`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WebSocketConfig {
+    /// Seconds between pings sent to each client; 0 disables heartbeats.
+    pub heartbeat_interval_secs: u64,
+    /// Close connections that sent nothing, pongs included, for this many
+    /// seconds; 0 disables the timeout.
+    pub idle_timeout_secs: u64,
+    /// How long a dropped connection can be resumed with its reconnection
+    /// token; 0 disables resumption. Each token works once, and websockets
+    /// with `auth` must present a token for the same principal again.
+    pub resume_window_secs: u64,
+    /// Messages kept for a dropped connection until it resumes. The oldest
+    /// are discarded first; 0 disables resumption, as a resumed client
+    /// would silently miss everything sent while it was away.
+    pub resume_buffer_size: usize,
+}
+
+impl Default for WebSocketConfig {
+    fn default() -> Self {
+        Self {
+            heartbeat_interval_secs: 30,
+            idle_timeout_secs: 90,
+            resume_window_secs: 60,
+            resume_buffer_size: 100,
+        }
+    }
+}
+
+impl WebSocketConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.idle_timeout_secs > 0
+            && self.heartbeat_interval_secs > 0
+            && self.idle_timeout_secs <= self.heartbeat_interval_secs
+        {
+            return Err(
+                "server.websocket.idle_timeout_secs must be greater than heartbeat_interval_secs".to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AdapterConfig {
     pub adapter_type: AdapterType,
     pub buffer_size: usize,
+    /// Encoding of messages on transports that serialize them (SQS).
+    #[serde(default)]
+    pub codec: WireCodec,
+    #[serde(default)]
+    pub claim_check: Option<ClaimCheckConfig>,
 }
 
 impl Default for AdapterConfig {
@@ -98,13 +220,79 @@ impl Default for AdapterConfig {
         Self {
             adapter_type: AdapterType::Memory,
             buffer_size: 1000,
+            codec: WireCodec::Json,
+            claim_check: None,
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Claim-check offloading: event payloads larger than `threshold_bytes` are
+/// written to a blob store and the message carries a reference instead.
+/// Consumers fetch the payload back before running handlers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClaimCheckConfig {
+    pub threshold_bytes: usize,
+    pub store: BlobStoreType,
+    /// Directory of the local store, relative to the project root or absolute.
+    pub path: String,
+    /// Bucket of the S3 store.
+    pub bucket: Option<String>,
+    /// S3 region; defaults to the AWS adapter's region.
+    pub region: Option<String>,
+    /// Key prefix of the S3 store.
+    pub prefix: String,
+    /// Delete a blob once every handler of its message has succeeded. Only
+    /// safe when every event has a single consumer.
+    pub delete_after_read: bool,
+}
+
+impl Default for ClaimCheckConfig {
+    fn default() -> Self {
+        Self {
+            // Below the 256 KiB SQS and EventBridge limits, leaving room for
+            // the message envelope.
+            threshold_bytes: 200 * 1024,
+            store: BlobStoreType::Local,
+            path: ".rohas/blobs".to_string(),
+            bucket: None,
+            region: None,
+            prefix: "rohas-claim-check/".to_string(),
+            delete_after_read: false,
+        }
+    }
+}
+
+impl ClaimCheckConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.threshold_bytes == 0 {
+            return Err("adapter.claim_check.threshold_bytes must be greater than 0".to_string());
+        }
+        if self.store == BlobStoreType::S3 && self.bucket.as_deref().unwrap_or_default().is_empty() {
+            return Err("adapter.claim_check.bucket is required for the s3 store".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BlobStoreType {
+    #[default]
+    Local,
+    S3,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum AdapterType {
     Memory,
+    /// Durable queue kept in RocksDB under the project, for deployments
+    /// without an external broker.
+    Embedded {
+        path: String,
+        max_retries: u32,
+        retry_delay_ms: u64,
+    },
     Nats { url: String },
     Kafka { brokers: String },
     RabbitMQ { url: String },
@@ -140,6 +328,12 @@ pub struct TelemetryConfig {
     
     #[serde(default = "default_true")]
     pub enable_traces: bool,
+
+    #[serde(default)]
+    pub sampling: TraceSamplingConfig,
+
+    #[serde(default)]
+    pub logs: LogConfig,
 }
 
 fn default_telemetry_path() -> String {
@@ -168,10 +362,182 @@ impl Default for TelemetryConfig {
             enable_metrics: default_true(),
             enable_logs: default_true(),
             enable_traces: default_true(),
+            sampling: TraceSamplingConfig::default(),
+            logs: LogConfig::default(),
+        }
+    }
+}
+
+/// Which traces are persisted. A trace is kept when it is picked by the head
+/// sample taken at the start of its root trace, or afterwards when it failed
+/// or ran slow.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TraceSamplingConfig {
+    /// Share of traces kept when no route rate matches (0.0 - 1.0).
+    pub rate: f64,
+    /// Per-route rates keyed by API, event or cron name, or by `"METHOD /path"`.
+    pub routes: std::collections::HashMap<String, f64>,
+    /// Keep failed traces even when they were not sampled.
+    pub keep_errors: bool,
+    /// Keep traces at least this slow even when they were not sampled; 0 disables.
+    pub slow_threshold_ms: u64,
+}
+
+impl Default for TraceSamplingConfig {
+    fn default() -> Self {
+        Self {
+            rate: 1.0,
+            routes: std::collections::HashMap::new(),
+            keep_errors: true,
+            slow_threshold_ms: 0,
+        }
+    }
+}
+
+impl TraceSamplingConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.0..=1.0).contains(&self.rate) {
+            return Err(format!("telemetry.sampling.rate must be between 0.0 and 1.0, got {}", self.rate));
+        }
+        for (route, rate) in &self.routes {
+            if !(0.0..=1.0).contains(rate) {
+                return Err(format!(
+                    "telemetry.sampling.routes.\"{}\" must be between 0.0 and 1.0, got {}",
+                    route, rate
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Retention of the in-memory log store and the sinks logs are exported to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    /// Entries kept in memory for the workbench and `rohas logs tail`.
+    pub max_entries: usize,
+    /// Drop entries older than this; 0 keeps them until `max_entries` is reached.
+    pub max_age_seconds: u64,
+    /// Minimum level written to the export sinks.
+    pub export_level: String,
+    pub file: Option<LogFileConfig>,
+    pub syslog: Option<SyslogConfig>,
+    pub http: Option<LogHttpConfig>,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: 1000,
+            max_age_seconds: 0,
+            export_level: "info".to_string(),
+            file: None,
+            syslog: None,
+            http: None,
+        }
+    }
+}
+
+impl LogConfig {
+    pub fn has_sinks(&self) -> bool {
+        self.file.is_some() || self.syslog.is_some() || self.http.is_some()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_entries == 0 {
+            return Err("telemetry.logs.max_entries must be greater than 0".to_string());
+        }
+        if !["trace", "debug", "info", "warn", "error"].contains(&self.export_level.as_str()) {
+            return Err(format!(
+                "telemetry.logs.export_level must be one of trace, debug, info, warn or error, got {}",
+                self.export_level
+            ));
         }
+        if let Some(file) = &self.file {
+            if file.max_files == 0 {
+                return Err("telemetry.logs.file.max_files must be greater than 0".to_string());
+            }
+        }
+        if let Some(http) = &self.http {
+            if http.batch_size == 0 {
+                return Err("telemetry.logs.http.batch_size must be greater than 0".to_string());
+            }
+        }
+        Ok(())
     }
 }
 
+/// NDJSON log file. The active file is rotated to `<path>.1`, `<path>.2`, ...
+/// when it exceeds `max_size_mb` or when the rotation period ends.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LogFileConfig {
+    pub path: String,
+    #[serde(default = "default_log_file_max_size_mb")]
+    pub max_size_mb: u64,
+    #[serde(default)]
+    pub rotation: LogRotation,
+    /// Rotated files kept besides the active one.
+    #[serde(default = "default_log_file_max_files")]
+    pub max_files: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogRotation {
+    /// Rotate on size only.
+    #[default]
+    Never,
+    Hourly,
+    Daily,
+}
+
+fn default_log_file_max_size_mb() -> u64 {
+    10
+}
+
+fn default_log_file_max_files() -> usize {
+    5
+}
+
+/// RFC 5424 syslog over UDP.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SyslogConfig {
+    /// `host:port` of the syslog server.
+    pub address: String,
+    #[serde(default = "default_syslog_app_name")]
+    pub app_name: String,
+}
+
+fn default_syslog_app_name() -> String {
+    "rohas".to_string()
+}
+
+/// Batches of NDJSON lines POSTed to an HTTP endpoint.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LogHttpConfig {
+    pub url: String,
+    #[serde(default)]
+    pub headers: std::collections::HashMap<String, String>,
+    #[serde(default = "default_log_http_batch_size")]
+    pub batch_size: usize,
+    /// Send a partial batch after this many seconds.
+    #[serde(default = "default_log_http_flush_interval")]
+    pub flush_interval_seconds: u64,
+}
+
+fn default_log_http_batch_size() -> usize {
+    100
+}
+
+fn default_log_http_flush_interval() -> u64 {
+    5
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum TelemetryAdapterType {
@@ -197,12 +563,438 @@ impl Default for WorkbenchConfig {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertingConfig {
+    pub enabled: bool,
+    pub check_interval_seconds: u64,
+    pub channels: Vec<AlertChannelConfig>,
+    pub rules: Vec<AlertRuleConfig>,
+}
+
+impl Default for AlertingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            check_interval_seconds: default_alert_check_interval(),
+            channels: Vec::new(),
+            rules: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertChannelConfig {
+    pub name: String,
+    pub channel_type: AlertChannelType,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum AlertChannelType {
+    Slack {
+        webhook_url: String,
+    },
+    Webhook {
+        url: String,
+        headers: std::collections::HashMap<String, String>,
+    },
+    Email {
+        smtp_host: String,
+        smtp_port: u16,
+        username: Option<String>,
+        password: Option<String>,
+        from: String,
+        to: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertRuleConfig {
+    pub name: String,
+    pub rule_type: AlertRuleType,
+    /// Names of the channels notified when the rule fires.
+    pub channels: Vec<String>,
+    /// Telemetry window the rule is evaluated over.
+    pub window_seconds: u64,
+    /// Minimum time between two notifications for the same rule.
+    pub cooldown_seconds: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum AlertRuleType {
+    /// Fires when the share of failed traces exceeds `threshold` (0.0 - 1.0).
+    ErrorRate {
+        threshold: f64,
+        min_requests: usize,
+        handler: Option<String>,
+    },
+    /// Fires when a cron job execution fails.
+    CronFailure { cron: Option<String> },
+    /// Fires when more than `threshold` messages of `event`, or of any event,
+    /// are quarantined within the window.
+    Quarantine { event: Option<String>, threshold: usize },
+    /// Fires when the dead letters of `topic` grow by more than `threshold`
+    /// within the window. Only the embedded queue keeps dead letters.
+    DeadLetterGrowth { topic: String, threshold: usize },
+}
+
+/// Fault injection for resilience testing. Probabilities are in 0.0 - 1.0.
+///
+/// Nothing is injected unless `enabled` is set; never enable it outside
+/// development and test environments.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ChaosConfig {
+    pub enabled: bool,
+    /// Chance of delaying a handler by `latency_min_ms..=latency_max_ms`.
+    pub latency_rate: f64,
+    pub latency_min_ms: u64,
+    pub latency_max_ms: u64,
+    /// Chance of failing a handler without running it.
+    pub handler_error_rate: f64,
+    /// Chance of an adapter publish returning an error.
+    pub publish_failure_rate: f64,
+    /// Chance of an event delivery being dropped before its handlers run.
+    pub drop_delivery_rate: f64,
+    /// Handler and event names to inject faults into; all when empty.
+    pub targets: Vec<String>,
+}
+
+impl ChaosConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        let rates = [
+            ("latency_rate", self.latency_rate),
+            ("handler_error_rate", self.handler_error_rate),
+            ("publish_failure_rate", self.publish_failure_rate),
+            ("drop_delivery_rate", self.drop_delivery_rate),
+        ];
+        for (name, rate) in rates {
+            if !(0.0..=1.0).contains(&rate) {
+                return Err(format!("chaos.{} must be between 0.0 and 1.0, got {}", name, rate));
+            }
+        }
+        if self.latency_min_ms > self.latency_max_ms {
+            return Err(format!(
+                "chaos.latency_min_ms ({}) is greater than chaos.latency_max_ms ({})",
+                self.latency_min_ms, self.latency_max_ms
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Handler calls scheduled with `state.schedule(...)`, and cron jobs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SchedulerConfig {
+    /// File the pending calls are kept in, relative to the project root or
+    /// absolute.
+    pub store_path: String,
+    /// File recording when each cron job last fell due, used to find runs
+    /// missed while the engine was down.
+    pub run_times_path: String,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            store_path: ".rohas/jobs.json".to_string(),
+            run_times_path: ".rohas/cron-runs.json".to_string(),
+        }
+    }
+}
+
+/// Where poison messages are set aside: messages that fail to decode, or
+/// that a handler fails on `max_attempts` times in a row.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QuarantineConfig {
+    /// Times a handler is run on a message before it is quarantined.
+    pub max_attempts: u32,
+    /// File the quarantined messages are kept in, relative to the project
+    /// root or absolute.
+    pub store_path: String,
+    /// Most messages kept; the oldest are dropped beyond this.
+    pub max_entries: usize,
+}
+
+impl Default for QuarantineConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            store_path: ".rohas/quarantine.json".to_string(),
+            max_entries: 1000,
+        }
+    }
+}
+
+impl QuarantineConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_attempts == 0 {
+            return Err("quarantine.max_attempts must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Recording of handler inputs and outputs per trace, for `rohas replay`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RecordingConfig {
+    pub enabled: bool,
+    /// Only record traces that failed.
+    pub failed_only: bool,
+    /// Directory the recordings are written to, relative to the project
+    /// root or absolute.
+    pub path: String,
+    /// Most recordings kept; the oldest are deleted beyond this.
+    pub max_recordings: usize,
+}
+
+impl Default for RecordingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            failed_only: false,
+            path: ".rohas/recordings".to_string(),
+            max_recordings: 100,
+        }
+    }
+}
+
+impl RecordingConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.path.is_empty() {
+            return Err("recording.path must not be empty".to_string());
+        }
+        if self.max_recordings == 0 {
+            return Err("recording.max_recordings must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Cargo profile `rohas dev` builds Rust handlers with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RustProfile {
+    /// Optimized, rebuilt from clean on each reload.
+    #[default]
+    Release,
+    /// Unoptimized and incremental; reloads take seconds.
+    Debug,
+}
+
+impl RustProfile {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RustProfile::Release => "release",
+            RustProfile::Debug => "debug",
+        }
+    }
+}
+
+/// The environment Python handlers import their packages from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PythonConfig {
+    /// Interpreter of the environment, e.g. `.venv/bin/python`; paths with
+    /// a directory are relative to the project root. When unset, a `.venv`
+    /// or `venv` directory or poetry's environment is used.
+    pub interpreter: Option<PathBuf>,
+    /// Install `requirements.txt`, or poetry's dependencies, into the
+    /// environment when `rohas dev` starts.
+    pub install_requirements: bool,
+}
+
+impl Default for PythonConfig {
+    fn default() -> Self {
+        Self {
+            interpreter: None,
+            install_requirements: true,
+        }
+    }
+}
+
+/// Where codegen writes handler stubs, for projects with their own layout.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CodegenConfig {
+    /// Directory of handlers, inside `src`.
+    pub handlers_dir: PathBuf,
+    /// Directory of middlewares, inside `src`.
+    pub middlewares_dir: PathBuf,
+    pub file_naming: rohas_codegen::FileNaming,
+    /// Write a stub for each handler of the schema that has no file yet.
+    pub handler_stubs: bool,
+}
+
+impl Default for CodegenConfig {
+    fn default() -> Self {
+        Self {
+            handlers_dir: PathBuf::from("src/handlers"),
+            middlewares_dir: PathBuf::from("src/middlewares"),
+            file_naming: rohas_codegen::FileNaming::Schema,
+            handler_stubs: true,
+        }
+    }
+}
+
+impl CodegenConfig {
+    pub fn validate(&self, language: &Language) -> Result<(), String> {
+        for (name, dir) in [("handlers_dir", &self.handlers_dir), ("middlewares_dir", &self.middlewares_dir)] {
+            // Handlers are bundled, and imported, from under src.
+            let inside_src = dir.strip_prefix("src").is_ok_and(|rest| {
+                rest.components().next().is_some() && rest.components().all(|c| matches!(c, Component::Normal(_)))
+            });
+            if !inside_src {
+                return Err(format!("codegen.{} must be a directory inside src, not {}", name, dir.display()));
+            }
+        }
+        if *language == Language::Rust && !self.layout().has_default_paths() {
+            return Err(
+                "codegen.handlers_dir, middlewares_dir and file_naming cannot be changed for Rust, whose handlers are modules of the crate"
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
+
+    /// The layout of the handlers under `src`, where codegen writes to.
+    pub fn layout(&self) -> rohas_codegen::Layout {
+        let under_src = |dir: &Path| dir.strip_prefix("src").unwrap_or(dir).to_path_buf();
+        rohas_codegen::Layout {
+            handlers_dir: under_src(&self.handlers_dir),
+            middlewares_dir: under_src(&self.middlewares_dir),
+            file_naming: self.file_naming,
+            handler_stubs: self.handler_stubs,
+        }
+    }
+}
+
+/// Settings for `rohas dev`. The engine only reads `file_editing`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DevConfig {
+    pub rust_profile: RustProfile,
+    /// Compile Rust handlers through `sccache` when it is on the PATH.
+    pub sccache: bool,
+    /// Serve the workbench endpoint that writes schema and handler files.
+    /// Set by `rohas dev` while it watches the project; never read from
+    /// `rohas.toml`, so deployed engines cannot enable it.
+    #[serde(skip)]
+    pub file_editing: bool,
+}
+
+/// Settings for `rohas k8s generate` only; the engine ignores them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KubernetesConfig {
+    /// Container image; defaults to `<project name>:<project version>`.
+    pub image: Option<String>,
+    pub namespace: Option<String>,
+    /// Pods to run when autoscaling is off.
+    pub replicas: u32,
+    /// Environment variables of the engine container.
+    pub env: BTreeMap<String, String>,
+    /// Secret whose keys are added to the container's environment.
+    pub env_secret: Option<String>,
+    pub resources: KubernetesResources,
+    pub autoscaling: KubernetesAutoscaling,
+}
+
+impl Default for KubernetesConfig {
+    fn default() -> Self {
+        Self {
+            image: None,
+            namespace: None,
+            replicas: 1,
+            env: BTreeMap::new(),
+            env_secret: None,
+            resources: KubernetesResources::default(),
+            autoscaling: KubernetesAutoscaling::default(),
+        }
+    }
+}
+
+impl KubernetesConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.replicas == 0 {
+            return Err("kubernetes.replicas must be at least 1".to_string());
+        }
+        let autoscaling = &self.autoscaling;
+        if autoscaling.min_replicas == 0 || autoscaling.max_replicas < autoscaling.min_replicas {
+            return Err("kubernetes.autoscaling needs 1 <= min_replicas <= max_replicas".to_string());
+        }
+        if !(1..=100).contains(&autoscaling.target_cpu_utilization) {
+            return Err("kubernetes.autoscaling.target_cpu_utilization must be a percentage from 1 to 100".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Resource requests and limits of the engine container, in Kubernetes
+/// quantities such as `250m` or `256Mi`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KubernetesResources {
+    pub cpu_request: Option<String>,
+    pub memory_request: Option<String>,
+    pub cpu_limit: Option<String>,
+    pub memory_limit: Option<String>,
+}
+
+impl Default for KubernetesResources {
+    fn default() -> Self {
+        Self {
+            cpu_request: Some("100m".to_string()),
+            memory_request: Some("128Mi".to_string()),
+            cpu_limit: None,
+            memory_limit: Some("512Mi".to_string()),
+        }
+    }
+}
+
+/// HorizontalPodAutoscaler scaling on CPU utilization.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KubernetesAutoscaling {
+    pub enabled: bool,
+    pub min_replicas: u32,
+    pub max_replicas: u32,
+    /// Average CPU utilization to hold, as a percentage of `cpu_request`.
+    pub target_cpu_utilization: u32,
+}
+
+impl Default for KubernetesAutoscaling {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_replicas: 1,
+            max_replicas: 5,
+            target_cpu_utilization: 80,
+        }
+    }
+}
+
+fn default_alert_check_interval() -> u64 {
+    30
+}
+
+fn default_alert_window() -> u64 {
+    300
+}
+
+fn default_alert_cooldown() -> u64 {
+    900
+}
+
 fn generate_api_key() -> String {
     let bytes = Uuid::new_v4().into_bytes();
     general_purpose::STANDARD.encode(bytes)
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct TomlConfig {
     project: TomlProject,
     server: TomlServer,
@@ -211,9 +1003,28 @@ struct TomlConfig {
     telemetry: Option<TomlTelemetry>,
     #[serde(default)]
     workbench: Option<TomlWorkbench>,
+    #[serde(default)]
+    alerting: Option<TomlAlerting>,
+    #[serde(default)]
+    chaos: Option<ChaosConfig>,
+    #[serde(default)]
+    scheduler: Option<SchedulerConfig>,
+    #[serde(default)]
+    quarantine: Option<QuarantineConfig>,
+    #[serde(default)]
+    recording: Option<RecordingConfig>,
+    #[serde(default)]
+    python: Option<PythonConfig>,
+    #[serde(default)]
+    codegen: Option<CodegenConfig>,
+    #[serde(default)]
+    dev: Option<DevConfig>,
+    #[serde(default)]
+    kubernetes: Option<KubernetesConfig>,
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct TomlProject {
     #[allow(dead_code)]
     name: String,
@@ -223,13 +1034,19 @@ struct TomlProject {
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct TomlServer {
     host: String,
     port: u16,
     enable_cors: bool,
+    strict_startup: Option<bool>,
+    websocket: Option<WebSocketConfig>,
+    error_format: Option<ErrorFormat>,
+    problem_type_base: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct TomlAdapter {
     #[serde(rename = "type")]
     adapter_type: String,
@@ -241,9 +1058,16 @@ struct TomlAdapter {
     queue_prefix: Option<String>, // For SQS
     event_bus_name: Option<String>, // For EventBridge
     source: Option<String>, // For EventBridge
+    codec: Option<String>,
+    claim_check: Option<ClaimCheckConfig>,
+    // Embedded queue fields
+    path: Option<String>,
+    max_retries: Option<u32>,
+    retry_delay_ms: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct TomlTelemetry {
     #[serde(rename = "type")]
     adapter_type: Option<String>,
@@ -253,25 +1077,194 @@ struct TomlTelemetry {
     enable_metrics: Option<bool>,
     enable_logs: Option<bool>,
     enable_traces: Option<bool>,
+    sampling: Option<TraceSamplingConfig>,
+    logs: Option<LogConfig>,
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct TomlWorkbench {
     api_key: Option<String>,
     allowed_origins: Option<Vec<String>>,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TomlAlerting {
+    enabled: Option<bool>,
+    check_interval_seconds: Option<u64>,
+    #[serde(default)]
+    channels: Vec<TomlAlertChannel>,
+    #[serde(default)]
+    rules: Vec<TomlAlertRule>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TomlAlertChannel {
+    name: String,
+    #[serde(rename = "type")]
+    channel_type: String,
+    // Slack
+    webhook_url: Option<String>,
+    // Webhook
+    url: Option<String>,
+    headers: Option<std::collections::HashMap<String, String>>,
+    // Email
+    smtp_host: Option<String>,
+    smtp_port: Option<u16>,
+    username: Option<String>,
+    password: Option<String>,
+    from: Option<String>,
+    to: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TomlAlertRule {
+    name: String,
+    #[serde(rename = "type")]
+    rule_type: String,
+    channels: Vec<String>,
+    window_seconds: Option<u64>,
+    cooldown_seconds: Option<u64>,
+    // error_rate, quarantine and dead_letter_growth
+    threshold: Option<f64>,
+    // error_rate
+    min_requests: Option<usize>,
+    handler: Option<String>,
+    // cron_failure
+    cron: Option<String>,
+    // quarantine
+    event: Option<String>,
+    // dead_letter_growth
+    topic: Option<String>,
+}
+
+/// The `threshold` of a rule that counts messages, which defaults to 0 so
+/// that any message fires the rule.
+fn count_threshold(rule: &TomlAlertRule) -> anyhow::Result<usize> {
+    match rule.threshold {
+        None => Ok(0),
+        Some(threshold) if threshold >= 0.0 && threshold.fract() == 0.0 => Ok(threshold as usize),
+        Some(threshold) => anyhow::bail!(
+            "Alert rule '{}' requires a whole number threshold, got {}",
+            rule.name,
+            threshold
+        ),
+    }
+}
+
+impl TomlAlerting {
+    fn into_alerting_config(self) -> anyhow::Result<AlertingConfig> {
+        let mut channels = Vec::new();
+        for channel in self.channels {
+            let channel_type = match channel.channel_type.to_lowercase().as_str() {
+                "slack" => AlertChannelType::Slack {
+                    webhook_url: channel.webhook_url.ok_or_else(|| {
+                        anyhow::anyhow!("Alert channel '{}' requires webhook_url", channel.name)
+                    })?,
+                },
+                "webhook" | "http" => AlertChannelType::Webhook {
+                    url: channel.url.ok_or_else(|| {
+                        anyhow::anyhow!("Alert channel '{}' requires url", channel.name)
+                    })?,
+                    headers: channel.headers.unwrap_or_default(),
+                },
+                "email" | "smtp" => AlertChannelType::Email {
+                    smtp_host: channel.smtp_host.ok_or_else(|| {
+                        anyhow::anyhow!("Alert channel '{}' requires smtp_host", channel.name)
+                    })?,
+                    smtp_port: channel.smtp_port.unwrap_or(587),
+                    username: channel.username,
+                    password: channel.password,
+                    from: channel.from.ok_or_else(|| {
+                        anyhow::anyhow!("Alert channel '{}' requires from", channel.name)
+                    })?,
+                    to: channel.to.filter(|to| !to.is_empty()).ok_or_else(|| {
+                        anyhow::anyhow!("Alert channel '{}' requires at least one recipient in to", channel.name)
+                    })?,
+                },
+                other => anyhow::bail!("Unsupported alert channel type: {}", other),
+            };
+            channels.push(AlertChannelConfig {
+                name: channel.name,
+                channel_type,
+            });
+        }
+
+        let mut rules = Vec::new();
+        for rule in self.rules {
+            let rule_type = match rule.rule_type.to_lowercase().as_str() {
+                "error_rate" => AlertRuleType::ErrorRate {
+                    threshold: rule.threshold.ok_or_else(|| {
+                        anyhow::anyhow!("Alert rule '{}' requires threshold", rule.name)
+                    })?,
+                    min_requests: rule.min_requests.unwrap_or(10),
+                    handler: rule.handler,
+                },
+                "cron_failure" => AlertRuleType::CronFailure { cron: rule.cron },
+                "quarantine" => AlertRuleType::Quarantine {
+                    threshold: count_threshold(&rule)?,
+                    event: rule.event.clone(),
+                },
+                "dead_letter_growth" => AlertRuleType::DeadLetterGrowth {
+                    topic: rule.topic.clone().ok_or_else(|| {
+                        anyhow::anyhow!("Alert rule '{}' requires topic", rule.name)
+                    })?,
+                    threshold: count_threshold(&rule)?,
+                },
+                other => anyhow::bail!("Unsupported alert rule type: {}", other),
+            };
+
+            if let Some(missing) = rule
+                .channels
+                .iter()
+                .find(|name| !channels.iter().any(|c| &c.name == *name))
+            {
+                anyhow::bail!("Alert rule '{}' references unknown channel '{}'", rule.name, missing);
+            }
+
+            rules.push(AlertRuleConfig {
+                name: rule.name,
+                rule_type,
+                channels: rule.channels,
+                window_seconds: rule.window_seconds.unwrap_or_else(default_alert_window),
+                cooldown_seconds: rule.cooldown_seconds.unwrap_or_else(default_alert_cooldown),
+            });
+        }
+
+        Ok(AlertingConfig {
+            enabled: self.enabled.unwrap_or(true),
+            check_interval_seconds: self
+                .check_interval_seconds
+                .unwrap_or_else(default_alert_check_interval),
+            channels,
+            rules,
+        })
+    }
+}
+
 impl TomlConfig {
     fn into_engine_config(self) -> anyhow::Result<EngineConfig> {
         let language = match self.project.language.to_lowercase().as_str() {
             "typescript" | "ts" => Language::TypeScript,
             "python" | "py" => Language::Python,
             "rust" | "rs" => Language::Rust,
-            _ => anyhow::bail!("Unsupported language: {}", self.project.language),
+            other => anyhow::bail!(
+                "Unsupported language: {}{}",
+                self.project.language,
+                did_you_mean(other, &["typescript", "python", "rust"])
+            ),
         };
 
         let adapter_type = match self.adapter.adapter_type.to_lowercase().as_str() {
             "memory" => AdapterType::Memory,
+            "embedded" => AdapterType::Embedded {
+                path: self.adapter.path.unwrap_or_else(|| ".rohas/queue".to_string()),
+                max_retries: self.adapter.max_retries.unwrap_or(3),
+                retry_delay_ms: self.adapter.retry_delay_ms.unwrap_or(1000),
+            },
             "aws" => {
                 let aws_type = self.adapter.aws_type.as_deref()
                     .unwrap_or("sqs")
@@ -294,16 +1287,39 @@ impl TomlConfig {
                 event_bus_name: None,
                 source: None,
             },
-            _ => anyhow::bail!("Unsupported adapter type: {}", self.adapter.adapter_type),
+            other => anyhow::bail!(
+                "Unsupported adapter type: {}{}",
+                self.adapter.adapter_type,
+                did_you_mean(other, &["memory", "embedded", "aws", "sqs"])
+            ),
+        };
+
+        if self.adapter.buffer_size == 0 {
+            anyhow::bail!("adapter.buffer_size must be greater than 0");
+        }
+
+        let codec = match self.adapter.codec.as_deref() {
+            Some(name) => WireCodec::from_name(name).ok_or_else(|| {
+                anyhow::anyhow!("Unsupported adapter codec: {}. Must be 'json', 'msgpack' or 'cbor'", name)
+            })?,
+            None => WireCodec::Json,
         };
 
+        if let Some(claim_check) = &self.adapter.claim_check {
+            claim_check.validate().map_err(anyhow::Error::msg)?;
+        }
+
         let telemetry = if let Some(telemetry) = self.telemetry {
             let adapter_type = match telemetry.adapter_type.as_deref().unwrap_or("rocksdb").to_lowercase().as_str() {
                 "rocksdb" => TelemetryAdapterType::RocksDB,
                 "prometheus" => TelemetryAdapterType::Prometheus,
                 "influxdb" => TelemetryAdapterType::InfluxDB,
                 "timescaledb" => TelemetryAdapterType::TimescaleDB,
-                _ => anyhow::bail!("Unsupported telemetry adapter type: {}", telemetry.adapter_type.unwrap_or_default()),
+                other => anyhow::bail!(
+                    "Unsupported telemetry adapter type: {}{}",
+                    other,
+                    did_you_mean(other, &["rocksdb", "prometheus", "influxdb", "timescaledb"])
+                ),
             };
             
             TelemetryConfig {
@@ -314,11 +1330,16 @@ impl TomlConfig {
                 enable_metrics: telemetry.enable_metrics.unwrap_or_else(default_true),
                 enable_logs: telemetry.enable_logs.unwrap_or_else(default_true),
                 enable_traces: telemetry.enable_traces.unwrap_or_else(default_true),
+                sampling: telemetry.sampling.unwrap_or_default(),
+                logs: telemetry.logs.unwrap_or_default(),
             }
         } else {
             TelemetryConfig::default()
         };
 
+        telemetry.sampling.validate().map_err(anyhow::Error::msg)?;
+        telemetry.logs.validate().map_err(anyhow::Error::msg)?;
+
         let workbench = if let Some(workbench) = self.workbench {
             WorkbenchConfig {
                 api_key: workbench.api_key.unwrap_or_else(generate_api_key),
@@ -328,6 +1349,26 @@ impl TomlConfig {
             WorkbenchConfig::default()
         };
 
+        let alerting = match self.alerting {
+            Some(alerting) => alerting.into_alerting_config()?,
+            None => AlertingConfig::default(),
+        };
+
+        let chaos = self.chaos.unwrap_or_default();
+        chaos.validate().map_err(anyhow::Error::msg)?;
+
+        let quarantine = self.quarantine.unwrap_or_default();
+        quarantine.validate().map_err(anyhow::Error::msg)?;
+        let recording = self.recording.unwrap_or_default();
+        recording.validate().map_err(anyhow::Error::msg)?;
+        let kubernetes = self.kubernetes.unwrap_or_default();
+        kubernetes.validate().map_err(anyhow::Error::msg)?;
+        let codegen = self.codegen.unwrap_or_default();
+        codegen.validate(&language).map_err(anyhow::Error::msg)?;
+
+        let websocket = self.server.websocket.unwrap_or_default();
+        websocket.validate().map_err(anyhow::Error::msg)?;
+
         Ok(EngineConfig {
             project_root: std::env::current_dir()?,
             language,
@@ -335,13 +1376,207 @@ impl TomlConfig {
                 host: self.server.host,
                 port: self.server.port,
                 enable_cors: self.server.enable_cors,
+                strict_startup: self.server.strict_startup.unwrap_or(false),
+                websocket,
+                error_format: self.server.error_format.unwrap_or_default(),
+                problem_type_base: self.server.problem_type_base,
             },
             adapter: AdapterConfig {
                 adapter_type,
                 buffer_size: self.adapter.buffer_size,
+                codec,
+                claim_check: self.adapter.claim_check,
             },
             telemetry,
             workbench,
+            alerting,
+            chaos,
+            scheduler: self.scheduler.unwrap_or_default(),
+            quarantine,
+            recording,
+            python: self.python.unwrap_or_default(),
+            codegen,
+            dev: self.dev.unwrap_or_default(),
+            kubernetes,
         })
     }
 }
+
+/// Adds a "did you mean" hint to serde's unknown field and variant errors,
+/// e.g. "unknown field `prot`, expected one of `host`, `port`, ...".
+fn explain_toml_error(error: &toml::de::Error) -> String {
+    let message = error.message();
+    let rendered = error.to_string();
+    let unknown = ["unknown field `", "unknown variant `"]
+        .iter()
+        .find_map(|prefix| message.strip_prefix(prefix))
+        .and_then(|rest| rest.split_once('`'));
+
+    let Some((unknown, rest)) = unknown else {
+        return rendered;
+    };
+    let expected: Vec<&str> = rest.split('`').skip(1).step_by(2).collect();
+    format!("{}{}", rendered.trim_end(), did_you_mean(unknown, &expected))
+}
+
+/// `", did you mean `x`?"` for the candidate closest to `value`, if any is
+/// close enough to be a plausible typo.
+fn did_you_mean(value: &str, candidates: &[&str]) -> String {
+    let max_distance = (value.len() / 3).max(1);
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(value, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| format!(", did you mean `{}`?", candidate))
+        .unwrap_or_default()
+}
+
+/// Edit distance counting an adjacent transposition as one edit, so `prot`
+/// is one edit from `port`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINIMAL: &str = r#"
+[project]
+name = "app"
+version = "0.1.0"
+language = "typescript"
+
+[server]
+host = "127.0.0.1"
+port = 3000
+enable_cors = true
+
+[adapter]
+type = "memory"
+buffer_size = 1000
+"#;
+
+    fn error(extra: &str) -> String {
+        EngineConfig::from_toml_str(&format!("{}{}", MINIMAL, extra))
+            .unwrap_err()
+            .to_string()
+    }
+
+    #[test]
+    fn test_minimal_config_parses() {
+        let config = EngineConfig::from_toml_str(MINIMAL).unwrap();
+        assert_eq!(config.language, Language::TypeScript);
+        assert_eq!(config.server.port, 3000);
+    }
+
+    #[test]
+    fn test_unknown_keys_are_rejected_with_a_suggestion() {
+        let message = error("\n[recording]\nenabeld = true\n");
+        assert!(message.contains("unknown field `enabeld`"), "{}", message);
+        assert!(message.contains(", did you mean `enabled`?"), "{}", message);
+
+        let message = error("\n[telemtry]\n");
+        assert!(message.contains("unknown field `telemtry`"), "{}", message);
+        assert!(message.contains(", did you mean `telemetry`?"), "{}", message);
+
+        let message = error("\n[alerting]\ncheck_interval_secs = 30\n");
+        assert!(message.contains("unknown field `check_interval_secs`"), "{}", message);
+        assert!(message.contains(", did you mean `check_interval_seconds`?"), "{}", message);
+    }
+
+    #[test]
+    fn test_message_alert_rules_parse() {
+        let rules = r#"
+[[alerting.channels]]
+name = "ops"
+type = "webhook"
+url = "https://example.com/alerts"
+
+[[alerting.rules]]
+name = "quarantined"
+type = "quarantine"
+channels = ["ops"]
+event = "OrderPlaced"
+
+[[alerting.rules]]
+name = "dead_letters"
+type = "dead_letter_growth"
+channels = ["ops"]
+topic = "OrderPlaced"
+threshold = 5
+"#;
+        let config = EngineConfig::from_toml_str(&format!("{}{}", MINIMAL, rules)).unwrap();
+        assert!(matches!(
+            &config.alerting.rules[0].rule_type,
+            AlertRuleType::Quarantine { event: Some(event), threshold: 0 } if event == "OrderPlaced"
+        ));
+        assert!(matches!(
+            &config.alerting.rules[1].rule_type,
+            AlertRuleType::DeadLetterGrowth { topic, threshold: 5 } if topic == "OrderPlaced"
+        ));
+
+        let message = error(&rules.replace("topic = \"OrderPlaced\"\n", ""));
+        assert!(message.contains("Alert rule 'dead_letters' requires topic"), "{}", message);
+        let message = error(&rules.replace("threshold = 5", "threshold = 0.5"));
+        assert!(message.contains("requires a whole number threshold"), "{}", message);
+    }
+
+    #[test]
+    fn test_unknown_keys_without_a_close_match_get_no_suggestion() {
+        let message = error("\n[chaos]\ncompletely_unrelated = 1\n");
+        assert!(message.contains("unknown field `completely_unrelated`"), "{}", message);
+        assert!(!message.contains("did you mean"), "{}", message);
+    }
+
+    #[test]
+    fn test_unknown_variants_get_a_suggestion() {
+        let config = MINIMAL.replace("[adapter]", "error_format = \"problme\"\n\n[adapter]");
+        let message = EngineConfig::from_toml_str(&config).unwrap_err().to_string();
+        assert!(message.contains("unknown variant `problme`"), "{}", message);
+        assert!(message.contains(", did you mean `problem`?"), "{}", message);
+    }
+
+    #[test]
+    fn test_did_you_mean() {
+        assert_eq!(did_you_mean("prot", &["host", "port"]), ", did you mean `port`?");
+        assert_eq!(did_you_mean("hots", &["host", "port"]), ", did you mean `host`?");
+        assert_eq!(did_you_mean("database", &["host", "port"]), "");
+        assert_eq!(edit_distance("prot", "port"), 1);
+        assert_eq!(edit_distance("", "port"), 4);
+        assert_eq!(edit_distance("port", "port"), 0);
+    }
+
+    #[test]
+    fn test_example_configs_parse() {
+        let examples = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../examples");
+        let mut parsed = 0;
+        for entry in fs::read_dir(examples).unwrap() {
+            let config = entry.unwrap().path().join("config/rohas.toml");
+            if config.exists() {
+                EngineConfig::from_file(&config).unwrap_or_else(|e| panic!("{}", e));
+                parsed += 1;
+            }
+        }
+        assert!(parsed > 0);
+    }
+}
@@ -1,11 +1,12 @@
-use crate::adapter::Adapter;
+use crate::adapter::{Adapter, AdapterBackend};
 use crate::api;
-use crate::config::EngineConfig;
+use crate::config::{AdapterConfig, EngineConfig};
 use crate::error::{EngineError, Result};
 use crate::event::EventBus;
 use crate::router;
+use crate::ws_session::SessionRegistry;
 use rohas_cron::{JobConfig, Scheduler};
-use rohas_parser::{Parser, Schema};
+use rohas_parser::{env::active_environment, Parser, Schema};
 use rohas_runtime::{Executor, RuntimeConfig};
 use std::collections::HashMap;
 use std::net::SocketAddr;
@@ -31,13 +32,14 @@ impl Engine {
     pub async fn from_schema_file(schema_path: PathBuf, config: EngineConfig) -> Result<Self> {
         info!("Loading schema from: {}", schema_path.display());
 
-        let schema = Parser::parse_file(&schema_path)?;
+        let schema = Parser::parse_file_for_environment(&schema_path, &active_environment())?;
         Self::from_schema(schema, config).await
     }
 
-    pub async fn from_schema(schema: Schema, config: EngineConfig) -> Result<Self> {
+    pub async fn from_schema(mut schema: Schema, config: EngineConfig) -> Result<Self> {
         info!("Initializing Rohas engine");
 
+        schema.resolve_process_env()?;
         schema.validate()?;
 
         let schema = Arc::new(schema);
@@ -46,6 +48,9 @@ impl Engine {
             language: config.language.clone().into(),
             project_root: config.project_root.clone(),
             timeout_seconds: 30,
+            python_interpreter: config.python.interpreter.clone(),
+            handlers_dir: config.codegen.handlers_dir.clone(),
+            middlewares_dir: config.codegen.middlewares_dir.clone(),
         };
 
         let executor = Arc::new(Executor::new(runtime_config));
@@ -75,54 +80,83 @@ impl Engine {
             }
         };
 
-        let trace_store = Arc::new(crate::telemetry::TraceStore::new(telemetry.clone()));
-        let tracing_log_store = Arc::new(crate::tracing_log::TracingLogStore::new(1000)); // Keep last 1000 logs
+        let mut trace_store = crate::telemetry::TraceStore::new(telemetry.clone(), config.telemetry.sampling.clone());
+        if config.recording.enabled {
+            info!("Recording handler runs to {}", config.recording.path);
+            trace_store = trace_store.with_recorder(crate::recording::Recorder::new(
+                config.recording.clone(),
+                &config.project_root,
+            ));
+        }
+        let trace_store = Arc::new(trace_store);
+        let log_config = &config.telemetry.logs;
+        let mut tracing_log_store = crate::tracing_log::TracingLogStore::new(log_config.max_entries)
+            .with_max_age(log_config.max_age_seconds);
+        if log_config.has_sinks() {
+            tracing_log_store = tracing_log_store.with_exporter(crate::log_export::LogExporter::start(
+                log_config.clone(),
+                &config.project_root,
+            ));
+        }
+        let tracing_log_store = Arc::new(tracing_log_store);
 
-        // Create adapter based on configuration
-        let adapter = Arc::new(match &config.adapter.adapter_type {
-            crate::config::AdapterType::Memory => {
-                info!("Using Memory adapter for event bus");
-                Adapter::Memory(Arc::new(adapter_memory::MemoryAdapter::new(config.adapter.buffer_size)))
-            }
-            crate::config::AdapterType::Aws { region, aws_type, queue_prefix, event_bus_name, source } => {
-                info!("Initializing AWS adapter - region: {}, default type: {}", region, aws_type);
-                let adapter_type = match aws_type.as_str() {
-                    "sqs" => adapter_aws::AwsAdapterType::Sqs,
-                    "eventbridge" => adapter_aws::AwsAdapterType::EventBridge,
-                    _ => return Err(EngineError::Initialization(format!("Unsupported AWS adapter type: {}", aws_type))),
-                };
-                let aws_config = adapter_aws::AwsConfig {
-                    region: region.clone(),
-                    queue_prefix: queue_prefix.clone(),
-                    event_bus_name: event_bus_name.clone(),
-                    source: source.clone(),
-                    ..Default::default()
+        let adapter = Arc::new(Adapter::from_config(&config.adapter, &config.project_root).await?);
+
+        if config.chaos.enabled {
+            warn!("Chaos mode is enabled; faults will be injected into handlers and event delivery");
+        }
+        let chaos = Arc::new(crate::chaos::ChaosController::new(config.chaos.clone()));
+        let event_metrics = Arc::new(crate::event_metrics::EventMetrics::new(telemetry.metric_store()));
+
+        let claim_check = match &config.adapter.claim_check {
+            Some(claim_check) => {
+                let region = match &config.adapter.adapter_type {
+                    crate::config::AdapterType::Aws { region, .. } => Some(region.as_str()),
+                    _ => None,
                 };
-                let aws_adapter = adapter_aws::AwsAdapter::new_with_both(adapter_type, aws_config)
-                    .await
-                    .map_err(|e| EngineError::Initialization(format!("Failed to initialize AWS adapter: {}", e)))?;
-                info!("AWS adapter (both SQS and EventBridge) initialized successfully with default type: {}", aws_type);
-                Adapter::Aws(Arc::new(aws_adapter))
-            }
-            crate::config::AdapterType::Nats { .. } => {
-                return Err(EngineError::Initialization("NATS adapter not yet implemented".to_string()));
+                info!(
+                    "Offloading event payloads over {} bytes to the {:?} blob store",
+                    claim_check.threshold_bytes, claim_check.store
+                );
+                Some(Arc::new(
+                    crate::claim_check::ClaimCheck::new(claim_check.clone(), &config.project_root, region).await?,
+                ))
             }
-            crate::config::AdapterType::Kafka { .. } => {
-                return Err(EngineError::Initialization("Kafka adapter not yet implemented".to_string()));
-            }
-            crate::config::AdapterType::RabbitMQ { .. } => {
-                return Err(EngineError::Initialization("RabbitMQ adapter not yet implemented".to_string()));
-            }
-        });
+            None => None,
+        };
 
-        let event_bus = Arc::new(EventBus::new(
-            adapter.clone(),
-            executor.clone(),
-            schema.clone(),
-            trace_store.clone(),
-        ));
+        let jobs_path = config.project_root.join(&config.scheduler.store_path);
+        let jobs = rohas_cron::JobStore::open(&jobs_path).await.map_err(|e| {
+            EngineError::Initialization(format!("Failed to open job store {}: {}", jobs_path.display(), e))
+        })?;
+
+        let quarantine_path = config.project_root.join(&config.quarantine.store_path);
+        let quarantine = crate::quarantine::QuarantineStore::open(&quarantine_path, config.quarantine.max_entries).await?;
+
+        let event_bus = Arc::new(
+            EventBus::new(
+                adapter.clone(),
+                executor.clone(),
+                schema.clone(),
+                trace_store.clone(),
+                chaos,
+                event_metrics,
+            )
+            .with_claim_check(claim_check)
+            .with_ws_sessions(Arc::new(SessionRegistry::new(config.server.websocket.clone())))
+            .with_jobs(Arc::new(jobs))
+            .with_quarantine(Arc::new(quarantine), config.quarantine.max_attempts),
+        );
 
-        let scheduler = Arc::new(Scheduler::new());
+        let run_times_path = config.project_root.join(&config.scheduler.run_times_path);
+        let run_times = rohas_cron::RunTimes::open(&run_times_path).await.map_err(|e| {
+            EngineError::Initialization(format!(
+                "Failed to open cron run times {}: {}",
+                run_times_path.display(),
+                e
+            ))
+        })?;
+        let scheduler = Arc::new(Scheduler::new().with_run_times(Arc::new(run_times)));
 
         Ok(Self {
             config,
@@ -156,6 +190,14 @@ impl Engine {
             });
         }
 
+        let report = crate::startup::check_system(&self.schema, &self.config, &self.executor).await;
+        if !report.issues.is_empty() {
+            if self.config.server.strict_startup && report.has_errors() {
+                return Err(EngineError::Initialization(report.to_string()));
+            }
+            warn!("{}", report.to_string().trim_end());
+        }
+
         info!("Initializing engine components");
 
         self.event_bus.initialize().await?;
@@ -185,9 +227,22 @@ impl Engine {
             info!("Telemetry retention disabled (retention_days = 0), traces will be kept forever");
         }
 
+        self.event_bus.metrics().start(
+            self.adapter.clone(),
+            self.schema.events.iter().map(|e| e.name.clone()).collect(),
+        );
+
+        if self.config.alerting.enabled && !self.config.alerting.rules.is_empty() {
+            let alert_manager = Arc::new(
+                crate::alerting::AlertManager::new(self.config.alerting.clone(), self.trace_store.clone())
+                    .with_quarantine(self.event_bus.quarantine())
+                    .with_adapter(self.adapter.clone()),
+            );
+            alert_manager.start();
+        }
+
         for cron in &self.schema.crons {
-            let job_config = JobConfig::new(cron.name.clone(), cron.schedule.clone())
-                .with_triggers(cron.triggers.clone());
+            let job_config = cron_job_config(cron);
 
             let job_id = self.scheduler.add_job(job_config).await?;
             info!("Registered cron job: {} ({})", cron.name, job_id);
@@ -222,9 +277,14 @@ impl Engine {
                             )
                             .await;
 
+                        let context = rohas_runtime::HandlerContext::new(&cron_name, serde_json::json!({}))
+                            .with_metadata(rohas_runtime::HandlerContext::TRACE_ID_KEY, &trace_id);
+                        let snapshot = trace_store.snapshot(&context);
                         let start = std::time::Instant::now();
-                        let exec_result = executor.execute(&cron_name, serde_json::json!({})).await;
+                        let exec_result =
+                            crate::recording::with_calls(&snapshot, executor.execute_with_context(context)).await;
                         let duration_ms = start.elapsed().as_millis() as u64;
+                        trace_store.record(&trace_id, snapshot, &exec_result).await;
 
                         match exec_result {
                             Ok(result) => {
@@ -239,7 +299,7 @@ impl Engine {
                                             .data
                                             .clone()
                                             .unwrap_or(serde_json::json!({}));
-                                        let emit_res = event_bus.emit(trigger, payload).await;
+                                        let emit_res = event_bus.emit_from(&trace_id, trigger, payload).await;
                                         let trigger_duration =
                                             trigger_start.elapsed().as_millis() as u64;
                                         let trigger_timestamp = chrono::Utc::now().to_rfc3339();
@@ -258,6 +318,8 @@ impl Engine {
                                             duration_ms: trigger_duration,
                                         });
                                     }
+
+                                    crate::jobs::schedule(&event_bus.jobs(), &cron_name, &result.scheduled).await;
                                 }
 
                                 trace_store
@@ -322,6 +384,7 @@ impl Engine {
         }
 
         self.scheduler.start().await?;
+        crate::jobs::start_runner(self.event_bus.clone(), self.executor.clone(), self.trace_store.clone());
 
         *initialized = true;
         info!("Engine initialized successfully");
@@ -340,20 +403,7 @@ impl Engine {
         ));
 
         info!("Starting HTTP server on {}", addr);
-        let arc_config = Arc::new(self.config.clone());
-        let mut router = api::build_router(
-            self.executor.clone(),
-            self.schema.clone(),
-            arc_config,
-            self.event_bus.clone(),
-            self.trace_store.clone(),
-            self.tracing_log_store.clone(),
-        );
-
-        if self.config.server.enable_cors {
-            router = router::with_cors(router);
-        }
-
+        let router = self.router();
         let listener = tokio::net::TcpListener::bind(addr).await?;
 
         axum::serve(
@@ -366,6 +416,31 @@ impl Engine {
         Ok(())
     }
 
+    pub(crate) fn router(&self) -> axum::Router {
+        let router = api::build_router(
+            self.executor.clone(),
+            self.schema.clone(),
+            Arc::new(self.config.clone()),
+            self.event_bus.clone(),
+            self.trace_store.clone(),
+            self.tracing_log_store.clone(),
+            self.scheduler.clone(),
+        );
+
+        if self.config.server.enable_cors {
+            router::with_cors(router)
+        } else {
+            router
+        }
+    }
+
+    /// Serves Lambda invocations instead of listening for HTTP. Adapter
+    /// subscriptions and the scheduler are left to the platform, so the
+    /// engine needs no [`Engine::initialize`].
+    pub fn lambda(&self) -> crate::lambda::LambdaHandler<'_> {
+        crate::lambda::LambdaHandler::new(self, self.router())
+    }
+
     pub async fn run(&self) -> Result<()> {
         self.initialize().await?;
         self.start_server().await?;
@@ -387,6 +462,23 @@ impl Engine {
         Ok(())
     }
 
+    /// Rebuilds the event adapter from `config` and re-subscribes the
+    /// schema's events, leaving the HTTP server and in-flight requests alone.
+    /// The current adapter keeps running if the new one fails to connect. An
+    /// embedded queue that stays at the same path takes its new retry
+    /// settings in place instead.
+    pub async fn reload_adapter(&self, config: &AdapterConfig) -> Result<()> {
+        if self.adapter.reconfigure(config, &self.config.project_root).await {
+            info!("Event adapter settings applied in place");
+            return Ok(());
+        }
+        let backend = AdapterBackend::from_config(config, &self.config.project_root).await?;
+        self.adapter.replace(backend).await;
+        self.event_bus.initialize().await?;
+        info!("Event adapter reloaded with {} events", self.schema.events.len());
+        Ok(())
+    }
+
     pub fn tracing_log_store(&self) -> Arc<crate::tracing_log::TracingLogStore> {
         self.tracing_log_store.clone()
     }
@@ -399,6 +491,57 @@ impl Engine {
         &self.executor
     }
 
+    /// Runs one API, event or cron handler without starting the server or
+    /// the scheduler. The run is traced like any other.
+    pub async fn invoke(&self, name: &str, payload: serde_json::Value) -> Result<crate::invoke::Invocation> {
+        crate::invoke::invoke(
+            &self.schema,
+            &self.config.language,
+            &self.executor,
+            &self.event_bus,
+            &self.trace_store,
+            name,
+            payload,
+        )
+        .await
+    }
+
+    /// Runs the handlers recorded for `trace_id` again with their recorded
+    /// contexts, for `rohas replay`. Nothing they trigger is published.
+    pub async fn replay(&self, trace_id: &str) -> Result<Vec<crate::recording::ReplayedStep>> {
+        let dir = crate::recording::Recorder::dir(&self.config.recording, &self.config.project_root);
+        let recording = crate::recording::Recorder::load(&dir, trace_id).await?;
+        Ok(crate::recording::replay(&self.executor, &recording).await)
+    }
+
+    /// Runs the handlers of one event delivered by the platform, for
+    /// [`Engine::lambda`].
+    pub async fn invoke_event(
+        &self,
+        event_name: &str,
+        payload: serde_json::Value,
+        metadata: &HashMap<String, String>,
+    ) -> Result<Vec<crate::invoke::Invocation>> {
+        let reference = crate::claim_check::is_reference(&payload).then(|| payload.clone());
+        let payload = self.event_bus.resolve_payload(payload).await?;
+        let invocations = crate::invoke::invoke_event(
+            &self.schema,
+            &self.executor,
+            &self.event_bus,
+            &self.trace_store,
+            event_name,
+            payload,
+            metadata,
+        )
+        .await?;
+        if let Some(reference) = reference {
+            if invocations.iter().all(|invocation| invocation.result.success) {
+                self.event_bus.release_payload(&reference).await;
+            }
+        }
+        Ok(invocations)
+    }
+
     async fn try_auto_register_rust_handlers(
         project_root: &PathBuf,
         executor: Arc<Executor>,
@@ -437,3 +580,157 @@ pub struct EngineStats {
     pub crons_count: usize,
     pub topics_count: usize,
 }
+
+/// The scheduler job for a schema `cron` block.
+pub fn cron_job_config(cron: &rohas_parser::Cron) -> JobConfig {
+    let kind = match cron.kind {
+        rohas_parser::ScheduleKind::Cron => rohas_cron::ScheduleKind::Cron,
+        rohas_parser::ScheduleKind::Every => rohas_cron::ScheduleKind::Every,
+        rohas_parser::ScheduleKind::At => rohas_cron::ScheduleKind::At,
+    };
+    let overlap = match cron.overlap {
+        rohas_parser::OverlapPolicy::Skip => rohas_cron::OverlapPolicy::Skip,
+        rohas_parser::OverlapPolicy::Queue => rohas_cron::OverlapPolicy::Queue,
+        rohas_parser::OverlapPolicy::Concurrent => rohas_cron::OverlapPolicy::Concurrent,
+    };
+    let misfire = match cron.misfire {
+        rohas_parser::MisfirePolicy::RunOnce => rohas_cron::MisfirePolicy::RunOnce,
+        rohas_parser::MisfirePolicy::Ignore => rohas_cron::MisfirePolicy::Ignore,
+    };
+    JobConfig::new(cron.name.clone(), cron.schedule.clone())
+        .with_kind(kind)
+        .with_overlap(overlap)
+        .with_misfire(misfire)
+        .with_triggers(cron.triggers.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AdapterType, Language};
+    use rohas_runtime::{Handler, HandlerContext, HandlerResult};
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    /// Counts its runs, failing each one when `fails` is set.
+    #[derive(Default)]
+    struct ChargeCard {
+        runs: AtomicUsize,
+        fails: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl Handler for ChargeCard {
+        async fn execute(&self, _context: HandlerContext) -> rohas_runtime::Result<HandlerResult> {
+            self.runs.fetch_add(1, Ordering::SeqCst);
+            if self.fails {
+                return Ok(HandlerResult::error("card declined", 0));
+            }
+            Ok(HandlerResult::success(json!(null), 0))
+        }
+
+        fn name(&self) -> &str {
+            "charge_card"
+        }
+    }
+
+    async fn engine(dir: &TempDir, adapter: AdapterConfig, handler: Arc<ChargeCard>) -> Engine {
+        let schema = Parser::parse_string(
+            r#"
+            model Order {
+                id Int
+            }
+
+            event OrderPlaced {
+                payload: Order
+                handler: [charge_card]
+            }
+            "#,
+        )
+        .unwrap();
+        let config = EngineConfig {
+            project_root: dir.path().to_path_buf(),
+            language: Language::Rust,
+            adapter,
+            ..EngineConfig::default()
+        };
+        let engine = Engine::from_schema(schema, config).await.unwrap();
+        engine.executor().register_handler(handler).await;
+        engine.event_bus.initialize().await.unwrap();
+        engine
+    }
+
+    async fn wait_for_runs(handler: &ChargeCard, runs: usize) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while handler.runs.load(Ordering::SeqCst) < runs {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await
+        .expect("timed out waiting for the handler");
+    }
+
+    #[tokio::test]
+    async fn test_reload_adapter_resubscribes_events() {
+        let dir = TempDir::new().unwrap();
+        let handler = Arc::new(ChargeCard::default());
+        let engine = engine(&dir, AdapterConfig::default(), handler.clone()).await;
+
+        engine.adapter.publish("OrderPlaced", json!({"id": 1})).await.unwrap();
+        wait_for_runs(&handler, 1).await;
+
+        engine.reload_adapter(&AdapterConfig::default()).await.unwrap();
+        engine.adapter.publish("OrderPlaced", json!({"id": 2})).await.unwrap();
+        wait_for_runs(&handler, 2).await;
+
+        // The old backend's subscription is gone, so the event runs once.
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(handler.runs.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_reload_adapter_keeps_the_current_adapter_on_failure() {
+        let dir = TempDir::new().unwrap();
+        let handler = Arc::new(ChargeCard::default());
+        let engine = engine(&dir, AdapterConfig::default(), handler.clone()).await;
+
+        let nats = AdapterConfig {
+            adapter_type: AdapterType::Nats { url: "nats://localhost:4222".to_string() },
+            ..AdapterConfig::default()
+        };
+        assert!(engine.reload_adapter(&nats).await.is_err());
+
+        engine.adapter.publish("OrderPlaced", json!({"id": 1})).await.unwrap();
+        wait_for_runs(&handler, 1).await;
+    }
+
+    #[cfg(feature = "rocksdb")]
+    fn embedded(max_retries: u32) -> AdapterConfig {
+        AdapterConfig {
+            adapter_type: AdapterType::Embedded {
+                path: "queue".to_string(),
+                max_retries,
+                retry_delay_ms: 1,
+            },
+            ..AdapterConfig::default()
+        }
+    }
+
+    #[cfg(feature = "rocksdb")]
+    #[tokio::test]
+    async fn test_reload_adapter_updates_an_embedded_queue_in_place() {
+        let dir = TempDir::new().unwrap();
+        let handler = Arc::new(ChargeCard { fails: true, ..ChargeCard::default() });
+        let engine = engine(&dir, embedded(0), handler.clone()).await;
+
+        // The queue keeps its directory locked, so reopening it would fail.
+        engine.reload_adapter(&embedded(2)).await.unwrap();
+        engine.adapter.publish("OrderPlaced", json!({"id": 1})).await.unwrap();
+
+        wait_for_runs(&handler, 3).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(handler.runs.load(Ordering::SeqCst), 3);
+    }
+}
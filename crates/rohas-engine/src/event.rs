@@ -1,17 +1,82 @@
 use crate::adapter::Adapter;
+use crate::chaos::ChaosController;
+use crate::claim_check::ClaimCheck;
+use crate::config::QuarantineConfig;
 use crate::error::{EngineError, Result};
-use crate::trace::{TraceEntryType, TraceStatus, TriggeredEventInfo};
+use crate::event_metrics::EventMetrics;
+use crate::quarantine::{PoisonKind, QuarantineStore, QuarantinedMessage};
+use crate::trace::{TraceContext, TraceEntryType, TraceStatus, TriggeredEventInfo};
 use crate::telemetry::TraceStore;
-use rohas_parser::{Event as SchemaEvent, Schema};
+use crate::ws_session::{SessionRegistry, EVENT_KEY};
+use adapter_codec::{DECODE_ERROR, FINAL_DELIVERY};
+use rohas_cron::JobStore;
+use rohas_parser::{Event as SchemaEvent, Filter, RetryCondition, Schema};
 use rohas_runtime::Executor;
+use std::collections::HashMap;
 use std::sync::Arc;
+use tokio::sync::Semaphore;
 use tracing::{debug, error, info, warn};
 
+/// Message metadata keys through which the engine carries a message's place
+/// in its event's retry policy from one delivery to the next, on adapters
+/// that redeliver.
+const ATTEMPT_KEY: &str = "retry_attempt";
+const SUCCEEDED_KEY: &str = "succeeded_handlers";
+
+/// Where a message stands in its event's retry policy.
+#[derive(Debug, Clone, PartialEq)]
+struct Redelivery {
+    /// Which attempt the delivery is, from 1.
+    attempt: u32,
+    /// Handlers that succeeded on an earlier attempt and are not run again.
+    succeeded: Vec<String>,
+}
+
+impl Redelivery {
+    fn first() -> Self {
+        Self {
+            attempt: 1,
+            succeeded: Vec::new(),
+        }
+    }
+
+    fn from_metadata(metadata: &HashMap<String, String>) -> Self {
+        Self {
+            attempt: metadata
+                .get(ATTEMPT_KEY)
+                .and_then(|attempt| attempt.parse().ok())
+                .unwrap_or(1)
+                .max(1),
+            succeeded: metadata
+                .get(SUCCEEDED_KEY)
+                .map(|handlers| handlers.split(',').filter(|h| !h.is_empty()).map(str::to_string).collect())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// The metadata of the next attempt: `metadata` with the attempt counted
+    /// and the handlers that have `succeeded` so far.
+    fn next(&self, metadata: &HashMap<String, String>, succeeded: &[String]) -> HashMap<String, String> {
+        let mut metadata = metadata.clone();
+        metadata.remove(FINAL_DELIVERY);
+        metadata.insert(ATTEMPT_KEY.to_string(), (self.attempt + 1).to_string());
+        metadata.insert(SUCCEEDED_KEY.to_string(), succeeded.join(","));
+        metadata
+    }
+}
+
 pub struct EventBus {
     adapter: Arc<Adapter>,
     executor: Arc<Executor>,
     schema: Arc<Schema>,
     trace_store: Arc<TraceStore>,
+    chaos: Arc<ChaosController>,
+    metrics: Arc<EventMetrics>,
+    claim_check: Option<Arc<ClaimCheck>>,
+    ws_sessions: Arc<SessionRegistry>,
+    jobs: Arc<JobStore>,
+    quarantine: Arc<QuarantineStore>,
+    max_attempts: u32,
 }
 
 impl EventBus {
@@ -20,12 +85,84 @@ impl EventBus {
         executor: Arc<Executor>,
         schema: Arc<Schema>,
         trace_store: Arc<TraceStore>,
+        chaos: Arc<ChaosController>,
+        metrics: Arc<EventMetrics>,
     ) -> Self {
         Self {
             adapter,
             executor,
             schema,
             trace_store,
+            chaos,
+            metrics,
+            claim_check: None,
+            ws_sessions: Arc::new(SessionRegistry::new(Default::default())),
+            jobs: Arc::new(JobStore::in_memory()),
+            quarantine: Arc::new(QuarantineStore::in_memory(QuarantineConfig::default().max_entries)),
+            max_attempts: QuarantineConfig::default().max_attempts,
+        }
+    }
+
+    /// Offloads large payloads on publish and resolves them on delivery.
+    pub fn with_claim_check(mut self, claim_check: Option<Arc<ClaimCheck>>) -> Self {
+        self.claim_check = claim_check;
+        self
+    }
+
+    /// Websocket sessions that `forward_to:` events are pushed to.
+    pub fn with_ws_sessions(mut self, ws_sessions: Arc<SessionRegistry>) -> Self {
+        self.ws_sessions = ws_sessions;
+        self
+    }
+
+    pub fn ws_sessions(&self) -> Arc<SessionRegistry> {
+        self.ws_sessions.clone()
+    }
+
+    /// Where handler calls scheduled with `state.schedule(...)` are kept.
+    pub fn with_jobs(mut self, jobs: Arc<JobStore>) -> Self {
+        self.jobs = jobs;
+        self
+    }
+
+    pub fn jobs(&self) -> Arc<JobStore> {
+        self.jobs.clone()
+    }
+
+    /// Where poison messages go, and how many times a handler runs on a
+    /// message before it counts as one.
+    pub fn with_quarantine(mut self, quarantine: Arc<QuarantineStore>, max_attempts: u32) -> Self {
+        self.quarantine = quarantine;
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    pub fn quarantine(&self) -> Arc<QuarantineStore> {
+        self.quarantine.clone()
+    }
+
+    pub fn chaos(&self) -> Arc<ChaosController> {
+        self.chaos.clone()
+    }
+
+    pub fn metrics(&self) -> Arc<EventMetrics> {
+        self.metrics.clone()
+    }
+
+    /// Replaces a claim-check reference by the stored payload, for messages
+    /// delivered outside an adapter subscription.
+    pub async fn resolve_payload(&self, payload: serde_json::Value) -> Result<serde_json::Value> {
+        match &self.claim_check {
+            Some(claim_check) => claim_check.resolve(payload).await,
+            None => Ok(payload),
+        }
+    }
+
+    /// Deletes the stored payload behind a claim-check reference, once the
+    /// message has been handled, when the claim check is set to.
+    pub async fn release_payload(&self, payload: &serde_json::Value) {
+        if let Some(claim_check) = &self.claim_check {
+            claim_check.release(payload).await;
         }
     }
 
@@ -62,18 +199,49 @@ impl EventBus {
         let adapter = self.adapter.clone();
         let trace_store = self.trace_store.clone();
         let schema = self.schema.clone();
+        let chaos = self.chaos.clone();
+        let metrics = self.metrics.clone();
+        let claim_check = self.claim_check.clone();
+        let ws_sessions = self.ws_sessions.clone();
+        let jobs = self.jobs.clone();
+        let quarantine = self.quarantine.clone();
+        // The event's retry policy takes over from the quarantine defaults.
+        let retry = event.retry.clone().map(Arc::new);
+        let max_attempts = retry
+            .as_ref()
+            .map_or(self.max_attempts, |policy| policy.max_retries.saturating_add(1));
+        let own_adapter_type = event.adapter_type.clone();
+        let forward = match &event.forward_to {
+            Some(forward) => {
+                let filter = forward
+                    .filter
+                    .as_deref()
+                    .map(Filter::parse)
+                    .transpose()
+                    .map_err(|e| EngineError::Initialization(format!("Invalid forward_filter of {}: {}", event.name, e)))?;
+                Some(Arc::new((forward.websocket.clone(), filter)))
+            }
+            None => None,
+        };
         
         let adapter_type = event.adapter_type.as_deref();
+        // One handler at a time unless the schema says otherwise, pulling
+        // only as many messages as can run.
+        let concurrency = event.concurrency.unwrap_or(1) as usize;
+        let prefetch = event.prefetch.map_or(concurrency, |prefetch| prefetch as usize);
+        let slots = Arc::new(Semaphore::new(concurrency));
+        self.metrics.set_concurrency(&event_name, concurrency);
 
         if let Some(adapter_type) = adapter_type {
             info!("Subscribing to event: {} (via {})", event_name, adapter_type);
         } else {
             debug!("Subscribing to event: {}", event_name);
         }
+        debug!("Event {} runs {} handler(s) at once with prefetch {}", event_name, concurrency, prefetch);
 
         let adapter_type_clone = adapter_type;
         self.adapter
-            .subscribe_with_type(event_name.clone(), move |msg: adapter_memory::Message| {
+            .subscribe_with_prefetch(event_name.clone(), move |msg: adapter_memory::Message| {
                 let handlers = handlers.clone();
                 let triggers = triggers.clone();
                 let executor = executor.clone();
@@ -82,8 +250,61 @@ impl EventBus {
                 let event_payload_type = event_payload_type.clone();
                 let trace_store = trace_store.clone();
                 let schema = schema.clone();
+                let chaos = chaos.clone();
+                let metrics = metrics.clone();
+                let claim_check = claim_check.clone();
+                let ws_sessions = ws_sessions.clone();
+                let jobs = jobs.clone();
+                let quarantine = quarantine.clone();
+                let forward = forward.clone();
+                let slots = slots.clone();
+                let retry = retry.clone();
+                let own_adapter_type = own_adapter_type.clone();
 
                 async move {
+                    if chaos.drop_delivery(&event_name) {
+                        return Ok(());
+                    }
+
+                    // Adapters that redeliver retry a failed message
+                    // themselves, so each delivery runs the handlers once.
+                    // With a retry policy the engine puts the message back
+                    // itself, its attempt and the handlers that succeeded
+                    // carried in the metadata, so the policy's max, backoff
+                    // and `on` apply; without one the adapter's own retries
+                    // do.
+                    let redelivers = adapter.redelivers().await;
+                    let requeues = redelivers && retry.is_some();
+                    let redelivery = if requeues {
+                        Redelivery::from_metadata(&msg.metadata)
+                    } else {
+                        Redelivery::first()
+                    };
+
+                    let mut slot = Some(metrics.acquire_slot(&event_name, &slots).await);
+                    metrics.record_consume(&event_name);
+
+                    if let Some(error) = msg.metadata.get(DECODE_ERROR) {
+                        let poison = QuarantinedMessage::new(&event_name, PoisonKind::Decode, msg.payload.clone(), error, 1)
+                            .with_metadata(msg.metadata.clone());
+                        if let Err(e) = quarantine.add(poison).await {
+                            error!("Failed to quarantine undecodable message of {}: {}", event_name, e);
+                        }
+                        return Ok(());
+                    }
+
+                    // The reference is kept to release the payload once the
+                    // handlers succeed.
+                    let mut msg = msg;
+                    let mut claimed = None;
+                    if let Some(claim_check) = &claim_check {
+                        let payload = claim_check.resolve(msg.payload.clone()).await.map_err(|e| {
+                            error!("Failed to resolve claim-check payload for {}: {}", event_name, e);
+                            e
+                        })?;
+                        claimed = Some(std::mem::replace(&mut msg.payload, payload));
+                    }
+
                     let span = tracing::info_span!(
                         "event_processing",
                         event = %event_name,
@@ -96,14 +317,26 @@ impl EventBus {
 
                     let mut metadata = std::collections::HashMap::new();
                     metadata.insert("event".to_string(), event_name.clone());
+                    if let Some(context) = TraceContext::from_metadata(&msg.metadata) {
+                        metadata.extend(context.to_metadata());
+                    }
                     let trace_id = trace_store
                         .start_trace(event_name.clone(), TraceEntryType::Event, metadata)
                         .await;
 
+                    let last_attempt = if redelivers { redelivery.attempt } else { max_attempts };
+                    let mut succeeded_handlers = redelivery.succeeded.clone();
                     let mut any_handler_failed = false;
+                    let mut retry_wanted = false;
+                    let mut attempts_made = 0;
                     let mut first_error: Option<String> = None;
+                    let mut failed_handlers = Vec::new();
 
                     for handler_name in &handlers {
+                        if redelivery.succeeded.contains(handler_name) {
+                            debug!("Handler {} already succeeded on {}, skipping it", handler_name, event_name);
+                            continue;
+                        }
                         let handler_span = tracing::info_span!(
                             "event_handler",
                             handler = %handler_name,
@@ -113,15 +346,61 @@ impl EventBus {
                         
                         info!("Executing handler: {} for event: {}", handler_name, event_name);
 
-                        let mut handler_context =
-                            rohas_runtime::HandlerContext::new(handler_name, msg.payload.clone());
-                        handler_context = handler_context.with_metadata("event_name", &event_name);
-                        handler_context = handler_context
-                            .with_metadata("event_payload_type", &event_payload_type);
+                        let mut attempt = redelivery.attempt - 1;
+                        let (result, duration_ms, snapshot) = loop {
+                            attempt += 1;
+                            let mut handler_context =
+                                rohas_runtime::HandlerContext::new(handler_name, msg.payload.clone());
+                            handler_context = handler_context.with_metadata("event_name", &event_name);
+                            handler_context = handler_context
+                                .with_metadata("event_payload_type", &event_payload_type)
+                                .with_metadata(rohas_runtime::HandlerContext::TRACE_ID_KEY, &trace_id);
 
-                        let start = std::time::Instant::now();
-                        let result = executor.execute_with_context(handler_context).await;
-                        let duration_ms = start.elapsed().as_millis() as u64;
+                            let snapshot = trace_store.snapshot(&handler_context);
+                            let start = std::time::Instant::now();
+                            let result = match chaos.before_handler(handler_name).await {
+                                Some(error) => Ok(rohas_runtime::HandlerResult::error(error, 0)),
+                                None => {
+                                    crate::recording::with_calls(&snapshot, executor.execute_with_context(handler_context))
+                                        .await
+                                }
+                            };
+                            let duration_ms = start.elapsed().as_millis() as u64;
+                            metrics.record_handler(&event_name, duration_ms);
+
+                            let succeeded = matches!(&result, Ok(exec_result) if exec_result.success);
+                            let condition = match &result {
+                                Err(rohas_runtime::RuntimeError::Timeout(_)) => RetryCondition::Timeout,
+                                _ => RetryCondition::Error,
+                            };
+                            let retryable = retry.as_ref().is_none_or(|policy| policy.retries(condition));
+                            if !succeeded && retryable {
+                                retry_wanted = true;
+                            }
+                            if succeeded || attempt >= last_attempt || !retryable {
+                                break (result, duration_ms, snapshot);
+                            }
+                            warn!(
+                                "Handler {} failed on attempt {}/{} for event {}, retrying",
+                                handler_name, attempt, max_attempts, event_name
+                            );
+                            if let Some(policy) = &retry {
+                                let delay_ms = policy.backoff.delay_ms(attempt);
+                                if delay_ms > 0 {
+                                    // Other messages may run while this one waits.
+                                    drop(slot.take());
+                                    tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
+                                    slot = Some(metrics.acquire_slot(&event_name, &slots).await);
+                                }
+                            }
+                        };
+                        trace_store.record(&trace_id, snapshot, &result).await;
+                        if matches!(&result, Ok(exec_result) if exec_result.success) {
+                            succeeded_handlers.push(handler_name.clone());
+                        } else {
+                            failed_handlers.push(handler_name.clone());
+                            attempts_made = attempts_made.max(attempt);
+                        }
 
                         match &result {
                             Ok(exec_result) => {
@@ -137,6 +416,7 @@ impl EventBus {
 
                                 if exec_result.success {
                                     info!("Handler {} completed successfully", handler_name);
+                                    crate::jobs::schedule(&jobs, handler_name, &exec_result.scheduled).await;
                                 } else {
                                     any_handler_failed = true;
                                     if first_error.is_none() {
@@ -174,18 +454,127 @@ impl EventBus {
                         }
                     }
 
+                    // A failure is quarantined once no further delivery will
+                    // follow: on the last attempt of the retry policy, or on
+                    // the adapter's last delivery when it does the retries.
+                    // A failure the retry policy does not retry is final on
+                    // any adapter. Either way the message only forwards and
+                    // triggers downstream events once its handlers succeed.
+                    let out_of_attempts = if requeues {
+                        redelivery.attempt >= max_attempts
+                    } else {
+                        msg.metadata.contains_key(FINAL_DELIVERY)
+                    };
+                    let final_delivery = !redelivers || !retry_wanted || out_of_attempts;
+                    if any_handler_failed && final_delivery {
+                        let poison = QuarantinedMessage::new(
+                            &event_name,
+                            PoisonKind::Handler,
+                            msg.payload.clone(),
+                            first_error.clone().unwrap_or_else(|| "Handler failed".to_string()),
+                            attempts_made,
+                        )
+                        .with_handlers(failed_handlers.clone())
+                        .with_metadata(msg.metadata.clone());
+                        if let Err(e) = quarantine.add(poison).await {
+                            error!("Failed to quarantine message of {}: {}", event_name, e);
+                        }
+                    }
+
+                    if any_handler_failed {
+                        let error = first_error.clone().unwrap_or_else(|| "Handler failed".to_string());
+                        trace_store
+                            .complete_trace(&trace_id, TraceStatus::Failed, first_error)
+                            .await;
+                        if let Some(policy) = retry.as_ref().filter(|_| requeues && !final_delivery) {
+                            let delay_ms = policy.backoff.delay_ms(redelivery.attempt);
+                            let metadata = redelivery.next(&msg.metadata, &succeeded_handlers);
+                            let payload = claimed.clone().unwrap_or_else(|| msg.payload.clone());
+                            // The message waits out its backoff in the
+                            // queue, so it holds no consumer slot meanwhile.
+                            let due = std::time::SystemTime::now() + std::time::Duration::from_millis(delay_ms);
+                            match adapter
+                                .publish_with_metadata_at(
+                                    event_name.clone(),
+                                    payload,
+                                    own_adapter_type.as_deref(),
+                                    metadata,
+                                    due,
+                                )
+                                .await
+                            {
+                                Ok(()) => {
+                                    warn!(
+                                        "Handler(s) {} failed on attempt {}/{} for event {}, retrying in {}ms",
+                                        failed_handlers.join(", "),
+                                        redelivery.attempt,
+                                        max_attempts,
+                                        event_name,
+                                        delay_ms
+                                    );
+                                    return Ok(());
+                                }
+                                // The adapter delivers the message again instead.
+                                Err(e) => error!("Failed to put {} back for a retry: {}", event_name, e),
+                            }
+                        }
+                        // A quarantined message is acknowledged without
+                        // forwarding it or triggering downstream events,
+                        // unless the adapter does the retries: it gets the
+                        // error back and dead-letters the message.
+                        let adapter_retries = redelivers && !requeues && retry_wanted;
+                        if final_delivery && !adapter_retries {
+                            return Ok(());
+                        }
+                        return Err(EngineError::EventDispatch(format!(
+                            "Handler(s) {} failed on {}: {}",
+                            failed_handlers.join(", "),
+                            event_name,
+                            error
+                        )));
+                    }
+
+                    if let Some(forward) = &forward {
+                        let (websocket, filter) = forward.as_ref();
+                        let start = std::time::Instant::now();
+                        let recipients = forward_to_websocket(&ws_sessions, websocket, filter.as_ref(), &event_name, &msg.payload).await;
+                        debug!("Forwarded {} to {} {} connection(s)", event_name, recipients, websocket);
+                        trace_store
+                            .add_step(
+                                &trace_id,
+                                format!("forward:{}", websocket),
+                                start.elapsed().as_millis() as u64,
+                                true,
+                                None,
+                            )
+                            .await;
+                    }
+
                     let mut triggered_events: Vec<TriggeredEventInfo> = Vec::new();
+                    let trace_context = trace_store.context(&trace_id).await.to_metadata();
                     for trigger in &triggers {
                         info!("Triggering downstream event: {}", trigger);
                         let trigger_start = std::time::Instant::now();
                         let trigger_event = schema.events.iter().find(|e| e.name == *trigger);
                         let adapter_type = trigger_event.and_then(|e| e.adapter_type.as_deref());
-                        let publish_result = adapter.publish_with_type(trigger, msg.payload.clone(), adapter_type).await;
+                        let publish_result = if chaos.fail_publish(trigger) {
+                            Err(EngineError::Adapter(format!("chaos: injected publish failure for {}", trigger)))
+                        } else {
+                            match offload(claim_check.as_deref(), trigger, msg.payload.clone()).await {
+                                Ok(payload) => {
+                                    adapter
+                                        .publish_with_metadata(trigger, payload, adapter_type, trace_context.clone())
+                                        .await
+                                }
+                                Err(e) => Err(e),
+                            }
+                        };
                         let trigger_duration = trigger_start.elapsed().as_millis() as u64;
                         let trigger_timestamp = chrono::Utc::now().to_rfc3339();
 
                         match publish_result {
                             Ok(_) => {
+                                metrics.record_publish(trigger);
                                 if let Some(adapter_type) = adapter_type {
                                     info!("Successfully triggered event: {} (via {})", trigger, adapter_type);
                                 } else {
@@ -220,18 +609,17 @@ impl EventBus {
                             .await;
                     }
 
-                    let status = if any_handler_failed {
-                        TraceStatus::Failed
-                    } else {
-                        TraceStatus::Success
-                    };
+                    if let (Some(claim_check), Some(reference)) = (&claim_check, &claimed) {
+                        claim_check.release(reference).await;
+                    }
+
                     trace_store
-                        .complete_trace(&trace_id, status, first_error)
+                        .complete_trace(&trace_id, TraceStatus::Success, None)
                         .await;
 
                     Ok(())
                 }
-            }, adapter_type_clone)
+            }, adapter_type_clone, prefetch)
             .await?;
 
         Ok(())
@@ -241,6 +629,27 @@ impl EventBus {
         &self,
         event_name: impl Into<String>,
         payload: serde_json::Value,
+    ) -> Result<()> {
+        self.emit_with_context(event_name, payload, None).await
+    }
+
+    /// Emits an event on behalf of the trace `trace_id`, which the event's
+    /// trace then links back to.
+    pub async fn emit_from(
+        &self,
+        trace_id: &str,
+        event_name: impl Into<String>,
+        payload: serde_json::Value,
+    ) -> Result<()> {
+        let context = self.trace_store.context(trace_id).await;
+        self.emit_with_context(event_name, payload, Some(context)).await
+    }
+
+    async fn emit_with_context(
+        &self,
+        event_name: impl Into<String>,
+        payload: serde_json::Value,
+        context: Option<TraceContext>,
     ) -> Result<()> {
         let event_name = event_name.into();
         info!("Emitting event: {}", event_name);
@@ -248,8 +657,23 @@ impl EventBus {
         let event = self.schema.events.iter().find(|e| e.name == event_name);
         let adapter_type = event.and_then(|e| e.adapter_type.as_deref());
 
-        match self.adapter.publish_with_type(event_name.clone(), payload, adapter_type).await {
+        let publish_result = if self.chaos.fail_publish(&event_name) {
+            Err(EngineError::Adapter("chaos: injected publish failure".to_string()))
+        } else {
+            match offload(self.claim_check.as_deref(), &event_name, payload).await {
+                Ok(payload) => {
+                    let metadata = context.map(|c| c.to_metadata()).unwrap_or_default();
+                    self.adapter
+                        .publish_with_metadata(event_name.clone(), payload, adapter_type, metadata)
+                        .await
+                }
+                Err(e) => Err(e),
+            }
+        };
+
+        match publish_result {
             Ok(_) => {
+                self.metrics.record_publish(&event_name);
                 if let Some(adapter_type) = adapter_type {
                     info!("Successfully emitted event: {} (via {})", event_name, adapter_type);
                 } else {
@@ -264,3 +688,395 @@ impl EventBus {
         }
     }
 }
+
+async fn offload(
+    claim_check: Option<&ClaimCheck>,
+    topic: &str,
+    payload: serde_json::Value,
+) -> Result<serde_json::Value> {
+    match claim_check {
+        Some(claim_check) => claim_check.offload(topic, payload).await,
+        None => Ok(payload),
+    }
+}
+
+/// Pushes an event to the connections of a websocket whose filter matches.
+async fn forward_to_websocket(
+    ws_sessions: &SessionRegistry,
+    websocket: &str,
+    filter: Option<&Filter>,
+    event_name: &str,
+    payload: &serde_json::Value,
+) -> usize {
+    let message = serde_json::json!({
+        EVENT_KEY: event_name,
+        "data": payload,
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+    })
+    .to_string();
+
+    ws_sessions
+        .broadcast(websocket, &message, |connection| {
+            filter.is_none_or(|filter| {
+                filter.matches(&serde_json::json!({ "payload": payload, "connection": connection }))
+            })
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapter::AdapterBackend;
+    use crate::config::{ChaosConfig, TraceSamplingConfig};
+    use crate::telemetry::TelemetryManager;
+    #[cfg(feature = "rocksdb")]
+    use adapter_rocksdb::queue::{QueueAdapter, QueueConfig};
+    use rohas_runtime::{Handler, HandlerContext, HandlerResult, RuntimeConfig};
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    /// Declines every card, counting its runs.
+    #[derive(Default)]
+    struct Failing {
+        runs: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl Handler for Failing {
+        async fn execute(&self, _context: HandlerContext) -> rohas_runtime::Result<HandlerResult> {
+            self.runs.fetch_add(1, Ordering::SeqCst);
+            Ok(HandlerResult::error("card declined", 0))
+        }
+
+        fn name(&self) -> &str {
+            "charge_card"
+        }
+    }
+
+    /// Sends every receipt, counting its runs.
+    #[cfg(feature = "rocksdb")]
+    #[derive(Default)]
+    struct Receipt {
+        runs: AtomicUsize,
+    }
+
+    #[cfg(feature = "rocksdb")]
+    #[async_trait::async_trait]
+    impl Handler for Receipt {
+        async fn execute(&self, _context: HandlerContext) -> rohas_runtime::Result<HandlerResult> {
+            self.runs.fetch_add(1, Ordering::SeqCst);
+            Ok(HandlerResult::success(json!(null), 0))
+        }
+
+        fn name(&self) -> &str {
+            "send_receipt"
+        }
+    }
+
+    fn memory_adapter() -> Arc<Adapter> {
+        Arc::new(Adapter::new(AdapterBackend::Memory(Arc::new(
+            adapter_memory::MemoryAdapter::default(),
+        ))))
+    }
+
+    /// An initialized bus for `OrderPlaced`, handled by `charge_card`, with
+    /// `extra` added to the event's definition.
+    async fn event_bus(adapter: Arc<Adapter>, handler: Arc<Failing>, extra: &str, dir: &TempDir) -> EventBus {
+        event_bus_with(adapter, vec![handler], extra, dir).await
+    }
+
+    /// An initialized bus for `OrderPlaced`, handled by `handlers` in order.
+    async fn event_bus_with(adapter: Arc<Adapter>, handlers: Vec<Arc<dyn Handler>>, extra: &str, dir: &TempDir) -> EventBus {
+        let names = handlers.iter().map(|h| h.name().to_string()).collect::<Vec<_>>().join(", ");
+        let executor = Arc::new(Executor::new(RuntimeConfig::default()));
+        for handler in handlers {
+            executor.register_handler(handler).await;
+        }
+        let schema = rohas_parser::Parser::parse_string(&format!(
+            r#"
+            model Order {{
+                id Int
+            }}
+
+            ws Orders {{
+                path: "/ws/orders"
+            }}
+
+            event OrderPlaced {{
+                payload: Order
+                handler: [{}]
+                {}
+            }}
+            "#,
+            names, extra
+        ))
+        .unwrap();
+        let telemetry = Arc::new(TelemetryManager::new(dir.path().join("telemetry"), 1).await.unwrap());
+        let bus = EventBus::new(
+            adapter,
+            executor,
+            Arc::new(schema),
+            Arc::new(TraceStore::new(telemetry.clone(), TraceSamplingConfig::default())),
+            Arc::new(ChaosController::new(ChaosConfig::default())),
+            Arc::new(EventMetrics::new(telemetry.metric_store())),
+        )
+        .with_quarantine(Arc::new(QuarantineStore::in_memory(10)), 1);
+        bus.initialize().await.unwrap();
+        bus
+    }
+
+    async fn quarantined(bus: &EventBus, count: usize) -> Vec<QuarantinedMessage> {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let messages = bus.quarantine().list().await;
+                if messages.len() >= count {
+                    return messages;
+                }
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await
+        .expect("timed out waiting for the quarantine")
+    }
+
+    #[test]
+    fn test_redelivery_carries_the_attempt_in_metadata() {
+        let first = Redelivery::from_metadata(&HashMap::new());
+        assert_eq!(first, Redelivery::first());
+
+        let metadata = HashMap::from([
+            ("root_trace_id".to_string(), "trace-1".to_string()),
+            (FINAL_DELIVERY.to_string(), "true".to_string()),
+        ]);
+        let next = first.next(&metadata, &["send_receipt".to_string()]);
+        let second = Redelivery::from_metadata(&next);
+
+        assert_eq!(second.attempt, 2);
+        assert_eq!(second.succeeded, vec!["send_receipt"]);
+        assert_eq!(next["root_trace_id"], "trace-1");
+        assert!(!next.contains_key(FINAL_DELIVERY));
+    }
+
+    #[tokio::test]
+    async fn test_retry_policy_retries_in_process() {
+        let dir = TempDir::new().unwrap();
+        let adapter = memory_adapter();
+        let handler = Arc::new(Failing::default());
+        let bus = event_bus(adapter.clone(), handler.clone(), "retry: { max: 2 }", &dir).await;
+
+        adapter.publish("OrderPlaced", json!({"id": 1})).await.unwrap();
+        let messages = quarantined(&bus, 1).await;
+
+        assert_eq!(handler.runs.load(Ordering::SeqCst), 3);
+        assert_eq!(messages[0].attempts, 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_policy_only_retries_listed_failures() {
+        let dir = TempDir::new().unwrap();
+        let adapter = memory_adapter();
+        let handler = Arc::new(Failing::default());
+        let bus = event_bus(adapter.clone(), handler.clone(), "retry: { max: 2, on: [Timeout] }", &dir).await;
+
+        adapter.publish("OrderPlaced", json!({"id": 1})).await.unwrap();
+        let messages = quarantined(&bus, 1).await;
+
+        assert_eq!(handler.runs.load(Ordering::SeqCst), 1);
+        assert_eq!(messages[0].attempts, 1);
+    }
+
+    #[tokio::test]
+    async fn test_backoff_releases_the_handler_slot() {
+        let dir = TempDir::new().unwrap();
+        let adapter = memory_adapter();
+        let handler = Arc::new(Failing::default());
+        let bus = event_bus(
+            adapter.clone(),
+            handler.clone(),
+            "concurrency: 1\n                prefetch: 2\n                retry: { max: 1, backoff: fixed(500ms) }",
+            &dir,
+        )
+        .await;
+
+        adapter.publish("OrderPlaced", json!({"id": 1})).await.unwrap();
+        adapter.publish("OrderPlaced", json!({"id": 2})).await.unwrap();
+        // Both first attempts run while the other message waits out its backoff.
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert_eq!(handler.runs.load(Ordering::SeqCst), 2);
+
+        quarantined(&bus, 2).await;
+        assert_eq!(handler.runs.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_quarantined_messages_do_not_forward_or_trigger() {
+        let dir = TempDir::new().unwrap();
+        let adapter = memory_adapter();
+        let triggered = Arc::new(AtomicUsize::new(0));
+        let counter = triggered.clone();
+        adapter
+            .subscribe_fn("OrderCharged", move |_| {
+                let counter = counter.clone();
+                async move {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                }
+            })
+            .await
+            .unwrap();
+        let bus = event_bus(
+            adapter.clone(),
+            Arc::new(Failing::default()),
+            "triggers: [OrderCharged]\n                forward_to: Orders",
+            &dir,
+        )
+        .await;
+
+        adapter.publish("OrderPlaced", json!({"id": 1})).await.unwrap();
+        quarantined(&bus, 1).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert_eq!(triggered.load(Ordering::SeqCst), 0);
+        let traces = bus.trace_store.get_traces(None).await;
+        let trace = traces.iter().find(|t| t.entry_point == "OrderPlaced").unwrap();
+        assert!(matches!(trace.status, TraceStatus::Failed));
+        assert!(trace.steps.iter().all(|step| !step.name.starts_with("forward:")));
+    }
+
+    #[cfg(feature = "rocksdb")]
+    fn embedded_adapter(dir: &TempDir) -> Arc<Adapter> {
+        let queue = QueueAdapter::open(QueueConfig {
+            path: dir.path().join("queue"),
+            max_retries: 1,
+            retry_delay: Duration::from_millis(10),
+        })
+        .unwrap();
+        Arc::new(Adapter::new(AdapterBackend::Embedded(Arc::new(queue))))
+    }
+
+    #[cfg(feature = "rocksdb")]
+    async fn dead_letters(adapter: &Adapter) -> Vec<crate::adapter::DeadLetter> {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let dead = adapter.dead_letters("OrderPlaced").await.unwrap().unwrap();
+                if !dead.is_empty() {
+                    return dead;
+                }
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await
+        .expect("timed out waiting for the dead letter")
+    }
+
+    #[cfg(feature = "rocksdb")]
+    #[tokio::test]
+    async fn test_embedded_queue_dead_letters_failed_messages() {
+        let dir = TempDir::new().unwrap();
+        let adapter = embedded_adapter(&dir);
+        let bus = event_bus(adapter.clone(), Arc::new(Failing::default()), "", &dir).await;
+
+        adapter.publish("OrderPlaced", json!({"id": 1})).await.unwrap();
+        let dead = dead_letters(&adapter).await;
+
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].payload, json!({"id": 1}));
+        assert_eq!(dead[0].attempts, 2);
+        assert!(dead[0].last_error.as_deref().unwrap().contains("card declined"));
+        // Only the final delivery is captured before the message is dead-lettered.
+        assert_eq!(bus.quarantine().list().await.len(), 1);
+    }
+
+    #[cfg(feature = "rocksdb")]
+    #[tokio::test]
+    async fn test_embedded_queue_enforces_the_retry_policy() {
+        let dir = TempDir::new().unwrap();
+        let adapter = embedded_adapter(&dir);
+        let handler = Arc::new(Failing::default());
+        let bus = event_bus(adapter.clone(), handler.clone(), "retry: { max: 2, backoff: fixed(50ms) }", &dir).await;
+
+        let start = std::time::Instant::now();
+        adapter.publish("OrderPlaced", json!({"id": 1})).await.unwrap();
+        let messages = quarantined(&bus, 1).await;
+
+        // The policy's attempts, not the queue's, each after its backoff.
+        assert_eq!(handler.runs.load(Ordering::SeqCst), 3);
+        assert_eq!(messages[0].attempts, 3);
+        assert!(start.elapsed() >= Duration::from_millis(100));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(adapter.dead_letters("OrderPlaced").await.unwrap().unwrap().is_empty());
+    }
+
+    #[cfg(feature = "rocksdb")]
+    #[tokio::test]
+    async fn test_embedded_queue_backoff_holds_no_consumer_slot() {
+        let dir = TempDir::new().unwrap();
+        let adapter = embedded_adapter(&dir);
+        let handler = Arc::new(Failing::default());
+        let _bus = event_bus(
+            adapter.clone(),
+            handler.clone(),
+            "concurrency: 1\n                prefetch: 1\n                retry: { max: 1, backoff: fixed(2s) }",
+            &dir,
+        )
+        .await;
+
+        adapter.publish("OrderPlaced", json!({"id": 1})).await.unwrap();
+        let runs = |count| {
+            let handler = handler.clone();
+            tokio::time::timeout(Duration::from_secs(1), async move {
+                while handler.runs.load(Ordering::SeqCst) < count {
+                    tokio::time::sleep(Duration::from_millis(1)).await;
+                }
+            })
+        };
+        runs(1).await.expect("the first message was not handled");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        // The first message waits out its backoff in the queue, so the
+        // second one is handled without waiting for it.
+        adapter.publish("OrderPlaced", json!({"id": 2})).await.unwrap();
+        runs(2).await.expect("the second message waited for the first one's backoff");
+    }
+
+    #[cfg(feature = "rocksdb")]
+    #[tokio::test]
+    async fn test_embedded_queue_follows_the_retry_policy_filter() {
+        let dir = TempDir::new().unwrap();
+        let adapter = embedded_adapter(&dir);
+        let handler = Arc::new(Failing::default());
+        let bus = event_bus(adapter.clone(), handler.clone(), "retry: { max: 2, on: [Timeout] }", &dir).await;
+
+        adapter.publish("OrderPlaced", json!({"id": 1})).await.unwrap();
+        let messages = quarantined(&bus, 1).await;
+
+        assert_eq!(handler.runs.load(Ordering::SeqCst), 1);
+        assert_eq!(messages[0].attempts, 1);
+    }
+
+    #[cfg(feature = "rocksdb")]
+    #[tokio::test]
+    async fn test_embedded_queue_skips_handlers_that_succeeded() {
+        let dir = TempDir::new().unwrap();
+        let adapter = embedded_adapter(&dir);
+        let receipt = Arc::new(Receipt::default());
+        let charge = Arc::new(Failing::default());
+        let bus = event_bus_with(
+            adapter.clone(),
+            vec![receipt.clone(), charge.clone()],
+            "retry: { max: 2 }",
+            &dir,
+        )
+        .await;
+
+        adapter.publish("OrderPlaced", json!({"id": 1})).await.unwrap();
+        let messages = quarantined(&bus, 1).await;
+
+        assert_eq!(receipt.runs.load(Ordering::SeqCst), 1);
+        assert_eq!(charge.runs.load(Ordering::SeqCst), 3);
+        assert_eq!(messages[0].handlers, vec!["charge_card"]);
+    }
+}
@@ -0,0 +1,247 @@
+use crate::adapter::Adapter;
+use chrono::{DateTime, Utc};
+use rohas_telemetry::{Metric, MetricStore, MetricType};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tracing::{info, warn};
+
+/// How often per-topic counters are written to the metric store.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(10);
+
+pub const PUBLISHED: &str = "event.published";
+pub const CONSUMED: &str = "event.consumed";
+pub const HANDLER_LATENCY: &str = "event.handler_latency_ms";
+pub const QUEUE_DEPTH: &str = "event.queue_depth";
+pub const IN_FLIGHT: &str = "event.in_flight";
+pub const WAITING: &str = "event.waiting";
+pub const SATURATION: &str = "event.saturation";
+
+const SERIES: [&str; 7] = [PUBLISHED, CONSUMED, HANDLER_LATENCY, QUEUE_DEPTH, IN_FLIGHT, WAITING, SATURATION];
+
+#[derive(Debug, Default)]
+struct TopicCounters {
+    published: u64,
+    consumed: u64,
+    handler_runs: u64,
+    handler_ms: u64,
+}
+
+/// Live handler occupancy of a topic. The peaks cover the current flush
+/// interval.
+#[derive(Debug, Default)]
+struct Occupancy {
+    concurrency: usize,
+    in_flight: usize,
+    waiting: usize,
+    peak_in_flight: usize,
+    peak_waiting: usize,
+}
+
+/// A handler slot of a topic, released when dropped.
+pub struct HandlerSlot {
+    metrics: Arc<EventMetrics>,
+    topic: String,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl Drop for HandlerSlot {
+    fn drop(&mut self) {
+        if let Some(occupancy) = self.metrics.occupancy.lock().unwrap().get_mut(&self.topic) {
+            occupancy.in_flight = occupancy.in_flight.saturating_sub(1);
+        }
+    }
+}
+
+/// A point in a per-topic metric series.
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricPoint {
+    pub timestamp: String,
+    pub value: f64,
+}
+
+/// Per-topic event throughput and lag series, keyed by series name.
+pub type TopicSeries = BTreeMap<String, BTreeMap<&'static str, Vec<MetricPoint>>>;
+
+/// Counts publishes, deliveries and handler latency per event topic and
+/// periodically writes them to the telemetry metric store, together with the
+/// adapter's queue depth where the adapter can report one and how saturated
+/// each topic's handler slots are.
+pub struct EventMetrics {
+    metric_store: Arc<MetricStore>,
+    topics: Mutex<HashMap<String, TopicCounters>>,
+    occupancy: Mutex<HashMap<String, Occupancy>>,
+}
+
+impl EventMetrics {
+    pub fn new(metric_store: Arc<MetricStore>) -> Self {
+        Self {
+            metric_store,
+            topics: Mutex::new(HashMap::new()),
+            occupancy: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Records how many handlers of a topic may run at once.
+    pub fn set_concurrency(&self, topic: &str, concurrency: usize) {
+        self.occupancy.lock().unwrap().entry(topic.to_string()).or_default().concurrency = concurrency;
+    }
+
+    /// Waits for one of `slots`, counting the message as waiting until it
+    /// gets one and as in flight until the returned slot is dropped.
+    pub async fn acquire_slot(self: &Arc<Self>, topic: &str, slots: &Arc<Semaphore>) -> HandlerSlot {
+        self.update_occupancy(topic, |o| o.waiting += 1);
+        let permit = slots.clone().acquire_owned().await.expect("handler slots are never closed");
+        self.update_occupancy(topic, |o| {
+            o.waiting = o.waiting.saturating_sub(1);
+            o.in_flight += 1;
+        });
+        HandlerSlot {
+            metrics: self.clone(),
+            topic: topic.to_string(),
+            _permit: permit,
+        }
+    }
+
+    fn update_occupancy(&self, topic: &str, update: impl FnOnce(&mut Occupancy)) {
+        let mut occupancy = self.occupancy.lock().unwrap();
+        let occupancy = occupancy.entry(topic.to_string()).or_default();
+        update(occupancy);
+        occupancy.peak_in_flight = occupancy.peak_in_flight.max(occupancy.in_flight);
+        occupancy.peak_waiting = occupancy.peak_waiting.max(occupancy.waiting);
+    }
+
+    pub fn record_publish(&self, topic: &str) {
+        self.topics.lock().unwrap().entry(topic.to_string()).or_default().published += 1;
+    }
+
+    pub fn record_consume(&self, topic: &str) {
+        self.topics.lock().unwrap().entry(topic.to_string()).or_default().consumed += 1;
+    }
+
+    pub fn record_handler(&self, topic: &str, duration_ms: u64) {
+        let mut topics = self.topics.lock().unwrap();
+        let counters = topics.entry(topic.to_string()).or_default();
+        counters.handler_runs += 1;
+        counters.handler_ms += duration_ms;
+    }
+
+    /// Starts the background task that flushes counters every
+    /// [`FLUSH_INTERVAL`].
+    pub fn start(self: Arc<Self>, adapter: Arc<Adapter>, topics: Vec<String>) {
+        info!(
+            "Started event metrics task (flushing every {}s)",
+            FLUSH_INTERVAL.as_secs()
+        );
+
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(FLUSH_INTERVAL).await;
+                self.flush(&adapter, &topics).await;
+            }
+        });
+    }
+
+    /// Writes the counts accumulated since the last flush. Counters are
+    /// stored as per-interval deltas, latency as the interval's mean and
+    /// occupancy as the interval's peak.
+    async fn flush(&self, adapter: &Adapter, topics: &[String]) {
+        let counters = std::mem::take(&mut *self.topics.lock().unwrap());
+        let timestamp = Utc::now().to_rfc3339();
+        let mut metrics = Vec::new();
+
+        for (topic, counters) in &counters {
+            metrics.push(metric(PUBLISHED, MetricType::Counter, counters.published as f64, topic, &timestamp, None));
+            metrics.push(metric(CONSUMED, MetricType::Counter, counters.consumed as f64, topic, &timestamp, None));
+            if counters.handler_runs > 0 {
+                let mean = counters.handler_ms as f64 / counters.handler_runs as f64;
+                metrics.push(metric(HANDLER_LATENCY, MetricType::Gauge, mean, topic, &timestamp, Some("ms")));
+            }
+        }
+
+        for (topic, occupancy) in self.occupancy.lock().unwrap().iter_mut() {
+            let (in_flight, waiting) = (occupancy.peak_in_flight, occupancy.peak_waiting);
+            metrics.push(metric(IN_FLIGHT, MetricType::Gauge, in_flight as f64, topic, &timestamp, None));
+            metrics.push(metric(WAITING, MetricType::Gauge, waiting as f64, topic, &timestamp, None));
+            if occupancy.concurrency > 0 {
+                let saturation = in_flight as f64 / occupancy.concurrency as f64;
+                metrics.push(metric(SATURATION, MetricType::Gauge, saturation, topic, &timestamp, None));
+            }
+            occupancy.peak_in_flight = occupancy.in_flight;
+            occupancy.peak_waiting = occupancy.waiting;
+        }
+
+        for topic in topics {
+            match adapter.queue_depth(topic).await {
+                Ok(Some(depth)) => {
+                    metrics.push(metric(QUEUE_DEPTH, MetricType::Gauge, depth as f64, topic, &timestamp, None));
+                }
+                Ok(None) => {}
+                Err(e) => warn!("Failed to read queue depth for {}: {}", topic, e),
+            }
+        }
+
+        for metric in metrics {
+            if let Err(e) = self.metric_store.store(metric).await {
+                warn!("Failed to store event metric: {}", e);
+            }
+        }
+    }
+
+    /// Reads the stored series for every topic (or only `topic`) in a time range.
+    pub async fn series(
+        &self,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+        topic: Option<&str>,
+    ) -> rohas_telemetry::Result<TopicSeries> {
+        let mut series: TopicSeries = BTreeMap::new();
+
+        for name in SERIES {
+            for metric in self.metric_store.query(name, start_time, end_time, None).await? {
+                let Some(metric_topic) = metric.labels.get("topic") else {
+                    continue;
+                };
+                if topic.is_some_and(|t| t != metric_topic) {
+                    continue;
+                }
+                series
+                    .entry(metric_topic.clone())
+                    .or_default()
+                    .entry(name)
+                    .or_default()
+                    .push(MetricPoint {
+                        timestamp: metric.timestamp,
+                        value: metric.value,
+                    });
+            }
+        }
+
+        for points in series.values_mut().flat_map(|s| s.values_mut()) {
+            points.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        }
+
+        Ok(series)
+    }
+}
+
+fn metric(
+    name: &str,
+    metric_type: MetricType,
+    value: f64,
+    topic: &str,
+    timestamp: &str,
+    unit: Option<&str>,
+) -> Metric {
+    Metric {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: name.to_string(),
+        metric_type,
+        value,
+        timestamp: timestamp.to_string(),
+        labels: HashMap::from([("topic".to_string(), topic.to_string())]),
+        unit: unit.map(str::to_string),
+    }
+}
@@ -0,0 +1,242 @@
+//! Running a single handler outside the server, for `rohas run` and
+//! `rohas lambda`.
+
+use crate::config::Language;
+use crate::error::{EngineError, Result};
+use crate::event::EventBus;
+use crate::telemetry::TraceStore;
+use crate::trace::{TraceContext, TraceEntryType, TraceStatus, TriggeredEventInfo};
+use rohas_codegen::templates;
+use rohas_parser::{Event, Schema};
+use rohas_runtime::{Executor, HandlerContext, HandlerResult};
+use serde_json::Value;
+use std::collections::HashMap;
+use tracing::{error, info};
+
+/// Outcome of a handler run with [`crate::Engine::invoke`].
+#[derive(Debug)]
+pub struct Invocation {
+    pub trace_id: String,
+    pub result: HandlerResult,
+    pub triggered_events: Vec<TriggeredEventInfo>,
+    /// The events the run published, for callers that run their handlers
+    /// themselves since nothing consumes them without an initialized engine.
+    pub emitted: Vec<EmittedEvent>,
+}
+
+/// An event published by an [`Invocation`], with the metadata that links
+/// its handler runs to the publishing trace.
+#[derive(Debug, Clone)]
+pub struct EmittedEvent {
+    pub event_name: String,
+    pub payload: Value,
+    pub metadata: HashMap<String, String>,
+}
+
+/// The schema entry a handler name refers to.
+struct Target {
+    handler: String,
+    entry_type: TraceEntryType,
+    metadata: HashMap<String, String>,
+    /// Metadata the handler sees in its context.
+    context: Vec<(&'static str, String)>,
+    /// Events the schema triggers after a successful run.
+    triggers: Vec<String>,
+}
+
+impl Target {
+    fn resolve(schema: &Schema, language: &Language, name: &str) -> Option<Self> {
+        if let Some(api) = schema.apis.iter().find(|api| api.name == name) {
+            let handler = match language {
+                Language::TypeScript => api.name.clone(),
+                Language::Python | Language::Rust => templates::to_snake_case(&api.name),
+            };
+            return Some(Self {
+                handler,
+                entry_type: TraceEntryType::Api,
+                metadata: HashMap::from([("api_name".to_string(), api.name.clone())]),
+                context: Vec::new(),
+                triggers: api.triggers.clone(),
+            });
+        }
+
+        if let Some(event) = schema.events.iter().find(|event| event.handlers.iter().any(|h| h == name)) {
+            return Some(Self::event_handler(event, name));
+        }
+
+        schema.crons.iter().find(|cron| cron.name == name).map(|cron| Self {
+            handler: cron.name.clone(),
+            entry_type: TraceEntryType::Cron,
+            metadata: HashMap::from([
+                ("cron_name".to_string(), cron.name.clone()),
+                ("schedule".to_string(), cron.schedule.clone()),
+            ]),
+            context: Vec::new(),
+            triggers: cron.triggers.clone(),
+        })
+    }
+
+    fn event_handler(event: &Event, handler: &str) -> Self {
+        Self {
+            handler: handler.to_string(),
+            entry_type: TraceEntryType::Event,
+            metadata: HashMap::from([("event".to_string(), event.name.clone())]),
+            context: vec![
+                ("event_name", event.name.clone()),
+                ("event_payload_type", event.payload.clone()),
+            ],
+            triggers: event.triggers.clone(),
+        }
+    }
+}
+
+/// Runs the API, event or cron handler `name` once with `payload`, publishing
+/// its triggers and storing the calls it schedules. Without an initialized
+/// engine nothing consumes the published events; they are returned in
+/// [`Invocation::emitted`] so the caller can run their handlers.
+pub async fn invoke(
+    schema: &Schema,
+    language: &Language,
+    executor: &Executor,
+    event_bus: &EventBus,
+    trace_store: &TraceStore,
+    name: &str,
+    payload: Value,
+) -> Result<Invocation> {
+    let mut target = Target::resolve(schema, language, name).ok_or_else(|| {
+        EngineError::Config(format!("No API, event handler or cron named '{}' in the schema", name))
+    })?;
+    target.metadata.insert("invocation".to_string(), "cli".to_string());
+
+    run(executor, event_bus, trace_store, name, target, payload).await
+}
+
+/// Runs every handler of `event_name` once with a delivered `payload`, for
+/// events Lambda receives instead of an adapter subscription. `metadata` is
+/// the delivered message's and links the runs to the publishing trace.
+pub async fn invoke_event(
+    schema: &Schema,
+    executor: &Executor,
+    event_bus: &EventBus,
+    trace_store: &TraceStore,
+    event_name: &str,
+    payload: Value,
+    metadata: &HashMap<String, String>,
+) -> Result<Vec<Invocation>> {
+    let event = schema
+        .events
+        .iter()
+        .find(|event| event.name == event_name)
+        .ok_or_else(|| EngineError::EventDispatch(format!("No event named '{}' in the schema", event_name)))?;
+
+    let mut invocations = Vec::with_capacity(event.handlers.len());
+    for handler in &event.handlers {
+        let mut target = Target::event_handler(event, handler);
+        target.metadata.insert("invocation".to_string(), "lambda".to_string());
+        if let Some(context) = TraceContext::from_metadata(metadata) {
+            target.metadata.extend(context.to_metadata());
+        }
+        invocations.push(run(executor, event_bus, trace_store, handler, target, payload.clone()).await?);
+    }
+    Ok(invocations)
+}
+
+async fn run(
+    executor: &Executor,
+    event_bus: &EventBus,
+    trace_store: &TraceStore,
+    name: &str,
+    target: Target,
+    payload: Value,
+) -> Result<Invocation> {
+    let trace_id = trace_store
+        .start_trace(name.to_string(), target.entry_type.clone(), target.metadata.clone())
+        .await;
+
+    // Event triggers pass the incoming event on; the others pass the result.
+    let event_payload = matches!(target.entry_type, TraceEntryType::Event).then(|| payload.clone());
+    let mut context =
+        HandlerContext::new(&target.handler, payload).with_metadata(HandlerContext::TRACE_ID_KEY, &trace_id);
+    for (key, value) in &target.context {
+        context = context.with_metadata(*key, value);
+    }
+
+    info!("Running handler {} once", target.handler);
+    let start = std::time::Instant::now();
+    let snapshot = trace_store.snapshot(&context);
+    let result = crate::recording::with_calls(&snapshot, executor.execute_with_context(context)).await;
+    trace_store.record(&trace_id, snapshot, &result).await;
+    let result = match result {
+        Ok(result) => result,
+        Err(e) => {
+            let err_msg = e.to_string();
+            trace_store
+                .add_step(
+                    &trace_id,
+                    target.handler.clone(),
+                    start.elapsed().as_millis() as u64,
+                    false,
+                    Some(err_msg.clone()),
+                )
+                .await;
+            trace_store
+                .complete_trace(&trace_id, TraceStatus::Failed, Some(err_msg))
+                .await;
+            return Err(e.into());
+        }
+    };
+    let duration_ms = start.elapsed().as_millis() as u64;
+
+    let mut triggered_events = Vec::new();
+    let mut emitted = Vec::new();
+    if result.success {
+        let data = event_payload.unwrap_or_else(|| result.data.clone().unwrap_or(Value::Object(Default::default())));
+        let emits = result
+            .triggers
+            .iter()
+            .map(|trigger| (trigger.event_name.clone(), trigger.payload.clone()))
+            .chain(target.triggers.iter().map(|trigger| {
+                let payload = result.auto_trigger_payloads.get(trigger).cloned().unwrap_or_else(|| data.clone());
+                (trigger.clone(), payload)
+            }));
+
+        for (event_name, payload) in emits {
+            let trigger_start = std::time::Instant::now();
+            match event_bus.emit_from(&trace_id, &event_name, payload.clone()).await {
+                Ok(()) => emitted.push(EmittedEvent {
+                    event_name: event_name.clone(),
+                    payload,
+                    metadata: trace_store.context(&trace_id).await.to_metadata(),
+                }),
+                Err(e) => error!("Failed to emit event {} from {}: {}", event_name, target.handler, e),
+            }
+            triggered_events.push(TriggeredEventInfo {
+                event_name,
+                timestamp: chrono::Utc::now().to_rfc3339(),
+                duration_ms: trigger_start.elapsed().as_millis() as u64,
+            });
+        }
+
+        crate::jobs::schedule(&event_bus.jobs(), &target.handler, &result.scheduled).await;
+    }
+
+    trace_store
+        .add_step_with_triggers(
+            &trace_id,
+            target.handler.clone(),
+            duration_ms.max(result.execution_time_ms),
+            result.success,
+            result.error.clone(),
+            triggered_events.clone(),
+        )
+        .await;
+    let status = if result.success { TraceStatus::Success } else { TraceStatus::Failed };
+    trace_store.complete_trace(&trace_id, status, result.error.clone()).await;
+
+    Ok(Invocation {
+        trace_id,
+        result,
+        triggered_events,
+        emitted,
+    })
+}
@@ -0,0 +1,143 @@
+//! Handler calls scheduled from handler code with
+//! `state.schedule(handler, when, payload)`.
+
+use crate::event::EventBus;
+use crate::telemetry::TraceStore;
+use crate::trace::{TraceEntryType, TraceStatus, TriggeredEventInfo};
+use chrono::{DateTime, Utc};
+use rohas_cron::{expression, JobStore, ScheduledCall};
+use rohas_runtime::{Executor, ScheduleRequest};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::time::{interval, Duration};
+use tracing::{error, info, warn};
+
+/// Stores the calls a handler scheduled. Calls with an invalid `when` are
+/// logged and dropped.
+pub async fn schedule(store: &JobStore, source: &str, requests: &[ScheduleRequest]) {
+    for request in requests {
+        let run_at = match resolve_when(&request.when) {
+            Ok(run_at) => run_at,
+            Err(e) => {
+                warn!("{} scheduled {} with an invalid time: {}", source, request.handler, e);
+                continue;
+            }
+        };
+        if let Err(e) = store.add(&request.handler, run_at, request.payload.clone()).await {
+            error!("Failed to schedule {} from {}: {}", request.handler, source, e);
+        }
+    }
+}
+
+/// An RFC 3339 time, or a delay from now such as `30s`.
+fn resolve_when(when: &str) -> rohas_cron::Result<DateTime<Utc>> {
+    if let Ok(time) = expression::parse_time(when) {
+        return Ok(time);
+    }
+    let delay = expression::parse_interval(when).map_err(|_| {
+        rohas_cron::CronError::InvalidExpression(format!(
+            "'{}': expected an RFC 3339 time or a delay such as 30s",
+            when
+        ))
+    })?;
+    chrono::Duration::from_std(delay)
+        .ok()
+        .and_then(|delay| Utc::now().checked_add_signed(delay))
+        .ok_or_else(|| rohas_cron::CronError::InvalidExpression(format!("'{}': delay is too long", when)))
+}
+
+/// Runs scheduled calls once they are due, checking every second.
+pub fn start_runner(event_bus: Arc<EventBus>, executor: Arc<Executor>, trace_store: Arc<TraceStore>) {
+    tokio::spawn(async move {
+        let mut ticker = interval(Duration::from_secs(1));
+        loop {
+            ticker.tick().await;
+            let due = match event_bus.jobs().take_due(Utc::now()).await {
+                Ok(due) => due,
+                Err(e) => {
+                    error!("Failed to read scheduled calls: {}", e);
+                    continue;
+                }
+            };
+            for call in due {
+                tokio::spawn(run(call, event_bus.clone(), executor.clone(), trace_store.clone()));
+            }
+        }
+    });
+}
+
+async fn run(call: ScheduledCall, event_bus: Arc<EventBus>, executor: Arc<Executor>, trace_store: Arc<TraceStore>) {
+    info!("Running scheduled call {} ({})", call.handler, call.id);
+
+    let mut metadata = HashMap::new();
+    metadata.insert("scheduled_id".to_string(), call.id.clone());
+    metadata.insert("scheduled_for".to_string(), call.run_at.to_rfc3339());
+    let trace_id = trace_store
+        .start_trace(call.handler.clone(), TraceEntryType::Cron, metadata)
+        .await;
+
+    let start = std::time::Instant::now();
+    let result = executor.execute(&call.handler, call.payload.clone()).await;
+    let duration_ms = start.elapsed().as_millis() as u64;
+
+    let (success, error) = match result {
+        Ok(result) => {
+            let mut triggered_events = Vec::new();
+            if result.success {
+                for trigger in &result.triggers {
+                    let trigger_start = std::time::Instant::now();
+                    if let Err(e) = event_bus.emit_from(&trace_id, &trigger.event_name, trigger.payload.clone()).await {
+                        error!("Failed to emit event {} from {}: {}", trigger.event_name, call.handler, e);
+                    }
+                    triggered_events.push(TriggeredEventInfo {
+                        event_name: trigger.event_name.clone(),
+                        timestamp: Utc::now().to_rfc3339(),
+                        duration_ms: trigger_start.elapsed().as_millis() as u64,
+                    });
+                }
+                schedule(&event_bus.jobs(), &call.handler, &result.scheduled).await;
+            }
+
+            trace_store
+                .add_step_with_triggers(
+                    &trace_id,
+                    call.handler.clone(),
+                    duration_ms.max(result.execution_time_ms),
+                    result.success,
+                    result.error.clone(),
+                    triggered_events,
+                )
+                .await;
+            (result.success, result.error)
+        }
+        Err(e) => {
+            let err_msg = e.to_string();
+            trace_store
+                .add_step(&trace_id, call.handler.clone(), duration_ms, false, Some(err_msg.clone()))
+                .await;
+            (false, Some(err_msg))
+        }
+    };
+
+    if !success {
+        error!("Scheduled call {} ({}) failed: {:?}", call.handler, call.id, error);
+    }
+    let status = if success { TraceStatus::Success } else { TraceStatus::Failed };
+    trace_store.complete_trace(&trace_id, status, error).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_when() {
+        assert_eq!(
+            resolve_when("2025-08-01T00:00:00Z").unwrap().to_rfc3339(),
+            "2025-08-01T00:00:00+00:00"
+        );
+        assert!(resolve_when("30s").unwrap() > Utc::now());
+        assert!(resolve_when("9999999999d").is_err());
+        assert!(resolve_when("soon").is_err());
+    }
+}
@@ -0,0 +1,544 @@
+//! Serving AWS Lambda invocations with the engine, for `rohas lambda`.
+//!
+//! API Gateway requests go through the same router as the HTTP server. SQS
+//! and EventBridge deliveries run the handlers of the event they carry, and
+//! `{"handler", "payload"}` invocations run one handler as `rohas run` does.
+//! Streaming responses, such as websockets and server-sent events, are not
+//! supported.
+
+use crate::engine::Engine;
+use crate::error::{EngineError, Result};
+use adapter_codec::{WireCodec, CONTENT_TYPE};
+use adapter_memory::Message;
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::{header::SET_COOKIE, Request, Response};
+use axum::Router;
+use base64::{engine::general_purpose, Engine as _};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use tower::ServiceExt;
+use tracing::error;
+
+/// Handles the invocations of one function instance.
+pub struct LambdaHandler<'a> {
+    engine: &'a Engine,
+    router: Router,
+}
+
+impl<'a> LambdaHandler<'a> {
+    pub(crate) fn new(engine: &'a Engine, router: Router) -> Self {
+        Self { engine, router }
+    }
+
+    /// Handles one invocation event and returns the function's response. An
+    /// error fails the invocation; failed SQS records are reported in the
+    /// response instead, so only they are retried.
+    pub async fn handle(&self, event: Value) -> Result<Value> {
+        if let Some(records) = event.get("Records").and_then(Value::as_array) {
+            return Ok(self.sqs(records).await);
+        }
+        if event.get("requestContext").is_some() {
+            return self.http(&event).await;
+        }
+        if let (Some(_), Some(detail)) = (event.get("detail-type"), event.get("detail")) {
+            self.deliver(decode_detail(detail)?).await?;
+            return Ok(Value::Null);
+        }
+        if let Some(handler) = event.get("handler").and_then(Value::as_str) {
+            let payload = event.get("payload").cloned().unwrap_or_else(|| json!({}));
+            return self.direct(handler, payload).await;
+        }
+        Err(EngineError::EventDispatch(
+            "Unsupported Lambda event; expected API Gateway, SQS, EventBridge or {\"handler\", \"payload\"}".to_string(),
+        ))
+    }
+
+    async fn http(&self, event: &Value) -> Result<Value> {
+        let v2 = event.get("version").and_then(Value::as_str) == Some("2.0");
+        let request = if v2 { request_v2(event)? } else { request_v1(event)? };
+        let response = self.router.clone().oneshot(request).await.unwrap_or_else(|never| match never {});
+        http_response(response, v2).await
+    }
+
+    async fn sqs(&self, records: &[Value]) -> Value {
+        let mut failures = Vec::new();
+        for record in records {
+            let result = match decode_record(record) {
+                Ok(message) => self.deliver(message).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                let id = record.get("messageId").cloned().unwrap_or(Value::Null);
+                error!("SQS message {} failed: {}", id, e);
+                failures.push(json!({ "itemIdentifier": id }));
+            }
+        }
+        json!({ "batchItemFailures": failures })
+    }
+
+    async fn deliver(&self, message: Message) -> Result<()> {
+        let invocations = self
+            .engine
+            .invoke_event(&message.topic, message.payload, &message.metadata)
+            .await?;
+        match invocations.iter().find(|invocation| !invocation.result.success) {
+            Some(failed) => Err(EngineError::EventDispatch(format!(
+                "Handler of {} failed: {}",
+                message.topic,
+                failed.result.error.as_deref().unwrap_or("unknown error")
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    async fn direct(&self, handler: &str, payload: Value) -> Result<Value> {
+        let result = self.engine.invoke(handler, payload).await?.result;
+        if !result.success {
+            return Err(EngineError::EventDispatch(format!(
+                "{} failed: {}",
+                handler,
+                result.error.as_deref().unwrap_or("unknown error")
+            )));
+        }
+        Ok(result.data.unwrap_or(Value::Null))
+    }
+}
+
+/// The message an SQS record carries, encoded as the SQS adapter publishes it.
+fn decode_record(record: &Value) -> Result<Message> {
+    let body = record.get("body").and_then(Value::as_str).unwrap_or_default();
+    // Messages without the attribute predate codecs and are JSON.
+    let codec = record
+        .get("messageAttributes")
+        .and_then(|attributes| attributes.get(CONTENT_TYPE))
+        .and_then(|attribute| attribute.get("stringValue"))
+        .and_then(Value::as_str)
+        .and_then(WireCodec::from_content_type)
+        .unwrap_or_default();
+    codec
+        .decode_text(body)
+        .map_err(|e| EngineError::Adapter(format!("Failed to decode SQS message: {}", e)))
+}
+
+/// The message in an EventBridge event's detail, which rules may deliver as
+/// an object or as its JSON text.
+fn decode_detail(detail: &Value) -> Result<Message> {
+    let message = match detail {
+        Value::String(text) => serde_json::from_str(text),
+        other => serde_json::from_value(other.clone()),
+    };
+    message.map_err(|e| EngineError::Adapter(format!("Failed to decode EventBridge detail: {}", e)))
+}
+
+/// A REST API (payload format 1.0) proxy request.
+fn request_v1(event: &Value) -> Result<Request<Body>> {
+    let method = event.get("httpMethod").and_then(Value::as_str).unwrap_or("GET");
+    let path = event.get("path").and_then(Value::as_str).unwrap_or("/");
+    let query = pairs(multi_value(event, "multiValueQueryStringParameters", "queryStringParameters"));
+    let query = form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(query)
+        .finish();
+    let headers = pairs(multi_value(event, "multiValueHeaders", "headers"));
+    let source_ip = event.pointer("/requestContext/identity/sourceIp").and_then(Value::as_str);
+    build_request(event, method, path, &query, &headers, source_ip)
+}
+
+/// An HTTP API (payload format 2.0) request.
+fn request_v2(event: &Value) -> Result<Request<Body>> {
+    let method = event
+        .pointer("/requestContext/http/method")
+        .and_then(Value::as_str)
+        .unwrap_or("GET");
+    let raw_path = event.get("rawPath").and_then(Value::as_str).unwrap_or("/");
+    // Named stages prefix the path with the stage name.
+    let path = match event.pointer("/requestContext/stage").and_then(Value::as_str) {
+        Some(stage) if stage != "$default" => match raw_path.strip_prefix(&format!("/{}", stage)) {
+            Some("") => "/",
+            Some(rest) if rest.starts_with('/') => rest,
+            _ => raw_path,
+        },
+        _ => raw_path,
+    };
+    let query = event.get("rawQueryString").and_then(Value::as_str).unwrap_or_default();
+
+    let cookies = event
+        .get("cookies")
+        .and_then(Value::as_array)
+        .map(|cookies| cookies.iter().filter_map(Value::as_str).collect::<Vec<_>>().join("; "))
+        .unwrap_or_default();
+    let mut headers = pairs(event.get("headers"));
+    if !cookies.is_empty() {
+        headers.push(("cookie", &cookies));
+    }
+    let source_ip = event.pointer("/requestContext/http/sourceIp").and_then(Value::as_str);
+    build_request(event, method, path, query, &headers, source_ip)
+}
+
+/// The multi-value form of a 1.0 field when present, which keeps repeated
+/// names, or else the single-value one.
+fn multi_value<'a>(event: &'a Value, multi: &str, single: &str) -> Option<&'a Value> {
+    event
+        .get(multi)
+        .filter(|value| !value.is_null())
+        .or_else(|| event.get(single))
+}
+
+/// Name/value pairs of an object whose values are strings or string arrays.
+fn pairs(object: Option<&Value>) -> Vec<(&str, &str)> {
+    let mut pairs = Vec::new();
+    for (name, value) in object.and_then(Value::as_object).into_iter().flatten() {
+        match value {
+            Value::String(value) => pairs.push((name.as_str(), value.as_str())),
+            Value::Array(values) => {
+                pairs.extend(values.iter().filter_map(Value::as_str).map(|value| (name.as_str(), value)))
+            }
+            _ => {}
+        }
+    }
+    pairs
+}
+
+fn build_request(
+    event: &Value,
+    method: &str,
+    path: &str,
+    query: &str,
+    headers: &[(&str, &str)],
+    source_ip: Option<&str>,
+) -> Result<Request<Body>> {
+    let uri = if query.is_empty() {
+        path.to_string()
+    } else {
+        format!("{}?{}", path, query)
+    };
+    let body = match event.get("body").and_then(Value::as_str) {
+        Some(body) if event.get("isBase64Encoded").and_then(Value::as_bool).unwrap_or(false) => general_purpose::STANDARD
+            .decode(body)
+            .map_err(|e| EngineError::Api(format!("Invalid base64 request body: {}", e)))?,
+        Some(body) => body.as_bytes().to_vec(),
+        None => Vec::new(),
+    };
+    // Handlers that rate limit or log the client read its address.
+    let ip = source_ip
+        .and_then(|ip| ip.parse().ok())
+        .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+
+    let mut request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .extension(ConnectInfo(SocketAddr::new(ip, 0)));
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    request
+        .body(Body::from(body))
+        .map_err(|e| EngineError::Api(format!("Invalid API Gateway request: {}", e)))
+}
+
+async fn http_response(response: Response<Body>, v2: bool) -> Result<Value> {
+    let (parts, body) = response.into_parts();
+    let bytes = axum::body::to_bytes(body, usize::MAX)
+        .await
+        .map_err(|e| EngineError::Api(format!("Failed to read response body: {}", e)))?;
+    // API Gateway passes binary bodies base64-encoded.
+    let (body, is_base64) = match std::str::from_utf8(&bytes) {
+        Ok(text) => (text.to_string(), false),
+        Err(_) => (general_purpose::STANDARD.encode(&bytes), true),
+    };
+
+    let mut headers: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for (name, value) in &parts.headers {
+        if let Ok(value) = value.to_str() {
+            headers.entry(name.as_str()).or_default().push(value);
+        }
+    }
+
+    let mut output = json!({
+        "statusCode": parts.status.as_u16(),
+        "body": body,
+        "isBase64Encoded": is_base64,
+    });
+    if v2 {
+        output["cookies"] = json!(headers.remove(SET_COOKIE.as_str()).unwrap_or_default());
+        let headers: BTreeMap<_, _> = headers.into_iter().map(|(name, values)| (name, values.join(","))).collect();
+        output["headers"] = json!(headers);
+    } else {
+        output["multiValueHeaders"] = json!(headers);
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{EngineConfig, Language};
+    use axum::http::StatusCode;
+    use rohas_runtime::{Handler, HandlerContext, HandlerResult};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    /// Answers with its payload, and fails orders with id 2.
+    struct Echo(&'static str);
+
+    #[async_trait::async_trait]
+    impl Handler for Echo {
+        async fn execute(&self, context: HandlerContext) -> rohas_runtime::Result<HandlerResult> {
+            if context.payload["id"] == 2 {
+                return Ok(HandlerResult::error("order 2 is cursed", 0));
+            }
+            Ok(HandlerResult::success(
+                json!({ "payload": context.payload, "query": context.query_params }),
+                0,
+            ))
+        }
+
+        fn name(&self) -> &str {
+            self.0
+        }
+    }
+
+    async fn engine(dir: &TempDir) -> Engine {
+        let schema = rohas_parser::Parser::parse_string(
+            r#"
+            model Order {
+                id Int
+            }
+
+            api CreateOrder {
+                method: POST
+                path: "/orders"
+                body: Order
+                response: Order
+            }
+
+            event OrderPlaced {
+                payload: Order
+                handler: [charge_card]
+            }
+            "#,
+        )
+        .unwrap();
+        let config = EngineConfig {
+            project_root: dir.path().to_path_buf(),
+            language: Language::Rust,
+            ..EngineConfig::default()
+        };
+        let engine = Engine::from_schema(schema, config).await.unwrap();
+        engine.executor().register_handler(Arc::new(Echo("create_order"))).await;
+        engine.executor().register_handler(Arc::new(Echo("charge_card"))).await;
+        engine
+    }
+
+    fn rest_event(body: &str) -> Value {
+        json!({
+            "resource": "/orders",
+            "path": "/orders",
+            "httpMethod": "POST",
+            "headers": { "Content-Type": "application/json" },
+            "multiValueHeaders": { "Content-Type": ["application/json"] },
+            "queryStringParameters": { "source": "web" },
+            "multiValueQueryStringParameters": { "source": ["web"] },
+            "requestContext": { "stage": "prod", "identity": { "sourceIp": "203.0.113.9" } },
+            "body": general_purpose::STANDARD.encode(body),
+            "isBase64Encoded": true,
+        })
+    }
+
+    fn http_api_event(body: &str) -> Value {
+        json!({
+            "version": "2.0",
+            "routeKey": "POST /orders",
+            "rawPath": "/prod/orders",
+            "rawQueryString": "source=app",
+            "cookies": ["session=abc", "theme=dark"],
+            "headers": { "content-type": "application/json" },
+            "requestContext": {
+                "stage": "prod",
+                "http": { "method": "POST", "path": "/prod/orders", "sourceIp": "198.51.100.4" },
+            },
+            "body": body,
+            "isBase64Encoded": false,
+        })
+    }
+
+    fn sqs_record(id: &str, body: &str) -> Value {
+        json!({
+            "messageId": id,
+            "body": body,
+            "messageAttributes": {},
+            "eventSource": "aws:sqs",
+        })
+    }
+
+    async fn body_of(request: Request<Body>) -> Vec<u8> {
+        axum::body::to_bytes(request.into_body(), usize::MAX).await.unwrap().to_vec()
+    }
+
+    fn source_ip(request: &Request<Body>) -> IpAddr {
+        request.extensions().get::<ConnectInfo<SocketAddr>>().unwrap().0.ip()
+    }
+
+    #[tokio::test]
+    async fn test_request_v1() {
+        let mut event = rest_event(r#"{"id":1}"#);
+        event["multiValueQueryStringParameters"] = json!({ "tag": ["a", "b"] });
+        event["multiValueHeaders"]["X-Tag"] = json!(["one", "two"]);
+
+        let request = request_v1(&event).unwrap();
+        assert_eq!(request.method(), "POST");
+        assert_eq!(request.uri(), "/orders?tag=a&tag=b");
+        assert_eq!(request.headers().get_all("x-tag").iter().count(), 2);
+        assert_eq!(source_ip(&request), "203.0.113.9".parse::<IpAddr>().unwrap());
+        assert_eq!(body_of(request).await, br#"{"id":1}"#);
+
+        // Without the multi-value fields the single-value ones are used.
+        event["multiValueQueryStringParameters"] = Value::Null;
+        let request = request_v1(&event).unwrap();
+        assert_eq!(request.uri(), "/orders?source=web");
+    }
+
+    #[tokio::test]
+    async fn test_request_v2() {
+        let request = request_v2(&http_api_event(r#"{"id":1}"#)).unwrap();
+        assert_eq!(request.method(), "POST");
+        assert_eq!(request.uri(), "/orders?source=app");
+        assert_eq!(request.headers()["cookie"], "session=abc; theme=dark");
+        assert_eq!(source_ip(&request), "198.51.100.4".parse::<IpAddr>().unwrap());
+        assert_eq!(body_of(request).await, br#"{"id":1}"#);
+
+        let mut event = http_api_event("");
+        event["requestContext"]["stage"] = json!("$default");
+        event["rawPath"] = json!("/prod/orders");
+        event["rawQueryString"] = json!("");
+        assert_eq!(request_v2(&event).unwrap().uri(), "/prod/orders");
+
+        event["requestContext"]["stage"] = json!("prod");
+        event["rawPath"] = json!("/prod");
+        assert_eq!(request_v2(&event).unwrap().uri(), "/");
+    }
+
+    #[test]
+    fn test_invalid_base64_bodies_are_rejected() {
+        let mut event = rest_event("");
+        event["body"] = json!("not base64!");
+        assert!(matches!(request_v1(&event), Err(EngineError::Api(_))));
+    }
+
+    #[tokio::test]
+    async fn test_http_response() {
+        let response = || {
+            Response::builder()
+                .status(StatusCode::CREATED)
+                .header("content-type", "application/json")
+                .header("vary", "origin")
+                .header("vary", "accept")
+                .header(SET_COOKIE, "session=abc")
+                .header(SET_COOKIE, "theme=dark")
+                .body(Body::from(r#"{"id":1}"#))
+                .unwrap()
+        };
+
+        let v1 = http_response(response(), false).await.unwrap();
+        assert_eq!(v1["statusCode"], 201);
+        assert_eq!(v1["body"], r#"{"id":1}"#);
+        assert_eq!(v1["isBase64Encoded"], false);
+        assert_eq!(v1["multiValueHeaders"]["set-cookie"], json!(["session=abc", "theme=dark"]));
+        assert_eq!(v1["multiValueHeaders"]["vary"], json!(["origin", "accept"]));
+
+        let v2 = http_response(response(), true).await.unwrap();
+        assert_eq!(v2["cookies"], json!(["session=abc", "theme=dark"]));
+        assert_eq!(v2["headers"]["vary"], "origin,accept");
+        assert!(v2["headers"].get("set-cookie").is_none());
+
+        let binary = Response::new(Body::from(vec![0xff, 0x00]));
+        let binary = http_response(binary, true).await.unwrap();
+        assert_eq!(binary["isBase64Encoded"], true);
+        assert_eq!(binary["body"], general_purpose::STANDARD.encode([0xff, 0x00]));
+    }
+
+    #[test]
+    fn test_decode_record() {
+        let message = json!({ "topic": "OrderPlaced", "payload": { "id": 1 }, "timestamp": "0", "metadata": {} });
+        let decoded = decode_record(&sqs_record("m1", &message.to_string())).unwrap();
+        assert_eq!(decoded.topic, "OrderPlaced");
+        assert_eq!(decoded.payload, json!({ "id": 1 }));
+
+        let mut record = sqs_record("m2", &WireCodec::MsgPack.encode_text(&message).unwrap());
+        record["messageAttributes"][CONTENT_TYPE] = json!({ "stringValue": "application/msgpack", "dataType": "String" });
+        assert_eq!(decode_record(&record).unwrap().payload, json!({ "id": 1 }));
+
+        assert!(decode_record(&sqs_record("m3", "not json")).is_err());
+    }
+
+    #[test]
+    fn test_decode_detail() {
+        let message = json!({ "topic": "OrderPlaced", "payload": { "id": 1 }, "timestamp": "0", "metadata": {} });
+        assert_eq!(decode_detail(&message).unwrap().topic, "OrderPlaced");
+        assert_eq!(decode_detail(&json!(message.to_string())).unwrap().topic, "OrderPlaced");
+        assert!(decode_detail(&json!({ "id": 1 })).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_handle_routes_api_gateway_requests() {
+        let dir = TempDir::new().unwrap();
+        let engine = engine(&dir).await;
+        let handler = engine.lambda();
+
+        let response = handler.handle(rest_event(r#"{"id":1}"#)).await.unwrap();
+        assert_eq!(response["statusCode"], 200, "{}", response);
+        let body: Value = serde_json::from_str(response["body"].as_str().unwrap()).unwrap();
+        assert_eq!(body["payload"]["id"], 1);
+        assert_eq!(body["query"]["source"], "web");
+        assert!(response.get("multiValueHeaders").is_some());
+
+        let response = handler.handle(http_api_event(r#"{"id":1}"#)).await.unwrap();
+        assert_eq!(response["statusCode"], 200, "{}", response);
+        let body: Value = serde_json::from_str(response["body"].as_str().unwrap()).unwrap();
+        assert_eq!(body["query"]["source"], "app");
+        assert!(response.get("headers").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_handle_reports_failed_sqs_records() {
+        let dir = TempDir::new().unwrap();
+        let engine = engine(&dir).await;
+        let message = |id: i64| json!({ "topic": "OrderPlaced", "payload": { "id": id }, "timestamp": "0", "metadata": {} });
+
+        let response = engine
+            .lambda()
+            .handle(json!({
+                "Records": [
+                    sqs_record("m1", &message(1).to_string()),
+                    sqs_record("m2", &message(2).to_string()),
+                    sqs_record("m3", "not json"),
+                ]
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            response,
+            json!({ "batchItemFailures": [{ "itemIdentifier": "m2" }, { "itemIdentifier": "m3" }] })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_handle_direct_invocations() {
+        let dir = TempDir::new().unwrap();
+        let engine = engine(&dir).await;
+        let handler = engine.lambda();
+
+        let data = handler
+            .handle(json!({ "handler": "CreateOrder", "payload": { "id": 1 } }))
+            .await
+            .unwrap();
+        assert_eq!(data["payload"], json!({ "id": 1 }));
+
+        let failed = handler.handle(json!({ "handler": "CreateOrder", "payload": { "id": 2 } })).await;
+        assert!(failed.unwrap_err().to_string().contains("order 2 is cursed"));
+
+        assert!(handler.handle(json!({ "unexpected": true })).await.is_err());
+    }
+}
@@ -1,16 +1,32 @@
 pub mod adapter;
+pub mod alerting;
 pub mod api;
+pub mod asyncapi;
+pub mod chaos;
+pub mod claim_check;
 pub mod config;
 pub mod engine;
 pub mod error;
 pub mod event;
+pub mod event_metrics;
+pub mod invoke;
+pub mod jobs;
+pub mod lambda;
+pub mod log_export;
+pub mod log_level;
+pub mod problem;
+pub mod quarantine;
+pub mod recording;
 pub mod router;
+pub mod slo;
+pub mod startup;
 pub mod telemetry;
 pub mod trace;
 pub mod tracing_log;
 pub mod workbench;
 pub mod workbench_auth;
 pub mod ws;
+pub mod ws_session;
 
 pub use config::EngineConfig;
 pub use engine::Engine;
@@ -0,0 +1,325 @@
+use crate::config::{LogConfig, LogFileConfig, LogHttpConfig, LogRotation, SyslogConfig};
+use crate::tracing_log::{level_rank, TracingLogEntry};
+use chrono::Utc;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::sync::mpsc::{self, error::TrySendError, Receiver, Sender};
+
+/// Entries waiting for the sinks before new ones are dropped.
+const QUEUE_CAPACITY: usize = 10_000;
+
+/// Writes stored log entries to the sinks configured under
+/// `[telemetry.logs]`.
+///
+/// Sink failures are reported on stderr rather than through `tracing`, since
+/// those logs would be fed straight back into the exporter.
+pub struct LogExporter;
+
+impl LogExporter {
+    /// Starts the export task and returns the sender to attach to the
+    /// [`TracingLogStore`](crate::tracing_log::TracingLogStore).
+    pub fn start(config: LogConfig, project_root: &Path) -> LogExportSender {
+        let (sender, receiver) = mpsc::channel(QUEUE_CAPACITY);
+        let sender = LogExportSender {
+            sender,
+            dropped: Arc::new(AtomicU64::new(0)),
+        };
+        let file = config.file.as_ref().map(|file| FileSink::new(file.clone(), project_root));
+        tokio::spawn(run(config, file, receiver, sender.dropped.clone()));
+        sender
+    }
+}
+
+/// Queues entries for the export task without ever blocking the caller.
+#[derive(Clone)]
+pub struct LogExportSender {
+    sender: Sender<TracingLogEntry>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl LogExportSender {
+    /// Queues `entry`, dropping and counting it when the sinks are too slow
+    /// to keep up.
+    pub fn send(&self, entry: TracingLogEntry) {
+        if let Err(TrySendError::Full(_)) = self.sender.try_send(entry) {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Entries dropped so far because the export queue was full.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+async fn run(
+    config: LogConfig,
+    mut file: Option<FileSink>,
+    mut receiver: Receiver<TracingLogEntry>,
+    dropped: Arc<AtomicU64>,
+) {
+    // The level is checked when the config is loaded.
+    let min_rank = level_rank(&config.export_level).unwrap_or_default();
+    let mut reported_drops = 0;
+    let syslog = match &config.syslog {
+        Some(syslog) => SyslogSink::connect(syslog.clone()).await,
+        None => None,
+    };
+    let mut http = config.http.clone().map(HttpSink::new);
+    let flush_interval = Duration::from_secs(
+        config.http.as_ref().map(|h| h.flush_interval_seconds.max(1)).unwrap_or(60),
+    );
+    let mut ticker = tokio::time::interval(flush_interval);
+
+    loop {
+        tokio::select! {
+            entry = receiver.recv() => {
+                let Some(entry) = entry else { break };
+                if level_rank(&entry.level).is_some_and(|rank| rank < min_rank) {
+                    continue;
+                }
+                let line = match serde_json::to_string(&entry) {
+                    Ok(line) => line,
+                    Err(_) => continue,
+                };
+
+                if let Some(file) = file.as_mut() {
+                    file.write(&line);
+                }
+                if let Some(syslog) = &syslog {
+                    syslog.send(&entry).await;
+                }
+                if let Some(http) = http.as_mut() {
+                    http.push(line).await;
+                }
+            }
+            _ = ticker.tick() => {
+                if let Some(http) = http.as_mut() {
+                    http.flush().await;
+                }
+                let drops = dropped.load(Ordering::Relaxed);
+                if drops > reported_drops {
+                    eprintln!("Log export queue full, dropped {} log entries", drops - reported_drops);
+                    reported_drops = drops;
+                }
+            }
+        }
+    }
+
+    if let Some(http) = http.as_mut() {
+        http.flush().await;
+    }
+}
+
+struct FileSink {
+    config: LogFileConfig,
+    path: PathBuf,
+    file: Option<File>,
+    size: u64,
+    period: String,
+}
+
+impl FileSink {
+    fn new(config: LogFileConfig, project_root: &Path) -> Self {
+        let path = if Path::new(&config.path).is_absolute() {
+            PathBuf::from(&config.path)
+        } else {
+            project_root.join(&config.path)
+        };
+        let period = current_period(config.rotation);
+        Self {
+            config,
+            path,
+            file: None,
+            size: 0,
+            period,
+        }
+    }
+
+    fn write(&mut self, line: &str) {
+        let period = current_period(self.config.rotation);
+        let max_size = self.config.max_size_mb * 1024 * 1024;
+        let line_size = line.len() as u64 + 1;
+        if period != self.period || (max_size > 0 && self.size > 0 && self.size + line_size > max_size) {
+            self.rotate();
+            self.period = period;
+        }
+
+        if let Err(e) = self.open().and_then(|file| writeln!(file, "{}", line)) {
+            eprintln!("Failed to write log file {}: {}", self.path.display(), e);
+            self.file = None;
+            return;
+        }
+        self.size += line_size;
+    }
+
+    fn open(&mut self) -> std::io::Result<&mut File> {
+        if self.file.is_none() {
+            if let Some(parent) = self.path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+            self.size = file.metadata()?.len();
+            self.file = Some(file);
+        }
+        Ok(self.file.as_mut().unwrap())
+    }
+
+    /// Shifts `<path>.N` to `<path>.N+1`, dropping the oldest, and moves the
+    /// active file to `<path>.1`.
+    fn rotate(&mut self) {
+        self.file = None;
+        self.size = 0;
+        if !self.path.exists() {
+            return;
+        }
+
+        let rotated = |index: usize| PathBuf::from(format!("{}.{}", self.path.display(), index));
+        let _ = fs::remove_file(rotated(self.config.max_files));
+        for index in (1..self.config.max_files).rev() {
+            let _ = fs::rename(rotated(index), rotated(index + 1));
+        }
+        if let Err(e) = fs::rename(&self.path, rotated(1)) {
+            eprintln!("Failed to rotate log file {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+fn current_period(rotation: LogRotation) -> String {
+    match rotation {
+        LogRotation::Never => String::new(),
+        LogRotation::Hourly => Utc::now().format("%Y-%m-%dT%H").to_string(),
+        LogRotation::Daily => Utc::now().format("%Y-%m-%d").to_string(),
+    }
+}
+
+struct SyslogSink {
+    config: SyslogConfig,
+    socket: UdpSocket,
+}
+
+impl SyslogSink {
+    async fn connect(config: SyslogConfig) -> Option<Self> {
+        let socket = match UdpSocket::bind("0.0.0.0:0").await {
+            Ok(socket) => socket,
+            Err(e) => {
+                eprintln!("Failed to open syslog socket: {}", e);
+                return None;
+            }
+        };
+        if let Err(e) = socket.connect(&config.address).await {
+            eprintln!("Failed to resolve syslog address {}: {}", config.address, e);
+            return None;
+        }
+        Some(Self { config, socket })
+    }
+
+    /// Sends the entry as an RFC 5424 message with the `user` facility.
+    async fn send(&self, entry: &TracingLogEntry) {
+        let severity = match entry.level.as_str() {
+            "error" => 3,
+            "warn" => 4,
+            "info" => 6,
+            _ => 7,
+        };
+        let message = format!(
+            "<{}>1 {} - {} - - - {}: {}",
+            8 + severity,
+            entry.timestamp,
+            self.config.app_name,
+            entry.target,
+            entry.message
+        );
+        if let Err(e) = self.socket.send(message.as_bytes()).await {
+            eprintln!("Failed to send syslog message to {}: {}", self.config.address, e);
+        }
+    }
+}
+
+struct HttpSink {
+    config: LogHttpConfig,
+    client: reqwest::Client,
+    batch: Vec<String>,
+}
+
+impl HttpSink {
+    fn new(config: LogHttpConfig) -> Self {
+        Self {
+            batch: Vec::with_capacity(config.batch_size),
+            config,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    async fn push(&mut self, line: String) {
+        self.batch.push(line);
+        if self.batch.len() >= self.config.batch_size {
+            self.flush().await;
+        }
+    }
+
+    /// POSTs the pending lines as one NDJSON body. A failed batch is dropped.
+    async fn flush(&mut self) {
+        if self.batch.is_empty() {
+            return;
+        }
+        let mut body = std::mem::take(&mut self.batch).join("\n");
+        body.push('\n');
+
+        let mut request = self
+            .client
+            .post(&self.config.url)
+            .header("content-type", "application/x-ndjson")
+            .body(body);
+        for (name, value) in &self.config.headers {
+            request = request.header(name, value);
+        }
+
+        match request.send().await {
+            Ok(response) if !response.status().is_success() => {
+                eprintln!("Log export to {} failed: HTTP {}", self.config.url, response.status());
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("Log export to {} failed: {}", self.config.url, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_full_queue_drops_and_counts() {
+        let (sender, mut receiver) = mpsc::channel(2);
+        let sender = LogExportSender {
+            sender,
+            dropped: Arc::new(AtomicU64::new(0)),
+        };
+        let entry = TracingLogEntry {
+            timestamp: Utc::now().to_rfc3339(),
+            level: "info".to_string(),
+            target: "test".to_string(),
+            message: "message".to_string(),
+            fields: HashMap::new(),
+            span_name: None,
+            span_fields: HashMap::new(),
+            file: None,
+            line: None,
+        };
+
+        for _ in 0..5 {
+            sender.send(entry.clone());
+        }
+        assert_eq!(sender.dropped(), 3);
+        assert!(receiver.try_recv().is_ok());
+        assert!(receiver.try_recv().is_ok());
+        assert!(receiver.try_recv().is_err());
+    }
+}
@@ -0,0 +1,147 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::reload::Handle;
+use tracing_subscriber::EnvFilter;
+
+type Reloader = Box<dyn Fn(EnvFilter) -> Result<(), String> + Send + Sync>;
+
+static LOG_LEVELS: OnceLock<Mutex<LogLevels>> = OnceLock::new();
+
+/// Runtime overrides on top of the filter the process started with.
+struct LogLevels {
+    base: String,
+    targets: BTreeMap<String, String>,
+    handlers: BTreeMap<String, String>,
+    reload: Reloader,
+}
+
+impl LogLevels {
+    fn filter(&self) -> String {
+        let mut directives = vec![self.base.clone()];
+        directives.extend(self.targets.iter().map(|(target, level)| format!("{}={}", target, level)));
+        // Handler executions run inside spans carrying a `handler` field.
+        directives.extend(
+            self.handlers
+                .iter()
+                .map(|(handler, level)| format!("[{{handler={}}}]={}", handler, level)),
+        );
+        directives.retain(|d| !d.is_empty());
+        directives.join(",")
+    }
+
+    fn apply(&self) -> Result<(), String> {
+        let filter = EnvFilter::try_new(self.filter()).map_err(|e| e.to_string())?;
+        (self.reload)(filter)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogLevelsSnapshot {
+    pub base: String,
+    pub targets: BTreeMap<String, String>,
+    pub handlers: BTreeMap<String, String>,
+    pub filter: String,
+}
+
+/// A module path (`rohas_engine::event`) or a handler name to set a level for.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogScope {
+    Target(String),
+    Handler(String),
+}
+
+/// Registers the reload handle of the process-wide `EnvFilter`. `base` is the
+/// filter it was created from; runtime overrides are appended to it.
+pub fn set_log_filter_handle<S>(base: impl Into<String>, handle: Handle<EnvFilter, S>)
+where
+    S: 'static,
+{
+    let reload: Reloader = Box::new(move |filter| handle.reload(filter).map_err(|e| e.to_string()));
+    let _ = LOG_LEVELS.set(Mutex::new(LogLevels {
+        base: base.into(),
+        targets: BTreeMap::new(),
+        handlers: BTreeMap::new(),
+        reload,
+    }));
+}
+
+pub fn log_levels() -> Option<LogLevelsSnapshot> {
+    let levels = LOG_LEVELS.get()?.lock().unwrap();
+    Some(LogLevelsSnapshot {
+        base: levels.base.clone(),
+        targets: levels.targets.clone(),
+        handlers: levels.handlers.clone(),
+        filter: levels.filter(),
+    })
+}
+
+/// Sets the level for a module or handler, or removes its override when
+/// `level` is `None`.
+pub fn set_log_level(scope: LogScope, level: Option<&str>) -> Result<(), String> {
+    let mut levels = LOG_LEVELS
+        .get()
+        .ok_or_else(|| "Runtime log levels are not available in this process".to_string())?
+        .lock()
+        .unwrap();
+
+    let level = level
+        .map(|level| {
+            level
+                .parse::<LevelFilter>()
+                .map(|l| l.to_string().to_lowercase())
+                .map_err(|_| format!("Invalid log level: {} (expected trace, debug, info, warn, error or off)", level))
+        })
+        .transpose()?;
+
+    let (overrides, name) = match &scope {
+        LogScope::Target(target) => {
+            validate_name(target, "module", |c| c.is_ascii_alphanumeric() || c == '_' || c == ':')?;
+            (&mut levels.targets, target)
+        }
+        LogScope::Handler(handler) => {
+            validate_name(handler, "handler", |c| c.is_ascii_alphanumeric() || c == '_' || c == '-')?;
+            (&mut levels.handlers, handler)
+        }
+    };
+
+    let previous = match level {
+        Some(level) => overrides.insert(name.clone(), level),
+        None => overrides.remove(name),
+    };
+
+    if let Err(e) = levels.apply() {
+        let overrides = match &scope {
+            LogScope::Target(_) => &mut levels.targets,
+            LogScope::Handler(_) => &mut levels.handlers,
+        };
+        match previous {
+            Some(previous) => overrides.insert(name.clone(), previous),
+            None => overrides.remove(name),
+        };
+        return Err(e);
+    }
+
+    Ok(())
+}
+
+/// Drops every runtime override and restores the startup filter.
+pub fn reset_log_levels() -> Result<(), String> {
+    let mut levels = LOG_LEVELS
+        .get()
+        .ok_or_else(|| "Runtime log levels are not available in this process".to_string())?
+        .lock()
+        .unwrap();
+    levels.targets.clear();
+    levels.handlers.clear();
+    levels.apply()
+}
+
+fn validate_name(name: &str, kind: &str, allowed: impl Fn(char) -> bool) -> Result<(), String> {
+    if name.is_empty() || !name.chars().all(allowed) {
+        return Err(format!("Invalid {} name: {}", kind, name));
+    }
+    Ok(())
+}
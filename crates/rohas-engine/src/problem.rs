@@ -0,0 +1,282 @@
+//! Error responses in the `application/problem+json` format (RFC 7807).
+
+use crate::config::ErrorFormat;
+use axum::{
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use rohas_parser::{FieldType, Schema};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+pub const CONTENT_TYPE: &str = "application/problem+json";
+
+/// An RFC 7807 problem document.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Problem {
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    /// Per-field failures of a request that did not pass validation.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldError>,
+}
+
+/// One field of a request body that failed validation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl Problem {
+    /// A problem titled with the status' reason phrase, typed `about:blank`.
+    pub fn new(status: StatusCode) -> Self {
+        Self {
+            problem_type: "about:blank".to_string(),
+            title: status.canonical_reason().unwrap_or("Error").to_string(),
+            status: status.as_u16(),
+            detail: None,
+            instance: None,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Types the problem as `<base>/<slug>` instead of `about:blank`.
+    pub fn with_type(mut self, base: Option<&str>, slug: &str) -> Self {
+        if let Some(base) = base {
+            self.problem_type = format!("{}/{}", base.trim_end_matches('/'), slug);
+        }
+        self
+    }
+
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    pub fn with_instance(mut self, instance: impl Into<String>) -> Self {
+        self.instance = Some(instance.into());
+        self
+    }
+
+    pub fn with_errors(mut self, errors: Vec<FieldError>) -> Self {
+        self.errors = errors;
+        self
+    }
+
+    /// Renders the problem in `format`. The legacy format is the plain
+    /// `{ "error": detail }` body of earlier releases.
+    pub fn into_response_as(self, format: ErrorFormat) -> Response {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        match format {
+            ErrorFormat::Problem => {
+                (status, [(header::CONTENT_TYPE, CONTENT_TYPE)], Json(self)).into_response()
+            }
+            ErrorFormat::Legacy => {
+                let mut body = serde_json::json!({
+                    "error": self.detail.unwrap_or(self.title),
+                });
+                if !self.errors.is_empty() {
+                    body["errors"] = serde_json::to_value(&self.errors).unwrap_or_default();
+                }
+                (status, Json(body)).into_response()
+            }
+        }
+    }
+}
+
+impl IntoResponse for Problem {
+    fn into_response(self) -> Response {
+        self.into_response_as(ErrorFormat::Problem)
+    }
+}
+
+/// Checks a JSON request body against the fields of its `input`, `model` or
+/// `type`: required fields must be present and primitive fields must have the
+/// declared JSON type. Fields named in `provided` (path and query parameters)
+/// count as present. Bodies of unknown types are not checked.
+pub fn validate_body(schema: &Schema, type_name: &str, body: &Value, provided: &[&str]) -> Vec<FieldError> {
+    let fields = if let Some(input) = schema.inputs.iter().find(|i| i.name == type_name) {
+        &input.fields
+    } else if let Some(model) = schema.models.iter().find(|m| m.name == type_name) {
+        &model.fields
+    } else if let Some(type_def) = schema.types.iter().find(|t| t.name == type_name) {
+        &type_def.fields
+    } else {
+        return Vec::new();
+    };
+
+    let Some(object) = body.as_object() else {
+        return vec![FieldError {
+            field: String::new(),
+            message: format!("Expected a {} object", type_name),
+        }];
+    };
+
+    let mut errors = Vec::new();
+    for field in fields {
+        // Computed fields are filled in from the other fields, never by clients.
+        if field.computed().is_some() {
+            if object.contains_key(&field.name) {
+                errors.push(FieldError {
+                    field: field.name.clone(),
+                    message: "is computed and cannot be set".to_string(),
+                });
+            }
+            continue;
+        }
+        match object.get(&field.name) {
+            None | Some(Value::Null) if field.optional => {}
+            None if provided.contains(&field.name.as_str()) => {}
+            None | Some(Value::Null) => errors.push(FieldError {
+                field: field.name.clone(),
+                message: "is required".to_string(),
+            }),
+            Some(value) => {
+                if let Some(expected) = type_mismatch(&field.field_type, value) {
+                    errors.push(FieldError {
+                        field: field.name.clone(),
+                        message: format!("must be {}", expected),
+                    });
+                }
+            }
+        }
+    }
+    errors
+}
+
+/// What `value` should have been, when it does not fit `field_type`.
+/// Custom types are not checked.
+fn type_mismatch(field_type: &FieldType, value: &Value) -> Option<&'static str> {
+    let fits = match field_type {
+        FieldType::Int => value.is_i64() || value.is_u64(),
+        FieldType::Float => value.is_number(),
+        FieldType::String | FieldType::DateTime => value.is_string(),
+        FieldType::Boolean => value.is_boolean(),
+        FieldType::Bytes => value.is_string() || value.is_array(),
+        FieldType::Array(_) => value.is_array(),
+        FieldType::Json | FieldType::Custom(_) => true,
+    };
+    if fits {
+        return None;
+    }
+    Some(match field_type {
+        FieldType::Int => "an integer",
+        FieldType::Float => "a number",
+        FieldType::String => "a string",
+        FieldType::DateTime => "an RFC 3339 date-time string",
+        FieldType::Boolean => "a boolean",
+        FieldType::Bytes => "a base64 string or byte array",
+        _ => "an array",
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> Schema {
+        rohas_parser::Parser::parse_string(
+            "input CreateUserInput {\n  name: String\n  age: Int\n  email: String?\n  tags: String[]\n}\n",
+        )
+        .unwrap()
+    }
+
+    fn fields(errors: &[FieldError]) -> Vec<(&str, &str)> {
+        errors.iter().map(|e| (e.field.as_str(), e.message.as_str())).collect()
+    }
+
+    #[test]
+    fn test_validate_body_reports_missing_and_mistyped_fields() {
+        let schema = schema();
+
+        let valid = json!({ "name": "Ada", "age": 36, "tags": [] });
+        assert!(validate_body(&schema, "CreateUserInput", &valid, &[]).is_empty());
+
+        let invalid = json!({ "name": 1, "age": 1.5, "email": null });
+        assert_eq!(
+            fields(&validate_body(&schema, "CreateUserInput", &invalid, &[])),
+            vec![("name", "must be a string"), ("age", "must be an integer"), ("tags", "is required")]
+        );
+    }
+
+    #[test]
+    fn test_validate_body_counts_provided_fields() {
+        let schema = schema();
+        let body = json!({ "name": "Ada", "tags": [] });
+
+        assert_eq!(
+            fields(&validate_body(&schema, "CreateUserInput", &body, &[])),
+            vec![("age", "is required")]
+        );
+        assert!(validate_body(&schema, "CreateUserInput", &body, &["age"]).is_empty());
+    }
+
+    #[test]
+    fn test_validate_body_rejects_non_objects_and_skips_unknown_types() {
+        let schema = schema();
+
+        assert_eq!(
+            fields(&validate_body(&schema, "CreateUserInput", &json!([1]), &[])),
+            vec![("", "Expected a CreateUserInput object")]
+        );
+        assert!(validate_body(&schema, "Unknown", &json!([1]), &[]).is_empty());
+    }
+
+    #[test]
+    fn test_type_mismatch() {
+        assert_eq!(type_mismatch(&FieldType::Int, &json!(1)), None);
+        assert_eq!(type_mismatch(&FieldType::Int, &json!(1.5)), Some("an integer"));
+        assert_eq!(type_mismatch(&FieldType::Float, &json!(1)), None);
+        assert_eq!(type_mismatch(&FieldType::Boolean, &json!("true")), Some("a boolean"));
+        assert_eq!(type_mismatch(&FieldType::DateTime, &json!(0)), Some("an RFC 3339 date-time string"));
+        assert_eq!(type_mismatch(&FieldType::Bytes, &json!([1, 2])), None);
+        assert_eq!(
+            type_mismatch(&FieldType::Array(Box::new(FieldType::Int)), &json!({})),
+            Some("an array")
+        );
+        assert_eq!(type_mismatch(&FieldType::Custom("User".to_string()), &json!(1)), None);
+    }
+
+    #[tokio::test]
+    async fn test_legacy_format() {
+        let problem = Problem::new(StatusCode::UNPROCESSABLE_ENTITY)
+            .with_detail("Request body failed validation")
+            .with_errors(vec![FieldError {
+                field: "name".to_string(),
+                message: "is required".to_string(),
+            }]);
+
+        let response = problem.clone().into_response_as(ErrorFormat::Legacy);
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(
+            serde_json::from_slice::<Value>(&body).unwrap(),
+            json!({
+                "error": "Request body failed validation",
+                "errors": [{ "field": "name", "message": "is required" }],
+            })
+        );
+
+        let response = Problem::new(StatusCode::NOT_FOUND).into_response_as(ErrorFormat::Legacy);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(serde_json::from_slice::<Value>(&body).unwrap(), json!({ "error": "Not Found" }));
+
+        let response = problem.into_response_as(ErrorFormat::Problem);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], CONTENT_TYPE);
+    }
+}
@@ -0,0 +1,171 @@
+use crate::error::{EngineError, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tokio::sync::Mutex;
+use tracing::warn;
+use uuid::Uuid;
+
+/// Why a message was quarantined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PoisonKind {
+    /// The adapter could not decode the message body.
+    Decode,
+    /// One or more handlers failed on every attempt.
+    Handler,
+}
+
+/// A message whose handlers failed on every attempt, or that could not be
+/// decoded, set aside for inspection.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuarantinedMessage {
+    pub id: String,
+    pub event: String,
+    pub kind: PoisonKind,
+    /// The payload, or for [`PoisonKind::Decode`] the raw body as a string.
+    pub payload: Value,
+    pub error: String,
+    pub attempts: u32,
+    /// Handlers that failed, for [`PoisonKind::Handler`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub handlers: Vec<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub metadata: HashMap<String, String>,
+    pub quarantined_at: DateTime<Utc>,
+    /// Set once the payload has been edited in the workbench.
+    #[serde(default)]
+    pub edited: bool,
+}
+
+impl QuarantinedMessage {
+    pub fn new(event: impl Into<String>, kind: PoisonKind, payload: Value, error: impl Into<String>, attempts: u32) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            event: event.into(),
+            kind,
+            payload,
+            error: error.into(),
+            attempts,
+            handlers: Vec::new(),
+            metadata: HashMap::new(),
+            quarantined_at: Utc::now(),
+            edited: false,
+        }
+    }
+
+    pub fn with_handlers(mut self, handlers: Vec<String>) -> Self {
+        self.handlers = handlers;
+        self
+    }
+
+    pub fn with_metadata(mut self, metadata: HashMap<String, String>) -> Self {
+        self.metadata = metadata;
+        self
+    }
+}
+
+/// Poison messages awaiting inspection, kept in a JSON file so they survive
+/// restarts. Once full, the oldest message is dropped for each new one.
+pub struct QuarantineStore {
+    path: Option<PathBuf>,
+    max_entries: usize,
+    messages: Mutex<Vec<QuarantinedMessage>>,
+}
+
+impl QuarantineStore {
+    /// A store that is lost when the process exits.
+    pub fn in_memory(max_entries: usize) -> Self {
+        Self {
+            path: None,
+            max_entries,
+            messages: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Opens the store at `path`, loading the messages quarantined there.
+    pub async fn open(path: impl Into<PathBuf>, max_entries: usize) -> Result<Self> {
+        let path = path.into();
+        let messages = match tokio::fs::read(&path).await {
+            Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| {
+                EngineError::Initialization(format!("Invalid quarantine store {}: {}", path.display(), e))
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(Self {
+            path: Some(path),
+            max_entries,
+            messages: Mutex::new(messages),
+        })
+    }
+
+    pub async fn add(&self, message: QuarantinedMessage) -> Result<()> {
+        warn!(
+            "Quarantined a message of {} after {} attempt(s): {}",
+            message.event, message.attempts, message.error
+        );
+        let mut messages = self.messages.lock().await;
+        messages.push(message);
+        if messages.len() > self.max_entries {
+            let dropped = messages.len() - self.max_entries;
+            messages.drain(..dropped);
+            warn!("Quarantine is full, dropped the {} oldest message(s)", dropped);
+        }
+        self.persist(&messages).await
+    }
+
+    /// Quarantined messages, newest first.
+    pub async fn list(&self) -> Vec<QuarantinedMessage> {
+        self.messages.lock().await.iter().rev().cloned().collect()
+    }
+
+    pub async fn get(&self, id: &str) -> Option<QuarantinedMessage> {
+        self.messages.lock().await.iter().find(|m| m.id == id).cloned()
+    }
+
+    /// Replaces a message's payload, returning the updated message.
+    pub async fn update_payload(&self, id: &str, payload: Value) -> Result<Option<QuarantinedMessage>> {
+        let mut messages = self.messages.lock().await;
+        let Some(message) = messages.iter_mut().find(|m| m.id == id) else {
+            return Ok(None);
+        };
+        message.payload = payload;
+        message.edited = true;
+        let updated = message.clone();
+        self.persist(&messages).await?;
+        Ok(Some(updated))
+    }
+
+    pub async fn remove(&self, id: &str) -> Result<Option<QuarantinedMessage>> {
+        let mut messages = self.messages.lock().await;
+        let Some(index) = messages.iter().position(|m| m.id == id) else {
+            return Ok(None);
+        };
+        let removed = messages.remove(index);
+        self.persist(&messages).await?;
+        Ok(Some(removed))
+    }
+
+    async fn persist(&self, messages: &[QuarantinedMessage]) -> Result<()> {
+        match &self.path {
+            Some(path) => write_json(path, &messages).await,
+            None => Ok(()),
+        }
+    }
+}
+
+async fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let body = serde_json::to_vec_pretty(value).map_err(|e| EngineError::Io(e.into()))?;
+    // Write a sibling file first so a crash never leaves a partial store.
+    let tmp = path.with_extension("json.tmp");
+    tokio::fs::write(&tmp, body).await?;
+    tokio::fs::rename(&tmp, path).await?;
+    Ok(())
+}
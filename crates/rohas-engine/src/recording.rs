@@ -0,0 +1,673 @@
+//! Recording the handler runs of a trace and replaying them, for
+//! `rohas replay`.
+//!
+//! A recording holds each handler's full context, its result, and the calls
+//! it made through the engine's KV store and HTTP client. Replaying runs the
+//! handlers again with the recorded contexts and compares the results. The
+//! KV and HTTP calls are answered from the recording, and triggered events
+//! and scheduled calls are not published, so a replay has no effect on the
+//! running system. The workbench also reads
+//! recordings to show how a payload changes along a chain of traces.
+
+use crate::config::RecordingConfig;
+use crate::error::{EngineError, Result};
+use crate::trace::{StepPayloads, TraceRecord};
+use chrono::{DateTime, Utc};
+use rohas_runtime::{CallTape, Executor, HandlerContext, HandlerResult, OutboundCall};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use tokio::sync::Mutex;
+use tracing::{debug, warn};
+
+/// One handler run of a recorded trace.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedStep {
+    pub context: HandlerContext,
+    /// What the handler returned; `None` when the runtime failed to run it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<HandlerResult>,
+    /// Why the runtime failed to run the handler.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The KV and HTTP calls of the run, in the order they were made.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub calls: Vec<OutboundCall>,
+}
+
+impl RecordedStep {
+    fn new(context: HandlerContext, calls: Vec<OutboundCall>, result: &rohas_runtime::Result<HandlerResult>) -> Self {
+        match result {
+            Ok(result) => Self {
+                context,
+                result: Some(result.clone()),
+                error: None,
+                calls,
+            },
+            Err(e) => Self {
+                context,
+                result: None,
+                error: Some(e.to_string()),
+                calls,
+            },
+        }
+    }
+
+    /// The parts of a run that a deterministic handler reproduces.
+    fn outcome(&self) -> Value {
+        match &self.result {
+            Some(result) => serde_json::json!({
+                "success": result.success,
+                "data": result.data,
+                "error": result.error,
+                "triggers": result.triggers,
+                "scheduled": result.scheduled,
+            }),
+            None => serde_json::json!({ "runtime_error": self.error }),
+        }
+    }
+}
+
+/// The handler runs of one trace, in the order they ran.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recording {
+    pub trace_id: String,
+    pub entry_point: String,
+    pub recorded_at: DateTime<Utc>,
+    pub steps: Vec<RecordedStep>,
+}
+
+/// A recorded handler run next to its replay.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayedStep {
+    pub handler: String,
+    pub recorded: Value,
+    pub replayed: Value,
+    /// KV and HTTP calls of the replay that the recording has no answer
+    /// to, such as a request to a URL the recorded run did not call.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unanswered_calls: Vec<String>,
+}
+
+impl ReplayedStep {
+    pub fn matches(&self) -> bool {
+        self.recorded == self.replayed && self.unanswered_calls.is_empty()
+    }
+}
+
+/// A handler's context and the tape its KV and HTTP calls go to, taken
+/// before a run that is recorded.
+pub struct Snapshot {
+    context: HandlerContext,
+    calls: CallTape,
+}
+
+impl Snapshot {
+    pub fn new(context: &HandlerContext) -> Self {
+        Self {
+            context: context.clone(),
+            calls: CallTape::recording(),
+        }
+    }
+}
+
+/// Runs `run`, a handler run, with its KV and HTTP calls recorded on the
+/// snapshot when there is one.
+pub async fn with_calls<F: Future>(snapshot: &Option<Snapshot>, run: F) -> F::Output {
+    match snapshot {
+        Some(snapshot) => snapshot.calls.clone().scope(run).await,
+        None => run.await,
+    }
+}
+
+/// Collects the handler runs of active traces and writes one JSON file per
+/// completed trace. Once full, the oldest recordings are deleted.
+pub struct Recorder {
+    config: RecordingConfig,
+    dir: PathBuf,
+    pending: Mutex<HashMap<String, Vec<RecordedStep>>>,
+}
+
+impl Recorder {
+    pub fn new(config: RecordingConfig, project_root: &Path) -> Self {
+        Self {
+            dir: Self::dir(&config, project_root),
+            config,
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Directory the recordings of a project are written to.
+    pub fn dir(config: &RecordingConfig, project_root: &Path) -> PathBuf {
+        project_root.join(&config.path)
+    }
+
+    pub async fn record(&self, trace_id: &str, snapshot: Snapshot, result: &rohas_runtime::Result<HandlerResult>) {
+        let step = RecordedStep::new(snapshot.context, snapshot.calls.calls(), result);
+        self.pending
+            .lock()
+            .await
+            .entry(trace_id.to_string())
+            .or_default()
+            .push(step);
+    }
+
+    /// Writes the recording of a completed trace, if it ran any handlers.
+    pub async fn finish(&self, trace_id: &str, entry_point: &str, failed: bool) {
+        let Some(steps) = self.pending.lock().await.remove(trace_id) else {
+            return;
+        };
+        if self.config.failed_only && !failed {
+            return;
+        }
+
+        let recording = Recording {
+            trace_id: trace_id.to_string(),
+            entry_point: entry_point.to_string(),
+            recorded_at: Utc::now(),
+            steps,
+        };
+        if let Err(e) = self.write(&recording).await {
+            warn!("Failed to write the recording of trace {}: {}", trace_id, e);
+        }
+    }
+
+    async fn write(&self, recording: &Recording) -> Result<()> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let body = serde_json::to_vec_pretty(recording).map_err(|e| EngineError::Io(e.into()))?;
+        tokio::fs::write(self.dir.join(format!("{}.json", recording.trace_id)), body).await?;
+        debug!("Recorded trace {}", recording.trace_id);
+        self.prune().await
+    }
+
+    async fn prune(&self) -> Result<()> {
+        let mut files = Vec::new();
+        let mut entries = tokio::fs::read_dir(&self.dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                files.push((entry.metadata().await?.modified()?, path));
+            }
+        }
+        if files.len() <= self.config.max_recordings {
+            return Ok(());
+        }
+        files.sort();
+        for (_, path) in &files[..files.len() - self.config.max_recordings] {
+            tokio::fs::remove_file(path).await?;
+        }
+        Ok(())
+    }
+
+    /// Reads the recording of `trace_id` from `dir`.
+    pub async fn load(dir: &Path, trace_id: &str) -> Result<Recording> {
+        let path = dir.join(format!("{}.json", trace_id));
+        let bytes = match tokio::fs::read(&path).await {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(EngineError::Config(format!(
+                    "No recording of trace {} in {}; is [recording] enabled?",
+                    trace_id,
+                    dir.display()
+                )))
+            }
+            Err(e) => return Err(e.into()),
+        };
+        serde_json::from_slice(&bytes)
+            .map_err(|e| EngineError::Config(format!("Invalid recording {}: {}", path.display(), e)))
+    }
+}
+
+/// Runs the handlers of `recording` again with their recorded contexts,
+/// answering their KV and HTTP calls from the recorded ones.
+pub async fn replay(executor: &Executor, recording: &Recording) -> Vec<ReplayedStep> {
+    let mut replayed = Vec::with_capacity(recording.steps.len());
+    for step in &recording.steps {
+        let calls = CallTape::replaying(step.calls.clone());
+        let result = calls.clone().scope(executor.execute_with_context(step.context.clone())).await;
+        replayed.push(ReplayedStep {
+            handler: step.context.handler_name.clone(),
+            recorded: step.outcome(),
+            replayed: RecordedStep::new(step.context.clone(), Vec::new(), &result).outcome(),
+            unanswered_calls: calls.unanswered(),
+        });
+    }
+    replayed
+}
+
+/// A value that differs between two payloads.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PayloadChange {
+    /// JSON pointer to the value, e.g. `/user/email`.
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<Value>,
+}
+
+/// The values added, removed or changed from `before` to `after`. Objects
+/// are compared key by key; arrays and other values as a whole.
+pub fn diff_payloads(before: &Value, after: &Value) -> Vec<PayloadChange> {
+    let mut changes = Vec::new();
+    diff_into(&mut changes, String::new(), before, after);
+    changes
+}
+
+fn diff_into(changes: &mut Vec<PayloadChange>, path: String, before: &Value, after: &Value) {
+    match (before, after) {
+        (Value::Object(before), Value::Object(after)) => {
+            for (key, old) in before {
+                let path = format!("{}/{}", path, key.replace('~', "~0").replace('/', "~1"));
+                match after.get(key) {
+                    Some(new) => diff_into(changes, path, old, new),
+                    None => changes.push(PayloadChange {
+                        path,
+                        before: Some(old.clone()),
+                        after: None,
+                    }),
+                }
+            }
+            for (key, new) in after.iter().filter(|(key, _)| !before.contains_key(*key)) {
+                changes.push(PayloadChange {
+                    path: format!("{}/{}", path, key.replace('~', "~0").replace('/', "~1")),
+                    before: None,
+                    after: Some(new.clone()),
+                });
+            }
+        }
+        (before, after) if before != after => changes.push(PayloadChange {
+            path,
+            before: Some(before.clone()),
+            after: Some(after.clone()),
+        }),
+        _ => {}
+    }
+}
+
+/// One handler run in the timeline of a chain of traces.
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelineStep {
+    pub trace_id: String,
+    pub entry_point: String,
+    pub handler: String,
+    pub timestamp: String,
+    pub success: bool,
+    /// The handler's payload, when its trace was recorded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub triggered_events: Vec<String>,
+    /// How `input` differs from the input of the previous recorded step.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<PayloadChange>,
+}
+
+/// The handler runs of `traces`, in order, with the payloads of those
+/// recorded in `dir`. Traces that were not recorded only give the handler
+/// names and outcomes.
+pub async fn timeline(dir: &Path, traces: &[TraceRecord]) -> Vec<TimelineStep> {
+    let mut steps = Vec::new();
+    for trace in traces {
+        let mut trace_steps: Vec<_> = trace.steps.iter().collect();
+        let Ok(recording) = Recorder::load(dir, &trace.id).await else {
+            steps.extend(trace_steps.into_iter().map(|step| TimelineStep {
+                trace_id: trace.id.clone(),
+                entry_point: trace.entry_point.clone(),
+                handler: step.handler_name.clone(),
+                timestamp: step.timestamp.clone(),
+                success: step.success,
+                input: None,
+                output: None,
+                error: step.error.clone(),
+                triggered_events: step.triggered_events.iter().map(|e| e.event_name.clone()).collect(),
+                changes: Vec::new(),
+            }));
+            continue;
+        };
+
+        for recorded in recording.steps {
+            let handler = recorded.context.handler_name;
+            // The trace step of the run has the events the schema triggered too.
+            let triggered_events = trace_steps
+                .iter()
+                .position(|step| step.handler_name == handler)
+                .map(|index| trace_steps.remove(index).triggered_events.iter().map(|e| e.event_name.clone()).collect())
+                .unwrap_or_default();
+            steps.push(TimelineStep {
+                trace_id: trace.id.clone(),
+                entry_point: trace.entry_point.clone(),
+                handler,
+                timestamp: recorded.context.timestamp,
+                success: recorded.result.as_ref().is_some_and(|result| result.success),
+                input: Some(recorded.context.payload),
+                output: recorded.result.as_ref().and_then(|result| result.data.clone()),
+                error: recorded.error.or_else(|| recorded.result.and_then(|result| result.error)),
+                triggered_events,
+                changes: Vec::new(),
+            });
+        }
+    }
+
+    let mut previous: Option<Value> = None;
+    for step in &mut steps {
+        if let Some(input) = &step.input {
+            if let Some(previous) = &previous {
+                step.changes = diff_payloads(previous, input);
+            }
+            previous = Some(input.clone());
+        }
+    }
+    steps
+}
+
+/// The payloads the handlers of `traces` ran with, from the traces that were
+/// recorded in `dir`.
+pub async fn step_payloads(dir: &Path, traces: &[TraceRecord]) -> StepPayloads {
+    let mut payloads = StepPayloads::new();
+    for trace in traces {
+        let Ok(recording) = Recorder::load(dir, &trace.id).await else {
+            continue;
+        };
+        for step in recording.steps {
+            payloads.entry(step.context.handler_name).or_default().push(step.context.payload);
+        }
+    }
+    payloads
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trace::{TraceEntryType, TraceStatus, TraceStep};
+    use rohas_runtime::{Handler, RuntimeConfig};
+    use serde_json::json;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    /// Doubles `n`.
+    struct Double;
+
+    #[async_trait::async_trait]
+    impl Handler for Double {
+        async fn execute(&self, context: HandlerContext) -> rohas_runtime::Result<HandlerResult> {
+            Ok(HandlerResult::success(json!(context.payload["n"].as_i64().unwrap() * 2), 0))
+        }
+
+        fn name(&self) -> &str {
+            "double"
+        }
+    }
+
+    /// Prices an order from the KV store, remembers it and charges it over
+    /// HTTP, at an address where nothing listens.
+    struct Checkout;
+
+    #[async_trait::async_trait]
+    impl Handler for Checkout {
+        async fn execute(&self, context: HandlerContext) -> rohas_runtime::Result<HandlerResult> {
+            let kv = rohas_runtime::KvStore::global();
+            let price = kv.get("recording:price");
+            kv.set("recording:last_order", context.payload.clone(), None);
+            let charge = rohas_runtime::ClientRequest::new("POST", "http://127.0.0.1:1/charge");
+            let charged = rohas_runtime::http_client::send(charge).await.map(|response| response.status);
+            Ok(HandlerResult::success(json!({ "price": price, "charged": charged.ok() }), 0))
+        }
+
+        fn name(&self) -> &str {
+            "checkout"
+        }
+    }
+
+    async fn executor(dir: &TempDir) -> Executor {
+        let executor = Executor::new(RuntimeConfig {
+            language: rohas_runtime::Language::Rust,
+            project_root: dir.path().to_path_buf(),
+            ..RuntimeConfig::default()
+        });
+        executor.register_handler(Arc::new(Double)).await;
+        executor.register_handler(Arc::new(Checkout)).await;
+        executor
+    }
+
+    fn recorder(dir: &TempDir, failed_only: bool, max_recordings: usize) -> Recorder {
+        let config = RecordingConfig {
+            enabled: true,
+            failed_only,
+            max_recordings,
+            ..RecordingConfig::default()
+        };
+        Recorder::new(config, dir.path())
+    }
+
+    fn recordings(dir: &TempDir) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(dir.path().join(".rohas/recordings"))
+            .map(|entries| entries.map(|e| e.unwrap().file_name().to_string_lossy().into_owned()).collect())
+            .unwrap_or_default();
+        names.sort();
+        names
+    }
+
+    fn step(handler: &str, n: i64, result: rohas_runtime::Result<HandlerResult>) -> RecordedStep {
+        RecordedStep::new(HandlerContext::new(handler, json!({ "n": n })), Vec::new(), &result)
+    }
+
+    #[tokio::test]
+    async fn test_recorder_writes_completed_traces() {
+        let dir = TempDir::new().unwrap();
+        let recorder = recorder(&dir, false, 10);
+
+        recorder
+            .record("t1", Snapshot::new(&HandlerContext::new("double", json!({ "n": 2 }))), &Ok(HandlerResult::success(json!(4), 3)))
+            .await;
+        recorder
+            .record("t1", Snapshot::new(&HandlerContext::new("notify", json!({}))), &Err(rohas_runtime::RuntimeError::Timeout(30)))
+            .await;
+        recorder.finish("t1", "Double", false).await;
+        recorder.finish("untraced", "Double", false).await;
+
+        assert_eq!(recordings(&dir), ["t1.json"]);
+        let recording = Recorder::load(&Recorder::dir(&recorder.config, dir.path()), "t1").await.unwrap();
+        assert_eq!(recording.entry_point, "Double");
+        assert_eq!(recording.steps.len(), 2);
+        assert_eq!(recording.steps[0].result.as_ref().unwrap().data, Some(json!(4)));
+        assert!(recording.steps[1].result.is_none());
+        assert!(recording.steps[1].error.as_deref().unwrap().contains("30"));
+    }
+
+    #[tokio::test]
+    async fn test_failed_only_skips_successful_traces() {
+        let dir = TempDir::new().unwrap();
+        let recorder = recorder(&dir, true, 10);
+        for trace_id in ["ok", "failed"] {
+            recorder
+                .record(trace_id, Snapshot::new(&HandlerContext::new("double", json!({ "n": 1 }))), &Ok(HandlerResult::success(json!(2), 0)))
+                .await;
+        }
+        recorder.finish("ok", "Double", false).await;
+        recorder.finish("failed", "Double", true).await;
+
+        assert_eq!(recordings(&dir), ["failed.json"]);
+        // The steps of the skipped trace are not kept around either.
+        assert!(recorder.pending.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_oldest_recordings_are_pruned() {
+        let dir = TempDir::new().unwrap();
+        let recorder = recorder(&dir, false, 2);
+        for trace_id in ["a", "b", "c"] {
+            recorder
+                .record(trace_id, Snapshot::new(&HandlerContext::new("double", json!({ "n": 1 }))), &Ok(HandlerResult::success(json!(2), 0)))
+                .await;
+            recorder.finish(trace_id, "Double", false).await;
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+
+        assert_eq!(recordings(&dir), ["b.json", "c.json"]);
+    }
+
+    #[tokio::test]
+    async fn test_load_of_an_unrecorded_trace() {
+        let dir = TempDir::new().unwrap();
+        let error = Recorder::load(dir.path(), "missing").await.unwrap_err().to_string();
+        assert!(error.contains("No recording of trace missing"), "{}", error);
+
+        std::fs::write(dir.path().join("broken.json"), "{").unwrap();
+        let error = Recorder::load(dir.path(), "broken").await.unwrap_err().to_string();
+        assert!(error.contains("Invalid recording"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_replay_compares_outcomes() {
+        let dir = TempDir::new().unwrap();
+        let executor = executor(&dir).await;
+
+        let recording = Recording {
+            trace_id: "t1".to_string(),
+            entry_point: "Double".to_string(),
+            recorded_at: Utc::now(),
+            steps: vec![
+                // The run time is not part of the comparison.
+                step("double", 2, Ok(HandlerResult::success(json!(4), 250))),
+                step("double", 3, Ok(HandlerResult::success(json!(7), 0))),
+                step("removed_handler", 1, Ok(HandlerResult::success(json!(1), 0))),
+            ],
+        };
+
+        let replayed = replay(&executor, &recording).await;
+        assert_eq!(replayed.len(), 3);
+        assert!(replayed[0].matches());
+        assert!(!replayed[1].matches());
+        assert_eq!(replayed[1].replayed["data"], json!(6));
+        assert_eq!(replayed[2].handler, "removed_handler");
+        assert!(!replayed[2].matches());
+        assert!(replayed[2].replayed["runtime_error"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_replay_answers_kv_and_http_calls_from_the_recording() {
+        let dir = TempDir::new().unwrap();
+        let executor = executor(&dir).await;
+        let recorder = recorder(&dir, false, 10);
+        let kv = rohas_runtime::KvStore::global();
+        kv.set("recording:price", json!(5), None);
+
+        let context = HandlerContext::new("checkout", json!({ "id": 1 }));
+        let snapshot = Some(Snapshot::new(&context));
+        let result = with_calls(&snapshot, executor.execute_with_context(context)).await;
+        if let Some(snapshot) = snapshot {
+            recorder.record("t1", snapshot, &result).await;
+        }
+        recorder.finish("t1", "Checkout", false).await;
+
+        let mut recording = Recorder::load(&Recorder::dir(&recorder.config, dir.path()), "t1").await.unwrap();
+        let calls: Vec<String> = recording.steps[0].calls.iter().map(OutboundCall::describe).collect();
+        assert_eq!(calls, ["kv get recording:price", "kv set recording:last_order", "http POST http://127.0.0.1:1/charge"]);
+        assert!(matches!(&recording.steps[0].calls[2], OutboundCall::Http { error: Some(_), .. }));
+
+        // The replay sees the recorded price and leaves the live store alone.
+        kv.set("recording:price", json!(7), None);
+        kv.delete("recording:last_order");
+        let replayed = replay(&executor, &recording).await;
+        assert!(replayed[0].matches(), "{:?}", replayed[0]);
+        assert_eq!(replayed[0].replayed["data"]["price"], json!(5));
+        assert_eq!(kv.get("recording:price"), Some(json!(7)));
+        assert_eq!(kv.get("recording:last_order"), None);
+
+        // A request the recording has no answer to is not sent.
+        recording.steps[0].calls.pop();
+        let replayed = replay(&executor, &recording).await;
+        assert!(!replayed[0].matches());
+        assert_eq!(replayed[0].unanswered_calls, ["http POST http://127.0.0.1:1/charge"]);
+    }
+
+    #[test]
+    fn test_diff_payloads() {
+        let before = json!({ "user": { "email": "a@x", "name": "Ann" }, "tags": [1, 2], "a/b": 1, "gone": true });
+        let after = json!({ "user": { "email": "b@x", "name": "Ann" }, "tags": [1, 2, 3], "a/b": 2, "new": null });
+
+        assert_eq!(
+            diff_payloads(&before, &after),
+            vec![
+                PayloadChange { path: "/a~1b".to_string(), before: Some(json!(1)), after: Some(json!(2)) },
+                PayloadChange { path: "/gone".to_string(), before: Some(json!(true)), after: None },
+                PayloadChange { path: "/tags".to_string(), before: Some(json!([1, 2])), after: Some(json!([1, 2, 3])) },
+                PayloadChange { path: "/user/email".to_string(), before: Some(json!("a@x")), after: Some(json!("b@x")) },
+                PayloadChange { path: "/new".to_string(), before: None, after: Some(json!(null)) },
+            ]
+        );
+        assert_eq!(diff_payloads(&before, &before), vec![]);
+        assert_eq!(diff_payloads(&json!(1), &json!("1")).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_timeline_diffs_recorded_inputs() {
+        let dir = TempDir::new().unwrap();
+        let recording = Recording {
+            trace_id: "recorded".to_string(),
+            entry_point: "Double".to_string(),
+            recorded_at: Utc::now(),
+            steps: vec![
+                step("double", 2, Ok(HandlerResult::success(json!(4), 0))),
+                step("double", 5, Ok(HandlerResult::error("too big", 0))),
+            ],
+        };
+        std::fs::write(dir.path().join("recorded.json"), serde_json::to_vec(&recording).unwrap()).unwrap();
+
+        let trace = |id: &str| TraceRecord {
+            id: id.to_string(),
+            entry_point: "Double".to_string(),
+            entry_type: TraceEntryType::Event,
+            status: TraceStatus::Success,
+            duration_ms: 1,
+            started_at: Utc::now().to_rfc3339(),
+            completed_at: None,
+            steps: vec![TraceStep {
+                name: "double".to_string(),
+                handler_name: "double".to_string(),
+                duration_ms: 1,
+                success: true,
+                error: None,
+                timestamp: Utc::now().to_rfc3339(),
+                triggered_events: vec![crate::trace::TriggeredEventInfo {
+                    event_name: "Doubled".to_string(),
+                    timestamp: Utc::now().to_rfc3339(),
+                    duration_ms: 0,
+                }],
+            }],
+            error: None,
+            metadata: HashMap::new(),
+        };
+
+        let steps = timeline(dir.path(), &[trace("unrecorded"), trace("recorded")]).await;
+        assert_eq!(steps.len(), 3);
+
+        assert_eq!(steps[0].trace_id, "unrecorded");
+        assert_eq!(steps[0].input, None);
+        assert_eq!(steps[0].triggered_events, ["Doubled"]);
+
+        assert_eq!(steps[1].input, Some(json!({ "n": 2 })));
+        assert_eq!(steps[1].output, Some(json!(4)));
+        assert_eq!(steps[1].triggered_events, ["Doubled"]);
+        assert!(steps[1].changes.is_empty());
+
+        assert!(!steps[2].success);
+        assert_eq!(steps[2].error.as_deref(), Some("too big"));
+        assert!(steps[2].triggered_events.is_empty());
+        assert_eq!(
+            steps[2].changes,
+            vec![PayloadChange { path: "/n".to_string(), before: Some(json!(2)), after: Some(json!(5)) }]
+        );
+
+        let payloads = step_payloads(dir.path(), &[trace("unrecorded"), trace("recorded")]).await;
+        assert_eq!(payloads.len(), 1);
+        assert_eq!(payloads["double"], vec![json!({ "n": 2 }), json!({ "n": 5 })]);
+    }
+}
@@ -0,0 +1,266 @@
+use crate::trace::{TraceEntryType, TraceRecord, TraceStatus};
+use chrono::{DateTime, Utc};
+use rohas_parser::{Schema, Slo};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SloState {
+    Ok,
+    Breached,
+    NoData,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SloStatus {
+    pub api: String,
+    pub target: Slo,
+    pub window_seconds: u64,
+    pub total_requests: usize,
+    pub failed_requests: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub availability: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub availability_burn_rate: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_p99_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_p99_burn_rate: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_p95_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_p95_burn_rate: Option<f64>,
+    pub state: SloState,
+}
+
+/// Computes SLO status for every API that declares an `slo` block, using API
+/// traces started within the last `window_seconds`.
+///
+/// Burn rates compare the observed error budget consumption with the budget
+/// allowed by the target: 1.0 means the budget is being spent exactly as fast
+/// as allowed, anything above means the SLO is breached for the window.
+pub fn compute_slo_status(schema: &Schema, traces: &[TraceRecord], window_seconds: u64) -> Vec<SloStatus> {
+    let window_start = Utc::now() - chrono::Duration::seconds(window_seconds as i64);
+
+    schema
+        .apis
+        .iter()
+        .filter_map(|api| api.slo.as_ref().map(|slo| (api, slo)))
+        .map(|(api, slo)| {
+            let requests: Vec<&TraceRecord> = traces
+                .iter()
+                .filter(|t| matches!(t.entry_type, TraceEntryType::Api))
+                .filter(|t| t.entry_point == api.name)
+                .filter(|t| !matches!(t.status, TraceStatus::Running))
+                .filter(|t| {
+                    DateTime::parse_from_rfc3339(&t.started_at)
+                        .map(|started| started >= window_start)
+                        .unwrap_or(false)
+                })
+                .collect();
+
+            status_for(&api.name, slo, &requests, window_seconds)
+        })
+        .collect()
+}
+
+fn status_for(api: &str, slo: &Slo, requests: &[&TraceRecord], window_seconds: u64) -> SloStatus {
+    let total = requests.len();
+    let failed = requests
+        .iter()
+        .filter(|t| matches!(t.status, TraceStatus::Failed))
+        .count();
+
+    let mut status = SloStatus {
+        api: api.to_string(),
+        target: slo.clone(),
+        window_seconds,
+        total_requests: total,
+        failed_requests: failed,
+        availability: None,
+        availability_burn_rate: None,
+        latency_p99_ms: None,
+        latency_p99_burn_rate: None,
+        latency_p95_ms: None,
+        latency_p95_burn_rate: None,
+        state: SloState::NoData,
+    };
+
+    if total == 0 {
+        return status;
+    }
+
+    let mut durations: Vec<u64> = requests.iter().map(|t| t.duration_ms).collect();
+    durations.sort_unstable();
+
+    let error_rate = failed as f64 / total as f64;
+    status.availability = Some((1.0 - error_rate) * 100.0);
+    status.latency_p99_ms = Some(percentile(&durations, 0.99));
+    status.latency_p95_ms = Some(percentile(&durations, 0.95));
+
+    if let Some(target) = slo.availability {
+        status.availability_burn_rate = Some(burn_rate(error_rate, 1.0 - target / 100.0));
+    }
+
+    let slow_fraction = |limit: u64| durations.iter().filter(|d| **d > limit).count() as f64 / total as f64;
+    if let Some(limit) = slo.latency_p99_ms {
+        status.latency_p99_burn_rate = Some(burn_rate(slow_fraction(limit), 0.01));
+    }
+    if let Some(limit) = slo.latency_p95_ms {
+        status.latency_p95_burn_rate = Some(burn_rate(slow_fraction(limit), 0.05));
+    }
+
+    let breached = [
+        status.availability_burn_rate,
+        status.latency_p99_burn_rate,
+        status.latency_p95_burn_rate,
+    ]
+    .iter()
+    .flatten()
+    .any(|rate| *rate > 1.0);
+
+    status.state = if breached { SloState::Breached } else { SloState::Ok };
+    status
+}
+
+/// Highest reported burn rate. A target that allows no failures, such as
+/// 100% availability, burns at this rate once any request fails; JSON has
+/// no infinity.
+const MAX_BURN_RATE: f64 = 1000.0;
+
+fn burn_rate(observed: f64, budget: f64) -> f64 {
+    if budget <= 0.0 {
+        if observed > 0.0 {
+            MAX_BURN_RATE
+        } else {
+            0.0
+        }
+    } else {
+        (observed / budget).min(MAX_BURN_RATE)
+    }
+}
+
+fn percentile(sorted: &[u64], quantile: f64) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (quantile * sorted.len() as f64).ceil() as usize;
+    sorted[rank.saturating_sub(1).min(sorted.len() - 1)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rohas_parser::Parser;
+    use std::collections::HashMap;
+
+    fn schema(slo: &str) -> Schema {
+        Parser::parse_string(&format!(
+            "api GetUser {{\n  method: GET\n  path: \"/users/:id\"\n  response: String\n  slo: {{ {} }}\n}}\n",
+            slo
+        ))
+        .unwrap()
+    }
+
+    fn request(api: &str, duration_ms: u64, status: TraceStatus) -> TraceRecord {
+        TraceRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            entry_point: api.to_string(),
+            entry_type: TraceEntryType::Api,
+            status,
+            duration_ms,
+            started_at: Utc::now().to_rfc3339(),
+            completed_at: None,
+            steps: Vec::new(),
+            error: None,
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_percentile() {
+        let durations: Vec<u64> = (1..=100).collect();
+        assert_eq!(percentile(&durations, 0.99), 99);
+        assert_eq!(percentile(&durations, 0.95), 95);
+        assert_eq!(percentile(&[7], 0.99), 7);
+        assert_eq!(percentile(&[10, 20, 30], 0.99), 30);
+        assert_eq!(percentile(&[], 0.99), 0);
+    }
+
+    #[test]
+    fn test_burn_rate() {
+        assert_eq!(burn_rate(0.002, 0.001), 2.0);
+        assert_eq!(burn_rate(0.0005, 0.001), 0.5);
+        assert_eq!(burn_rate(0.0, 0.01), 0.0);
+        // A target that allows no failures is capped, not infinite.
+        assert_eq!(burn_rate(0.0, 0.0), 0.0);
+        assert_eq!(burn_rate(0.5, 0.0), MAX_BURN_RATE);
+        assert_eq!(burn_rate(1.0, 0.0001), MAX_BURN_RATE);
+    }
+
+    #[test]
+    fn test_latency_burn_rate_counts_requests_over_the_target() {
+        let schema = schema("latency_p99: 300ms");
+        let mut traces: Vec<TraceRecord> = (0..98).map(|_| request("GetUser", 100, TraceStatus::Success)).collect();
+        traces.push(request("GetUser", 400, TraceStatus::Success));
+        traces.push(request("GetUser", 500, TraceStatus::Success));
+
+        let status = &compute_slo_status(&schema, &traces, 3600)[0];
+        assert_eq!(status.latency_p99_ms, Some(400));
+        assert_eq!(status.latency_p99_burn_rate, Some(2.0));
+        assert!(matches!(status.state, SloState::Breached));
+
+        // One slow request in 99 is just over the 1% the target allows.
+        traces.pop();
+        let status = &compute_slo_status(&schema, &traces, 3600)[0];
+        assert_eq!(status.latency_p99_ms, Some(400));
+        assert!((status.latency_p99_burn_rate.unwrap() - 1.0 / 0.99).abs() < 1e-9);
+        assert!(matches!(status.state, SloState::Breached));
+
+        traces.pop();
+        let status = &compute_slo_status(&schema, &traces, 3600)[0];
+        assert_eq!(status.latency_p99_burn_rate, Some(0.0));
+        assert!(matches!(status.state, SloState::Ok));
+    }
+
+    #[test]
+    fn test_availability_counts_only_completed_requests_of_the_api_in_the_window() {
+        let schema = schema("availability: 99.0");
+        let mut old = request("GetUser", 10, TraceStatus::Failed);
+        old.started_at = (Utc::now() - chrono::Duration::seconds(7200)).to_rfc3339();
+        let mut event = request("GetUser", 10, TraceStatus::Failed);
+        event.entry_type = TraceEntryType::Event;
+        let mut traces = vec![
+            old,
+            event,
+            request("ListUsers", 10, TraceStatus::Failed),
+            request("GetUser", 10, TraceStatus::Running),
+            request("GetUser", 10, TraceStatus::Failed),
+        ];
+        traces.extend((0..49).map(|_| request("GetUser", 10, TraceStatus::Success)));
+
+        let status = &compute_slo_status(&schema, &traces, 3600)[0];
+        assert_eq!(status.total_requests, 50);
+        assert_eq!(status.failed_requests, 1);
+        assert_eq!(status.availability, Some(98.0));
+        assert!((status.availability_burn_rate.unwrap() - 2.0).abs() < 1e-9);
+        assert!(matches!(status.state, SloState::Breached));
+    }
+
+    #[test]
+    fn test_slo_without_requests_has_no_data() {
+        let status = &compute_slo_status(&schema("availability: 99.9"), &[], 3600)[0];
+        assert_eq!(status.total_requests, 0);
+        assert_eq!(status.availability_burn_rate, None);
+        assert!(matches!(status.state, SloState::NoData));
+    }
+
+    #[test]
+    fn test_full_availability_target_serializes_its_burn_rate() {
+        let traces = [request("GetUser", 10, TraceStatus::Failed)];
+        let status = &compute_slo_status(&schema("availability: 100"), &traces, 3600)[0];
+        let json = serde_json::to_value(status).unwrap();
+        assert_eq!(json["availability_burn_rate"], serde_json::json!(MAX_BURN_RATE));
+        assert_eq!(json["state"], "breached");
+    }
+}
@@ -0,0 +1,196 @@
+use crate::config::{EngineConfig, Language};
+use rohas_codegen::templates;
+use rohas_parser::Schema;
+use rohas_runtime::Executor;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueSeverity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StartupIssue {
+    pub severity: IssueSeverity,
+    pub message: String,
+}
+
+/// Problems found while checking the resolved schema against the project
+/// before the engine starts serving requests.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StartupReport {
+    pub issues: Vec<StartupIssue>,
+}
+
+impl StartupReport {
+    pub fn has_errors(&self) -> bool {
+        self.issues.iter().any(|i| i.severity == IssueSeverity::Error)
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &StartupIssue> {
+        self.issues.iter().filter(|i| i.severity == IssueSeverity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &StartupIssue> {
+        self.issues.iter().filter(|i| i.severity == IssueSeverity::Warning)
+    }
+
+    fn error(&mut self, message: String) {
+        self.issues.push(StartupIssue {
+            severity: IssueSeverity::Error,
+            message,
+        });
+    }
+
+    fn warning(&mut self, message: String) {
+        self.issues.push(StartupIssue {
+            severity: IssueSeverity::Warning,
+            message,
+        });
+    }
+}
+
+impl fmt::Display for StartupReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let errors = self.errors().count();
+        let warnings = self.warnings().count();
+        writeln!(f, "Startup check found {} error(s) and {} warning(s):", errors, warnings)?;
+        for issue in self.errors().chain(self.warnings()) {
+            let label = match issue.severity {
+                IssueSeverity::Error => "error",
+                IssueSeverity::Warning => "warning",
+            };
+            writeln!(f, "  {}: {}", label, issue.message)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks that every route, handler, middleware and event referenced by the
+/// schema can actually be served.
+pub async fn check_system(schema: &Schema, config: &EngineConfig, executor: &Executor) -> StartupReport {
+    let mut report = StartupReport::default();
+
+    check_routes(schema, &mut report);
+
+    for api in &schema.apis {
+        let handler_name = handler_name_for(&config.language, &api.name);
+        if !executor.has_handler(&handler_name).await {
+            report.error(format!(
+                "API '{}' ({} {}) has no handler '{}'",
+                api.name, api.method, api.path, handler_name
+            ));
+        }
+    }
+
+    for event in &schema.events {
+        if event.handlers.is_empty() {
+            report.warning(format!("Event '{}' has no handlers", event.name));
+        }
+        for handler in &event.handlers {
+            if !executor.has_handler(handler).await {
+                report.error(format!(
+                    "Event '{}' references missing handler '{}'",
+                    event.name, handler
+                ));
+            }
+        }
+    }
+
+    for cron in &schema.crons {
+        if !executor.has_handler(&cron.name).await {
+            report.error(format!("Cron '{}' has no handler", cron.name));
+        }
+    }
+
+    let mut middlewares: Vec<(&str, &str)> = Vec::new();
+    for api in &schema.apis {
+        middlewares.extend(api.middlewares.iter().map(|m| (m.as_str(), api.name.as_str())));
+    }
+    for ws in &schema.websockets {
+        middlewares.extend(ws.middleware_handlers().map(|m| (m.as_str(), ws.name.as_str())));
+    }
+    let mut checked = HashSet::new();
+    for (middleware, owner) in middlewares {
+        if !checked.insert(middleware) {
+            continue;
+        }
+        let handler_name = handler_name_for(&config.language, middleware);
+        if !executor.has_handler(&handler_name).await {
+            report.error(format!(
+                "Middleware '{}' (used by '{}') has no implementation",
+                middleware, owner
+            ));
+        }
+    }
+
+    let event_names: HashSet<&str> = schema.events.iter().map(|e| e.name.as_str()).collect();
+    let triggers = schema
+        .apis
+        .iter()
+        .map(|a| (a.name.as_str(), &a.triggers))
+        .chain(schema.events.iter().map(|e| (e.name.as_str(), &e.triggers)))
+        .chain(schema.crons.iter().map(|c| (c.name.as_str(), &c.triggers)))
+        .chain(schema.websockets.iter().map(|w| (w.name.as_str(), &w.triggers)));
+    for (owner, triggers) in triggers {
+        for trigger in triggers {
+            if !event_names.contains(trigger.as_str()) {
+                report.warning(format!("'{}' triggers undefined event '{}'", owner, trigger));
+            }
+        }
+    }
+
+    report
+}
+
+fn check_routes(schema: &Schema, report: &mut StartupReport) {
+    let mut routes: HashMap<(String, String), &str> = HashMap::new();
+
+    let entries = schema
+        .apis
+        .iter()
+        .map(|api| (api.method.to_string(), api.path.as_str(), api.name.as_str()))
+        .chain(
+            schema
+                .websockets
+                .iter()
+                .map(|ws| ("GET".to_string(), ws.path.as_str(), ws.name.as_str())),
+        );
+
+    for (method, path, name) in entries {
+        let key = (method.clone(), route_shape(path));
+        if let Some(existing) = routes.insert(key, name) {
+            report.error(format!(
+                "Route {} {} is declared by both '{}' and '{}'",
+                method, path, existing, name
+            ));
+        }
+    }
+}
+
+/// Reduces a path to its matching shape so `/users/{id}` and `/users/{userId}`
+/// compare equal.
+fn route_shape(path: &str) -> String {
+    path.trim_end_matches('/')
+        .split('/')
+        .map(|segment| {
+            if segment.starts_with('{') || segment.starts_with(':') {
+                "{}"
+            } else {
+                segment
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn handler_name_for(language: &Language, name: &str) -> String {
+    match language {
+        Language::TypeScript => name.to_string(),
+        Language::Python | Language::Rust => templates::to_snake_case(name),
+    }
+}
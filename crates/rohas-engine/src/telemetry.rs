@@ -1,3 +1,4 @@
+#[cfg(feature = "rocksdb")]
 use adapter_rocksdb::RocksDBAdapter;
 use rohas_telemetry::{LogStore, MetricStore, TelemetryAdapter, TraceStore as TelemetryTraceStore, traces::{TraceStep as TelemetryTraceStep, TriggeredEventInfo as TelemetryTriggeredEventInfo}, storage::IterateCallback};
 use async_trait::async_trait;
@@ -6,9 +7,12 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 use chrono::Utc;
-use std::collections::HashMap;
+use rohas_runtime::{HandlerContext, HandlerResult};
+use std::collections::{HashMap, HashSet};
 
-use crate::trace::{TraceEntryType, TraceRecord, TraceStatus, TraceStep, TriggeredEventInfo};
+use crate::config::TraceSamplingConfig;
+use crate::recording::{Recorder, Snapshot};
+use crate::trace::{TraceContext, TraceEntryType, TraceRecord, TraceStatus, TraceStep, TriggeredEventInfo};
 
 pub struct TelemetryManager {
     _adapter: TelemetryAdapter,
@@ -21,8 +25,17 @@ pub struct TelemetryManager {
 
 impl TelemetryManager {
     pub async fn new(telemetry_path: PathBuf, retention_days: u32) -> Result<Self, Box<dyn std::error::Error>> {
-        let rocksdb_adapter = RocksDBAdapter::new(telemetry_path).await?;
-        let storage: Arc<dyn rohas_telemetry::StorageAdapter> = Arc::new(rocksdb_adapter);
+        #[cfg(feature = "rocksdb")]
+        let storage: Arc<dyn rohas_telemetry::StorageAdapter> =
+            Arc::new(RocksDBAdapter::new(telemetry_path).await?);
+        #[cfg(not(feature = "rocksdb"))]
+        let storage: Arc<dyn rohas_telemetry::StorageAdapter> = {
+            tracing::info!(
+                "Built without RocksDB; telemetry for {} is kept in memory",
+                telemetry_path.display()
+            );
+            Arc::new(rohas_telemetry::MemoryStorage::new())
+        };
         
         let trace_store = Arc::new(TelemetryTraceStore::new(storage.clone()));
         let log_store = Arc::new(LogStore::new(storage.clone()));
@@ -95,16 +108,77 @@ impl rohas_telemetry::StorageAdapter for StorageWrapper {
     }
 }
 
+/// Decides which completed traces are persisted.
+struct TraceSampler {
+    config: TraceSamplingConfig,
+}
+
+impl TraceSampler {
+    /// Head decision, taken when the root trace of a chain starts.
+    fn sample(&self, entry_point: &str, metadata: &HashMap<String, String>) -> bool {
+        let route = match (metadata.get("method"), metadata.get("path")) {
+            (Some(method), Some(path)) => Some(format!("{} {}", method, path)),
+            _ => None,
+        };
+        let rate = self
+            .config
+            .routes
+            .get(entry_point)
+            .or_else(|| route.and_then(|r| self.config.routes.get(&r)))
+            .copied()
+            .unwrap_or(self.config.rate);
+
+        rate >= 1.0 || (rate > 0.0 && rand::random::<f64>() < rate)
+    }
+
+    /// Tail decision for traces the head sample skipped.
+    fn retain(&self, trace: &TraceRecord) -> bool {
+        (self.config.keep_errors && matches!(trace.status, TraceStatus::Failed))
+            || (self.config.slow_threshold_ms > 0 && trace.duration_ms >= self.config.slow_threshold_ms)
+    }
+}
+
 pub struct TraceStore {
     telemetry: Arc<TelemetryManager>,
     active_traces: Arc<RwLock<HashMap<String, TraceRecord>>>,
+    sampler: TraceSampler,
+    /// Active traces the head sample skipped.
+    unsampled: RwLock<HashSet<String>>,
+    recorder: Option<Recorder>,
 }
 
 impl TraceStore {
-    pub fn new(telemetry: Arc<TelemetryManager>) -> Self {
+    pub fn new(telemetry: Arc<TelemetryManager>, sampling: TraceSamplingConfig) -> Self {
         Self {
             active_traces: telemetry.active_traces.clone(),
             telemetry,
+            sampler: TraceSampler { config: sampling },
+            unsampled: RwLock::new(HashSet::new()),
+            recorder: None,
+        }
+    }
+
+    /// Records the handler runs of every trace for `rohas replay`.
+    pub fn with_recorder(mut self, recorder: Recorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    /// A copy of a handler's context to pass to [`TraceStore::record`] after
+    /// the run, when recording is on. Run the handler through
+    /// [`crate::recording::with_calls`] to record its KV and HTTP calls.
+    pub fn snapshot(&self, context: &HandlerContext) -> Option<Snapshot> {
+        self.recorder.as_ref().map(|_| Snapshot::new(context))
+    }
+
+    pub async fn record(
+        &self,
+        trace_id: &str,
+        snapshot: Option<Snapshot>,
+        result: &rohas_runtime::Result<HandlerResult>,
+    ) {
+        if let (Some(recorder), Some(snapshot)) = (&self.recorder, snapshot) {
+            recorder.record(trace_id, snapshot, result).await;
         }
     }
 
@@ -117,6 +191,16 @@ impl TraceStore {
         let id = Uuid::new_v4().to_string();
         let started_at = Utc::now().to_rfc3339();
 
+        // A trace started by another one follows its root's decision, so a
+        // chain is kept or skipped as a whole.
+        let sampled = match TraceContext::from_metadata(&metadata) {
+            Some(context) => context.sampled,
+            None => self.sampler.sample(&entry_point, &metadata),
+        };
+        if !sampled {
+            self.unsampled.write().await.insert(id.clone());
+        }
+
         let trace = TraceRecord {
             id: id.clone(),
             entry_point,
@@ -136,6 +220,24 @@ impl TraceStore {
         id
     }
 
+    /// Context for messages the trace publishes: the trace becomes their
+    /// parent, and its own root, if any, stays the root along with its
+    /// sampling decision.
+    pub async fn context(&self, trace_id: &str) -> TraceContext {
+        let root_id = self
+            .active_traces
+            .read()
+            .await
+            .get(trace_id)
+            .and_then(|trace| trace.metadata.get(TraceContext::ROOT_KEY).cloned())
+            .unwrap_or_else(|| trace_id.to_string());
+        TraceContext {
+            root_id,
+            parent_id: trace_id.to_string(),
+            sampled: !self.unsampled.read().await.contains(trace_id),
+        }
+    }
+
     pub async fn add_step(
         &self,
         trace_id: &str,
@@ -195,6 +297,16 @@ impl TraceStore {
                 }
             }
 
+            if let Some(recorder) = &self.recorder {
+                let failed = matches!(trace.status, TraceStatus::Failed);
+                recorder.finish(trace_id, &trace.entry_point, failed).await;
+            }
+
+            let head_sampled = !self.unsampled.write().await.remove(trace_id);
+            if !head_sampled && !self.sampler.retain(&trace) {
+                return;
+            }
+
             let telemetry_entry = rohas_telemetry::TraceEntry {
                 id: trace.id.clone(),
                 entry_point: trace.entry_point.clone(),
@@ -344,6 +456,146 @@ impl TraceStore {
     pub async fn clear(&self) {
         let mut active = self.active_traces.write().await;
         active.clear();
+        self.unsampled.write().await.clear();
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sampler(rate: f64, routes: &[(&str, f64)]) -> TraceSampler {
+        TraceSampler {
+            config: TraceSamplingConfig {
+                rate,
+                routes: routes.iter().map(|(route, rate)| (route.to_string(), *rate)).collect(),
+                ..TraceSamplingConfig::default()
+            },
+        }
+    }
+
+    fn share_sampled(sampler: &TraceSampler, entry_point: &str, metadata: &HashMap<String, String>) -> f64 {
+        let runs = 20_000;
+        let sampled = (0..runs).filter(|_| sampler.sample(entry_point, metadata)).count();
+        sampled as f64 / runs as f64
+    }
+
+    fn api_metadata(method: &str, path: &str) -> HashMap<String, String> {
+        HashMap::from([
+            ("method".to_string(), method.to_string()),
+            ("path".to_string(), path.to_string()),
+        ])
+    }
+
+    fn completed(status: TraceStatus, duration_ms: u64) -> TraceRecord {
+        TraceRecord {
+            id: "t1".to_string(),
+            entry_point: "CreateOrder".to_string(),
+            entry_type: TraceEntryType::Api,
+            status,
+            duration_ms,
+            started_at: Utc::now().to_rfc3339(),
+            completed_at: None,
+            steps: Vec::new(),
+            error: None,
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_sample_rates() {
+        let none = HashMap::new();
+        assert_eq!(share_sampled(&sampler(1.0, &[]), "CreateOrder", &none), 1.0);
+        assert_eq!(share_sampled(&sampler(0.0, &[]), "CreateOrder", &none), 0.0);
+
+        let share = share_sampled(&sampler(0.25, &[]), "CreateOrder", &none);
+        assert!((0.23..0.27).contains(&share), "sampled {}", share);
+    }
+
+    #[test]
+    fn test_route_rates_override_the_default() {
+        let sampler = sampler(0.0, &[("OrderPlaced", 1.0), ("POST /orders", 1.0), ("CreateOrder", 0.0)]);
+        let post = api_metadata("POST", "/orders");
+
+        assert_eq!(share_sampled(&sampler, "OrderPlaced", &HashMap::new()), 1.0);
+        assert_eq!(share_sampled(&sampler, "ListOrders", &post), 1.0);
+        assert_eq!(share_sampled(&sampler, "ListOrders", &api_metadata("GET", "/orders")), 0.0);
+        // A rate for the name wins over one for the route.
+        assert_eq!(share_sampled(&sampler, "CreateOrder", &post), 0.0);
+    }
+
+    #[test]
+    fn test_retain_failed_and_slow_traces() {
+        let mut sampler = sampler(0.0, &[]);
+        sampler.config.slow_threshold_ms = 500;
+        assert!(sampler.retain(&completed(TraceStatus::Failed, 1)));
+        assert!(sampler.retain(&completed(TraceStatus::Success, 500)));
+        assert!(!sampler.retain(&completed(TraceStatus::Success, 499)));
+
+        sampler.config.keep_errors = false;
+        sampler.config.slow_threshold_ms = 0;
+        assert!(!sampler.retain(&completed(TraceStatus::Failed, 10_000)));
+    }
+
+    #[tokio::test]
+    async fn test_unsampled_traces_are_only_kept_when_they_fail() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let telemetry = Arc::new(TelemetryManager::new(dir.path().to_path_buf(), 0).await.unwrap());
+        let sampling = TraceSamplingConfig {
+            rate: 0.0,
+            ..TraceSamplingConfig::default()
+        };
+        let store = TraceStore::new(telemetry, sampling);
+
+        let ok = store.start_trace("CreateOrder".to_string(), TraceEntryType::Api, HashMap::new()).await;
+        let failed = store.start_trace("CreateOrder".to_string(), TraceEntryType::Api, HashMap::new()).await;
+        store.complete_trace(&ok, TraceStatus::Success, None).await;
+        store.complete_trace(&failed, TraceStatus::Failed, Some("boom".to_string())).await;
+
+        let traces = store.get_traces(None).await;
+        assert_eq!(traces.iter().map(|t| t.id.as_str()).collect::<Vec<_>>(), [failed.as_str()]);
+        assert!(store.unsampled.read().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_child_traces_follow_their_root() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let telemetry = Arc::new(TelemetryManager::new(dir.path().to_path_buf(), 0).await.unwrap());
+        let sampling = TraceSamplingConfig {
+            rate: 0.0,
+            routes: HashMap::from([("CreateOrder".to_string(), 1.0), ("OrderPlaced".to_string(), 1.0)]),
+            ..TraceSamplingConfig::default()
+        };
+        let store = TraceStore::new(telemetry, sampling);
+
+        // Children of an unsampled root are skipped, whatever their own rate.
+        let skipped = store.start_trace("ListOrders".to_string(), TraceEntryType::Api, HashMap::new()).await;
+        let context = store.context(&skipped).await;
+        assert!(!context.sampled);
+        let skipped_child = store
+            .start_trace("OrderPlaced".to_string(), TraceEntryType::Event, context.to_metadata())
+            .await;
+        let grandchild = store
+            .start_trace("OrderPlaced".to_string(), TraceEntryType::Event, store.context(&skipped_child).await.to_metadata())
+            .await;
+        assert_eq!(store.context(&grandchild).await.root_id, skipped);
+        assert!(!store.context(&grandchild).await.sampled);
+
+        // Children of a sampled root are kept, whatever their own rate.
+        let kept = store.start_trace("CreateOrder".to_string(), TraceEntryType::Api, HashMap::new()).await;
+        let child = store
+            .start_trace("ShipOrder".to_string(), TraceEntryType::Event, store.context(&kept).await.to_metadata())
+            .await;
+        assert!(store.context(&child).await.sampled);
+
+        for trace in [&grandchild, &skipped_child, &child, &kept, &skipped] {
+            store.complete_trace(trace, TraceStatus::Success, None).await;
+        }
+        let traces = store.get_traces(None).await;
+        let mut ids = traces.iter().map(|t| t.id.as_str()).collect::<Vec<_>>();
+        ids.sort();
+        let mut expected = [kept.as_str(), child.as_str()];
+        expected.sort();
+        assert_eq!(ids, expected);
+    }
+}
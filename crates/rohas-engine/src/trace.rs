@@ -1,5 +1,43 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Links the trace of an event's handlers to the trace that published the
+/// event. It travels in adapter message metadata, so a chain of API call,
+/// events and handlers stays connected across a broker.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceContext {
+    /// The first trace of the chain, e.g. the API call.
+    pub root_id: String,
+    /// The trace that published the message.
+    pub parent_id: String,
+    /// The root's sampling decision, which every trace of the chain follows.
+    pub sampled: bool,
+}
+
+impl TraceContext {
+    pub const ROOT_KEY: &'static str = "root_trace_id";
+    pub const PARENT_KEY: &'static str = "parent_trace_id";
+    pub const SAMPLED_KEY: &'static str = "trace_sampled";
+
+    pub fn from_metadata(metadata: &HashMap<String, String>) -> Option<Self> {
+        let parent_id = metadata.get(Self::PARENT_KEY)?.clone();
+        let root_id = metadata
+            .get(Self::ROOT_KEY)
+            .cloned()
+            .unwrap_or_else(|| parent_id.clone());
+        let sampled = metadata.get(Self::SAMPLED_KEY).is_none_or(|sampled| sampled != "false");
+        Some(Self { root_id, parent_id, sampled })
+    }
+
+    pub fn to_metadata(&self) -> HashMap<String, String> {
+        HashMap::from([
+            (Self::ROOT_KEY.to_string(), self.root_id.clone()),
+            (Self::PARENT_KEY.to_string(), self.parent_id.clone()),
+            (Self::SAMPLED_KEY.to_string(), self.sampled.to_string()),
+        ])
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TriggeredEventInfo {
@@ -54,3 +92,455 @@ pub enum TraceStatus {
     Running,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepChange {
+    Unchanged,
+    Slower,
+    Faster,
+    Added,
+    Removed,
+    NewlyFailing,
+    Recovered,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepDiff {
+    pub name: String,
+    pub change: StepChange,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub baseline_ms: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub candidate_ms: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delta_ms: Option<f64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub added_events: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed_events: Vec<String>,
+    /// How the shape of the step's payload changed. Only compared when both
+    /// sets have recorded payloads for the step.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub payload_changes: Vec<ShapeChange>,
+}
+
+/// A payload field whose JSON type changed, was added or was removed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShapeChange {
+    /// JSON pointer to the field, e.g. `/user/email`.
+    pub path: String,
+    /// The field's type before, e.g. `string`, or `string|null` when it
+    /// differed between payloads.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<String>,
+}
+
+/// The payloads each handler of a set of traces ran with, by handler name,
+/// as far as the traces were recorded.
+pub type StepPayloads = HashMap<String, Vec<Value>>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceSetSummary {
+    pub trace_count: usize,
+    pub avg_duration_ms: f64,
+    pub failure_rate: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceDiff {
+    pub baseline: TraceSetSummary,
+    pub candidate: TraceSetSummary,
+    pub duration_delta_ms: f64,
+    pub steps: Vec<StepDiff>,
+    pub newly_failing_steps: Vec<String>,
+}
+
+#[derive(Default)]
+struct StepAggregate {
+    total_ms: u64,
+    count: usize,
+    failures: usize,
+    events: BTreeSet<String>,
+}
+
+impl StepAggregate {
+    fn avg_ms(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.total_ms as f64 / self.count as f64
+        }
+    }
+
+    fn failing(&self) -> bool {
+        self.failures * 2 > self.count
+    }
+}
+
+fn aggregate_steps(traces: &[TraceRecord]) -> (Vec<String>, HashMap<String, StepAggregate>) {
+    let mut order = Vec::new();
+    let mut steps: HashMap<String, StepAggregate> = HashMap::new();
+
+    for trace in traces {
+        for step in &trace.steps {
+            let aggregate = steps.entry(step.name.clone()).or_insert_with(|| {
+                order.push(step.name.clone());
+                StepAggregate::default()
+            });
+            aggregate.total_ms += step.duration_ms;
+            aggregate.count += 1;
+            if !step.success {
+                aggregate.failures += 1;
+            }
+            aggregate
+                .events
+                .extend(step.triggered_events.iter().map(|e| e.event_name.clone()));
+        }
+    }
+
+    (order, steps)
+}
+
+fn summarize(traces: &[TraceRecord]) -> TraceSetSummary {
+    let count = traces.len();
+    if count == 0 {
+        return TraceSetSummary {
+            trace_count: 0,
+            avg_duration_ms: 0.0,
+            failure_rate: 0.0,
+        };
+    }
+
+    let total: u64 = traces.iter().map(|t| t.duration_ms).sum();
+    let failed = traces
+        .iter()
+        .filter(|t| matches!(t.status, TraceStatus::Failed))
+        .count();
+
+    TraceSetSummary {
+        trace_count: count,
+        avg_duration_ms: total as f64 / count as f64,
+        failure_rate: failed as f64 / count as f64,
+    }
+}
+
+/// The JSON type of every field of `payloads`, by JSON pointer. Objects are
+/// followed key by key; arrays count as one value, as in
+/// [`diff_payloads`](crate::recording::diff_payloads).
+fn payload_shape(payloads: &[Value]) -> BTreeMap<String, BTreeSet<&'static str>> {
+    fn walk(shape: &mut BTreeMap<String, BTreeSet<&'static str>>, path: String, value: &Value) {
+        let kind = match value {
+            Value::Null => "null",
+            Value::Bool(_) => "boolean",
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Array(_) => "array",
+            Value::Object(object) => {
+                for (key, value) in object {
+                    walk(shape, format!("{}/{}", path, key.replace('~', "~0").replace('/', "~1")), value);
+                }
+                "object"
+            }
+        };
+        shape.entry(path).or_default().insert(kind);
+    }
+
+    let mut shape = BTreeMap::new();
+    for payload in payloads {
+        walk(&mut shape, String::new(), payload);
+    }
+    shape
+}
+
+fn diff_shapes(before: &[Value], after: &[Value]) -> Vec<ShapeChange> {
+    if before.is_empty() || after.is_empty() {
+        return Vec::new();
+    }
+    let before = payload_shape(before);
+    let after = payload_shape(after);
+    let kind = |kinds: &BTreeSet<&str>| kinds.iter().copied().collect::<Vec<_>>().join("|");
+
+    let paths: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let (old, new) = (before.get(path).map(kind), after.get(path).map(kind));
+            (old != new).then(|| ShapeChange {
+                path: path.clone(),
+                before: old,
+                after: new,
+            })
+        })
+        .collect()
+}
+
+/// Compares two sets of traces step by step. Each set is usually a single
+/// trace, or all traces of one handler within a time window, in which case
+/// step latencies are averaged and a step counts as failing when most of
+/// its executions failed.
+pub fn diff_traces(baseline: &[TraceRecord], candidate: &[TraceRecord]) -> TraceDiff {
+    diff_traces_with_payloads(baseline, candidate, &StepPayloads::new(), &StepPayloads::new())
+}
+
+/// [`diff_traces`], also comparing the shape of the payloads the steps ran
+/// with, where the traces were recorded.
+pub fn diff_traces_with_payloads(
+    baseline: &[TraceRecord],
+    candidate: &[TraceRecord],
+    baseline_payloads: &StepPayloads,
+    candidate_payloads: &StepPayloads,
+) -> TraceDiff {
+    let (baseline_order, baseline_steps) = aggregate_steps(baseline);
+    let (candidate_order, candidate_steps) = aggregate_steps(candidate);
+
+    let mut names = baseline_order;
+    for name in candidate_order {
+        if !baseline_steps.contains_key(&name) {
+            names.push(name);
+        }
+    }
+
+    let mut steps = Vec::new();
+    let mut newly_failing_steps = Vec::new();
+
+    for name in names {
+        let before = baseline_steps.get(&name);
+        let after = candidate_steps.get(&name);
+
+        let diff = match (before, after) {
+            (Some(before), Some(after)) => {
+                let delta = after.avg_ms() - before.avg_ms();
+                let change = if after.failing() && !before.failing() {
+                    newly_failing_steps.push(name.clone());
+                    StepChange::NewlyFailing
+                } else if before.failing() && !after.failing() {
+                    StepChange::Recovered
+                } else if delta > 0.0 {
+                    StepChange::Slower
+                } else if delta < 0.0 {
+                    StepChange::Faster
+                } else {
+                    StepChange::Unchanged
+                };
+
+                let payload_changes = diff_shapes(
+                    baseline_payloads.get(&name).map(Vec::as_slice).unwrap_or_default(),
+                    candidate_payloads.get(&name).map(Vec::as_slice).unwrap_or_default(),
+                );
+
+                StepDiff {
+                    name,
+                    change,
+                    baseline_ms: Some(before.avg_ms()),
+                    candidate_ms: Some(after.avg_ms()),
+                    delta_ms: Some(delta),
+                    added_events: after.events.difference(&before.events).cloned().collect(),
+                    removed_events: before.events.difference(&after.events).cloned().collect(),
+                    payload_changes,
+                }
+            }
+            (Some(before), None) => StepDiff {
+                name,
+                change: StepChange::Removed,
+                baseline_ms: Some(before.avg_ms()),
+                candidate_ms: None,
+                delta_ms: None,
+                added_events: Vec::new(),
+                removed_events: before.events.iter().cloned().collect(),
+                payload_changes: Vec::new(),
+            },
+            (None, Some(after)) => {
+                if after.failing() {
+                    newly_failing_steps.push(name.clone());
+                }
+                StepDiff {
+                    name,
+                    change: StepChange::Added,
+                    baseline_ms: None,
+                    candidate_ms: Some(after.avg_ms()),
+                    delta_ms: None,
+                    added_events: after.events.iter().cloned().collect(),
+                    removed_events: Vec::new(),
+                    payload_changes: Vec::new(),
+                }
+            }
+            (None, None) => continue,
+        };
+
+        steps.push(diff);
+    }
+
+    let baseline = summarize(baseline);
+    let candidate = summarize(candidate);
+
+    TraceDiff {
+        duration_delta_ms: candidate.avg_duration_ms - baseline.avg_duration_ms,
+        baseline,
+        candidate,
+        steps,
+        newly_failing_steps,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn step(name: &str, duration_ms: u64, success: bool, events: &[&str]) -> TraceStep {
+        TraceStep {
+            name: name.to_string(),
+            handler_name: name.to_string(),
+            duration_ms,
+            success,
+            error: None,
+            timestamp: "2026-01-01T00:00:00Z".to_string(),
+            triggered_events: events
+                .iter()
+                .map(|event| TriggeredEventInfo {
+                    event_name: event.to_string(),
+                    timestamp: "2026-01-01T00:00:00Z".to_string(),
+                    duration_ms: 0,
+                })
+                .collect(),
+        }
+    }
+
+    fn trace(duration_ms: u64, status: TraceStatus, steps: Vec<TraceStep>) -> TraceRecord {
+        TraceRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            entry_point: "CreateOrder".to_string(),
+            entry_type: TraceEntryType::Api,
+            status,
+            duration_ms,
+            started_at: "2026-01-01T00:00:00Z".to_string(),
+            completed_at: None,
+            steps,
+            error: None,
+            metadata: HashMap::new(),
+        }
+    }
+
+    fn change(diff: &TraceDiff, name: &str) -> StepChange {
+        diff.steps.iter().find(|s| s.name == name).unwrap().change.clone()
+    }
+
+    #[test]
+    fn test_diff_traces_compares_steps() {
+        let baseline = trace(
+            100,
+            TraceStatus::Success,
+            vec![
+                step("validate", 10, true, &[]),
+                step("charge", 50, true, &["OrderCharged"]),
+                step("audit", 5, true, &[]),
+                step("legacy", 5, true, &[]),
+            ],
+        );
+        let candidate = trace(
+            150,
+            TraceStatus::Failed,
+            vec![
+                step("validate", 10, true, &[]),
+                step("charge", 80, true, &["OrderCharged", "ReceiptSent"]),
+                step("audit", 2, false, &[]),
+                step("notify", 3, true, &[]),
+            ],
+        );
+
+        let diff = diff_traces(&[baseline], &[candidate]);
+        assert_eq!(diff.duration_delta_ms, 50.0);
+        assert_eq!(diff.candidate.failure_rate, 1.0);
+        let names: Vec<&str> = diff.steps.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["validate", "charge", "audit", "legacy", "notify"]);
+
+        assert!(matches!(change(&diff, "validate"), StepChange::Unchanged));
+        assert!(matches!(change(&diff, "charge"), StepChange::Slower));
+        assert!(matches!(change(&diff, "audit"), StepChange::NewlyFailing));
+        assert!(matches!(change(&diff, "legacy"), StepChange::Removed));
+        assert!(matches!(change(&diff, "notify"), StepChange::Added));
+        assert_eq!(diff.newly_failing_steps, ["audit"]);
+
+        let charge = &diff.steps[1];
+        assert_eq!(charge.delta_ms, Some(30.0));
+        assert_eq!(charge.added_events, ["ReceiptSent"]);
+        assert!(charge.removed_events.is_empty());
+        assert!(charge.payload_changes.is_empty());
+    }
+
+    #[test]
+    fn test_diff_traces_averages_sets_and_fails_on_most_runs() {
+        let baseline = [
+            trace(10, TraceStatus::Success, vec![step("charge", 10, true, &[])]),
+            trace(30, TraceStatus::Failed, vec![step("charge", 30, false, &[])]),
+        ];
+        let candidate = [
+            trace(10, TraceStatus::Success, vec![step("charge", 10, true, &[])]),
+            trace(10, TraceStatus::Success, vec![step("charge", 5, true, &[])]),
+            trace(10, TraceStatus::Failed, vec![step("charge", 3, false, &[])]),
+        ];
+
+        let diff = diff_traces(&baseline, &candidate);
+        assert_eq!(diff.baseline.avg_duration_ms, 20.0);
+        assert_eq!(diff.duration_delta_ms, -10.0);
+        // One failure in two runs is not most of them; one in three neither.
+        assert!(matches!(change(&diff, "charge"), StepChange::Faster));
+        assert_eq!(diff.steps[0].baseline_ms, Some(20.0));
+        assert_eq!(diff.steps[0].candidate_ms, Some(6.0));
+
+        let failing = [trace(10, TraceStatus::Failed, vec![step("charge", 10, false, &[])])];
+        let diff = diff_traces(&failing, &candidate);
+        assert!(matches!(change(&diff, "charge"), StepChange::Recovered));
+        assert!(diff.newly_failing_steps.is_empty());
+
+        let diff = diff_traces(&[], &failing);
+        assert!(matches!(change(&diff, "charge"), StepChange::Added));
+        assert_eq!(diff.newly_failing_steps, ["charge"]);
+        assert_eq!(diff.baseline.trace_count, 0);
+    }
+
+    #[test]
+    fn test_diff_traces_compares_recorded_payload_shapes() {
+        let baseline = [trace(10, TraceStatus::Success, vec![step("charge", 10, true, &[])])];
+        let candidate = [trace(10, TraceStatus::Success, vec![step("charge", 10, true, &[])])];
+        let before = StepPayloads::from([(
+            "charge".to_string(),
+            vec![
+                json!({ "amount": 10, "card": { "last4": "4242" }, "note": "gift" }),
+                json!({ "amount": 20, "card": { "last4": "0005" }, "note": null }),
+            ],
+        )]);
+        let after = StepPayloads::from([(
+            "charge".to_string(),
+            vec![json!({ "amount": "10.00", "card": { "last4": "4242", "brand": "visa" }, "note": "gift" })],
+        )]);
+
+        let diff = diff_traces_with_payloads(&baseline, &candidate, &before, &after);
+        assert_eq!(
+            diff.steps[0].payload_changes,
+            vec![
+                ShapeChange {
+                    path: "/amount".to_string(),
+                    before: Some("number".to_string()),
+                    after: Some("string".to_string()),
+                },
+                ShapeChange {
+                    path: "/card/brand".to_string(),
+                    before: None,
+                    after: Some("string".to_string()),
+                },
+                ShapeChange {
+                    path: "/note".to_string(),
+                    before: Some("null|string".to_string()),
+                    after: Some("string".to_string()),
+                },
+            ]
+        );
+
+        // Without recorded payloads on both sides there is nothing to compare.
+        let diff = diff_traces_with_payloads(&baseline, &candidate, &before, &StepPayloads::new());
+        assert!(diff.steps[0].payload_changes.is_empty());
+    }
+}
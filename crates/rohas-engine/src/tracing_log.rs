@@ -1,7 +1,9 @@
 use chrono::Utc;
+use rohas_runtime::handler_log;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
+use crate::log_export::LogExportSender;
 use tokio::sync::RwLock;
 use tracing::{Event, Level, Subscriber};
 use tracing_subscriber::layer::Context;
@@ -37,9 +39,23 @@ pub struct TracingLogEntry {
     pub line: Option<u32>,
 }
 
+/// Orders level names from `trace` (0) to `error` (4); `None` for anything else.
+pub fn level_rank(level: &str) -> Option<u8> {
+    match level {
+        "trace" => Some(0),
+        "debug" => Some(1),
+        "info" => Some(2),
+        "warn" => Some(3),
+        "error" => Some(4),
+        _ => None,
+    }
+}
+
 pub struct TracingLogStore {
     logs: Arc<RwLock<Vec<TracingLogEntry>>>,
     max_logs: usize,
+    max_age: Option<chrono::Duration>,
+    exporter: Option<LogExportSender>,
 }
 
 impl TracingLogStore {
@@ -47,16 +63,41 @@ impl TracingLogStore {
         Self {
             logs: Arc::new(RwLock::new(Vec::new())),
             max_logs,
+            max_age: None,
+            exporter: None,
         }
     }
 
+    /// Drops entries older than `max_age_seconds` as new ones arrive; 0 keeps them.
+    pub fn with_max_age(mut self, max_age_seconds: u64) -> Self {
+        self.max_age = (max_age_seconds > 0).then(|| chrono::Duration::seconds(max_age_seconds as i64));
+        self
+    }
+
+    /// Forwards every stored entry to a log exporter.
+    pub fn with_exporter(mut self, exporter: LogExportSender) -> Self {
+        self.exporter = Some(exporter);
+        self
+    }
+
     pub async fn add_log(&self, entry: TracingLogEntry) {
+        if let Some(exporter) = &self.exporter {
+            exporter.send(entry.clone());
+        }
+
         let mut logs = self.logs.write().await;
         logs.push(entry);
         
         if logs.len() > self.max_logs {
             logs.remove(0);
         }
+
+        if let Some(max_age) = self.max_age {
+            let cutoff = Utc::now() - max_age;
+            logs.retain(|log| {
+                chrono::DateTime::parse_from_rfc3339(&log.timestamp).map_or(true, |timestamp| timestamp >= cutoff)
+            });
+        }
     }
 
     pub async fn get_logs(&self, limit: Option<usize>, level_filter: Option<&str>) -> Vec<TracingLogEntry> {
@@ -107,6 +148,9 @@ where
         let mut fields = HashMap::new();
         let mut visitor = FieldVisitor::new(&mut fields);
         event.record(&mut visitor);
+        if metadata.target() == handler_log::TARGET {
+            expand_handler_fields(&mut fields);
+        }
 
         let span_name = ctx
             .lookup_current()
@@ -148,6 +192,26 @@ where
     }
 }
 
+/// Spreads the fields a Python or TypeScript handler logged into the
+/// entry's fields, next to `handler` and `trace_id`.
+fn expand_handler_fields(fields: &mut HashMap<String, String>) {
+    if fields.get("trace_id").is_some_and(|trace_id| trace_id.is_empty()) {
+        fields.remove("trace_id");
+    }
+    let Some(json) = fields.remove(handler_log::FIELDS) else {
+        return;
+    };
+    if let Ok(serde_json::Value::Object(handler_fields)) = serde_json::from_str(&json) {
+        for (key, value) in handler_fields {
+            let value = match value {
+                serde_json::Value::String(value) => value,
+                value => value.to_string(),
+            };
+            fields.entry(key).or_insert(value);
+        }
+    }
+}
+
 struct FieldVisitor<'a> {
     fields: &'a mut HashMap<String, String>,
 }
@@ -180,3 +244,47 @@ impl<'a> tracing::field::Visit for FieldVisitor<'a> {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(timestamp: String) -> TracingLogEntry {
+        TracingLogEntry {
+            timestamp,
+            level: "info".to_string(),
+            target: "test".to_string(),
+            message: "message".to_string(),
+            fields: HashMap::new(),
+            span_name: None,
+            span_fields: HashMap::new(),
+            file: None,
+            line: None,
+        }
+    }
+
+    #[test]
+    fn test_level_rank() {
+        assert_eq!(level_rank("trace"), Some(0));
+        assert_eq!(level_rank("error"), Some(4));
+        assert_eq!(level_rank("wran"), None);
+        assert_eq!(level_rank("ERROR"), None);
+    }
+
+    #[tokio::test]
+    async fn test_max_age_compares_timestamps() {
+        let store = TracingLogStore::new(100).with_max_age(60);
+        let west = chrono::FixedOffset::west_opt(10 * 3600).unwrap();
+        let east = chrono::FixedOffset::east_opt(14 * 3600).unwrap();
+
+        // Each sorts on the wrong side of a UTC cutoff when compared as text.
+        let recent = (Utc::now() - chrono::Duration::seconds(10)).with_timezone(&west).to_rfc3339();
+        let old = (Utc::now() - chrono::Duration::seconds(600)).with_timezone(&east).to_rfc3339();
+        store.add_log(entry(recent.clone())).await;
+        store.add_log(entry(old)).await;
+
+        let logs = store.get_logs(None, None).await;
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].timestamp, recent);
+    }
+}
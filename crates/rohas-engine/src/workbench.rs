@@ -3,11 +3,12 @@ use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
     response::{IntoResponse, Response},
-    routing::{get, post},
+    routing::{get, post, put},
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path as StdPath;
@@ -51,6 +52,8 @@ pub struct ProjectFile {
     pub size: u64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -175,28 +178,51 @@ pub struct TraceRecord {
     pub metadata: HashMap<String, String>,
 }
 
-pub fn workbench_routes() -> Router<ApiState> {
-    Router::new()
+/// Routes for the workbench UI. The file editing endpoint is only mounted
+/// when `file_editing` is set, which `rohas dev` does while watching.
+pub fn workbench_routes(file_editing: bool) -> Router<ApiState> {
+    let router = if file_editing {
+        Router::new().route("/api/workbench/files", put(update_file))
+    } else {
+        Router::new()
+    };
+
+    router
         .route("/api/workbench/snapshot", get(get_snapshot))
         .route("/api/workbench/data", get(get_workbench_data))
         .route("/api/workbench/schema-graph", get(get_schema_graph))
         .route("/api/workbench/traces", get(get_traces))
         .route("/api/workbench/traces/poll", get(poll_traces))
+        .route("/api/workbench/traces/compare", get(compare_traces))
+        .route("/api/workbench/traces/{id}/timeline", get(get_trace_timeline))
         .route("/api/workbench/logs", get(get_tracing_logs))
         .route("/api/workbench/logs/poll", get(poll_tracing_logs))
         .route("/api/workbench/endpoints", get(get_endpoints))
         .route("/api/workbench/types/{type_name}", get(get_type_schema))
         .route("/api/workbench/events/{name}/trigger", post(trigger_event))
         .route("/api/workbench/system-metrics", get(get_system_metrics))
+        .route("/api/workbench/chaos", get(get_chaos).put(update_chaos))
+        .route("/api/workbench/metrics/events", get(get_event_metrics))
+        .route("/api/workbench/metrics/slo", get(get_slo_status))
+        .route("/api/workbench/quarantine", get(list_quarantine))
+        .route(
+            "/api/workbench/quarantine/{id}",
+            put(update_quarantined).delete(discard_quarantined),
+        )
+        .route("/api/workbench/quarantine/{id}/reinject", post(reinject_quarantined))
+        .route(
+            "/api/workbench/log-levels",
+            get(get_log_levels).put(update_log_level).delete(reset_log_levels),
+        )
 }
 
 async fn get_snapshot(State(state): State<ApiState>) -> Result<Response, WorkbenchError> {
-    let snapshot = load_project_snapshot(&state.config.project_root)?;
+    let snapshot = load_project_snapshot(&state.config.project_root, &state.config.codegen.handlers_dir)?;
     Ok(Json(snapshot).into_response())
 }
 
 async fn get_workbench_data(State(state): State<ApiState>) -> Result<Response, WorkbenchError> {
-    let snapshot = load_project_snapshot(&state.config.project_root)?;
+    let snapshot = load_project_snapshot(&state.config.project_root, &state.config.codegen.handlers_dir)?;
     let schema_rows = flatten_schema_buckets(&snapshot.schema.buckets, 50);
     let handler_rows = flatten_handler_buckets(&snapshot.handlers.buckets, 50);
     let activity = build_activity_feed(&schema_rows, &handler_rows);
@@ -216,7 +242,7 @@ async fn get_workbench_data(State(state): State<ApiState>) -> Result<Response, W
 }
 
 async fn get_schema_graph(State(state): State<ApiState>) -> Result<Response, WorkbenchError> {
-    let snapshot = load_project_snapshot(&state.config.project_root)?;
+    let snapshot = load_project_snapshot(&state.config.project_root, &state.config.codegen.handlers_dir)?;
     let graph = build_schema_graph(&snapshot)?;
     Ok(Json(graph).into_response())
 }
@@ -342,20 +368,154 @@ async fn poll_traces(
     Ok(Json(api_traces).into_response())
 }
 
+#[derive(Deserialize)]
+struct CompareTracesQuery {
+    baseline: Option<String>,
+    candidate: Option<String>,
+    handler: Option<String>,
+    baseline_from: Option<String>,
+    baseline_to: Option<String>,
+    candidate_from: Option<String>,
+    candidate_to: Option<String>,
+}
+
+/// Diffs either two traces by ID (`baseline`, `candidate`) or all traces of a
+/// handler in two time windows (`handler`, `baseline_from/to`,
+/// `candidate_from/to`). Payload shapes are compared for the steps that
+/// have recorded payloads on both sides, so only with recording enabled.
+async fn compare_traces(
+    State(state): State<ApiState>,
+    Query(params): Query<CompareTracesQuery>,
+) -> Result<Response, WorkbenchError> {
+    let traces = state.trace_store.get_traces(None).await;
+
+    let (baseline, candidate) = match (&params.baseline, &params.candidate, &params.handler) {
+        (Some(baseline_id), Some(candidate_id), _) => {
+            let find = |id: &str| {
+                traces
+                    .iter()
+                    .find(|t| t.id == id)
+                    .cloned()
+                    .ok_or_else(|| WorkbenchError::NotFound(format!("Trace not found: {}", id)))
+            };
+            (vec![find(baseline_id)?], vec![find(candidate_id)?])
+        }
+        (_, _, Some(handler)) => {
+            let baseline_window = parse_window(&params.baseline_from, &params.baseline_to)?;
+            let candidate_window = parse_window(&params.candidate_from, &params.candidate_to)?;
+            (
+                filter_traces(&traces, handler, baseline_window),
+                filter_traces(&traces, handler, candidate_window),
+            )
+        }
+        _ => {
+            return Err(WorkbenchError::BadRequest(
+                "Provide baseline and candidate trace IDs, or a handler with two time windows"
+                    .to_string(),
+            ))
+        }
+    };
+
+    let dir = crate::recording::Recorder::dir(&state.config.recording, &state.config.project_root);
+    let baseline_payloads = crate::recording::step_payloads(&dir, &baseline).await;
+    let candidate_payloads = crate::recording::step_payloads(&dir, &candidate).await;
+    Ok(Json(crate::trace::diff_traces_with_payloads(
+        &baseline,
+        &candidate,
+        &baseline_payloads,
+        &candidate_payloads,
+    ))
+    .into_response())
+}
+
+/// Every handler run of the chain of traces `id` belongs to, from the API
+/// call or cron job that started it, with the payloads of recorded traces and
+/// how they change from step to step.
+async fn get_trace_timeline(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+) -> Result<Response, WorkbenchError> {
+    let traces = state.trace_store.get_traces(None).await;
+    let trace = traces
+        .iter()
+        .find(|t| t.id == id)
+        .ok_or_else(|| WorkbenchError::NotFound(format!("Trace not found: {}", id)))?;
+    let root_id = trace
+        .metadata
+        .get(crate::trace::TraceContext::ROOT_KEY)
+        .cloned()
+        .unwrap_or_else(|| trace.id.clone());
+
+    let mut chain: Vec<_> = traces
+        .into_iter()
+        .filter(|t| t.id == root_id || t.metadata.get(crate::trace::TraceContext::ROOT_KEY) == Some(&root_id))
+        .collect();
+    chain.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+
+    let dir = crate::recording::Recorder::dir(&state.config.recording, &state.config.project_root);
+    let steps = crate::recording::timeline(&dir, &chain).await;
+    Ok(Json(json!({
+        "root_trace_id": root_id,
+        "recording_enabled": state.config.recording.enabled,
+        "steps": steps,
+    }))
+    .into_response())
+}
+
+type TimeWindow = (
+    chrono::DateTime<chrono::FixedOffset>,
+    chrono::DateTime<chrono::FixedOffset>,
+);
+
+fn parse_window(from: &Option<String>, to: &Option<String>) -> Result<TimeWindow, WorkbenchError> {
+    let parse = |value: &Option<String>, name: &str| {
+        let value = value
+            .as_deref()
+            .ok_or_else(|| WorkbenchError::BadRequest(format!("Missing time window bound: {}", name)))?;
+        chrono::DateTime::parse_from_rfc3339(value)
+            .map_err(|e| WorkbenchError::BadRequest(format!("Invalid timestamp '{}': {}", value, e)))
+    };
+
+    Ok((parse(from, "from")?, parse(to, "to")?))
+}
+
+fn filter_traces(
+    traces: &[crate::trace::TraceRecord],
+    handler: &str,
+    (from, to): TimeWindow,
+) -> Vec<crate::trace::TraceRecord> {
+    traces
+        .iter()
+        .filter(|t| t.entry_point == handler || t.steps.iter().any(|s| s.handler_name == handler))
+        .filter(|t| {
+            chrono::DateTime::parse_from_rfc3339(&t.started_at)
+                .map(|started| started >= from && started < to)
+                .unwrap_or(false)
+        })
+        .cloned()
+        .collect()
+}
+
 #[derive(Deserialize)]
 struct LogsQuery {
     limit: Option<usize>,
     level: Option<String>,
+    /// Only entries at this level or above.
+    min_level: Option<String>,
 }
 
 async fn get_tracing_logs(
     State(state): State<ApiState>,
     Query(params): Query<LogsQuery>,
 ) -> Result<Response, WorkbenchError> {
-    let logs = state
+    let mut logs = state
         .tracing_log_store
-        .get_logs(params.limit, params.level.as_deref())
+        .get_logs(None, params.level.as_deref())
         .await;
+    retain_min_level(&mut logs, params.min_level.as_deref())?;
+    if let Some(limit) = params.limit {
+        logs.truncate(limit);
+    }
     
     Ok(Json(logs).into_response())
 }
@@ -364,9 +524,26 @@ async fn get_tracing_logs(
 struct PollLogsQuery {
     since: Option<String>, // timestamp
     level: Option<String>,
+    min_level: Option<String>,
     timeout: Option<u64>,
 }
 
+fn retain_min_level(
+    logs: &mut Vec<crate::tracing_log::TracingLogEntry>,
+    min_level: Option<&str>,
+) -> Result<(), WorkbenchError> {
+    if let Some(min_level) = min_level {
+        let min_rank = crate::tracing_log::level_rank(min_level).ok_or_else(|| {
+            WorkbenchError::BadRequest(format!(
+                "Unknown log level {}, expected trace, debug, info, warn or error",
+                min_level
+            ))
+        })?;
+        logs.retain(|log| crate::tracing_log::level_rank(&log.level).is_some_and(|rank| rank >= min_rank));
+    }
+    Ok(())
+}
+
 async fn poll_tracing_logs(
     State(state): State<ApiState>,
     Query(params): Query<PollLogsQuery>,
@@ -384,13 +561,14 @@ async fn poll_tracing_logs(
             .get_logs(None, params.level.as_deref())
             .await;
         
-        let filtered_logs: Vec<_> = if let Some(since) = &params.since {
+        let mut filtered_logs: Vec<_> = if let Some(since) = &params.since {
             logs.into_iter()
                 .filter(|log| log.timestamp > *since)
                 .collect()
         } else {
             logs
         };
+        retain_min_level(&mut filtered_logs, params.min_level.as_deref())?;
         
         if !filtered_logs.is_empty() {
             return Ok(Json(filtered_logs).into_response());
@@ -404,11 +582,372 @@ async fn poll_tracing_logs(
     }
 }
 
-fn load_project_snapshot(project_root: &StdPath) -> Result<ProjectSnapshot, WorkbenchError> {
+const SCHEMA_EXTENSIONS: &[&str] = &[".ro"];
+const HANDLER_EXTENSIONS: &[&str] = &[".py", ".ts", ".js", ".tsx", ".rs"];
+
+/// Held from the content hash check until the write completes, so two saves
+/// of the same file cannot both pass the `base_hash` check.
+static FILE_WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Deserialize)]
+struct UpdateFileRequest {
+    path: String,
+    content: String,
+    /// Hash of the content the client started editing from. When set, the
+    /// save is rejected if the file changed on disk in the meantime.
+    #[serde(default)]
+    base_hash: Option<String>,
+}
+
+/// Writes a schema or handler file after validating it. The dev server's file
+/// watcher picks up the write and hot reloads the engine or handler runtime.
+async fn update_file(
+    State(state): State<ApiState>,
+    Json(request): Json<UpdateFileRequest>,
+) -> Result<Response, WorkbenchError> {
+    let project_root = state.config.project_root.clone();
+    let handlers_dir = state.config.codegen.handlers_dir.clone();
+    // Validation shells out to node and python3, so none of this runs on the
+    // async workers.
+    let saved = tokio::task::spawn_blocking(move || write_file(&project_root, &handlers_dir, &request))
+        .await
+        .map_err(|e| WorkbenchError::Internal(format!("File update failed: {}", e)))??;
+    Ok(Json(saved).into_response())
+}
+
+fn write_file(
+    project_root: &StdPath,
+    handlers_dir: &StdPath,
+    request: &UpdateFileRequest,
+) -> Result<serde_json::Value, WorkbenchError> {
+    let path = resolve_editable_path(project_root, handlers_dir, &request.path)?;
+    let _guard = FILE_WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    let current_hash = if path.exists() {
+        let current = fs::read_to_string(&path)
+            .map_err(|e| WorkbenchError::Internal(format!("Failed to read file: {}", e)))?;
+        Some(content_hash(&current))
+    } else {
+        None
+    };
+
+    if let Some(base_hash) = &request.base_hash {
+        if current_hash.as_ref() != Some(base_hash) {
+            return Err(WorkbenchError::Conflict(format!(
+                "File {} was modified since it was loaded",
+                request.path
+            )));
+        }
+    }
+
+    validate_file_content(project_root, &path, &request.content)?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| WorkbenchError::Internal(format!("Failed to create directory: {}", e)))?;
+    }
+    fs::write(&path, &request.content)
+        .map_err(|e| WorkbenchError::Internal(format!("Failed to write file: {}", e)))?;
+
+    Ok(json!({
+        "success": true,
+        "path": request.path,
+        "hash": content_hash(&request.content),
+        "created": current_hash.is_none(),
+    }))
+}
+
+/// Resolves a project-relative path, only allowing schema files under
+/// `schema/` and handler files under `handlers_dir`. Symlinks are followed
+/// so that a linked directory or file cannot lead outside the project.
+fn resolve_editable_path(
+    project_root: &StdPath,
+    handlers_dir: &StdPath,
+    relative_path: &str,
+) -> Result<std::path::PathBuf, WorkbenchError> {
+    let relative = StdPath::new(relative_path);
+    if relative.is_absolute()
+        || relative
+            .components()
+            .any(|c| !matches!(c, std::path::Component::Normal(_)))
+    {
+        return Err(WorkbenchError::BadRequest(format!(
+            "Invalid file path: {}",
+            relative_path
+        )));
+    }
+
+    let lower = relative_path.to_lowercase();
+    let allowed = if relative.starts_with("schema") {
+        SCHEMA_EXTENSIONS.iter().any(|ext| lower.ends_with(ext))
+    } else if relative.starts_with(handlers_dir) {
+        HANDLER_EXTENSIONS.iter().any(|ext| lower.ends_with(ext))
+    } else {
+        false
+    };
+
+    if !allowed {
+        return Err(WorkbenchError::BadRequest(format!(
+            "Only schema and handler files can be edited: {}",
+            relative_path
+        )));
+    }
+
+    let path = project_root.join(relative);
+    let canonical_root = project_root
+        .canonicalize()
+        .map_err(|e| WorkbenchError::Internal(format!("Failed to resolve project root: {}", e)))?;
+    // The file and its parents may not exist yet; the deepest existing
+    // ancestor is where a symlink could point elsewhere.
+    let existing = path
+        .ancestors()
+        .find(|ancestor| ancestor.exists())
+        .unwrap_or(project_root);
+    let resolved = existing
+        .canonicalize()
+        .map_err(|e| WorkbenchError::Internal(format!("Failed to resolve {}: {}", relative_path, e)))?;
+    if !resolved.starts_with(&canonical_root) {
+        return Err(WorkbenchError::BadRequest(format!(
+            "File path leaves the project: {}",
+            relative_path
+        )));
+    }
+
+    Ok(path)
+}
+
+fn validate_file_content(project_root: &StdPath, path: &StdPath, content: &str) -> Result<(), WorkbenchError> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "ro" => validate_schema_directory(project_root, path, content),
+        "py" => validate_python_syntax(content),
+        "ts" | "tsx" | "js" => validate_typescript_syntax(project_root, path, content),
+        "rs" => validate_rust_syntax(content),
+        _ => Ok(()),
+    }
+}
+
+/// Parses the edited schema file, then checks the whole `schema/` directory
+/// with the edit applied. Only errors the edit introduces are reported, so a
+/// problem elsewhere in the project does not block unrelated saves. The file
+/// on its own is checked for syntax and declarations only, as it may refer
+/// to definitions in the other files.
+fn validate_schema_directory(project_root: &StdPath, path: &StdPath, content: &str) -> Result<(), WorkbenchError> {
+    let environment = rohas_parser::env::active_environment();
+    let (edited, diagnostics) = rohas_parser::Parser::parse_part_with_diagnostics(content, &environment);
+    if !diagnostics.is_empty() {
+        return Err(WorkbenchError::Schema(diagnostics));
+    }
+
+    let mut files = Vec::new();
+    collect_schema_files(&project_root.join("schema"), &mut files)?;
+
+    let mut others = rohas_parser::Schema::new();
+    let mut current = None;
+    for file in files {
+        let source = fs::read_to_string(&file)
+            .map_err(|e| WorkbenchError::Internal(format!("Failed to read {}: {}", file.display(), e)))?;
+        let (schema, _) = rohas_parser::Parser::parse_with_diagnostics(&source, &environment);
+        if file == path {
+            current = Some(schema);
+        } else {
+            merge_schema(&mut others, schema);
+        }
+    }
+
+    let mut before = others.clone();
+    if let Some(current) = current {
+        merge_schema(&mut before, current);
+    }
+    let existing: Vec<String> = before
+        .validation_errors()
+        .into_iter()
+        .map(|(_, error)| error.to_string())
+        .collect();
+
+    let mut after = others;
+    merge_schema(&mut after, edited);
+    let introduced: Vec<String> = after
+        .validation_errors()
+        .into_iter()
+        .map(|(_, error)| error.to_string())
+        .filter(|error| !existing.contains(error))
+        .collect();
+
+    if introduced.is_empty() {
+        Ok(())
+    } else {
+        Err(WorkbenchError::Validation(introduced.join("; ")))
+    }
+}
+
+fn collect_schema_files(dir: &StdPath, files: &mut Vec<std::path::PathBuf>) -> Result<(), WorkbenchError> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    let entries = fs::read_dir(dir)
+        .map_err(|e| WorkbenchError::Internal(format!("Failed to read {}: {}", dir.display(), e)))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_schema_files(&path, files)?;
+        } else if path.extension().and_then(|e| e.to_str()) == Some("ro") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn merge_schema(into: &mut rohas_parser::Schema, schema: rohas_parser::Schema) {
+    into.models.extend(schema.models);
+    into.types.extend(schema.types);
+    into.inputs.extend(schema.inputs);
+    into.apis.extend(schema.apis);
+    into.events.extend(schema.events);
+    into.crons.extend(schema.crons);
+    into.websockets.extend(schema.websockets);
+}
+
+/// Parses a Rust handler. Like the TypeScript check this catches syntax
+/// errors only; type errors surface when the dev server rebuilds handlers.
+fn validate_rust_syntax(content: &str) -> Result<(), WorkbenchError> {
+    syn::parse_file(content).map(|_| ()).map_err(|e| {
+        let start = e.span().start();
+        WorkbenchError::Validation(format!(
+            "Rust error: line {}, column {}: {}",
+            start.line,
+            start.column + 1,
+            e
+        ))
+    })
+}
+
+/// Exit code of [`TYPESCRIPT_CHECK`] when the project has no `typescript`.
+const TYPESCRIPT_UNAVAILABLE: i32 = 3;
+
+/// Parses stdin with the project's TypeScript compiler and prints one line
+/// per syntax error. Types are not checked, as that needs the whole project.
+const TYPESCRIPT_CHECK: &str = r#"
+let ts;
+try {
+    ts = require(require.resolve("typescript", { paths: [process.cwd()] }));
+} catch (e) {
+    process.exit(3);
+}
+const source = require("fs").readFileSync(0, "utf8");
+const output = ts.transpileModule(source, {
+    fileName: process.argv[1],
+    reportDiagnostics: true,
+    compilerOptions: { jsx: ts.JsxEmit.Preserve },
+});
+for (const d of output.diagnostics || []) {
+    const message = ts.flattenDiagnosticMessageText(d.messageText, " ");
+    if (d.file && d.start !== undefined) {
+        const { line, character } = d.file.getLineAndCharacterOfPosition(d.start);
+        console.error(`line ${line + 1}, column ${character + 1}: ${message}`);
+    } else {
+        console.error(message);
+    }
+}
+process.exit(output.diagnostics && output.diagnostics.length ? 1 : 0);
+"#;
+
+fn validate_typescript_syntax(project_root: &StdPath, path: &StdPath, content: &str) -> Result<(), WorkbenchError> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("handler.ts");
+    let mut child = match Command::new("node")
+        .args(["-e", TYPESCRIPT_CHECK, file_name])
+        .current_dir(project_root)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => {
+            tracing::warn!("Skipping TypeScript syntax check, node not available: {}", e);
+            return Ok(());
+        }
+    };
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(content.as_bytes())
+            .map_err(|e| WorkbenchError::Internal(format!("Failed to run node: {}", e)))?;
+    }
+
+    let output = child
+        .wait_with_output()
+        .map_err(|e| WorkbenchError::Internal(format!("Failed to run node: {}", e)))?;
+
+    match output.status.code() {
+        Some(0) => Ok(()),
+        Some(TYPESCRIPT_UNAVAILABLE) => {
+            tracing::warn!("Skipping TypeScript syntax check, typescript is not installed in the project");
+            Ok(())
+        }
+        _ => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let message = stderr.lines().next().unwrap_or("invalid syntax");
+            Err(WorkbenchError::Validation(format!("TypeScript error: {}", message)))
+        }
+    }
+}
+
+fn validate_python_syntax(content: &str) -> Result<(), WorkbenchError> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let mut child = match Command::new("python3")
+        .args(["-c", "import ast, sys; ast.parse(sys.stdin.read())"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => {
+            tracing::warn!("Skipping Python syntax check, python3 not available: {}", e);
+            return Ok(());
+        }
+    };
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(content.as_bytes())
+            .map_err(|e| WorkbenchError::Internal(format!("Failed to run python3: {}", e)))?;
+    }
+
+    let output = child
+        .wait_with_output()
+        .map_err(|e| WorkbenchError::Internal(format!("Failed to run python3: {}", e)))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let message = stderr.lines().last().unwrap_or("invalid syntax");
+        Err(WorkbenchError::Validation(format!("Python error: {}", message)))
+    }
+}
+
+fn content_hash(content: &str) -> String {
+    let digest = Sha256::digest(content.as_bytes());
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn load_project_snapshot(project_root: &StdPath, handlers_dir: &StdPath) -> Result<ProjectSnapshot, WorkbenchError> {
     let root = project_root.to_string_lossy().to_string();
     let config = read_project_config(project_root).ok();
     let schema = read_schema_buckets(project_root)?;
-    let handlers = read_handler_buckets(project_root)?;
+    let handlers = read_handler_buckets(project_root, handlers_dir)?;
 
     Ok(ProjectSnapshot {
         root,
@@ -461,7 +1000,7 @@ fn read_project_config(project_root: &StdPath) -> Result<ProjectConfig, Workbenc
 
 fn read_schema_buckets(project_root: &StdPath) -> Result<SchemaInfo, WorkbenchError> {
     let schema_dir = project_root.join("schema");
-    let buckets = collect_buckets(project_root, &schema_dir, true, &[".ro"])?;
+    let buckets = collect_buckets(project_root, &schema_dir, true, SCHEMA_EXTENSIONS)?;
 
     Ok(SchemaInfo {
         total: buckets.iter().map(|b| b.files.len()).sum(),
@@ -469,9 +1008,9 @@ fn read_schema_buckets(project_root: &StdPath) -> Result<SchemaInfo, WorkbenchEr
     })
 }
 
-fn read_handler_buckets(project_root: &StdPath) -> Result<HandlerInfo, WorkbenchError> {
-    let handlers_dir = project_root.join("src").join("handlers");
-    let buckets = collect_buckets(project_root, &handlers_dir, true, &[".py", ".ts", ".js", ".tsx"])?;
+fn read_handler_buckets(project_root: &StdPath, handlers_dir: &StdPath) -> Result<HandlerInfo, WorkbenchError> {
+    let handlers_dir = project_root.join(handlers_dir);
+    let buckets = collect_buckets(project_root, &handlers_dir, true, HANDLER_EXTENSIONS)?;
 
     Ok(HandlerInfo {
         total: buckets.iter().map(|b| b.files.len()).sum(),
@@ -582,11 +1121,14 @@ fn collect_files(
             .to_string_lossy()
             .to_string();
 
+        let hash = content.as_deref().map(content_hash);
+
         files.push(ProjectFile {
             name: file_name,
             relative_path,
             size: metadata.len(),
             content,
+            hash,
         });
     }
 
@@ -849,8 +1391,13 @@ pub struct ApiEndpoint {
     pub path: String,
     pub body: Option<String>,
     pub response: String,
+    pub consumes: String,
+    pub produces: String,
     pub triggers: Vec<String>,
     pub middlewares: Vec<String>,
+    /// The API's `example:`, filled in from the body and response types'
+    /// examples where it leaves them out.
+    pub example: Option<rohas_parser::ApiExample>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -864,6 +1411,8 @@ pub struct WebSocketEndpoint {
     pub triggers: Vec<String>,
     pub broadcast: bool,
     pub middlewares: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth: Option<rohas_parser::WsAuth>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -871,6 +1420,14 @@ pub struct CronJob {
     pub name: String,
     pub schedule: String,
     pub triggers: Vec<String>,
+    /// The schedule in words, or why the expression is invalid.
+    pub description: String,
+    pub next_runs: Vec<String>,
+    pub overlap: rohas_parser::OverlapPolicy,
+    pub misfire: rohas_parser::MisfirePolicy,
+    /// Recent runs, newest first, including ones skipped by the overlap or
+    /// misfire policy.
+    pub history: Vec<rohas_cron::ExecutionRecord>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -879,6 +1436,8 @@ pub struct EventEndpoint {
     pub payload: String,
     pub handlers: Vec<String>,
     pub triggers: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub forward_to: Option<rohas_parser::Forward>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -899,9 +1458,12 @@ async fn get_endpoints(State(state): State<ApiState>) -> Result<Response, Workbe
             method: api.method.to_string(),
             path: api.path.clone(),
             body: api.body.clone(),
-            response: api.response.clone(),
+            response: api.response_declaration(),
+            consumes: api.consumes.mime().to_string(),
+            produces: api.produces.mime().to_string(),
             triggers: api.triggers.clone(),
             middlewares: api.middlewares.clone(),
+            example: api_example(api, &state.schema),
         })
         .collect();
 
@@ -919,19 +1481,30 @@ async fn get_endpoints(State(state): State<ApiState>) -> Result<Response, Workbe
             triggers: ws.triggers.clone(),
             broadcast: ws.broadcast,
             middlewares: ws.middlewares.clone(),
+            auth: ws.auth.clone(),
         })
         .collect();
 
-    let crons: Vec<CronJob> = state
-        .schema
-        .crons
-        .iter()
-        .map(|cron| CronJob {
+    let mut crons: Vec<CronJob> = Vec::with_capacity(state.schema.crons.len());
+    for cron in &state.schema.crons {
+        let job = crate::engine::cron_job_config(cron);
+        let (description, next_runs) = match (job.describe(), job.next_runs(chrono::Utc::now(), 5)) {
+            (Ok(description), Ok(runs)) => (description, runs.iter().map(|run| run.to_rfc3339()).collect()),
+            (Err(e), _) | (_, Err(e)) => (e.to_string(), Vec::new()),
+        };
+        let mut history = state.scheduler.history(&cron.name).await;
+        history.reverse();
+        crons.push(CronJob {
             name: cron.name.clone(),
             schedule: cron.schedule.clone(),
             triggers: cron.triggers.clone(),
-        })
-        .collect();
+            description,
+            next_runs,
+            overlap: cron.overlap,
+            misfire: cron.misfire,
+            history,
+        });
+    }
 
     let events: Vec<EventEndpoint> = state
         .schema
@@ -942,6 +1515,7 @@ async fn get_endpoints(State(state): State<ApiState>) -> Result<Response, Workbe
             payload: event.payload.clone(),
             handlers: event.handlers.clone(),
             triggers: event.triggers.clone(),
+            forward_to: event.forward_to.clone(),
         })
         .collect();
 
@@ -955,6 +1529,23 @@ async fn get_endpoints(State(state): State<ApiState>) -> Result<Response, Workbe
     Ok(Json(data).into_response())
 }
 
+fn api_example(api: &rohas_parser::Api, schema: &rohas_parser::Schema) -> Option<rohas_parser::ApiExample> {
+    let mut example = api.example.clone().unwrap_or_default();
+    if example.request.is_none() {
+        example.request = api.body.as_deref().and_then(|body| schema.example_for(body)).cloned();
+    }
+    if example.response.is_none() {
+        example.response = schema.example_for(&api.response).map(|item| {
+            if api.paginated {
+                json!({ "items": [item], "total": 1, "next_cursor": null })
+            } else {
+                item.clone()
+            }
+        });
+    }
+    (example != rohas_parser::ApiExample::default()).then_some(example)
+}
+
 #[derive(Deserialize)]
 struct TriggerEventRequest {
     payload: Option<serde_json::Value>,
@@ -984,6 +1575,10 @@ async fn get_type_schema(
         "Json" => json!({
             "type": "object"
         }),
+        "Bytes" => json!({
+            "type": "string",
+            "format": "binary"
+        }),
         _ => {
             if let Some(input) = state.schema.inputs.iter().find(|i| i.name == type_name) {
                 let mut properties = serde_json::Map::new();
@@ -999,6 +1594,10 @@ async fn get_type_schema(
                             "format": "date-time"
                         }),
                         FieldType::Json => json!({ "type": "object" }),
+                        FieldType::Bytes => json!({
+                            "type": "string",
+                            "format": "binary"
+                        }),
                         FieldType::Custom(_custom_type) => {
                             json!({ "type": "object" })
                         }
@@ -1034,6 +1633,9 @@ async fn get_type_schema(
                 if !required.is_empty() {
                     schema.as_object_mut().unwrap().insert("required".to_string(), json!(required));
                 }
+                if let Some(example) = state.schema.example_for(&type_name) {
+                    schema.as_object_mut().unwrap().insert("examples".to_string(), json!([example]));
+                }
                 schema
             } else if let Some(model) = state.schema.models.iter().find(|m| m.name == type_name) {
                 let mut properties = serde_json::Map::new();
@@ -1049,6 +1651,10 @@ async fn get_type_schema(
                             "format": "date-time"
                         }),
                         FieldType::Json => json!({ "type": "object" }),
+                        FieldType::Bytes => json!({
+                            "type": "string",
+                            "format": "binary"
+                        }),
                         FieldType::Custom(custom_type) => {
                             json!({ "type": "object" })
                         }
@@ -1084,6 +1690,9 @@ async fn get_type_schema(
                 if !required.is_empty() {
                     schema.as_object_mut().unwrap().insert("required".to_string(), json!(required));
                 }
+                if let Some(example) = state.schema.example_for(&type_name) {
+                    schema.as_object_mut().unwrap().insert("examples".to_string(), json!([example]));
+                }
                 schema
             } else {
                 return Err(WorkbenchError::NotFound(format!("Type '{}' not found", type_name)));
@@ -1115,6 +1724,177 @@ async fn trigger_event(
     .into_response())
 }
 
+#[derive(Deserialize)]
+struct SloQuery {
+    /// Window in seconds ending now.
+    window: Option<u64>,
+}
+
+async fn get_slo_status(
+    State(state): State<ApiState>,
+    Query(params): Query<SloQuery>,
+) -> Result<Response, WorkbenchError> {
+    let window = params.window.unwrap_or(3600);
+    let traces = state.trace_store.get_traces(None).await;
+    let apis = crate::slo::compute_slo_status(&state.schema, &traces, window);
+    Ok(Json(json!({
+        "window_seconds": window,
+        "apis": apis,
+    }))
+    .into_response())
+}
+
+async fn get_chaos(State(state): State<ApiState>) -> Result<Response, WorkbenchError> {
+    Ok(Json(serde_json::json!({
+        "allowed": state.chaos.allowed(),
+        "settings": state.chaos.settings(),
+        "stats": state.chaos.stats(),
+    }))
+    .into_response())
+}
+
+async fn update_chaos(
+    State(state): State<ApiState>,
+    Json(settings): Json<crate::config::ChaosConfig>,
+) -> Result<Response, WorkbenchError> {
+    if !state.chaos.allowed() {
+        return Err(WorkbenchError::Conflict(
+            "Chaos mode is disabled; set [chaos] enabled = true in rohas.toml to allow fault injection"
+                .to_string(),
+        ));
+    }
+
+    state.chaos.update(settings).map_err(WorkbenchError::Validation)?;
+    tracing::warn!("Chaos settings updated: {:?}", state.chaos.settings());
+
+    get_chaos(State(state)).await
+}
+
+async fn list_quarantine(State(state): State<ApiState>) -> Result<Response, WorkbenchError> {
+    let messages = state.event_bus.quarantine().list().await;
+    Ok(Json(json!({ "messages": messages })).into_response())
+}
+
+#[derive(Deserialize)]
+struct QuarantinePayload {
+    payload: serde_json::Value,
+}
+
+async fn update_quarantined(
+    Path(id): Path<String>,
+    State(state): State<ApiState>,
+    Json(request): Json<QuarantinePayload>,
+) -> Result<Response, WorkbenchError> {
+    let message = state
+        .event_bus
+        .quarantine()
+        .update_payload(&id, request.payload)
+        .await
+        .map_err(|e| WorkbenchError::Internal(format!("Failed to update quarantined message: {}", e)))?
+        .ok_or_else(|| WorkbenchError::NotFound(format!("No quarantined message {}", id)))?;
+    Ok(Json(message).into_response())
+}
+
+async fn discard_quarantined(
+    Path(id): Path<String>,
+    State(state): State<ApiState>,
+) -> Result<Response, WorkbenchError> {
+    state
+        .event_bus
+        .quarantine()
+        .remove(&id)
+        .await
+        .map_err(|e| WorkbenchError::Internal(format!("Failed to discard quarantined message: {}", e)))?
+        .ok_or_else(|| WorkbenchError::NotFound(format!("No quarantined message {}", id)))?;
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+/// Publishes a quarantined message's (possibly edited) payload to its event
+/// again and removes it from the quarantine.
+async fn reinject_quarantined(
+    Path(id): Path<String>,
+    State(state): State<ApiState>,
+) -> Result<Response, WorkbenchError> {
+    let quarantine = state.event_bus.quarantine();
+    let message = quarantine
+        .get(&id)
+        .await
+        .ok_or_else(|| WorkbenchError::NotFound(format!("No quarantined message {}", id)))?;
+
+    state
+        .event_bus
+        .emit(&message.event, message.payload.clone())
+        .await
+        .map_err(|e| WorkbenchError::Internal(format!("Failed to emit event: {}", e)))?;
+    quarantine
+        .remove(&id)
+        .await
+        .map_err(|e| WorkbenchError::Internal(format!("Failed to remove quarantined message: {}", e)))?;
+
+    Ok(Json(json!({
+        "success": true,
+        "event": message.event,
+        "payload": message.payload,
+    }))
+    .into_response())
+}
+
+#[derive(Deserialize)]
+struct EventMetricsQuery {
+    /// Window in seconds ending now.
+    window: Option<u64>,
+    topic: Option<String>,
+}
+
+async fn get_event_metrics(
+    State(state): State<ApiState>,
+    Query(params): Query<EventMetricsQuery>,
+) -> Result<Response, WorkbenchError> {
+    let window = params.window.unwrap_or(3600);
+    let end_time = chrono::Utc::now();
+    let start_time = end_time - chrono::Duration::seconds(window as i64);
+
+    let topics = state
+        .event_metrics
+        .series(start_time, end_time, params.topic.as_deref())
+        .await
+        .map_err(|e| WorkbenchError::Internal(format!("Failed to read event metrics: {}", e)))?;
+
+    Ok(Json(json!({
+        "window_seconds": window,
+        "topics": topics,
+    }))
+    .into_response())
+}
+
+async fn get_log_levels() -> Result<Response, WorkbenchError> {
+    let levels = crate::log_level::log_levels().ok_or_else(|| {
+        WorkbenchError::Conflict("Runtime log levels are not available in this process".to_string())
+    })?;
+    Ok(Json(levels).into_response())
+}
+
+#[derive(Deserialize)]
+struct LogLevelUpdate {
+    #[serde(flatten)]
+    scope: crate::log_level::LogScope,
+    /// `None` removes the override.
+    level: Option<String>,
+}
+
+async fn update_log_level(Json(update): Json<LogLevelUpdate>) -> Result<Response, WorkbenchError> {
+    tracing::info!("Setting log level for {:?} to {:?}", update.scope, update.level);
+    crate::log_level::set_log_level(update.scope, update.level.as_deref())
+        .map_err(WorkbenchError::Validation)?;
+    get_log_levels().await
+}
+
+async fn reset_log_levels() -> Result<Response, WorkbenchError> {
+    crate::log_level::reset_log_levels().map_err(WorkbenchError::Internal)?;
+    tracing::info!("Log levels reset");
+    get_log_levels().await
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct SystemMetrics {
     pub cpu: f32,
@@ -1180,14 +1960,29 @@ async fn get_system_metrics() -> Result<Response, WorkbenchError> {
 
 #[derive(Debug)]
 pub enum WorkbenchError {
+    BadRequest(String),
     NotFound(String),
+    Conflict(String),
+    Validation(String),
+    /// Every problem of an edited schema file, for the editor to mark.
+    Schema(Vec<rohas_parser::Diagnostic>),
     Internal(String),
 }
 
 impl IntoResponse for WorkbenchError {
     fn into_response(self) -> Response {
         let (status, message) = match self {
+            WorkbenchError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
             WorkbenchError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
+            WorkbenchError::Conflict(msg) => (StatusCode::CONFLICT, msg),
+            WorkbenchError::Validation(msg) => (StatusCode::UNPROCESSABLE_ENTITY, msg),
+            WorkbenchError::Schema(diagnostics) => {
+                let body = serde_json::json!({
+                    "error": format!("Schema error: {}", diagnostics[0]),
+                    "diagnostics": diagnostics,
+                });
+                return (StatusCode::UNPROCESSABLE_ENTITY, Json(body)).into_response();
+            }
             WorkbenchError::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
         };
 
@@ -1199,3 +1994,155 @@ impl IntoResponse for WorkbenchError {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("schema")).unwrap();
+        fs::create_dir_all(dir.path().join("src/handlers")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_resolve_editable_path() {
+        let dir = project();
+        let handlers = StdPath::new("src/handlers");
+
+        let path = resolve_editable_path(dir.path(), handlers, "src/handlers/api/create_user.ts").unwrap();
+        assert_eq!(path, dir.path().join("src/handlers/api/create_user.ts"));
+        assert!(resolve_editable_path(dir.path(), handlers, "schema/user.ro").is_ok());
+
+        for rejected in [
+            "../outside.ro",
+            "schema/../../outside.ro",
+            "/etc/passwd",
+            "config/rohas.toml",
+            "schema/user.ts",
+            "src/handlers/api/create_user.sh",
+        ] {
+            assert!(
+                resolve_editable_path(dir.path(), handlers, rejected).is_err(),
+                "{} was allowed",
+                rejected
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_editable_path_rejects_symlinks_out_of_the_project() {
+        let dir = project();
+        let outside = tempfile::tempdir().unwrap();
+        fs::write(outside.path().join("secret.ts"), "").unwrap();
+        let handlers = StdPath::new("src/handlers");
+
+        std::os::unix::fs::symlink(outside.path(), dir.path().join("src/handlers/linked")).unwrap();
+        assert!(resolve_editable_path(dir.path(), handlers, "src/handlers/linked/secret.ts").is_err());
+        assert!(resolve_editable_path(dir.path(), handlers, "src/handlers/linked/new/file.ts").is_err());
+
+        std::os::unix::fs::symlink(outside.path().join("secret.ts"), dir.path().join("src/handlers/file.ts"))
+            .unwrap();
+        assert!(resolve_editable_path(dir.path(), handlers, "src/handlers/file.ts").is_err());
+
+        fs::create_dir_all(dir.path().join("src/handlers/api")).unwrap();
+        std::os::unix::fs::symlink(dir.path().join("src/handlers/api"), dir.path().join("src/handlers/alias"))
+            .unwrap();
+        assert!(resolve_editable_path(dir.path(), handlers, "src/handlers/alias/create_user.ts").is_ok());
+    }
+
+    #[test]
+    fn test_validate_typescript_syntax() {
+        let dir = project();
+        let path = dir.path().join("src/handlers/api/create_user.ts");
+        let typescript_available = std::process::Command::new("node")
+            .args(["-e", "require('typescript')"])
+            .current_dir(dir.path())
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false);
+
+        let valid = "export async function handler(req: { name: string }): Promise<string> {\n  return req.name;\n}\n";
+        assert!(validate_file_content(dir.path(), &path, valid).is_ok());
+
+        // Without node or typescript the check is skipped.
+        let invalid = "export async function handler(req: { name: string ) {\n";
+        assert_eq!(validate_file_content(dir.path(), &path, invalid).is_err(), typescript_available);
+    }
+
+    #[test]
+    fn test_validate_schema_checks_the_whole_directory() {
+        let dir = project();
+        fs::write(dir.path().join("schema/user.ro"), "type User {\n  name: String\n}\n").unwrap();
+        let path = dir.path().join("schema/account.ro");
+
+        assert!(validate_file_content(dir.path(), &path, "type Account {\n  id: String\n}\n").is_ok());
+        let err = validate_file_content(dir.path(), &path, "type User {\n  id: String\n}\n").unwrap_err();
+        assert!(matches!(err, WorkbenchError::Validation(message) if message.contains("User")));
+
+        // Rewriting a definition in its own file is not a duplicate.
+        let user = dir.path().join("schema/user.ro");
+        assert!(validate_file_content(dir.path(), &user, "type User {\n  id: String\n}\n").is_ok());
+    }
+
+    #[test]
+    fn test_validate_schema_ignores_existing_errors() {
+        let dir = project();
+        fs::write(dir.path().join("schema/a.ro"), "type Dup {\n  name: String\n}\n").unwrap();
+        fs::write(dir.path().join("schema/b.ro"), "type Dup {\n  name: String\n}\n").unwrap();
+        let path = dir.path().join("schema/c.ro");
+
+        assert!(validate_file_content(dir.path(), &path, "type Other {\n  id: String\n}\n").is_ok());
+    }
+
+    #[test]
+    fn test_validate_schema_resolves_references_across_files() {
+        let dir = project();
+        let feeds = dir.path().join("schema/feeds.ro");
+        fs::write(&feeds, "ws OrderFeed {\n  path: \"/ws/orders\"\n}\n").unwrap();
+        let path = dir.path().join("schema/events.ro");
+        let event = "event OrderUpdated {\n  payload: Order\n  forward_to: OrderFeed\n}\n";
+
+        assert!(validate_file_content(dir.path(), &path, event).is_ok());
+        let missing = event.replace("OrderFeed", "Missing");
+        let err = validate_file_content(dir.path(), &path, &missing).unwrap_err();
+        assert!(matches!(err, WorkbenchError::Validation(message) if message.contains("Missing")));
+    }
+
+    #[test]
+    fn test_validate_rust_syntax() {
+        let dir = project();
+        let path = dir.path().join("src/handlers/api/create_user.rs");
+
+        let valid = "pub async fn handle_create_user(req: Request) -> Result<Response> {\n    Ok(req.into())\n}\n";
+        assert!(validate_file_content(dir.path(), &path, valid).is_ok());
+
+        let invalid = "pub async fn handle_create_user(req: Request -> Result<Response> {\n}\n";
+        let err = validate_file_content(dir.path(), &path, invalid).unwrap_err();
+        assert!(matches!(err, WorkbenchError::Validation(message) if message.starts_with("Rust error: line 1")));
+    }
+
+    #[test]
+    fn test_write_file_rejects_stale_base_hash() {
+        let dir = project();
+        let handlers = StdPath::new("src/handlers");
+        let request = |content: &str, base_hash: Option<String>| UpdateFileRequest {
+            path: "schema/user.ro".to_string(),
+            content: content.to_string(),
+            base_hash,
+        };
+
+        let first = "type User {\n  name: String\n}\n";
+        let saved = write_file(dir.path(), handlers, &request(first, None)).unwrap();
+        assert_eq!(saved["created"], true);
+        let hash = saved["hash"].as_str().unwrap().to_string();
+
+        let second = "type User {\n  id: String\n}\n";
+        assert!(write_file(dir.path(), handlers, &request(second, Some(hash.clone()))).is_ok());
+        let err = write_file(dir.path(), handlers, &request(first, Some(hash))).unwrap_err();
+        assert!(matches!(err, WorkbenchError::Conflict(_)));
+        assert_eq!(fs::read_to_string(dir.path().join("schema/user.ro")).unwrap(), second);
+    }
+}
@@ -1,15 +1,142 @@
-use axum::extract::ws::{Message, WebSocket};
+use axum::extract::ws::{Message, WebSocket as Socket, WebSocketUpgrade};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
 use chrono::Utc;
 use futures_util::{SinkExt, StreamExt};
 use rohas_codegen::templates;
+use rohas_parser::{TokenSource, WebSocket, WsAuth};
 use serde_json::{json, Value};
 use std::collections::HashMap;
-use std::time::Instant;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
 use tracing::{debug, error, warn};
 use uuid::Uuid;
 
+use crate::ws_session::{RECONNECT_TOKEN_PARAM, SESSION_KEY};
 use crate::{api::ApiState, config, trace::TraceEntryType};
 
+fn handler_name_for(state: &ApiState, name: &str) -> String {
+    match state.config.language {
+        config::Language::TypeScript => name.to_string(),
+        config::Language::Python | config::Language::Rust => templates::to_snake_case(name),
+    }
+}
+
+fn read_token(auth: &WsAuth, headers: &HeaderMap, query: &HashMap<String, String>) -> Option<String> {
+    let token = match auth.from {
+        TokenSource::Query => query.get(&auth.name).cloned(),
+        TokenSource::Header => headers
+            .get(auth.name.as_str())
+            .and_then(|h| h.to_str().ok())
+            .map(|value| value.strip_prefix("Bearer ").unwrap_or(value).to_string()),
+    };
+    token.filter(|token| !token.is_empty())
+}
+
+/// Runs the websocket's auth handler and returns the principal it resolved
+/// the token to.
+async fn authenticate(
+    state: &ApiState,
+    auth: &WsAuth,
+    ws_name: &str,
+    token: String,
+    connection: &Value,
+) -> Result<Value, String> {
+    let handler_name = handler_name_for(state, &auth.handler);
+    let payload = json!({
+        "token": token,
+        "connection": connection,
+        "websocket_name": ws_name,
+    });
+
+    let mut context = rohas_runtime::HandlerContext::new(&handler_name, payload);
+    context.metadata.insert("middleware".to_string(), "true".to_string());
+    context.metadata.insert("websocket_name".to_string(), ws_name.to_string());
+
+    let result = state
+        .executor
+        .execute_with_context(context)
+        .await
+        .map_err(|e| format!("Auth handler '{}' execution failed: {}", auth.handler, e))?;
+    if !result.success {
+        return Err(result.error.unwrap_or_else(|| "Authentication failed".to_string()));
+    }
+
+    result
+        .data
+        .and_then(|data| data.get("principal").cloned())
+        .filter(|principal| !principal.is_null())
+        .ok_or_else(|| format!("Auth handler '{}' returned no principal", auth.handler))
+}
+
+/// Checks the websocket's `auth` before accepting the upgrade, so rejected
+/// clients get a plain 401 instead of a socket. A valid reconnection token
+/// resumes the earlier session instead, once `auth` accepts the client's
+/// token again for the same principal.
+pub async fn websocket_upgrade(
+    upgrade: WebSocketUpgrade,
+    state: ApiState,
+    ws_name: String,
+    addr: SocketAddr,
+    headers: HeaderMap,
+    query: HashMap<String, String>,
+) -> Response {
+    let Some(ws_config) = state.schema.websockets.iter().find(|ws| ws.name == ws_name) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    if let Some(token) = query.get(RECONNECT_TOKEN_PARAM) {
+        // Claiming uses up the token, so a rejected resume cannot be retried
+        // with it.
+        if let Some(connection) = state.ws_sessions.claim(&ws_name, token).await {
+            if let Some(auth) = &ws_config.auth {
+                let Some(token) = read_token(auth, &headers, &query) else {
+                    debug!("Rejected WebSocket {} resume without a token", ws_name);
+                    return (StatusCode::UNAUTHORIZED, "Missing token").into_response();
+                };
+                match authenticate(&state, auth, &ws_name, token, &connection).await {
+                    Ok(principal) if principal == connection["principal"] => {}
+                    Ok(_) => {
+                        warn!("Rejected WebSocket {} resume: token belongs to another principal", ws_name);
+                        return (StatusCode::UNAUTHORIZED, "Token belongs to another principal").into_response();
+                    }
+                    Err(e) => {
+                        warn!("Rejected WebSocket {} resume: {}", ws_name, e);
+                        return (StatusCode::UNAUTHORIZED, e).into_response();
+                    }
+                }
+            }
+            return upgrade.on_upgrade(move |socket| websocket_handler(socket, state, ws_name, connection, true));
+        }
+        debug!("Unknown or expired reconnection token for WebSocket {}", ws_name);
+    }
+
+    let mut connection = json!({
+        "connection_id": Uuid::new_v4().to_string(),
+        "path": ws_config.path,
+        "connected_at": Utc::now().to_rfc3339(),
+        "ip_address": crate::api::client_ip(&headers, addr),
+        "principal": null,
+    });
+
+    if let Some(auth) = &ws_config.auth {
+        let Some(token) = read_token(auth, &headers, &query) else {
+            debug!("Rejected WebSocket {} connection without a token", ws_name);
+            return (StatusCode::UNAUTHORIZED, "Missing token").into_response();
+        };
+        match authenticate(&state, auth, &ws_name, token, &connection).await {
+            Ok(principal) => connection["principal"] = principal,
+            Err(e) => {
+                warn!("Rejected WebSocket {} connection: {}", ws_name, e);
+                return (StatusCode::UNAUTHORIZED, e).into_response();
+            }
+        }
+    }
+
+    upgrade.on_upgrade(move |socket| websocket_handler(socket, state, ws_name, connection, false))
+}
+
 async fn execute_websocket_middlewares(
     state: ApiState,
     middlewares: &[String],
@@ -24,11 +151,7 @@ async fn execute_websocket_middlewares(
     debug!("Executing {} middlewares for WebSocket: {}", middlewares.len(), ws_name);
 
     for middleware_name in middlewares {
-        let middleware_handler_name = match state.config.language {
-            config::Language::TypeScript => middleware_name.clone(),
-            config::Language::Python => templates::to_snake_case(middleware_name.as_str()),
-            config::Language::Rust => templates::to_snake_case(middleware_name.as_str()),
-        };
+        let middleware_handler_name = handler_name_for(&state, middleware_name);
 
         debug!("Executing WebSocket middleware: {}", middleware_handler_name);
 
@@ -72,8 +195,8 @@ async fn execute_websocket_middlewares(
     Ok(())
 }
 
-pub async fn websocket_handler(socket: WebSocket, state: ApiState, ws_name: String) {
-    let connection_id = Uuid::new_v4().to_string();
+async fn websocket_handler(socket: Socket, state: ApiState, ws_name: String, connection: Value, resumed: bool) {
+    let connection_id = connection["connection_id"].as_str().unwrap_or_default().to_string();
     let ws_config = state
         .schema
         .websockets
@@ -81,19 +204,125 @@ pub async fn websocket_handler(socket: WebSocket, state: ApiState, ws_name: Stri
         .find(|ws| ws.name == ws_name)
         .expect("WebSocket config not found");
 
-    let (mut sender, mut receiver) = socket.split();
-    let connected_at = Utc::now();
+    let (mut sink, mut receiver) = socket.split();
+    let (sender, mut outgoing) = mpsc::unbounded_channel::<Message>();
+    tokio::spawn(async move {
+        while let Some(message) = outgoing.recv().await {
+            if let Err(e) = sink.send(message).await {
+                debug!("WebSocket send failed: {}", e);
+                break;
+            }
+        }
+    });
 
-    let connection = json!({
-        "connection_id": connection_id,
-        "path": ws_config.path,
-        "connected_at": connected_at.to_rfc3339(),
+    if resumed {
+        let Some((reconnect_token, missed)) = state.ws_sessions.attach(&connection_id, sender.clone()).await else {
+            debug!("WebSocket session {} expired before it was resumed", connection_id);
+            return;
+        };
+        send_session(&state, &sender, &connection_id, &reconnect_token, true);
+        debug!("Resumed WebSocket connection {} with {} missed messages", connection_id, missed.len());
+        for text in missed {
+            let _ = sender.send(Message::Text(text.into()));
+        }
+    } else if !connect(&state, ws_config, &ws_name, &connection, &sender).await {
+        return;
+    }
+
+    let settings = state.ws_sessions.config().clone();
+    let mut heartbeat = tokio::time::interval(Duration::from_secs(settings.heartbeat_interval_secs.max(1)));
+    heartbeat.tick().await;
+    let idle_timeout = (settings.idle_timeout_secs > 0).then(|| Duration::from_secs(settings.idle_timeout_secs));
+    let mut last_seen = tokio::time::Instant::now();
+
+    let closed_by_client = loop {
+        tokio::select! {
+            msg = receiver.next() => {
+                last_seen = tokio::time::Instant::now();
+                match msg {
+                    Some(Ok(Message::Text(text))) => {
+                        handle_message(&state, ws_config, &ws_name, &connection, &connection_id, text.as_str()).await;
+                    }
+                    Some(Ok(Message::Close(_))) => break true,
+                    Some(Err(e)) => {
+                        error!("WebSocket error: {}", e);
+                        break false;
+                    }
+                    None => break false,
+                    _ => {}
+                }
+            }
+            _ = heartbeat.tick(), if settings.heartbeat_interval_secs > 0 => {
+                if sender.send(Message::Ping(Default::default())).is_err() {
+                    break false;
+                }
+            }
+            _ = idle_deadline(idle_timeout.map(|timeout| last_seen + timeout)) => {
+                debug!("Closing idle WebSocket connection {}", connection_id);
+                let _ = sender.send(Message::Close(None));
+                break false;
+            }
+        }
+    };
+
+    // A client that went away without closing can come back with its
+    // reconnection token until the resume window ends.
+    if closed_by_client || !state.ws_sessions.resumable() {
+        state.ws_sessions.remove(&connection_id).await;
+        disconnect(&state, &ws_name, &connection).await;
+    } else if let Some(generation) = state.ws_sessions.detach(&connection_id).await {
+        let window = Duration::from_secs(settings.resume_window_secs);
+        tokio::spawn(async move {
+            tokio::time::sleep(window).await;
+            if let Some(connection) = state.ws_sessions.expire(&connection_id, generation).await {
+                disconnect(&state, &ws_name, &connection).await;
+            }
+        });
+    }
+}
+
+async fn idle_deadline(deadline: Option<tokio::time::Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
+
+fn send_session(
+    state: &ApiState,
+    sender: &mpsc::UnboundedSender<Message>,
+    connection_id: &str,
+    reconnect_token: &str,
+    resumed: bool,
+) {
+    if !state.ws_sessions.resumable() {
+        return;
+    }
+    let message = json!({
+        SESSION_KEY: {
+            "connection_id": connection_id,
+            "reconnect_token": reconnect_token,
+            "resumed": resumed,
+        }
     });
+    let _ = sender.send(Message::Text(message.to_string().into()));
+}
+
+/// Runs the middlewares and `onConnect` handlers of a new connection.
+/// Returns false when a middleware rejected it.
+async fn connect(
+    state: &ApiState,
+    ws_config: &WebSocket,
+    ws_name: &str,
+    connection: &Value,
+    sender: &mpsc::UnboundedSender<Message>,
+) -> bool {
+    let connection_id = connection["connection_id"].as_str().unwrap_or_default();
 
     // Start trace for connection
     let mut metadata = HashMap::new();
     metadata.insert("path".to_string(), ws_config.path.clone());
-    metadata.insert("connection_id".to_string(), connection_id.clone());
+    metadata.insert("connection_id".to_string(), connection_id.to_string());
     let connection_trace_id = state
         .trace_store
         .start_trace(
@@ -114,7 +343,7 @@ pub async fn websocket_handler(socket: WebSocket, state: ApiState, ws_name: Stri
             &ws_config.middlewares,
             middleware_payload,
             &connection_trace_id,
-            &ws_name,
+            ws_name,
         )
         .await;
 
@@ -124,23 +353,22 @@ pub async fn websocket_handler(socket: WebSocket, state: ApiState, ws_name: Stri
                 .trace_store
                 .complete_trace(&connection_trace_id, crate::trace::TraceStatus::Failed, Some(e))
                 .await;
-            return;
+            return false;
         }
     }
 
+    let reconnect_token = state.ws_sessions.open(ws_name, connection.clone(), sender.clone()).await;
+    send_session(state, sender, connection_id, &reconnect_token, false);
+
     if !ws_config.on_connect.is_empty() {
         for handler_name in &ws_config.on_connect {
-            let handler_name = match state.config.language {
-                config::Language::TypeScript => handler_name.clone(),
-                config::Language::Python => templates::to_snake_case(handler_name.as_str()),
-                config::Language::Rust => templates::to_snake_case(handler_name.as_str()),
-            };
+            let handler_name = handler_name_for(state, handler_name);
 
             let payload = connection.clone();
             let mut context = rohas_runtime::HandlerContext::new(&handler_name, payload);
             context
                 .metadata
-                .insert("websocket_name".to_string(), ws_name.clone());
+                .insert("websocket_name".to_string(), ws_name.to_string());
             
             let start = Instant::now();
             let result = state.executor.execute_with_context(context).await;
@@ -165,9 +393,7 @@ pub async fn websocket_handler(socket: WebSocket, state: ApiState, ws_name: Stri
                     if let Some(data) = result.data {
                         if let Ok(msg) = serde_json::to_string(&data) {
                             debug!("Sending welcome message: {}", msg);
-                            if let Err(e) = sender.send(Message::Text(msg.into())).await {
-                                error!("Failed to send welcome message: {}", e);
-                            }
+                            state.ws_sessions.send(connection_id, msg).await;
                         } else {
                             warn!("Failed to serialize welcome message");
                         }
@@ -188,188 +414,182 @@ pub async fn websocket_handler(socket: WebSocket, state: ApiState, ws_name: Stri
         .complete_trace(&connection_trace_id, crate::trace::TraceStatus::Success, None)
         .await;
 
-    while let Some(msg) = receiver.next().await {
-        match msg {
-            Ok(Message::Text(text)) => {
-                let text_str = text.to_string();
-                let message_data: Value =
-                    serde_json::from_str(&text_str).unwrap_or_else(|_| json!({ "data": text_str }));
-
-                let message = json!({
-                    "data": message_data,
-                    "timestamp": Utc::now().to_rfc3339(),
-                });
-
-                let mut message_metadata = HashMap::new();
-                message_metadata.insert("path".to_string(), ws_config.path.clone());
-                message_metadata.insert("connection_id".to_string(), connection_id.clone());
-                let message_trace_id = state
-                    .trace_store
-                    .start_trace(
-                        format!("{} (message)", ws_name),
-                        TraceEntryType::WebSocket,
-                        message_metadata,
-                    )
-                    .await;
+    true
+}
 
-                if !ws_config.on_message.is_empty() {
-                    for handler_name in &ws_config.on_message {
-                        let handler_name = match state.config.language {
-                            config::Language::TypeScript => handler_name.clone(),
-                            config::Language::Python => {
-                                templates::to_snake_case(handler_name.as_str())
-                            }
-                            config::Language::Rust => {
-                                templates::to_snake_case(handler_name.as_str())
-                            }
-                        };
+async fn handle_message(
+    state: &ApiState,
+    ws_config: &WebSocket,
+    ws_name: &str,
+    connection: &Value,
+    connection_id: &str,
+    text: &str,
+) {
+    let text_str = text.to_string();
+    let message_data: Value =
+        serde_json::from_str(&text_str).unwrap_or_else(|_| json!({ "data": text_str }));
+
+    let message = json!({
+        "data": message_data,
+        "timestamp": Utc::now().to_rfc3339(),
+    });
 
-                        let handler_payload = json!({
-                            "message": message,
-                            "connection": connection,
-                        });
+    let mut message_metadata = HashMap::new();
+    message_metadata.insert("path".to_string(), ws_config.path.clone());
+    message_metadata.insert("connection_id".to_string(), connection_id.to_string());
+    let message_trace_id = state
+        .trace_store
+        .start_trace(
+            format!("{} (message)", ws_name),
+            TraceEntryType::WebSocket,
+            message_metadata,
+        )
+        .await;
 
-                        let mut context =
-                            rohas_runtime::HandlerContext::new(&handler_name, handler_payload);
-                        context
-                            .metadata
-                            .insert("websocket_name".to_string(), ws_name.clone());
-                        
-                        let start = Instant::now();
-                        let result = state.executor.execute_with_context(context).await;
-                        let duration_ms = start.elapsed().as_millis() as u64;
-
-                        // Collect triggered events with timestamps and duration, add trace step
-                        let mut triggered_events = Vec::new();
-                        if let Ok(ref exec_result) = result {
-                            if exec_result.success {
-                                // Add events from handler result triggers
-                                for triggered_event in &exec_result.triggers {
-                                    let trigger_start = std::time::Instant::now();
-                                    // Emit the event and measure duration
-                                    let emit_result = state
-                                        .event_bus
-                                        .emit(
-                                            &triggered_event.event_name,
-                                            triggered_event.payload.clone(),
-                                        )
-                                        .await;
-                                    let trigger_duration = trigger_start.elapsed().as_millis() as u64;
-                                    let trigger_timestamp = chrono::Utc::now().to_rfc3339();
-                                    
-                                    if let Err(e) = emit_result {
-                                        tracing::error!(
-                                            "Failed to emit event {} from websocket {}: {}",
-                                            triggered_event.event_name,
-                                            ws_name,
-                                            e
-                                        );
-                                    }
-                                    
-                                    triggered_events.push(crate::trace::TriggeredEventInfo {
-                                        event_name: triggered_event.event_name.clone(),
-                                        timestamp: trigger_timestamp,
-                                        duration_ms: trigger_duration,
-                                    });
-                                }
-                                // Add auto-triggered events from WebSocket config
-                                for trigger in &ws_config.triggers {
-                                    if exec_result.auto_trigger_payloads.contains_key(trigger) {
-                                        let trigger_start = std::time::Instant::now();
-                                        let payload = exec_result.auto_trigger_payloads.get(trigger).cloned();
-                                        
-                                        if let Some(payload) = payload {
-                                            // Emit the event and measure duration
-                                            let emit_result = state.event_bus.emit(trigger, payload).await;
-                                            let trigger_duration = trigger_start.elapsed().as_millis() as u64;
-                                            let trigger_timestamp = chrono::Utc::now().to_rfc3339();
-                                            
-                                            if let Err(e) = emit_result {
-                                                tracing::error!(
-                                                    "Failed to emit auto-triggered event {} from websocket {}: {}",
-                                                    trigger,
-                                                    ws_name,
-                                                    e
-                                                );
-                                            }
-                                            
-                                            triggered_events.push(crate::trace::TriggeredEventInfo {
-                                                event_name: trigger.clone(),
-                                                timestamp: trigger_timestamp,
-                                                duration_ms: trigger_duration,
-                                            });
-                                        }
-                                    }
-                                }
-                            }
-                            
-                            state
-                                .trace_store
-                                .add_step_with_triggers(
-                                    &message_trace_id,
-                                    handler_name.clone(),
-                                    duration_ms.max(exec_result.execution_time_ms),
-                                    exec_result.success,
-                                    exec_result.error.clone(),
-                                    triggered_events.clone(),
-                                )
-                                .await;
+    if !ws_config.on_message.is_empty() {
+        for handler_name in &ws_config.on_message {
+            let handler_name = handler_name_for(state, handler_name);
+
+            let handler_payload = json!({
+                "message": message,
+                "connection": connection,
+            });
+
+            let mut context =
+                rohas_runtime::HandlerContext::new(&handler_name, handler_payload);
+            context
+                .metadata
+                .insert("websocket_name".to_string(), ws_name.to_string());
+
+            let start = Instant::now();
+            let result = state.executor.execute_with_context(context).await;
+            let duration_ms = start.elapsed().as_millis() as u64;
+
+            // Collect triggered events with timestamps and duration, add trace step
+            let mut triggered_events = Vec::new();
+            if let Ok(ref exec_result) = result {
+                if exec_result.success {
+                    // Add events from handler result triggers
+                    for triggered_event in &exec_result.triggers {
+                        let trigger_start = std::time::Instant::now();
+                        // Emit the event and measure duration
+                        let emit_result = state
+                            .event_bus
+                            .emit_from(
+                                &message_trace_id,
+                                &triggered_event.event_name,
+                                triggered_event.payload.clone(),
+                            )
+                            .await;
+                        let trigger_duration = trigger_start.elapsed().as_millis() as u64;
+                        let trigger_timestamp = chrono::Utc::now().to_rfc3339();
+
+                        if let Err(e) = emit_result {
+                            tracing::error!(
+                                "Failed to emit event {} from websocket {}: {}",
+                                triggered_event.event_name,
+                                ws_name,
+                                e
+                            );
                         }
 
-                        if let Ok(result) = result {
-                            if result.success {
-                                if let Some(data) = result.data {
-                                    if let Ok(msg) = serde_json::to_string(&data) {
-                                        tracing::debug!("Sending response message: {}", msg);
-                                        if let Err(e) = sender.send(Message::Text(msg.into())).await
-                                        {
-                                            tracing::error!(
-                                                "Failed to send response message: {}",
-                                                e
-                                            );
-                                        }
-                                    } else {
-                                        tracing::warn!("Failed to serialize response message");
-                                    }
-                                } else {
-                                    tracing::debug!("Handler returned no data (None)");
+                        triggered_events.push(crate::trace::TriggeredEventInfo {
+                            event_name: triggered_event.event_name.clone(),
+                            timestamp: trigger_timestamp,
+                            duration_ms: trigger_duration,
+                        });
+                    }
+                    // Add auto-triggered events from WebSocket config
+                    for trigger in &ws_config.triggers {
+                        if exec_result.auto_trigger_payloads.contains_key(trigger) {
+                            let trigger_start = std::time::Instant::now();
+                            let payload = exec_result.auto_trigger_payloads.get(trigger).cloned();
+
+                            if let Some(payload) = payload {
+                                // Emit the event and measure duration
+                                let emit_result = state.event_bus.emit_from(&message_trace_id, trigger, payload).await;
+                                let trigger_duration = trigger_start.elapsed().as_millis() as u64;
+                                let trigger_timestamp = chrono::Utc::now().to_rfc3339();
+
+                                if let Err(e) = emit_result {
+                                    tracing::error!(
+                                        "Failed to emit auto-triggered event {} from websocket {}: {}",
+                                        trigger,
+                                        ws_name,
+                                        e
+                                    );
                                 }
-                            } else {
-                                tracing::warn!("Handler execution failed: {:?}", result.error);
-                            }
 
-                        } else {
-                            error!("Handler execution error: {:?}", result);
+                                triggered_events.push(crate::trace::TriggeredEventInfo {
+                                    event_name: trigger.clone(),
+                                    timestamp: trigger_timestamp,
+                                    duration_ms: trigger_duration,
+                                });
+                            }
                         }
                     }
+
+                    crate::jobs::schedule(&state.event_bus.jobs(), &handler_name, &exec_result.scheduled).await;
                 }
 
-                // Complete message trace
-                let trace_status = if ws_config.on_message.is_empty() {
-                    crate::trace::TraceStatus::Success
-                } else {
-                    // Check if all handlers succeeded by looking at the last result
-                    crate::trace::TraceStatus::Success // Simplified - could check all results
-                };
                 state
                     .trace_store
-                    .complete_trace(&message_trace_id, trace_status, None)
+                    .add_step_with_triggers(
+                        &message_trace_id,
+                        handler_name.clone(),
+                        duration_ms.max(exec_result.execution_time_ms),
+                        exec_result.success,
+                        exec_result.error.clone(),
+                        triggered_events.clone(),
+                    )
                     .await;
             }
-            Ok(Message::Close(_)) => {
-                break;
-            }
-            Err(e) => {
-                error!("WebSocket error: {}", e);
-                break;
+
+            if let Ok(result) = result {
+                if result.success {
+                    if let Some(data) = result.data {
+                        if let Ok(msg) = serde_json::to_string(&data) {
+                            tracing::debug!("Sending response message: {}", msg);
+                            state.ws_sessions.send(connection_id, msg).await;
+                        } else {
+                            tracing::warn!("Failed to serialize response message");
+                        }
+                    } else {
+                        tracing::debug!("Handler returned no data (None)");
+                    }
+                } else {
+                    tracing::warn!("Handler execution failed: {:?}", result.error);
+                }
+
+            } else {
+                error!("Handler execution error: {:?}", result);
             }
-            _ => {}
         }
     }
 
+    // Complete message trace
+    let trace_status = if ws_config.on_message.is_empty() {
+        crate::trace::TraceStatus::Success
+    } else {
+        // Check if all handlers succeeded by looking at the last result
+        crate::trace::TraceStatus::Success // Simplified - could check all results
+    };
+    state
+        .trace_store
+        .complete_trace(&message_trace_id, trace_status, None)
+        .await;
+}
+
+/// Runs the `onDisconnect` handlers once a connection is gone for good.
+async fn disconnect(state: &ApiState, ws_name: &str, connection: &Value) {
+    let connection_id = connection["connection_id"].as_str().unwrap_or_default();
+    let Some(ws_config) = state.schema.websockets.iter().find(|ws| ws.name == ws_name) else {
+        return;
+    };
+
     let mut disconnect_metadata = HashMap::new();
     disconnect_metadata.insert("path".to_string(), ws_config.path.clone());
-    disconnect_metadata.insert("connection_id".to_string(), connection_id.clone());
+    disconnect_metadata.insert("connection_id".to_string(), connection_id.to_string());
     let disconnect_trace_id = state
         .trace_store
         .start_trace(
@@ -381,17 +601,13 @@ pub async fn websocket_handler(socket: WebSocket, state: ApiState, ws_name: Stri
 
     if !ws_config.on_disconnect.is_empty() {
         for handler_name in &ws_config.on_disconnect {
-            let handler_name = match state.config.language {
-                config::Language::TypeScript => handler_name.clone(),
-                config::Language::Python => templates::to_snake_case(handler_name.as_str()),
-                config::Language::Rust => templates::to_snake_case(handler_name.as_str()),
-            };
+            let handler_name = handler_name_for(state, handler_name);
 
             let payload = connection.clone();
             let mut context = rohas_runtime::HandlerContext::new(&handler_name, payload);
             context
                 .metadata
-                .insert("websocket_name".to_string(), ws_name.clone());
+                .insert("websocket_name".to_string(), ws_name.to_string());
             
             let start = Instant::now();
             let result = state.executor.execute_with_context(context).await;
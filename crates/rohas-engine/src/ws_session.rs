@@ -0,0 +1,278 @@
+use axum::extract::ws::Message;
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use tokio::sync::{mpsc, Mutex};
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::config::WebSocketConfig;
+
+/// Field of the first message of a connection, carrying its id and
+/// reconnection token.
+pub const SESSION_KEY: &str = "$session";
+
+/// Field naming the event of a message forwarded with `forward_to:`.
+pub const EVENT_KEY: &str = "$event";
+
+/// Query parameter a reconnecting client passes its token in.
+pub const RECONNECT_TOKEN_PARAM: &str = "reconnect_token";
+
+struct Session {
+    ws_name: String,
+    connection: Value,
+    reconnect_token: String,
+    /// Outgoing channel of the attached socket, `None` while the client is
+    /// away.
+    sender: Option<mpsc::UnboundedSender<Message>>,
+    /// Messages that could not be delivered while the client was away.
+    missed: VecDeque<String>,
+    /// Bumped on every detach so expiry timers of earlier drops do nothing.
+    generation: u64,
+}
+
+/// Open websocket sessions, including dropped ones that can still be
+/// resumed.
+pub struct SessionRegistry {
+    config: WebSocketConfig,
+    sessions: Mutex<HashMap<String, Session>>,
+}
+
+impl SessionRegistry {
+    pub fn new(config: WebSocketConfig) -> Self {
+        Self {
+            config,
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> &WebSocketConfig {
+        &self.config
+    }
+
+    pub fn resumable(&self) -> bool {
+        self.config.resume_window_secs > 0 && self.config.resume_buffer_size > 0
+    }
+
+    /// Registers a new connection and returns its reconnection token.
+    pub async fn open(&self, ws_name: &str, connection: Value, sender: mpsc::UnboundedSender<Message>) -> String {
+        let connection_id = connection_id(&connection);
+        let reconnect_token = new_token();
+        self.sessions.lock().await.insert(
+            connection_id,
+            Session {
+                ws_name: ws_name.to_string(),
+                connection,
+                reconnect_token: reconnect_token.clone(),
+                sender: Some(sender),
+                missed: VecDeque::new(),
+                generation: 0,
+            },
+        );
+        reconnect_token
+    }
+
+    /// Returns the connection of the dropped session the token belongs to.
+    /// The token is replaced, so it cannot be used a second time.
+    pub async fn claim(&self, ws_name: &str, token: &str) -> Option<Value> {
+        let mut sessions = self.sessions.lock().await;
+        let session = sessions
+            .values_mut()
+            .find(|s| s.sender.is_none() && s.ws_name == ws_name && s.reconnect_token == token)?;
+        session.reconnect_token = new_token();
+        Some(session.connection.clone())
+    }
+
+    /// Attaches the socket of a resumed connection. Returns its new
+    /// reconnection token and the messages it missed, or `None` when the
+    /// session expired in the meantime.
+    pub async fn attach(
+        &self,
+        connection_id: &str,
+        sender: mpsc::UnboundedSender<Message>,
+    ) -> Option<(String, Vec<String>)> {
+        let mut sessions = self.sessions.lock().await;
+        let session = sessions.get_mut(connection_id)?;
+        session.sender = Some(sender);
+        session.reconnect_token = new_token();
+        Some((session.reconnect_token.clone(), session.missed.drain(..).collect()))
+    }
+
+    /// Keeps a dropped connection for resumption. Returns the generation to
+    /// pass to [`SessionRegistry::expire`].
+    pub async fn detach(&self, connection_id: &str) -> Option<u64> {
+        let mut sessions = self.sessions.lock().await;
+        let session = sessions.get_mut(connection_id)?;
+        session.sender = None;
+        session.generation += 1;
+        Some(session.generation)
+    }
+
+    /// Removes a dropped session unless it was resumed since `generation`,
+    /// returning its connection.
+    pub async fn expire(&self, connection_id: &str, generation: u64) -> Option<Value> {
+        let mut sessions = self.sessions.lock().await;
+        let session = sessions.get(connection_id)?;
+        if session.sender.is_some() || session.generation != generation {
+            return None;
+        }
+        sessions.remove(connection_id).map(|s| s.connection)
+    }
+
+    pub async fn remove(&self, connection_id: &str) {
+        self.sessions.lock().await.remove(connection_id);
+    }
+
+    /// Sends a text message to a connection. While the client is away the
+    /// message is kept until it resumes. Returns false for unknown
+    /// connections.
+    pub async fn send(&self, connection_id: &str, text: String) -> bool {
+        let mut sessions = self.sessions.lock().await;
+        let Some(session) = sessions.get_mut(connection_id) else {
+            return false;
+        };
+        self.deliver(session, text);
+        true
+    }
+
+    /// Sends a text message to every connection of a websocket that
+    /// `accept` returns true for. Returns the number of recipients.
+    pub async fn broadcast(&self, ws_name: &str, text: &str, accept: impl Fn(&Value) -> bool) -> usize {
+        let mut sessions = self.sessions.lock().await;
+        let mut recipients = 0;
+        for session in sessions.values_mut() {
+            if session.ws_name == ws_name && accept(&session.connection) {
+                self.deliver(session, text.to_string());
+                recipients += 1;
+            }
+        }
+        recipients
+    }
+
+    fn deliver(&self, session: &mut Session, text: String) {
+        if let Some(sender) = &session.sender {
+            if sender.send(Message::Text(text.clone().into())).is_ok() {
+                return;
+            }
+        }
+
+        if self.resumable() {
+            if session.missed.len() >= self.config.resume_buffer_size {
+                session.missed.pop_front();
+                debug!(
+                    "Dropped oldest missed message of connection {}",
+                    connection_id(&session.connection)
+                );
+            }
+            session.missed.push_back(text);
+        }
+    }
+}
+
+fn connection_id(connection: &Value) -> String {
+    connection["connection_id"].as_str().unwrap_or_default().to_string()
+}
+
+fn new_token() -> String {
+    Uuid::new_v4().simple().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn registry(resume_buffer_size: usize) -> SessionRegistry {
+        SessionRegistry::new(WebSocketConfig {
+            resume_buffer_size,
+            ..WebSocketConfig::default()
+        })
+    }
+
+    async fn open(registry: &SessionRegistry, connection_id: &str) -> (String, mpsc::UnboundedReceiver<Message>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let token = registry
+            .open("Orders", json!({"connection_id": connection_id}), sender)
+            .await;
+        (token, receiver)
+    }
+
+    fn received(receiver: &mut mpsc::UnboundedReceiver<Message>) -> Vec<String> {
+        let mut texts = Vec::new();
+        while let Ok(Message::Text(text)) = receiver.try_recv() {
+            texts.push(text.to_string());
+        }
+        texts
+    }
+
+    #[tokio::test]
+    async fn test_resume_delivers_missed_messages() {
+        let registry = registry(10);
+        let (token, mut receiver) = open(&registry, "c1").await;
+        assert!(registry.send("c1", "first".into()).await);
+        assert_eq!(received(&mut receiver), vec!["first"]);
+
+        registry.detach("c1").await.unwrap();
+        assert!(registry.send("c1", "second".into()).await);
+        assert_eq!(registry.broadcast("Orders", "third", |_| true).await, 1);
+
+        assert_eq!(registry.claim("Payments", &token).await, None);
+        assert_eq!(registry.claim("Orders", &token).await, Some(json!({"connection_id": "c1"})));
+        assert_eq!(registry.claim("Orders", &token).await, None);
+
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let (new_token, missed) = registry.attach("c1", sender).await.unwrap();
+        assert_ne!(new_token, token);
+        assert_eq!(missed, vec!["second", "third"]);
+
+        assert!(registry.send("c1", "fourth".into()).await);
+        assert_eq!(received(&mut receiver), vec!["fourth"]);
+    }
+
+    #[tokio::test]
+    async fn test_full_buffer_drops_the_oldest_messages() {
+        let registry = registry(2);
+        let _ = open(&registry, "c1").await;
+        registry.detach("c1").await.unwrap();
+        for text in ["a", "b", "c"] {
+            registry.send("c1", text.into()).await;
+        }
+
+        let (sender, _receiver) = mpsc::unbounded_channel();
+        let (_, missed) = registry.attach("c1", sender).await.unwrap();
+        assert_eq!(missed, vec!["b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn test_expire_removes_sessions_that_were_not_resumed() {
+        let registry = registry(10);
+        let _ = open(&registry, "c1").await;
+        let _ = open(&registry, "c2").await;
+
+        let generation = registry.detach("c1").await.unwrap();
+        assert_eq!(registry.expire("c1", generation).await, Some(json!({"connection_id": "c1"})));
+        assert!(!registry.send("c1", "gone".into()).await);
+
+        let generation = registry.detach("c2").await.unwrap();
+        let (sender, _receiver) = mpsc::unbounded_channel();
+        registry.attach("c2", sender).await.unwrap();
+        assert_eq!(registry.expire("c2", generation).await, None);
+
+        let later = registry.detach("c2").await.unwrap();
+        assert_eq!(registry.expire("c2", generation).await, None);
+        assert!(registry.expire("c2", later).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_zero_buffer_size_disables_resumption() {
+        let registry = registry(0);
+        assert!(!registry.resumable());
+
+        let _ = open(&registry, "c1").await;
+        registry.detach("c1").await.unwrap();
+        registry.send("c1", "missed".into()).await;
+
+        let (sender, _receiver) = mpsc::unbounded_channel();
+        let (_, missed) = registry.attach("c1", sender).await.unwrap();
+        assert!(missed.is_empty());
+    }
+}
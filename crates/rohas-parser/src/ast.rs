@@ -24,55 +24,121 @@ impl Schema {
         }
     }
 
+    /// The `example:` value of the model, input or type named `name`.
+    pub fn example_for(&self, name: &str) -> Option<&serde_json::Value> {
+        self.models
+            .iter()
+            .find(|m| m.name == name)
+            .and_then(|m| m.example.as_ref())
+            .or_else(|| self.inputs.iter().find(|i| i.name == name).and_then(|i| i.example.as_ref()))
+            .or_else(|| self.types.iter().find(|t| t.name == name).and_then(|t| t.example.as_ref()))
+    }
+
     pub fn validate(&self) -> crate::Result<()> {
+        match self.validation_errors().into_iter().next() {
+            Some((_, error)) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// Every validation error of the schema, with the name of the definition
+    /// it is about.
+    pub fn validation_errors(&self) -> Vec<(&str, crate::ParseError)> {
+        let mut errors = Vec::new();
         let mut names = std::collections::HashSet::new();
 
-        for model in &self.models {
-            if !names.insert(&model.name) {
-                return Err(crate::ParseError::DuplicateDefinition(format!(
-                    "Model '{}'",
-                    model.name
-                )));
+        let definitions = self
+            .models
+            .iter()
+            .map(|m| ("Model", &m.name))
+            .chain(self.types.iter().map(|t| ("Type", &t.name)))
+            .chain(self.apis.iter().map(|a| ("API", &a.name)))
+            .chain(self.events.iter().map(|e| ("Event", &e.name)))
+            .chain(self.websockets.iter().map(|w| ("WebSocket", &w.name)));
+        for (kind, name) in definitions {
+            if !names.insert(name) {
+                errors.push((
+                    name.as_str(),
+                    crate::ParseError::DuplicateDefinition(format!("{} '{}'", kind, name)),
+                ));
             }
         }
 
-        for type_def in &self.types {
-            if !names.insert(&type_def.name) {
-                return Err(crate::ParseError::DuplicateDefinition(format!(
-                    "Type '{}'",
-                    type_def.name
-                )));
+        for model in &self.models {
+            for field in &model.fields {
+                let Some(computed) = field.attributes.iter().find(|a| a.name == "computed") else {
+                    continue;
+                };
+                if computed.args.len() != 1 || computed.args[0].trim().is_empty() {
+                    errors.push((
+                        model.name.as_str(),
+                        crate::ParseError::InvalidAttribute(format!(
+                            "@computed on '{}.{}' takes one expression",
+                            model.name, field.name
+                        )),
+                    ));
+                } else if let Some(other) = field.attributes.iter().find(|a| a.name == "default" || a.name == "id") {
+                    errors.push((
+                        model.name.as_str(),
+                        crate::ParseError::InvalidAttribute(format!(
+                            "'{}.{}' is computed and cannot also have @{}",
+                            model.name, field.name, other.name
+                        )),
+                    ));
+                }
             }
         }
 
-        for api in &self.apis {
-            if !names.insert(&api.name) {
-                return Err(crate::ParseError::DuplicateDefinition(format!(
-                    "API '{}'",
-                    api.name
-                )));
+        let inputs = self.inputs.iter().map(|i| (&i.name, &i.fields));
+        let types = self.types.iter().map(|t| (&t.name, &t.fields));
+        for (name, fields) in inputs.chain(types) {
+            if let Some(field) = fields.iter().find(|f| f.computed().is_some()) {
+                errors.push((
+                    name.as_str(),
+                    crate::ParseError::InvalidAttribute(format!(
+                        "'{}.{}': only model fields can be @computed",
+                        name, field.name
+                    )),
+                ));
             }
         }
 
-        for event in &self.events {
-            if !names.insert(&event.name) {
-                return Err(crate::ParseError::DuplicateDefinition(format!(
-                    "Event '{}'",
-                    event.name
-                )));
+        let examples = self
+            .models
+            .iter()
+            .map(|m| (&m.name, &m.fields, &m.example))
+            .chain(self.inputs.iter().map(|i| (&i.name, &i.fields, &i.example)))
+            .chain(self.types.iter().map(|t| (&t.name, &t.fields, &t.example)));
+        for (name, fields, example) in examples {
+            let Some(serde_json::Value::Object(example)) = example else {
+                continue;
+            };
+            if let Some(key) = example.keys().find(|key| !fields.iter().any(|f| &f.name == *key)) {
+                errors.push((
+                    name.as_str(),
+                    crate::ParseError::InvalidModel(format!(
+                        "Example of '{}' sets unknown field '{}'",
+                        name, key
+                    )),
+                ));
             }
         }
 
-        for websocket in &self.websockets {
-            if !names.insert(&websocket.name) {
-                return Err(crate::ParseError::DuplicateDefinition(format!(
-                    "WebSocket '{}'",
-                    websocket.name
-                )));
+        for event in &self.events {
+            if let Some(forward) = &event.forward_to {
+                if !self.websockets.iter().any(|ws| ws.name == forward.websocket) {
+                    errors.push((
+                        event.name.as_str(),
+                        crate::ParseError::UndefinedReference(format!(
+                            "WebSocket '{}' (forward_to of event '{}')",
+                            forward.websocket, event.name
+                        )),
+                    ));
+                }
             }
         }
 
-        Ok(())
+        errors
     }
 }
 
@@ -82,11 +148,61 @@ impl Default for Schema {
     }
 }
 
+/// Version of the JSON format written by [`Schema::to_json`]. It is raised
+/// only when documents of the previous version can no longer be read;
+/// fields added with a default keep it.
+pub const SCHEMA_JSON_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct SchemaDocument<'a> {
+    version: u32,
+    schema: &'a Schema,
+}
+
+impl Schema {
+    /// The schema as a versioned JSON document, for tools that consume
+    /// parsed schemas: `{"version": 1, "schema": {...}}`.
+    pub fn to_json(&self) -> String {
+        let document = SchemaDocument {
+            version: SCHEMA_JSON_VERSION,
+            schema: self,
+        };
+        serde_json::to_string_pretty(&document).expect("schema serializes to JSON")
+    }
+
+    /// Reads a document written by [`Schema::to_json`] of the same format
+    /// version.
+    pub fn from_json(json: &str) -> crate::Result<Self> {
+        #[derive(Deserialize)]
+        struct Document {
+            version: u32,
+            schema: serde_json::Value,
+        }
+
+        let document: Document = serde_json::from_str(json)
+            .map_err(|e| crate::ParseError::ParseError(format!("Invalid schema JSON: {}", e)))?;
+        if document.version != SCHEMA_JSON_VERSION {
+            return Err(crate::ParseError::ParseError(format!(
+                "Schema JSON version {} is not supported; expected {}",
+                document.version, SCHEMA_JSON_VERSION
+            )));
+        }
+        serde_json::from_value(document.schema)
+            .map_err(|e| crate::ParseError::ParseError(format!("Invalid schema JSON: {}", e)))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Model {
     pub name: String,
     pub fields: Vec<Field>,
     pub attributes: Vec<Attribute>,
+    /// Sample value from `example: { ... }`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub example: Option<serde_json::Value>,
+    /// Text of the `///` comments above the declaration.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -95,6 +211,21 @@ pub struct Field {
     pub field_type: FieldType,
     pub optional: bool,
     pub attributes: Vec<Attribute>,
+    /// Text of the `///` comments above the field.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+}
+
+impl Field {
+    /// Expression of a `@computed("...")` field, whose value the database
+    /// derives from other fields. Computed fields are read-only.
+    pub fn computed(&self) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|a| a.name == "computed")
+            .and_then(|a| a.args.first())
+            .map(String::as_str)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -105,6 +236,7 @@ pub enum FieldType {
     Float,
     DateTime,
     Json,
+    Bytes,
     Custom(String),
     Array(Box<FieldType>),
 }
@@ -118,6 +250,7 @@ impl FieldType {
             "Float" => FieldType::Float,
             "DateTime" => FieldType::DateTime,
             "Json" => FieldType::Json,
+            "Bytes" => FieldType::Bytes,
             _ => FieldType::Custom(s.to_string()),
         }
     }
@@ -129,6 +262,7 @@ impl FieldType {
             FieldType::Boolean => "boolean".to_string(),
             FieldType::DateTime => "Date".to_string(),
             FieldType::Json => "any".to_string(),
+            FieldType::Bytes => "Uint8Array".to_string(),
             FieldType::Custom(name) => name.clone(),
             FieldType::Array(inner) => format!("{}[]", inner.to_typescript()),
         }
@@ -142,6 +276,7 @@ impl FieldType {
             FieldType::Boolean => "bool".to_string(),
             FieldType::DateTime => "datetime".to_string(),
             FieldType::Json => "dict".to_string(),
+            FieldType::Bytes => "bytes".to_string(),
             FieldType::Custom(name) => name.clone(),
             FieldType::Array(inner) => format!("list[{}]", inner.to_python()),
         }
@@ -155,10 +290,39 @@ impl FieldType {
             FieldType::Boolean => "bool".to_string(),
             FieldType::DateTime => "chrono::DateTime<chrono::Utc>".to_string(),
             FieldType::Json => "serde_json::Value".to_string(),
+            FieldType::Bytes => "Vec<u8>".to_string(),
             FieldType::Custom(name) => name.clone(),
             FieldType::Array(inner) => format!("Vec<{}>", inner.to_rust()),
         }
     }
+
+    pub fn to_kotlin(&self) -> String {
+        match self {
+            FieldType::Int => "Long".to_string(),
+            FieldType::Float => "Double".to_string(),
+            FieldType::String => "String".to_string(),
+            FieldType::Boolean => "Boolean".to_string(),
+            FieldType::DateTime => "Instant".to_string(),
+            FieldType::Json => "JsonElement".to_string(),
+            FieldType::Bytes => "ByteArray".to_string(),
+            FieldType::Custom(name) => name.clone(),
+            FieldType::Array(inner) => format!("List<{}>", inner.to_kotlin()),
+        }
+    }
+
+    pub fn to_swift(&self) -> String {
+        match self {
+            FieldType::Int => "Int".to_string(),
+            FieldType::Float => "Double".to_string(),
+            FieldType::String => "String".to_string(),
+            FieldType::Boolean => "Bool".to_string(),
+            FieldType::DateTime => "Date".to_string(),
+            FieldType::Json => "JSONValue".to_string(),
+            FieldType::Bytes => "Data".to_string(),
+            FieldType::Custom(name) => name.clone(),
+            FieldType::Array(inner) => format!("[{}]", inner.to_swift()),
+        }
+    }
 }
 
 /// Attribute (e.g., @id, @unique, @default)
@@ -174,9 +338,127 @@ pub struct Api {
     pub method: HttpMethod,
     pub path: String,
     pub body: Option<String>,
+    /// Response type, or the item type of a paginated response.
     pub response: String,
+    /// Declared as `response: Page<T>`: the response is a page of `response`
+    /// items, `{ items, total, next_cursor }`, and the engine reads `limit`
+    /// and `cursor` from the query string.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub paginated: bool,
     pub triggers: Vec<String>,
     pub middlewares: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slo: Option<Slo>,
+    /// Media type of the request body (`consumes: "..."`).
+    #[serde(default, skip_serializing_if = "ContentType::is_json")]
+    pub consumes: ContentType,
+    /// Media type of the response body (`produces: "..."`).
+    #[serde(default, skip_serializing_if = "ContentType::is_json")]
+    pub produces: ContentType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub example: Option<ApiExample>,
+    /// Text of the `///` comments above the declaration.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+}
+
+/// Sample request body and response of an API (`example: { ... }`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ApiExample {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response: Option<serde_json::Value>,
+}
+
+impl Api {
+    /// The request body type handlers receive. Raw bodies are `Bytes` or
+    /// `String` regardless of the declared `body:` type.
+    pub fn body_type(&self) -> Option<String> {
+        match self.consumes {
+            ContentType::OctetStream => Some("Bytes".to_string()),
+            ContentType::Csv => Some("String".to_string()),
+            ContentType::Json | ContentType::MsgPack => self.body.clone(),
+        }
+    }
+
+    /// The response as declared, e.g. `Page<User>`.
+    pub fn response_declaration(&self) -> String {
+        if self.paginated {
+            format!("Page<{}>", self.response)
+        } else {
+            self.response.clone()
+        }
+    }
+
+    /// The response type handlers return, following the same rules as
+    /// [`Api::body_type`]. For paginated APIs it is the item type.
+    pub fn response_type(&self) -> &str {
+        match self.produces {
+            ContentType::OctetStream => "Bytes",
+            ContentType::Csv => "String",
+            ContentType::Json | ContentType::MsgPack => &self.response,
+        }
+    }
+}
+
+/// Media types an API can consume or produce. Structured types (JSON,
+/// MessagePack) are decoded into the declared schema types; raw types are
+/// passed to handlers as bytes or text.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentType {
+    #[default]
+    Json,
+    OctetStream,
+    Csv,
+    MsgPack,
+}
+
+impl ContentType {
+    pub fn from_mime(mime: &str) -> Option<Self> {
+        match mime.trim().to_lowercase().as_str() {
+            "application/json" => Some(ContentType::Json),
+            "application/octet-stream" => Some(ContentType::OctetStream),
+            "text/csv" => Some(ContentType::Csv),
+            "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack" => {
+                Some(ContentType::MsgPack)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn mime(&self) -> &'static str {
+        match self {
+            ContentType::Json => "application/json",
+            ContentType::OctetStream => "application/octet-stream",
+            ContentType::Csv => "text/csv",
+            ContentType::MsgPack => "application/msgpack",
+        }
+    }
+
+    pub fn is_json(&self) -> bool {
+        *self == ContentType::Json
+    }
+
+    /// Whether bodies of this type are decoded into schema types.
+    pub fn is_structured(&self) -> bool {
+        matches!(self, ContentType::Json | ContentType::MsgPack)
+    }
+}
+
+/// Service level objective declared on an API (`slo: { ... }`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Slo {
+    /// Target 99th percentile latency in milliseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_p99_ms: Option<u64>,
+    /// Target 95th percentile latency in milliseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_p95_ms: Option<u64>,
+    /// Target share of successful requests, in percent (e.g. `99.9`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub availability: Option<f64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -221,25 +503,181 @@ pub struct Event {
     pub triggers: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub adapter_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forward_to: Option<Forward>,
+    /// Most handlers run at once for this event (`concurrency:`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrency: Option<u32>,
+    /// Most messages the subscription holds at once, running or waiting
+    /// for a handler slot (`prefetch:`). At least `concurrency`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefetch: Option<u32>,
+    /// How failed handlers are retried (`retry:`); the engine's
+    /// `[quarantine]` settings apply when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryPolicy>,
+    /// Text of the `///` comments above the declaration.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+}
+
+/// Retries of an event's failed handlers (`retry: { ... }`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RetryPolicy {
+    /// Most times a handler is run again after failing (`max:`).
+    pub max_retries: u32,
+    pub backoff: Backoff,
+    /// Failures that are retried (`on:`); empty retries every failure.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub on: Vec<RetryCondition>,
+}
+
+impl RetryPolicy {
+    pub fn retries(&self, condition: RetryCondition) -> bool {
+        self.on.is_empty() || self.on.contains(&condition)
+    }
+}
+
+/// Wait before each retry (`backoff:`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Backoff {
+    /// `none`: retry right away.
+    None,
+    /// `fixed(1s)`
+    Fixed { delay_ms: u64 },
+    /// `exponential(1s, 2x, cap 5m)`: the delay is multiplied by `factor`
+    /// after each retry, up to `cap_ms`.
+    Exponential {
+        initial_ms: u64,
+        factor: f64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        cap_ms: Option<u64>,
+    },
+}
+
+impl Backoff {
+    /// Milliseconds to wait before retry number `retry`, counting from 1.
+    pub fn delay_ms(&self, retry: u32) -> u64 {
+        match self {
+            Backoff::None => 0,
+            Backoff::Fixed { delay_ms } => *delay_ms,
+            Backoff::Exponential {
+                initial_ms,
+                factor,
+                cap_ms,
+            } => {
+                let delay = *initial_ms as f64 * factor.powi(retry.saturating_sub(1) as i32);
+                let delay = if delay.is_finite() { delay.min(u64::MAX as f64) as u64 } else { u64::MAX };
+                cap_ms.map_or(delay, |cap| delay.min(cap))
+            }
+        }
+    }
+}
+
+/// A kind of handler failure a [`RetryPolicy`] can retry.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum RetryCondition {
+    /// The handler ran past its runtime's timeout.
+    Timeout,
+    /// The handler returned an error or could not be run.
+    Error,
+}
+
+impl RetryCondition {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "Timeout" => Some(RetryCondition::Timeout),
+            "Error" => Some(RetryCondition::Error),
+            _ => None,
+        }
+    }
+}
+
+/// Pushes an event's payload to the clients of a websocket
+/// (`forward_to:` and `forward_filter:`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Forward {
+    pub websocket: String,
+    /// [`crate::Filter`] source, checked per connection.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Cron {
     pub name: String,
+    /// Cron expression, interval (`30s`) or RFC 3339 time, per `kind`.
     pub schedule: String,
+    #[serde(default, skip_serializing_if = "ScheduleKind::is_cron")]
+    pub kind: ScheduleKind,
+    #[serde(default)]
+    pub overlap: OverlapPolicy,
+    #[serde(default)]
+    pub misfire: MisfirePolicy,
     pub triggers: Vec<String>,
+    /// Text of the `///` comments above the declaration.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+}
+
+/// Which of `schedule:`, `every:` or `at:` a cron job was declared with.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ScheduleKind {
+    #[default]
+    Cron,
+    Every,
+    At,
+}
+
+impl ScheduleKind {
+    pub fn is_cron(&self) -> bool {
+        *self == ScheduleKind::Cron
+    }
+}
+
+/// `overlap:` of a cron job: what happens when a run is due while the
+/// previous one is still going.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OverlapPolicy {
+    #[default]
+    Skip,
+    Queue,
+    Concurrent,
+}
+
+/// `misfire:` of a cron job: what happens to runs missed while the engine
+/// was down.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MisfirePolicy {
+    RunOnce,
+    #[default]
+    Ignore,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Type {
     pub name: String,
     pub fields: Vec<Field>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub example: Option<serde_json::Value>,
+    /// Text of the `///` comments above the declaration.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Input {
     pub name: String,
     pub fields: Vec<Field>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub example: Option<serde_json::Value>,
+    /// Text of the `///` comments above the declaration.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -253,6 +691,58 @@ pub struct WebSocket {
     pub triggers: Vec<String>,
     pub broadcast: bool,
     pub middlewares: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<WsAuth>,
+    /// Text of the `///` comments above the declaration.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+}
+
+impl WebSocket {
+    /// Middlewares followed by the auth handler, which is implemented and
+    /// registered like a middleware.
+    pub fn middleware_handlers(&self) -> impl Iterator<Item = &String> {
+        self.middlewares
+            .iter()
+            .chain(self.auth.as_ref().map(|auth| &auth.handler))
+    }
+}
+
+/// Authentication checked when a websocket connection is upgraded
+/// (`auth: { handler: ..., from: query, name: "token" }`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WsAuth {
+    /// Middleware that validates the token and returns the principal.
+    pub handler: String,
+    /// Where the token is read from.
+    pub from: TokenSource,
+    /// Query parameter or header carrying the token.
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenSource {
+    Query,
+    Header,
+}
+
+impl TokenSource {
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s {
+            "query" => Some(TokenSource::Query),
+            "header" => Some(TokenSource::Header),
+            _ => None,
+        }
+    }
+
+    /// Parameter or header read when the schema names none.
+    pub fn default_name(self) -> &'static str {
+        match self {
+            TokenSource::Query => "token",
+            TokenSource::Header => "authorization",
+        }
+    }
 }
 
 #[cfg(test)]
@@ -288,6 +778,8 @@ mod tests {
             name: "User".to_string(),
             fields: vec![],
             attributes: vec![],
+            example: None,
+            doc: None,
         });
 
         assert!(schema.validate().is_ok());
@@ -297,6 +789,8 @@ mod tests {
             name: "User".to_string(),
             fields: vec![],
             attributes: vec![],
+            example: None,
+            doc: None,
         });
 
         assert!(schema.validate().is_err());
@@ -0,0 +1,323 @@
+//! Positioned problems of a schema file, as reported by
+//! [`Parser::parse_with_diagnostics`](crate::Parser::parse_with_diagnostics).
+//!
+//! A syntax error stops pest at the first problem, so a file that fails to
+//! parse is split at its top-level definitions and each one is parsed on its
+//! own. Every part is padded to keep the positions it has in the file.
+
+use crate::grammar::Rule;
+use pest::iterators::Pair;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const KEYWORDS: &[&str] = &["model", "type", "api", "event", "cron", "input", "ws", "const", "group", "when"];
+
+/// A problem in a schema file. Lines and columns are 1-based; the end is
+/// just past the last character of the span.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+    pub message: String,
+    /// What the text at the position was probably meant to be.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, " (did you mean `{}`?)", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+impl Diagnostic {
+    /// The name of a top-level definition, and a diagnostic spanning its
+    /// keyword and name.
+    pub(crate) fn header(pair: &Pair<Rule>) -> (String, Self) {
+        let span = pair.as_span();
+        let name = pair.clone().into_inner().next().map(|name| name.as_span()).unwrap_or(span);
+        let (line, column) = span.start_pos().line_col();
+        let (end_line, end_column) = name.end_pos().line_col();
+        let diagnostic = Self {
+            line,
+            column,
+            end_line,
+            end_column,
+            message: String::new(),
+            suggestion: None,
+        };
+        (name.as_str().to_string(), diagnostic)
+    }
+
+    pub(crate) fn with_message(mut self, message: String) -> Self {
+        self.message = message;
+        self
+    }
+
+    /// A syntax error of `input`, spanning the word it points at.
+    pub(crate) fn from_pest(err: pest::error::Error<Rule>, input: &str) -> Self {
+        let err = err.renamed_rules(describe);
+        let message = err.variant.message().into_owned();
+        match err.line_col {
+            pest::error::LineColLocation::Span((line, column), (end_line, end_column)) => Self {
+                line,
+                column,
+                end_line,
+                end_column,
+                message,
+                suggestion: None,
+            },
+            pest::error::LineColLocation::Pos((line, column)) => {
+                let word: String = err
+                    .line()
+                    .chars()
+                    .skip(column - 1)
+                    .take_while(|c| c.is_ascii_alphanumeric() || *c == '_')
+                    .collect();
+                let width = if word.is_empty() {
+                    usize::from(err.line().chars().nth(column - 1).is_some_and(|c| c != '\n'))
+                } else {
+                    word.chars().count()
+                };
+                Self {
+                    line,
+                    column,
+                    end_line: line,
+                    end_column: column + width,
+                    message,
+                    suggestion: suggest(&word, definition_kind(input)),
+                }
+            }
+        }
+    }
+}
+
+fn describe(rule: &Rule) -> String {
+    match rule {
+        Rule::EOI => "end of file",
+        Rule::ident => "a name",
+        Rule::string => "a string",
+        Rule::number | Rule::decimal => "a number",
+        Rule::boolean => "true or false",
+        Rule::duration => "a duration",
+        Rule::model
+        | Rule::type_def
+        | Rule::api
+        | Rule::event
+        | Rule::cron
+        | Rule::input
+        | Rule::ws
+        | Rule::const_def
+        | Rule::attr_group
+        | Rule::when_block => "a definition",
+        Rule::field | Rule::input_field => "a field",
+        Rule::field_type => "a type",
+        Rule::attribute => "an attribute",
+        Rule::api_property => "an API property",
+        Rule::event_property => "an event property",
+        Rule::cron_property => "a cron property",
+        Rule::ws_property => "a WebSocket property",
+        Rule::http_method => "an HTTP method",
+        Rule::json_value => "a JSON value",
+        _ => return format!("{:?}", rule),
+    }
+    .to_string()
+}
+
+/// Keyword of the first definition in `text`, inside a `when` block if it
+/// starts with one.
+fn definition_kind(text: &str) -> Option<&str> {
+    text.lines()
+        .map(str::trim_start)
+        .filter(|line| !line.is_empty() && !line.starts_with("//") && !line.starts_with("when"))
+        .find_map(|line| line.split_whitespace().next().filter(|word| KEYWORDS.contains(word)))
+}
+
+fn properties(kind: &str) -> &'static [&'static str] {
+    match kind {
+        "api" => &[
+            "method", "path", "body", "response", "triggers", "middlewares", "slo", "consumes", "produces",
+            "example",
+        ],
+        "event" => &[
+            "payload", "handler", "triggers", "type", "forward_to", "forward_filter", "concurrency", "prefetch",
+            "retry",
+        ],
+        "cron" => &["schedule", "every", "at", "overlap", "misfire", "triggers"],
+        "ws" => &[
+            "path", "message", "onConnect", "onMessage", "onDisconnect", "triggers", "broadcast", "middlewares",
+            "auth",
+        ],
+        "model" | "type" | "input" => &["example"],
+        _ => &[],
+    }
+}
+
+/// The keyword or property of `kind` closest to `word`, if one is close
+/// enough to be a typo of it.
+fn suggest(word: &str, kind: Option<&str>) -> Option<String> {
+    if word.is_empty() {
+        return None;
+    }
+    let max_distance = (word.chars().count() / 3).max(1);
+    KEYWORDS
+        .iter()
+        .chain(kind.map(properties).unwrap_or_default())
+        .filter(|candidate| **candidate != word)
+        .map(|candidate| (edit_distance(word, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.to_string())
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// Splits `input` before each top-level definition. Each part is padded with
+/// the lines before it, blanked except for comments, so positions and doc
+/// comments are those of the whole file.
+pub(crate) fn split_definitions(input: &str) -> Vec<String> {
+    let mut starts = Vec::new();
+    let mut in_block_comment = false;
+    // Brace depth inside a `when` block, whose definitions stay in its part,
+    // and whether its opening brace was seen.
+    let mut when_block: Option<(usize, bool)> = None;
+    let mut offset = 0;
+    for line in input.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if when_block.is_none()
+            && !in_block_comment
+            && starts_definition(trimmed, &input[offset + line.len() - trimmed.len()..])
+        {
+            starts.push(offset);
+            if trimmed.starts_with("when") {
+                when_block = Some((0, false));
+            }
+        }
+        if let Some((depth, opened)) = when_block.as_mut() {
+            let (opening, closing) = count_braces(line);
+            *opened |= opening > 0;
+            *depth = (*depth + opening).saturating_sub(closing);
+            if *opened && *depth == 0 {
+                when_block = None;
+            }
+        }
+        if in_block_comment {
+            in_block_comment = !line.contains("*/");
+        } else if let Some(open) = line.find("/*") {
+            in_block_comment = !line[open..].contains("*/");
+        }
+        offset += line.len();
+    }
+
+    if starts.first() != Some(&0) {
+        starts.insert(0, 0);
+    }
+    starts
+        .iter()
+        .zip(starts.iter().skip(1).copied().chain(Some(input.len())))
+        .map(|(&start, end)| {
+            let mut part: String = input[..start]
+                .split_inclusive('\n')
+                .map(|line| if line.trim_start().starts_with("//") { line } else { "\n" })
+                .collect();
+            part.push_str(&input[start..end]);
+            part
+        })
+        .collect()
+}
+
+/// Opening and closing braces of `line`, outside strings and comments.
+fn count_braces(line: &str) -> (usize, usize) {
+    let (mut opening, mut closing) = (0, 0);
+    let mut in_string = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => in_string = !in_string,
+            '/' if !in_string && chars.peek() == Some(&'/') => break,
+            '{' if !in_string => opening += 1,
+            '}' if !in_string => closing += 1,
+            _ => {}
+        }
+    }
+    (opening, closing)
+}
+
+/// Whether `rest`, which starts with `line`, starts with a definition's
+/// keyword, name and opening brace, a constant's `=`, or a `when` block.
+fn starts_definition(line: &str, rest: &str) -> bool {
+    let Some(keyword) = KEYWORDS.iter().find(|keyword| line.starts_with(**keyword)) else {
+        return false;
+    };
+    let after_keyword = &rest[keyword.len()..];
+    let name = after_keyword.trim_start_matches([' ', '\t']);
+    if name.len() == after_keyword.len() || !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return false;
+    }
+    let after_name = name.trim_start_matches(|c: char| c.is_ascii_alphanumeric() || c == '_');
+    let after_name = after_name.trim_start();
+    match *keyword {
+        "const" => after_name.starts_with('='),
+        "when" => after_name.starts_with("==") || after_name.starts_with("!="),
+        _ => after_name.starts_with('{'),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_definitions_keeps_positions() {
+        let input = "/// A user\nmodel User {\n  id Int\n}\n\napi Health {\n  method: GET\n}\n";
+        let parts = split_definitions(input);
+
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[0], "/// A user\n");
+        assert_eq!(parts[1], "/// A user\nmodel User {\n  id Int\n}\n\n");
+        assert_eq!(parts[2], "/// A user\n\n\n\n\napi Health {\n  method: GET\n}\n");
+    }
+
+    #[test]
+    fn test_split_definitions_keeps_when_blocks() {
+        let input = "when env == \"production\" {\n  cron Report {\n    schedule: \"0 0 * * * *\"\n  }\n}\nws Chat {\n}\n";
+        let parts = split_definitions(input);
+
+        assert_eq!(parts.len(), 2);
+        assert!(parts[0].ends_with("  }\n}\n"));
+        assert!(parts[1].ends_with("ws Chat {\n}\n"));
+    }
+
+    #[test]
+    fn test_suggest() {
+        assert_eq!(suggest("methd", Some("api")), Some("method".to_string()));
+        assert_eq!(suggest("modle", None), Some("model".to_string()));
+        assert_eq!(suggest("schedule", Some("api")), None);
+    }
+}
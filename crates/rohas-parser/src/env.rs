@@ -0,0 +1,202 @@
+//! `${NAME}` references to environment variables in schema strings, e.g.
+//! `@default("${DEFAULT_REGION}")` or `schedule: "${REPORT_SCHEDULE}"`.
+//!
+//! `${NAME:-fallback}` uses `fallback` when the variable is unset, and `$${`
+//! is a literal `${`. Attribute arguments, cron schedules and forward filters
+//! are interpolated. The parser keeps references as written; the engine
+//! resolves them when it loads the schema, so one schema serves every
+//! environment.
+//!
+//! `when env == "production" { ... }` blocks are instead evaluated when the
+//! schema is parsed, against the environment the caller passes in. Loaders
+//! pass [`active_environment`]; the parser itself never reads `ROHAS_ENV`.
+
+use crate::ast::Schema;
+use crate::error::{ParseError, Result};
+
+impl Schema {
+    /// Names of the variables the schema references, in order of appearance.
+    pub fn env_vars(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        self.clone().visit_interpolated(&mut |_, value| {
+            interpolate(value, &mut |name| {
+                if !names.iter().any(|n| n == name) {
+                    names.push(name.to_string());
+                }
+                None
+            });
+        });
+        names
+    }
+
+    /// Replaces `${NAME}` references with values from `lookup`. Fails with
+    /// every unset variable that has no fallback and where it is used.
+    pub fn resolve_env(&mut self, lookup: impl Fn(&str) -> Option<String>) -> Result<()> {
+        let mut missing = Vec::new();
+        self.visit_interpolated(&mut |location, value| {
+            let (resolved, unset) = interpolate(value, &mut |name| lookup(name));
+            missing.extend(unset.into_iter().map(|name| format!("{} ({})", name, location)));
+            *value = resolved;
+        });
+
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(ParseError::MissingEnvVar(missing.join(", ")))
+        }
+    }
+
+    /// Resolves references from the process environment.
+    pub fn resolve_process_env(&mut self) -> Result<()> {
+        self.resolve_env(|name| std::env::var(name).ok())
+    }
+
+    fn visit_interpolated(&mut self, visit: &mut impl FnMut(&str, &mut String)) {
+        for model in &mut self.models {
+            for attribute in &mut model.attributes {
+                let location = format!("model {} @{}", model.name, attribute.name);
+                for arg in &mut attribute.args {
+                    visit(&location, arg);
+                }
+            }
+            for field in &mut model.fields {
+                for attribute in &mut field.attributes {
+                    let location = format!("{}.{} @{}", model.name, field.name, attribute.name);
+                    for arg in &mut attribute.args {
+                        visit(&location, arg);
+                    }
+                }
+            }
+        }
+        for cron in &mut self.crons {
+            visit(&format!("cron {} schedule", cron.name), &mut cron.schedule);
+        }
+        for event in &mut self.events {
+            if let Some(filter) = event.forward_to.as_mut().and_then(|f| f.filter.as_mut()) {
+                visit(&format!("event {} forward_filter", event.name), filter);
+            }
+        }
+    }
+}
+
+/// Variable naming the environment that `when env` blocks are evaluated
+/// against.
+pub const ENVIRONMENT_VAR: &str = "ROHAS_ENV";
+
+/// Environment of [`Parser::parse_string`](crate::Parser::parse_string) and
+/// [`Parser::parse_file`](crate::Parser::parse_file), and of
+/// [`active_environment`] when `ROHAS_ENV` is unset.
+pub const DEFAULT_ENVIRONMENT: &str = "development";
+
+/// The environment the CLI and engine load schemas for: `ROHAS_ENV`, else
+/// [`DEFAULT_ENVIRONMENT`].
+pub fn active_environment() -> String {
+    std::env::var(ENVIRONMENT_VAR)
+        .ok()
+        .filter(|environment| !environment.is_empty())
+        .unwrap_or_else(|| DEFAULT_ENVIRONMENT.to_string())
+}
+
+/// Expands the references in `value`, returning the result and the names
+/// that `lookup` could not resolve and that have no fallback.
+fn interpolate(
+    value: &str,
+    lookup: &mut impl FnMut(&str) -> Option<String>,
+) -> (String, Vec<String>) {
+    let mut out = String::with_capacity(value.len());
+    let mut missing = Vec::new();
+    let mut rest = value;
+
+    while let Some(start) = rest.find('$') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start..];
+
+        if let Some(tail) = after.strip_prefix("$${") {
+            out.push_str("${");
+            rest = tail;
+            continue;
+        }
+
+        let reference = after
+            .strip_prefix("${")
+            .and_then(|body| body.split_once('}'));
+        let Some((body, tail)) = reference else {
+            out.push('$');
+            rest = &after[1..];
+            continue;
+        };
+
+        let (name, fallback) = match body.split_once(":-") {
+            Some((name, fallback)) => (name, Some(fallback)),
+            None => (body, None),
+        };
+        match (lookup(name), fallback) {
+            (Some(found), _) => out.push_str(&found),
+            (None, Some(fallback)) => out.push_str(fallback),
+            (None, None) => missing.push(name.to_string()),
+        }
+        rest = tail;
+    }
+
+    out.push_str(rest);
+    (out, missing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parser;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "REGION" => Some("eu-west-1".to_string()),
+            "SCHEDULE" => Some("0 0 * * * *".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let mut lookup = lookup;
+        assert_eq!(interpolate("s3://${REGION}/x", &mut lookup), ("s3://eu-west-1/x".to_string(), vec![]));
+        assert_eq!(interpolate("${MISSING:-us-east-1}", &mut lookup), ("us-east-1".to_string(), vec![]));
+        assert_eq!(interpolate("${MISSING:-}", &mut lookup), (String::new(), vec![]));
+        assert_eq!(interpolate("$${REGION} costs $5", &mut lookup), ("${REGION} costs $5".to_string(), vec![]));
+        assert_eq!(interpolate("${REGION", &mut lookup), ("${REGION".to_string(), vec![]));
+        assert_eq!(
+            interpolate("${A}-${B}", &mut lookup),
+            ("-".to_string(), vec!["A".to_string(), "B".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_resolve_schema_env() {
+        let input = r#"
+            model Upload {
+                id Int @id
+                region String @default("${REGION}")
+                bucket String @default("${BUCKET}")
+            }
+
+            cron Report {
+                schedule: "${SCHEDULE}"
+            }
+        "#;
+        let schema = Parser::parse_string(input).unwrap();
+        assert_eq!(schema.env_vars(), vec!["REGION", "BUCKET", "SCHEDULE"]);
+
+        let err = schema.clone().resolve_env(lookup).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Missing environment variable: BUCKET (Upload.bucket @default)"
+        );
+
+        let mut resolved = schema.clone();
+        resolved
+            .resolve_env(|name| lookup(name).or_else(|| (name == "BUCKET").then(|| "uploads".to_string())))
+            .unwrap();
+        assert_eq!(resolved.models[0].fields[1].attributes[0].args, vec!["eu-west-1"]);
+        assert_eq!(resolved.models[0].fields[2].attributes[0].args, vec!["uploads"]);
+        assert_eq!(resolved.crons[0].schedule, "0 0 * * * *");
+    }
+}
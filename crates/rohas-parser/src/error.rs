@@ -35,6 +35,9 @@ pub enum ParseError {
     #[error("Undefined reference: {0}")]
     UndefinedReference(String),
 
+    #[error("Missing environment variable: {0}")]
+    MissingEnvVar(String),
+
     #[error("File not found: {0}")]
     FileNotFound(String),
 
@@ -0,0 +1,336 @@
+//! Filter expressions used by `forward_filter:`, e.g.
+//! `payload.status == 'shipped' && payload.customer_id == connection.principal.id`.
+//!
+//! Paths start at `payload` (the event payload) or `connection` (the
+//! websocket connection the event would be pushed to). Comparisons combine
+//! with `&&`, `||`, `!` and parentheses. A bare path is true unless it is
+//! missing, `null`, `false`, `0` or `""`.
+
+use serde_json::Value;
+use std::cmp::Ordering;
+
+const ROOTS: [&str; 2] = ["payload", "connection"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    expr: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Operand, CompareOp, Operand),
+    Truthy(Operand),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Operand {
+    Path(Vec<String>),
+    Literal(Value),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Dot,
+    Str(String),
+    Number(f64),
+    Op(CompareOp),
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+}
+
+impl Filter {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let tokens = tokenize(source)?;
+        let mut parser = ExprParser { tokens, pos: 0 };
+        let expr = parser.or()?;
+        if parser.pos < parser.tokens.len() {
+            return Err(format!("Unexpected {:?} in filter", parser.tokens[parser.pos]));
+        }
+        Ok(Self { expr })
+    }
+
+    /// Evaluates the filter against `{ "payload": ..., "connection": ... }`.
+    pub fn matches(&self, scope: &Value) -> bool {
+        self.expr.eval(scope)
+    }
+}
+
+impl Expr {
+    fn eval(&self, scope: &Value) -> bool {
+        match self {
+            Expr::Or(a, b) => a.eval(scope) || b.eval(scope),
+            Expr::And(a, b) => a.eval(scope) && b.eval(scope),
+            Expr::Not(e) => !e.eval(scope),
+            Expr::Compare(left, op, right) => compare(&left.resolve(scope), *op, &right.resolve(scope)),
+            Expr::Truthy(operand) => match operand.resolve(scope) {
+                Value::Null | Value::Bool(false) => false,
+                Value::Number(n) => n.as_f64() != Some(0.0),
+                Value::String(s) => !s.is_empty(),
+                _ => true,
+            },
+        }
+    }
+}
+
+impl Operand {
+    fn resolve(&self, scope: &Value) -> Value {
+        match self {
+            Operand::Literal(value) => value.clone(),
+            Operand::Path(path) => path
+                .iter()
+                .try_fold(scope, |value, key| value.get(key))
+                .cloned()
+                .unwrap_or(Value::Null),
+        }
+    }
+}
+
+fn compare(left: &Value, op: CompareOp, right: &Value) -> bool {
+    let ordering = match (left, right) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64().and_then(|a| b.as_f64().and_then(|b| a.partial_cmp(&b))),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        _ => None,
+    };
+
+    match op {
+        CompareOp::Eq => ordering.map_or(left == right, |o| o == Ordering::Equal),
+        CompareOp::Ne => ordering.map_or(left != right, |o| o != Ordering::Equal),
+        CompareOp::Lt => ordering == Some(Ordering::Less),
+        CompareOp::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+        CompareOp::Gt => ordering == Some(Ordering::Greater),
+        CompareOp::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        match c {
+            ' ' | '\t' | '\n' | '\r' => i += 1,
+            '.' => {
+                tokens.push(Token::Dot);
+                i += 1;
+            }
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            }
+            '&' if next == Some('&') => {
+                tokens.push(Token::And);
+                i += 2;
+            }
+            '|' if next == Some('|') => {
+                tokens.push(Token::Or);
+                i += 2;
+            }
+            '=' if next == Some('=') => {
+                tokens.push(Token::Op(CompareOp::Eq));
+                i += 2;
+            }
+            '!' if next == Some('=') => {
+                tokens.push(Token::Op(CompareOp::Ne));
+                i += 2;
+            }
+            '!' => {
+                tokens.push(Token::Not);
+                i += 1;
+            }
+            '<' | '>' => {
+                let op = match (c, next == Some('=')) {
+                    ('<', false) => CompareOp::Lt,
+                    ('<', true) => CompareOp::Le,
+                    ('>', false) => CompareOp::Gt,
+                    _ => CompareOp::Ge,
+                };
+                tokens.push(Token::Op(op));
+                i += if next == Some('=') { 2 } else { 1 };
+            }
+            '\'' | '"' => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&ch| ch == c)
+                    .ok_or_else(|| "Unterminated string in filter".to_string())?;
+                tokens.push(Token::Str(chars[i + 1..i + 1 + end].iter().collect()));
+                i += end + 2;
+            }
+            c if c.is_ascii_digit() || (c == '-' && next.is_some_and(|n| n.is_ascii_digit())) => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                let number = text
+                    .parse::<f64>()
+                    .map_err(|_| format!("Invalid number '{}' in filter", text))?;
+                tokens.push(Token::Number(number));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                tokens.push(Token::Ident(chars[start..i].iter().collect()));
+            }
+            other => return Err(format!("Unexpected character '{}' in filter", other)),
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct ExprParser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl ExprParser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        match self.peek() {
+            Some(Token::Not) => {
+                self.pos += 1;
+                Ok(Expr::Not(Box::new(self.unary()?)))
+            }
+            Some(Token::LParen) => {
+                self.pos += 1;
+                let expr = self.or()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(expr),
+                    _ => Err("Missing ')' in filter".to_string()),
+                }
+            }
+            _ => self.comparison(),
+        }
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        let left = self.operand()?;
+        if let Some(Token::Op(op)) = self.peek().cloned() {
+            self.pos += 1;
+            let right = self.operand()?;
+            return Ok(Expr::Compare(left, op, right));
+        }
+        Ok(Expr::Truthy(left))
+    }
+
+    fn operand(&mut self) -> Result<Operand, String> {
+        match self.next() {
+            Some(Token::Str(s)) => Ok(Operand::Literal(Value::String(s))),
+            Some(Token::Number(n)) => Ok(Operand::Literal(
+                serde_json::Number::from_f64(n).map(Value::Number).unwrap_or(Value::Null),
+            )),
+            Some(Token::Ident(ident)) => match ident.as_str() {
+                "true" => Ok(Operand::Literal(Value::Bool(true))),
+                "false" => Ok(Operand::Literal(Value::Bool(false))),
+                "null" => Ok(Operand::Literal(Value::Null)),
+                _ => {
+                    if !ROOTS.contains(&ident.as_str()) {
+                        return Err(format!(
+                            "Filter path '{}' must start with payload or connection",
+                            ident
+                        ));
+                    }
+                    let mut path = vec![ident];
+                    while self.peek() == Some(&Token::Dot) {
+                        self.pos += 1;
+                        match self.next() {
+                            Some(Token::Ident(key)) => path.push(key),
+                            _ => return Err("Expected a field name after '.' in filter".to_string()),
+                        }
+                    }
+                    Ok(Operand::Path(path))
+                }
+            },
+            Some(token) => Err(format!("Unexpected {:?} in filter", token)),
+            None => Err("Unexpected end of filter".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_filter_matches() {
+        let scope = json!({
+            "payload": { "status": "shipped", "total": 120, "customer_id": "c-1" },
+            "connection": { "principal": { "id": "c-1" } },
+        });
+
+        let matching = [
+            "payload.status == 'shipped'",
+            "payload.total >= 100 && payload.total < 200.5",
+            "payload.customer_id == connection.principal.id",
+            "!(payload.status == \"pending\") || payload.total > 1000",
+            "payload.customer_id",
+        ];
+        for source in matching {
+            assert!(Filter::parse(source).unwrap().matches(&scope), "{}", source);
+        }
+
+        let rejecting = ["payload.status != 'shipped'", "payload.missing", "payload.status > 3"];
+        for source in rejecting {
+            assert!(!Filter::parse(source).unwrap().matches(&scope), "{}", source);
+        }
+
+        assert!(Filter::parse("status == 'shipped'").is_err());
+        assert!(Filter::parse("payload.status ==").is_err());
+        assert!(Filter::parse("(payload.total > 1").is_err());
+    }
+}
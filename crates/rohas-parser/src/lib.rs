@@ -1,10 +1,31 @@
+//! Parser for Rohas schema files (`.ro`).
+//!
+//! [`Parser`] reads schema files into a [`Schema`], whose types are the
+//! public AST used by codegen, the engine and external tools such as linters
+//! and CI checks. This crate only depends on the parsing and serialization
+//! libraries, so tools can use it without the rest of the workspace.
+//!
+//! # Stability
+//!
+//! The AST follows semver: within a minor version, fields and variants are
+//! only added, and are added with a serde default so that
+//! [`Schema::from_json`] reads older documents. Renaming or removing them
+//! is a breaking change. [`Schema::to_json`] writes a document tagged with
+//! [`SCHEMA_JSON_VERSION`], which is raised when a document of the previous
+//! version can no longer be read.
+
 pub mod ast;
+pub mod diagnostic;
+pub mod env;
 pub mod error;
+pub mod filter;
 pub mod grammar;
 pub mod parser;
 
 pub use ast::*;
+pub use diagnostic::Diagnostic;
 pub use error::{ParseError, Result};
+pub use filter::Filter;
 pub use parser::Parser;
 
 #[cfg(test)]
@@ -1,74 +1,288 @@
 use crate::ast::*;
+use crate::diagnostic::{self, Diagnostic};
 use crate::error::{ParseError, Result};
+use crate::filter::Filter;
 use crate::grammar::{RohasParser, Rule};
+use pest::iterators::{Pair, Pairs};
 use pest::Parser as PestParser;
+use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tracing::{debug, info};
 
 pub struct Parser;
 
 impl Parser {
+    /// Parses the file at `path` for the
+    /// [default environment](crate::env::DEFAULT_ENVIRONMENT).
     pub fn parse_file<P: AsRef<Path>>(path: P) -> Result<Schema> {
+        Self::parse_file_for_environment(path, crate::env::DEFAULT_ENVIRONMENT)
+    }
+
+    /// Parses the file at `path`, keeping the `when env` blocks that match
+    /// `environment`.
+    pub fn parse_file_for_environment<P: AsRef<Path>>(path: P, environment: &str) -> Result<Schema> {
         let path = path.as_ref();
         info!("Parsing schema file: {}", path.display());
 
         let content = fs::read_to_string(path)
             .map_err(|e| ParseError::FileNotFound(format!("{}: {}", path.display(), e)))?;
 
-        Self::parse_string(&content)
+        Self::parse_for_environment(&content, environment)
     }
 
+    /// Parses `input` for the [default environment](crate::env::DEFAULT_ENVIRONMENT).
     pub fn parse_string(input: &str) -> Result<Schema> {
+        Self::parse_for_environment(input, crate::env::DEFAULT_ENVIRONMENT)
+    }
+
+    /// Parses `input`, keeping the `when env` blocks that match `environment`.
+    pub fn parse_for_environment(input: &str, environment: &str) -> Result<Schema> {
         let pairs = RohasParser::parse(Rule::schema, input)?;
         let mut schema = Schema::new();
+        let mut declarations = Declarations::default();
 
-        for pair in pairs {
-            if pair.as_rule() == Rule::schema {
-                for inner_pair in pair.into_inner() {
-                    match inner_pair.as_rule() {
-                        Rule::model => {
-                            let model = Self::parse_model(inner_pair)?;
-                            schema.models.push(model);
-                        }
-                        Rule::type_def => {
-                            let type_def = Self::parse_type(inner_pair)?;
-                            schema.types.push(type_def);
-                        }
-                        Rule::api => {
-                            let api = Self::parse_api(inner_pair)?;
-                            schema.apis.push(api);
-                        }
-                        Rule::event => {
-                            let event = Self::parse_event(inner_pair)?;
-                            schema.events.push(event);
-                        }
-                        Rule::cron => {
-                            let cron = Self::parse_cron(inner_pair)?;
-                            schema.crons.push(cron);
-                        }
-                        Rule::input => {
-                            let input = Self::parse_input(inner_pair)?;
-                            schema.inputs.push(input);
-                        }
-                        Rule::ws => {
-                            let ws = Self::parse_websocket(inner_pair)?;
-                            schema.websockets.push(ws);
-                        }
-                        Rule::EOI => {}
-                        _ => {
-                            debug!("Unexpected rule: {:?}", inner_pair.as_rule());
-                        }
-                    }
-                }
+        for pair in Self::definitions(pairs, environment) {
+            Self::add_definition(&mut schema, &mut declarations, pair)?;
+        }
+        declarations.resolve()?;
+        for model in &mut schema.models {
+            declarations.expand(model)?;
+        }
+
+        schema.validate()?;
+        Ok(schema)
+    }
+
+    /// Parses the files at `paths` as one schema, keeping the `when env`
+    /// blocks that match `environment`. Constants and attribute groups
+    /// declared in any of the files apply to all of them.
+    pub fn parse_files_for_environment<P: AsRef<Path>>(paths: &[P], environment: &str) -> Result<Schema> {
+        let mut sources = Vec::with_capacity(paths.len());
+        for path in paths {
+            let path = path.as_ref();
+            info!("Parsing schema file: {}", path.display());
+            let content = fs::read_to_string(path)
+                .map_err(|e| ParseError::FileNotFound(format!("{}: {}", path.display(), e)))?;
+            sources.push((path, content));
+        }
+
+        let mut schema = Schema::new();
+        let mut declarations = Declarations::default();
+        for (path, content) in &sources {
+            let in_file = |e: ParseError| ParseError::ParseError(format!("{}: {}", path.display(), e));
+            let pairs = RohasParser::parse(Rule::schema, content).map_err(|e| in_file(e.into()))?;
+            declarations.file = Some(path.to_path_buf());
+            for pair in Self::definitions(pairs, environment) {
+                Self::add_definition(&mut schema, &mut declarations, pair).map_err(in_file)?;
             }
         }
+        declarations.resolve()?;
+        for model in &mut schema.models {
+            declarations.expand(model)?;
+        }
 
         schema.validate()?;
         Ok(schema)
     }
 
+    /// Parses `input` and reports every problem in it instead of stopping at
+    /// the first. Parsing recovers at each top-level definition, so the
+    /// returned schema has the definitions without syntax errors. `when env`
+    /// blocks are kept when they match `environment`.
+    pub fn parse_with_diagnostics(input: &str, environment: &str) -> (Schema, Vec<Diagnostic>) {
+        Self::diagnose(input, environment, true)
+    }
+
+    /// Like [`Parser::parse_with_diagnostics`], for one file of a schema
+    /// split across files: references to definitions that are not in
+    /// `input` are left to the check of the whole schema, so only syntax
+    /// and declaration problems are reported.
+    pub fn parse_part_with_diagnostics(input: &str, environment: &str) -> (Schema, Vec<Diagnostic>) {
+        Self::diagnose(input, environment, false)
+    }
+
+    fn diagnose(input: &str, environment: &str, references: bool) -> (Schema, Vec<Diagnostic>) {
+        let mut schema = Schema::new();
+        let mut declarations = Declarations::default();
+        let mut diagnostics = Vec::new();
+        // Position of each definition's name, for validation errors.
+        let mut headers = Vec::new();
+
+        let mut parse_part = |text: &str, diagnostics: &mut Vec<Diagnostic>| match RohasParser::parse(Rule::schema, text) {
+            Ok(pairs) => {
+                for pair in Self::definitions(pairs, environment) {
+                    let (name, header) = Diagnostic::header(&pair);
+                    if let Err(e) = Self::add_definition(&mut schema, &mut declarations, pair) {
+                        diagnostics.push(header.clone().with_message(e.to_string()));
+                    }
+                    headers.push((name, header));
+                }
+            }
+            Err(e) => diagnostics.push(Diagnostic::from_pest(e, text)),
+        };
+
+        if RohasParser::parse(Rule::schema, input).is_ok() {
+            parse_part(input, &mut diagnostics);
+        } else {
+            for part in diagnostic::split_definitions(input) {
+                parse_part(&part, &mut diagnostics);
+            }
+        }
+
+        for model in &mut schema.models {
+            if let Err(e) = declarations.expand(model) {
+                if let Some((_, header)) = headers.iter().find(|(name, _)| *name == model.name) {
+                    diagnostics.push(header.clone().with_message(e.to_string()));
+                }
+            }
+        }
+
+        let incomplete = !diagnostics.is_empty();
+        for (name, error) in schema.validation_errors() {
+            // References may point at definitions that failed to parse.
+            if (incomplete || !references) && matches!(error, ParseError::UndefinedReference(_)) {
+                continue;
+            }
+            let mut named = headers.iter().filter(|(header_name, _)| header_name == name).map(|(_, header)| header);
+            let header = match error {
+                ParseError::DuplicateDefinition(_) => named.next_back(),
+                _ => named.next(),
+            };
+            if let Some(header) = header {
+                diagnostics.push(header.clone().with_message(error.to_string()));
+            }
+        }
+
+        diagnostics.sort_by_key(|d| (d.line, d.column));
+        (schema, diagnostics)
+    }
+
+    /// The top-level definitions, with those of the `when env` blocks that
+    /// match `environment`.
+    fn definitions<'i>(pairs: Pairs<'i, Rule>, environment: &str) -> Vec<Pair<'i, Rule>> {
+        let mut definitions = Vec::new();
+        for pair in pairs.filter(|pair| pair.as_rule() == Rule::schema).flat_map(|pair| pair.into_inner()) {
+            match pair.as_rule() {
+                Rule::model
+                | Rule::type_def
+                | Rule::api
+                | Rule::event
+                | Rule::cron
+                | Rule::input
+                | Rule::ws
+                | Rule::const_def
+                | Rule::attr_group => definitions.push(pair),
+                Rule::when_block => {
+                    let mut inner = pair.into_inner();
+                    let equals = inner.next().is_some_and(|op| op.as_str() == "==");
+                    let value = inner.next().map(|value| value.as_str().trim_matches('"'));
+                    if equals == (value == Some(environment)) {
+                        definitions.extend(inner);
+                    }
+                }
+                Rule::EOI => {}
+                rule => debug!("Unexpected rule: {:?}", rule),
+            }
+        }
+        definitions
+    }
+
+    fn add_definition(schema: &mut Schema, declarations: &mut Declarations, pair: Pair<Rule>) -> Result<()> {
+        match pair.as_rule() {
+            Rule::model => {
+                let references = Self::constant_references(&pair);
+                let model = Self::parse_model(pair)?;
+                declarations.refer(&model.name, references);
+                schema.models.push(model);
+            }
+            Rule::type_def => schema.types.push(Self::parse_type(pair)?),
+            Rule::api => schema.apis.push(Self::parse_api(pair)?),
+            Rule::event => schema.events.push(Self::parse_event(pair)?),
+            Rule::cron => schema.crons.push(Self::parse_cron(pair)?),
+            Rule::input => schema.inputs.push(Self::parse_input(pair)?),
+            Rule::ws => schema.websockets.push(Self::parse_websocket(pair)?),
+            Rule::const_def => {
+                let (name, value) = Self::parse_constant(pair)?;
+                declarations.declare_constant(name, value)?;
+            }
+            Rule::attr_group => {
+                let (name, attributes) = Self::parse_attribute_group(pair)?;
+                declarations.declare_group(name, attributes)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn parse_constant(pair: Pair<Rule>) -> Result<(String, String)> {
+        let mut inner = pair.into_inner();
+        let name = inner
+            .next()
+            .ok_or_else(|| ParseError::ParseError("Missing constant name".into()))?
+            .as_str()
+            .to_string();
+        let value = inner
+            .next()
+            .ok_or_else(|| ParseError::ParseError(format!("Missing value of constant '{}'", name)))?
+            .as_str()
+            .trim_matches('"')
+            .to_string();
+        Ok((name, value))
+    }
+
+    /// The upper-case identifiers among the model's attribute arguments,
+    /// which refer to constants.
+    fn constant_references(pair: &Pair<Rule>) -> Vec<String> {
+        pair.clone()
+            .into_inner()
+            .flatten()
+            .filter(|arg| arg.as_rule() == Rule::attr_arg)
+            .filter_map(|arg| arg.into_inner().next())
+            .filter(|value| value.as_rule() == Rule::ident && is_constant_name(value.as_str()))
+            .map(|value| value.as_str().to_string())
+            .collect()
+    }
+
+    fn parse_attribute_group(pair: Pair<Rule>) -> Result<(String, Vec<Attribute>)> {
+        let mut inner = pair.into_inner();
+        let name = inner
+            .next()
+            .ok_or_else(|| ParseError::InvalidAttribute("Missing attribute group name".into()))?
+            .as_str()
+            .to_string();
+        let attributes = inner.map(Self::parse_attribute).collect::<Result<_>>()?;
+        Ok((name, attributes))
+    }
+
+    /// Joins the `///` lines directly above `pair`, without their markers.
+    /// Comments are skipped by the grammar, so they are read from the source.
+    fn doc_comment(pair: &pest::iterators::Pair<Rule>) -> Option<String> {
+        let before = &pair.get_input()[..pair.as_span().start()];
+        let (above, line_start) = before.rsplit_once('\n')?;
+        // Something else on the same line, e.g. `model User { id Int`.
+        if !line_start.trim().is_empty() {
+            return None;
+        }
+        let mut lines: Vec<&str> = Vec::new();
+        for line in above.lines().rev() {
+            let line = line.trim();
+            match line.strip_prefix("///") {
+                Some(text) if !text.starts_with('/') => {
+                    lines.push(text.strip_prefix(' ').unwrap_or(text).trim_end());
+                }
+                _ => break,
+            }
+        }
+        if lines.is_empty() {
+            return None;
+        }
+        lines.reverse();
+        Some(lines.join("\n"))
+    }
+
     fn parse_model(pair: pest::iterators::Pair<Rule>) -> Result<Model> {
+        let doc = Self::doc_comment(&pair);
         let mut inner = pair.into_inner();
         let name = inner
             .next()
@@ -77,10 +291,13 @@ impl Parser {
             .to_string();
 
         let mut fields = Vec::new();
+        let mut example = None;
 
         for field_pair in inner {
-            if field_pair.as_rule() == Rule::field {
-                fields.push(Self::parse_field(field_pair)?);
+            match field_pair.as_rule() {
+                Rule::field => fields.push(Self::parse_field(field_pair)?),
+                Rule::example_decl => example = Self::parse_example_decl(field_pair),
+                _ => {}
             }
         }
 
@@ -88,10 +305,13 @@ impl Parser {
             name,
             fields,
             attributes: Vec::new(),
+            example,
+            doc,
         })
     }
 
     fn parse_field(pair: pest::iterators::Pair<Rule>) -> Result<Field> {
+        let doc = Self::doc_comment(&pair);
         let mut inner = pair.into_inner();
 
         let name = inner
@@ -122,6 +342,7 @@ impl Parser {
             field_type,
             optional,
             attributes,
+            doc,
         })
     }
 
@@ -170,6 +391,7 @@ impl Parser {
     }
 
     fn parse_api(pair: pest::iterators::Pair<Rule>) -> Result<Api> {
+        let doc = Self::doc_comment(&pair);
         let mut inner = pair.into_inner();
         let name = inner
             .next()
@@ -181,8 +403,13 @@ impl Parser {
         let mut path = None;
         let mut body = None;
         let mut response = None;
+        let mut paginated = false;
         let mut triggers = Vec::new();
         let mut middlewares = Vec::new();
+        let mut slo = None;
+        let mut consumes = ContentType::Json;
+        let mut produces = ContentType::Json;
+        let mut example = None;
 
         for prop in inner {
             if prop.as_rule() == Rule::api_property {
@@ -192,6 +419,12 @@ impl Parser {
                 if let Some(key) = prop_inner.next() {
                     match key.as_rule() {
                         Rule::http_method => method = HttpMethod::from_str(key.as_str()),
+                        Rule::string if prop_text.starts_with("consumes:") => {
+                            consumes = Self::parse_content_type(key.as_str())?;
+                        }
+                        Rule::string if prop_text.starts_with("produces:") => {
+                            produces = Self::parse_content_type(key.as_str())?;
+                        }
                         Rule::string => path = Some(key.as_str().trim_matches('"').to_string()),
                         Rule::ident => {
                             if prop_text.starts_with("body:") {
@@ -200,30 +433,186 @@ impl Parser {
                                 response = Some(key.as_str().to_string());
                             }
                         }
+                        Rule::page_type => {
+                            response = key.into_inner().next().map(|item| item.as_str().to_string());
+                            paginated = true;
+                        }
                         Rule::trigger_list => {
                             triggers = Self::parse_string_list(key)?;
                         }
                         Rule::string_list | Rule::middleware_list => {
                             middlewares = Self::parse_string_list(key)?;
                         }
+                        Rule::kv_block if prop_text.starts_with("slo:") => {
+                            slo = Some(Self::parse_slo(key)?);
+                        }
+                        Rule::api_example => example = Some(Self::parse_api_example(key)),
                         _ => {}
                     }
                 }
             }
         }
 
+        if paginated && !produces.is_structured() {
+            return Err(ParseError::InvalidApi(format!(
+                "'{}' returns a Page, which needs a JSON or MessagePack response",
+                name
+            )));
+        }
+
         Ok(Api {
             name,
             method: method.ok_or_else(|| ParseError::InvalidApi("Missing HTTP method".into()))?,
             path: path.ok_or_else(|| ParseError::InvalidApi("Missing path".into()))?,
             body,
             response: response.ok_or_else(|| ParseError::InvalidApi("Missing response".into()))?,
+            paginated,
             triggers,
             middlewares,
+            slo,
+            consumes,
+            produces,
+            example,
+            doc,
         })
     }
 
+    fn parse_api_example(pair: pest::iterators::Pair<Rule>) -> ApiExample {
+        let mut example = ApiExample::default();
+        for entry in pair.into_inner() {
+            let mut entry_inner = entry.into_inner();
+            let (Some(part), Some(value)) = (entry_inner.next(), entry_inner.next()) else {
+                continue;
+            };
+            let value = Self::parse_json_value(value);
+            match part.as_str() {
+                "request" => example.request = Some(value),
+                _ => example.response = Some(value),
+            }
+        }
+        example
+    }
+
+    fn parse_example_decl(pair: pest::iterators::Pair<Rule>) -> Option<serde_json::Value> {
+        pair.into_inner().next().map(Self::parse_json_value)
+    }
+
+    fn parse_json_value(pair: pest::iterators::Pair<Rule>) -> serde_json::Value {
+        use serde_json::Value;
+
+        match pair.as_rule() {
+            Rule::json_value => pair.into_inner().next().map(Self::parse_json_value).unwrap_or(Value::Null),
+            Rule::json_object => {
+                let mut map = serde_json::Map::new();
+                for json_pair in pair.into_inner() {
+                    let mut inner = json_pair.into_inner();
+                    if let (Some(key), Some(value)) = (inner.next(), inner.next()) {
+                        map.insert(key.as_str().trim_matches('"').to_string(), Self::parse_json_value(value));
+                    }
+                }
+                Value::Object(map)
+            }
+            Rule::json_array => Value::Array(pair.into_inner().map(Self::parse_json_value).collect()),
+            Rule::string => Value::String(pair.as_str().trim_matches('"').to_string()),
+            Rule::decimal => {
+                let text = pair.as_str();
+                text.parse::<i64>()
+                    .map(Value::from)
+                    .or_else(|_| text.parse::<f64>().map(Value::from))
+                    .unwrap_or(Value::Null)
+            }
+            Rule::boolean => Value::Bool(pair.as_str() == "true"),
+            _ => Value::Null,
+        }
+    }
+
+    fn parse_content_type(value: &str) -> Result<ContentType> {
+        let mime = value.trim_matches('"');
+        ContentType::from_mime(mime).ok_or_else(|| {
+            ParseError::InvalidApi(format!(
+                "Unsupported content type: {} (expected application/json, application/octet-stream, text/csv or application/msgpack)",
+                mime
+            ))
+        })
+    }
+
+    fn parse_slo(pair: pest::iterators::Pair<Rule>) -> Result<Slo> {
+        let mut slo = Slo::default();
+
+        for (key, value) in Self::parse_kv_block(pair) {
+            match key.as_str() {
+                "latency_p99" => {
+                    slo.latency_p99_ms = Some(Self::parse_duration_ms(&value).ok_or_else(|| {
+                        ParseError::InvalidApi(format!("Invalid latency_p99 duration: {}", value))
+                    })?)
+                }
+                "latency_p95" => {
+                    slo.latency_p95_ms = Some(Self::parse_duration_ms(&value).ok_or_else(|| {
+                        ParseError::InvalidApi(format!("Invalid latency_p95 duration: {}", value))
+                    })?)
+                }
+                "availability" => {
+                    let availability = value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|a| *a > 0.0 && *a <= 100.0)
+                        .ok_or_else(|| {
+                            ParseError::InvalidApi(format!("Invalid availability target: {}", value))
+                        })?;
+                    slo.availability = Some(availability);
+                }
+                other => {
+                    return Err(ParseError::InvalidApi(format!("Unknown SLO property: {}", other)))
+                }
+            }
+        }
+
+        Ok(slo)
+    }
+
+    /// Collects the entries of a `{ key: value, ... }` block, with string
+    /// values unquoted.
+    fn parse_kv_block(pair: pest::iterators::Pair<Rule>) -> Vec<(String, String)> {
+        let mut entries = Vec::new();
+        for entry in pair.into_inner() {
+            if entry.as_rule() != Rule::kv_entry {
+                continue;
+            }
+            let mut entry_inner = entry.into_inner();
+            if let (Some(key), Some(value)) = (entry_inner.next(), entry_inner.next()) {
+                entries.push((
+                    key.as_str().to_string(),
+                    value.as_str().trim_matches('"').to_string(),
+                ));
+            }
+        }
+        entries
+    }
+
+    /// Parses durations like `300ms`, `2s`, `1.5m` or `1h` into milliseconds.
+    /// A bare number is taken as milliseconds.
+    pub(crate) fn parse_duration_ms(value: &str) -> Option<u64> {
+        let value = value.trim();
+        let split = value
+            .find(|c: char| c.is_ascii_alphabetic())
+            .unwrap_or(value.len());
+        let (number, unit) = value.split_at(split);
+        let number: f64 = number.parse().ok()?;
+        let multiplier = match unit {
+            "" | "ms" => 1.0,
+            "s" => 1_000.0,
+            "m" => 60_000.0,
+            "h" => 3_600_000.0,
+            _ => return None,
+        };
+        if number < 0.0 {
+            return None;
+        }
+        Some((number * multiplier).round() as u64)
+    }
+
     fn parse_event(pair: pest::iterators::Pair<Rule>) -> Result<Event> {
+        let doc = Self::doc_comment(&pair);
         let mut inner = pair.into_inner();
         let name = inner
             .next()
@@ -235,6 +624,11 @@ impl Parser {
         let mut handlers = Vec::new();
         let mut triggers = Vec::new();
         let mut adapter_type = None;
+        let mut forward_to = None;
+        let mut forward_filter = None;
+        let mut concurrency = None;
+        let mut prefetch = None;
+        let mut retry = None;
 
         for prop in inner {
             if prop.as_rule() == Rule::event_property {
@@ -255,8 +649,34 @@ impl Parser {
                                         value.as_str(), name
                                     )));
                                 }
+                            } else if prop_text.starts_with("forward_to:") {
+                                forward_to = Some(value.as_str().to_string());
+                            }
+                        }
+                        Rule::string if prop_text.starts_with("forward_filter:") => {
+                            let filter = value.as_str().trim_matches('"').to_string();
+                            Filter::parse(&filter).map_err(|e| {
+                                ParseError::InvalidEvent(format!("Invalid forward_filter of event '{}': {}", name, e))
+                            })?;
+                            forward_filter = Some(filter);
+                        }
+                        Rule::number => {
+                            let key = prop_text.split(':').next().unwrap_or_default();
+                            let limit = value.as_str().parse::<u32>().ok().filter(|n| *n > 0).ok_or_else(|| {
+                                ParseError::InvalidEvent(format!(
+                                    "{} of event '{}' must be a positive integer, got {}",
+                                    key, name, value.as_str()
+                                ))
+                            })?;
+                            if key == "concurrency" {
+                                concurrency = Some(limit);
+                            } else {
+                                prefetch = Some(limit);
                             }
                         }
+                        Rule::retry_policy => {
+                            retry = Some(Self::parse_retry_policy(value, &name)?);
+                        }
                         Rule::handler_list | Rule::trigger_list => {
                             let items = Self::parse_string_list(value)?;
                             if prop_text.starts_with("handler:") {
@@ -271,16 +691,113 @@ impl Parser {
             }
         }
 
+        let forward_to = match (forward_to, forward_filter) {
+            (Some(websocket), filter) => Some(Forward { websocket, filter }),
+            (None, Some(_)) => {
+                return Err(ParseError::InvalidEvent(format!(
+                    "Event '{}' has a forward_filter but no forward_to",
+                    name
+                )))
+            }
+            (None, None) => None,
+        };
+
+        if let (Some(concurrency), Some(prefetch)) = (concurrency, prefetch) {
+            if prefetch < concurrency {
+                return Err(ParseError::InvalidEvent(format!(
+                    "Event '{}' has prefetch {} below its concurrency {}",
+                    name, prefetch, concurrency
+                )));
+            }
+        }
+
         Ok(Event {
             name,
             payload,
             handlers,
             triggers,
             adapter_type,
+            forward_to,
+            concurrency,
+            prefetch,
+            retry,
+            doc,
+        })
+    }
+
+    fn parse_retry_policy(pair: pest::iterators::Pair<Rule>, event: &str) -> Result<RetryPolicy> {
+        let invalid = |message: String| ParseError::InvalidEvent(format!("Invalid retry of event '{}': {}", event, message));
+        let mut max_retries = None;
+        let mut backoff = Backoff::None;
+        let mut on = Vec::new();
+
+        for entry in pair.into_inner() {
+            let Some(value) = entry.into_inner().next() else {
+                continue;
+            };
+            match value.as_rule() {
+                Rule::number => {
+                    let max = value
+                        .as_str()
+                        .parse::<u32>()
+                        .map_err(|_| invalid(format!("max must be a non-negative integer, got {}", value.as_str())))?;
+                    max_retries = Some(max);
+                }
+                Rule::backoff => {
+                    let Some(kind) = value.into_inner().next() else {
+                        continue;
+                    };
+                    let rule = kind.as_rule();
+                    let mut args = kind.into_inner();
+                    let mut next_arg = || args.next().map(|arg| arg.as_str().to_string());
+                    backoff = match rule {
+                        Rule::backoff_fixed => Backoff::Fixed {
+                            delay_ms: next_arg().as_deref().and_then(Self::parse_duration_ms).unwrap_or_default(),
+                        },
+                        Rule::backoff_exponential => {
+                            let initial_ms = next_arg().as_deref().and_then(Self::parse_duration_ms).unwrap_or_default();
+                            let mut factor = 2.0;
+                            let mut cap_ms = None;
+                            while let Some(arg) = next_arg() {
+                                match arg.strip_suffix('x') {
+                                    Some(f) => factor = f.parse().unwrap_or(factor),
+                                    None => cap_ms = Self::parse_duration_ms(&arg),
+                                }
+                            }
+                            if factor < 1.0 {
+                                return Err(invalid(format!("backoff factor must be at least 1x, got {}x", factor)));
+                            }
+                            Backoff::Exponential {
+                                initial_ms,
+                                factor,
+                                cap_ms,
+                            }
+                        }
+                        _ => Backoff::None,
+                    };
+                }
+                Rule::trigger_list => {
+                    for name in Self::parse_string_list(value)? {
+                        let condition = RetryCondition::parse(&name)
+                            .ok_or_else(|| invalid(format!("unknown condition '{}', expected Timeout or Error", name)))?;
+                        if !on.contains(&condition) {
+                            on.push(condition);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(RetryPolicy {
+            max_retries: max_retries.ok_or_else(|| invalid("max is required".to_string()))?,
+            backoff,
+            on,
         })
     }
 
     fn parse_cron(pair: pest::iterators::Pair<Rule>) -> Result<Cron> {
+        let doc = Self::doc_comment(&pair);
         let mut inner = pair.into_inner();
         let name = inner
             .next()
@@ -288,30 +805,74 @@ impl Parser {
             .as_str()
             .to_string();
 
-        let mut schedule = String::new();
+        let mut schedules = Vec::new();
         let mut triggers = Vec::new();
+        let mut overlap = OverlapPolicy::default();
+        let mut misfire = MisfirePolicy::default();
 
         for prop in inner {
             if prop.as_rule() == Rule::cron_property {
+                let prop_text = prop.as_str();
                 let mut prop_inner = prop.into_inner();
                 if let Some(value) = prop_inner.next() {
+                    let text = value.as_str().trim_matches('"').to_string();
                     match value.as_rule() {
-                        Rule::string => schedule = value.as_str().trim_matches('"').to_string(),
                         Rule::trigger_list => triggers = Self::parse_string_list(value)?,
+                        _ if prop_text.starts_with("overlap:") => {
+                            overlap = match text.as_str() {
+                                "skip" => OverlapPolicy::Skip,
+                                "queue" => OverlapPolicy::Queue,
+                                "concurrent" => OverlapPolicy::Concurrent,
+                                other => {
+                                    return Err(ParseError::InvalidCron(format!(
+                                        "Cron '{}' has unknown overlap '{}' (expected skip, queue or concurrent)",
+                                        name, other
+                                    )))
+                                }
+                            }
+                        }
+                        _ if prop_text.starts_with("misfire:") => {
+                            misfire = match text.as_str() {
+                                "run_once" => MisfirePolicy::RunOnce,
+                                "ignore" => MisfirePolicy::Ignore,
+                                other => {
+                                    return Err(ParseError::InvalidCron(format!(
+                                        "Cron '{}' has unknown misfire '{}' (expected run_once or ignore)",
+                                        name, other
+                                    )))
+                                }
+                            }
+                        }
+                        _ if prop_text.starts_with("every:") => schedules.push((ScheduleKind::Every, text)),
+                        _ if prop_text.starts_with("at:") => schedules.push((ScheduleKind::At, text)),
+                        Rule::string => schedules.push((ScheduleKind::Cron, text)),
                         _ => {}
                     }
                 }
             }
         }
 
+        if schedules.len() > 1 {
+            return Err(ParseError::InvalidCron(format!(
+                "Cron '{}' must set only one of schedule, every or at",
+                name
+            )));
+        }
+        let (kind, schedule) = schedules.pop().unwrap_or_default();
+
         Ok(Cron {
             name,
             schedule,
+            kind,
+            overlap,
+            misfire,
             triggers,
+            doc,
         })
     }
 
     fn parse_type(pair: pest::iterators::Pair<Rule>) -> Result<Type> {
+        let doc = Self::doc_comment(&pair);
         let mut inner = pair.into_inner();
         let name = inner
             .next()
@@ -320,9 +881,13 @@ impl Parser {
             .to_string();
 
         let mut fields = Vec::new();
+        let mut example = None;
 
         for field_pair in inner {
-            if field_pair.as_rule() == Rule::input_field {
+            if field_pair.as_rule() == Rule::example_decl {
+                example = Self::parse_example_decl(field_pair);
+            } else if field_pair.as_rule() == Rule::input_field {
+                let doc = Self::doc_comment(&field_pair);
                 let mut field_inner = field_pair.into_inner();
 
                 let field_name = field_inner
@@ -344,14 +909,21 @@ impl Parser {
                     field_type,
                     optional,
                     attributes: Vec::new(),
+                    doc,
                 });
             }
         }
 
-        Ok(Type { name, fields })
+        Ok(Type {
+            name,
+            fields,
+            example,
+            doc,
+        })
     }
 
     fn parse_input(pair: pest::iterators::Pair<Rule>) -> Result<Input> {
+        let doc = Self::doc_comment(&pair);
         let mut inner = pair.into_inner();
         let name = inner
             .next()
@@ -360,9 +932,13 @@ impl Parser {
             .to_string();
 
         let mut fields = Vec::new();
+        let mut example = None;
 
         for field_pair in inner {
-            if field_pair.as_rule() == Rule::input_field {
+            if field_pair.as_rule() == Rule::example_decl {
+                example = Self::parse_example_decl(field_pair);
+            } else if field_pair.as_rule() == Rule::input_field {
+                let doc = Self::doc_comment(&field_pair);
                 let mut field_inner = field_pair.into_inner();
 
                 let field_name = field_inner
@@ -384,11 +960,17 @@ impl Parser {
                     field_type,
                     optional,
                     attributes: Vec::new(),
+                    doc,
                 });
             }
         }
 
-        Ok(Input { name, fields })
+        Ok(Input {
+            name,
+            fields,
+            example,
+            doc,
+        })
     }
 
     fn parse_string_list(pair: pest::iterators::Pair<Rule>) -> Result<Vec<String>> {
@@ -404,6 +986,7 @@ impl Parser {
     }
 
     fn parse_websocket(pair: pest::iterators::Pair<Rule>) -> Result<WebSocket> {
+        let doc = Self::doc_comment(&pair);
         let mut inner = pair.into_inner();
         let name = inner
             .next()
@@ -419,6 +1002,7 @@ impl Parser {
         let mut triggers = Vec::new();
         let mut broadcast = false;
         let mut middlewares = Vec::new();
+        let mut auth = None;
 
         for prop in inner {
             if prop.as_rule() == Rule::ws_property {
@@ -459,6 +1043,9 @@ impl Parser {
                                 broadcast = key.as_str() == "true";
                             }
                         }
+                        Rule::kv_block if prop_text.starts_with("auth:") => {
+                            auth = Some(Self::parse_ws_auth(key)?);
+                        }
                         _ => {}
                     }
                 }
@@ -475,8 +1062,132 @@ impl Parser {
             triggers,
             broadcast,
             middlewares,
+            auth,
+            doc,
         })
     }
+
+    fn parse_ws_auth(pair: pest::iterators::Pair<Rule>) -> Result<WsAuth> {
+        let mut handler = None;
+        let mut from = TokenSource::Query;
+        let mut name = None;
+
+        for (key, value) in Self::parse_kv_block(pair) {
+            match key.as_str() {
+                "handler" => handler = Some(value),
+                "from" => {
+                    from = TokenSource::from_name(&value).ok_or_else(|| {
+                        ParseError::InvalidApi(format!(
+                            "Invalid auth token source: {} (expected query or header)",
+                            value
+                        ))
+                    })?
+                }
+                "name" => name = Some(value),
+                other => {
+                    return Err(ParseError::InvalidApi(format!("Unknown auth property: {}", other)))
+                }
+            }
+        }
+
+        Ok(WsAuth {
+            handler: handler.ok_or_else(|| ParseError::InvalidApi("Missing auth handler".into()))?,
+            from,
+            name: name.unwrap_or_else(|| from.default_name().to_string()),
+        })
+    }
+}
+
+/// Whether an identifier argument is written as a constant, e.g.
+/// `DEFAULT_REGION`, rather than as a value such as `now`.
+fn is_constant_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_uppercase())
+        && name.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Constants and attribute groups of the schema. They apply to every file
+/// parsed together, before or after their declaration.
+#[derive(Default)]
+struct Declarations {
+    constants: HashMap<String, (String, Option<PathBuf>)>,
+    groups: HashMap<String, (Vec<Attribute>, Option<PathBuf>)>,
+    /// Constants each model's attribute arguments refer to, with its file.
+    references: Vec<(String, String, Option<PathBuf>)>,
+    /// The file whose definitions are being added.
+    file: Option<PathBuf>,
+}
+
+impl Declarations {
+    fn declare_constant(&mut self, name: String, value: String) -> Result<()> {
+        Self::declare(&mut self.constants, "Constant", name, value, &self.file)
+    }
+
+    fn declare_group(&mut self, name: String, attributes: Vec<Attribute>) -> Result<()> {
+        Self::declare(&mut self.groups, "Attribute group", name, attributes, &self.file)
+    }
+
+    fn declare<T>(
+        declared: &mut HashMap<String, (T, Option<PathBuf>)>,
+        kind: &str,
+        name: String,
+        value: T,
+        file: &Option<PathBuf>,
+    ) -> Result<()> {
+        if let Some((_, first)) = declared.get(&name) {
+            return Err(ParseError::DuplicateDefinition(match first.as_ref().filter(|first| Some(*first) != file.as_ref()) {
+                Some(first) => format!("{} '{}', first declared in {}", kind, name, first.display()),
+                None => format!("{} '{}'", kind, name),
+            }));
+        }
+        declared.insert(name, (value, file.clone()));
+        Ok(())
+    }
+
+    fn refer(&mut self, model: &str, constants: Vec<String>) {
+        self.references
+            .extend(constants.into_iter().map(|constant| (model.to_string(), constant, self.file.clone())));
+    }
+
+    /// Checks that every constant the models refer to is declared.
+    fn resolve(&self) -> Result<()> {
+        for (model, constant, file) in &self.references {
+            if !self.constants.contains_key(constant) {
+                let file = file.as_ref().map(|file| format!(" in {}", file.display())).unwrap_or_default();
+                return Err(ParseError::UndefinedReference(format!(
+                    "Constant '{}' on model '{}'{}",
+                    constant, model, file
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Replaces the groups among the model's field attributes with their
+    /// attributes, then the constants among the arguments with their values.
+    fn expand(&self, model: &mut Model) -> Result<()> {
+        for field in &mut model.fields {
+            let mut attributes = Vec::with_capacity(field.attributes.len());
+            for attribute in field.attributes.drain(..) {
+                match self.groups.get(&attribute.name) {
+                    Some(_) if !attribute.args.is_empty() => {
+                        return Err(ParseError::InvalidAttribute(format!(
+                            "Attribute group '{}' on '{}.{}' takes no arguments",
+                            attribute.name, model.name, field.name
+                        )));
+                    }
+                    Some((group, _)) => attributes.extend(group.iter().cloned()),
+                    None => attributes.push(attribute),
+                }
+            }
+            for arg in attributes.iter_mut().flat_map(|attribute| attribute.args.iter_mut()) {
+                if let Some((value, _)) = self.constants.get(arg.as_str()) {
+                    *arg = value.clone();
+                }
+            }
+            field.attributes = attributes;
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -516,6 +1227,49 @@ mod tests {
         assert_eq!(schema.apis[0].name, "CreateUser");
     }
 
+    #[test]
+    fn test_parse_api_slo() {
+        let input = r#"
+            api GetUser {
+                method: GET
+                path: "/users/{id}"
+                response: User
+                slo: { latency_p99: 300ms, availability: 99.9 }
+            }
+        "#;
+
+        let schema = Parser::parse_string(input).expect("Failed to parse");
+        let slo = schema.apis[0].slo.as_ref().expect("Missing SLO");
+        assert_eq!(slo.latency_p99_ms, Some(300));
+        assert_eq!(slo.availability, Some(99.9));
+
+        let invalid = input.replace("availability: 99.9", "uptime: 99.9");
+        assert!(Parser::parse_string(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_parse_api_content_types() {
+        let input = r#"
+            api UploadAvatar {
+                method: PUT
+                path: "/users/{id}/avatar"
+                consumes: "application/octet-stream"
+                produces: "text/csv"
+                response: String
+            }
+        "#;
+
+        let schema = Parser::parse_string(input).expect("Failed to parse");
+        let api = &schema.apis[0];
+        assert_eq!(api.path, "/users/{id}/avatar");
+        assert_eq!(api.consumes, ContentType::OctetStream);
+        assert_eq!(api.produces, ContentType::Csv);
+        assert_eq!(api.body_type().as_deref(), Some("Bytes"));
+
+        let invalid = input.replace("text/csv", "text/html");
+        assert!(Parser::parse_string(&invalid).is_err());
+    }
+
     #[test]
     fn test_parse_event() {
         let input = r#"
@@ -531,4 +1285,272 @@ mod tests {
         assert_eq!(schema.events[0].name, "UserCreated");
         assert_eq!(schema.events[0].handlers.len(), 2);
     }
+
+    #[test]
+    fn test_parse_event_concurrency() {
+        let input = r#"
+            event OrderPlaced {
+                payload: Order
+                handler: [ChargeCard]
+                concurrency: 4
+                prefetch: 16
+            }
+        "#;
+
+        let schema = Parser::parse_string(input).expect("Failed to parse");
+        assert_eq!(schema.events[0].concurrency, Some(4));
+        assert_eq!(schema.events[0].prefetch, Some(16));
+
+        assert!(Parser::parse_string(&input.replace("concurrency: 4", "concurrency: 0")).is_err());
+        assert!(Parser::parse_string(&input.replace("prefetch: 16", "prefetch: 2")).is_err());
+    }
+
+    #[test]
+    fn test_parse_event_retry_policy() {
+        let input = r#"
+            event OrderPlaced {
+                payload: Order
+                handler: [charge_card]
+                retry: { max: 5, backoff: exponential(1s, 3x, cap 5m), on: [Timeout] }
+            }
+        "#;
+
+        let schema = Parser::parse_string(input).expect("Failed to parse");
+        let retry = schema.events[0].retry.as_ref().unwrap();
+        assert_eq!(retry.max_retries, 5);
+        assert_eq!(
+            retry.backoff,
+            Backoff::Exponential {
+                initial_ms: 1_000,
+                factor: 3.0,
+                cap_ms: Some(300_000),
+            }
+        );
+        assert_eq!(retry.backoff.delay_ms(1), 1_000);
+        assert_eq!(retry.backoff.delay_ms(3), 9_000);
+        assert_eq!(retry.backoff.delay_ms(10), 300_000);
+        assert!(retry.retries(RetryCondition::Timeout));
+        assert!(!retry.retries(RetryCondition::Error));
+
+        let fixed = Parser::parse_string(&input.replace("exponential(1s, 3x, cap 5m), on: [Timeout]", "fixed(500ms)")).unwrap();
+        let retry = fixed.events[0].retry.as_ref().unwrap();
+        assert_eq!(retry.backoff, Backoff::Fixed { delay_ms: 500 });
+        assert!(retry.retries(RetryCondition::Error));
+
+        assert!(Parser::parse_string(&input.replace("max: 5, ", "")).is_err());
+        assert!(Parser::parse_string(&input.replace("[Timeout]", "[Http5xx]")).is_err());
+        assert!(Parser::parse_string(&input.replace("3x", "0.5x")).is_err());
+    }
+
+    #[test]
+    fn test_parse_computed_field() {
+        let input = r#"
+            model User {
+                first_name String
+                last_name  String
+                full_name  String @computed("first_name || ' ' || last_name")
+            }
+        "#;
+
+        let schema = Parser::parse_string(input).expect("Failed to parse");
+        let fields = &schema.models[0].fields;
+        assert_eq!(fields[0].computed(), None);
+        assert_eq!(fields[2].computed(), Some("first_name || ' ' || last_name"));
+
+        assert!(Parser::parse_string(&input.replace("@computed(\"first_name || ' ' || last_name\")", "@computed")).is_err());
+        assert!(Parser::parse_string(&input.replace("last_name\")", "last_name\") @default(\"x\")")).is_err());
+        assert!(Parser::parse_string(&input.replace("model User", "input User")).is_err());
+    }
+
+    #[test]
+    fn test_parse_websocket_auth() {
+        let input = r#"
+            ws Chat {
+                path: "/ws/chat"
+                onConnect: [OnConnect]
+                auth: { handler: AuthenticateChat, from: header }
+            }
+        "#;
+
+        let schema = Parser::parse_string(input).expect("Failed to parse");
+        let auth = schema.websockets[0].auth.as_ref().expect("Missing auth");
+        assert_eq!(auth.handler, "AuthenticateChat");
+        assert_eq!(auth.from, TokenSource::Header);
+        assert_eq!(auth.name, "authorization");
+
+        let query = input.replace("from: header", "from: query, name: \"access_token\"");
+        let schema = Parser::parse_string(&query).expect("Failed to parse");
+        let auth = schema.websockets[0].auth.as_ref().expect("Missing auth");
+        assert_eq!(auth.from, TokenSource::Query);
+        assert_eq!(auth.name, "access_token");
+
+        let invalid = input.replace("from: header", "from: cookie");
+        assert!(Parser::parse_string(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_parse_event_forward_to() {
+        let input = r#"
+            ws OrderFeed {
+                path: "/ws/orders"
+            }
+
+            event OrderUpdated {
+                payload: Order
+                forward_to: OrderFeed
+                forward_filter: "payload.customer_id == connection.principal.id"
+            }
+        "#;
+
+        let schema = Parser::parse_string(input).expect("Failed to parse");
+        let forward = schema.events[0].forward_to.as_ref().expect("Missing forward_to");
+        assert_eq!(forward.websocket, "OrderFeed");
+        assert_eq!(
+            forward.filter.as_deref(),
+            Some("payload.customer_id == connection.principal.id")
+        );
+
+        let unknown = input.replace("forward_to: OrderFeed", "forward_to: Missing");
+        assert!(Parser::parse_string(&unknown).is_err());
+
+        let invalid = input.replace("connection.principal.id", "principal.id");
+        assert!(Parser::parse_string(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_parse_interval_and_one_shot_crons() {
+        let input = r#"
+            cron PollInventory {
+                every: 30s
+            }
+
+            cron Launch {
+                at: 2025-08-01T00:00:00Z
+                triggers: [LaunchStarted]
+            }
+
+            cron Nightly {
+                schedule: "0 0 2 * * *"
+            }
+        "#;
+
+        let schema = Parser::parse_string(input).expect("Failed to parse");
+        assert_eq!(schema.crons[0].kind, ScheduleKind::Every);
+        assert_eq!(schema.crons[0].schedule, "30s");
+        assert_eq!(schema.crons[1].kind, ScheduleKind::At);
+        assert_eq!(schema.crons[1].schedule, "2025-08-01T00:00:00Z");
+        assert_eq!(schema.crons[1].triggers, vec!["LaunchStarted"]);
+        assert_eq!(schema.crons[2].kind, ScheduleKind::Cron);
+
+        let both = input.replace("every: 30s", "every: 30s\n schedule: \"0 * * * * *\"");
+        assert!(Parser::parse_string(&both).is_err());
+    }
+
+    #[test]
+    fn test_parse_examples() {
+        let input = r#"
+            model User {
+                id    Int @id
+                name  String
+                tags  String[]
+                example: { id: 1, "name": "Ada", tags: ["admin"], }
+            }
+
+            input CreateUser {
+                name: String
+                example: String?
+                example: { name: "Ada", example: null }
+            }
+
+            api CreateUser {
+                method: POST
+                path: "/users"
+                body: CreateUser
+                response: User
+                example: {
+                    request: { name: "Ada" }
+                    response: { id: 1, name: "Ada", score: -2.5, active: true }
+                }
+            }
+        "#;
+
+        let schema = Parser::parse_string(input).expect("Failed to parse");
+        assert_eq!(
+            schema.models[0].example,
+            Some(serde_json::json!({ "id": 1, "name": "Ada", "tags": ["admin"] }))
+        );
+        assert_eq!(schema.inputs[0].fields.len(), 2);
+        assert_eq!(schema.inputs[0].example, Some(serde_json::json!({ "name": "Ada", "example": null })));
+        let example = schema.apis[0].example.as_ref().unwrap();
+        assert_eq!(example.request, Some(serde_json::json!({ "name": "Ada" })));
+        assert_eq!(
+            example.response,
+            Some(serde_json::json!({ "id": 1, "name": "Ada", "score": -2.5, "active": true }))
+        );
+
+        let unknown = input.replace("tags: [\"admin\"]", "role: \"admin\"");
+        assert!(Parser::parse_string(&unknown).is_err());
+    }
+
+    #[test]
+    fn test_parse_cron_overlap_and_misfire() {
+        let input = r#"
+            cron Reconcile {
+                schedule: "0 */5 * * * *"
+                overlap: queue
+                misfire: run_once
+            }
+
+            cron Cleanup {
+                every: 1h
+            }
+        "#;
+
+        let schema = Parser::parse_string(input).expect("Failed to parse");
+        assert_eq!(schema.crons[0].overlap, OverlapPolicy::Queue);
+        assert_eq!(schema.crons[0].misfire, MisfirePolicy::RunOnce);
+        assert_eq!(schema.crons[1].overlap, OverlapPolicy::Skip);
+        assert_eq!(schema.crons[1].misfire, MisfirePolicy::Ignore);
+
+        let unknown = input.replace("overlap: queue", "overlap: wait");
+        assert!(Parser::parse_string(&unknown).is_err());
+    }
+
+    #[test]
+    fn test_parse_doc_comments() {
+        let input = r#"
+            /// A registered user.
+            /// Created on sign-up.
+            model User {
+                /// Primary key.
+                id   Int @id
+                name String
+            }
+
+            // Not documentation.
+            input CreateUser {
+                /// Display name.
+                name: String
+            }
+
+            //// Divider
+
+            /// Creates a user.
+            api CreateUser {
+                method: POST
+                path: "/users"
+                body: CreateUser
+                response: User
+            }
+        "#;
+
+        let schema = Parser::parse_string(input).expect("Failed to parse");
+        let user = &schema.models[0];
+        assert_eq!(user.doc.as_deref(), Some("A registered user.\nCreated on sign-up."));
+        assert_eq!(user.fields[0].doc.as_deref(), Some("Primary key."));
+        assert_eq!(user.fields[1].doc, None);
+        assert_eq!(schema.inputs[0].doc, None);
+        assert_eq!(schema.inputs[0].fields[0].doc.as_deref(), Some("Display name."));
+        assert_eq!(schema.apis[0].doc.as_deref(), Some("Creates a user."));
+    }
 }
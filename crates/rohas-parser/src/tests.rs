@@ -65,4 +65,242 @@ mod integration_tests {
         assert_eq!(cleanup_cron.name, "CleanupOldUsers");
         assert_eq!(cleanup_cron.schedule, "0 0 * * *");
     }
+
+    #[test]
+    fn test_schema_json_round_trip() {
+        let input = r#"
+            /// A registered user.
+            model User {
+                id Int @id @auto
+                name String
+                tags String[]
+                example: { id: 1, name: "Ada", tags: ["admin"] }
+            }
+
+            api GetUser {
+                method: GET
+                path: "/users/{id}"
+                response: User
+            }
+
+            event UserCreated {
+                payload: User
+                handler: [send_welcome_email]
+                retry: { max: 5, backoff: exponential(1s, 3x, cap 5m), on: [Timeout] }
+            }
+
+            cron PollInventory {
+                every: 30s
+            }
+
+            ws Chat {
+                path: "/ws/chat"
+                onConnect: [OnConnect]
+                auth: { handler: AuthenticateChat, from: header }
+            }
+        "#;
+
+        let schema = Parser::parse_string(input).expect("Failed to parse schema");
+        let json = schema.to_json();
+
+        let document: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(document["version"], crate::SCHEMA_JSON_VERSION);
+        assert_eq!(crate::Schema::from_json(&json).unwrap(), schema);
+
+        let newer = json.replacen(
+            &format!("\"version\": {}", crate::SCHEMA_JSON_VERSION),
+            "\"version\": 999",
+            1,
+        );
+        assert!(crate::Schema::from_json(&newer).is_err());
+    }
+
+    #[test]
+    fn test_parse_with_diagnostics() {
+        let input = "model User {
+  id Int @id
+}
+
+api GetUser {
+  methd: GET
+  path: \"/users/:id\"
+}
+
+model User {
+  name String
+}
+
+api Health {
+  method: GET
+  path: \"/health\"
+  response: String
+}
+
+event Broken {
+  payload
+}
+";
+
+        let (schema, diagnostics) = Parser::parse_with_diagnostics(input, crate::env::DEFAULT_ENVIRONMENT);
+
+        let positions: Vec<_> = diagnostics.iter().map(|d| (d.line, d.column)).collect();
+        assert_eq!(positions, vec![(6, 3), (10, 1), (21, 3)]);
+        assert_eq!(diagnostics[0].end_column, 8);
+        assert_eq!(diagnostics[0].suggestion.as_deref(), Some("method"));
+        assert!(diagnostics[1].message.contains("Duplicate definition"));
+        assert_eq!(schema.apis.len(), 1);
+        assert_eq!(schema.apis[0].name, "Health");
+
+        let (_, diagnostics) = Parser::parse_with_diagnostics(
+            "api Health {\n  method: GET\n  path: \"/health\"\n  response: String\n}\n",
+            crate::env::DEFAULT_ENVIRONMENT,
+        );
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_parse_part_with_diagnostics() {
+        let input = "event OrderUpdated {\n  payload: Order\n  forward_to: OrderFeed\n}\n";
+
+        // The websocket may be declared in another file of the schema.
+        let (schema, diagnostics) = Parser::parse_part_with_diagnostics(input, crate::env::DEFAULT_ENVIRONMENT);
+        assert!(diagnostics.is_empty());
+        assert_eq!(schema.events.len(), 1);
+        let (_, diagnostics) = Parser::parse_with_diagnostics(input, crate::env::DEFAULT_ENVIRONMENT);
+        assert_eq!(diagnostics.len(), 1);
+
+        let duplicate = format!("{}{}", input, input);
+        let (_, diagnostics) = Parser::parse_part_with_diagnostics(&duplicate, crate::env::DEFAULT_ENVIRONMENT);
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].message.contains("Duplicate definition"));
+    }
+
+    #[test]
+    fn test_constants_and_attribute_groups() {
+        let input = r#"
+            const DEFAULT_REGION = "us-east-1"
+
+            model Upload {
+                id     Int    @key
+                region String @default(DEFAULT_REGION)
+            }
+
+            group key { @id @auto }
+        "#;
+
+        let schema = Parser::parse_string(input).expect("Failed to parse schema");
+        let fields = &schema.models[0].fields;
+        let names: Vec<_> = fields[0].attributes.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, vec!["id", "auto"]);
+        assert_eq!(fields[1].attributes[0].args, vec!["us-east-1"]);
+
+        let with_args = input.replace("@key", "@key(1)");
+        assert!(Parser::parse_string(&with_args).is_err());
+        let duplicate = format!("{}\n group key {{ @unique }}", input);
+        assert!(Parser::parse_string(&duplicate).is_err());
+        let unknown = input.replace("@default(DEFAULT_REGION)", "@default(REGION)");
+        let error = Parser::parse_string(&unknown).unwrap_err().to_string();
+        assert!(error.contains("Constant 'REGION' on model 'Upload'"), "{}", error);
+    }
+
+    #[test]
+    fn test_declarations_apply_across_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let shared = dir.path().join("shared.ro");
+        let models = dir.path().join("models.ro");
+        std::fs::write(&shared, "const DEFAULT_REGION = \"us-east-1\"\ngroup key { @id @auto }\n").unwrap();
+        std::fs::write(
+            &models,
+            "model Upload {\n  id Int @key\n  region String @default(DEFAULT_REGION)\n  created DateTime @default(now)\n}\n",
+        )
+        .unwrap();
+
+        let schema = Parser::parse_files_for_environment(&[&shared, &models], crate::env::DEFAULT_ENVIRONMENT)
+            .expect("Failed to parse schema files");
+        let fields = &schema.models[0].fields;
+        let names: Vec<_> = fields[0].attributes.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, vec!["id", "auto"]);
+        assert_eq!(fields[1].attributes[0].args, vec!["us-east-1"]);
+        assert_eq!(fields[2].attributes[0].args, vec!["now"]);
+
+        let other = dir.path().join("other.ro");
+        std::fs::write(&other, "group key { @unique }\n").unwrap();
+        let error = Parser::parse_files_for_environment(&[&shared, &models, &other], crate::env::DEFAULT_ENVIRONMENT)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("other.ro"), "{}", error);
+        assert!(error.contains("Attribute group 'key', first declared in"), "{}", error);
+
+        let error = Parser::parse_files_for_environment(&[&models], crate::env::DEFAULT_ENVIRONMENT)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("Constant 'DEFAULT_REGION' on model 'Upload' in"), "{}", error);
+    }
+
+    #[test]
+    fn test_when_env_blocks() {
+        let input = r#"
+            api Health {
+                method: GET
+                path: "/health"
+                response: String
+            }
+
+            when env == "production" {
+                cron NightlyReport {
+                    schedule: "0 0 2 * * *"
+                }
+            }
+
+            when env != "production" {
+                api SeedData {
+                    method: POST
+                    path: "/dev/seed"
+                    response: String
+                }
+            }
+        "#;
+
+        let production = Parser::parse_for_environment(input, "production").expect("Failed to parse schema");
+        assert_eq!(production.apis.len(), 1);
+        assert_eq!(production.crons[0].name, "NightlyReport");
+
+        let development = Parser::parse_for_environment(input, "development").expect("Failed to parse schema");
+        assert_eq!(development.apis[1].name, "SeedData");
+        assert!(development.crons.is_empty());
+
+        // Without an environment, the default one applies whatever ROHAS_ENV is.
+        assert_eq!(Parser::parse_string(input).unwrap(), development);
+
+        let (schema, diagnostics) = Parser::parse_with_diagnostics(input, "production");
+        assert!(diagnostics.is_empty());
+        assert_eq!(schema, production);
+    }
+
+    #[test]
+    fn test_paginated_response() {
+        let input = r#"
+            api ListUsers {
+                method: GET
+                path: "/users"
+                response: Page<User>
+            }
+
+            api GetPageView {
+                method: GET
+                path: "/page-view"
+                response: PageView
+            }
+        "#;
+
+        let schema = Parser::parse_string(input).expect("Failed to parse schema");
+        assert!(schema.apis[0].paginated);
+        assert_eq!(schema.apis[0].response, "User");
+        assert_eq!(schema.apis[0].response_declaration(), "Page<User>");
+        assert!(!schema.apis[1].paginated);
+        assert_eq!(schema.apis[1].response, "PageView");
+
+        let csv = input.replacen("response: Page<User>", "response: Page<User>\n produces: \"text/csv\"", 1);
+        assert!(Parser::parse_string(&csv).is_err());
+    }
 }
@@ -0,0 +1,410 @@
+//! The calls a handler run makes through the engine's [`KvStore`] and
+//! [`http_client`], recorded for `rohas replay` and answered from the
+//! recording when the run is replayed.
+//!
+//! A [`CallTape`] is attached to a run with [`CallTape::scope`]. Rust
+//! handlers reach it through a task-local, so calls from tasks they spawn
+//! are not on it; the Python and V8 runtimes install it on the blocking
+//! thread their handlers run on with [`CallTape::enter`].
+//!
+//! [`KvStore`]: crate::kv::KvStore
+//! [`http_client`]: crate::http_client
+
+use crate::error::{Result, RuntimeError};
+use crate::http_client::{ClientRequest, ClientResponse};
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+/// One call of a handler run, with what it returned.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum OutboundCall {
+    KvGet {
+        key: String,
+        value: Option<Value>,
+    },
+    KvSet {
+        key: String,
+        value: Value,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ttl_ms: Option<u64>,
+    },
+    KvDelete {
+        key: String,
+        existed: bool,
+    },
+    Http {
+        request: ClientRequest,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        response: Option<RecordedResponse>,
+        /// Why no response was received.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+}
+
+impl OutboundCall {
+    /// What the call was, e.g. `kv get order:1` or `http POST https://...`.
+    pub fn describe(&self) -> String {
+        match self {
+            OutboundCall::KvGet { key, .. } => format!("kv get {}", key),
+            OutboundCall::KvSet { key, .. } => format!("kv set {}", key),
+            OutboundCall::KvDelete { key, .. } => format!("kv delete {}", key),
+            OutboundCall::Http { request, .. } => format!("http {} {}", request.method.to_uppercase(), request.url),
+        }
+    }
+}
+
+/// A [`ClientResponse`] as kept in a recording; the body is base64 so that
+/// binary responses survive.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub status: u16,
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+impl From<&ClientResponse> for RecordedResponse {
+    fn from(response: &ClientResponse) -> Self {
+        Self {
+            status: response.status,
+            headers: response.headers.clone(),
+            body: base64::engine::general_purpose::STANDARD.encode(&response.body),
+        }
+    }
+}
+
+impl RecordedResponse {
+    fn to_response(&self) -> ClientResponse {
+        ClientResponse {
+            status: self.status,
+            headers: self.headers.clone(),
+            body: base64::engine::general_purpose::STANDARD
+                .decode(&self.body)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Where the calls of a handler run go.
+#[derive(Debug, Clone)]
+pub enum CallTape {
+    /// Calls reach the store and the network, and are appended here.
+    Record(Arc<Mutex<Vec<OutboundCall>>>),
+    /// Calls are answered from a recording and never reach the store or
+    /// the network.
+    Replay(Arc<Mutex<Replay>>),
+}
+
+/// The recorded calls a replayed run has not made yet, and the calls it
+/// made that the recording had no answer to.
+#[derive(Debug, Default)]
+pub struct Replay {
+    recorded: Vec<OutboundCall>,
+    unanswered: Vec<String>,
+}
+
+tokio::task_local! {
+    static TASK_TAPE: CallTape;
+}
+
+thread_local! {
+    static THREAD_TAPE: RefCell<Option<CallTape>> = const { RefCell::new(None) };
+}
+
+/// Takes the tape off its thread when dropped.
+pub struct ThreadTapeGuard(Option<CallTape>);
+
+impl Drop for ThreadTapeGuard {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        THREAD_TAPE.with(|tape| *tape.borrow_mut() = previous);
+    }
+}
+
+impl CallTape {
+    pub fn recording() -> Self {
+        CallTape::Record(Arc::default())
+    }
+
+    /// Answers calls from `recorded`, in the order they were made.
+    pub fn replaying(recorded: Vec<OutboundCall>) -> Self {
+        CallTape::Replay(Arc::new(Mutex::new(Replay {
+            recorded,
+            unanswered: Vec::new(),
+        })))
+    }
+
+    /// The tape of the handler run in progress on this task or thread.
+    pub fn current() -> Option<CallTape> {
+        TASK_TAPE
+            .try_with(Clone::clone)
+            .ok()
+            .or_else(|| THREAD_TAPE.with(|tape| tape.borrow().clone()))
+    }
+
+    /// Runs `future` with its calls going to this tape.
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        TASK_TAPE.scope(self, future).await
+    }
+
+    /// Sends the calls made on this thread to the tape until the guard is
+    /// dropped.
+    pub fn enter(self) -> ThreadTapeGuard {
+        ThreadTapeGuard(THREAD_TAPE.with(|tape| tape.borrow_mut().replace(self)))
+    }
+
+    /// The calls recorded so far.
+    pub fn calls(&self) -> Vec<OutboundCall> {
+        match self {
+            CallTape::Record(calls) => calls.lock().unwrap().clone(),
+            CallTape::Replay(_) => Vec::new(),
+        }
+    }
+
+    /// The calls of a replayed run that the recording had no answer to.
+    pub fn unanswered(&self) -> Vec<String> {
+        match self {
+            CallTape::Record(_) => Vec::new(),
+            CallTape::Replay(replay) => replay.lock().unwrap().unanswered.clone(),
+        }
+    }
+
+    fn push(calls: &Mutex<Vec<OutboundCall>>, call: OutboundCall) {
+        calls.lock().unwrap().push(call);
+    }
+
+    /// Takes the first recorded call `answer` accepts, noting `call` as
+    /// unanswered when there is none.
+    fn answer<T>(replay: &Mutex<Replay>, call: &OutboundCall, answer: impl Fn(&OutboundCall) -> Option<T>) -> Option<T> {
+        let mut replay = replay.lock().unwrap();
+        let found = replay
+            .recorded
+            .iter()
+            .enumerate()
+            .find_map(|(index, recorded)| answer(recorded).map(|value| (index, value)));
+        match found {
+            Some((index, value)) => {
+                replay.recorded.remove(index);
+                Some(value)
+            }
+            None => {
+                replay.unanswered.push(call.describe());
+                None
+            }
+        }
+    }
+}
+
+pub(crate) fn kv_get(key: &str, live: impl FnOnce() -> Option<Value>) -> Option<Value> {
+    match CallTape::current() {
+        None => live(),
+        Some(CallTape::Record(calls)) => {
+            let value = live();
+            CallTape::push(&calls, OutboundCall::KvGet { key: key.to_string(), value: value.clone() });
+            value
+        }
+        Some(CallTape::Replay(replay)) => {
+            let call = OutboundCall::KvGet { key: key.to_string(), value: None };
+            CallTape::answer(&replay, &call, |recorded| match recorded {
+                OutboundCall::KvGet { key: k, value } if k == key => Some(value.clone()),
+                _ => None,
+            })
+            .flatten()
+        }
+    }
+}
+
+/// Whether the write should reach the store; a replayed run only checks
+/// that it was recorded.
+pub(crate) fn kv_set(key: &str, value: &Value, ttl_ms: Option<u64>) -> bool {
+    match CallTape::current() {
+        None => true,
+        Some(CallTape::Record(calls)) => {
+            CallTape::push(&calls, OutboundCall::KvSet { key: key.to_string(), value: value.clone(), ttl_ms });
+            true
+        }
+        Some(CallTape::Replay(replay)) => {
+            let call = OutboundCall::KvSet { key: key.to_string(), value: value.clone(), ttl_ms };
+            CallTape::answer(&replay, &call, |recorded| match recorded {
+                OutboundCall::KvSet { key: k, .. } if k == key => Some(()),
+                _ => None,
+            });
+            false
+        }
+    }
+}
+
+pub(crate) fn kv_delete(key: &str, live: impl FnOnce() -> bool) -> bool {
+    match CallTape::current() {
+        None => live(),
+        Some(CallTape::Record(calls)) => {
+            let existed = live();
+            CallTape::push(&calls, OutboundCall::KvDelete { key: key.to_string(), existed });
+            existed
+        }
+        Some(CallTape::Replay(replay)) => {
+            let call = OutboundCall::KvDelete { key: key.to_string(), existed: false };
+            CallTape::answer(&replay, &call, |recorded| match recorded {
+                OutboundCall::KvDelete { key: k, existed } if k == key => Some(*existed),
+                _ => None,
+            })
+            .unwrap_or(false)
+        }
+    }
+}
+
+pub(crate) async fn http<F>(request: ClientRequest, live: F) -> Result<ClientResponse>
+where
+    F: Future<Output = Result<ClientResponse>>,
+{
+    match CallTape::current() {
+        None => live.await,
+        Some(CallTape::Record(calls)) => {
+            let result = live.await;
+            let (response, error) = match &result {
+                Ok(response) => (Some(response.into()), None),
+                Err(e) => (None, Some(e.to_string())),
+            };
+            CallTape::push(&calls, OutboundCall::Http { request, response, error });
+            result
+        }
+        Some(CallTape::Replay(replay)) => {
+            let call = OutboundCall::Http { request: request.clone(), response: None, error: None };
+            let answer = CallTape::answer(&replay, &call, |recorded| match recorded {
+                OutboundCall::Http { request: r, response, error }
+                    if r.method.eq_ignore_ascii_case(&request.method) && r.url == request.url =>
+                {
+                    Some((response.clone(), error.clone()))
+                }
+                _ => None,
+            });
+            match answer {
+                Some((Some(response), _)) => Ok(response.to_response()),
+                Some((None, error)) => Err(RuntimeError::Http(error.unwrap_or_default())),
+                None => Err(RuntimeError::Http(format!(
+                    "no recorded response to {} {} to replay",
+                    request.method.to_uppercase(),
+                    request.url
+                ))),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kv::KvStore;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_records_kv_calls() {
+        let store = KvStore::new();
+        store.set("order:1", json!({"status": "paid"}), None);
+        let tape = CallTape::recording();
+
+        tape.clone()
+            .scope(async {
+                assert_eq!(store.get("order:1"), Some(json!({"status": "paid"})));
+                store.set("order:2", json!(2), Some(std::time::Duration::from_secs(1)));
+                assert!(!store.delete("missing"));
+            })
+            .await;
+
+        assert_eq!(
+            tape.calls(),
+            vec![
+                OutboundCall::KvGet { key: "order:1".to_string(), value: Some(json!({"status": "paid"})) },
+                OutboundCall::KvSet { key: "order:2".to_string(), value: json!(2), ttl_ms: Some(1000) },
+                OutboundCall::KvDelete { key: "missing".to_string(), existed: false },
+            ]
+        );
+        // Calls outside the scope are not recorded.
+        store.get("order:1");
+        assert_eq!(tape.calls().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_replays_kv_calls_without_touching_the_store() {
+        let store = KvStore::new();
+        store.set("order:1", json!("live"), None);
+        let tape = CallTape::replaying(vec![
+            OutboundCall::KvDelete { key: "order:1".to_string(), existed: true },
+            OutboundCall::KvGet { key: "order:1".to_string(), value: Some(json!("recorded")) },
+            OutboundCall::KvSet { key: "order:1".to_string(), value: json!("new"), ttl_ms: None },
+        ]);
+
+        tape.clone()
+            .scope(async {
+                assert_eq!(store.get("order:1"), Some(json!("recorded")));
+                store.set("order:1", json!("new"), None);
+                assert!(store.delete("order:1"));
+                assert_eq!(store.get("order:1"), None);
+                store.set("order:9", json!(9), None);
+            })
+            .await;
+
+        assert_eq!(store.get("order:1"), Some(json!("live")));
+        assert_eq!(store.get("order:9"), None);
+        assert_eq!(tape.unanswered(), ["kv get order:1", "kv set order:9"]);
+    }
+
+    #[tokio::test]
+    async fn test_replays_http_calls() {
+        let recorded = |url: &str, status: u16| OutboundCall::Http {
+            request: ClientRequest::new("POST", url),
+            response: Some(RecordedResponse::from(&ClientResponse {
+                status,
+                headers: HashMap::new(),
+                body: vec![0, 159, 146, 150],
+            })),
+            error: None,
+        };
+        let tape = CallTape::replaying(vec![
+            recorded("http://payments/charge", 201),
+            OutboundCall::Http {
+                request: ClientRequest::new("GET", "http://down"),
+                response: None,
+                error: Some("connection refused".to_string()),
+            },
+        ]);
+
+        let (charged, down, unrecorded) = tape
+            .clone()
+            .scope(async {
+                (
+                    crate::http_client::send(ClientRequest::new("post", "http://payments/charge")).await,
+                    crate::http_client::send(ClientRequest::new("GET", "http://down")).await,
+                    crate::http_client::send(ClientRequest::new("GET", "http://127.0.0.1:1")).await,
+                )
+            })
+            .await;
+
+        let charged = charged.unwrap();
+        assert_eq!(charged.status, 201);
+        assert_eq!(charged.body, vec![0, 159, 146, 150]);
+        assert!(down.unwrap_err().to_string().contains("connection refused"));
+        assert!(unrecorded.unwrap_err().to_string().contains("no recorded response"));
+        assert_eq!(tape.unanswered(), ["http GET http://127.0.0.1:1"]);
+    }
+
+    #[test]
+    fn test_thread_tape() {
+        let tape = CallTape::recording();
+        let store = KvStore::new();
+        {
+            let _guard = tape.clone().enter();
+            store.get("thread:1");
+        }
+        store.get("thread:2");
+
+        assert_eq!(tape.calls(), vec![OutboundCall::KvGet { key: "thread:1".to_string(), value: None }]);
+        assert!(CallTape::current().is_none());
+    }
+}
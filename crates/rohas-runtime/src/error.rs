@@ -27,9 +27,16 @@ pub enum RuntimeError {
 
     #[error("Invalid handler response: {0}")]
     InvalidResponse(String),
+
+    #[error("HTTP request failed: {0}")]
+    Http(String),
+
+    #[error("{0} handlers are not supported by this build; rebuild rohas with the `{1}` feature")]
+    RuntimeUnavailable(&'static str, &'static str),
 }
 
 // Implement conversion from pyo3::PyErr
+#[cfg(feature = "python")]
 impl From<pyo3::PyErr> for RuntimeError {
     fn from(err: pyo3::PyErr) -> Self {
         RuntimeError::PythonError(err.to_string())
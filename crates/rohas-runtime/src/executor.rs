@@ -1,12 +1,16 @@
 use crate::error::{Result, RuntimeError};
 use crate::handler::{Handler, HandlerContext, HandlerResult};
+#[cfg(feature = "node")]
 use crate::node_runtime::NodeRuntime;
+#[cfg(feature = "python")]
+use crate::python_env::PythonEnvironment;
+#[cfg(feature = "python")]
 use crate::python_runtime::PythonRuntime;
 use crate::rust_runtime::RustRuntime;
 use crate::{Language, RuntimeConfig};
 use rohas_codegen::templates;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{debug, info};
@@ -14,31 +18,56 @@ use tracing::{debug, info};
 pub struct Executor {
     config: RuntimeConfig,
     handlers: Arc<RwLock<HashMap<String, Arc<dyn Handler>>>>,
+    #[cfg(feature = "python")]
     python_runtime: Arc<PythonRuntime>,
+    #[cfg(feature = "node")]
     node_runtime: Arc<NodeRuntime>,
     rust_runtime: Arc<RustRuntime>,
 }
 
 impl Executor {
     pub fn new(config: RuntimeConfig) -> Self {
-        let mut python_runtime = PythonRuntime::new().expect("Failed to initialize Python runtime");
-        python_runtime.set_project_root(config.project_root.clone());
-        let python_runtime = Arc::new(python_runtime);
+        #[cfg(feature = "python")]
+        let python_runtime = {
+            let mut python_runtime =
+                PythonRuntime::new().expect("Failed to initialize Python runtime");
+            python_runtime.set_project_root(config.project_root.clone());
+            if config.language == Language::Python {
+                match PythonEnvironment::detect(&config.project_root, config.python_interpreter.as_deref()) {
+                    Ok(Some(environment)) => {
+                        if let Err(e) = python_runtime.use_environment(environment) {
+                            tracing::warn!("{}", e);
+                        }
+                    }
+                    Ok(None) => debug!("No Python environment found; using the embedded interpreter's packages"),
+                    Err(e) => tracing::warn!("Failed to inspect the project's Python environment: {}", e),
+                }
+            }
+            Arc::new(python_runtime)
+        };
 
-        let mut node_runtime = NodeRuntime::new().expect("Failed to initialize Node.js runtime");
-        node_runtime.set_project_root(config.project_root.clone());
-        let node_runtime = Arc::new(node_runtime);
+        #[cfg(feature = "node")]
+        let node_runtime = {
+            let mut node_runtime = NodeRuntime::new().expect("Failed to initialize Node.js runtime");
+            node_runtime.set_project_root(config.project_root.clone());
+            Arc::new(node_runtime)
+        };
 
         let mut rust_runtime = RustRuntime::new().expect("Failed to initialize Rust runtime");
         rust_runtime.set_project_root(config.project_root.clone());
         let rust_runtime = Arc::new(rust_runtime);
 
-        info!("Executor initialized with Python, Node.js, and Rust runtimes");
+        info!(
+            "Executor initialized with {} runtimes",
+            Self::runtimes().join(", ")
+        );
 
         let executor = Self {
             config: config.clone(),
             handlers: Arc::new(RwLock::new(HashMap::new())),
+            #[cfg(feature = "python")]
             python_runtime,
+            #[cfg(feature = "node")]
             node_runtime,
             rust_runtime: rust_runtime.clone(),
         };
@@ -46,6 +75,18 @@ impl Executor {
         executor
     }
 
+    /// Names of the handler runtimes compiled into this build.
+    pub fn runtimes() -> Vec<&'static str> {
+        let mut runtimes = Vec::new();
+        if cfg!(feature = "python") {
+            runtimes.push("Python");
+        }
+        if cfg!(feature = "node") {
+            runtimes.push("Node.js");
+        }
+        runtimes.push("Rust");
+        runtimes
+    }
 
     pub async fn register_handler(&self, handler: Arc<dyn Handler>) {
         let name = handler.name().to_string();
@@ -69,32 +110,38 @@ impl Executor {
         payload: serde_json::Value,
         query_params: HashMap<String, String>,
     ) -> Result<HandlerResult> {
-        debug!("Executing handler: {}", handler_name);
-
         let mut context = HandlerContext::new(handler_name, payload);
         context.query_params = query_params;
-
-        {
-            let handlers = self.handlers.read().await;
-            if let Some(handler) = handlers.get(handler_name) {
-                return handler.execute(context.clone()).await;
-            }
-        }
-
-        self.execute_external_handler(context).await
+        self.execute_with_context(context).await
     }
 
+    /// Runs a handler, failing with [`RuntimeError::Timeout`] once it takes
+    /// longer than `timeout_seconds`, whatever its language.
     pub async fn execute_with_context(&self, context: HandlerContext) -> Result<HandlerResult> {
         debug!("Executing handler: {}", context.handler_name);
 
-        {
-            let handlers = self.handlers.read().await;
-            if let Some(handler) = handlers.get(&context.handler_name) {
-                return handler.execute(context.clone()).await;
-            }
+        let timeout = std::time::Duration::from_secs(self.config.timeout_seconds);
+        tokio::time::timeout(timeout, self.execute_without_timeout(context))
+            .await
+            .map_err(|_| RuntimeError::Timeout(self.config.timeout_seconds))?
+    }
+
+    async fn execute_without_timeout(&self, context: HandlerContext) -> Result<HandlerResult> {
+        let handler = self.handlers.read().await.get(&context.handler_name).cloned();
+        match handler {
+            Some(handler) => handler.execute(context).await,
+            None => self.execute_external_handler(context).await,
+        }
+    }
+
+    /// Returns true when the handler is registered in-process or its file can
+    /// be found in the project.
+    pub async fn has_handler(&self, handler_name: &str) -> bool {
+        if self.handlers.read().await.contains_key(handler_name) {
+            return true;
         }
 
-        self.execute_external_handler(context).await
+        self.resolve_handler_path(handler_name).is_ok()
     }
 
     async fn execute_external_handler(&self, context: HandlerContext) -> Result<HandlerResult> {
@@ -115,58 +162,33 @@ impl Executor {
                 res.execution_time_ms = execution_time_ms;
                 Ok(res)
             }
+            // Kept as an error so that retry policies can tell timeouts apart.
+            Err(e @ RuntimeError::Timeout(_)) => Err(e),
             Err(e) => Ok(HandlerResult::error(e.to_string(), execution_time_ms)),
         }
     }
 
     fn resolve_handler_path(&self, handler_name: &str) -> Result<PathBuf> {
-        let handlers_dir = self.config.project_root.join("src/handlers");
-        let middlewares_dir = self.config.project_root.join("src/middlewares");
+        let handlers_dir = self.config.project_root.join(&self.config.handlers_dir);
+        let middlewares_dir = self.config.project_root.join(&self.config.middlewares_dir);
 
         let snake_case_name = templates::to_snake_case(handler_name);
-
-        let possible_paths = [
-            middlewares_dir.join(format!(
-                "{}.{}",
-                snake_case_name,
-                self.config.language.file_extension()
-            )),
-            middlewares_dir.join(format!(
-                "{}.{}",
-                handler_name,
-                self.config.language.file_extension()
-            )),
-            handlers_dir.join(format!(
-                "api/{}.{}",
-                handler_name,
-                self.config.language.file_extension()
-            )),
-            handlers_dir.join(format!(
-                "events/{}.{}",
-                handler_name,
-                self.config.language.file_extension()
-            )),
-            handlers_dir.join(format!(
-                "websockets/{}.{}",
-                handler_name,
-                self.config.language.file_extension()
-            )),
-            handlers_dir.join(format!(
-                "cron/{}.{}",
-                snake_case_name,
-                self.config.language.file_extension()
-            )),
-            handlers_dir.join(format!(
-                "cron/{}.{}",
-                handler_name,
-                self.config.language.file_extension()
-            )),
-            handlers_dir.join(format!(
-                "{}.{}",
-                handler_name,
-                self.config.language.file_extension()
-            )),
-        ];
+        let ext = self.config.language.file_extension();
+
+        // Files are named as the handler, or in snake_case with
+        // `file_naming = "snake_case"`.
+        let mut possible_paths = Vec::new();
+        for dir in [
+            middlewares_dir,
+            handlers_dir.join("api"),
+            handlers_dir.join("events"),
+            handlers_dir.join("websockets"),
+            handlers_dir.join("cron"),
+            handlers_dir,
+        ] {
+            possible_paths.push(dir.join(format!("{}.{}", handler_name, ext)));
+            possible_paths.push(dir.join(format!("{}.{}", snake_case_name, ext)));
+        }
 
         for path in &possible_paths {
             if path.exists() {
@@ -180,6 +202,7 @@ impl Executor {
         )))
     }
 
+    #[cfg(feature = "node")]
     async fn execute_typescript(
         &self,
         handler_path: &PathBuf,
@@ -194,6 +217,16 @@ impl Executor {
             .await
     }
 
+    #[cfg(not(feature = "node"))]
+    async fn execute_typescript(
+        &self,
+        _handler_path: &PathBuf,
+        _context: &HandlerContext,
+    ) -> Result<HandlerResult> {
+        Err(RuntimeError::RuntimeUnavailable("TypeScript", "node"))
+    }
+
+    #[cfg(feature = "python")]
     async fn execute_python(
         &self,
         handler_path: &PathBuf,
@@ -205,6 +238,15 @@ impl Executor {
             .await
     }
 
+    #[cfg(not(feature = "python"))]
+    async fn execute_python(
+        &self,
+        _handler_path: &PathBuf,
+        _context: &HandlerContext,
+    ) -> Result<HandlerResult> {
+        Err(RuntimeError::RuntimeUnavailable("Python", "python"))
+    }
+
     async fn execute_rust(
         &self,
         handler_path: &PathBuf,
@@ -221,22 +263,36 @@ impl Executor {
         handlers.keys().cloned().collect()
     }
 
-    pub async fn reload_python_module(&self, module_name: &str) -> Result<()> {
-        self.python_runtime.reload_module(module_name).await
+    /// Reloads Python handlers after the project file `path` changed.
+    #[cfg(feature = "python")]
+    pub async fn reload_python_module(&self, path: &Path) -> Result<()> {
+        self.python_runtime.reload_module(path).await
     }
 
+    #[cfg(not(feature = "python"))]
+    pub async fn reload_python_module(&self, _path: &Path) -> Result<()> {
+        Err(RuntimeError::RuntimeUnavailable("Python", "python"))
+    }
+
+    #[cfg(feature = "node")]
     pub async fn reload_node_module(&self, module_name: &str) -> Result<()> {
         self.node_runtime.reload_module(module_name).await
     }
 
+    #[cfg(not(feature = "node"))]
+    pub async fn reload_node_module(&self, _module_name: &str) -> Result<()> {
+        Err(RuntimeError::RuntimeUnavailable("TypeScript", "node"))
+    }
+
     pub async fn clear_handler_cache(&self) -> Result<()> {
         match self.config.language {
             Language::TypeScript => {
+                #[cfg(feature = "node")]
                 self.node_runtime.clear_cache().await?;
             }
             Language::Python => {
-                // @TODO Python runtime doesn't cache modules the same way
-                // Module reloading is handled differently in pyo3
+                #[cfg(feature = "python")]
+                self.python_runtime.clear_cache().await?;
             }
             Language::Rust => {
                 self.rust_runtime.clear_handlers().await;
@@ -311,4 +367,31 @@ mod tests {
 
         assert!(result.success);
     }
+
+    struct SlowHandler;
+
+    #[async_trait::async_trait]
+    impl Handler for SlowHandler {
+        async fn execute(&self, _context: HandlerContext) -> Result<HandlerResult> {
+            tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+            Ok(HandlerResult::success(serde_json::json!({}), 0))
+        }
+
+        fn name(&self) -> &str {
+            "slow_handler"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_times_out() {
+        let executor = Executor::new(RuntimeConfig {
+            language: Language::Rust,
+            timeout_seconds: 1,
+            ..RuntimeConfig::default()
+        });
+        executor.register_handler(Arc::new(SlowHandler)).await;
+
+        let result = executor.execute("slow_handler", serde_json::json!({})).await;
+        assert!(matches!(result, Err(RuntimeError::Timeout(1))));
+    }
 }
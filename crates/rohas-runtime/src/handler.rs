@@ -12,9 +12,57 @@ pub struct HandlerContext {
     pub metadata: HashMap<String, String>,
 
     pub timestamp: String,
+
+    /// Body of an API that consumes a raw media type; `payload` then only
+    /// holds the path parameters.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_body: Option<RawBody>,
+
+    /// Cookies sent with an API request, by name.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub cookies: HashMap<String, String>,
+}
+
+/// Request body passed to handlers as-is instead of being decoded as JSON.
+/// Bytes serialize as a base64 string rather than an array of numbers.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RawBody {
+    Bytes(#[serde(with = "base64_bytes")] Vec<u8>),
+    Text(String),
+}
+
+/// Base64 for [`RawBody::Bytes`], still reading the arrays of numbers
+/// earlier versions wrote, e.g. in recordings.
+mod base64_bytes {
+    use base64::{engine::general_purpose, Engine as _};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&general_purpose::STANDARD.encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Encoded {
+            Base64(String),
+            Array(Vec<u8>),
+        }
+
+        match Encoded::deserialize(deserializer)? {
+            Encoded::Base64(text) => general_purpose::STANDARD
+                .decode(text)
+                .map_err(serde::de::Error::custom),
+            Encoded::Array(bytes) => Ok(bytes),
+        }
+    }
 }
 
 impl HandlerContext {
+    /// Metadata key of the trace the handler runs in.
+    pub const TRACE_ID_KEY: &'static str = "trace_id";
+
     pub fn new(handler_name: impl Into<String>, payload: serde_json::Value) -> Self {
         Self {
             handler_name: handler_name.into(),
@@ -22,9 +70,20 @@ impl HandlerContext {
             query_params: HashMap::new(),
             metadata: HashMap::new(),
             timestamp: chrono::Utc::now().to_rfc3339(),
+            raw_body: None,
+            cookies: HashMap::new(),
         }
     }
 
+    pub fn trace_id(&self) -> Option<&str> {
+        self.metadata.get(Self::TRACE_ID_KEY).map(String::as_str)
+    }
+
+    pub fn with_raw_body(mut self, body: RawBody) -> Self {
+        self.raw_body = Some(body);
+        self
+    }
+
     pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
         self.metadata.insert(key.into(), value.into());
         self
@@ -42,6 +101,91 @@ pub struct TriggeredEvent {
     pub payload: serde_json::Value,
 }
 
+/// Status and headers set with `state.response()`, applied to the HTTP
+/// response of an API handler. Header names are kept lowercase.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HttpResponse {
+    /// Replaces the 200 status of a successful call.
+    #[serde(default)]
+    pub status: Option<u16>,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// Sent as `Set-Cookie` headers.
+    #[serde(default)]
+    pub cookies: Vec<SetCookie>,
+}
+
+impl HttpResponse {
+    pub fn status(&mut self, status: u16) -> &mut Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Sets a header, replacing an earlier value of it.
+    pub fn header(&mut self, name: impl Into<String>, value: impl Into<String>) -> &mut Self {
+        self.headers.insert(name.into().to_ascii_lowercase(), value.into());
+        self
+    }
+
+    /// Redirects to `location` with a 302.
+    pub fn redirect(&mut self, location: impl Into<String>) -> &mut Self {
+        self.status(302).header("location", location)
+    }
+
+    /// Redirects to `location` with a 308, which clients may cache.
+    pub fn permanent_redirect(&mut self, location: impl Into<String>) -> &mut Self {
+        self.status(308).header("location", location)
+    }
+}
+
+/// A cookie set with `state.cookies().set(name, value, options)`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SetCookie {
+    pub name: String,
+    pub value: String,
+    #[serde(default)]
+    pub options: CookieOptions,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CookieOptions {
+    /// `/` when unset.
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub domain: Option<String>,
+    /// Seconds until the cookie expires; 0 removes it. Unset for a cookie
+    /// that lasts the browser session.
+    #[serde(default)]
+    pub max_age: Option<i64>,
+    #[serde(default)]
+    pub secure: bool,
+    #[serde(default)]
+    pub http_only: bool,
+    #[serde(default)]
+    pub same_site: Option<SameSite>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SameSite {
+    #[serde(alias = "strict")]
+    Strict,
+    #[serde(alias = "lax")]
+    Lax,
+    #[serde(alias = "none")]
+    None,
+}
+
+/// A handler call requested with `state.schedule(handler, when, payload)`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleRequest {
+    pub handler: String,
+    /// RFC 3339 time, or a delay from now such as `30s`.
+    pub when: String,
+    #[serde(default)]
+    pub payload: serde_json::Value,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HandlerResult {
     pub success: bool,
@@ -57,6 +201,13 @@ pub struct HandlerResult {
 
     #[serde(default)]
     pub auto_trigger_payloads: std::collections::HashMap<String, serde_json::Value>,
+
+    #[serde(default)]
+    pub scheduled: Vec<ScheduleRequest>,
+
+    /// Status and headers an API handler set on its HTTP response.
+    #[serde(default)]
+    pub response: HttpResponse,
 }
 
 impl HandlerResult {
@@ -68,6 +219,8 @@ impl HandlerResult {
             execution_time_ms,
             triggers: Vec::new(),
             auto_trigger_payloads: std::collections::HashMap::new(),
+            scheduled: Vec::new(),
+            response: HttpResponse::default(),
         }
     }
 
@@ -79,6 +232,8 @@ impl HandlerResult {
             execution_time_ms,
             triggers: Vec::new(),
             auto_trigger_payloads: std::collections::HashMap::new(),
+            scheduled: Vec::new(),
+            response: HttpResponse::default(),
         }
     }
 
@@ -94,6 +249,25 @@ impl HandlerResult {
         self
     }
 
+    pub fn with_scheduled(
+        mut self,
+        handler: impl Into<String>,
+        when: impl Into<String>,
+        payload: serde_json::Value,
+    ) -> Self {
+        self.scheduled.push(ScheduleRequest {
+            handler: handler.into(),
+            when: when.into(),
+            payload,
+        });
+        self
+    }
+
+    pub fn with_response(mut self, response: HttpResponse) -> Self {
+        self.response = response;
+        self
+    }
+
     pub fn with_auto_trigger_payload(
         mut self,
         event_name: impl Into<String>,
@@ -111,3 +285,20 @@ pub trait Handler: Send + Sync {
 
     fn name(&self) -> &str;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_raw_bytes_serialize_as_base64() {
+        let body = RawBody::Bytes(vec![0, 1, 254, 255]);
+        let json = serde_json::to_value(&body).unwrap();
+        assert_eq!(json, serde_json::json!({ "bytes": "AAH+/w==" }));
+        assert_eq!(serde_json::from_value::<RawBody>(json).unwrap(), body);
+
+        let legacy = serde_json::json!({ "bytes": [0, 1, 254, 255] });
+        assert_eq!(serde_json::from_value::<RawBody>(legacy).unwrap(), body);
+        assert!(serde_json::from_value::<RawBody>(serde_json::json!({ "bytes": "not base64!" })).is_err());
+    }
+}
@@ -0,0 +1,40 @@
+//! Records that Python and TypeScript handlers log through `state.logger`.
+//!
+//! They are emitted as tracing events with the [`TARGET`] target, so the
+//! engine's log store keeps them next to the logs of Rust handlers. The
+//! handler's own fields travel as one JSON object in `handler_fields`, which
+//! the store expands back into separate fields.
+
+use tracing::Level;
+
+pub const TARGET: &str = "rohas::handler";
+
+/// Field holding the JSON object of the handler's own fields.
+pub const FIELDS: &str = "handler_fields";
+
+pub fn emit(level: &str, handler: &str, trace_id: Option<&str>, message: &str, fields: &serde_json::Value) {
+    let trace_id = trace_id.unwrap_or_default();
+    let fields = match fields {
+        serde_json::Value::Object(object) if !object.is_empty() => fields.to_string(),
+        _ => String::new(),
+    };
+    macro_rules! emit_at {
+        ($level:expr) => {
+            tracing::event!(
+                target: TARGET,
+                $level,
+                handler = %handler,
+                trace_id = %trace_id,
+                handler_fields = %fields,
+                message = %message
+            )
+        };
+    }
+    match level {
+        "error" => emit_at!(Level::ERROR),
+        "warn" | "warning" => emit_at!(Level::WARN),
+        "debug" => emit_at!(Level::DEBUG),
+        "trace" => emit_at!(Level::TRACE),
+        _ => emit_at!(Level::INFO),
+    }
+}
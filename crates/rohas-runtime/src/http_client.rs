@@ -0,0 +1,187 @@
+//! Outbound HTTP requests of handlers. Rust handlers send them through
+//! `rohas_sdk::HttpClient`, and the V8 runtime through this module, as
+//! TypeScript handlers have no `fetch` there, and the Python runtime
+//! through `_rohas.http_send`. Requests made during a recorded or replayed
+//! run go through its [`CallTape`](crate::calls::CallTape).
+
+use crate::error::{Result, RuntimeError};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// Timeout of requests that do not set their own.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClientRequest {
+    #[serde(default = "default_method")]
+    pub method: String,
+    pub url: String,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+fn default_method() -> String {
+    "GET".to_string()
+}
+
+impl ClientRequest {
+    pub fn new(method: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            method: method.into(),
+            url: url.into(),
+            headers: HashMap::new(),
+            body: None,
+            timeout_ms: None,
+        }
+    }
+
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.insert(name.into(), value.into());
+        self
+    }
+
+    pub fn body(mut self, body: impl Into<String>) -> Self {
+        self.body = Some(body.into());
+        self
+    }
+
+    /// Sends `value` as a JSON body.
+    pub fn json<T: Serialize>(self, value: &T) -> Result<Self> {
+        let body = serde_json::to_string(value)?;
+        Ok(self.header("content-type", "application/json").body(body))
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout_ms = Some(timeout.as_millis() as u64);
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientResponse {
+    pub status: u16,
+    /// Header names are lowercase.
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+impl ClientResponse {
+    /// Whether the status is 2xx.
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T> {
+        Ok(serde_json::from_slice(&self.body)?)
+    }
+}
+
+fn client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .user_agent(concat!("rohas/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .unwrap_or_default()
+    })
+}
+
+/// Sends `request`. Responses of any status are returned; only failures to
+/// get one, such as timeouts and refused connections, are errors.
+pub async fn send(request: ClientRequest) -> Result<ClientResponse> {
+    crate::calls::http(request.clone(), send_live(request)).await
+}
+
+async fn send_live(request: ClientRequest) -> Result<ClientResponse> {
+    let method = reqwest::Method::from_bytes(request.method.to_uppercase().as_bytes())
+        .map_err(|_| RuntimeError::Http(format!("invalid method {}", request.method)))?;
+    let timeout = request.timeout_ms.map_or(DEFAULT_TIMEOUT, Duration::from_millis);
+
+    let mut builder = client().request(method, &request.url).timeout(timeout);
+    for (name, value) in &request.headers {
+        builder = builder.header(name, value);
+    }
+    if let Some(body) = request.body {
+        builder = builder.body(body);
+    }
+
+    let response = builder
+        .send()
+        .await
+        .map_err(|e| RuntimeError::Http(format!("{} {}: {}", request.method, request.url, e)))?;
+    let status = response.status().as_u16();
+    let headers = response
+        .headers()
+        .iter()
+        .filter_map(|(name, value)| Some((name.as_str().to_string(), value.to_str().ok()?.to_string())))
+        .collect();
+    let body = response
+        .bytes()
+        .await
+        .map_err(|e| RuntimeError::Http(format!("{} {}: {}", request.method, request.url, e)))?;
+
+    Ok(ClientResponse {
+        status,
+        headers,
+        body: body.to_vec(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Answers one request with `response` and hands back what it received.
+    async fn serve_once(response: &'static str) -> (String, tokio::task::JoinHandle<String>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut received = vec![0; 4096];
+            let read = socket.read(&mut received).await.unwrap();
+            socket.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&received[..read]).into_owned()
+        });
+        (url, server)
+    }
+
+    #[tokio::test]
+    async fn test_send_returns_status_headers_and_body() {
+        let (url, server) = serve_once(
+            "HTTP/1.1 404 Not Found\r\nContent-Type: application/json\r\nContent-Length: 17\r\nConnection: close\r\n\r\n{\"error\":\"nope\"}\n",
+        )
+        .await;
+
+        let request = ClientRequest::new("post", format!("{}/orders", url))
+            .json(&serde_json::json!({"id": 1}))
+            .unwrap();
+        let response = send(request).await.unwrap();
+
+        assert_eq!(response.status, 404);
+        assert!(!response.is_success());
+        assert_eq!(response.headers["content-type"], "application/json");
+        assert_eq!(response.json::<serde_json::Value>().unwrap(), serde_json::json!({"error": "nope"}));
+
+        let received = server.await.unwrap();
+        assert!(received.starts_with("POST /orders HTTP/1.1"));
+        assert!(received.ends_with("{\"id\":1}"));
+    }
+
+    #[tokio::test]
+    async fn test_send_rejects_invalid_methods() {
+        let request = ClientRequest::new("NOT A METHOD", "http://127.0.0.1:1");
+        assert!(matches!(send(request).await, Err(RuntimeError::Http(_))));
+    }
+}
@@ -0,0 +1,132 @@
+//! Key-value store shared by the handlers of an engine process.
+//!
+//! Values are JSON and live in memory until the process exits or their TTL
+//! runs out, which suits caches, counters and idempotency keys rather than
+//! data that must survive a restart. Rust handlers reach it through
+//! `rohas_sdk::Kv`; the Python and V8 runtimes bridge their SDKs to it.
+//! Calls made during a recorded or replayed run go through its
+//! [`CallTape`](crate::calls::CallTape).
+
+use crate::calls;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Writes between sweeps of expired entries that were never read again.
+const SWEEP_INTERVAL: u64 = 256;
+
+#[derive(Debug, Default)]
+pub struct KvStore {
+    inner: Mutex<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    entries: HashMap<String, Entry>,
+    writes: u64,
+}
+
+#[derive(Debug)]
+struct Entry {
+    value: Value,
+    expires_at: Option<Instant>,
+}
+
+impl Entry {
+    fn expired(&self, now: Instant) -> bool {
+        self.expires_at.is_some_and(|at| at <= now)
+    }
+}
+
+impl KvStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The store of this process, shared by every runtime.
+    pub fn global() -> &'static KvStore {
+        static STORE: OnceLock<KvStore> = OnceLock::new();
+        STORE.get_or_init(KvStore::new)
+    }
+
+    pub fn get(&self, key: &str) -> Option<Value> {
+        calls::kv_get(key, || {
+            let mut inner = self.inner.lock().unwrap();
+            match inner.entries.get(key) {
+                Some(entry) if entry.expired(Instant::now()) => {
+                    inner.entries.remove(key);
+                    None
+                }
+                Some(entry) => Some(entry.value.clone()),
+                None => None,
+            }
+        })
+    }
+
+    /// Stores `value` under `key`, replacing an earlier value. With a `ttl`
+    /// the key is gone once it has passed.
+    pub fn set(&self, key: impl Into<String>, value: Value, ttl: Option<Duration>) {
+        let key = key.into();
+        if !calls::kv_set(&key, &value, ttl.map(|ttl| ttl.as_millis() as u64)) {
+            return;
+        }
+        let now = Instant::now();
+        let mut inner = self.inner.lock().unwrap();
+        inner.writes += 1;
+        if inner.writes.is_multiple_of(SWEEP_INTERVAL) {
+            inner.entries.retain(|_, entry| !entry.expired(now));
+        }
+        inner.entries.insert(
+            key,
+            Entry {
+                value,
+                expires_at: ttl.map(|ttl| now + ttl),
+            },
+        );
+    }
+
+    /// Removes `key`, returning whether it held a value.
+    pub fn delete(&self, key: &str) -> bool {
+        calls::kv_delete(key, || {
+            let mut inner = self.inner.lock().unwrap();
+            inner
+                .entries
+                .remove(key)
+                .is_some_and(|entry| !entry.expired(Instant::now()))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_set_get_delete() {
+        let store = KvStore::new();
+        assert_eq!(store.get("order:1"), None);
+
+        store.set("order:1", json!({"status": "paid"}), None);
+        assert_eq!(store.get("order:1"), Some(json!({"status": "paid"})));
+
+        store.set("order:1", json!({"status": "shipped"}), None);
+        assert_eq!(store.get("order:1"), Some(json!({"status": "shipped"})));
+
+        assert!(store.delete("order:1"));
+        assert!(!store.delete("order:1"));
+        assert_eq!(store.get("order:1"), None);
+    }
+
+    #[test]
+    fn test_ttl_expires_entries() {
+        let store = KvStore::new();
+        store.set("session", json!("abc"), Some(Duration::ZERO));
+        store.set("cart", json!([1, 2]), Some(Duration::from_secs(60)));
+
+        assert_eq!(store.get("session"), None);
+        assert!(!store.delete("session"));
+        assert_eq!(store.get("cart"), Some(json!([1, 2])));
+    }
+}
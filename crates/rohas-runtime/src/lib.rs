@@ -1,13 +1,28 @@
+pub mod calls;
 pub mod error;
 pub mod executor;
 pub mod handler;
+pub mod handler_log;
+pub mod http_client;
+pub mod kv;
+#[cfg(feature = "node")]
 pub mod node_runtime;
+pub mod python_env;
+#[cfg(feature = "python")]
 pub mod python_runtime;
 pub mod rust_runtime;
+#[cfg(feature = "node")]
+pub mod source_map;
 
+pub use calls::{CallTape, OutboundCall};
 pub use error::{Result, RuntimeError};
 pub use executor::Executor;
-pub use handler::{Handler, HandlerContext, HandlerResult};
+pub use http_client::{ClientRequest, ClientResponse};
+pub use kv::KvStore;
+pub use handler::{
+    CookieOptions, Handler, HandlerContext, HandlerResult, HttpResponse, RawBody, SameSite, ScheduleRequest, SetCookie,
+};
+pub use python_env::PythonEnvironment;
 pub use rust_runtime::RustRuntime;
 
 #[derive(Debug, Clone)]
@@ -15,6 +30,12 @@ pub struct RuntimeConfig {
     pub language: Language,
     pub project_root: std::path::PathBuf,
     pub timeout_seconds: u64,
+    /// Interpreter of the Python environment to import packages from;
+    /// detected from the project when unset.
+    pub python_interpreter: Option<std::path::PathBuf>,
+    /// Directories of handlers and middlewares, relative to the project root.
+    pub handlers_dir: std::path::PathBuf,
+    pub middlewares_dir: std::path::PathBuf,
 }
 
 impl Default for RuntimeConfig {
@@ -23,6 +44,9 @@ impl Default for RuntimeConfig {
             language: Language::TypeScript,
             project_root: std::env::current_dir().unwrap_or_default(),
             timeout_seconds: 30,
+            python_interpreter: None,
+            handlers_dir: std::path::PathBuf::from("src/handlers"),
+            middlewares_dir: std::path::PathBuf::from("src/middlewares"),
         }
     }
 }
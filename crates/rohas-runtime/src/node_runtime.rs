@@ -1,11 +1,16 @@
+use crate::calls::CallTape;
 use crate::error::Result;
 use crate::handler::{HandlerContext, HandlerResult};
+use crate::handler_log;
+use crate::http_client::{self, ClientRequest};
+use crate::kv::KvStore;
+use crate::source_map::SourceMap;
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use tracing::{debug, info};
-use v8::{Context, ContextScope, HandleScope, Script};
+use v8::{Context, ContextScope, HandleScope, MapFnTo, Script};
 
 static V8_PLATFORM: Lazy<()> = Lazy::new(|| {
     let platform = v8::new_default_platform(0, false).make_shared();
@@ -91,14 +96,23 @@ impl NodeRuntime {
             modules.insert(module_key.clone(), handler_code.clone());
         }
 
-        let result = tokio::task::spawn_blocking(move || {
-            Self::execute_js_code_sync(&handler_code, &context)
+        let tape = CallTape::current();
+        let (mut result, handler_code) = tokio::task::spawn_blocking(move || {
+            let _tape = tape.map(CallTape::enter);
+            Self::execute_js_code_sync(&handler_code, &context, &module_key).map(|result| (result, handler_code))
         })
         .await
         .map_err(|e| {
             crate::error::RuntimeError::ExecutionFailed(format!("Blocking task failed: {}", e))
         })??;
 
+        // Point stack positions in the compiled output at the .ts sources.
+        if let Some(error) = &result.error {
+            if let Some(map) = SourceMap::load(&absolute_path, &handler_code, self.project_root.as_deref()) {
+                result.error = Some(map.remap(error, &absolute_path.to_string_lossy()));
+            }
+        }
+
         let execution_time_ms = start.elapsed().as_millis() as u64;
         Ok(HandlerResult {
             execution_time_ms,
@@ -106,23 +120,49 @@ impl NodeRuntime {
         })
     }
 
-    fn execute_js_code_sync(handler_code: &str, context: &HandlerContext) -> Result<HandlerResult> {
+    /// Runs `handler_code` with stack positions reported as in the file named
+    /// `resource_name`, rather than in the wrapper around it.
+    fn execute_js_code_sync(
+        handler_code: &str,
+        context: &HandlerContext,
+        resource_name: &str,
+    ) -> Result<HandlerResult> {
         let context_json = serde_json::to_string(context)?;
         let handler_name = &context.handler_name;
 
         let wrapper = Self::generate_wrapper(handler_code, &context_json, handler_name);
+        let prefix = &wrapper[..wrapper.find(handler_code).unwrap_or(0)];
+        let line_offset = prefix.matches('\n').count() as i32;
 
         let isolate = &mut v8::Isolate::new(v8::CreateParams::default());
         let scope = std::pin::pin!(HandleScope::new(isolate));
         let scope = &mut scope.init();
         let v8_context = Context::new(scope, Default::default());
         let scope = &mut ContextScope::new(scope, v8_context);
+        Self::install_native_functions(scope, v8_context)?;
 
         let code = v8::String::new(scope, &wrapper).ok_or_else(|| {
             crate::error::RuntimeError::ExecutionFailed("Failed to create V8 string".into())
         })?;
 
-        let script = Script::compile(scope, code, None).ok_or_else(|| {
+        let resource_name = v8::String::new(scope, resource_name).ok_or_else(|| {
+            crate::error::RuntimeError::ExecutionFailed("Failed to create V8 string".into())
+        })?;
+        let origin = v8::ScriptOrigin::new(
+            scope,
+            resource_name.into(),
+            -line_offset,
+            0,
+            false,
+            0,
+            None,
+            false,
+            false,
+            false,
+            None,
+        );
+
+        let script = Script::compile(scope, code, Some(&origin)).ok_or_else(|| {
             crate::error::RuntimeError::ExecutionFailed("Failed to compile script".into())
         })?;
 
@@ -154,6 +194,8 @@ impl NodeRuntime {
                     execution_time_ms: 0,
                     triggers: Vec::new(),
                     auto_trigger_payloads: std::collections::HashMap::new(),
+                    scheduled: Vec::new(),
+                    response: crate::handler::HttpResponse::default(),
                 });
             }
         } else {
@@ -175,32 +217,8 @@ impl NodeRuntime {
                     log.get("handler").and_then(|v| v.as_str()),
                     log.get("message").and_then(|v| v.as_str()),
                 ) {
-                    // Convert fields to HashMap
-                    let mut field_map = std::collections::HashMap::new();
-                    if let Some(fields) = log.get("fields") {
-                        if let Some(fields_obj) = fields.as_object() {
-                            for (key, value) in fields_obj {
-                                field_map.insert(key.clone(), format!("{:?}", value));
-                            }
-                        }
-                    }
-                    
-                    // Emit tracing event
-                    let span = tracing::span!(
-                        tracing::Level::INFO,
-                        "handler_log",
-                        handler = %handler
-                    );
-                    let _enter = span.enter();
-                    
-                    match level {
-                        "error" => tracing::error!(message = %message, ?field_map),
-                        "warn" => tracing::warn!(message = %message, ?field_map),
-                        "info" => tracing::info!(message = %message, ?field_map),
-                        "debug" => tracing::debug!(message = %message, ?field_map),
-                        "trace" => tracing::trace!(message = %message, ?field_map),
-                        _ => tracing::info!(message = %message, ?field_map),
-                    }
+                    let fields = log.get("fields").unwrap_or(&serde_json::Value::Null);
+                    handler_log::emit(level, handler, context.trace_id(), message, fields);
                 }
             }
         }
@@ -215,20 +233,60 @@ impl NodeRuntime {
             .and_then(|v| serde_json::from_value::<std::collections::HashMap<String, serde_json::Value>>(v.clone()).ok())
             .unwrap_or_default();
         
+        let scheduled: Vec<crate::handler::ScheduleRequest> = result_value
+            .get("_rohas_scheduled")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default();
+
+        let response: crate::handler::HttpResponse = result_value
+            .get("_rohas_response")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default();
+
         if let Some(obj) = result_value.as_object_mut() {
             obj.remove("_rohas_logs");
             obj.remove("_rohas_triggers");
             obj.remove("_rohas_auto_trigger_payloads");
+            obj.remove("_rohas_scheduled");
+            obj.remove("_rohas_response");
         }
         
         let mut handler_result: HandlerResult = serde_json::from_value(result_value)?;
         
         handler_result.triggers = triggers;
         handler_result.auto_trigger_payloads = auto_trigger_payloads;
+        handler_result.scheduled = scheduled;
+        handler_result.response = response;
 
         Ok(handler_result)
     }
 
+    /// Globals through which the wrapper's `Kv` and `HttpClient` reach the
+    /// engine. Values cross as JSON strings.
+    fn install_native_functions(
+        scope: &mut v8::PinScope,
+        v8_context: v8::Local<Context>,
+    ) -> Result<()> {
+        let global = v8_context.global(scope);
+        let functions: [(&str, v8::FunctionCallback); 4] = [
+            ("__rohas_kv_get", native_kv_get.map_fn_to()),
+            ("__rohas_kv_set", native_kv_set.map_fn_to()),
+            ("__rohas_kv_delete", native_kv_delete.map_fn_to()),
+            ("__rohas_http", native_http.map_fn_to()),
+        ];
+        for (name, callback) in functions {
+            let function = v8::Function::new_raw(scope, callback);
+            let name = v8::String::new(scope, name);
+            let (Some(function), Some(name)) = (function, name) else {
+                return Err(crate::error::RuntimeError::ExecutionFailed(
+                    "Failed to create native functions".into(),
+                ));
+            };
+            global.set(scope, name.into(), function.into());
+        }
+        Ok(())
+    }
+
     fn generate_wrapper(handler_code: &str, context_json: &str, handler_name: &str) -> String {
         let context_escaped = context_json
             .replace('\\', "\\\\")
@@ -271,41 +329,176 @@ impl NodeRuntime {
                 this.handlerName = handlerName;
                 this.logFn = logFn;
             }}
-            info(message, fields) {{
+            // An object logs a structured record; its `message` key is the message.
+            log(level, message, fields) {{
+                if (typeof message === 'object' && message !== null) {{
+                    const {{ message: text = '', ...rest }} = message;
+                    fields = {{ ...rest, ...fields }};
+                    message = String(text);
+                }}
                 if (this.logFn) {{
-                    this.logFn("info", this.handlerName, message, fields || {{}});
+                    this.logFn(level, this.handlerName, message, fields || {{}});
                 }}
             }}
+            info(message, fields) {{
+                this.log("info", message, fields);
+            }}
             error(message, fields) {{
-                if (this.logFn) {{
-                    this.logFn("error", this.handlerName, message, fields || {{}});
-                }}
+                this.log("error", message, fields);
             }}
             warning(message, fields) {{
-                if (this.logFn) {{
-                    this.logFn("warn", this.handlerName, message, fields || {{}});
-                }}
+                this.log("warn", message, fields);
             }}
             warn(message, fields) {{
                 this.warning(message, fields);
             }}
             debug(message, fields) {{
-                if (this.logFn) {{
-                    this.logFn("debug", this.handlerName, message, fields || {{}});
-                }}
+                this.log("debug", message, fields);
             }}
             trace(message, fields) {{
-                if (this.logFn) {{
-                    this.logFn("trace", this.handlerName, message, fields || {{}});
+                this.log("trace", message, fields);
+            }}
+        }}
+
+        class HttpResponse {{
+            constructor() {{
+                this.statusCode = null;
+                this.headers = {{}};
+            }}
+            status(code) {{
+                this.statusCode = code;
+                return this;
+            }}
+            header(name, value) {{
+                this.headers[String(name).toLowerCase()] = String(value);
+                return this;
+            }}
+            redirect(location) {{
+                return this.status(302).header('location', location);
+            }}
+            permanentRedirect(location) {{
+                return this.status(308).header('location', location);
+            }}
+            toOptions() {{
+                return {{ status: this.statusCode, headers: {{ ...this.headers }} }};
+            }}
+        }}
+
+        class Cookies {{
+            constructor(request) {{
+                this.request = request || {{}};
+                this.cookies = [];
+            }}
+            get(name) {{
+                return this.request[name];
+            }}
+            set(name, value, options) {{
+                options = options || {{}};
+                this.cookies = this.cookies.filter((cookie) => cookie.name !== name);
+                this.cookies.push({{
+                    name,
+                    value: String(value),
+                    options: {{
+                        path: options.path ?? null,
+                        domain: options.domain ?? null,
+                        max_age: options.maxAge == null ? null : Math.floor(options.maxAge),
+                        secure: !!options.secure,
+                        http_only: !!options.httpOnly,
+                        same_site: options.sameSite ?? null,
+                    }},
+                }});
+                return this;
+            }}
+            delete(name) {{
+                return this.set(name, '', {{ maxAge: 0 }});
+            }}
+            toList() {{
+                return [...this.cookies];
+            }}
+        }}
+
+        // Key-value store shared by the engine's handlers
+        class Kv {{
+            async get(key) {{
+                const value = __rohas_kv_get(String(key));
+                return value === undefined ? undefined : JSON.parse(value);
+            }}
+            async set(key, value, options) {{
+                const ttlSeconds = options && options.ttlSeconds;
+                __rohas_kv_set(
+                    String(key),
+                    JSON.stringify(value === undefined ? null : value),
+                    ttlSeconds == null ? undefined : ttlSeconds * 1000
+                );
+            }}
+            async delete(key) {{
+                return __rohas_kv_delete(String(key));
+            }}
+        }}
+
+        class HttpClientResponse {{
+            constructor(response) {{
+                this.status = response.status;
+                this.headers = response.headers;
+                this.ok = response.status >= 200 && response.status < 300;
+                this.body = response.body;
+            }}
+            text() {{
+                return this.body;
+            }}
+            json() {{
+                return JSON.parse(this.body);
+            }}
+        }}
+
+        // Outbound HTTP, sent by the engine as there is no fetch here
+        class HttpClient {{
+            async request(url, options = {{}}) {{
+                const headers = {{}};
+                for (const [name, value] of Object.entries(options.headers || {{}})) {{
+                    headers[name.toLowerCase()] = String(value);
+                }}
+                let body = options.body;
+                if (body !== undefined && body !== null && typeof body !== 'string') {{
+                    body = JSON.stringify(body);
+                    headers['content-type'] = headers['content-type'] || 'application/json';
                 }}
+                const response = __rohas_http(JSON.stringify({{
+                    method: (options.method || 'GET').toUpperCase(),
+                    url,
+                    headers,
+                    body: body === undefined ? null : body,
+                    timeout_ms: options.timeoutMs == null ? null : options.timeoutMs,
+                }}));
+                return new HttpClientResponse(JSON.parse(response));
+            }}
+            get(url, options) {{
+                return this.request(url, {{ ...options, method: 'GET' }});
+            }}
+            post(url, body, options) {{
+                return this.request(url, {{ ...options, method: 'POST', body }});
+            }}
+            put(url, body, options) {{
+                return this.request(url, {{ ...options, method: 'PUT', body }});
+            }}
+            patch(url, body, options) {{
+                return this.request(url, {{ ...options, method: 'PATCH', body }});
+            }}
+            delete(url, options) {{
+                return this.request(url, {{ ...options, method: 'DELETE' }});
             }}
         }}
 
         class State {{
-            constructor(handlerName, logFn) {{
+            constructor(handlerName, logFn, cookies) {{
                 this.triggers = [];
                 this.autoTriggerPayloads = new Map();
+                this.scheduled = [];
+                this.httpResponse = new HttpResponse();
+                this.cookieJar = new Cookies(cookies);
                 this.logger = new Logger(handlerName || "unknown", logFn);
+                this.kv = new Kv();
+                this.http = new HttpClient();
             }}
             triggerEvent(eventName, payload) {{
                 this.triggers.push({{ eventName, payload }});
@@ -313,9 +506,28 @@ impl NodeRuntime {
             setPayload(eventName, payload) {{
                 this.autoTriggerPayloads.set(eventName, payload);
             }}
+            schedule(handler, when, payload) {{
+                this.scheduled.push({{
+                    handler,
+                    when: when instanceof Date ? when.toISOString() : String(when),
+                    payload: payload === undefined ? null : payload,
+                }});
+            }}
             getTriggers() {{
                 return [...this.triggers];
             }}
+            getScheduled() {{
+                return [...this.scheduled];
+            }}
+            response() {{
+                return this.httpResponse;
+            }}
+            cookies() {{
+                return this.cookieJar;
+            }}
+            getResponse() {{
+                return {{ ...this.httpResponse.toOptions(), cookies: this.cookieJar.toList() }};
+            }}
             getAutoTriggerPayload(eventName) {{
                 return this.autoTriggerPayloads.get(eventName);
             }}
@@ -324,16 +536,50 @@ impl NodeRuntime {
             }}
         }}
 
-        // Load handler code (CommonJS or plain)
+        // Load handler code (CommonJS or plain), unindented so its columns
+        // match the compiled file
         (function(exports, module, require) {{
-            {}
+{}
         }})(exports, module, require);
 
         // Parse context
         const context = JSON.parse('{}');
 
+        // Raw API bodies arrive as {{ bytes: "<base64>" }} or {{ text: "..." }}
+        const _rohas_from_base64 = (text) => {{
+            const alphabet = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+            const digits = text.replace(/=+$/, "");
+            const bytes = new Uint8Array(Math.floor(digits.length * 3 / 4));
+            let buffer = 0, bits = 0, index = 0;
+            for (let i = 0; i < digits.length; i++) {{
+                buffer = ((buffer << 6) | alphabet.indexOf(digits[i])) & 0xffff;
+                bits += 6;
+                if (bits >= 8) {{
+                    bits -= 8;
+                    bytes[index++] = (buffer >> bits) & 0xff;
+                }}
+            }}
+            return bytes;
+        }};
+        if (context.raw_body) {{
+            context.body = context.raw_body.bytes !== undefined
+                ? _rohas_from_base64(context.raw_body.bytes)
+                : context.raw_body.text;
+        }}
+
         // Create State object with logging
-        const state = new State('{}', _rohas_log_fn);
+        const state = new State('{}', _rohas_log_fn, context.cookies);
+
+        // Byte arrays are returned as arrays of numbers
+        const _rohas_to_json = (value) => {{
+            if (value instanceof Uint8Array) {{
+                return Array.from(value);
+            }}
+            if (value && value.data instanceof Uint8Array) {{
+                return {{ ...value, data: Array.from(value.data) }};
+            }}
+            return value;
+        }};
 
         // Find handler function
         let handlerFn;
@@ -390,12 +636,14 @@ impl NodeRuntime {
         // Return success result with logs
         return {{
             success: true,
-            data: result,
+            data: _rohas_to_json(result),
             error: null,
             execution_time_ms: 0,
             _rohas_logs: _rohas_logs,
             _rohas_triggers: state.getTriggers(),
-            _rohas_auto_trigger_payloads: Object.fromEntries(state.getAllAutoTriggerPayloads())
+            _rohas_auto_trigger_payloads: Object.fromEntries(state.getAllAutoTriggerPayloads()),
+            _rohas_scheduled: state.getScheduled(),
+            _rohas_response: state.getResponse()
         }};
     }} catch (error) {{
         // Return error result
@@ -451,6 +699,87 @@ impl NodeRuntime {
     }
 }
 
+fn throw_error(scope: &mut v8::PinScope, message: &str) {
+    if let Some(message) = v8::String::new(scope, message) {
+        let exception = v8::Exception::error(scope, message);
+        scope.throw_exception(exception);
+    }
+}
+
+fn native_kv_get(
+    scope: &mut v8::PinScope,
+    args: v8::FunctionCallbackArguments,
+    mut rv: v8::ReturnValue<v8::Value>,
+) {
+    let key = args.get(0).to_rust_string_lossy(scope);
+    match KvStore::global().get(&key) {
+        Some(value) => {
+            if let Some(value) = v8::String::new(scope, &value.to_string()) {
+                rv.set(value.into());
+            }
+        }
+        None => rv.set_undefined(),
+    }
+}
+
+fn native_kv_set(
+    scope: &mut v8::PinScope,
+    args: v8::FunctionCallbackArguments,
+    _rv: v8::ReturnValue<v8::Value>,
+) {
+    let key = args.get(0).to_rust_string_lossy(scope);
+    let value = args.get(1).to_rust_string_lossy(scope);
+    let value = match serde_json::from_str(&value) {
+        Ok(value) => value,
+        Err(e) => return throw_error(scope, &format!("kv value is not JSON: {}", e)),
+    };
+    let ttl = args
+        .get(2)
+        .number_value(scope)
+        .filter(|ms| ms.is_finite() && *ms >= 0.0)
+        .map(|ms| std::time::Duration::from_millis(ms as u64));
+    KvStore::global().set(key, value, ttl);
+}
+
+fn native_kv_delete(
+    scope: &mut v8::PinScope,
+    args: v8::FunctionCallbackArguments,
+    mut rv: v8::ReturnValue<v8::Value>,
+) {
+    let key = args.get(0).to_rust_string_lossy(scope);
+    rv.set_bool(KvStore::global().delete(&key));
+}
+
+/// Sends a request and waits for its response. Handlers run on a blocking
+/// thread, so the runtime's reactor can drive the request meanwhile.
+fn native_http(
+    scope: &mut v8::PinScope,
+    args: v8::FunctionCallbackArguments,
+    mut rv: v8::ReturnValue<v8::Value>,
+) {
+    let request = args.get(0).to_rust_string_lossy(scope);
+    let request: ClientRequest = match serde_json::from_str(&request) {
+        Ok(request) => request,
+        Err(e) => return throw_error(scope, &format!("Invalid HTTP request: {}", e)),
+    };
+    let Ok(handle) = tokio::runtime::Handle::try_current() else {
+        return throw_error(scope, "HTTP requests need the engine's runtime");
+    };
+    let response = match handle.block_on(http_client::send(request)) {
+        Ok(response) => response,
+        Err(e) => return throw_error(scope, &e.to_string()),
+    };
+    let json = serde_json::json!({
+        "status": response.status,
+        "headers": response.headers,
+        "body": response.text(),
+    })
+    .to_string();
+    if let Some(json) = v8::String::new(scope, &json) {
+        rv.set(json.into());
+    }
+}
+
 impl Default for NodeRuntime {
     fn default() -> Self {
         Self::new().expect("Failed to initialize V8 runtime")
@@ -479,11 +808,31 @@ mod tests {
 
         let context = HandlerContext::new("test", serde_json::json!({"data": "test"}));
 
-        let result = NodeRuntime::execute_js_code_sync(handler_code, &context);
+        let result = NodeRuntime::execute_js_code_sync(handler_code, &context, "handler.js");
         assert!(result.is_ok());
 
         let result = result.unwrap();
         assert!(result.success);
         assert!(result.data.is_some());
     }
+
+    #[tokio::test]
+    async fn test_state_kv_reaches_the_kv_store() {
+        let _runtime = NodeRuntime::new().unwrap();
+
+        let handler_code = r#"
+            module.exports = async function handler(context, state) {
+                await state.kv.set("node:test", { count: 1 });
+                const value = await state.kv.get("node:test");
+                return { count: value.count, missing: (await state.kv.get("node:missing")) === undefined };
+            };
+        "#;
+
+        let context = HandlerContext::new("test", serde_json::json!({}));
+        let result = NodeRuntime::execute_js_code_sync(handler_code, &context, "handler.js").unwrap();
+
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.data, Some(serde_json::json!({"count": 1, "missing": true})));
+        assert!(KvStore::global().delete("node:test"));
+    }
 }
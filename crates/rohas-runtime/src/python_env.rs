@@ -0,0 +1,153 @@
+//! Finding the virtualenv or poetry environment of a Python project, whose
+//! packages the embedded interpreter imports handlers' dependencies from.
+
+use crate::error::{Result, RuntimeError};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use tracing::info;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnvironmentKind {
+    /// The interpreter set by `python.interpreter` in rohas.toml.
+    Configured,
+    /// A `.venv` or `venv` directory in the project root.
+    Venv,
+    /// The environment poetry manages for the project.
+    Poetry,
+}
+
+#[derive(Debug, Clone)]
+pub struct PythonEnvironment {
+    pub kind: EnvironmentKind,
+    pub interpreter: PathBuf,
+    /// `major.minor` of the environment's Python.
+    pub version: String,
+    pub site_packages: Vec<PathBuf>,
+}
+
+impl fmt::Display for PythonEnvironment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            EnvironmentKind::Configured => "configured",
+            EnvironmentKind::Venv => "virtualenv",
+            EnvironmentKind::Poetry => "poetry",
+        };
+        write!(f, "{} environment {} (Python {})", kind, self.interpreter.display(), self.version)
+    }
+}
+
+impl PythonEnvironment {
+    /// The environment of the project at `project_root`: the configured
+    /// interpreter's, else a `.venv` or `venv` directory, else poetry's.
+    /// `None` when the project has none.
+    pub fn detect(project_root: &Path, interpreter: Option<&Path>) -> Result<Option<Self>> {
+        if let Some(interpreter) = interpreter {
+            let interpreter = if interpreter.is_relative() && interpreter.components().count() > 1 {
+                project_root.join(interpreter)
+            } else {
+                interpreter.to_path_buf()
+            };
+            return Self::inspect(EnvironmentKind::Configured, interpreter).map(Some);
+        }
+
+        for dir in [".venv", "venv"] {
+            let interpreter = venv_interpreter(&project_root.join(dir));
+            if interpreter.exists() {
+                return Self::inspect(EnvironmentKind::Venv, interpreter).map(Some);
+            }
+        }
+
+        if uses_poetry(project_root) {
+            let output = Command::new("poetry")
+                .args(["env", "info", "--path"])
+                .current_dir(project_root)
+                .stderr(Stdio::null())
+                .output();
+            // No poetry on the PATH, or no environment created yet.
+            if let Ok(output) = output {
+                if output.status.success() {
+                    let venv = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+                    return Self::inspect(EnvironmentKind::Poetry, venv_interpreter(&venv)).map(Some);
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn inspect(kind: EnvironmentKind, interpreter: PathBuf) -> Result<Self> {
+        let output = Command::new(&interpreter)
+            .args([
+                "-c",
+                "import json, site, sys; print(json.dumps({'version': '%d.%d' % sys.version_info[:2], 'site_packages': site.getsitepackages()}))",
+            ])
+            .output()
+            .map_err(|e| RuntimeError::PythonError(format!("Failed to run {}: {}", interpreter.display(), e)))?;
+        if !output.status.success() {
+            return Err(RuntimeError::PythonError(format!(
+                "{} failed: {}",
+                interpreter.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        #[derive(serde::Deserialize)]
+        struct Info {
+            version: String,
+            site_packages: Vec<PathBuf>,
+        }
+        let info: Info = serde_json::from_slice(&output.stdout)?;
+        Ok(Self {
+            kind,
+            interpreter,
+            version: info.version,
+            site_packages: info.site_packages,
+        })
+    }
+
+    /// Installs the project's declared dependencies into the environment,
+    /// with `poetry install` for poetry projects and otherwise from
+    /// `requirements.txt`.
+    pub fn install_requirements(&self, project_root: &Path) -> Result<()> {
+        let mut command = if self.kind == EnvironmentKind::Poetry {
+            let mut command = Command::new("poetry");
+            command.args(["install", "--no-root"]);
+            command
+        } else if project_root.join("requirements.txt").exists() {
+            let mut command = Command::new(&self.interpreter);
+            command.args(["-m", "pip", "install", "--quiet", "-r", "requirements.txt"]);
+            command
+        } else {
+            return Ok(());
+        };
+
+        info!("Installing Python dependencies into {}", self);
+        let status = command
+            .current_dir(project_root)
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit())
+            .status()?;
+        if !status.success() {
+            return Err(RuntimeError::PythonError(format!(
+                "Installing dependencies into {} failed",
+                self
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn venv_interpreter(venv: &Path) -> PathBuf {
+    if cfg!(windows) {
+        venv.join("Scripts").join("python.exe")
+    } else {
+        venv.join("bin").join("python")
+    }
+}
+
+fn uses_poetry(project_root: &Path) -> bool {
+    project_root.join("poetry.lock").exists()
+        || std::fs::read_to_string(project_root.join("pyproject.toml"))
+            .is_ok_and(|content| content.contains("[tool.poetry"))
+}
@@ -1,7 +1,12 @@
+use crate::calls::CallTape;
 use crate::error::{Result, RuntimeError};
-use crate::handler::{HandlerContext, HandlerResult};
+use crate::handler::{HandlerContext, HandlerResult, RawBody};
+use crate::handler_log;
+use crate::http_client::{self, ClientRequest};
+use crate::kv::KvStore;
+use crate::python_env::PythonEnvironment;
 use pyo3::prelude::*;
-use pyo3::types::{PyDict, PyModule, PyTuple};
+use pyo3::types::{PyBytes, PyDict, PyModule, PyTuple};
 use rohas_codegen::templates;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
@@ -11,6 +16,7 @@ use tracing::{debug, info};
 #[pyclass]
 struct RohasLogFn {
     handler_name: String,
+    trace_id: Option<String>,
 }
 
 #[pymethods]
@@ -22,36 +28,77 @@ impl RohasLogFn {
         message: String,
         fields: Bound<'_, PyDict>,
     ) -> PyResult<()> {
-        let mut field_map = std::collections::HashMap::new();
-        for (key, value) in fields.iter() {
-            let key_str = key.extract::<String>()?;
-            let value_str = format!("{:?}", value);
-            field_map.insert(key_str, value_str);
-        }
-        
-        let span = tracing::span!(
-            tracing::Level::INFO,
-            "handler_log",
-            handler = %handler
-        );
-        let _enter = span.enter();
-        
-        match level.as_str() {
-            "error" => tracing::error!(message = %message, ?field_map),
-            "warn" => tracing::warn!(message = %message, ?field_map),
-            "info" => tracing::info!(message = %message, ?field_map),
-            "debug" => tracing::debug!(message = %message, ?field_map),
-            "trace" => tracing::trace!(message = %message, ?field_map),
-            _ => tracing::info!(message = %message, ?field_map),
-        }
-        
+        let py = fields.py();
+        // Values JSON can't hold, such as datetimes, are logged as str().
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("default", py.import("builtins")?.getattr("str")?)?;
+        let json: String = py
+            .import("json")?
+            .call_method("dumps", (&fields,), Some(&kwargs))?
+            .extract()?;
+        let fields = serde_json::from_str(&json).unwrap_or_default();
+
+        handler_log::emit(&level, &handler, self.trace_id.as_deref(), &message, &fields);
         Ok(())
     }
 }
 
+/// `_rohas.kv_get`: the JSON of a key of the process' [`KvStore`].
+#[pyfunction]
+fn kv_get(key: &str) -> Option<String> {
+    KvStore::global().get(key).map(|value| value.to_string())
+}
+
+#[pyfunction]
+#[pyo3(signature = (key, value, ttl_ms=None))]
+fn kv_set(key: &str, value: &str, ttl_ms: Option<u64>) -> PyResult<()> {
+    let value = serde_json::from_str(value).map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+    KvStore::global().set(key, value, ttl_ms.map(std::time::Duration::from_millis));
+    Ok(())
+}
+
+#[pyfunction]
+fn kv_delete(key: &str) -> bool {
+    KvStore::global().delete(key)
+}
+
+/// `_rohas.http_send`: sends the JSON of a [`ClientRequest`] and returns the
+/// response as JSON, its body base64. The GIL is released while the request
+/// is in flight; handlers run on a blocking thread, so the runtime's reactor
+/// drives it meanwhile.
+#[pyfunction]
+fn http_send(py: Python<'_>, request: &str) -> PyResult<String> {
+    let request: ClientRequest =
+        serde_json::from_str(request).map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+    let handle = tokio::runtime::Handle::try_current()
+        .map_err(|_| pyo3::exceptions::PyRuntimeError::new_err("HTTP requests need the engine's runtime"))?;
+    let response = py
+        .detach(|| handle.block_on(http_client::send(request)))
+        .map_err(|e| pyo3::exceptions::PyConnectionError::new_err(e.to_string()))?;
+    Ok(serde_json::to_string(&crate::calls::RecordedResponse::from(&response))
+        .expect("responses serialize"))
+}
+
+/// Registers the `_rohas` module, through which the `rohas_sdk` package
+/// reaches the engine, unless an earlier run did.
+fn install_native_module(py: Python<'_>) -> PyResult<()> {
+    let modules = py.import("sys")?.getattr("modules")?;
+    if modules.contains("_rohas")? {
+        return Ok(());
+    }
+    let module = PyModule::new(py, "_rohas")?;
+    module.add_function(wrap_pyfunction!(kv_get, &module)?)?;
+    module.add_function(wrap_pyfunction!(kv_set, &module)?)?;
+    module.add_function(wrap_pyfunction!(kv_delete, &module)?)?;
+    module.add_function(wrap_pyfunction!(http_send, &module)?)?;
+    modules.set_item("_rohas", module)
+}
+
 pub struct PythonRuntime {
     modules: Arc<RwLock<std::collections::HashMap<String, Py<PyModule>>>>,
     project_root: Arc<Mutex<Option<PathBuf>>>,
+    /// The project environment whose packages handlers import.
+    environment: Option<PythonEnvironment>,
 }
 
 impl PythonRuntime {
@@ -63,6 +110,7 @@ impl PythonRuntime {
         Ok(Self {
             modules: Arc::new(RwLock::new(std::collections::HashMap::new())),
             project_root: Arc::new(Mutex::new(None)),
+            environment: None,
         })
     }
 
@@ -71,6 +119,32 @@ impl PythonRuntime {
         *project_root = Some(root);
     }
 
+    /// Makes the packages of `environment` importable. Its Python must be
+    /// the version rohas embeds, as compiled packages only load in the
+    /// version they were built for.
+    pub fn use_environment(&mut self, environment: PythonEnvironment) -> Result<()> {
+        Python::attach(|py| {
+            let version = py.version_info();
+            let embedded = format!("{}.{}", version.major, version.minor);
+            if environment.version != embedded {
+                return Err(RuntimeError::PythonError(format!(
+                    "The {} does not match the Python {} rohas embeds; recreate it with python{}",
+                    environment, embedded, embedded
+                )));
+            }
+
+            let site = py.import("site")?;
+            for dir in &environment.site_packages {
+                site.call_method1("addsitedir", (dir.to_string_lossy(),))?;
+            }
+            Ok(())
+        })?;
+
+        info!("Using Python packages from the {}", environment);
+        self.environment = Some(environment);
+        Ok(())
+    }
+
     pub async fn execute_handler(
         &self,
         handler_path: &Path,
@@ -83,7 +157,9 @@ impl PythonRuntime {
 
         debug!("Executing Python handler: {:?}", handler_path);
 
+        let tape = CallTape::current();
         let task = tokio::task::spawn_blocking(move || {
+            let _tape = tape.map(CallTape::enter);
             Python::with_gil(|py| {
                 Self::execute_handler_sync(
                     py,
@@ -97,8 +173,9 @@ impl PythonRuntime {
 
         let result = tokio::time::timeout(std::time::Duration::from_secs(30), task)
             .await
-            .map_err(|_| RuntimeError::ExecutionFailed("Handler execution timeout (30s)".into()))?
-            .map_err(|e| RuntimeError::ExecutionFailed(format!("Task join error: {}", e)))??;
+            .map_err(|_| RuntimeError::Timeout(30))?
+            .map_err(|e| RuntimeError::ExecutionFailed(format!("Task join error: {}", e)))?
+            .map_err(|e| self.explain_missing_module(e))?;
 
         let execution_time_ms = start.elapsed().as_millis() as u64;
         Ok(HandlerResult {
@@ -107,6 +184,23 @@ impl PythonRuntime {
         })
     }
 
+    /// Adds where to install a package to "No module named" import errors.
+    fn explain_missing_module(&self, error: RuntimeError) -> RuntimeError {
+        match error {
+            RuntimeError::ExecutionFailed(message) if message.contains("ModuleNotFoundError") => {
+                let hint = match &self.environment {
+                    Some(environment) => format!(
+                        "declare the package in requirements.txt or pyproject.toml and restart rohas dev, which installs it into the {}",
+                        environment
+                    ),
+                    None => "create a .venv for the project, or set python.interpreter in rohas.toml, and declare the package in requirements.txt or pyproject.toml".to_string(),
+                };
+                RuntimeError::ExecutionFailed(format!("{}; {}", message, hint))
+            }
+            error => error,
+        }
+    }
+
     fn execute_handler_sync(
         py: Python<'_>,
         handler_path: &Path,
@@ -114,6 +208,7 @@ impl PythonRuntime {
         context: &HandlerContext,
         project_root: Option<&PathBuf>,
     ) -> Result<HandlerResult> {
+        install_native_module(py)?;
         let sys = py.import("sys")?;
         let sys_path = sys.getattr("path")?;
 
@@ -138,15 +233,8 @@ impl PythonRuntime {
             .and_then(|s| s.to_str())
             .ok_or_else(|| RuntimeError::ExecutionFailed("Invalid module name".into()))?;
 
-        // Hot-reload support for Python handlers:
-        // - Invalidate import caches
-        let importlib = py.import("importlib")?;
-        let _ = importlib.call_method0("invalidate_caches");
-
-        if let Ok(modules_dict) = sys.getattr("modules") {
-            let _ = modules_dict.del_item(module_name);
-        }
-
+        // Modules stay imported between runs; `reload_module` drops them when
+        // their files change.
         let module = PyModule::import(py, module_name).map_err(|e| {
             RuntimeError::ExecutionFailed(format!("Failed to import module: {}", e))
         })?;
@@ -206,10 +294,11 @@ impl PythonRuntime {
         
         let log_fn_instance = Py::new(py, RohasLogFn {
             handler_name: handler_name.to_string(),
+            trace_id: context.trace_id().map(str::to_string),
         })?;
         
         let log_fn_py: PyObject = log_fn_instance.into();
-        let state_obj = state_class.call1((handler_name, log_fn_py))?;
+        let state_obj = state_class.call1((handler_name, log_fn_py, Self::cookies_to_py_dict(py, context)?))?;
         let state_obj_for_triggers = state_obj.clone();
 
         let result = if param_count == 0 {
@@ -293,7 +382,11 @@ impl PythonRuntime {
                 }
             };
 
-            match json_module.call_method1("dumps", (json_ready.as_any(),)) {
+            // `bytes` responses are serialized as lists of ints.
+            let dumps_kwargs = PyDict::new(py);
+            dumps_kwargs.set_item("default", py.import("builtins")?.getattr("list")?)?;
+
+            match json_module.call_method("dumps", (json_ready.as_any(),), Some(&dumps_kwargs)) {
                 Ok(json_result) => json_result.extract::<String>()?,
                 Err(e) => {
                     debug!("Failed to serialize response to JSON: {}, falling back to string representation", e);
@@ -354,6 +447,32 @@ impl PythonRuntime {
                 debug!("Failed to call get_triggers() on State object");
             }
 
+            if let Ok(scheduled_py) = state_obj_for_triggers.call_method0("get_scheduled") {
+                let json_module = py.import("json")?;
+                let scheduled = json_module
+                    .call_method1("dumps", (scheduled_py,))
+                    .and_then(|json| json.extract::<String>())
+                    .ok()
+                    .and_then(|json| serde_json::from_str::<Vec<crate::handler::ScheduleRequest>>(&json).ok());
+                match scheduled {
+                    Some(scheduled) => result.scheduled = scheduled,
+                    None => debug!("get_scheduled() did not return a list of scheduled calls"),
+                }
+            }
+
+            if let Ok(response_py) = state_obj_for_triggers.call_method0("get_response") {
+                let json_module = py.import("json")?;
+                let response = json_module
+                    .call_method1("dumps", (response_py,))
+                    .and_then(|json| json.extract::<String>())
+                    .ok()
+                    .and_then(|json| serde_json::from_str::<crate::handler::HttpResponse>(&json).ok());
+                match response {
+                    Some(response) => result.response = response,
+                    None => debug!("get_response() did not return a status and headers"),
+                }
+            }
+
             if let Ok(payloads_py) =
                 state_obj_for_triggers.call_method0("get_all_auto_trigger_payloads")
             {
@@ -411,15 +530,30 @@ impl PythonRuntime {
             }
         }
 
+        match &context.raw_body {
+            Some(RawBody::Bytes(bytes)) => dict.set_item("body", PyBytes::new(py, bytes))?,
+            Some(RawBody::Text(text)) => dict.set_item("body", text)?,
+            None => {}
+        }
+
         let query_params_dict = PyDict::new(py);
         for (key, value) in &context.query_params {
             query_params_dict.set_item(key, value)?;
         }
         dict.set_item("query_params", query_params_dict)?;
+        dict.set_item("cookies", Self::cookies_to_py_dict(py, context)?)?;
 
         Ok(dict)
     }
 
+    fn cookies_to_py_dict<'py>(py: Python<'py>, context: &HandlerContext) -> PyResult<Bound<'py, PyDict>> {
+        let cookies = PyDict::new(py);
+        for (name, value) in &context.cookies {
+            cookies.set_item(name, value)?;
+        }
+        Ok(cookies)
+    }
+
     fn instantiate_request_class<'py>(
         py: Python<'py>,
         handler_name: &str,
@@ -1033,12 +1167,79 @@ impl PythonRuntime {
         }
     }
 
-    pub async fn reload_module(&self, module_name: &str) -> Result<()> {
-        let mut modules = self.modules.write().await;
-        modules.remove(module_name);
-        info!("Reloaded Python module: {}", module_name);
+    /// Compiles `path` after it changed and, once it compiles, drops the
+    /// project's modules from `sys.modules`, so the next run of each handler
+    /// imports the current code. A file with a syntax error is reported and
+    /// leaves the loaded modules serving. Handlers already running finish
+    /// with the modules they started with.
+    pub async fn reload_module(&self, path: &Path) -> Result<()> {
+        let file = path.to_path_buf();
+        let project_root = self.project_root.lock().unwrap().clone();
+
+        let task = tokio::task::spawn_blocking(move || {
+            Python::attach(|py| {
+                let source = std::fs::read_to_string(&file)?;
+                let builtins = py.import("builtins")?;
+                builtins
+                    .call_method1("compile", (source, file.to_string_lossy().as_ref(), "exec"))
+                    .map_err(|e| RuntimeError::PythonError(e.to_string()))?;
+                Self::drop_project_modules(py, project_root.as_deref())
+            })
+        });
+        let dropped = task
+            .await
+            .map_err(|e| RuntimeError::ExecutionFailed(format!("Task join error: {}", e)))??;
+        self.modules.write().await.clear();
+        info!("Reloaded Python handlers after {} changed ({} module(s) dropped)", path.display(), dropped);
+        Ok(())
+    }
+
+    /// Drops every project module, e.g. after generated code changed.
+    pub async fn clear_cache(&self) -> Result<()> {
+        let project_root = self.project_root.lock().unwrap().clone();
+        self.modules.write().await.clear();
+        let task = tokio::task::spawn_blocking(move || {
+            Python::attach(|py| Self::drop_project_modules(py, project_root.as_deref()))
+        });
+        let dropped = task
+            .await
+            .map_err(|e| RuntimeError::ExecutionFailed(format!("Task join error: {}", e)))??;
+        info!("Cleared {} Python module(s)", dropped);
         Ok(())
     }
+
+    /// Removes the modules loaded from the project's `src` directory from
+    /// `sys.modules`, leaving the standard library and installed packages.
+    fn drop_project_modules(py: Python<'_>, project_root: Option<&Path>) -> Result<usize> {
+        let Some(root) = project_root else {
+            return Ok(0);
+        };
+        let src = root.join("src");
+        let src = src.canonicalize().unwrap_or(src);
+
+        let sys = py.import("sys")?;
+        let modules = sys.getattr("modules")?;
+        let modules = modules
+            .cast::<PyDict>()
+            .map_err(|e| RuntimeError::PythonError(e.to_string()))?;
+        let mut stale = Vec::new();
+        for (name, module) in modules.iter() {
+            let Ok(file) = module.getattr("__file__").and_then(|file| file.extract::<String>()) else {
+                continue;
+            };
+            let file = PathBuf::from(file);
+            let file = file.canonicalize().unwrap_or(file);
+            if file.starts_with(&src) && !file.components().any(|c| c.as_os_str() == "site-packages") {
+                stale.push(name);
+            }
+        }
+        for name in &stale {
+            modules.del_item(name)?;
+        }
+
+        py.import("importlib")?.call_method0("invalidate_caches")?;
+        Ok(stale.len())
+    }
 }
 
 impl Default for PythonRuntime {
@@ -1078,6 +1279,89 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_native_module_reaches_the_kv_store() {
+        Python::with_gil(|py| {
+            install_native_module(py).unwrap();
+            install_native_module(py).unwrap();
+            let code = std::ffi::CString::new(
+                "import _rohas, json\n_rohas.kv_set('python:test', json.dumps({'count': 1}))\nvalue = json.loads(_rohas.kv_get('python:test'))",
+            )
+            .unwrap();
+            let globals = PyDict::new(py);
+            py.run(&code, Some(&globals), None).unwrap();
+            let value: i64 = globals.get_item("value").unwrap().unwrap().get_item("count").unwrap().extract().unwrap();
+            assert_eq!(value, 1);
+        });
+        assert_eq!(KvStore::global().get("python:test"), Some(serde_json::json!({"count": 1})));
+        assert!(KvStore::global().delete("python:test"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_native_http_send_goes_through_the_call_tape() {
+        let response = crate::calls::RecordedResponse::from(&crate::http_client::ClientResponse {
+            status: 201,
+            headers: std::collections::HashMap::new(),
+            body: b"charged".to_vec(),
+        });
+        let tape = CallTape::replaying(vec![crate::calls::OutboundCall::Http {
+            request: ClientRequest::new("POST", "http://payments/charge"),
+            response: Some(response),
+            error: None,
+        }]);
+
+        let thread_tape = tape.clone();
+        let (status, body) = tokio::task::spawn_blocking(move || {
+            let _tape = thread_tape.enter();
+            Python::with_gil(|py| {
+                install_native_module(py).unwrap();
+                let code = std::ffi::CString::new(
+                    "import _rohas, json, base64\nresponse = json.loads(_rohas.http_send(json.dumps({'method': 'POST', 'url': 'http://payments/charge'})))\nstatus = response['status']\nbody = base64.b64decode(response['body']).decode()",
+                )
+                .unwrap();
+                let globals = PyDict::new(py);
+                py.run(&code, Some(&globals), None).unwrap();
+                let status: u16 = globals.get_item("status").unwrap().unwrap().extract().unwrap();
+                let body: String = globals.get_item("body").unwrap().unwrap().extract().unwrap();
+                (status, body)
+            })
+        })
+        .await
+        .unwrap();
+
+        assert_eq!((status, body.as_str()), (201, "charged"));
+        assert!(tape.unanswered().is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_broken_save_leaves_the_old_handlers_serving() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let handlers = dir.path().join("src/handlers/api");
+        std::fs::create_dir_all(&handlers).unwrap();
+        let file = handlers.join("reload_orders.py");
+        std::fs::write(&file, "def handle_list_orders():\n    return 'old'\n").unwrap();
+
+        let mut runtime = PythonRuntime::new().unwrap();
+        runtime.set_project_root(dir.path().to_path_buf());
+        let handlers_path = handlers.to_string_lossy().to_string();
+        let serving = move || {
+            Python::attach(|py| {
+                py.import("sys").unwrap().getattr("path").unwrap().call_method1("insert", (0, &handlers_path)).unwrap();
+                let module = py.import("reload_orders").unwrap();
+                module.call_method0("handle_list_orders").unwrap().extract::<String>().unwrap()
+            })
+        };
+        assert_eq!(serving(), "old");
+
+        std::fs::write(&file, "def handle_list_orders(:\n    return 'new'\n").unwrap();
+        assert!(runtime.reload_module(&file).await.is_err());
+        assert_eq!(serving(), "old");
+
+        std::fs::write(&file, "def handle_list_orders():\n    return 'new'\n").unwrap();
+        runtime.reload_module(&file).await.unwrap();
+        assert_eq!(serving(), "new");
+    }
+
     #[test]
     fn test_to_snake_case() {
         assert_eq!(to_snake_case("CreateUser"), "create_user");
@@ -0,0 +1,222 @@
+//! Source maps of compiled TypeScript handlers, for mapping the positions in
+//! error stacks back to the `.ts` sources.
+
+use std::path::Path;
+
+/// A position in a source file; lines and columns are 1-based as in stacks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourcePosition {
+    pub source: String,
+    pub line: u32,
+    pub column: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    generated_column: u32,
+    source: usize,
+    line: u32,
+    column: u32,
+}
+
+/// A parsed version 3 source map.
+#[derive(Debug, Clone)]
+pub struct SourceMap {
+    sources: Vec<String>,
+    /// Segments of each generated line, ordered by column.
+    lines: Vec<Vec<Segment>>,
+}
+
+impl SourceMap {
+    /// Reads the source map of the compiled file at `path`, named by its
+    /// `sourceMappingURL` comment or else `<path>.map`. Sources are resolved
+    /// against `project_root`, which bundlers use as their context.
+    pub fn load(path: &Path, code: &str, project_root: Option<&Path>) -> Option<Self> {
+        let dir = path.parent()?;
+        let map_path = match source_mapping_url(code) {
+            // Inline maps are not emitted by the generated build config.
+            Some(url) if url.starts_with("data:") => return None,
+            Some(url) => dir.join(url),
+            None => {
+                let mut name = path.as_os_str().to_owned();
+                name.push(".map");
+                name.into()
+            }
+        };
+        let json = std::fs::read_to_string(map_path).ok()?;
+        Self::parse(&json, dir, project_root)
+    }
+
+    pub fn parse(json: &str, map_dir: &Path, project_root: Option<&Path>) -> Option<Self> {
+        let map: serde_json::Value = serde_json::from_str(json).ok()?;
+        let source_root = map.get("sourceRoot").and_then(|root| root.as_str()).unwrap_or_default();
+        let sources = map
+            .get("sources")?
+            .as_array()?
+            .iter()
+            .map(|source| resolve_source(source.as_str().unwrap_or_default(), source_root, map_dir, project_root))
+            .collect();
+        let lines = decode_mappings(map.get("mappings")?.as_str()?)?;
+        Some(Self { sources, lines })
+    }
+
+    /// The source position of a 1-based position in the generated file.
+    pub fn lookup(&self, line: u32, column: u32) -> Option<SourcePosition> {
+        let segments = self.lines.get(line.checked_sub(1)? as usize)?;
+        let index = segments.partition_point(|segment| segment.generated_column < column);
+        // The segment starting at or before the column, or else the first.
+        let segment = segments.get(index.saturating_sub(1))?;
+        Some(SourcePosition {
+            source: self.sources.get(segment.source)?.clone(),
+            line: segment.line + 1,
+            column: segment.column + 1,
+        })
+    }
+
+    /// Rewrites the `file:line:column` positions in `text` to their sources.
+    /// Positions without a mapping are kept.
+    pub fn remap(&self, text: &str, file: &str) -> String {
+        let mut output = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find(file) {
+            output.push_str(&rest[..start]);
+            let after = &rest[start + file.len()..];
+            match parse_position(after).and_then(|(line, column, len)| Some((self.lookup(line, column)?, len))) {
+                Some((position, len)) => {
+                    output.push_str(&format!("{}:{}:{}", position.source, position.line, position.column));
+                    rest = &after[len..];
+                }
+                None => {
+                    output.push_str(file);
+                    rest = after;
+                }
+            }
+        }
+        output.push_str(rest);
+        output
+    }
+}
+
+fn source_mapping_url(code: &str) -> Option<&str> {
+    code.lines()
+        .rev()
+        .find_map(|line| line.trim().strip_prefix("//# sourceMappingURL="))
+        .map(str::trim)
+}
+
+fn resolve_source(source: &str, source_root: &str, map_dir: &Path, project_root: Option<&Path>) -> String {
+    // webpack://<namespace>/./src/handlers/Health.ts
+    if let Some(rest) = source.strip_prefix("webpack://") {
+        let relative = rest.split_once('/').map_or(rest, |(_, path)| path);
+        let relative = relative.strip_prefix("./").unwrap_or(relative);
+        return match project_root {
+            Some(root) => root.join(relative).to_string_lossy().into_owned(),
+            None => relative.to_string(),
+        };
+    }
+    map_dir.join(source_root).join(source).to_string_lossy().into_owned()
+}
+
+/// `:line:column` at the start of `text`, and its length.
+fn parse_position(text: &str) -> Option<(u32, u32, usize)> {
+    let digits = |text: &str| text.bytes().take_while(u8::is_ascii_digit).count();
+    let text_after_line = text.strip_prefix(':')?;
+    let line_len = digits(text_after_line);
+    let line = text_after_line[..line_len].parse().ok()?;
+    let text_after_column = text_after_line[line_len..].strip_prefix(':')?;
+    let column_len = digits(text_after_column);
+    let column = text_after_column[..column_len].parse().ok()?;
+    Some((line, column, 2 + line_len + column_len))
+}
+
+fn decode_mappings(mappings: &str) -> Option<Vec<Vec<Segment>>> {
+    let mut lines = Vec::new();
+    let (mut source, mut line, mut column) = (0i64, 0i64, 0i64);
+    for generated_line in mappings.split(';') {
+        let mut segments = Vec::new();
+        let mut generated_column = 0i64;
+        for segment in generated_line.split(',').filter(|segment| !segment.is_empty()) {
+            let fields = decode_vlq(segment)?;
+            generated_column += *fields.first()?;
+            // Segments of one field have no source.
+            if fields.len() < 4 {
+                continue;
+            }
+            source += fields[1];
+            line += fields[2];
+            column += fields[3];
+            segments.push(Segment {
+                generated_column: u32::try_from(generated_column).ok()? + 1,
+                source: usize::try_from(source).ok()?,
+                line: u32::try_from(line).ok()?,
+                column: u32::try_from(column).ok()?,
+            });
+        }
+        segments.sort_by_key(|segment| segment.generated_column);
+        lines.push(segments);
+    }
+    Some(lines)
+}
+
+/// The base64 VLQ values of one segment.
+fn decode_vlq(segment: &str) -> Option<Vec<i64>> {
+    let mut values = Vec::new();
+    let (mut value, mut shift) = (0i64, 0u32);
+    for byte in segment.bytes() {
+        let digit = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        } as i64;
+        value += (digit & 0x1f) << shift;
+        if digit & 0x20 != 0 {
+            shift += 5;
+            continue;
+        }
+        values.push(if value & 1 == 1 { -(value >> 1) } else { value >> 1 });
+        value = 0;
+        shift = 0;
+    }
+    Some(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_vlq() {
+        assert_eq!(decode_vlq("AAgBC").unwrap(), vec![0, 0, 16, 1]);
+        assert_eq!(decode_vlq("D").unwrap(), vec![-1]);
+    }
+
+    #[test]
+    fn test_remap_stack_positions() {
+        // Generated line 2, columns 1 and 7, from lines 3 and 4 of Health.ts.
+        let json = r#"{
+            "version": 3,
+            "sources": ["webpack://app/./src/handlers/Health.ts"],
+            "mappings": ";AAEA,MACE"
+        }"#;
+        let map = SourceMap::parse(json, Path::new("/app/.rohas/handlers"), Some(Path::new("/app"))).unwrap();
+
+        assert_eq!(
+            map.lookup(2, 9),
+            Some(SourcePosition {
+                source: "/app/src/handlers/Health.ts".to_string(),
+                line: 4,
+                column: 3,
+            })
+        );
+        assert_eq!(map.lookup(1, 1), None);
+
+        let stack = "Error: boom\n    at handle (/app/.rohas/handlers/Health.js:2:3)\n    at other (/app/.rohas/handlers/Health.js:9:1)";
+        assert_eq!(
+            map.remap(stack, "/app/.rohas/handlers/Health.js"),
+            "Error: boom\n    at handle (/app/src/handlers/Health.ts:3:1)\n    at other (/app/.rohas/handlers/Health.js:9:1)"
+        );
+    }
+}
@@ -0,0 +1,38 @@
+use rohas_runtime::http_client;
+use rohas_runtime::{ClientRequest, ClientResponse, Result};
+use serde::Serialize;
+
+/// Client for outbound HTTP requests. Every response is returned, whatever
+/// its status; check [`ClientResponse::is_success`] where that matters.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HttpClient;
+
+impl HttpClient {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub async fn get(&self, url: impl Into<String>) -> Result<ClientResponse> {
+        self.send(ClientRequest::new("GET", url)).await
+    }
+
+    /// Sends `body` as JSON.
+    pub async fn post<T: Serialize>(&self, url: impl Into<String>, body: &T) -> Result<ClientResponse> {
+        self.send(ClientRequest::new("POST", url).json(body)?).await
+    }
+
+    /// Sends `body` as JSON.
+    pub async fn put<T: Serialize>(&self, url: impl Into<String>, body: &T) -> Result<ClientResponse> {
+        self.send(ClientRequest::new("PUT", url).json(body)?).await
+    }
+
+    pub async fn delete(&self, url: impl Into<String>) -> Result<ClientResponse> {
+        self.send(ClientRequest::new("DELETE", url)).await
+    }
+
+    /// Sends a request built with [`ClientRequest`], for other methods,
+    /// headers or a timeout other than the default 30 seconds.
+    pub async fn send(&self, request: ClientRequest) -> Result<ClientResponse> {
+        http_client::send(request).await
+    }
+}
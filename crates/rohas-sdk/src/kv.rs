@@ -0,0 +1,78 @@
+use rohas_runtime::{KvStore, Result};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::time::Duration;
+
+/// Key-value store shared by the handlers of the engine process. Values are
+/// kept as JSON in memory, so they are lost when the engine restarts.
+#[derive(Debug, Clone, Copy)]
+pub struct Kv {
+    store: &'static KvStore,
+}
+
+impl Kv {
+    pub fn new() -> Self {
+        Self {
+            store: KvStore::global(),
+        }
+    }
+
+    /// Value stored under `key`, or `None` when it is missing or expired.
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        match self.store.get(key) {
+            Some(value) => Ok(Some(serde_json::from_value(value)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn set<T: Serialize>(&self, key: impl Into<String>, value: &T) -> Result<()> {
+        self.store.set(key, serde_json::to_value(value)?, None);
+        Ok(())
+    }
+
+    /// Like [`Kv::set`], but the key is removed once `ttl` has passed.
+    pub fn set_with_ttl<T: Serialize>(&self, key: impl Into<String>, value: &T, ttl: Duration) -> Result<()> {
+        self.store.set(key, serde_json::to_value(value)?, Some(ttl));
+        Ok(())
+    }
+
+    /// Removes `key`, returning whether it held a value.
+    pub fn delete(&self, key: &str) -> bool {
+        self.store.delete(key)
+    }
+}
+
+impl Default for Kv {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Counter {
+        hits: u32,
+    }
+
+    #[test]
+    fn test_typed_values() {
+        let kv = Kv::new();
+        kv.set("sdk:counter", &Counter { hits: 3 }).unwrap();
+
+        assert_eq!(kv.get::<Counter>("sdk:counter").unwrap(), Some(Counter { hits: 3 }));
+        assert!(kv.get::<String>("sdk:counter").is_err());
+        assert!(kv.delete("sdk:counter"));
+        assert_eq!(kv.get::<Counter>("sdk:counter").unwrap(), None);
+    }
+
+    #[test]
+    fn test_ttl() {
+        let kv = Kv::new();
+        kv.set_with_ttl("sdk:token", &"abc", Duration::ZERO).unwrap();
+        assert_eq!(kv.get::<String>("sdk:token").unwrap(), None);
+    }
+}
@@ -0,0 +1,22 @@
+//! Handler-facing API for Rohas Rust handlers.
+//!
+//! Generated code and user handlers import from this crate instead of reaching
+//! into `rohas-runtime` directly, so the engine internals can change without
+//! breaking handler code.
+
+pub mod http;
+pub mod kv;
+pub mod logger;
+pub mod state;
+pub mod testing;
+
+pub use http::HttpClient;
+pub use kv::Kv;
+pub use logger::Logger;
+pub use state::{Cookies, State, TriggeredEvent};
+pub use testing::TestEngine;
+
+pub use rohas_runtime::{
+    ClientRequest, ClientResponse, CookieOptions, HandlerContext, HandlerResult, HttpResponse, RawBody, Result,
+    RuntimeError, RustRuntime, SameSite, ScheduleRequest, SetCookie,
+};
@@ -0,0 +1,35 @@
+use tracing::{debug, error, info, trace, warn};
+
+/// Structured logger for handlers.
+#[derive(Debug, Clone)]
+pub struct Logger {
+    handler_name: String,
+}
+
+impl Logger {
+    pub fn new(handler_name: impl Into<String>) -> Self {
+        Self {
+            handler_name: handler_name.into(),
+        }
+    }
+
+    pub fn info(&self, message: &str) {
+        info!(handler = %self.handler_name, %message);
+    }
+
+    pub fn error(&self, message: &str) {
+        error!(handler = %self.handler_name, %message);
+    }
+
+    pub fn warn(&self, message: &str) {
+        warn!(handler = %self.handler_name, %message);
+    }
+
+    pub fn debug(&self, message: &str) {
+        debug!(handler = %self.handler_name, %message);
+    }
+
+    pub fn trace(&self, message: &str) {
+        trace!(handler = %self.handler_name, %message);
+    }
+}
@@ -0,0 +1,217 @@
+use crate::http::HttpClient;
+use crate::kv::Kv;
+use crate::logger::Logger;
+use rohas_runtime::{CookieOptions, HttpResponse, ScheduleRequest, SetCookie};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// State struct for Rust handlers.
+#[derive(Debug, Clone)]
+pub struct State {
+    handler_name: String,
+    triggers: Vec<TriggeredEvent>,
+    auto_trigger_payloads: HashMap<String, Value>,
+    scheduled: Vec<ScheduleRequest>,
+    response: HttpResponse,
+    request_cookies: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TriggeredEvent {
+    pub event_name: String,
+    pub payload: Value,
+}
+
+impl State {
+    /// Create a new State instance.
+    pub fn new(handler_name: impl Into<String>) -> Self {
+        Self {
+            handler_name: handler_name.into(),
+            triggers: Vec::new(),
+            auto_trigger_payloads: HashMap::new(),
+            scheduled: Vec::new(),
+            response: HttpResponse::default(),
+            request_cookies: HashMap::new(),
+        }
+    }
+
+    /// Sets the cookies of the API request the handler runs for.
+    pub fn with_cookies(mut self, cookies: HashMap<String, String>) -> Self {
+        self.request_cookies = cookies;
+        self
+    }
+
+    /// Name of the handler this state belongs to.
+    pub fn handler_name(&self) -> &str {
+        &self.handler_name
+    }
+
+    /// Manually trigger an event (for events NOT in schema triggers).
+    pub fn trigger_event(&mut self, event_name: impl Into<String>, payload: Value) {
+        self.triggers.push(TriggeredEvent {
+            event_name: event_name.into(),
+            payload,
+        });
+    }
+
+    /// Set payload for an auto-triggered event (for events IN schema triggers).
+    pub fn set_payload(&mut self, event_name: impl Into<String>, payload: Value) {
+        self.auto_trigger_payloads.insert(event_name.into(), payload);
+    }
+
+    /// Run a handler later, at an RFC 3339 time or after a delay such as
+    /// `30s`. The call is kept in the engine's job store.
+    pub fn schedule(&mut self, handler: impl Into<String>, when: impl Into<String>, payload: Value) {
+        self.scheduled.push(ScheduleRequest {
+            handler: handler.into(),
+            when: when.into(),
+            payload,
+        });
+    }
+
+    /// The HTTP response of an API handler, to set its status and headers,
+    /// e.g. `state.response().status(201).header("Cache-Control", "no-store")`.
+    pub fn response(&mut self) -> &mut HttpResponse {
+        &mut self.response
+    }
+
+    /// Cookies of the API request, and those to set on its response.
+    pub fn cookies(&mut self) -> Cookies<'_> {
+        Cookies {
+            request: &self.request_cookies,
+            response: &mut self.response.cookies,
+        }
+    }
+
+    /// Get all manually triggered events (internal use).
+    pub fn get_triggers(&self) -> &[TriggeredEvent] {
+        &self.triggers
+    }
+
+    /// Get all scheduled handler calls (internal use).
+    pub fn get_scheduled(&self) -> &[ScheduleRequest] {
+        &self.scheduled
+    }
+
+    /// Get the response status and headers (internal use).
+    pub fn get_response(&self) -> &HttpResponse {
+        &self.response
+    }
+
+    /// Get all auto-trigger payloads (internal use).
+    pub fn get_all_auto_trigger_payloads(&self) -> &HashMap<String, Value> {
+        &self.auto_trigger_payloads
+    }
+
+    /// Get a logger instance for this handler.
+    pub fn logger(&self) -> Logger {
+        Logger::new(&self.handler_name)
+    }
+
+    /// Key-value store shared by the engine's handlers.
+    pub fn kv(&self) -> Kv {
+        Kv::new()
+    }
+
+    /// Client for outbound HTTP requests.
+    pub fn http(&self) -> HttpClient {
+        HttpClient::new()
+    }
+}
+
+/// Cookies of an API request, and those the handler sets on its response.
+#[derive(Debug)]
+pub struct Cookies<'a> {
+    request: &'a HashMap<String, String>,
+    response: &'a mut Vec<SetCookie>,
+}
+
+impl<'a> Cookies<'a> {
+    /// Value of a cookie sent with the request.
+    pub fn get(&self, name: &str) -> Option<&'a str> {
+        self.request.get(name).map(String::as_str)
+    }
+
+    /// Sets a cookie on the response, replacing one set earlier with the
+    /// same name.
+    pub fn set(&mut self, name: impl Into<String>, value: impl Into<String>, options: CookieOptions) -> &mut Self {
+        let name = name.into();
+        self.response.retain(|cookie| cookie.name != name);
+        self.response.push(SetCookie {
+            name,
+            value: value.into(),
+            options,
+        });
+        self
+    }
+
+    /// Removes a cookie from the client by setting it expired.
+    pub fn delete(&mut self, name: impl Into<String>) -> &mut Self {
+        let options = CookieOptions {
+            max_age: Some(0),
+            ..CookieOptions::default()
+        };
+        self.set(name, "", options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_trigger_and_payloads() {
+        let mut state = State::new("create_user");
+        state.trigger_event("AuditLog", json!({"action": "create"}));
+        state.set_payload("UserCreated", json!({"id": 1}));
+
+        assert_eq!(state.handler_name(), "create_user");
+        assert_eq!(state.get_triggers().len(), 1);
+        assert_eq!(state.get_triggers()[0].event_name, "AuditLog");
+        assert_eq!(
+            state.get_all_auto_trigger_payloads().get("UserCreated"),
+            Some(&json!({"id": 1}))
+        );
+    }
+
+    #[test]
+    fn test_schedule() {
+        let mut state = State::new("create_order");
+        state.schedule("send_reminder", "1d", json!({"order_id": 7}));
+
+        assert_eq!(state.get_scheduled().len(), 1);
+        assert_eq!(state.get_scheduled()[0].handler, "send_reminder");
+        assert_eq!(state.get_scheduled()[0].when, "1d");
+    }
+
+    #[test]
+    fn test_response() {
+        let mut state = State::new("download_report");
+        state.response().status(201).header("Cache-Control", "no-store");
+        assert_eq!(state.get_response().status, Some(201));
+        assert_eq!(state.get_response().headers.get("cache-control").map(String::as_str), Some("no-store"));
+
+        state.response().redirect("/reports/7");
+        assert_eq!(state.get_response().status, Some(302));
+        assert_eq!(state.get_response().headers.get("location").map(String::as_str), Some("/reports/7"));
+    }
+
+    #[test]
+    fn test_cookies() {
+        let cookies = HashMap::from([("session".to_string(), "abc".to_string())]);
+        let mut state = State::new("login").with_cookies(cookies);
+        assert_eq!(state.cookies().get("session"), Some("abc"));
+
+        let options = CookieOptions {
+            http_only: true,
+            ..CookieOptions::default()
+        };
+        state.cookies().set("session", "def", options).delete("theme");
+        let set = &state.get_response().cookies;
+        assert_eq!(set.len(), 2);
+        assert_eq!(set[0].value, "def");
+        assert!(set[0].options.http_only);
+        assert_eq!(set[1].options.max_age, Some(0));
+    }
+}
@@ -0,0 +1,211 @@
+use crate::state::{State, TriggeredEvent};
+use rohas_runtime::{Result, RuntimeError};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// Guards against event trigger cycles in the schema.
+const MAX_DISPATCHES: usize = 1000;
+
+type EventHandlerFn = Arc<dyn Fn(Value) -> Pin<Box<dyn Future<Output = Result<()>> + Send>> + Send + Sync>;
+
+/// In-process engine for handler tests.
+///
+/// Handlers are called directly by the test; the engine collects the events
+/// they trigger and dispatches them to registered event handlers, following
+/// event trigger chains declared in the schema.
+#[derive(Default)]
+pub struct TestEngine {
+    handlers: HashMap<String, Vec<EventHandlerFn>>,
+    chains: HashMap<String, Vec<String>>,
+    published: Mutex<Vec<TriggeredEvent>>,
+    published_notify: Notify,
+}
+
+impl TestEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a handler that runs whenever `event_name` is published.
+    pub fn on_event<F, Fut>(&mut self, event_name: impl Into<String>, handler: F)
+    where
+        F: Fn(Value) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.handlers
+            .entry(event_name.into())
+            .or_default()
+            .push(Arc::new(move |payload| Box::pin(handler(payload))));
+    }
+
+    /// Publish `triggers` with the same payload whenever `event_name` is published.
+    pub fn chain(&mut self, event_name: impl Into<String>, triggers: &[&str]) {
+        self.chains
+            .insert(event_name.into(), triggers.iter().map(|t| t.to_string()).collect());
+    }
+
+    /// State to pass to the handler under test.
+    pub fn state(&self, handler_name: impl Into<String>) -> State {
+        State::new(handler_name)
+    }
+
+    /// Dispatch the events a handler triggered. `triggers` are the API's schema
+    /// triggers; they use the payload set with `State::set_payload` or fall back
+    /// to the response.
+    pub async fn complete<T: Serialize>(
+        &self,
+        state: &State,
+        triggers: &[&str],
+        response: &T,
+    ) -> Result<Vec<TriggeredEvent>> {
+        let mut events = state.get_triggers().to_vec();
+        for event_name in triggers {
+            let payload = match state.get_all_auto_trigger_payloads().get(*event_name) {
+                Some(payload) => payload.clone(),
+                None => serde_json::to_value(response)?,
+            };
+            events.push(TriggeredEvent {
+                event_name: event_name.to_string(),
+                payload,
+            });
+        }
+
+        for event in &events {
+            self.publish(&event.event_name, event.payload.clone()).await?;
+        }
+
+        Ok(events)
+    }
+
+    /// Publish an event, run its handlers and follow its schema triggers.
+    pub async fn publish(&self, event_name: &str, payload: Value) -> Result<()> {
+        let mut queue = VecDeque::from([TriggeredEvent {
+            event_name: event_name.to_string(),
+            payload,
+        }]);
+        let mut dispatched = 0;
+
+        while let Some(event) = queue.pop_front() {
+            dispatched += 1;
+            if dispatched > MAX_DISPATCHES {
+                return Err(RuntimeError::ExecutionFailed(format!(
+                    "Event chain starting at {} did not settle",
+                    event_name
+                )));
+            }
+
+            self.published.lock().unwrap().push(event.clone());
+            self.published_notify.notify_waiters();
+            let handlers = self.handlers.get(&event.event_name).cloned().unwrap_or_default();
+            for handler in handlers {
+                handler(event.payload.clone()).await?;
+            }
+            for next in self.chains.get(&event.event_name).into_iter().flatten() {
+                queue.push_back(TriggeredEvent {
+                    event_name: next.clone(),
+                    payload: event.payload.clone(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Every event published so far, in order.
+    pub fn published(&self) -> Vec<TriggeredEvent> {
+        self.published.lock().unwrap().clone()
+    }
+
+    /// Returns the first published event named `event_name`, waiting up to
+    /// `timeout` for it if it has not been published yet.
+    pub async fn expect_event(&self, event_name: &str, timeout: Duration) -> Result<TriggeredEvent> {
+        let wait = async {
+            loop {
+                let notified = self.published_notify.notified();
+                if let Some(event) = self.find_published(event_name) {
+                    return event;
+                }
+                notified.await;
+            }
+        };
+
+        tokio::time::timeout(timeout, wait).await.map_err(|_| {
+            let seen: Vec<String> = self.published().into_iter().map(|e| e.event_name).collect();
+            RuntimeError::ExecutionFailed(format!(
+                "Expected event {} within {:?} (published: {})",
+                event_name,
+                timeout,
+                if seen.is_empty() { "none".to_string() } else { seen.join(", ") }
+            ))
+        })
+    }
+
+    fn find_published(&self, event_name: &str) -> Option<TriggeredEvent> {
+        self.published
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|e| e.event_name == event_name)
+            .cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_complete_dispatches_triggers_and_chains() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut engine = TestEngine::new();
+        let counter = calls.clone();
+        engine.on_event("UserCreated", move |_payload| {
+            let counter = counter.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            }
+        });
+        engine.chain("UserCreated", &["WelcomeEmailQueued"]);
+
+        let mut state = engine.state("create_user");
+        state.trigger_event("AuditLog", json!({"action": "create"}));
+        let events = engine
+            .complete(&state, &["UserCreated"], &json!({"id": 1}))
+            .await
+            .unwrap();
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].payload, json!({"id": 1}));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        let published: Vec<String> = engine.published().into_iter().map(|e| e.event_name).collect();
+        assert_eq!(published, vec!["AuditLog", "UserCreated", "WelcomeEmailQueued"]);
+    }
+
+    #[tokio::test]
+    async fn test_expect_event_waits_for_publish() {
+        let engine = Arc::new(TestEngine::new());
+
+        let waiter = {
+            let engine = engine.clone();
+            tokio::spawn(async move { engine.expect_event("UserCreated", Duration::from_secs(1)).await })
+        };
+        tokio::task::yield_now().await;
+        engine.publish("UserCreated", json!({"id": 1})).await.unwrap();
+
+        let event = waiter.await.unwrap().unwrap();
+        assert_eq!(event.payload, json!({"id": 1}));
+        assert!(engine
+            .expect_event("UserDeleted", Duration::from_millis(10))
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,481 @@
+//! Conversion of stored traces into formats understood by other tracing tools.
+//!
+//! Each trace becomes a root span for its entry point with one child span per
+//! step. Events triggered by a step are attached to its span as span events
+//! (OTLP), logs (Jaeger) or annotations (Zipkin).
+
+use crate::traces::{TraceEntry, TraceStep};
+use chrono::{DateTime, Utc};
+use serde_json::{json, Map, Value};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// OTLP/JSON `ExportTraceServiceRequest`.
+    Otlp,
+    /// The JSON format the Jaeger UI loads from file.
+    JaegerJson,
+    /// Zipkin v2 JSON span list.
+    Zipkin,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "otlp" | "otlp-json" => Ok(ExportFormat::Otlp),
+            "jaeger" | "jaeger-json" => Ok(ExportFormat::JaegerJson),
+            "zipkin" | "zipkin-json" => Ok(ExportFormat::Zipkin),
+            other => Err(format!(
+                "Unsupported export format: {} (expected otlp, jaeger-json or zipkin)",
+                other
+            )),
+        }
+    }
+}
+
+/// Converts traces into a single JSON document in the given format.
+pub fn export_traces(entries: &[TraceEntry], format: ExportFormat, service_name: &str) -> Value {
+    let spans: Vec<(&TraceEntry, Vec<Span>)> = entries.iter().map(|e| (e, spans_for(e))).collect();
+
+    match format {
+        ExportFormat::Otlp => to_otlp(&spans, service_name),
+        ExportFormat::JaegerJson => to_jaeger(&spans, service_name),
+        ExportFormat::Zipkin => to_zipkin(&spans, service_name),
+    }
+}
+
+/// Format-neutral span derived from a trace or one of its steps.
+struct Span {
+    trace_id: String,
+    span_id: String,
+    parent_id: Option<String>,
+    name: String,
+    kind: SpanKind,
+    start_us: i64,
+    duration_us: i64,
+    error: Option<String>,
+    attributes: Vec<(String, String)>,
+    events: Vec<(i64, String)>,
+}
+
+#[derive(Clone, Copy)]
+enum SpanKind {
+    Server,
+    Consumer,
+    Internal,
+}
+
+fn spans_for(entry: &TraceEntry) -> Vec<Span> {
+    let trace_id = hex_trace_id(&entry.id);
+    let root_id = span_id(&entry.id, 0);
+    let start_us = parse_micros(&entry.started_at).unwrap_or(0);
+
+    let mut attributes: Vec<(String, String)> = vec![
+        ("rohas.entry_type".to_string(), entry.entry_type.clone()),
+        ("rohas.status".to_string(), entry.status.clone()),
+    ];
+    let mut metadata: Vec<_> = entry.metadata.iter().collect();
+    metadata.sort();
+    attributes.extend(metadata.into_iter().map(|(k, v)| (format!("rohas.{}", k), v.clone())));
+
+    let kind = match entry.entry_type.as_str() {
+        "api" | "websocket" => SpanKind::Server,
+        "event" => SpanKind::Consumer,
+        _ => SpanKind::Internal,
+    };
+
+    let mut spans = vec![Span {
+        trace_id: trace_id.clone(),
+        span_id: root_id.clone(),
+        parent_id: None,
+        name: entry.entry_point.clone(),
+        kind,
+        start_us,
+        duration_us: entry.duration_ms as i64 * 1000,
+        error: entry.error.clone().or_else(|| {
+            (entry.status == "failed").then(|| "failed".to_string())
+        }),
+        attributes,
+        events: Vec::new(),
+    }];
+
+    for (index, step) in entry.steps.iter().enumerate() {
+        spans.push(step_span(entry, step, index + 1, &trace_id, &root_id, start_us));
+    }
+
+    spans
+}
+
+fn step_span(
+    entry: &TraceEntry,
+    step: &TraceStep,
+    index: usize,
+    trace_id: &str,
+    root_id: &str,
+    trace_start_us: i64,
+) -> Span {
+    let duration_us = step.duration_ms as i64 * 1000;
+    // Steps are recorded when they finish.
+    let start_us = parse_micros(&step.timestamp)
+        .map(|end| (end - duration_us).max(trace_start_us))
+        .unwrap_or(trace_start_us);
+
+    Span {
+        trace_id: trace_id.to_string(),
+        span_id: span_id(&entry.id, index),
+        parent_id: Some(root_id.to_string()),
+        name: step.name.clone(),
+        kind: SpanKind::Internal,
+        start_us,
+        duration_us,
+        error: if step.success {
+            None
+        } else {
+            Some(step.error.clone().unwrap_or_else(|| "failed".to_string()))
+        },
+        attributes: vec![("rohas.handler".to_string(), step.handler_name.clone())],
+        events: step
+            .triggered_events
+            .iter()
+            .map(|e| {
+                (
+                    parse_micros(&e.timestamp).unwrap_or(start_us),
+                    format!("triggered {}", e.event_name),
+                )
+            })
+            .collect(),
+    }
+}
+
+fn to_otlp(traces: &[(&TraceEntry, Vec<Span>)], service_name: &str) -> Value {
+    let spans: Vec<Value> = traces
+        .iter()
+        .flat_map(|(_, spans)| spans.iter())
+        .map(|span| {
+            let mut value = json!({
+                "traceId": span.trace_id,
+                "spanId": span.span_id,
+                "name": span.name,
+                "kind": match span.kind {
+                    SpanKind::Internal => 1,
+                    SpanKind::Server => 2,
+                    SpanKind::Consumer => 5,
+                },
+                "startTimeUnixNano": (span.start_us * 1000).to_string(),
+                "endTimeUnixNano": ((span.start_us + span.duration_us) * 1000).to_string(),
+                "attributes": span.attributes.iter().map(|(k, v)| otlp_attribute(k, v)).collect::<Vec<_>>(),
+                "events": span.events.iter().map(|(ts, name)| json!({
+                    "timeUnixNano": (ts * 1000).to_string(),
+                    "name": name,
+                })).collect::<Vec<_>>(),
+                "status": match &span.error {
+                    Some(message) => json!({ "code": 2, "message": message }),
+                    None => json!({ "code": 1 }),
+                },
+            });
+            if let Some(parent) = &span.parent_id {
+                value["parentSpanId"] = json!(parent);
+            }
+            value
+        })
+        .collect();
+
+    json!({
+        "resourceSpans": [{
+            "resource": {
+                "attributes": [otlp_attribute("service.name", service_name)],
+            },
+            "scopeSpans": [{
+                "scope": { "name": "rohas", "version": env!("CARGO_PKG_VERSION") },
+                "spans": spans,
+            }],
+        }],
+    })
+}
+
+fn otlp_attribute(key: &str, value: &str) -> Value {
+    json!({ "key": key, "value": { "stringValue": value } })
+}
+
+fn to_jaeger(traces: &[(&TraceEntry, Vec<Span>)], service_name: &str) -> Value {
+    let data: Vec<Value> = traces
+        .iter()
+        .map(|(_, spans)| {
+            let trace_id = spans.first().map(|s| s.trace_id.clone()).unwrap_or_default();
+            let spans: Vec<Value> = spans
+                .iter()
+                .map(|span| {
+                    let mut tags: Vec<Value> = span
+                        .attributes
+                        .iter()
+                        .map(|(k, v)| json!({ "key": k, "type": "string", "value": v }))
+                        .collect();
+                    tags.push(json!({
+                        "key": "span.kind",
+                        "type": "string",
+                        "value": match span.kind {
+                            SpanKind::Server => "server",
+                            SpanKind::Consumer => "consumer",
+                            SpanKind::Internal => "internal",
+                        },
+                    }));
+                    if let Some(error) = &span.error {
+                        tags.push(json!({ "key": "error", "type": "bool", "value": true }));
+                        tags.push(json!({ "key": "error.message", "type": "string", "value": error }));
+                    }
+
+                    json!({
+                        "traceID": span.trace_id,
+                        "spanID": span.span_id,
+                        "operationName": span.name,
+                        "references": span.parent_id.iter().map(|parent| json!({
+                            "refType": "CHILD_OF",
+                            "traceID": span.trace_id,
+                            "spanID": parent,
+                        })).collect::<Vec<_>>(),
+                        "startTime": span.start_us,
+                        "duration": span.duration_us,
+                        "tags": tags,
+                        "logs": span.events.iter().map(|(ts, name)| json!({
+                            "timestamp": ts,
+                            "fields": [{ "key": "event", "type": "string", "value": name }],
+                        })).collect::<Vec<_>>(),
+                        "processID": "p1",
+                        "warnings": Value::Null,
+                    })
+                })
+                .collect();
+
+            json!({
+                "traceID": trace_id,
+                "spans": spans,
+                "processes": {
+                    "p1": { "serviceName": service_name, "tags": [] },
+                },
+                "warnings": Value::Null,
+            })
+        })
+        .collect();
+
+    json!({ "data": data })
+}
+
+fn to_zipkin(traces: &[(&TraceEntry, Vec<Span>)], service_name: &str) -> Value {
+    let spans: Vec<Value> = traces
+        .iter()
+        .flat_map(|(_, spans)| spans.iter())
+        .map(|span| {
+            let mut tags: Map<String, Value> = span
+                .attributes
+                .iter()
+                .map(|(k, v)| (k.clone(), json!(v)))
+                .collect();
+            if let Some(error) = &span.error {
+                tags.insert("error".to_string(), json!(error));
+            }
+
+            let mut value = json!({
+                "traceId": span.trace_id,
+                "id": span.span_id,
+                "name": span.name,
+                "timestamp": span.start_us,
+                "duration": span.duration_us,
+                "localEndpoint": { "serviceName": service_name },
+                "tags": tags,
+                "annotations": span.events.iter().map(|(ts, name)| json!({
+                    "timestamp": ts,
+                    "value": name,
+                })).collect::<Vec<_>>(),
+            });
+            match span.kind {
+                SpanKind::Server => value["kind"] = json!("SERVER"),
+                SpanKind::Consumer => value["kind"] = json!("CONSUMER"),
+                SpanKind::Internal => {}
+            }
+            if let Some(parent) = &span.parent_id {
+                value["parentId"] = json!(parent);
+            }
+            value
+        })
+        .collect();
+
+    Value::Array(spans)
+}
+
+fn parse_micros(timestamp: &str) -> Option<i64> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|t| t.with_timezone(&Utc).timestamp_micros())
+}
+
+/// Trace ids are UUIDs; their 32 hex digits are a valid 128-bit trace id.
+fn hex_trace_id(id: &str) -> String {
+    let hex: String = id.chars().filter(|c| c.is_ascii_hexdigit()).collect();
+    if hex.len() == 32 {
+        hex.to_lowercase()
+    } else {
+        format!("{:032x}", fnv1a(id.as_bytes()) as u128)
+    }
+}
+
+/// Stable 64-bit span id for the `index`-th span of a trace.
+fn span_id(trace_id: &str, index: usize) -> String {
+    let mut bytes = trace_id.as_bytes().to_vec();
+    bytes.extend_from_slice(&index.to_le_bytes());
+    format!("{:016x}", fnv1a(&bytes))
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traces::TriggeredEventInfo;
+    use std::collections::HashMap;
+
+    const TRACE_ID: &str = "0f8e1c2a-3b4d-4e5f-8a9b-0c1d2e3f4a5b";
+
+    /// An API call whose handler ran 40ms, finishing 50ms in, and published
+    /// `OrderPlaced` on the way.
+    fn entry(status: &str, step_error: Option<&str>) -> TraceEntry {
+        TraceEntry {
+            id: TRACE_ID.to_string(),
+            entry_point: "CreateOrder".to_string(),
+            entry_type: "api".to_string(),
+            status: status.to_string(),
+            duration_ms: 60,
+            started_at: "2024-05-01T12:00:00Z".to_string(),
+            completed_at: Some("2024-05-01T12:00:00.060Z".to_string()),
+            steps: vec![TraceStep {
+                name: "create_order".to_string(),
+                handler_name: "create_order".to_string(),
+                duration_ms: 40,
+                success: step_error.is_none(),
+                error: step_error.map(str::to_string),
+                timestamp: "2024-05-01T12:00:00.050Z".to_string(),
+                triggered_events: vec![TriggeredEventInfo {
+                    event_name: "OrderPlaced".to_string(),
+                    timestamp: "2024-05-01T12:00:00.045Z".to_string(),
+                    duration_ms: 1,
+                }],
+            }],
+            error: None,
+            metadata: HashMap::from([("path".to_string(), "/orders".to_string())]),
+        }
+    }
+
+    const START_US: i64 = 1_714_564_800_000_000;
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!("otlp".parse(), Ok(ExportFormat::Otlp));
+        assert_eq!("OTLP-JSON".parse(), Ok(ExportFormat::Otlp));
+        assert_eq!("jaeger".parse(), Ok(ExportFormat::JaegerJson));
+        assert_eq!("zipkin-json".parse(), Ok(ExportFormat::Zipkin));
+        assert!("datadog".parse::<ExportFormat>().unwrap_err().contains("datadog"));
+    }
+
+    #[test]
+    fn test_ids() {
+        assert_eq!(hex_trace_id(TRACE_ID), "0f8e1c2a3b4d4e5f8a9b0c1d2e3f4a5b");
+        let hashed = hex_trace_id("not-a-uuid");
+        assert_eq!(hashed.len(), 32);
+        assert!(hashed.chars().all(|c| c.is_ascii_hexdigit()));
+
+        assert_eq!(span_id(TRACE_ID, 1), span_id(TRACE_ID, 1));
+        assert_ne!(span_id(TRACE_ID, 0), span_id(TRACE_ID, 1));
+        assert_eq!(span_id(TRACE_ID, 0).len(), 16);
+    }
+
+    #[test]
+    fn test_spans_of_a_trace() {
+        let spans = spans_for(&entry("failed", Some("out of stock")));
+        assert_eq!(spans.len(), 2);
+
+        let root = &spans[0];
+        assert_eq!(root.name, "CreateOrder");
+        assert_eq!(root.parent_id, None);
+        assert_eq!((root.start_us, root.duration_us), (START_US, 60_000));
+        assert_eq!(root.error.as_deref(), Some("failed"));
+        assert!(root.attributes.contains(&("rohas.path".to_string(), "/orders".to_string())));
+
+        let step = &spans[1];
+        assert_eq!(step.parent_id.as_deref(), Some(root.span_id.as_str()));
+        assert_eq!(step.trace_id, root.trace_id);
+        // The step ended 50ms in after running 40ms.
+        assert_eq!((step.start_us, step.duration_us), (START_US + 10_000, 40_000));
+        assert_eq!(step.error.as_deref(), Some("out of stock"));
+        assert_eq!(step.events, vec![(START_US + 45_000, "triggered OrderPlaced".to_string())]);
+    }
+
+    #[test]
+    fn test_step_start_is_clamped_to_the_trace_start() {
+        let mut entry = entry("success", None);
+        entry.steps[0].duration_ms = 500;
+        entry.steps[0].success = false;
+        let spans = spans_for(&entry);
+        assert_eq!(spans[1].start_us, START_US);
+        assert_eq!(spans[0].error, None);
+        assert_eq!(spans[1].error.as_deref(), Some("failed"));
+    }
+
+    #[test]
+    fn test_otlp() {
+        let document = export_traces(&[entry("success", None)], ExportFormat::Otlp, "shop");
+        let resource = &document["resourceSpans"][0];
+        assert_eq!(resource["resource"]["attributes"][0]["value"]["stringValue"], "shop");
+
+        let spans = resource["scopeSpans"][0]["spans"].as_array().unwrap();
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[0]["traceId"], "0f8e1c2a3b4d4e5f8a9b0c1d2e3f4a5b");
+        assert_eq!(spans[0]["kind"], 2);
+        assert_eq!(spans[0]["status"], json!({ "code": 1 }));
+        assert_eq!(spans[0]["startTimeUnixNano"], (START_US * 1000).to_string());
+        assert_eq!(spans[0]["endTimeUnixNano"], ((START_US + 60_000) * 1000).to_string());
+        assert!(spans[0].get("parentSpanId").is_none());
+        assert_eq!(spans[1]["parentSpanId"], spans[0]["spanId"]);
+        assert_eq!(spans[1]["kind"], 1);
+        assert_eq!(spans[1]["events"][0]["name"], "triggered OrderPlaced");
+    }
+
+    #[test]
+    fn test_jaeger() {
+        let document = export_traces(&[entry("failed", Some("out of stock"))], ExportFormat::JaegerJson, "shop");
+        let trace = &document["data"][0];
+        assert_eq!(trace["processes"]["p1"]["serviceName"], "shop");
+
+        let spans = trace["spans"].as_array().unwrap();
+        assert_eq!(spans[0]["references"], json!([]));
+        assert_eq!(spans[1]["references"][0]["spanID"], spans[0]["spanID"]);
+        assert_eq!(spans[1]["startTime"], START_US + 10_000);
+        let tags = spans[1]["tags"].as_array().unwrap();
+        assert!(tags.contains(&json!({ "key": "error", "type": "bool", "value": true })));
+        assert!(tags.contains(&json!({ "key": "error.message", "type": "string", "value": "out of stock" })));
+        assert_eq!(spans[1]["logs"][0]["fields"][0]["value"], "triggered OrderPlaced");
+    }
+
+    #[test]
+    fn test_zipkin() {
+        let mut event = entry("success", None);
+        event.id = "event-trace".to_string();
+        event.entry_type = "event".to_string();
+        let document = export_traces(&[entry("failed", None), event], ExportFormat::Zipkin, "shop");
+        let spans = document.as_array().unwrap();
+        assert_eq!(spans.len(), 4);
+
+        assert_eq!(spans[0]["kind"], "SERVER");
+        assert_eq!(spans[0]["tags"]["error"], "failed");
+        assert_eq!(spans[0]["localEndpoint"]["serviceName"], "shop");
+        assert!(spans[1].get("kind").is_none());
+        assert_eq!(spans[1]["parentId"], spans[0]["id"]);
+        assert_eq!(spans[1]["annotations"][0]["value"], "triggered OrderPlaced");
+        assert_eq!(spans[2]["kind"], "CONSUMER");
+        assert_ne!(spans[2]["traceId"], spans[0]["traceId"]);
+    }
+}
@@ -1,5 +1,6 @@
 pub mod adapter;
 pub mod error;
+pub mod export;
 pub mod logs;
 pub mod metrics;
 pub mod storage;
@@ -7,8 +8,9 @@ pub mod traces;
 
 pub use adapter::TelemetryAdapter;
 pub use error::{Result, TelemetryError};
+pub use export::{export_traces, ExportFormat};
 pub use logs::{LogEntry, LogStore};
 pub use metrics::{Metric, MetricStore, MetricType};
-pub use storage::StorageAdapter;
+pub use storage::{MemoryStorage, StorageAdapter};
 pub use traces::{TraceEntry, TraceStore};
 
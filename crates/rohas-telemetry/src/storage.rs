@@ -31,3 +31,55 @@ pub trait StorageAdapter: Send + Sync {
     }
 }
 
+
+/// In-process storage, used when the engine is built without RocksDB (e.g. musl
+/// builds). Telemetry is lost on restart.
+#[derive(Default)]
+pub struct MemoryStorage {
+    entries: tokio::sync::RwLock<std::collections::BTreeMap<Vec<u8>, Vec<u8>>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl StorageAdapter for MemoryStorage {
+    async fn put(&self, key: &[u8], value: &[u8]) -> Result<()> {
+        self.entries.write().await.insert(key.to_vec(), value.to_vec());
+        Ok(())
+    }
+
+    async fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        Ok(self.entries.read().await.get(key).cloned())
+    }
+
+    async fn delete(&self, key: &[u8]) -> Result<()> {
+        self.entries.write().await.remove(key);
+        Ok(())
+    }
+
+    async fn get_by_prefix(&self, prefix: &[u8]) -> Result<Vec<Vec<u8>>> {
+        let entries = self.entries.read().await;
+        Ok(entries
+            .range(prefix.to_vec()..)
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .map(|(key, _)| key.clone())
+            .collect())
+    }
+
+    async fn iterate(&self, prefix: &[u8], mut callback: Box<dyn IterateCallback>) -> Result<()> {
+        let entries = self.entries.read().await;
+        for (key, value) in entries
+            .range(prefix.to_vec()..)
+            .take_while(|(key, _)| key.starts_with(prefix))
+        {
+            if !callback.call(key, value)? {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
@@ -6,6 +6,7 @@ CRATES=(
     "crates/rohas-parser"
     "crates/rohas-codegen"
     "crates/rohas-runtime"
+    "crates/rohas-sdk"
     "crates/rohas-telemetry"
     "crates/rohas-engine"
     "crates/rohas-cron"
@@ -0,0 +1,24 @@
+"""Handler-facing API for Rohas Python handlers.
+
+The generated `generated/state.py` re-exports this package, so handlers keep
+importing `State` from there.
+"""
+
+from .http import HttpClient, HttpClientError, HttpClientResponse
+from .kv import Kv
+from .logger import Logger
+from .state import Cookies, HttpResponse, State, TriggeredEvent
+
+__version__ = "0.1.0"
+
+__all__ = [
+    "Cookies",
+    "HttpClient",
+    "HttpClientError",
+    "HttpClientResponse",
+    "HttpResponse",
+    "Kv",
+    "Logger",
+    "State",
+    "TriggeredEvent",
+]
@@ -0,0 +1,125 @@
+import base64
+import json
+import sys
+import urllib.error
+import urllib.request
+from typing import Any, Dict, Optional
+
+DEFAULT_TIMEOUT = 30.0
+
+
+class HttpClientError(Exception):
+    """Raised when no response was received, or by `raise_for_status`."""
+
+
+class HttpClientResponse:
+    """Response of an outbound request. Header names are lowercase."""
+    
+    def __init__(self, status: int, headers: Dict[str, str], body: bytes) -> None:
+        self.status = status
+        self.headers = headers
+        self.body = body
+    
+    @property
+    def ok(self) -> bool:
+        """Whether the status is 2xx."""
+        return 200 <= self.status < 300
+    
+    def text(self) -> str:
+        return self.body.decode('utf-8', errors='replace')
+    
+    def json(self) -> Any:
+        return json.loads(self.body)
+    
+    def raise_for_status(self) -> 'HttpClientResponse':
+        """Raise `HttpClientError` unless the status is 2xx."""
+        if not self.ok:
+            raise HttpClientError(f"HTTP {self.status}: {self.text()[:200]}")
+        return self
+
+
+class HttpClient:
+    """Client for outbound HTTP requests.
+    
+    Every response is returned, whatever its status; check `ok` or call
+    `raise_for_status()` where that matters.
+    """
+    
+    def request(
+        self,
+        method: str,
+        url: str,
+        *,
+        headers: Optional[Dict[str, str]] = None,
+        json_body: Any = None,
+        body: Optional[bytes] = None,
+        timeout: float = DEFAULT_TIMEOUT,
+    ) -> HttpClientResponse:
+        """Send a request.
+        
+        Args:
+            method: HTTP method
+            url: Absolute URL
+            headers: Request headers
+            json_body: Value to send as a JSON body
+            body: Raw body, when not sending JSON
+            timeout: Seconds to wait for the response
+        """
+        headers = {name.lower(): str(value) for name, value in (headers or {}).items()}
+        if json_body is not None:
+            body = json.dumps(json_body).encode('utf-8')
+            headers.setdefault('content-type', 'application/json')
+        # Inside the engine, requests go through it so that recorded runs
+        # keep them and replays answer them.
+        native = sys.modules.get('_rohas')
+        if native is not None and hasattr(native, 'http_send'):
+            return self._send_native(native, method, url, headers, body, timeout)
+        request = urllib.request.Request(url, data=body, headers=headers, method=method.upper())
+        try:
+            with urllib.request.urlopen(request, timeout=timeout) as response:
+                return self._response(response.status, response.headers, response.read())
+        except urllib.error.HTTPError as e:
+            return self._response(e.code, e.headers, e.read())
+        except (urllib.error.URLError, OSError) as e:
+            raise HttpClientError(f"{method.upper()} {url}: {e}") from e
+    
+    def get(self, url: str, **kwargs: Any) -> HttpClientResponse:
+        return self.request('GET', url, **kwargs)
+    
+    def post(self, url: str, json_body: Any = None, **kwargs: Any) -> HttpClientResponse:
+        return self.request('POST', url, json_body=json_body, **kwargs)
+    
+    def put(self, url: str, json_body: Any = None, **kwargs: Any) -> HttpClientResponse:
+        return self.request('PUT', url, json_body=json_body, **kwargs)
+    
+    def patch(self, url: str, json_body: Any = None, **kwargs: Any) -> HttpClientResponse:
+        return self.request('PATCH', url, json_body=json_body, **kwargs)
+    
+    def delete(self, url: str, **kwargs: Any) -> HttpClientResponse:
+        return self.request('DELETE', url, **kwargs)
+    
+    @staticmethod
+    def _send_native(
+        native: Any,
+        method: str,
+        url: str,
+        headers: Dict[str, str],
+        body: Optional[bytes],
+        timeout: float,
+    ) -> HttpClientResponse:
+        request = {
+            'method': method.upper(),
+            'url': url,
+            'headers': headers,
+            'body': None if body is None else body.decode('utf-8'),
+            'timeout_ms': int(timeout * 1000),
+        }
+        try:
+            response = json.loads(native.http_send(json.dumps(request)))
+        except ConnectionError as e:
+            raise HttpClientError(str(e)) from e
+        return HttpClientResponse(response['status'], response['headers'], base64.b64decode(response['body']))
+    
+    @staticmethod
+    def _response(status: int, headers: Any, body: bytes) -> HttpClientResponse:
+        return HttpClientResponse(status, {name.lower(): value for name, value in headers.items()}, body)
@@ -0,0 +1,67 @@
+import json
+import sys
+import time
+from datetime import timedelta
+from typing import Any, Dict, Optional, Tuple, Union
+
+class _LocalStore:
+    """Stand-in for the engine's store when running outside the engine, e.g. in tests."""
+    
+    def __init__(self) -> None:
+        self._entries: Dict[str, Tuple[str, Optional[float]]] = {}
+    
+    def kv_get(self, key: str) -> Optional[str]:
+        entry = self._entries.get(key)
+        if entry is None:
+            return None
+        value, expires_at = entry
+        if expires_at is not None and expires_at <= time.monotonic():
+            del self._entries[key]
+            return None
+        return value
+    
+    def kv_set(self, key: str, value: str, ttl_ms: Optional[int] = None) -> None:
+        expires_at = None if ttl_ms is None else time.monotonic() + ttl_ms / 1000
+        self._entries[key] = (value, expires_at)
+    
+    def kv_delete(self, key: str) -> bool:
+        return self.kv_get(key) is not None and self._entries.pop(key, None) is not None
+
+
+_local = _LocalStore()
+
+
+def _store() -> Any:
+    # The engine registers `_rohas` before it imports handlers.
+    native = sys.modules.get('_rohas')
+    return native if native is not None else _local
+
+
+class Kv:
+    """Key-value store shared by the handlers of the engine process.
+    
+    Values must be JSON serializable. They are kept in memory, so they are
+    lost when the engine restarts.
+    """
+    
+    def get(self, key: str, default: Any = None) -> Any:
+        """Value stored under `key`, or `default` when it is missing or expired."""
+        value = _store().kv_get(key)
+        return default if value is None else json.loads(value)
+    
+    def set(self, key: str, value: Any, ttl: Union[timedelta, float, None] = None) -> None:
+        """Store `value` under `key`, replacing an earlier value.
+        
+        Args:
+            key: Key to store the value under
+            value: JSON serializable value
+            ttl: Time, or seconds, after which the key is removed. None keeps it
+        """
+        if isinstance(ttl, timedelta):
+            ttl = ttl.total_seconds()
+        ttl_ms = None if ttl is None else max(0, int(ttl * 1000))
+        _store().kv_set(key, json.dumps(value), ttl_ms)
+    
+    def delete(self, key: str) -> bool:
+        """Remove `key`, returning whether it held a value."""
+        return _store().kv_delete(key)
@@ -0,0 +1,70 @@
+from typing import Any, Dict, Union
+
+
+class Logger:
+    """Logger for handlers to emit structured logs."""
+    
+    def __init__(self, handler_name: str, log_fn: Any):
+        self._handler_name = handler_name
+        self._log_fn = log_fn
+    
+    def _log(self, level: str, message: Union[str, Dict[str, Any]], fields: Dict[str, Any]) -> None:
+        if isinstance(message, dict):
+            fields = {**message, **fields}
+            message = str(fields.pop("message", ""))
+        if self._log_fn:
+            self._log_fn(level, self._handler_name, message, fields)
+    
+    def info(self, message: Union[str, Dict[str, Any]], **kwargs: Any) -> None:
+        """Log an info message.
+        
+        Args:
+            message: Log message, or a dict of fields whose "message" key is the message
+            **kwargs: Additional fields to include in the log
+        """
+        self._log("info", message, kwargs)
+    
+    def error(self, message: Union[str, Dict[str, Any]], **kwargs: Any) -> None:
+        """Log an error message.
+        
+        Args:
+            message: Log message, or a dict of fields whose "message" key is the message
+            **kwargs: Additional fields to include in the log
+        """
+        self._log("error", message, kwargs)
+    
+    def warning(self, message: Union[str, Dict[str, Any]], **kwargs: Any) -> None:
+        """Log a warning message.
+        
+        Args:
+            message: Log message, or a dict of fields whose "message" key is the message
+            **kwargs: Additional fields to include in the log
+        """
+        self._log("warn", message, kwargs)
+    
+    def warn(self, message: Union[str, Dict[str, Any]], **kwargs: Any) -> None:
+        """Log a warning message (alias for warning).
+        
+        Args:
+            message: Log message, or a dict of fields whose "message" key is the message
+            **kwargs: Additional fields to include in the log
+        """
+        self.warning(message, **kwargs)
+    
+    def debug(self, message: Union[str, Dict[str, Any]], **kwargs: Any) -> None:
+        """Log a debug message.
+        
+        Args:
+            message: Log message, or a dict of fields whose "message" key is the message
+            **kwargs: Additional fields to include in the log
+        """
+        self._log("debug", message, kwargs)
+    
+    def trace(self, message: Union[str, Dict[str, Any]], **kwargs: Any) -> None:
+        """Log a trace message.
+        
+        Args:
+            message: Log message, or a dict of fields whose "message" key is the message
+            **kwargs: Additional fields to include in the log
+        """
+        self._log("trace", message, kwargs)
@@ -0,0 +1,202 @@
+from datetime import datetime, timedelta, timezone
+from typing import Any, Dict, List, Optional, Union
+from pydantic import BaseModel
+
+from .http import HttpClient
+from .kv import Kv
+from .logger import Logger
+
+
+class TriggeredEvent(BaseModel):
+    event_name: str
+    payload: Dict[str, Any]
+
+
+class HttpResponse:
+    """Status and headers of an API handler's HTTP response."""
+    
+    def __init__(self) -> None:
+        self._status: Optional[int] = None
+        self._headers: Dict[str, str] = {}
+    
+    def status(self, code: int) -> 'HttpResponse':
+        """Respond with `code` instead of 200."""
+        self._status = code
+        return self
+    
+    def header(self, name: str, value: str) -> 'HttpResponse':
+        """Set a response header, replacing an earlier value of it."""
+        self._headers[name.lower()] = str(value)
+        return self
+    
+    def redirect(self, location: str) -> 'HttpResponse':
+        """Redirect to `location` with a 302."""
+        return self.status(302).header('location', location)
+    
+    def permanent_redirect(self, location: str) -> 'HttpResponse':
+        """Redirect to `location` with a 308, which clients may cache."""
+        return self.status(308).header('location', location)
+
+
+class Cookies:
+    """Cookies of an API request, and those the handler sets on its response."""
+    
+    def __init__(self, request_cookies: Optional[Dict[str, str]] = None) -> None:
+        self._request: Dict[str, str] = dict(request_cookies or {})
+        self._cookies: List[Dict[str, Any]] = []
+    
+    def get(self, name: str, default: Optional[str] = None) -> Optional[str]:
+        """Value of a cookie sent with the request."""
+        return self._request.get(name, default)
+    
+    def set(
+        self,
+        name: str,
+        value: str,
+        *,
+        path: Optional[str] = None,
+        domain: Optional[str] = None,
+        max_age: Optional[int] = None,
+        secure: bool = False,
+        http_only: bool = False,
+        same_site: Optional[str] = None,
+    ) -> 'Cookies':
+        """Set a cookie on the response, replacing one set earlier with the same name.
+        
+        Args:
+            name: Cookie name
+            value: Cookie value, without spaces, quotes, commas, semicolons or backslashes
+            path: Path the cookie is sent for, '/' by default
+            domain: Domain the cookie is sent to
+            max_age: Seconds until the cookie expires; 0 removes it. None for a session cookie
+            secure: Only send the cookie over HTTPS
+            http_only: Hide the cookie from JavaScript
+            same_site: 'Strict', 'Lax' or 'None'; 'None' requires secure
+        """
+        self._cookies = [cookie for cookie in self._cookies if cookie['name'] != name]
+        self._cookies.append({
+            'name': name,
+            'value': str(value),
+            'options': {
+                'path': path,
+                'domain': domain,
+                'max_age': None if max_age is None else int(max_age),
+                'secure': secure,
+                'http_only': http_only,
+                'same_site': same_site,
+            },
+        })
+        return self
+    
+    def delete(self, name: str) -> 'Cookies':
+        """Remove a cookie from the client by setting it expired."""
+        return self.set(name, '', max_age=0)
+    
+    def to_list(self) -> List[Dict[str, Any]]:
+        """The cookies set so far. Used internally by the runtime."""
+        return [dict(cookie) for cookie in self._cookies]
+
+
+class State:
+    """Context object for handlers to trigger events and access runtime state."""
+    
+    def __init__(
+        self,
+        handler_name: Optional[str] = None,
+        log_fn: Optional[Any] = None,
+        cookies: Optional[Dict[str, str]] = None,
+    ):
+        self._triggers: List[TriggeredEvent] = []
+        self._auto_trigger_payloads: Dict[str, Dict[str, Any]] = {}
+        self._scheduled: List[Dict[str, Any]] = []
+        self._response = HttpResponse()
+        self._cookies = Cookies(cookies)
+        self.logger = Logger(handler_name or "unknown", log_fn)
+        self.kv = Kv()
+        self.http = HttpClient()
+    
+    def trigger_event(self, event_name: str, payload: Dict[str, Any]) -> None:
+        """Manually trigger an event with the given payload.
+        
+        Use this for events that are NOT defined in the schema's triggers list.
+        
+        Args:
+            event_name: Name of the event to trigger
+            payload: Event payload data (will be serialized to JSON)
+        """
+        self._triggers.append(TriggeredEvent(
+            event_name=event_name,
+            payload=payload
+        ))
+    
+    def set_payload(self, event_name: str, payload: Dict[str, Any]) -> None:
+        """Set the payload for an auto-triggered event.
+        
+        Use this for events that ARE defined in the schema's triggers list.
+        The event will be automatically triggered after the handler completes,
+        using the payload you set here.
+        
+        Args:
+            event_name: Name of the event (must match a trigger in schema)
+            payload: Event payload data (will be serialized to JSON)
+        """
+        self._auto_trigger_payloads[event_name] = payload
+    
+    def schedule(self, handler: str, when: Union[datetime, timedelta, str], payload: Any = None) -> None:
+        """Run a handler later.
+        
+        The call is kept in the engine's job store, so it still runs after a
+        restart.
+        
+        Args:
+            handler: Name of the handler to run
+            when: Time to run at, a delay, or a delay such as '30s', '5m' or '1d'
+            payload: Payload passed to the handler
+        """
+        if isinstance(when, datetime):
+            if when.tzinfo is None:
+                when = when.replace(tzinfo=timezone.utc)
+            when = when.isoformat()
+        elif isinstance(when, timedelta):
+            when = f"{int(when.total_seconds() * 1000)}ms"
+        self._scheduled.append({'handler': handler, 'when': when, 'payload': payload})
+    
+    def get_scheduled(self) -> List[Dict[str, Any]]:
+        """Get all scheduled handler calls. Used internally by the runtime."""
+        return self._scheduled.copy()
+    
+    def response(self) -> HttpResponse:
+        """The HTTP response of an API handler, to set its status and headers.
+        
+        Example:
+            state.response().status(201).header('Cache-Control', 'no-store')
+        """
+        return self._response
+    
+    def cookies(self) -> Cookies:
+        """Cookies of the API request, and those to set on its response.
+        
+        Example:
+            state.cookies().set('session', session_id, http_only=True, secure=True, same_site='Lax')
+        """
+        return self._cookies
+    
+    def get_response(self) -> Dict[str, Any]:
+        """Get the response status, headers and cookies. Used internally by the runtime."""
+        return {
+            'status': self._response._status,
+            'headers': self._response._headers.copy(),
+            'cookies': self._cookies.to_list(),
+        }
+    
+    def get_triggers(self) -> List[TriggeredEvent]:
+        """Get all manually triggered events. Used internally by the runtime."""
+        return self._triggers.copy()
+    
+    def get_auto_trigger_payload(self, event_name: str) -> Optional[Dict[str, Any]]:
+        """Get payload for an auto-triggered event. Used internally by the runtime."""
+        return self._auto_trigger_payloads.get(event_name)
+    
+    def get_all_auto_trigger_payloads(self) -> Dict[str, Dict[str, Any]]:
+        """Get all auto-trigger payloads. Used internally by the runtime."""
+        return self._auto_trigger_payloads.copy()
@@ -0,0 +1,101 @@
+import base64
+import json
+import sys
+import threading
+import unittest
+from http.server import BaseHTTPRequestHandler, HTTPServer
+
+from rohas_sdk import HttpClient, HttpClientError
+
+
+class _Handler(BaseHTTPRequestHandler):
+    def do_POST(self) -> None:
+        body = self.rfile.read(int(self.headers["Content-Length"]))
+        self._reply(201, {"received": json.loads(body), "type": self.headers["Content-Type"]})
+
+    def do_GET(self) -> None:
+        self._reply(404, {"error": "not found"})
+
+    def _reply(self, status: int, payload: dict) -> None:
+        body = json.dumps(payload).encode()
+        self.send_response(status)
+        self.send_header("Content-Type", "application/json")
+        self.send_header("Content-Length", str(len(body)))
+        self.end_headers()
+        self.wfile.write(body)
+
+    def log_message(self, *args: object) -> None:
+        pass
+
+
+class HttpClientTest(unittest.TestCase):
+    @classmethod
+    def setUpClass(cls) -> None:
+        cls.server = HTTPServer(("127.0.0.1", 0), _Handler)
+        cls.url = f"http://127.0.0.1:{cls.server.server_port}"
+        threading.Thread(target=cls.server.serve_forever, daemon=True).start()
+
+    @classmethod
+    def tearDownClass(cls) -> None:
+        cls.server.shutdown()
+        cls.server.server_close()
+
+    def test_post_sends_json(self) -> None:
+        response = HttpClient().post(f"{self.url}/orders", {"id": 1})
+
+        self.assertEqual(response.status, 201)
+        self.assertTrue(response.ok)
+        self.assertEqual(response.headers["content-type"], "application/json")
+        self.assertEqual(response.json(), {"received": {"id": 1}, "type": "application/json"})
+
+    def test_error_statuses_are_responses(self) -> None:
+        response = HttpClient().get(f"{self.url}/missing")
+
+        self.assertEqual(response.status, 404)
+        self.assertFalse(response.ok)
+        self.assertEqual(response.json(), {"error": "not found"})
+        with self.assertRaises(HttpClientError):
+            response.raise_for_status()
+
+    def test_connection_failures_raise(self) -> None:
+        with self.assertRaises(HttpClientError):
+            HttpClient().get("http://127.0.0.1:1", timeout=1)
+
+    def test_uses_the_engine_client(self) -> None:
+        requests = []
+
+        class Native:
+            def http_send(self, request):
+                requests.append(json.loads(request))
+                if requests[-1]["url"].endswith("/down"):
+                    raise ConnectionError("GET http://payments/down: connection refused")
+                return json.dumps({
+                    "status": 201,
+                    "headers": {"content-type": "application/json"},
+                    "body": base64.b64encode(b'{"charged": true}').decode(),
+                })
+
+        sys.modules["_rohas"] = Native()
+        try:
+            response = HttpClient().post("http://payments/charge", {"id": 1}, timeout=2)
+            with self.assertRaises(HttpClientError):
+                HttpClient().get("http://payments/down")
+        finally:
+            del sys.modules["_rohas"]
+
+        self.assertEqual(response.status, 201)
+        self.assertEqual(response.json(), {"charged": True})
+        self.assertEqual(
+            requests[0],
+            {
+                "method": "POST",
+                "url": "http://payments/charge",
+                "headers": {"content-type": "application/json"},
+                "body": '{"id": 1}',
+                "timeout_ms": 2000,
+            },
+        )
+
+
+if __name__ == "__main__":
+    unittest.main()
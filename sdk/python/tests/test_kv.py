@@ -0,0 +1,66 @@
+import json
+import sys
+import unittest
+from datetime import timedelta
+
+from rohas_sdk import Kv
+
+
+class KvTest(unittest.TestCase):
+    def test_set_get_delete(self) -> None:
+        kv = Kv()
+        self.assertIsNone(kv.get("order:1"))
+        self.assertEqual(kv.get("order:1", {}), {})
+
+        kv.set("order:1", {"status": "paid"})
+        self.assertEqual(kv.get("order:1"), {"status": "paid"})
+
+        self.assertTrue(kv.delete("order:1"))
+        self.assertFalse(kv.delete("order:1"))
+        self.assertIsNone(kv.get("order:1"))
+
+    def test_ttl(self) -> None:
+        kv = Kv()
+        kv.set("session", "abc", ttl=0)
+        kv.set("cart", [1, 2], ttl=timedelta(minutes=1))
+
+        self.assertIsNone(kv.get("session"))
+        self.assertFalse(kv.delete("session"))
+        self.assertEqual(kv.get("cart"), [1, 2])
+
+    def test_values_must_be_json(self) -> None:
+        with self.assertRaises(TypeError):
+            Kv().set("key", object())
+
+    def test_uses_the_engine_store(self) -> None:
+        calls = []
+
+        class Native:
+            def kv_get(self, key):
+                calls.append(("get", key))
+                return json.dumps({"from": "engine"})
+
+            def kv_set(self, key, value, ttl_ms=None):
+                calls.append(("set", key, value, ttl_ms))
+
+            def kv_delete(self, key):
+                calls.append(("delete", key))
+                return True
+
+        sys.modules["_rohas"] = Native()
+        try:
+            kv = Kv()
+            kv.set("order:1", {"id": 1}, ttl=1.5)
+            self.assertEqual(kv.get("order:1"), {"from": "engine"})
+            self.assertTrue(kv.delete("order:1"))
+        finally:
+            del sys.modules["_rohas"]
+
+        self.assertEqual(
+            calls,
+            [("set", "order:1", '{"id": 1}', 1500), ("get", "order:1"), ("delete", "order:1")],
+        )
+
+
+if __name__ == "__main__":
+    unittest.main()
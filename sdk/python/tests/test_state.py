@@ -0,0 +1,40 @@
+import unittest
+
+from rohas_sdk import HttpClient, Kv, State
+
+
+class StateTest(unittest.TestCase):
+    def test_triggers_and_payloads(self) -> None:
+        state = State("create_user")
+        state.trigger_event("AuditLog", {"action": "create"})
+        state.set_payload("UserCreated", {"id": 1})
+
+        self.assertEqual(state.get_triggers()[0].event_name, "AuditLog")
+        self.assertEqual(state.get_auto_trigger_payload("UserCreated"), {"id": 1})
+
+    def test_response_and_cookies(self) -> None:
+        state = State("login", cookies={"session": "abc"})
+        state.response().status(201).header("Cache-Control", "no-store")
+        state.cookies().set("session", "def", http_only=True)
+
+        self.assertEqual(state.cookies().get("session"), "abc")
+        response = state.get_response()
+        self.assertEqual(response["status"], 201)
+        self.assertEqual(response["headers"], {"cache-control": "no-store"})
+        self.assertTrue(response["cookies"][0]["options"]["http_only"])
+
+    def test_logger_forwards_fields(self) -> None:
+        logs = []
+        state = State("create_user", lambda *args: logs.append(args))
+        state.logger.info({"message": "created", "id": 1}, source="api")
+
+        self.assertEqual(logs, [("info", "create_user", "created", {"id": 1, "source": "api"})])
+
+    def test_clients(self) -> None:
+        state = State()
+        self.assertIsInstance(state.kv, Kv)
+        self.assertIsInstance(state.http, HttpClient)
+
+
+if __name__ == "__main__":
+    unittest.main()
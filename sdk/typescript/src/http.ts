@@ -0,0 +1,105 @@
+/** Timeout of requests that do not set their own. */
+export const DEFAULT_TIMEOUT_MS = 30_000;
+
+/** Function the Rohas engine defines for handlers running in it, which have no `fetch`. */
+interface NativeHttp {
+  __rohas_http(request: string): string;
+}
+
+export interface HttpRequestOptions {
+  method?: string;
+  headers?: Record<string, string>;
+  /** A string is sent as is, other values as JSON. */
+  body?: unknown;
+  /** Milliseconds to wait for the response. */
+  timeoutMs?: number;
+}
+
+/**
+ * Response of an outbound request. Header names are lowercase.
+ */
+export class HttpClientResponse {
+  readonly ok: boolean;
+
+  constructor(
+    readonly status: number,
+    readonly headers: Record<string, string>,
+    private readonly body: string,
+  ) {
+    this.ok = status >= 200 && status < 300;
+  }
+
+  text(): string {
+    return this.body;
+  }
+
+  json<T = any>(): T {
+    return JSON.parse(this.body) as T;
+  }
+}
+
+function engine(): NativeHttp | undefined {
+  const scope = globalThis as any;
+  return typeof scope.__rohas_http === 'function' ? scope : undefined;
+}
+
+/**
+ * Client for outbound HTTP requests, from `state.http`. Every response is
+ * returned, whatever its status; check `ok` where that matters. Requests
+ * that get no response reject.
+ */
+export class HttpClient {
+  async request(url: string, options: HttpRequestOptions = {}): Promise<HttpClientResponse> {
+    const method = (options.method || 'GET').toUpperCase();
+    const headers: Record<string, string> = {};
+    for (const [name, value] of Object.entries(options.headers || {})) {
+      headers[name.toLowerCase()] = String(value);
+    }
+    let body = options.body;
+    if (body !== undefined && body !== null && typeof body !== 'string') {
+      body = JSON.stringify(body);
+      headers['content-type'] = headers['content-type'] || 'application/json';
+    }
+    const timeoutMs = options.timeoutMs ?? DEFAULT_TIMEOUT_MS;
+
+    const native = engine();
+    if (native) {
+      const response = JSON.parse(
+        native.__rohas_http(JSON.stringify({ method, url, headers, body: body ?? null, timeout_ms: timeoutMs })),
+      );
+      return new HttpClientResponse(response.status, response.headers, response.body);
+    }
+
+    const response = await fetch(url, {
+      method,
+      headers,
+      body: (body as string | null | undefined) ?? undefined,
+      signal: AbortSignal.timeout(timeoutMs),
+    });
+    const responseHeaders: Record<string, string> = {};
+    response.headers.forEach((value, name) => {
+      responseHeaders[name] = value;
+    });
+    return new HttpClientResponse(response.status, responseHeaders, await response.text());
+  }
+
+  get(url: string, options?: HttpRequestOptions): Promise<HttpClientResponse> {
+    return this.request(url, { ...options, method: 'GET' });
+  }
+
+  post(url: string, body?: unknown, options?: HttpRequestOptions): Promise<HttpClientResponse> {
+    return this.request(url, { ...options, method: 'POST', body });
+  }
+
+  put(url: string, body?: unknown, options?: HttpRequestOptions): Promise<HttpClientResponse> {
+    return this.request(url, { ...options, method: 'PUT', body });
+  }
+
+  patch(url: string, body?: unknown, options?: HttpRequestOptions): Promise<HttpClientResponse> {
+    return this.request(url, { ...options, method: 'PATCH', body });
+  }
+
+  delete(url: string, options?: HttpRequestOptions): Promise<HttpClientResponse> {
+    return this.request(url, { ...options, method: 'DELETE' });
+  }
+}
@@ -0,0 +1,10 @@
+/**
+ * Handler-facing API for Rohas TypeScript handlers.
+ *
+ * The generated `generated/state.ts` re-exports this package, so handlers
+ * keep importing `State` from there.
+ */
+export * from './http';
+export * from './kv';
+export * from './logger';
+export * from './state';
@@ -0,0 +1,71 @@
+/** Functions the Rohas engine defines for handlers running in it. */
+interface NativeKv {
+  __rohas_kv_get(key: string): string | undefined;
+  __rohas_kv_set(key: string, value: string, ttlMs?: number): void;
+  __rohas_kv_delete(key: string): boolean;
+}
+
+export interface KvSetOptions {
+  /** Seconds until the key is removed. Unset keeps it. */
+  ttlSeconds?: number;
+}
+
+function engine(): NativeKv | undefined {
+  const scope = globalThis as any;
+  return typeof scope.__rohas_kv_get === 'function' ? scope : undefined;
+}
+
+/** Stand-in for the engine's store when running outside the engine, e.g. in tests. */
+const local = new Map<string, { value: string; expiresAt?: number }>();
+
+function readLocal(key: string): string | undefined {
+  const entry = local.get(key);
+  if (entry && entry.expiresAt !== undefined && entry.expiresAt <= Date.now()) {
+    local.delete(key);
+    return undefined;
+  }
+  return entry?.value;
+}
+
+/**
+ * Key-value store shared by the handlers of the engine process, from
+ * `state.kv`. Values must be JSON serializable. They are kept in memory,
+ * so they are lost when the engine restarts.
+ */
+export class Kv {
+  /**
+   * Value stored under `key`, or `undefined` when it is missing or expired.
+   */
+  async get<T = any>(key: string): Promise<T | undefined> {
+    const native = engine();
+    const value = native ? native.__rohas_kv_get(String(key)) : readLocal(String(key));
+    return value === undefined ? undefined : (JSON.parse(value) as T);
+  }
+
+  /**
+   * Store `value` under `key`, replacing an earlier value.
+   *
+   * @example await state.kv.set(`rates:${currency}`, rates, { ttlSeconds: 300 })
+   */
+  async set(key: string, value: unknown, options: KvSetOptions = {}): Promise<void> {
+    const json = JSON.stringify(value === undefined ? null : value);
+    const ttlMs = options.ttlSeconds == null ? undefined : Math.max(0, options.ttlSeconds * 1000);
+    const native = engine();
+    if (native) {
+      native.__rohas_kv_set(String(key), json, ttlMs);
+    } else {
+      local.set(String(key), { value: json, expiresAt: ttlMs === undefined ? undefined : Date.now() + ttlMs });
+    }
+  }
+
+  /**
+   * Remove `key`, returning whether it held a value.
+   */
+  async delete(key: string): Promise<boolean> {
+    const native = engine();
+    if (native) {
+      return native.__rohas_kv_delete(String(key));
+    }
+    return readLocal(String(key)) !== undefined && local.delete(String(key));
+  }
+}
@@ -0,0 +1,90 @@
+/**
+ * Logger for handlers to emit structured logs.
+ */
+export class Logger {
+  private handlerName: string;
+  private logFn?: (level: string, handler: string, message: string, fields: Record<string, any>) => void;
+
+  constructor(handlerName: string, logFn?: (level: string, handler: string, message: string, fields: Record<string, any>) => void) {
+    this.handlerName = handlerName;
+    this.logFn = logFn;
+  }
+
+  private log(
+    level: string,
+    message: string | Record<string, any>,
+    fields: Record<string, any> | undefined,
+    fallback: (...args: any[]) => void
+  ): void {
+    if (typeof message === "object" && message !== null) {
+      const { message: text = "", ...rest } = message;
+      fields = { ...rest, ...fields };
+      message = String(text);
+    }
+    if (this.logFn) {
+      this.logFn(level, this.handlerName, message, fields || {});
+    } else {
+      fallback(`[${this.handlerName}] ${message}`, fields || {});
+    }
+  }
+
+  /**
+   * Log an info message.
+   * 
+   * @param message - Log message, or a record of fields whose `message` key is the message
+   * @param fields - Additional fields to include in the log
+   */
+  info(message: string | Record<string, any>, fields?: Record<string, any>): void {
+    this.log("info", message, fields, console.log);
+  }
+
+  /**
+   * Log an error message.
+   * 
+   * @param message - Log message, or a record of fields whose `message` key is the message
+   * @param fields - Additional fields to include in the log
+   */
+  error(message: string | Record<string, any>, fields?: Record<string, any>): void {
+    this.log("error", message, fields, console.error);
+  }
+
+  /**
+   * Log a warning message.
+   * 
+   * @param message - Log message, or a record of fields whose `message` key is the message
+   * @param fields - Additional fields to include in the log
+   */
+  warning(message: string | Record<string, any>, fields?: Record<string, any>): void {
+    this.log("warn", message, fields, console.warn);
+  }
+
+  /**
+   * Log a warning message (alias for warning).
+   * 
+   * @param message - Log message, or a record of fields whose `message` key is the message
+   * @param fields - Additional fields to include in the log
+   */
+  warn(message: string | Record<string, any>, fields?: Record<string, any>): void {
+    this.warning(message, fields);
+  }
+
+  /**
+   * Log a debug message.
+   * 
+   * @param message - Log message, or a record of fields whose `message` key is the message
+   * @param fields - Additional fields to include in the log
+   */
+  debug(message: string | Record<string, any>, fields?: Record<string, any>): void {
+    this.log("debug", message, fields, console.debug);
+  }
+
+  /**
+   * Log a trace message.
+   * 
+   * @param message - Log message, or a record of fields whose `message` key is the message
+   * @param fields - Additional fields to include in the log
+   */
+  trace(message: string | Record<string, any>, fields?: Record<string, any>): void {
+    this.log("trace", message, fields, console.trace);
+  }
+}
@@ -0,0 +1,270 @@
+import { HttpClient } from './http';
+import { Kv } from './kv';
+import { Logger } from './logger';
+
+export interface TriggeredEvent {
+  eventName: string;
+  payload: any;
+}
+
+export interface ScheduledCall {
+  handler: string;
+  /** RFC 3339 time, or a delay from now such as `30s`. */
+  when: string;
+  payload: any;
+}
+
+export interface CookieOptions {
+  /** Defaults to `/`. */
+  path?: string;
+  domain?: string;
+  /** Seconds until the cookie expires; 0 removes it. Unset for a session cookie. */
+  maxAge?: number;
+  secure?: boolean;
+  httpOnly?: boolean;
+  /** `None` requires `secure`. */
+  sameSite?: 'Strict' | 'Lax' | 'None';
+}
+
+/** A cookie to send in a `Set-Cookie` header. */
+export interface SetCookie {
+  name: string;
+  value: string;
+  options: {
+    path: string | null;
+    domain: string | null;
+    max_age: number | null;
+    secure: boolean;
+    http_only: boolean;
+    same_site: 'Strict' | 'Lax' | 'None' | null;
+  };
+}
+
+/** Status, headers and cookies of an API handler's HTTP response. */
+export interface ResponseOptions {
+  status: number | null;
+  headers: Record<string, string>;
+  cookies: SetCookie[];
+}
+
+/**
+ * Cookies of an API request, and those the handler sets on its response,
+ * from `state.cookies()`.
+ */
+export class Cookies {
+  private request: Record<string, string>;
+  private cookies: SetCookie[] = [];
+
+  constructor(request?: Record<string, string>) {
+    this.request = request || {};
+  }
+
+  /**
+   * Value of a cookie sent with the request.
+   */
+  get(name: string): string | undefined {
+    return this.request[name];
+  }
+
+  /**
+   * Set a cookie on the response, replacing one set earlier with the same name.
+   */
+  set(name: string, value: string, options: CookieOptions = {}): this {
+    this.cookies = this.cookies.filter((cookie) => cookie.name !== name);
+    this.cookies.push({
+      name,
+      value: String(value),
+      options: {
+        path: options.path ?? null,
+        domain: options.domain ?? null,
+        max_age: options.maxAge == null ? null : Math.floor(options.maxAge),
+        secure: !!options.secure,
+        http_only: !!options.httpOnly,
+        same_site: options.sameSite ?? null,
+      },
+    });
+    return this;
+  }
+
+  /**
+   * Remove a cookie from the client by setting it expired.
+   */
+  delete(name: string): this {
+    return this.set(name, '', { maxAge: 0 });
+  }
+
+  /**
+   * The cookies set so far. Used internally by the runtime.
+   */
+  toList(): SetCookie[] {
+    return [...this.cookies];
+  }
+}
+
+/**
+ * The HTTP response of an API handler, from `state.response()`.
+ */
+export class HttpResponse {
+  private statusCode: number | null = null;
+  private headers: Record<string, string> = {};
+
+  /**
+   * Respond with `code` instead of 200.
+   */
+  status(code: number): this {
+    this.statusCode = code;
+    return this;
+  }
+
+  /**
+   * Set a response header, replacing an earlier value of it.
+   */
+  header(name: string, value: string): this {
+    this.headers[name.toLowerCase()] = String(value);
+    return this;
+  }
+
+  /**
+   * Redirect to `location` with a 302.
+   */
+  redirect(location: string): this {
+    return this.status(302).header('location', location);
+  }
+
+  /**
+   * Redirect to `location` with a 308, which clients may cache.
+   */
+  permanentRedirect(location: string): this {
+    return this.status(308).header('location', location);
+  }
+
+  /**
+   * The status and headers set so far. Used internally by the runtime.
+   */
+  toOptions(): Omit<ResponseOptions, 'cookies'> {
+    return { status: this.statusCode, headers: { ...this.headers } };
+  }
+}
+
+/**
+ * Context object for handlers to trigger events and access runtime state.
+ */
+export class State {
+  private triggers: TriggeredEvent[] = [];
+  private autoTriggerPayloads: Map<string, any> = new Map();
+  private scheduled: ScheduledCall[] = [];
+  private httpResponse: HttpResponse = new HttpResponse();
+  private cookieJar: Cookies;
+  public logger: Logger;
+  /** Key-value store shared by the engine's handlers. */
+  public kv: Kv = new Kv();
+  /** Client for outbound HTTP requests. */
+  public http: HttpClient = new HttpClient();
+
+  constructor(
+    handlerName?: string,
+    logFn?: (level: string, handler: string, message: string, fields: Record<string, any>) => void,
+    cookies?: Record<string, string>,
+  ) {
+    this.logger = new Logger(handlerName || "unknown", logFn);
+    this.cookieJar = new Cookies(cookies);
+  }
+
+  /**
+   * Manually trigger an event with the given payload.
+   * 
+   * Use this for events that are NOT defined in the schema's triggers list.
+   * 
+   * @param eventName - Name of the event to trigger
+   * @param payload - Event payload data (will be serialized to JSON)
+   */
+  triggerEvent(eventName: string, payload: any): void {
+    this.triggers.push({
+      eventName,
+      payload,
+    });
+  }
+
+  /**
+   * Set the payload for an auto-triggered event.
+   * 
+   * Use this for events that ARE defined in the schema's triggers list.
+   * The event will be automatically triggered after the handler completes,
+   * using the payload you set here.
+   * 
+   * @param eventName - Name of the event (must match a trigger in schema)
+   * @param payload - Event payload data (will be serialized to JSON)
+   */
+  setPayload(eventName: string, payload: any): void {
+    this.autoTriggerPayloads.set(eventName, payload);
+  }
+
+  /**
+   * Run a handler later. The call is kept in the engine's job store, so it
+   * still runs after a restart.
+   * 
+   * @param handler - Name of the handler to run
+   * @param when - Time to run at, or a delay such as '30s', '5m' or '1d'
+   * @param payload - Payload passed to the handler
+   */
+  schedule(handler: string, when: Date | string, payload?: any): void {
+    this.scheduled.push({
+      handler,
+      when: when instanceof Date ? when.toISOString() : when,
+      payload: payload ?? null,
+    });
+  }
+
+  /**
+   * Get all manually triggered events. Used internally by the runtime.
+   */
+  getTriggers(): TriggeredEvent[] {
+    return [...this.triggers];
+  }
+
+  /**
+   * Get all scheduled handler calls. Used internally by the runtime.
+   */
+  getScheduled(): ScheduledCall[] {
+    return [...this.scheduled];
+  }
+
+  /**
+   * The HTTP response of an API handler, to set its status and headers.
+   *
+   * @example state.response().status(201).header('Cache-Control', 'no-store')
+   */
+  response(): HttpResponse {
+    return this.httpResponse;
+  }
+
+  /**
+   * Cookies of the API request, and those to set on its response.
+   *
+   * @example state.cookies().set('session', id, { httpOnly: true, secure: true, sameSite: 'Lax' })
+   */
+  cookies(): Cookies {
+    return this.cookieJar;
+  }
+
+  /**
+   * Get the response status, headers and cookies. Used internally by the runtime.
+   */
+  getResponse(): ResponseOptions {
+    return { ...this.httpResponse.toOptions(), cookies: this.cookieJar.toList() };
+  }
+
+  /**
+   * Get payload for an auto-triggered event. Used internally by the runtime.
+   */
+  getAutoTriggerPayload(eventName: string): any | undefined {
+    return this.autoTriggerPayloads.get(eventName);
+  }
+
+  /**
+   * Get all auto-trigger payloads. Used internally by the runtime.
+   */
+  getAllAutoTriggerPayloads(): Map<string, any> {
+    return new Map(this.autoTriggerPayloads);
+  }
+}
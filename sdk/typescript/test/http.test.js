@@ -0,0 +1,68 @@
+const { test, before, after } = require('node:test');
+const assert = require('node:assert');
+const http = require('node:http');
+const { HttpClient } = require('../dist');
+
+let server;
+let url;
+
+before(async () => {
+  server = http.createServer((req, res) => {
+    let body = '';
+    req.on('data', (chunk) => (body += chunk));
+    req.on('end', () => {
+      const status = req.method === 'POST' ? 201 : 404;
+      res.writeHead(status, { 'Content-Type': 'application/json', 'X-Request-Id': '7' });
+      res.end(JSON.stringify({ method: req.method, type: req.headers['content-type'] || null, body }));
+    });
+  });
+  await new Promise((resolve) => server.listen(0, '127.0.0.1', resolve));
+  url = `http://127.0.0.1:${server.address().port}`;
+});
+
+after(() => server.close());
+
+test('post sends JSON', async () => {
+  const response = await new HttpClient().post(`${url}/orders`, { id: 1 });
+
+  assert.strictEqual(response.status, 201);
+  assert.strictEqual(response.ok, true);
+  assert.strictEqual(response.headers['x-request-id'], '7');
+  assert.deepStrictEqual(response.json(), { method: 'POST', type: 'application/json', body: '{"id":1}' });
+});
+
+test('error statuses are responses', async () => {
+  const response = await new HttpClient().get(`${url}/missing`);
+
+  assert.strictEqual(response.status, 404);
+  assert.strictEqual(response.ok, false);
+  assert.strictEqual(response.json().method, 'GET');
+});
+
+test('connection failures reject', async () => {
+  await assert.rejects(new HttpClient().get('http://127.0.0.1:1', { timeoutMs: 1000 }));
+});
+
+test('uses the engine client when running in the engine', async (t) => {
+  let sent;
+  globalThis.__rohas_http = (request) => {
+    sent = JSON.parse(request);
+    return JSON.stringify({ status: 202, headers: { 'content-type': 'text/plain' }, body: 'queued' });
+  };
+  t.after(() => delete globalThis.__rohas_http);
+
+  const response = await new HttpClient().put('https://example.com/jobs/1', 'run', {
+    headers: { 'X-Token': 'abc' },
+    timeoutMs: 500,
+  });
+
+  assert.deepStrictEqual(sent, {
+    method: 'PUT',
+    url: 'https://example.com/jobs/1',
+    headers: { 'x-token': 'abc' },
+    body: 'run',
+    timeout_ms: 500,
+  });
+  assert.strictEqual(response.status, 202);
+  assert.strictEqual(response.text(), 'queued');
+});
@@ -0,0 +1,50 @@
+const { test } = require('node:test');
+const assert = require('node:assert');
+const { Kv } = require('../dist');
+
+test('set, get and delete', async () => {
+  const kv = new Kv();
+  assert.strictEqual(await kv.get('order:1'), undefined);
+
+  await kv.set('order:1', { status: 'paid' });
+  assert.deepStrictEqual(await kv.get('order:1'), { status: 'paid' });
+
+  assert.strictEqual(await kv.delete('order:1'), true);
+  assert.strictEqual(await kv.delete('order:1'), false);
+  assert.strictEqual(await kv.get('order:1'), undefined);
+});
+
+test('ttl expires keys', async () => {
+  const kv = new Kv();
+  await kv.set('session', 'abc', { ttlSeconds: 0 });
+  await kv.set('cart', [1, 2], { ttlSeconds: 60 });
+
+  assert.strictEqual(await kv.get('session'), undefined);
+  assert.deepStrictEqual(await kv.get('cart'), [1, 2]);
+});
+
+test('uses the engine store when running in the engine', async (t) => {
+  const calls = [];
+  globalThis.__rohas_kv_get = (key) => {
+    calls.push(['get', key]);
+    return JSON.stringify({ from: 'engine' });
+  };
+  globalThis.__rohas_kv_set = (key, value, ttlMs) => calls.push(['set', key, value, ttlMs]);
+  globalThis.__rohas_kv_delete = (key) => calls.push(['delete', key]) > 0;
+  t.after(() => {
+    delete globalThis.__rohas_kv_get;
+    delete globalThis.__rohas_kv_set;
+    delete globalThis.__rohas_kv_delete;
+  });
+
+  const kv = new Kv();
+  await kv.set('order:1', { id: 1 }, { ttlSeconds: 1.5 });
+  assert.deepStrictEqual(await kv.get('order:1'), { from: 'engine' });
+  assert.strictEqual(await kv.delete('order:1'), true);
+
+  assert.deepStrictEqual(calls, [
+    ['set', 'order:1', '{"id":1}', 1500],
+    ['get', 'order:1'],
+    ['delete', 'order:1'],
+  ]);
+});
@@ -0,0 +1,38 @@
+const { test } = require('node:test');
+const assert = require('node:assert');
+const { HttpClient, Kv, State } = require('../dist');
+
+test('triggers and payloads', () => {
+  const state = new State('create_user');
+  state.triggerEvent('AuditLog', { action: 'create' });
+  state.setPayload('UserCreated', { id: 1 });
+
+  assert.strictEqual(state.getTriggers()[0].eventName, 'AuditLog');
+  assert.deepStrictEqual(state.getAutoTriggerPayload('UserCreated'), { id: 1 });
+});
+
+test('response and cookies', () => {
+  const state = new State('login', undefined, { session: 'abc' });
+  state.response().status(201).header('Cache-Control', 'no-store');
+  state.cookies().set('session', 'def', { httpOnly: true });
+
+  assert.strictEqual(state.cookies().get('session'), 'abc');
+  const response = state.getResponse();
+  assert.strictEqual(response.status, 201);
+  assert.deepStrictEqual(response.headers, { 'cache-control': 'no-store' });
+  assert.strictEqual(response.cookies[0].options.http_only, true);
+});
+
+test('logger forwards fields', () => {
+  const logs = [];
+  const state = new State('create_user', (...args) => logs.push(args));
+  state.logger.info({ message: 'created', id: 1 }, { source: 'api' });
+
+  assert.deepStrictEqual(logs, [['info', 'create_user', 'created', { id: 1, source: 'api' }]]);
+});
+
+test('clients', () => {
+  const state = new State();
+  assert.ok(state.kv instanceof Kv);
+  assert.ok(state.http instanceof HttpClient);
+});
@@ -22,6 +22,7 @@ import { fetchWorkbenchData } from "@/lib/workbench-data";
 import type { ProjectConfig } from "@/lib/project";
 import type { ActivityItem } from "@/types/workbench";
 import { SystemMetrics } from "@/components/workbench/system-metrics";
+import { SloStatus } from "@/components/workbench/slo-status";
 
 export const revalidate = 0;
 
@@ -99,6 +100,8 @@ export default async function OverviewPage() {
             </Button>
           </CardFooter>
         </Card>
+
+        <SloStatus />
       </section>
 
       <section className="grid gap-6 lg:grid-cols-2">
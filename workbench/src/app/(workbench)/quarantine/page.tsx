@@ -0,0 +1,38 @@
+"use client";
+
+import { useCallback, useEffect, useState } from "react";
+import { Loader2 } from "lucide-react";
+import { QuarantineInspector } from "@/components/workbench/quarantine-inspector";
+import { fetchQuarantine, type QuarantinedMessage } from "@/lib/workbench-data";
+
+export default function QuarantinePage() {
+  const [messages, setMessages] = useState<QuarantinedMessage[] | null>(null);
+
+  const refresh = useCallback(async () => {
+    setMessages(await fetchQuarantine());
+  }, []);
+
+  useEffect(() => {
+    refresh();
+  }, [refresh]);
+
+  return (
+    <div className="space-y-8">
+      <div>
+        <h1 className="text-3xl font-bold">Quarantine</h1>
+        <p className="text-muted-foreground mt-2">
+          Messages that failed to decode or kept failing their handlers. Inspect them, correct the payload and
+          re-inject them into their event.
+        </p>
+      </div>
+
+      {messages === null ? (
+        <div className="flex items-center justify-center min-h-[400px]">
+          <Loader2 className="h-8 w-8 animate-spin text-muted-foreground" />
+        </div>
+      ) : (
+        <QuarantineInspector messages={messages} onChange={refresh} />
+      )}
+    </div>
+  );
+}
@@ -91,7 +91,10 @@ export function ApiTester({ apis, websockets }: { apis: ApiEndpoint[]; websocket
         method: selectedApi.method as RequestMethod,
         url: `${baseUrl}${selectedApi.path}`,
         headers: { "Content-Type": "application/json" },
-        body: selectedApi.body || "",
+        body:
+          selectedApi.example?.request !== undefined
+            ? JSON.stringify(selectedApi.example.request, null, 2)
+            : selectedApi.body || "",
       });
       setResponse({
         status: null,
@@ -272,7 +275,16 @@ export function ApiTester({ apis, websockets }: { apis: ApiEndpoint[]; websocket
       return;
     }
 
-    const schema = wsMessageSchema as { properties?: Record<string, unknown>; required?: string[] };
+    const schema = wsMessageSchema as {
+      properties?: Record<string, unknown>;
+      required?: string[];
+      examples?: unknown[];
+    };
+    if (schema.examples?.length) {
+      setWsMessage(JSON.stringify(schema.examples[0], null, 2));
+      return;
+    }
+
     const example: Record<string, unknown> = {};
 
     if (schema.properties) {
@@ -42,7 +42,51 @@ export function CronsList({ crons }: { crons: CronJob[] }) {
                     <Clock className="h-3 w-3 mr-1" />
                     {cron.schedule}
                   </Badge>
+                  <Badge variant="secondary" className="text-xs">
+                    overlap: {cron.overlap}
+                  </Badge>
+                  <Badge variant="secondary" className="text-xs">
+                    misfire: {cron.misfire}
+                  </Badge>
                 </div>
+                <p className="flex items-center gap-1 text-sm text-muted-foreground">
+                  <Calendar className="h-3 w-3" />
+                  {cron.description}
+                </p>
+                {cron.next_runs.length > 0 && (
+                  <div className="flex flex-wrap gap-2">
+                    <span className="text-xs text-muted-foreground">Next runs:</span>
+                    {cron.next_runs.map((run) => (
+                      <Badge key={run} variant="outline" className="text-xs font-mono">
+                        {new Date(run).toISOString().replace("T", " ").slice(0, 19)} UTC
+                      </Badge>
+                    ))}
+                  </div>
+                )}
+                {cron.history.length > 0 && (
+                  <div className="space-y-1">
+                    <span className="text-xs text-muted-foreground">Recent runs:</span>
+                    {cron.history.slice(0, 5).map((run, idx) => (
+                      <div key={idx} className="flex items-center gap-2 text-xs">
+                        <Badge
+                          variant={run.status === "Failed" ? "destructive" : "outline"}
+                          className="text-xs"
+                        >
+                          {run.status}
+                        </Badge>
+                        <span className="font-mono">
+                          {new Date(run.scheduled_for ?? run.started_at)
+                            .toISOString()
+                            .replace("T", " ")
+                            .slice(0, 19)}{" "}
+                          UTC
+                        </span>
+                        {run.reason && <span className="text-muted-foreground">{run.reason}</span>}
+                        {run.error && <span className="text-destructive">{run.error}</span>}
+                      </div>
+                    ))}
+                  </div>
+                )}
                 {cron.triggers.length > 0 && (
                   <div className="flex flex-wrap gap-2">
                     <span className="text-xs text-muted-foreground">Triggers:</span>
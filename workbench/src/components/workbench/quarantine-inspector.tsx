@@ -0,0 +1,200 @@
+"use client";
+
+import { useEffect, useState } from "react";
+import { AlertTriangle, Loader2, RotateCcw, Save, Trash2, XCircle } from "lucide-react";
+import { Button } from "@/components/ui/button";
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "@/components/ui/card";
+import { Badge } from "@/components/ui/badge";
+import { JsonEditor } from "@/components/ui/json-editor";
+import { cn } from "@/lib/utils";
+import { api, ApiError } from "@/lib/api";
+import type { QuarantinedMessage } from "@/lib/workbench-data";
+
+// Undecodable bodies are kept as the raw string; show that text as-is.
+function payloadText(message: QuarantinedMessage): string {
+  return typeof message.payload === "string" ? message.payload : JSON.stringify(message.payload, null, 2);
+}
+
+function errorMessage(error: unknown): string {
+  return error instanceof ApiError || error instanceof Error ? error.message : "Unknown error";
+}
+
+export function QuarantineInspector({
+  messages,
+  onChange,
+}: {
+  messages: QuarantinedMessage[];
+  onChange: () => Promise<void>;
+}) {
+  const [selectedId, setSelectedId] = useState<string | null>(messages[0]?.id ?? null);
+  const selected = messages.find((message) => message.id === selectedId) ?? messages[0] ?? null;
+  const [payload, setPayload] = useState("");
+  const [busy, setBusy] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    setPayload(selected ? payloadText(selected) : "");
+    setError(null);
+  }, [selected]);
+
+  const run = async (action: (message: QuarantinedMessage) => Promise<void>) => {
+    if (!selected) return;
+    setBusy(true);
+    setError(null);
+    try {
+      await action(selected);
+      await onChange();
+    } catch (e) {
+      setError(errorMessage(e));
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  // Saves the edited payload if it changed; it must be valid JSON.
+  const save = async (message: QuarantinedMessage) => {
+    if (payload === payloadText(message)) return;
+    let parsed: unknown;
+    try {
+      parsed = JSON.parse(payload);
+    } catch {
+      throw new Error("Payload is not valid JSON");
+    }
+    await api.put(`/api/workbench/quarantine/${message.id}`, { payload: parsed });
+  };
+
+  const reinject = async (message: QuarantinedMessage) => {
+    await save(message);
+    await api.post(`/api/workbench/quarantine/${message.id}/reinject`);
+  };
+
+  const discard = async (message: QuarantinedMessage) => {
+    await api.delete(`/api/workbench/quarantine/${message.id}`);
+  };
+
+  if (messages.length === 0) {
+    return <p className="text-sm text-muted-foreground">No quarantined messages.</p>;
+  }
+
+  return (
+    <div className="grid grid-cols-1 lg:grid-cols-3 gap-4">
+      <Card className="lg:col-span-1">
+        <CardHeader>
+          <CardTitle>Messages</CardTitle>
+          <CardDescription>{messages.length} quarantined, newest first</CardDescription>
+        </CardHeader>
+        <CardContent>
+          <div className="space-y-2 max-h-[600px] overflow-y-auto">
+            {messages.map((message) => (
+              <button
+                key={message.id}
+                onClick={() => setSelectedId(message.id)}
+                className={cn(
+                  "w-full text-left p-3 rounded-md border transition-colors",
+                  selected?.id === message.id
+                    ? "bg-accent border-accent-foreground/20"
+                    : "border-border hover:bg-accent/50"
+                )}
+              >
+                <div className="flex items-center gap-2 mb-1">
+                  <AlertTriangle className="h-4 w-4 text-orange-500" />
+                  <span className="text-sm font-medium">{message.event}</span>
+                  <Badge variant="secondary" className="text-xs ml-auto">
+                    {message.kind}
+                  </Badge>
+                </div>
+                <p className="text-xs text-muted-foreground truncate">{message.error}</p>
+                <p className="text-xs text-muted-foreground mt-1">
+                  {new Date(message.quarantined_at).toLocaleString()}
+                </p>
+              </button>
+            ))}
+          </div>
+        </CardContent>
+      </Card>
+
+      {selected && (
+        <Card className="lg:col-span-2">
+          <CardHeader>
+            <CardTitle>{selected.event}</CardTitle>
+            <CardDescription>
+              {selected.kind === "decode"
+                ? "The message body could not be decoded. Rewrite it as the event's JSON payload to re-inject it."
+                : `Failed after ${selected.attempts} attempt(s).`}
+            </CardDescription>
+          </CardHeader>
+          <CardContent className="space-y-4">
+            <div className="space-y-1">
+              <label className="text-sm font-medium">Error</label>
+              <pre className="w-full max-h-[160px] overflow-auto rounded-md border border-input bg-muted/50 p-3 text-xs font-mono whitespace-pre-wrap">
+                {selected.error}
+              </pre>
+            </div>
+
+            {selected.handlers && selected.handlers.length > 0 && (
+              <div className="space-y-1">
+                <label className="text-sm font-medium">Failed Handlers</label>
+                <div className="flex flex-wrap gap-2">
+                  {selected.handlers.map((handler) => (
+                    <Badge key={handler} variant="outline" className="text-xs">
+                      {handler}
+                    </Badge>
+                  ))}
+                </div>
+              </div>
+            )}
+
+            {selected.metadata && Object.keys(selected.metadata).length > 0 && (
+              <div className="space-y-1">
+                <label className="text-sm font-medium">Metadata</label>
+                <div className="flex flex-wrap gap-2">
+                  {Object.entries(selected.metadata).map(([key, value]) => (
+                    <Badge key={key} variant="secondary" className="text-xs font-mono">
+                      {key}={value}
+                    </Badge>
+                  ))}
+                </div>
+              </div>
+            )}
+
+            <div className="space-y-2">
+              <div className="flex items-center gap-2">
+                <label className="text-sm font-medium">Payload</label>
+                {selected.edited && (
+                  <Badge variant="outline" className="text-xs">
+                    edited
+                  </Badge>
+                )}
+              </div>
+              <div className="rounded-md border border-input overflow-hidden">
+                <JsonEditor value={payload} onChange={setPayload} height="300px" className="w-full" />
+              </div>
+            </div>
+
+            <div className="flex items-center gap-2">
+              <Button onClick={() => run(reinject)} disabled={busy} className="flex-1">
+                {busy ? <Loader2 className="h-4 w-4 mr-2 animate-spin" /> : <RotateCcw className="h-4 w-4 mr-2" />}
+                Re-inject
+              </Button>
+              <Button variant="outline" onClick={() => run(save)} disabled={busy}>
+                <Save className="h-4 w-4 mr-2" />
+                Save
+              </Button>
+              <Button variant="outline" onClick={() => run(discard)} disabled={busy}>
+                <Trash2 className="h-4 w-4 mr-2" />
+                Discard
+              </Button>
+            </div>
+
+            {error && (
+              <div className="flex items-center gap-2 text-red-500">
+                <XCircle className="h-4 w-4" />
+                <span className="text-sm">{error}</span>
+              </div>
+            )}
+          </CardContent>
+        </Card>
+      )}
+    </div>
+  );
+}
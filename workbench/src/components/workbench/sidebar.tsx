@@ -2,7 +2,7 @@
 
 import Link from "next/link";
 import { usePathname } from "next/navigation";
-import { Boxes, Code2, LayoutDashboard, Settings, Workflow, ChevronLeft, ChevronRight, Network } from "lucide-react";
+import { Boxes, Code2, LayoutDashboard, Settings, Workflow, ChevronLeft, ChevronRight, Network, ShieldAlert } from "lucide-react";
 import { ActivitySquare } from "lucide-react";
 import { useEffect } from "react";
 
@@ -24,6 +24,7 @@ const navItems: Array<{
   { label: "Schema Graph", href: "/schema-graph", icon: Workflow, view: "schema-graph" },
   { label: "Tracing", href: "/tracing", icon: ActivitySquare, view: "tracing" },
   { label: "API & WebSocket", href: "/endpoints", icon: Network, view: "endpoints" },
+  { label: "Quarantine", href: "/quarantine", icon: ShieldAlert, view: "quarantine" },
   { label: "CLI Tasks", href: "/cli", icon: Code2, view: "cli" },
   { label: "Settings", href: "/settings", icon: Settings, view: "settings" },
 ];
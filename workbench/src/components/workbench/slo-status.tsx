@@ -0,0 +1,110 @@
+"use client";
+
+import { useEffect, useState } from "react";
+import { Gauge } from "lucide-react";
+import { Badge } from "@/components/ui/badge";
+import {
+  Card,
+  CardContent,
+  CardDescription,
+  CardHeader,
+  CardTitle,
+} from "@/components/ui/card";
+import { fetchSloStatus, type SloStatus as ApiSloStatus } from "@/lib/workbench-data";
+
+const WINDOW_SECONDS = 3600;
+const REFRESH_MS = 10_000;
+
+export function SloStatus() {
+  const [apis, setApis] = useState<ApiSloStatus[]>([]);
+
+  useEffect(() => {
+    const update = async () => setApis(await fetchSloStatus(WINDOW_SECONDS));
+
+    update();
+    const interval = setInterval(update, REFRESH_MS);
+
+    return () => clearInterval(interval);
+  }, []);
+
+  return (
+    <Card className="lg:col-span-3">
+      <CardHeader>
+        <CardTitle>Service level objectives</CardTitle>
+        <CardDescription>
+          Burn rates over the last hour for APIs with an `slo` block. Above 1× spends the error budget faster than the
+          target allows.
+        </CardDescription>
+      </CardHeader>
+      <CardContent>
+        {apis.length === 0 ? (
+          <p className="text-sm text-muted-foreground">No API declares an SLO.</p>
+        ) : (
+          <div className="space-y-3">
+            {apis.map((status) => (
+              <SloRow key={status.api} status={status} />
+            ))}
+          </div>
+        )}
+      </CardContent>
+    </Card>
+  );
+}
+
+function SloRow({ status }: { status: ApiSloStatus }) {
+  const objectives = [
+    {
+      label: "Availability",
+      target: status.target.availability,
+      targetLabel: `${status.target.availability}%`,
+      observed: status.availability === undefined ? undefined : `${status.availability.toFixed(2)}%`,
+      burnRate: status.availability_burn_rate,
+    },
+    {
+      label: "p99",
+      target: status.target.latency_p99_ms,
+      targetLabel: `${status.target.latency_p99_ms}ms`,
+      observed: status.latency_p99_ms === undefined ? undefined : `${status.latency_p99_ms}ms`,
+      burnRate: status.latency_p99_burn_rate,
+    },
+    {
+      label: "p95",
+      target: status.target.latency_p95_ms,
+      targetLabel: `${status.target.latency_p95_ms}ms`,
+      observed: status.latency_p95_ms === undefined ? undefined : `${status.latency_p95_ms}ms`,
+      burnRate: status.latency_p95_burn_rate,
+    },
+  ].filter((objective) => objective.target !== undefined);
+
+  return (
+    <div className="flex flex-col gap-2 rounded-lg border bg-card p-4 md:flex-row md:items-center md:justify-between">
+      <div className="flex items-center gap-2">
+        <Gauge className="h-4 w-4 text-muted-foreground" />
+        <span className="font-semibold">{status.api}</span>
+        <Badge variant={status.state === "breached" ? "destructive" : status.state === "ok" ? "success" : "outline"}>
+          {status.state === "no_data" ? "no data" : status.state}
+        </Badge>
+        <span className="text-xs text-muted-foreground">
+          {status.failed_requests} of {status.total_requests} requests failed
+        </span>
+      </div>
+      <div className="flex flex-wrap gap-4 text-sm">
+        {objectives.map((objective) => (
+          <div key={objective.label} className="space-y-0.5">
+            <div className="text-xs text-muted-foreground">
+              {objective.label} · target {objective.targetLabel}
+            </div>
+            <div>
+              <span className="font-medium">{objective.observed ?? "—"}</span>
+              {objective.burnRate !== undefined && (
+                <span className={objective.burnRate > 1 ? "ml-2 text-destructive" : "ml-2 text-muted-foreground"}>
+                  {objective.burnRate.toFixed(2)}× burn
+                </span>
+              )}
+            </div>
+          </div>
+        ))}
+      </div>
+    </div>
+  );
+}
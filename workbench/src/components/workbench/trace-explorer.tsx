@@ -211,6 +211,22 @@ export function TraceExplorer({ traces }: { traces: TraceRecord[] }) {
     [selectedTraceId, filteredTraces],
   );
 
+  // Traces linked to the selected one through the events they published,
+  // in start order, with their depth in the chain.
+  const traceChain = useMemo(() => {
+    if (!selectedTrace) return [];
+    const rootId = selectedTrace.metadata?.root_trace_id ?? selectedTrace.id;
+    const chain = traces
+      .filter((trace) => trace.id === rootId || trace.metadata?.root_trace_id === rootId)
+      .sort((a, b) => a.startedAt.localeCompare(b.startedAt));
+    const byId = new Map(chain.map((trace) => [trace.id, trace]));
+    const depth = (trace: TraceRecord): number => {
+      const parent = trace.metadata?.parent_trace_id ? byId.get(trace.metadata.parent_trace_id) : undefined;
+      return parent && parent !== trace ? depth(parent) + 1 : 0;
+    };
+    return chain.map((trace) => ({ trace, depth: depth(trace) }));
+  }, [selectedTrace, traces]);
+
   const toggleExpand = (traceId: string) => {
     setExpandedTraces((prev) => {
       const next = new Set(prev);
@@ -466,6 +482,34 @@ export function TraceExplorer({ traces }: { traces: TraceRecord[] }) {
               </div>
             )}
 
+            {traceChain.length > 1 && (
+              <div>
+                <p className="text-xs font-medium text-muted-foreground mb-2">Trace Chain</p>
+                <div className="space-y-1">
+                  {traceChain.map(({ trace, depth }) => (
+                    <button
+                      key={trace.id}
+                      type="button"
+                      onClick={() => setSelectedTraceId(trace.id)}
+                      className={cn(
+                        "flex items-center justify-between gap-2 rounded-md border px-2 py-1 text-left text-xs",
+                        trace.id === selectedTrace.id ? "border-primary bg-primary/5" : "hover:bg-muted",
+                      )}
+                      style={{ marginLeft: `${depth * 12}px`, width: `calc(100% - ${depth * 12}px)` }}
+                    >
+                      <span className="truncate">
+                        <span className="capitalize text-muted-foreground">{trace.entryType}</span>{" "}
+                        {trace.entryPoint}
+                      </span>
+                      <span className={cn("rounded px-1.5", statusVariants[trace.status].className)}>
+                        {formatNumber(trace.durationMs)}ms
+                      </span>
+                    </button>
+                  ))}
+                </div>
+              </div>
+            )}
+
             <div>
               <p className="text-xs font-medium text-muted-foreground mb-2">Execution Route</p>
               <div className="space-y-2">
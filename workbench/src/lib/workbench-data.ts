@@ -299,6 +299,12 @@ export type ApiEndpoint = {
   response: string;
   triggers: string[];
   middlewares: string[];
+  example: ApiExample | null;
+};
+
+export type ApiExample = {
+  request?: unknown;
+  response?: unknown;
 };
 
 export type WebSocketEndpoint = {
@@ -313,10 +319,26 @@ export type WebSocketEndpoint = {
   middlewares: string[];
 };
 
+export type CronRun = {
+  job_id: string;
+  started_at: string;
+  completed_at: string | null;
+  status: "Scheduled" | "Running" | "Completed" | "Failed" | "Disabled" | "Skipped";
+  error: string | null;
+  duration_ms: number | null;
+  scheduled_for?: string;
+  reason?: string;
+};
+
 export type CronJob = {
   name: string;
   schedule: string;
   triggers: string[];
+  description: string;
+  next_runs: string[];
+  overlap: "skip" | "queue" | "concurrent";
+  misfire: "run_once" | "ignore";
+  history: CronRun[];
 };
 
 export type EventEndpoint = {
@@ -380,3 +402,60 @@ export async function fetchSystemMetrics(): Promise<SystemMetrics | null> {
     return null;
   }
 }
+
+export type QuarantinedMessage = {
+  id: string;
+  event: string;
+  kind: "decode" | "handler";
+  payload: unknown;
+  error: string;
+  attempts: number;
+  handlers?: string[];
+  metadata?: Record<string, string>;
+  quarantined_at: string;
+  edited: boolean;
+};
+
+export async function fetchQuarantine(): Promise<QuarantinedMessage[]> {
+  try {
+    const data = await apiRequest<{ messages: QuarantinedMessage[] }>("/api/workbench/quarantine");
+    return data.messages;
+  } catch (error) {
+    console.error("Failed to fetch quarantined messages:", error);
+    return [];
+  }
+}
+
+export type SloTarget = {
+  latency_p99_ms?: number;
+  latency_p95_ms?: number;
+  availability?: number;
+};
+
+export type SloStatus = {
+  api: string;
+  target: SloTarget;
+  window_seconds: number;
+  total_requests: number;
+  failed_requests: number;
+  availability?: number;
+  availability_burn_rate?: number;
+  latency_p99_ms?: number;
+  latency_p99_burn_rate?: number;
+  latency_p95_ms?: number;
+  latency_p95_burn_rate?: number;
+  state: "ok" | "breached" | "no_data";
+};
+
+export async function fetchSloStatus(window = 3600): Promise<SloStatus[]> {
+  try {
+    const data = await apiRequest<{ window_seconds: number; apis: SloStatus[] }>(
+      "/api/workbench/metrics/slo",
+      { params: { window } }
+    );
+    return data.apis;
+  } catch (error) {
+    console.error("Failed to fetch SLO status:", error);
+    return [];
+  }
+}
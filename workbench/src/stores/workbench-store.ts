@@ -9,7 +9,8 @@ export type View =
   | "tracing"
   | "cli"
   | "settings"
-  | "endpoints";
+  | "endpoints"
+  | "quarantine";
 
 type WorkbenchState = {
   view: View;